@@ -0,0 +1,162 @@
+//! Report of what an upgrade would change: the deployed program data against the program data staged in the buffer,
+//! optionally section by section of the ELF. Approvers attach the report to the signing request of the upgrade.
+//! ProgramData is zero padded to its allocated size, so the deployed program is cut to the size of its ELF.
+
+use std::fmt;
+use goblin::elf::Elf;
+use serde::Serialize;
+use solana_program::hash::hash;
+use solana_program::pubkey::Pubkey;
+use upgrade_program_client::DeployedProgram;
+
+#[derive(Serialize, PartialEq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionChange {
+    Added,
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SectionDiff {
+    pub name: String,
+    pub change: SectionChange,
+    pub deployed_size: Option<usize>,
+    pub buffer_size: Option<usize>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Report {
+    pub program: String,
+    pub program_data: String,
+    pub buffer: String,
+    /// Slot of the last deployment
+    pub deployed_slot: u64,
+    pub upgrade_authority: Option<String>,
+    pub deployed_size: usize,
+    /// Size the ProgramData account is allocated for, larger buffers need ExtendProgram first
+    pub capacity: usize,
+    pub buffer_size: usize,
+    /// Hex SHA-256 of the deployed program data
+    pub deployed_hash: String,
+    /// Hex SHA-256 of the buffer program data, the code hash signed for the upgrade
+    pub buffer_hash: String,
+    pub identical: bool,
+    pub fits: bool,
+    /// Section-level diff, `None` if it was not requested
+    pub sections: Option<Vec<SectionDiff>>,
+}
+
+/// Deployed program without the padding of ProgramData: up to the end of the last ELF header table or section,
+/// or up to the last non-zero byte if the data is not an ELF.
+fn deployed_code(data: &[u8]) -> &[u8] {
+    let size = match Elf::parse_header(data) {
+        Ok(header) => {
+            let tables = [
+                header.e_shoff + header.e_shnum as u64 * header.e_shentsize as u64,
+                header.e_phoff + header.e_phnum as u64 * header.e_phentsize as u64,
+            ];
+            let sections = Elf::parse(data).map(|elf| elf.section_headers.iter()
+                .filter_map(|section| section.file_range().map(|range| range.end as u64))
+                .max()
+                .unwrap_or_default());
+            tables.into_iter().chain(sections.ok()).max().unwrap_or_default() as usize
+        }
+        Err(_) => data.iter().rposition(|byte| *byte != 0).map_or(0, |last| last + 1),
+    };
+    &data[..size.min(data.len())]
+}
+
+pub fn report(program: &Pubkey, deployed: &DeployedProgram, buffer: &Pubkey, buffer_data: &[u8], sections: bool) -> Result<Report, String> {
+    let deployed_code = deployed_code(&deployed.data);
+
+    Ok(Report {
+        program: program.to_string(),
+        program_data: deployed.program_data_address.to_string(),
+        buffer: buffer.to_string(),
+        deployed_slot: deployed.slot,
+        upgrade_authority: deployed.upgrade_authority.map(|authority| authority.to_string()),
+        deployed_size: deployed_code.len(),
+        capacity: deployed.data.len(),
+        buffer_size: buffer_data.len(),
+        deployed_hash: hex::encode(hash(deployed_code)),
+        buffer_hash: hex::encode(hash(buffer_data)),
+        identical: deployed_code == buffer_data,
+        fits: buffer_data.len() <= deployed.data.len(),
+        sections: match sections {
+            true => Some(section_diff(deployed_code, buffer_data)?),
+            false => None,
+        },
+    })
+}
+
+/// Named sections of the ELF with their contents, empty for sections without data in the file such as `.bss`.
+fn elf_sections<'a>(label: &str, data: &'a [u8]) -> Result<Vec<(String, &'a [u8])>, String> {
+    let elf = Elf::parse(data).map_err(|e| format!("{} is not a valid ELF: {}", label, e))?;
+    Ok(elf.section_headers.iter()
+        .filter_map(|header| {
+            let name = elf.shdr_strtab.get_at(header.sh_name).filter(|name| !name.is_empty())?;
+            let contents = header.file_range().and_then(|range| data.get(range)).unwrap_or_default();
+            Some((name.to_string(), contents))
+        })
+        .collect())
+}
+
+fn section_diff(deployed: &[u8], buffer: &[u8]) -> Result<Vec<SectionDiff>, String> {
+    let deployed = elf_sections("deployed program", deployed)?;
+    let buffer = elf_sections("buffer", buffer)?;
+
+    let mut diff: Vec<_> = deployed.iter()
+        .map(|(name, old)| {
+            let new = buffer.iter().find(|(buffer_name, _)| buffer_name == name).map(|(_, new)| *new);
+            SectionDiff {
+                name: name.clone(),
+                change: match new {
+                    None => SectionChange::Removed,
+                    Some(new) if new == *old => SectionChange::Unchanged,
+                    Some(_) => SectionChange::Changed,
+                },
+                deployed_size: Some(old.len()),
+                buffer_size: new.map(<[u8]>::len),
+            }
+        })
+        .collect();
+    diff.extend(buffer.iter()
+        .filter(|(name, _)| !deployed.iter().any(|(deployed_name, _)| deployed_name == name))
+        .map(|(name, new)| SectionDiff { name: name.clone(), change: SectionChange::Added, deployed_size: None, buffer_size: Some(new.len()) }));
+    Ok(diff)
+}
+
+fn size(size: Option<usize>) -> String {
+    size.map_or_else(|| "-".to_string(), |size| size.to_string())
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Program:           {}", self.program)?;
+        writeln!(f, "ProgramData:       {} (deployed at slot {})", self.program_data, self.deployed_slot)?;
+        writeln!(f, "Upgrade authority: {}", self.upgrade_authority.as_deref().unwrap_or("none, the program is immutable"))?;
+        writeln!(f, "Buffer:            {}", self.buffer)?;
+        writeln!(f, "Deployed code:     {} bytes, sha256 {}", self.deployed_size, self.deployed_hash)?;
+        writeln!(f, "Buffer code:       {} bytes, sha256 {}", self.buffer_size, self.buffer_hash)?;
+        writeln!(f, "Capacity:          {} bytes{}", self.capacity, match self.fits {
+            true => String::new(),
+            false => format!(", extend the program by {} bytes before upgrading", self.buffer_size - self.capacity),
+        })?;
+        writeln!(f, "Result:            {}", match self.identical {
+            true => "buffer is identical to the deployed program",
+            false => "buffer differs from the deployed program",
+        })?;
+
+        if let Some(sections) = &self.sections {
+            writeln!(f)?;
+            writeln!(f, "{:<24} {:<10} {:>12} {:>12}", "Section", "Change", "Deployed", "Buffer")?;
+            for section in sections {
+                let change = format!("{:?}", section.change).to_lowercase();
+                writeln!(f, "{:<24} {:<10} {:>12} {:>12}", section.name, change, size(section.deployed_size), size(section.buffer_size))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+//! Ledger hardware wallet over Linux hidraw. The Solana app signs transaction messages as the fee payer or nonce
+//! authority, the Ethereum app signs operation hashes with EIP-191 `personal_sign`, which the program accepts
+//! as `EcdsaSignature::personal_sign`.
+//!
+//! Signers are selected with `usb://ledger?key=<account>[/<change>]` for `44'/501'/account'/change'` of the Solana app
+//! and `usb://ledger?key=<index>` for `44'/60'/0'/0/index` of the Ethereum app, without `key` the first account is used.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::sync::Mutex;
+
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer, SignerError};
+use upgrade::ecdsa::EcdsaSignature;
+
+use crate::Result;
+
+pub const LEDGER_URI: &str = "usb://ledger";
+
+const LEDGER_VENDOR_ID: &str = "00002C97";
+/// Report descriptor prefix of the generic HID interface: Usage Page 0xFFA0
+const LEDGER_USAGE_PAGE: [u8; 3] = [0x06, 0xa0, 0xff];
+const HID_PACKET_SIZE: usize = 64;
+const HID_CHANNEL: [u8; 2] = [0x01, 0x01];
+const HID_TAG_APDU: u8 = 0x05;
+const APDU_CLA: u8 = 0xe0;
+const APDU_MAX_CHUNK_SIZE: usize = 255;
+const HARDENED: u32 = 0x8000_0000;
+
+const SW_OK: u16 = 0x9000;
+const SW_USER_REJECTED: u16 = 0x6985;
+const SW_APP_NOT_OPEN: [u16; 3] = [0x6d00, 0x6e00, 0x6e01];
+
+mod solana_app {
+    pub const INS_GET_PUBKEY: u8 = 0x05;
+    pub const INS_SIGN_MESSAGE: u8 = 0x06;
+    pub const P1_NON_CONFIRM: u8 = 0x00;
+    pub const P1_CONFIRM: u8 = 0x01;
+    pub const P2_EXTEND: u8 = 0x01;
+    pub const P2_MORE: u8 = 0x02;
+}
+
+mod ethereum_app {
+    pub const INS_GET_PUBLIC_KEY: u8 = 0x02;
+    pub const INS_SIGN_PERSONAL_MESSAGE: u8 = 0x08;
+    pub const P1_FIRST_CHUNK: u8 = 0x00;
+    pub const P1_SUBSEQUENT_CHUNK: u8 = 0x80;
+}
+
+pub fn is_ledger_uri(source: &str) -> bool {
+    source == LEDGER_URI || source.starts_with(&format!("{}?", LEDGER_URI))
+}
+
+fn uri_key(source: &str) -> Result<Vec<u32>> {
+    match source.strip_prefix(LEDGER_URI).and_then(|query| query.strip_prefix("?key=")) {
+        Some(key) => key.split('/')
+            .map(|index| index.trim_end_matches('\'').parse::<u32>().map_err(|e| format!("invalid key {}: {}", key, e).into()))
+            .collect(),
+        None if source == LEDGER_URI => Ok(vec![]),
+        None => Err(format!("unsupported ledger uri {}, expected {}?key=<indexes>", source, LEDGER_URI).into()),
+    }
+}
+
+/// `44'/501'/account'/change'` derivation path of the Solana app.
+pub fn solana_path(source: &str) -> Result<Vec<u32>> {
+    let key = uri_key(source)?;
+    if key.len() > 2 {
+        return Err("solana key should be <account>[/<change>]".into());
+    }
+
+    Ok([44, 501].iter().chain(key.iter()).map(|index| index | HARDENED).collect())
+}
+
+/// `44'/60'/0'/0/index` derivation path of the Ethereum app.
+pub fn ethereum_path(source: &str) -> Result<Vec<u32>> {
+    let index = match uri_key(source)?.as_slice() {
+        [] => 0,
+        [index] => *index,
+        _ => return Err("ethereum key should be <index>".into()),
+    };
+
+    Ok(vec![44 | HARDENED, 60 | HARDENED, HARDENED, 0, index])
+}
+
+fn serialize_path(path: &[u32]) -> Vec<u8> {
+    let mut data = vec![path.len() as u8];
+    for index in path {
+        data.extend_from_slice(&index.to_be_bytes());
+    }
+    data
+}
+
+/// Ledger device opened through its generic HID interface.
+pub struct Ledger {
+    device: File,
+}
+
+impl Ledger {
+    /// Opens the first connected Ledger.
+    pub fn open() -> Result<Self> {
+        for entry in fs::read_dir("/sys/class/hidraw")? {
+            let entry = entry?;
+            let uevent = fs::read_to_string(entry.path().join("device/uevent")).unwrap_or_default();
+            let descriptor = fs::read(entry.path().join("device/report_descriptor")).unwrap_or_default();
+            if !uevent.to_uppercase().contains(LEDGER_VENDOR_ID) || !descriptor.starts_with(&LEDGER_USAGE_PAGE) {
+                continue;
+            }
+
+            let path = format!("/dev/{}", entry.file_name().to_string_lossy());
+            let device = OpenOptions::new().read(true).write(true).open(&path)
+                .map_err(|e| format!("failed to open ledger at {}: {}", path, e))?;
+            return Ok(Ledger { device });
+        }
+
+        Err("no ledger found, connect and unlock it".into())
+    }
+
+    /// Sends APDU and returns response data without the status word.
+    fn exchange(&mut self, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<Vec<u8>> {
+        let mut apdu = vec![APDU_CLA, ins, p1, p2, data.len() as u8];
+        apdu.extend_from_slice(data);
+        self.write(&apdu)?;
+
+        let response = self.read()?;
+        if response.len() < 2 {
+            return Err("ledger response is too short".into());
+        }
+        let (data, status) = response.split_at(response.len() - 2);
+        match u16::from_be_bytes([status[0], status[1]]) {
+            SW_OK => Ok(data.to_vec()),
+            SW_USER_REJECTED => Err("rejected on ledger".into()),
+            status if SW_APP_NOT_OPEN.contains(&status) => Err("required ledger app is not open".into()),
+            status => Err(format!("ledger error status {:#06x}", status).into()),
+        }
+    }
+
+    fn write(&mut self, apdu: &[u8]) -> Result<()> {
+        let mut payload = (apdu.len() as u16).to_be_bytes().to_vec();
+        payload.extend_from_slice(apdu);
+
+        for (sequence, chunk) in payload.chunks(HID_PACKET_SIZE - 5).enumerate() {
+            // Leading zero is the report id hidraw expects for devices without numbered reports
+            let mut packet = vec![0x00];
+            packet.extend_from_slice(&HID_CHANNEL);
+            packet.push(HID_TAG_APDU);
+            packet.extend_from_slice(&(sequence as u16).to_be_bytes());
+            packet.extend_from_slice(chunk);
+            packet.resize(HID_PACKET_SIZE + 1, 0);
+            self.device.write_all(&packet)?;
+        }
+
+        Ok(())
+    }
+
+    fn read(&mut self) -> Result<Vec<u8>> {
+        let mut response = vec![];
+        let mut length = None;
+        let mut sequence = 0u16;
+
+        while length.is_none_or(|length| response.len() < length) {
+            let mut packet = [0u8; HID_PACKET_SIZE];
+            let read = self.device.read(&mut packet)?;
+            if read < 5 || packet[..2] != HID_CHANNEL || packet[2] != HID_TAG_APDU || u16::from_be_bytes([packet[3], packet[4]]) != sequence {
+                return Err("unexpected ledger response packet".into());
+            }
+
+            let mut chunk = &packet[5..read];
+            if sequence == 0 {
+                if chunk.len() < 2 {
+                    return Err("unexpected ledger response packet".into());
+                }
+                length = Some(u16::from_be_bytes([chunk[0], chunk[1]]) as usize);
+                chunk = &chunk[2..];
+            }
+            response.extend_from_slice(chunk);
+            sequence += 1;
+        }
+
+        response.truncate(length.unwrap_or_default());
+        Ok(response)
+    }
+
+    pub fn solana_pubkey(&mut self, path: &[u32]) -> Result<Pubkey> {
+        let data = self.exchange(solana_app::INS_GET_PUBKEY, solana_app::P1_NON_CONFIRM, 0, &serialize_path(path))?;
+        Ok(Pubkey::try_from(data.as_slice()).map_err(|_| "invalid pubkey returned by ledger")?)
+    }
+
+    /// Signs transaction message with the Solana app, the user confirms it on the device.
+    pub fn solana_sign(&mut self, path: &[u32], message: &[u8]) -> Result<Signature> {
+        // Single signer path followed by the message
+        let mut payload = vec![1];
+        payload.extend_from_slice(&serialize_path(path));
+        payload.extend_from_slice(message);
+
+        let chunks = payload.chunks(APDU_MAX_CHUNK_SIZE).collect::<Vec<_>>();
+        let mut response = vec![];
+        for (index, chunk) in chunks.iter().enumerate() {
+            let mut p2 = if index > 0 { solana_app::P2_EXTEND } else { 0 };
+            if index + 1 < chunks.len() {
+                p2 |= solana_app::P2_MORE;
+            }
+            response = self.exchange(solana_app::INS_SIGN_MESSAGE, solana_app::P1_CONFIRM, p2, chunk)?;
+        }
+
+        Ok(Signature::try_from(response.as_slice()).map_err(|_| "invalid signature returned by ledger")?)
+    }
+
+    /// 20 byte Ethereum address of the Ethereum app account.
+    pub fn ethereum_address(&mut self, path: &[u32]) -> Result<[u8; 20]> {
+        let data = self.exchange(ethereum_app::INS_GET_PUBLIC_KEY, 0, 0, &serialize_path(path))?;
+        // Public key length and key, then address length and ASCII hex of the address
+        let address_offset = 1 + *data.first().ok_or("empty ledger response")? as usize;
+        let address = data.get(address_offset + 1..address_offset + 41).ok_or("invalid address returned by ledger")?;
+        Ok(hex::decode(address)?.try_into().map_err(|_| "invalid address returned by ledger")?)
+    }
+
+    /// Signs EIP-191 `personal_sign` message of `hash` with the Ethereum app, the user confirms it on the device.
+    pub fn ethereum_sign_hash(&mut self, path: &[u32], hash: &[u8]) -> Result<EcdsaSignature> {
+        let mut payload = serialize_path(path);
+        payload.extend_from_slice(&(hash.len() as u32).to_be_bytes());
+        payload.extend_from_slice(hash);
+
+        let mut response = vec![];
+        for (index, chunk) in payload.chunks(APDU_MAX_CHUNK_SIZE).enumerate() {
+            let p1 = if index == 0 { ethereum_app::P1_FIRST_CHUNK } else { ethereum_app::P1_SUBSEQUENT_CHUNK };
+            response = self.exchange(ethereum_app::INS_SIGN_PERSONAL_MESSAGE, p1, 0, chunk)?;
+        }
+
+        // v || r || s, v is 27 or 28
+        if response.len() != 65 {
+            return Err("invalid signature returned by ledger".into());
+        }
+        Ok(EcdsaSignature {
+            signature: response[1..].try_into().unwrap(),
+            recovery_id: response[0] as u64,
+            personal_sign: true,
+        })
+    }
+}
+
+/// Solana signer backed by the Solana app of a Ledger.
+pub struct LedgerSigner {
+    ledger: Mutex<Ledger>,
+    path: Vec<u32>,
+    pubkey: Pubkey,
+}
+
+impl LedgerSigner {
+    pub fn new(source: &str) -> Result<Self> {
+        let path = solana_path(source)?;
+        let mut ledger = Ledger::open()?;
+        let pubkey = ledger.solana_pubkey(&path)?;
+        Ok(LedgerSigner { ledger: Mutex::new(ledger), path, pubkey })
+    }
+}
+
+impl Signer for LedgerSigner {
+    fn try_pubkey(&self) -> std::result::Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> std::result::Result<Signature, SignerError> {
+        eprintln!("Confirm the transaction of {} on ledger", self.pubkey);
+        self.ledger.lock().unwrap()
+            .solana_sign(&self.path, message)
+            .map_err(|e| SignerError::Custom(e.to_string()))
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,840 @@
+//! Command-line tool covering the UpgradeAdmin lifecycle: initialization, inspection, signing and sending of operations.
+//! Operations are signed locally with secp256k1 keyfiles and/or accept signatures collected from other signers,
+//! nonces are fetched from the chain. Transactions can use a durable nonce to be signed offline and broadcast later.
+//! Both the fee payer and the secp256k1 signers can be a Ledger, see `ledger`. secp256k1 key files are either hex
+//! of the secret key or password-encrypted keystores, see `upgrade_program_client::keystore`.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::str::FromStr;
+
+use clap::{Args, Parser, Subcommand};
+use solana_program::hash::Hash;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::hashing::{self, HashAlgorithm, HashingMode, NonceWidth};
+use upgrade::instructions::InitializeAdminArgs;
+use upgrade::state::{AdminKey, Curve, UpgradeAdmin};
+use upgrade_program_client as client;
+use upgrade_program_client::coordinator;
+use upgrade_program_client::keystore;
+use upgrade_program_client::signing::{self, NonceKind, Operation};
+use upgrade_program_client::snapshot;
+use upgrade_program_client::transaction::{self, ComputeUnitLimit, DurableNonce, PriorityFee, TransactionConfig};
+
+mod diff;
+mod ledger;
+
+/// Environment variable with the keystore password for non-interactive use
+const KEYSTORE_PASSWORD_ENV: &str = "UPGRADE_ADMIN_KEYSTORE_PASSWORD";
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(name = "upgrade-admin", about = "Manage program upgrades through the upgrade program")]
+struct Cli {
+    /// JSON RPC URL of the cluster
+    #[arg(long, global = true, default_value = "http://localhost:8899")]
+    url: String,
+    /// Fee payer: keypair file, usb://ledger, or pubkey of a payer that signs offline
+    #[arg(long, global = true, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+    /// Address of the upgrade program
+    #[arg(long, global = true)]
+    program_id: Option<Pubkey>,
+    #[command(flatten)]
+    transaction: TransactionArgs,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create UpgradeAdmin of the contract
+    Init {
+        #[arg(long)]
+        contract: Pubkey,
+        /// Signer key: hex of 64 byte public key, 33 byte compressed public key or 20 byte Ethereum address
+        #[arg(long = "public-key", required = true, value_parser = parse_admin_key)]
+        public_keys: Vec<AdminKey>,
+        #[arg(long, default_value_t = 1)]
+        threshold: u8,
+        /// Hex of 32 byte domain separator mixed into every signed hash
+        #[arg(long, value_parser = parse_hash, default_value = "0000000000000000000000000000000000000000000000000000000000000000")]
+        domain: [u8; 32],
+        /// Sign u128 nonces instead of u64 ones, for admins expected to outlive u64 nonces
+        #[arg(long)]
+        wide_nonces: bool,
+    },
+    /// Print UpgradeAdmin of the contract
+    Show {
+        #[arg(long)]
+        contract: Pubkey,
+    },
+    /// Print signature of the upgrade for the current nonce, to be collected by the operator
+    SignUpgrade {
+        #[command(flatten)]
+        upgrade: UpgradeTarget,
+        /// secp256k1 signer: keystore, file with hex of 32 byte key, or usb://ledger for the Ethereum app
+        #[arg(long)]
+        signer_key: PathBuf,
+        /// Unix timestamp after which signatures are no longer valid
+        #[arg(long)]
+        expires_at: i64,
+    },
+    /// Print JSON envelope with the exact digest of the operation for HSM or TSS signers
+    Digest {
+        /// Contract that seeds UpgradeAdmin address
+        #[arg(long)]
+        contract: Pubkey,
+        /// Nonce to sign for, the current nonce of UpgradeAdmin by default
+        #[arg(long)]
+        nonce: Option<u128>,
+        /// Unix timestamp after which signatures are no longer valid
+        #[arg(long)]
+        expires_at: i64,
+        #[command(subcommand)]
+        operation: DigestOperation,
+    },
+    /// Upgrade the contract from the buffer
+    Upgrade {
+        #[command(flatten)]
+        upgrade: UpgradeTarget,
+        #[command(flatten)]
+        signing: Signing,
+    },
+    /// Stage replacement of the signer set
+    ChangeKey {
+        #[arg(long)]
+        contract: Pubkey,
+        #[arg(long = "public-key", required = true, value_parser = parse_admin_key)]
+        public_keys: Vec<AdminKey>,
+        #[arg(long)]
+        threshold: u8,
+        #[command(flatten)]
+        signing: Signing,
+    },
+    /// Transfer upgrade authority of the contract from UpgradeAdmin
+    ChangeAuthority {
+        #[arg(long)]
+        contract: Pubkey,
+        #[arg(long)]
+        new_authority: Pubkey,
+        #[command(flatten)]
+        signing: Signing,
+    },
+    /// Close UpgradeAdmin and withdraw its lamports
+    Close {
+        #[arg(long)]
+        contract: Pubkey,
+        #[arg(long)]
+        destination: Pubkey,
+        #[command(flatten)]
+        signing: Signing,
+    },
+    /// Sign the message printed by --sign-only with --keypair, works without network access
+    SignMessage {
+        /// Base64 of the transaction message
+        message: String,
+    },
+    /// Print Ethereum address of the Ledger Ethereum app account, to be used as --public-key
+    LedgerAddress {
+        #[arg(long, default_value = ledger::LEDGER_URI)]
+        signer_key: String,
+    },
+    /// Manage password-encrypted secp256k1 keystores
+    #[command(subcommand)]
+    Keystore(KeystoreCommand),
+    /// Print events of UpgradeAdmin as JSON lines as transactions emitting them are confirmed
+    Watch {
+        #[arg(long)]
+        contract: Pubkey,
+        /// WebSocket RPC URL, derived from --url by default
+        #[arg(long)]
+        ws_url: Option<String>,
+    },
+    /// Collect signatures of a threshold signer set through a signing request file
+    #[command(subcommand)]
+    Request(RequestCommand),
+    /// Write every UpgradeAdmin and PDA of the program with its data to a JSON snapshot
+    ExportState {
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Check that accounts of the program still match the snapshot written by export-state
+    VerifyState {
+        snapshot: PathBuf,
+    },
+    /// Print what upgrading the contract from the buffer would change, for approvers to review before signing
+    DiffUpgrade {
+        #[arg(long)]
+        contract: Pubkey,
+        #[arg(long)]
+        buffer: Pubkey,
+        /// Compare the programs section by section of the ELF
+        #[arg(long)]
+        sections: bool,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Signing request files exchanged between the coordinator and the operators.
+#[derive(Subcommand)]
+enum RequestCommand {
+    /// Write a signing request of the operation for the current nonce
+    New {
+        /// Contract that seeds UpgradeAdmin address
+        #[arg(long)]
+        contract: Pubkey,
+        /// Unix timestamp after which signatures are no longer valid
+        #[arg(long)]
+        expires_at: i64,
+        #[arg(long)]
+        output: PathBuf,
+        #[command(subcommand)]
+        operation: DigestOperation,
+    },
+    /// Sign the request digest and print the signature, works without network access
+    Sign {
+        request: PathBuf,
+        /// secp256k1 signer: keystore, file with hex of 32 byte key, or usb://ledger for the Ethereum app
+        #[arg(long)]
+        signer_key: PathBuf,
+    },
+    /// Verify signatures of operators against the signer set and add them to the request
+    Add {
+        request: PathBuf,
+        /// Signature in `<hex of 64 byte signature>:<recovery id>` format, as printed by `request sign`
+        #[arg(long = "signature", required = true, value_parser = parse_signature)]
+        signatures: Vec<EcdsaSignature>,
+    },
+    /// Print the signers collected so far and the signed weight
+    Status {
+        request: PathBuf,
+    },
+    /// Send the operation once the signed weight reaches the threshold
+    Submit {
+        request: PathBuf,
+    },
+}
+
+/// The password is read from UPGRADE_ADMIN_KEYSTORE_PASSWORD or prompted on the terminal.
+#[derive(Subcommand)]
+enum KeystoreCommand {
+    /// Generate a secp256k1 key into a new keystore and print its Ethereum address
+    New {
+        #[arg(long)]
+        output: PathBuf,
+        #[arg(long, default_value_t = keystore::DEFAULT_ITERATIONS)]
+        iterations: u32,
+    },
+    /// Encrypt an existing file with hex of 32 byte secret key into a new keystore
+    Import {
+        #[arg(long)]
+        secret_key: PathBuf,
+        #[arg(long)]
+        output: PathBuf,
+        #[arg(long, default_value_t = keystore::DEFAULT_ITERATIONS)]
+        iterations: u32,
+    },
+    /// Decrypt the keystore and print its Ethereum address
+    Address {
+        keystore: PathBuf,
+    },
+}
+
+/// Durable nonce and offline signing options, analogous to solana-cli.
+#[derive(Args)]
+struct TransactionArgs {
+    /// Durable nonce account to use instead of a recent blockhash, the transaction stays valid until the nonce is advanced
+    #[arg(long, global = true)]
+    nonce_account: Option<Pubkey>,
+    /// Nonce authority: keypair file, usb://ledger, or pubkey of an authority that signs offline. The fee payer by default
+    #[arg(long, global = true, requires = "nonce_account")]
+    nonce_authority: Option<String>,
+    /// Blockhash to use instead of fetching it, the stored nonce value with --nonce-account
+    #[arg(long, global = true)]
+    blockhash: Option<Hash>,
+    /// Print the message and signatures of the available signers instead of sending the transaction
+    #[arg(long, global = true)]
+    sign_only: bool,
+    /// Signature made offline in `<pubkey>=<signature>` format, as printed by --sign-only and sign-message
+    #[arg(long = "signer", global = true, value_parser = parse_presigned)]
+    signers: Vec<(Pubkey, Signature)>,
+    /// Compute unit limit, measured by simulation by default
+    #[arg(long, global = true)]
+    compute_unit_limit: Option<u32>,
+    /// Compute unit price in micro-lamports, the recent priority fee by default
+    #[arg(long, global = true)]
+    compute_unit_price: Option<u64>,
+    /// Percentile of priority fees paid in recent slots for the accounts written by the transaction
+    #[arg(long, global = true, default_value_t = transaction::DEFAULT_PRIORITY_FEE_PERCENTILE)]
+    priority_fee_percentile: u8,
+    /// Cap of the recent priority fee in micro-lamports per compute unit
+    #[arg(long, global = true, default_value_t = transaction::DEFAULT_MAX_COMPUTE_UNIT_PRICE)]
+    max_compute_unit_price: u64,
+}
+
+/// Operations of the digest command. `--target` of contract-scoped operations defaults to the admin contract.
+#[derive(Subcommand)]
+enum DigestOperation {
+    Upgrade {
+        #[arg(long)]
+        target: Option<Pubkey>,
+        #[arg(long)]
+        buffer: Pubkey,
+        /// Hex of SHA-256 of the buffer program data, fetched from the buffer by default
+        #[arg(long, value_parser = parse_hash)]
+        code_hash: Option<[u8; 32]>,
+        #[arg(long)]
+        spill: Pubkey,
+    },
+    ProposeUpgrade {
+        #[arg(long)]
+        target: Option<Pubkey>,
+        #[arg(long)]
+        buffer: Pubkey,
+        #[arg(long, value_parser = parse_hash)]
+        code_hash: Option<[u8; 32]>,
+        #[arg(long)]
+        not_before_slot: Option<u64>,
+        #[arg(long)]
+        not_after_slot: Option<u64>,
+    },
+    CancelUpgrade {
+        #[arg(long)]
+        target: Option<Pubkey>,
+        #[arg(long)]
+        buffer: Pubkey,
+        #[arg(long)]
+        close_buffer: bool,
+    },
+    Rollback {
+        #[arg(long)]
+        target: Option<Pubkey>,
+        #[arg(long)]
+        buffer: Pubkey,
+    },
+    ExtendProgram {
+        #[arg(long)]
+        target: Option<Pubkey>,
+        #[arg(long)]
+        additional_bytes: u32,
+    },
+    ChangeKey {
+        #[arg(long = "public-key", required = true, value_parser = parse_admin_key)]
+        public_keys: Vec<AdminKey>,
+        #[arg(long)]
+        threshold: u8,
+    },
+    CancelKeyChange,
+    ChangeAuthority {
+        #[arg(long)]
+        target: Option<Pubkey>,
+        #[arg(long)]
+        new_authority: Pubkey,
+    },
+    SetImmutable {
+        #[arg(long)]
+        target: Option<Pubkey>,
+    },
+    Close {
+        #[arg(long)]
+        destination: Pubkey,
+    },
+    Pause,
+    Unpause,
+    Deactivate,
+    Reactivate,
+}
+
+#[derive(Args)]
+struct UpgradeTarget {
+    #[arg(long)]
+    contract: Pubkey,
+    #[arg(long)]
+    buffer: Pubkey,
+    /// Account receiving lamports of the buffer
+    #[arg(long)]
+    spill: Pubkey,
+}
+
+#[derive(Args)]
+struct Signing {
+    /// secp256k1 signers: keystores, files with hex of 32 byte key, or usb://ledger for the Ethereum app
+    #[arg(long = "signer-key")]
+    signer_keys: Vec<PathBuf>,
+    /// Signatures of other signers in `<hex of 64 byte signature>:<recovery id>` format, as printed by sign-* commands
+    #[arg(long = "signature", value_parser = parse_signature)]
+    signatures: Vec<EcdsaSignature>,
+    /// Unix timestamp after which signatures are no longer valid
+    #[arg(long)]
+    expires_at: i64,
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match &cli.command {
+        Command::SignMessage { message } => {
+            let (pubkey, signer) = signer(&cli.keypair)?;
+            let signer = signer.ok_or("--keypair should be a keypair file or usb://ledger")?;
+            let message = transaction::decode_message(message)?;
+            println!("{}={}", pubkey, signer.try_sign_message(&message.serialize())?);
+            return Ok(());
+        }
+        Command::LedgerAddress { signer_key } => {
+            let address = ledger::Ledger::open()?.ethereum_address(&ledger::ethereum_path(signer_key)?)?;
+            println!("0x{}", hex::encode(address));
+            return Ok(());
+        }
+        Command::Keystore(command) => return run_keystore(command),
+        Command::DiffUpgrade { contract, buffer, sections, json } => {
+            let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+            let deployed = client::get_deployed_program(&rpc, contract)?;
+            let buffer_data = client::get_buffer_program(&rpc, buffer)?;
+            let report = diff::report(contract, &deployed, buffer, &buffer_data, *sections)?;
+            match json {
+                true => println!("{}", serde_json::to_string_pretty(&report)?),
+                false => print!("{}", report),
+            }
+            return Ok(());
+        }
+        Command::Request(RequestCommand::Sign { request, signer_key }) => {
+            let request = coordinator::read(request)?;
+            eprintln!("{}", serde_json::to_string_pretty(&request.envelope)?);
+            let signature = sign(signer_key, &request.envelope.digest)?;
+            println!("{}:{}", hex::encode(signature.signature), signature.recovery_id);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+    let program_id = cli.program_id.ok_or("--program-id is required")?;
+
+    match cli.command {
+        Command::Init { contract, public_keys, threshold, domain, wide_nonces } => {
+            let args = InitializeAdminArgs {
+                public_keys,
+                weights: vec![],
+                threshold,
+                contract,
+                hashing_mode: HashingMode::Legacy,
+                domain,
+                signers_root: None,
+                upgrade_key: None,
+                key_change_delay: 0,
+                curve: Curve::Secp256k1,
+                hash_algorithm: HashAlgorithm::Keccak,
+                guardian: None,
+                recovery_delay: 0,
+                managed_contracts: vec![],
+                expires_at_slot: None,
+                executors: vec![],
+                reimbursement: 0,
+                min_delay_slots: 0,
+                min_upgrade_interval_slots: 0,
+                nonce_width: match wide_nonces {
+                    true => NonceWidth::U128,
+                    false => NonceWidth::U64,
+                },
+            };
+
+            let payer = signer(&cli.keypair)?;
+            let instruction = client::initialize_admin_instruction(&program_id, &payer.0, args);
+            if let Some(signature) = send(&rpc, payer, &cli.transaction, &[instruction])? {
+                println!("UpgradeAdmin {} created: {}", client::admin_address(&program_id, &contract), signature);
+            }
+        }
+        Command::Show { contract } => {
+            println!("{:#?}", client::get_upgrade_admin(&rpc, &program_id, &contract)?);
+        }
+        Command::SignUpgrade { upgrade, signer_key, expires_at } => {
+            let upgrade_admin = client::get_upgrade_admin(&rpc, &program_id, &upgrade.contract)?;
+            let code_hash = client::get_buffer_code_hash(&rpc, &upgrade.buffer)?;
+            let hash = hashing::upgrade_hash(&program_id, &upgrade_admin, &upgrade.contract, &upgrade.buffer, &code_hash, &upgrade.spill, expires_at);
+            let signature = sign(&signer_key, hash.as_ref())?;
+            println!("{}:{}", hex::encode(signature.signature), signature.recovery_id);
+        }
+        Command::Digest { contract, nonce, expires_at, operation } => {
+            let upgrade_admin = client::get_upgrade_admin(&rpc, &program_id, &contract)?;
+            let operation = digest_operation(&rpc, &contract, operation)?;
+            let nonce = nonce.unwrap_or(match operation.nonce_kind() {
+                NonceKind::Upgrade => upgrade_admin.upgrade_nonce,
+                NonceKind::Key => upgrade_admin.key_nonce,
+                NonceKind::Authority => upgrade_admin.authority_nonce,
+            });
+            let envelope = signing::envelope(&program_id, &upgrade_admin, nonce, operation, expires_at)?;
+            println!("{}", serde_json::to_string_pretty(&envelope)?);
+        }
+        Command::Upgrade { upgrade, signing } => {
+            let upgrade_admin = client::get_upgrade_admin(&rpc, &program_id, &upgrade.contract)?;
+            let code_hash = client::get_buffer_code_hash(&rpc, &upgrade.buffer)?;
+            let hash = hashing::upgrade_hash(&program_id, &upgrade_admin, &upgrade.contract, &upgrade.buffer, &code_hash, &upgrade.spill, signing.expires_at);
+            let signatures = collect_signatures(&signing, hash.as_ref())?;
+            let instruction = client::upgrade_instruction(&rpc, &program_id, &upgrade.contract, &upgrade.buffer, &upgrade.spill, code_hash, signatures, vec![], signing.expires_at)?;
+            if let Some(signature) = send(&rpc, signer(&cli.keypair)?, &cli.transaction, &[instruction])? {
+                println!("Contract {} upgraded: {}", upgrade.contract, signature);
+            }
+        }
+        Command::ChangeKey { contract, public_keys, threshold, signing } => {
+            let upgrade_admin = client::get_upgrade_admin(&rpc, &program_id, &contract)?;
+            let hash = hashing::change_public_key_hash(&program_id, &upgrade_admin, &public_keys, &[], threshold, None, &None, signing.expires_at);
+            let signatures = collect_signatures(&signing, hash.as_ref())?;
+            let instruction = client::change_public_key_instruction(&rpc, &program_id, &contract, public_keys, vec![], threshold, None, None, signatures, vec![], signing.expires_at)?;
+            if let Some(signature) = send(&rpc, signer(&cli.keypair)?, &cli.transaction, &[instruction])? {
+                println!("Key change staged: {}", signature);
+            }
+        }
+        Command::ChangeAuthority { contract, new_authority, signing } => {
+            let upgrade_admin = client::get_upgrade_admin(&rpc, &program_id, &contract)?;
+            let hash = hashing::change_authority_hash(&program_id, &upgrade_admin, &contract, &new_authority, signing.expires_at);
+            let signatures = collect_signatures(&signing, hash.as_ref())?;
+            let instruction = client::change_authority_instruction(&rpc, &program_id, &contract, &new_authority, signatures, vec![], signing.expires_at)?;
+            if let Some(signature) = send(&rpc, signer(&cli.keypair)?, &cli.transaction, &[instruction])? {
+                println!("Authority of {} changed to {}: {}", contract, new_authority, signature);
+            }
+        }
+        Command::Close { contract, destination, signing } => {
+            let upgrade_admin = client::get_upgrade_admin(&rpc, &program_id, &contract)?;
+            let hash = hashing::close_admin_hash(&program_id, &upgrade_admin, &destination, signing.expires_at);
+            let signatures = collect_signatures(&signing, hash.as_ref())?;
+            let instruction = client::close_admin_instruction(&rpc, &program_id, &contract, &destination, signatures, vec![], signing.expires_at)?;
+            if let Some(signature) = send(&rpc, signer(&cli.keypair)?, &cli.transaction, &[instruction])? {
+                println!("UpgradeAdmin of {} closed: {}", contract, signature);
+            }
+        }
+        Command::Watch { contract, ws_url } => {
+            let admin = client::admin_address(&program_id, &contract);
+            let ws_url = ws_url.unwrap_or_else(|| websocket_url(&cli.url));
+            for event in client::events::subscribe_admin_events(&ws_url, &admin)? {
+                println!("{}", serde_json::json!({ "slot": event.slot, "signature": event.signature.to_string(), "event": event.event }));
+            }
+            return Err("websocket connection closed".into());
+        }
+        Command::ExportState { output } => {
+            let snapshot = snapshot::export(&rpc, &program_id)?;
+            std::fs::write(&output, serde_json::to_string_pretty(&snapshot)?)?;
+            println!("{} accounts at slot {} written to {}", snapshot.accounts.len(), snapshot.slot, output.display());
+        }
+        Command::VerifyState { snapshot: path } => {
+            let snapshot: snapshot::Snapshot = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            if snapshot.program_id != program_id {
+                return Err(format!("{} is a snapshot of program {}", path.display(), snapshot.program_id).into());
+            }
+            let mismatches = snapshot::verify(&rpc, &snapshot)?;
+            for mismatch in &mismatches {
+                println!("{}", mismatch);
+            }
+            if !mismatches.is_empty() {
+                return Err(format!("{} accounts differ from the snapshot at slot {}", mismatches.len(), snapshot.slot).into());
+            }
+            println!("{} accounts match the snapshot at slot {}", snapshot.accounts.len(), snapshot.slot);
+        }
+        Command::Request(command) => run_request(&rpc, &program_id, &cli.keypair, &cli.transaction, command)?,
+        Command::SignMessage { .. } | Command::LedgerAddress { .. } | Command::Keystore(_) | Command::DiffUpgrade { .. } => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn run_request(rpc: &RpcClient, program_id: &Pubkey, keypair: &str, args: &TransactionArgs, command: RequestCommand) -> Result<()> {
+    let (request, upgrade_admin) = match command {
+        RequestCommand::New { contract, expires_at, output, operation } => {
+            let upgrade_admin = client::get_upgrade_admin(rpc, program_id, &contract)?;
+            let operation = digest_operation(rpc, &contract, operation)?;
+            let request = coordinator::request(program_id, &upgrade_admin, operation, expires_at)?;
+            coordinator::write(&output, &request)?;
+            println!("{}", hex::encode(request.envelope.digest));
+            return Ok(());
+        }
+        RequestCommand::Add { request: path, signatures } => {
+            let mut request = coordinator::read(&path)?;
+            let upgrade_admin = request_admin(rpc, &request)?;
+            for signature in &signatures {
+                let signer = coordinator::add_signature(&mut request, &upgrade_admin, signature)?;
+                println!("Added signature of {}", admin_key_string(&signer));
+            }
+            coordinator::write(&path, &request)?;
+            (request, upgrade_admin)
+        }
+        RequestCommand::Status { request: path } => {
+            let request = coordinator::read(&path)?;
+            let upgrade_admin = request_admin(rpc, &request)?;
+            for partial in &request.signatures {
+                println!("Signed by {}", admin_key_string(&partial.signer));
+            }
+            (request, upgrade_admin)
+        }
+        RequestCommand::Submit { request: path } => {
+            let request = coordinator::read(&path)?;
+            let upgrade_admin = request_admin(rpc, &request)?;
+            let payer = signer(keypair)?;
+            let instruction = coordinator::assemble(&request, &upgrade_admin, &payer.0)?;
+            if let Some(signature) = send(rpc, payer, args, &[instruction])? {
+                println!("Operation of {} sent: {}", path.display(), signature);
+            }
+            return Ok(());
+        }
+        RequestCommand::Sign { .. } => unreachable!(),
+    };
+
+    let quorum = coordinator::quorum(&request, &upgrade_admin)?;
+    println!("Signed weight: {}, threshold: {}", quorum.weight, quorum.threshold);
+    Ok(())
+}
+
+/// UpgradeAdmin the signing request was made for.
+fn request_admin(rpc: &RpcClient, request: &coordinator::SigningRequest) -> Result<UpgradeAdmin> {
+    let data = rpc.get_account_data(&request.envelope.admin)?;
+    Ok(client::decode_upgrade_admin(&request.envelope.admin, &data)?)
+}
+
+fn admin_key_string(key: &AdminKey) -> String {
+    match key {
+        AdminKey::PublicKey(key) => hex::encode(key),
+        AdminKey::EthAddress(address) => format!("0x{}", hex::encode(address)),
+        AdminKey::CompressedPublicKey(key) => hex::encode(key),
+        AdminKey::Ed25519(key) => key.to_string(),
+    }
+}
+
+fn run_keystore(command: &KeystoreCommand) -> Result<()> {
+    let (secret, output, iterations) = match command {
+        KeystoreCommand::New { output, iterations } => (keystore::generate_secret(), output, *iterations),
+        KeystoreCommand::Import { secret_key, output, iterations } => (read_secret(secret_key)?, output, *iterations),
+        KeystoreCommand::Address { keystore: path } => {
+            let secret = keystore::read(path, &keystore_password(&format!("Password of {}", path.display()))?)?;
+            println!("0x{}", hex::encode(keystore::eth_address(&secret)?));
+            return Ok(());
+        }
+    };
+
+    let password = keystore_password("New keystore password")?;
+    if std::env::var(KEYSTORE_PASSWORD_ENV).is_err() && keystore_password("Repeat password")? != password {
+        return Err("passwords do not match".into());
+    }
+
+    let keystore = keystore::write(output, &secret, &password, iterations)?;
+    println!("0x{}", keystore.address.unwrap_or_default());
+    Ok(())
+}
+
+/// Keystore password from UPGRADE_ADMIN_KEYSTORE_PASSWORD, otherwise prompted on the terminal without echo.
+fn keystore_password(prompt: &str) -> Result<String> {
+    if let Ok(password) = std::env::var(KEYSTORE_PASSWORD_ENV) {
+        return Ok(password);
+    }
+
+    eprint!("{}: ", prompt);
+    let stty = |arg: &str| std::process::Command::new("stty").arg(arg).stdin(Stdio::inherit()).status().is_ok_and(|status| status.success());
+    let echo_disabled = stty("-echo");
+    let mut password = String::new();
+    let result = std::io::stdin().read_line(&mut password);
+    if echo_disabled {
+        stty("echo");
+        eprintln!();
+    }
+
+    result?;
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// secp256k1 secret key from a keystore or from a file with its hex.
+fn read_secret(path: &Path) -> Result<[u8; 32]> {
+    let content = std::fs::read_to_string(path)?;
+    if let Some(encrypted) = keystore::parse(&content) {
+        let password = keystore_password(&format!("Password of {}", path.display()))?;
+        return Ok(keystore::decrypt(&encrypted, &password)?);
+    }
+
+    hex::decode(content.trim())?.try_into().map_err(|_| "secret key should be 32 bytes".into())
+}
+
+/// WebSocket URL of the RPC node at `url`: ws scheme and the next port, as solana-cli derives it.
+fn websocket_url(url: &str) -> String {
+    let url = url.replacen("http", "ws", 1);
+    match url.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => format!("{}:{}", host, port.parse::<u16>().unwrap() + 1),
+        _ => url,
+    }
+}
+
+fn read_keypair(path: &str) -> Result<Keypair> {
+    let path = match path.strip_prefix("~/") {
+        Some(rest) => PathBuf::from(std::env::var("HOME")?).join(rest),
+        None => PathBuf::from(path),
+    };
+
+    read_keypair_file(&path).map_err(|e| format!("failed to read keypair {}: {}", path.display(), e).into())
+}
+
+/// Keypair file, Ledger, or bare pubkey of a signer whose signature is made offline.
+fn signer(source: &str) -> Result<(Pubkey, Option<Box<dyn Signer>>)> {
+    if let Ok(pubkey) = Pubkey::from_str(source) {
+        return Ok((pubkey, None));
+    }
+
+    let signer: Box<dyn Signer> = if ledger::is_ledger_uri(source) {
+        Box::new(ledger::LedgerSigner::new(source)?)
+    } else {
+        Box::new(read_keypair(source)?)
+    };
+    Ok((signer.pubkey(), Some(signer)))
+}
+
+/// Sends `instructions` paid by `payer`, or prints the message and available signatures with --sign-only.
+/// Returns the transaction signature if it was sent.
+fn send(rpc: &RpcClient, payer: (Pubkey, Option<Box<dyn Signer>>), args: &TransactionArgs, instructions: &[Instruction]) -> Result<Option<Signature>> {
+    let (payer, payer_signer) = payer;
+    let mut keypairs = payer_signer.into_iter().collect::<Vec<_>>();
+    let durable_nonce = match args.nonce_account {
+        Some(account) => {
+            let authority = match &args.nonce_authority {
+                Some(source) => {
+                    let (authority, keypair) = signer(source)?;
+                    keypairs.extend(keypair);
+                    authority
+                }
+                None => payer,
+            };
+            Some(DurableNonce { account, authority })
+        }
+        None => None,
+    };
+
+    let config = TransactionConfig {
+        durable_nonce,
+        blockhash: args.blockhash,
+        compute_unit_limit: match args.compute_unit_limit {
+            Some(limit) => ComputeUnitLimit::Fixed(limit),
+            None => ComputeUnitLimit::default(),
+        },
+        priority_fee: match args.compute_unit_price {
+            Some(price) => PriorityFee::Fixed(price),
+            None => PriorityFee::Recent { percentile: args.priority_fee_percentile, max: args.max_compute_unit_price },
+        },
+    };
+    let message = transaction::build_message(rpc, &payer, instructions, &config)?;
+    let signers = keypairs.iter().map(|keypair| keypair.as_ref()).collect::<Vec<_>>();
+    if args.sign_only {
+        print!("{}", transaction::sign_only(&message, &signers)?);
+        return Ok(None);
+    }
+
+    Ok(Some(transaction::send_message(rpc, &message, &signers, &args.signers)?))
+}
+
+fn digest_operation(rpc: &RpcClient, contract: &Pubkey, operation: DigestOperation) -> Result<Operation> {
+    let code_hash = |code_hash: Option<[u8; 32]>, buffer: &Pubkey| code_hash.map_or_else(|| client::get_buffer_code_hash(rpc, buffer), Ok);
+
+    Ok(match operation {
+        DigestOperation::Upgrade { target, buffer, code_hash: hash, spill } => Operation::Upgrade {
+            contract: target.unwrap_or(*contract),
+            code_hash: code_hash(hash, &buffer)?,
+            buffer,
+            spill,
+        },
+        DigestOperation::ProposeUpgrade { target, buffer, code_hash: hash, not_before_slot, not_after_slot } => Operation::ProposeUpgrade {
+            contract: target.unwrap_or(*contract),
+            code_hash: code_hash(hash, &buffer)?,
+            buffer,
+            not_before_slot,
+            not_after_slot,
+        },
+        DigestOperation::CancelUpgrade { target, buffer, close_buffer } =>
+            Operation::CancelUpgrade { contract: target.unwrap_or(*contract), buffer, close_buffer },
+        DigestOperation::Rollback { target, buffer } =>
+            Operation::Rollback { contract: target.unwrap_or(*contract), buffer },
+        DigestOperation::ExtendProgram { target, additional_bytes } =>
+            Operation::ExtendProgram { contract: target.unwrap_or(*contract), additional_bytes },
+        DigestOperation::ChangeKey { public_keys, threshold } => Operation::ChangePublicKey {
+            new_public_keys: public_keys,
+            new_weights: vec![],
+            new_threshold: threshold,
+            new_signers_root: None,
+            new_upgrade_key: None,
+        },
+        DigestOperation::CancelKeyChange => Operation::CancelKeyChange,
+        DigestOperation::ChangeAuthority { target, new_authority } =>
+            Operation::ChangeAuthority { contract: target.unwrap_or(*contract), new_authority },
+        DigestOperation::SetImmutable { target } => Operation::SetImmutable { contract: target.unwrap_or(*contract) },
+        DigestOperation::Close { destination } => Operation::CloseAdmin { destination },
+        DigestOperation::Pause => Operation::SetPaused { paused: true },
+        DigestOperation::Unpause => Operation::SetPaused { paused: false },
+        DigestOperation::Deactivate => Operation::SetActive { active: false },
+        DigestOperation::Reactivate => Operation::SetActive { active: true },
+    })
+}
+
+/// Signs `hash` with every local signer key and appends the signatures passed by other signers.
+fn collect_signatures(signing: &Signing, hash: &[u8]) -> Result<Vec<EcdsaSignature>> {
+    let mut signatures = signing.signer_keys.iter()
+        .map(|path| sign(path, hash))
+        .collect::<Result<Vec<_>>>()?;
+    signatures.extend(signing.signatures.iter().cloned());
+    Ok(signatures)
+}
+
+fn sign(key_path: &Path, hash: &[u8]) -> Result<EcdsaSignature> {
+    let source = key_path.to_string_lossy();
+    if ledger::is_ledger_uri(&source) {
+        eprintln!("Confirm the message {} on ledger", hex::encode(hash));
+        return ledger::Ledger::open()?.ethereum_sign_hash(&ledger::ethereum_path(&source)?, hash);
+    }
+
+    let secret_key = libsecp256k1::SecretKey::parse(&read_secret(key_path)?).map_err(|e| format!("invalid secret key: {:?}", e))?;
+    let message = libsecp256k1::Message::parse_slice(hash).map_err(|e| format!("invalid hash: {:?}", e))?;
+    let (signature, recovery_id) = libsecp256k1::sign(&message, &secret_key);
+    Ok(EcdsaSignature {
+        signature: signature.serialize(),
+        recovery_id: recovery_id.serialize() as u64,
+        personal_sign: false,
+    })
+}
+
+fn parse_admin_key(value: &str) -> std::result::Result<AdminKey, String> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).map_err(|e| e.to_string())?;
+    match bytes.len() {
+        64 => Ok(AdminKey::PublicKey(bytes.try_into().unwrap())),
+        33 => Ok(AdminKey::CompressedPublicKey(bytes.try_into().unwrap())),
+        20 => Ok(AdminKey::EthAddress(bytes.try_into().unwrap())),
+        len => Err(format!("expected 64, 33 or 20 bytes, got {}", len)),
+    }
+}
+
+fn parse_hash(value: &str) -> std::result::Result<[u8; 32], String> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).map_err(|e| e.to_string())?;
+    bytes.try_into().map_err(|_| "expected 32 bytes".to_string())
+}
+
+fn parse_signature(value: &str) -> std::result::Result<EcdsaSignature, String> {
+    let (signature, recovery_id) = value.split_once(':').ok_or("expected <signature>:<recovery id>")?;
+    let signature = hex::decode(signature.trim_start_matches("0x")).map_err(|e| e.to_string())?;
+    Ok(EcdsaSignature {
+        signature: signature.try_into().map_err(|_| "expected 64 byte signature".to_string())?,
+        recovery_id: u64::from_str(recovery_id).map_err(|e| e.to_string())?,
+        personal_sign: false,
+    })
+}
+
+fn parse_presigned(value: &str) -> std::result::Result<(Pubkey, Signature), String> {
+    let (pubkey, signature) = value.split_once('=').ok_or("expected <pubkey>=<signature>")?;
+    Ok((
+        Pubkey::from_str(pubkey).map_err(|e| e.to_string())?,
+        Signature::from_str(signature).map_err(|e| e.to_string())?,
+    ))
+}
@@ -0,0 +1,242 @@
+//! Collection of partial signatures for threshold signer sets. The coordinator writes a signing request with the
+//! operation digest, operators sign the digest independently and return their signatures, every signature is checked
+//! against the signer set stored in UpgradeAdmin as it is added, and the instruction is assembled once the signed
+//! weight reaches the threshold. Only secp256k1 signers stored in `public_keys` are collected, signers proven by
+//! merkle proof and precompile signatures are passed to the instruction builders directly.
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::secp256k1_recover::secp256k1_recover;
+use thiserror::Error;
+use upgrade::ecdsa::{self, EcdsaSignature};
+use upgrade::instructions;
+use upgrade::state::{AdminKey, Curve, UpgradeAdmin};
+use crate::encoding;
+use crate::signing::{self, NonceKind, Operation, SigningEnvelope};
+
+#[derive(Debug, Error)]
+pub enum CoordinatorError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid signing request: {0}")]
+    InvalidFormat(String),
+    #[error("signing request is for UpgradeAdmin {0}")]
+    AdminMismatch(Pubkey),
+    #[error("signing request is stale: nonce, signer set or policy of UpgradeAdmin changed since it was made")]
+    StaleRequest,
+    #[error("signatures of {0} signers can not be collected off-chain")]
+    UnsupportedCurve(String),
+    #[error("invalid signature: {0}")]
+    InvalidSignature(ProgramError),
+    #[error("signature of {0} does not belong to the signer set")]
+    UnknownSigner(String),
+    #[error("{0} has already signed")]
+    DuplicateSigner(String),
+    #[error("quorum is not reached: signed weight {weight}, threshold {threshold}")]
+    QuorumNotReached { weight: u64, threshold: u8 },
+    #[error("invalid operation: {0}")]
+    InvalidOperation(ProgramError),
+}
+
+pub type Result<T> = std::result::Result<T, CoordinatorError>;
+
+/// Signature of one operator together with the stored signer key it recovers to.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct PartialSignature {
+    #[serde(serialize_with = "encoding::admin_key", deserialize_with = "encoding::de::admin_key")]
+    pub signer: AdminKey,
+    #[serde(serialize_with = "encoding::bytes", deserialize_with = "encoding::de::bytes")]
+    pub signature: [u8; 64],
+    pub recovery_id: u64,
+    #[serde(default)]
+    pub personal_sign: bool,
+}
+
+impl From<&PartialSignature> for EcdsaSignature {
+    fn from(partial: &PartialSignature) -> Self {
+        EcdsaSignature {
+            signature: partial.signature,
+            recovery_id: partial.recovery_id,
+            personal_sign: partial.personal_sign,
+        }
+    }
+}
+
+/// Signing request file shared with operators: the envelope to review and sign and the signatures collected so far.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct SigningRequest {
+    pub envelope: SigningEnvelope,
+    #[serde(default)]
+    pub signatures: Vec<PartialSignature>,
+}
+
+/// Signed weight of the collected signatures against the threshold of the signer set.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Quorum {
+    pub weight: u64,
+    pub threshold: u8,
+}
+
+impl Quorum {
+    pub fn is_reached(&self) -> bool {
+        self.weight >= self.threshold as u64
+    }
+}
+
+fn current_nonce(admin: &UpgradeAdmin, kind: NonceKind) -> u128 {
+    match kind {
+        NonceKind::Upgrade => admin.upgrade_nonce,
+        NonceKind::Key => admin.key_nonce,
+        NonceKind::Authority => admin.authority_nonce,
+    }
+}
+
+/// Signing request of `operation` for the current nonce of UpgradeAdmin.
+pub fn request(program_id: &Pubkey, admin: &UpgradeAdmin, operation: Operation, expires_at: i64) -> Result<SigningRequest> {
+    if admin.curve != Curve::Secp256k1 {
+        return Err(CoordinatorError::UnsupportedCurve(format!("{:?}", admin.curve)));
+    }
+
+    let nonce = current_nonce(admin, operation.nonce_kind());
+    let envelope = signing::envelope(program_id, admin, nonce, operation, expires_at).map_err(CoordinatorError::InvalidOperation)?;
+    Ok(SigningRequest { envelope, signatures: vec![] })
+}
+
+/// Checks that the request is for `admin` and that its digest is still the one the processor will verify.
+pub fn check_request(request: &SigningRequest, admin: &UpgradeAdmin) -> Result<()> {
+    let envelope = &request.envelope;
+    let address = crate::admin_address(&envelope.program_id, &admin.contract);
+    if envelope.admin != address {
+        return Err(CoordinatorError::AdminMismatch(envelope.admin));
+    }
+    if admin.curve != Curve::Secp256k1 {
+        return Err(CoordinatorError::UnsupportedCurve(format!("{:?}", admin.curve)));
+    }
+
+    let nonce = current_nonce(admin, envelope.operation.nonce_kind());
+    let digest = signing::digest(&envelope.program_id, admin, nonce, &envelope.operation, envelope.expires_at)
+        .map_err(CoordinatorError::InvalidOperation)?;
+    if envelope.nonce != nonce || digest.to_bytes() != envelope.digest {
+        return Err(CoordinatorError::StaleRequest);
+    }
+
+    Ok(())
+}
+
+/// Stored signer of `admin` that made `signature` over `digest`, with the same recovery rules as the processor.
+pub fn recover_signer(digest: &[u8; 32], signature: &EcdsaSignature, admin: &UpgradeAdmin) -> Result<AdminKey> {
+    ecdsa::verify_low_s(&signature.signature).map_err(CoordinatorError::InvalidSignature)?;
+    let recovery_id = ecdsa::normalize_recovery_id(signature.recovery_id).map_err(CoordinatorError::InvalidSignature)?;
+    let hash = match signature.personal_sign {
+        true => ecdsa::personal_sign_hash(digest).to_bytes(),
+        false => *digest,
+    };
+    let key = secp256k1_recover(&hash, recovery_id, &signature.signature)
+        .map_err(|_| CoordinatorError::InvalidSignature(upgrade::error::UpgradeError::SignatureRecoveryFailed.into()))?
+        .to_bytes();
+
+    admin.public_keys.iter()
+        .find(|target_key| match target_key {
+            AdminKey::PublicKey(public_key) => key == *public_key,
+            AdminKey::EthAddress(address) => ecdsa::eth_address(&key) == *address,
+            AdminKey::CompressedPublicKey(public_key) => ecdsa::compress_public_key(&key) == *public_key,
+            AdminKey::Ed25519(_) => false,
+        })
+        .cloned()
+        .ok_or_else(|| CoordinatorError::UnknownSigner(format!("0x{}", hex::encode(ecdsa::eth_address(&key)))))
+}
+
+/// Validates `signature` of an operator against the signer set and adds it to the request.
+/// Returns the stored key of the signer.
+pub fn add_signature(request: &mut SigningRequest, admin: &UpgradeAdmin, signature: &EcdsaSignature) -> Result<AdminKey> {
+    check_request(request, admin)?;
+    let signer = recover_signer(&request.envelope.digest, signature, admin)?;
+    if request.signatures.iter().any(|partial| partial.signer == signer) {
+        return Err(CoordinatorError::DuplicateSigner(encoding::admin_key_string(&signer)));
+    }
+
+    request.signatures.push(PartialSignature {
+        signer: signer.clone(),
+        signature: signature.signature,
+        recovery_id: signature.recovery_id,
+        personal_sign: signature.personal_sign,
+    });
+    Ok(signer)
+}
+
+/// Signed weight of the collected signatures. Every signature is verified again, so signers removed from the set
+/// since they signed are not counted.
+pub fn quorum(request: &SigningRequest, admin: &UpgradeAdmin) -> Result<Quorum> {
+    check_request(request, admin)?;
+
+    let mut signed = vec![false; admin.public_keys.len()];
+    for partial in &request.signatures {
+        let signer = recover_signer(&request.envelope.digest, &partial.into(), admin)?;
+        let index = admin.public_keys.iter().position(|key| *key == signer).unwrap();
+        if signed[index] {
+            return Err(CoordinatorError::DuplicateSigner(encoding::admin_key_string(&signer)));
+        }
+        signed[index] = true;
+    }
+
+    let weight = signed.iter().enumerate()
+        .filter(|(_, signed)| **signed)
+        .map(|(index, _)| admin.weights.get(index).copied().unwrap_or(1) as u64)
+        .sum();
+    Ok(Quorum { weight, threshold: admin.threshold })
+}
+
+/// Instruction of the request with the collected signatures, once the quorum is reached. `payer` pays for
+/// account creation and receives lamports of closed buffers where the operation does not name the recipient.
+pub fn assemble(request: &SigningRequest, admin: &UpgradeAdmin, payer: &Pubkey) -> Result<Instruction> {
+    let quorum = quorum(request, admin)?;
+    if !quorum.is_reached() {
+        return Err(CoordinatorError::QuorumNotReached { weight: quorum.weight, threshold: quorum.threshold });
+    }
+
+    let envelope = &request.envelope;
+    let program_id = envelope.program_id;
+    let admin_contract = admin.contract;
+    let nonce = envelope.nonce;
+    let signatures = request.signatures.iter().map(EcdsaSignature::from).collect::<Vec<_>>();
+    let expires_at = envelope.expires_at;
+
+    Ok(match envelope.operation.clone() {
+        Operation::Upgrade { contract, buffer, code_hash, spill } =>
+            instructions::upgrade_managed(program_id, admin_contract, contract, buffer, spill, nonce, code_hash, signatures, vec![], expires_at),
+        Operation::ProposeUpgrade { contract, buffer, code_hash, not_before_slot, not_after_slot } =>
+            instructions::propose_upgrade(program_id, admin_contract, contract, buffer, *payer, nonce, code_hash, not_before_slot, not_after_slot, signatures, vec![], expires_at),
+        Operation::CancelUpgrade { contract, buffer, close_buffer } =>
+            instructions::cancel_upgrade(program_id, admin_contract, contract, buffer, *payer, nonce, close_buffer, signatures, vec![], expires_at),
+        Operation::Rollback { contract, buffer } =>
+            instructions::rollback(program_id, admin_contract, contract, buffer, *payer, nonce, signatures, expires_at),
+        Operation::ExtendProgram { contract, additional_bytes } =>
+            instructions::extend_program(program_id, admin_contract, contract, *payer, additional_bytes, nonce, signatures, vec![], expires_at),
+        Operation::ChangePublicKey { new_public_keys, new_weights, new_threshold, new_signers_root, new_upgrade_key } =>
+            instructions::change_public_key(program_id, admin_contract, new_public_keys, new_weights, new_threshold, new_signers_root, new_upgrade_key, nonce, signatures, vec![], expires_at),
+        Operation::CancelKeyChange =>
+            instructions::cancel_key_change(program_id, admin_contract, signatures, vec![], expires_at),
+        Operation::ChangeAuthority { contract, new_authority } =>
+            instructions::change_managed_authority(program_id, admin_contract, contract, new_authority, nonce, signatures, vec![], expires_at),
+        Operation::SetImmutable { contract } =>
+            instructions::set_immutable(program_id, admin_contract, contract, nonce, signatures, vec![], expires_at),
+        Operation::CloseAdmin { destination } =>
+            instructions::close_admin(program_id, admin_contract, destination, nonce, signatures, vec![], expires_at),
+        Operation::SetPaused { paused: true } => instructions::pause(program_id, admin_contract, signatures, vec![], expires_at),
+        Operation::SetPaused { paused: false } => instructions::unpause(program_id, admin_contract, signatures, vec![], expires_at),
+        Operation::SetActive { active: false } => instructions::deactivate(program_id, admin_contract, signatures, vec![], expires_at),
+        Operation::SetActive { active: true } => instructions::reactivate(program_id, admin_contract, signatures, vec![], expires_at),
+    })
+}
+
+pub fn read(path: &Path) -> Result<SigningRequest> {
+    serde_json::from_str(&std::fs::read_to_string(path)?).map_err(|e| CoordinatorError::InvalidFormat(e.to_string()))
+}
+
+pub fn write(path: &Path, request: &SigningRequest) -> Result<()> {
+    let content = serde_json::to_string_pretty(request).map_err(|e| CoordinatorError::InvalidFormat(e.to_string()))?;
+    Ok(std::fs::write(path, content)?)
+}
@@ -0,0 +1,148 @@
+//! Human-readable diagnosis of failed simulations. Custom errors of the failing instruction are decoded through
+//! `UpgradeError` or `SystemError`, and for errors caused by account state the accounts are fetched to say what
+//! was found instead of what was expected.
+
+use std::fmt;
+use num_traits::FromPrimitive;
+use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_program::instruction::{AccountMeta, InstructionError};
+use solana_program::message::Message;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction::SystemError;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::transaction::TransactionError;
+use upgrade::error::UpgradeError;
+use upgrade::state::VersionedUpgradeAdmin;
+use crate::parse::{self, ParsedAccount};
+
+/// Log line of `secp256k1_recover` results printed by the program
+const RECOVERED_KEY_LOG_PREFIX: &str = "Program log: Recovered public key from signature: ";
+
+/// Failed simulation: decoded error of the failing instruction, findings about the accounts it used and program logs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnosis {
+    pub error: TransactionError,
+    // Index of the failing instruction and its program
+    pub instruction: Option<(u8, Pubkey)>,
+    pub description: String,
+    pub details: Vec<String>,
+    pub logs: Vec<String>,
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.instruction {
+            Some((index, program)) => write!(f, "instruction {} of program {} failed: {}", index, program, self.description)?,
+            None => write!(f, "{}", self.description)?,
+        }
+        for detail in &self.details {
+            write!(f, "\n  {}", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes `error` of simulated `message`, fetching accounts of the failing instruction where it explains the error.
+pub fn diagnose(rpc: &RpcClient, message: &Message, error: TransactionError, logs: Vec<String>) -> Diagnosis {
+    let TransactionError::InstructionError(index, instruction_error) = &error else {
+        return Diagnosis { description: error.to_string(), error, instruction: None, details: vec![], logs };
+    };
+
+    let Some(instruction) = message.instructions.get(*index as usize) else {
+        return Diagnosis { description: error.to_string(), error, instruction: None, details: vec![], logs };
+    };
+    let program = message.account_keys[instruction.program_id_index as usize];
+    let accounts = instruction.accounts.iter()
+        .map(|account| AccountMeta::new_readonly(message.account_keys[*account as usize], false))
+        .collect::<Vec<_>>();
+    // Accounts are named only for instructions of the upgrade program
+    let named = match program == solana_program::system_program::id() {
+        true => vec![],
+        false => parse::parse_instruction(&instruction.data, &accounts).map(|parsed| parsed.accounts).unwrap_or_default(),
+    };
+    let account = |name: &str| named.iter().find(|account| account.name == name).map(|account| account.pubkey);
+
+    let mut details = vec![];
+    let description = match instruction_error {
+        InstructionError::Custom(code) if program == solana_program::system_program::id() => match SystemError::from_u32(*code) {
+            Some(system_error) => format!("system program error: {}", system_error),
+            None => instruction_error.to_string(),
+        },
+        InstructionError::Custom(code) if !named.is_empty() => match UpgradeError::from_u32(*code) {
+            Some(upgrade_error) => {
+                details = upgrade_error_details(rpc, &upgrade_error, &named, &logs);
+                format!("{} (upgrade program error {})", upgrade_error, code)
+            }
+            None => instruction_error.to_string(),
+        },
+        // Loader errors of CPIs made by the upgrade program surface as errors of its instruction
+        InstructionError::IncorrectAuthority => {
+            if let (Some(program_data), Some(admin)) = (account("program_data"), account("upgrade_admin")) {
+                details.extend(program_authority(rpc, &program_data, &admin));
+            }
+            format!("loader error: {}", instruction_error)
+        }
+        _ => instruction_error.to_string(),
+    };
+
+    details.extend(logs.iter()
+        .filter(|log| log.starts_with(&format!("Program {} failed", bpf_loader_upgradeable::id())))
+        .cloned());
+    Diagnosis { error: error.clone(), instruction: Some((*index, program)), description, details, logs }
+}
+
+fn upgrade_error_details(rpc: &RpcClient, error: &UpgradeError, accounts: &[ParsedAccount], logs: &[String]) -> Vec<String> {
+    let account = |name: &str| accounts.iter().find(|account| account.name == name).map(|account| account.pubkey);
+    let admin = account("upgrade_admin");
+
+    match error {
+        UpgradeError::WrongBufferAuthority => match (account("buffer"), admin) {
+            (Some(buffer), Some(admin)) => match rpc.get_account_data(&buffer).ok().and_then(|data| bincode::deserialize(&data).ok()) {
+                Some(UpgradeableLoaderState::Buffer { authority_address: Some(authority) }) =>
+                    vec![format!("buffer authority is {}, expected admin PDA {}", authority, admin)],
+                Some(UpgradeableLoaderState::Buffer { authority_address: None }) =>
+                    vec![format!("buffer {} has no authority, expected admin PDA {}", buffer, admin)],
+                _ => vec![format!("{} is not a buffer of the upgradeable loader", buffer)],
+            },
+            _ => vec![],
+        },
+        UpgradeError::NonceMismatch | UpgradeError::NotEnoughSignatures | UpgradeError::SignatureExpired => {
+            let Some(admin) = admin else { return vec![] };
+            match rpc.get_account_data(&admin).ok().and_then(|data| VersionedUpgradeAdmin::unpack(&data).ok()) {
+                Some(VersionedUpgradeAdmin::V2(upgrade_admin)) => vec![format!(
+                    "admin {} has upgrade nonce {}, authority nonce {}, key nonce {}, threshold {} of {} signers",
+                    admin, upgrade_admin.upgrade_nonce, upgrade_admin.authority_nonce, upgrade_admin.key_nonce,
+                    upgrade_admin.threshold, upgrade_admin.public_keys.len(),
+                )],
+                _ => vec![],
+            }
+        }
+        UpgradeError::CodeHashMismatch => match account("buffer") {
+            Some(buffer) => match rpc.get_account_data(&buffer) {
+                Ok(data) if data.len() >= UpgradeableLoaderState::size_of_buffer_metadata() => vec![format!(
+                    "buffer {} code hash is {}",
+                    buffer,
+                    hex::encode(solana_program::hash::hash(&data[UpgradeableLoaderState::size_of_buffer_metadata()..]).to_bytes()),
+                )],
+                _ => vec![],
+            },
+            None => vec![],
+        },
+        UpgradeError::RecoveredKeyMismatch => logs.iter()
+            .filter_map(|log| log.strip_prefix(RECOVERED_KEY_LOG_PREFIX))
+            .map(|key| format!("signature recovered to public key {} (base58)", key))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Finding about the upgrade authority of the program if it is not `admin`.
+fn program_authority(rpc: &RpcClient, program_data: &Pubkey, admin: &Pubkey) -> Option<String> {
+    match bincode::deserialize(&rpc.get_account_data(program_data).ok()?).ok()? {
+        UpgradeableLoaderState::ProgramData { upgrade_authority_address: Some(authority), .. } if authority != *admin =>
+            Some(format!("program upgrade authority is {}, expected admin PDA {}", authority, admin)),
+        UpgradeableLoaderState::ProgramData { upgrade_authority_address: None, .. } =>
+            Some("program is immutable".to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,119 @@
+//! Serde serializers rendering program types the way operators read them: base58 addresses and hex bytes.
+//! `de` parses the same representation back for files exchanged between operators.
+
+use std::str::FromStr;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::ser::{SerializeSeq, Serializer};
+use solana_program::pubkey::Pubkey;
+use upgrade::state::AdminKey;
+
+pub fn pubkey<S: Serializer>(value: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+pub fn bytes<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(value))
+}
+
+pub fn base64<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&BASE64.encode(value))
+}
+
+pub fn option_bytes<S: Serializer>(value: &Option<[u8; 32]>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => bytes(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Hex of the key bytes, the key kind follows from the length; base58 for Ed25519 keys.
+pub fn admin_key_string(key: &AdminKey) -> String {
+    match key {
+        AdminKey::PublicKey(key) => hex::encode(key),
+        AdminKey::EthAddress(address) => hex::encode(address),
+        AdminKey::CompressedPublicKey(key) => hex::encode(key),
+        AdminKey::Ed25519(key) => key.to_string(),
+    }
+}
+
+pub fn admin_key<S: Serializer>(value: &AdminKey, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&admin_key_string(value))
+}
+
+/// Inverse of `admin_key_string`.
+pub fn parse_admin_key(value: &str) -> Result<AdminKey, String> {
+    if let Ok(bytes) = hex::decode(value.trim_start_matches("0x")) {
+        return match bytes.len() {
+            64 => Ok(AdminKey::PublicKey(bytes.try_into().unwrap())),
+            33 => Ok(AdminKey::CompressedPublicKey(bytes.try_into().unwrap())),
+            20 => Ok(AdminKey::EthAddress(bytes.try_into().unwrap())),
+            len => Err(format!("expected 64, 33 or 20 bytes, got {}", len)),
+        };
+    }
+
+    Pubkey::from_str(value).map(AdminKey::Ed25519).map_err(|_| format!("invalid key {}", value))
+}
+
+pub fn admin_keys<S: Serializer>(value: &[AdminKey], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(value.len()))?;
+    for key in value {
+        seq.serialize_element(&admin_key_string(key))?;
+    }
+    seq.end()
+}
+
+pub fn option_admin_key<S: Serializer>(value: &Option<AdminKey>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(key) => admin_key(key, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub mod de {
+    use std::str::FromStr;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use serde::de::{Deserialize, Deserializer, Error};
+    use solana_program::pubkey::Pubkey;
+    use upgrade::state::AdminKey;
+
+    fn hex_array<E: Error, const N: usize>(value: &str) -> Result<[u8; N], E> {
+        hex::decode(value.trim_start_matches("0x")).map_err(E::custom)?
+            .try_into()
+            .map_err(|_| E::custom(format!("expected {} bytes", N)))
+    }
+
+    pub fn pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Pubkey::from_str(&value).map_err(D::Error::custom)
+    }
+
+    pub fn bytes<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+        hex_array(&String::deserialize(deserializer)?)
+    }
+
+    pub fn base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        BASE64.decode(String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    pub fn option_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<[u8; 32]>, D::Error> {
+        Option::<String>::deserialize(deserializer)?.map(|value| hex_array(&value)).transpose()
+    }
+
+    pub fn admin_key<'de, D: Deserializer<'de>>(deserializer: D) -> Result<AdminKey, D::Error> {
+        super::parse_admin_key(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    pub fn admin_keys<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<AdminKey>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?.iter()
+            .map(|value| super::parse_admin_key(value).map_err(D::Error::custom))
+            .collect()
+    }
+
+    pub fn option_admin_key<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<AdminKey>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| super::parse_admin_key(&value).map_err(D::Error::custom))
+            .transpose()
+    }
+}
@@ -0,0 +1,62 @@
+//! Real-time events of UpgradeAdmin over the WebSocket RPC: logs of transactions mentioning the admin account
+//! are decoded into `UpgradeEvent`s, so monitoring services can alert on unexpected upgrades or authority changes.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+use solana_program::pubkey::Pubkey;
+use solana_pubsub_client::pubsub_client::{LogsSubscription, PubsubClient};
+use solana_rpc_client_api::config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use upgrade::events::UpgradeEvent;
+use crate::{parse, Result};
+
+/// Event of UpgradeAdmin with the transaction that emitted it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdminEvent {
+    pub signature: Signature,
+    pub slot: u64,
+    pub event: UpgradeEvent,
+}
+
+/// Blocking stream of events of a single UpgradeAdmin, ends when the WebSocket connection is closed.
+/// Events of failed transactions are skipped, they did not change the admin. Dropping the subscription unsubscribes.
+pub struct AdminEventSubscription {
+    subscription: LogsSubscription,
+    admin: Pubkey,
+    pending: VecDeque<AdminEvent>,
+}
+
+impl Iterator for AdminEventSubscription {
+    type Item = AdminEvent;
+
+    fn next(&mut self) -> Option<AdminEvent> {
+        while self.pending.is_empty() {
+            let response = self.subscription.1.recv().ok()?;
+            if response.value.err.is_some() {
+                continue;
+            }
+
+            let Ok(signature) = Signature::from_str(&response.value.signature) else {
+                continue;
+            };
+            self.pending.extend(parse::parse_events(&response.value.logs)
+                .into_iter()
+                .filter(|event| *event.admin() == self.admin)
+                .map(|event| AdminEvent { signature, slot: response.context.slot, event }));
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+/// Subscribes to confirmed events of UpgradeAdmin at `admin` through the WebSocket RPC at `rpc_ws`.
+pub fn subscribe_admin_events(rpc_ws: &str, admin: &Pubkey) -> Result<AdminEventSubscription> {
+    let subscription = PubsubClient::logs_subscribe(
+        rpc_ws,
+        RpcTransactionLogsFilter::Mentions(vec![admin.to_string()]),
+        RpcTransactionLogsConfig { commitment: Some(CommitmentConfig::confirmed()) },
+    )?;
+
+    Ok(AdminEventSubscription { subscription, admin: *admin, pending: VecDeque::new() })
+}
@@ -0,0 +1,196 @@
+//! Password-encrypted secp256k1 keyfiles in Web3 Secret Storage (Ethereum keystore v3) format with PBKDF2-HMAC-SHA256
+//! key derivation and AES-128-CTR encryption, readable by Ethereum wallets. Keystores with scrypt derivation are rejected.
+
+use std::path::Path;
+use aes::Aes128;
+use ctr::cipher::{NewCipher, StreamCipher};
+use hmac::Hmac;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use solana_program::keccak;
+use thiserror::Error;
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+pub const KEYSTORE_VERSION: u32 = 3;
+pub const CIPHER: &str = "aes-128-ctr";
+pub const KDF: &str = "pbkdf2";
+pub const PRF: &str = "hmac-sha256";
+/// PBKDF2 iterations of new keystores, the value used by geth
+pub const DEFAULT_ITERATIONS: u32 = 262_144;
+
+const DERIVED_KEY_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 32;
+const IV_LENGTH: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid keystore: {0}")]
+    InvalidFormat(String),
+    #[error("unsupported keystore: {0}")]
+    Unsupported(String),
+    #[error("invalid keystore password")]
+    InvalidPassword,
+    #[error("invalid secp256k1 secret key")]
+    InvalidSecretKey,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct KdfParams {
+    pub c: u32,
+    pub dklen: usize,
+    pub prf: String,
+    pub salt: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Crypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: serde_json::Value,
+    pub mac: String,
+}
+
+/// Web3 Secret Storage v3 keyfile.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Keystore {
+    pub crypto: Crypto,
+    pub id: String,
+    pub version: u32,
+    /// Hex of Ethereum address of the key, without 0x prefix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+/// Ethereum address of the secp256k1 secret key, the form UpgradeAdmin stores it in as `AdminKey::EthAddress`.
+pub fn eth_address(secret: &[u8; 32]) -> Result<[u8; 20], KeystoreError> {
+    let secret_key = libsecp256k1::SecretKey::parse(secret).map_err(|_| KeystoreError::InvalidSecretKey)?;
+    let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize();
+    Ok(keccak::hash(&public_key[1..]).to_bytes()[12..].try_into().unwrap())
+}
+
+/// Random secp256k1 secret key.
+pub fn generate_secret() -> [u8; 32] {
+    libsecp256k1::SecretKey::random(&mut rand::rngs::OsRng).serialize()
+}
+
+fn derive_key(password: &str, params: &KdfParams) -> Result<Vec<u8>, KeystoreError> {
+    if params.prf != PRF {
+        return Err(KeystoreError::Unsupported(format!("prf {}", params.prf)));
+    }
+    if params.dklen != DERIVED_KEY_LENGTH {
+        return Err(KeystoreError::Unsupported(format!("dklen {}", params.dklen)));
+    }
+
+    let salt = decode_hex(&params.salt)?;
+    let mut key = vec![0; params.dklen];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), &salt, params.c, &mut key);
+    Ok(key)
+}
+
+fn mac(derived_key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    keccak::hashv(&[&derived_key[16..32], ciphertext]).to_bytes()
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, KeystoreError> {
+    hex::decode(value.trim_start_matches("0x")).map_err(|e| KeystoreError::InvalidFormat(e.to_string()))
+}
+
+/// Random UUID v4 used as the keystore id.
+fn uuid() -> String {
+    let mut bytes = [0u8; 16];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Encrypts the secp256k1 secret key with `password` using `iterations` of PBKDF2.
+pub fn encrypt(secret: &[u8; 32], password: &str, iterations: u32) -> Result<Keystore, KeystoreError> {
+    let address = eth_address(secret)?;
+    let mut salt = [0u8; SALT_LENGTH];
+    let mut iv = [0u8; IV_LENGTH];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    rand::rngs::OsRng.fill_bytes(&mut iv);
+
+    let params = KdfParams { c: iterations, dklen: DERIVED_KEY_LENGTH, prf: PRF.to_string(), salt: hex::encode(salt) };
+    let derived_key = derive_key(password, &params)?;
+    let mut ciphertext = secret.to_vec();
+    Aes128Ctr::new(derived_key[..16].into(), (&iv).into()).apply_keystream(&mut ciphertext);
+
+    Ok(Keystore {
+        crypto: Crypto {
+            cipher: CIPHER.to_string(),
+            cipherparams: CipherParams { iv: hex::encode(iv) },
+            mac: hex::encode(mac(&derived_key, &ciphertext)),
+            ciphertext: hex::encode(ciphertext),
+            kdf: KDF.to_string(),
+            kdfparams: serde_json::to_value(params).map_err(|e| KeystoreError::InvalidFormat(e.to_string()))?,
+        },
+        id: uuid(),
+        version: KEYSTORE_VERSION,
+        address: Some(hex::encode(address)),
+    })
+}
+
+/// Decrypts the secp256k1 secret key, fails on wrong password before decryption by the keystore MAC.
+pub fn decrypt(keystore: &Keystore, password: &str) -> Result<[u8; 32], KeystoreError> {
+    if keystore.version != KEYSTORE_VERSION {
+        return Err(KeystoreError::Unsupported(format!("version {}", keystore.version)));
+    }
+    if keystore.crypto.kdf != KDF {
+        return Err(KeystoreError::Unsupported(format!("kdf {}, only {} is supported", keystore.crypto.kdf, KDF)));
+    }
+    if keystore.crypto.cipher != CIPHER {
+        return Err(KeystoreError::Unsupported(format!("cipher {}", keystore.crypto.cipher)));
+    }
+
+    let params: KdfParams = serde_json::from_value(keystore.crypto.kdfparams.clone())
+        .map_err(|e| KeystoreError::InvalidFormat(e.to_string()))?;
+    let derived_key = derive_key(password, &params)?;
+    let mut secret = decode_hex(&keystore.crypto.ciphertext)?;
+    if decode_hex(&keystore.crypto.mac)? != mac(&derived_key, &secret) {
+        return Err(KeystoreError::InvalidPassword);
+    }
+
+    let iv: [u8; IV_LENGTH] = decode_hex(&keystore.crypto.cipherparams.iv)?
+        .try_into()
+        .map_err(|_| KeystoreError::InvalidFormat("iv should be 16 bytes".to_string()))?;
+    Aes128Ctr::new(derived_key[..16].into(), (&iv).into()).apply_keystream(&mut secret);
+    secret.try_into().map_err(|_| KeystoreError::InvalidSecretKey)
+}
+
+/// Parses keystore JSON, `None` if the content is not a keystore.
+pub fn parse(content: &str) -> Option<Keystore> {
+    serde_json::from_str(content).ok()
+}
+
+/// Reads and decrypts the keystore at `path`.
+pub fn read(path: &Path, password: &str) -> Result<[u8; 32], KeystoreError> {
+    let keystore = parse(&std::fs::read_to_string(path)?)
+        .ok_or_else(|| KeystoreError::InvalidFormat(format!("{} is not a keystore", path.display())))?;
+    decrypt(&keystore, password)
+}
+
+/// Encrypts the secret key into a new keystore at `path`, existing files are not overwritten.
+pub fn write(path: &Path, secret: &[u8; 32], password: &str, iterations: u32) -> Result<Keystore, KeystoreError> {
+    let keystore = encrypt(secret, password, iterations)?;
+    let content = serde_json::to_string_pretty(&keystore).map_err(|e| KeystoreError::InvalidFormat(e.to_string()))?;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, content.as_bytes()))?;
+    Ok(keystore)
+}
@@ -0,0 +1,423 @@
+//! RPC helpers of the upgrade program: PDA derivation, UpgradeAdmin decoding and sending of signed operations
+//! with the nonce fetched from the chain.
+
+use solana_program::bpf_loader_upgradeable::UpgradeableLoaderState;
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_pubsub_client::pubsub_client::PubsubClientError;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::client_error::Error as RpcError;
+use solana_rpc_client_api::config::RpcProgramAccountsConfig;
+use solana_rpc_client_api::filter::RpcFilterType;
+use solana_sdk::signature::{Keypair, Signature, Signer, SignerError};
+use thiserror::Error;
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::instructions::{self, InitializeAdminArgs};
+use upgrade::merkle::SignerProof;
+use upgrade::pda;
+use upgrade::state::{AdminKey, PendingUpgrade, UpgradeAdmin, UpgradeHistory, VersionedUpgradeAdmin, MAX_ADMIN_SIZE, MAX_PENDING_UPGRADE_SIZE, MAX_UPGRADE_HISTORY_SIZE, V1_ADMIN_SIZE};
+use borsh::BorshDeserialize;
+use diagnose::Diagnosis;
+use transaction::TransactionConfig;
+
+pub mod coordinator;
+pub mod diagnose;
+mod encoding;
+pub mod events;
+pub mod keystore;
+pub mod lookup_table;
+pub mod parse;
+pub mod signing;
+pub mod snapshot;
+pub mod transaction;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    // Boxed, RPC errors are much larger than the other variants
+    #[error("rpc error: {0}")]
+    Rpc(Box<RpcError>),
+    #[error("pubsub error: {0}")]
+    Pubsub(Box<PubsubClientError>),
+    #[error("upgrade admin {0} is not initialized")]
+    AdminNotInitialized(Pubkey),
+    #[error("upgrade admin {0} has V1 layout and should be migrated")]
+    OutdatedAdmin(Pubkey),
+    #[error("invalid upgrade admin data: {0}")]
+    InvalidAdmin(ProgramError),
+    #[error("account {0} is not a buffer")]
+    InvalidBuffer(Pubkey),
+    #[error("account {0} is not an upgradeable program")]
+    InvalidProgram(Pubkey),
+    #[error("account {0} is not an address lookup table or lacks the looked up address")]
+    InvalidLookupTable(Pubkey),
+    #[error("account {0} is not an initialized nonce account")]
+    InvalidNonceAccount(Pubkey),
+    #[error("invalid transaction message: {0}")]
+    InvalidMessage(String),
+    #[error("signature of {0} does not match the transaction message")]
+    InvalidSignature(Pubkey),
+    #[error("missing signature of {0}")]
+    MissingSigner(Pubkey),
+    // Boxed, diagnosis carries the simulation logs
+    #[error("transaction simulation failed: {0}")]
+    Simulation(Box<Diagnosis>),
+    #[error("signing error: {0}")]
+    Signer(#[from] SignerError),
+}
+
+impl From<RpcError> for ClientError {
+    fn from(e: RpcError) -> Self {
+        ClientError::Rpc(Box::new(e))
+    }
+}
+
+impl From<PubsubClientError> for ClientError {
+    fn from(e: PubsubClientError) -> Self {
+        ClientError::Pubsub(Box::new(e))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// Address of UpgradeAdmin of `contract`.
+pub fn admin_address(program_id: &Pubkey, contract: &Pubkey) -> Pubkey {
+    pda::find_admin_address(program_id, contract).0
+}
+
+/// Decodes UpgradeAdmin account data stored at `address`.
+pub fn decode_upgrade_admin(address: &Pubkey, data: &[u8]) -> Result<UpgradeAdmin> {
+    match VersionedUpgradeAdmin::unpack(data).map_err(ClientError::InvalidAdmin)? {
+        VersionedUpgradeAdmin::V1(_) => Err(ClientError::OutdatedAdmin(*address)),
+        VersionedUpgradeAdmin::V2(upgrade_admin) if upgrade_admin.is_initialized => Ok(upgrade_admin),
+        VersionedUpgradeAdmin::V2(_) => Err(ClientError::AdminNotInitialized(*address)),
+    }
+}
+
+/// Fetches and decodes UpgradeAdmin of `contract`.
+pub fn get_upgrade_admin(rpc: &RpcClient, program_id: &Pubkey, contract: &Pubkey) -> Result<UpgradeAdmin> {
+    let address = admin_address(program_id, contract);
+    let data = rpc.get_account_data(&address)?;
+    decode_upgrade_admin(&address, &data)
+}
+
+/// Accounts of the upgrade program with `size` bytes of data. Every account kind is allocated with its own fixed size,
+/// so the size tells the kinds apart.
+fn get_program_accounts_by_size(rpc: &RpcClient, program_id: &Pubkey, size: usize) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::DataSize(size as u64)]),
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    Ok(rpc.get_program_accounts_with_config(program_id, config)?
+        .into_iter()
+        .map(|(address, account)| (address, account.data))
+        .collect())
+}
+
+/// Every initialized UpgradeAdmin of the program, including admins with V1 layout.
+pub fn get_upgrade_admins(rpc: &RpcClient, program_id: &Pubkey) -> Result<Vec<(Pubkey, VersionedUpgradeAdmin)>> {
+    let mut admins = vec![];
+    for size in [MAX_ADMIN_SIZE, V1_ADMIN_SIZE] {
+        for (address, data) in get_program_accounts_by_size(rpc, program_id, size)? {
+            match VersionedUpgradeAdmin::unpack(&data) {
+                Ok(VersionedUpgradeAdmin::V1(admin)) if admin.is_initialized => admins.push((address, VersionedUpgradeAdmin::V1(admin))),
+                Ok(VersionedUpgradeAdmin::V2(admin)) if admin.is_initialized => admins.push((address, VersionedUpgradeAdmin::V2(admin))),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(admins)
+}
+
+/// Every upgrade proposed by ProposeUpgrade and not executed or cancelled yet.
+pub fn get_pending_upgrades(rpc: &RpcClient, program_id: &Pubkey) -> Result<Vec<(Pubkey, PendingUpgrade)>> {
+    Ok(get_program_accounts_by_size(rpc, program_id, MAX_PENDING_UPGRADE_SIZE)?
+        .into_iter()
+        .filter_map(|(address, data)| PendingUpgrade::deserialize(&mut data.as_slice()).ok().map(|pending| (address, pending)))
+        .filter(|(_, pending)| pending.is_initialized)
+        .collect())
+}
+
+/// Deployment logs of every contract upgraded through the program.
+pub fn get_upgrade_histories(rpc: &RpcClient, program_id: &Pubkey) -> Result<Vec<(Pubkey, UpgradeHistory)>> {
+    Ok(get_program_accounts_by_size(rpc, program_id, MAX_UPGRADE_HISTORY_SIZE)?
+        .into_iter()
+        .filter_map(|(address, data)| UpgradeHistory::deserialize(&mut data.as_slice()).ok().map(|history| (address, history)))
+        .filter(|(_, history)| history.is_initialized)
+        .collect())
+}
+
+/// Program data written to `buffer`, without the buffer metadata.
+pub fn get_buffer_program(rpc: &RpcClient, buffer: &Pubkey) -> Result<Vec<u8>> {
+    let mut data = rpc.get_account_data(buffer)?;
+    let metadata_size = UpgradeableLoaderState::size_of_buffer_metadata();
+    match bincode::deserialize(&data) {
+        Ok(UpgradeableLoaderState::Buffer { .. }) if data.len() >= metadata_size => Ok(data.split_off(metadata_size)),
+        _ => Err(ClientError::InvalidBuffer(*buffer)),
+    }
+}
+
+/// SHA-256 of the program data in `buffer`, the code hash signed for upgrades from it.
+pub fn get_buffer_code_hash(rpc: &RpcClient, buffer: &Pubkey) -> Result<[u8; 32]> {
+    Ok(solana_program::hash::hash(&get_buffer_program(rpc, buffer)?).to_bytes())
+}
+
+/// Currently deployed version of an upgradeable program.
+#[derive(Debug, Clone)]
+pub struct DeployedProgram {
+    pub program_data_address: Pubkey,
+    /// Slot of the last deployment
+    pub slot: u64,
+    pub upgrade_authority: Option<Pubkey>,
+    /// Program data without the ProgramData metadata, zero padded to the allocated size
+    pub data: Vec<u8>,
+}
+
+pub fn get_deployed_program(rpc: &RpcClient, program: &Pubkey) -> Result<DeployedProgram> {
+    let program_data_address = match bincode::deserialize(&rpc.get_account_data(program)?) {
+        Ok(UpgradeableLoaderState::Program { programdata_address }) => programdata_address,
+        _ => return Err(ClientError::InvalidProgram(*program)),
+    };
+
+    let mut data = rpc.get_account_data(&program_data_address)?;
+    let metadata_size = UpgradeableLoaderState::size_of_programdata_metadata();
+    match bincode::deserialize(&data) {
+        Ok(UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address }) if data.len() >= metadata_size => Ok(DeployedProgram {
+            program_data_address,
+            slot,
+            upgrade_authority: upgrade_authority_address,
+            data: data.split_off(metadata_size),
+        }),
+        _ => Err(ClientError::InvalidProgram(*program)),
+    }
+}
+
+/// Sends `instructions` in one transaction paid by `payer` with the latest blockhash and waits for confirmation.
+pub fn send_instructions(rpc: &RpcClient, payer: &Keypair, instructions: &[Instruction]) -> Result<Signature> {
+    let message = transaction::build_message(rpc, &payer.pubkey(), instructions, &TransactionConfig::default())?;
+    transaction::send_message(rpc, &message, &[payer], &[])
+}
+
+/// InitializeAdmin of `args.contract`, `payer` funds the admin and its history accounts.
+pub fn initialize_admin_instruction(program_id: &Pubkey, payer: &Pubkey, args: InitializeAdminArgs) -> Instruction {
+    instructions::initialize_admin(
+        *program_id,
+        args.contract,
+        *payer,
+        args.public_keys,
+        args.weights,
+        args.threshold,
+        args.hashing_mode,
+        args.domain,
+        args.signers_root,
+        args.upgrade_key,
+        args.key_change_delay,
+        args.curve,
+        args.hash_algorithm,
+        args.guardian,
+        args.recovery_delay,
+        args.managed_contracts,
+        args.expires_at_slot,
+        args.executors,
+        args.reimbursement,
+        args.min_delay_slots,
+        args.min_upgrade_interval_slots,
+        args.nonce_width,
+    )
+}
+
+/// Creates UpgradeAdmin of `args.contract`, `payer` funds the admin and its history accounts.
+pub fn send_initialize_admin(rpc: &RpcClient, payer: &Keypair, program_id: &Pubkey, args: InitializeAdminArgs) -> Result<Signature> {
+    let instruction = initialize_admin_instruction(program_id, &payer.pubkey(), args);
+    send_instructions(rpc, payer, &[instruction])
+}
+
+/// Upgrade of `contract` from `buffer` with signatures made for the current upgrade nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn upgrade_instruction(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    contract: &Pubkey,
+    buffer: &Pubkey,
+    spill: &Pubkey,
+    code_hash: [u8; 32],
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Result<Instruction> {
+    let upgrade_admin = get_upgrade_admin(rpc, program_id, contract)?;
+    Ok(instructions::upgrade(
+        *program_id,
+        *contract,
+        *buffer,
+        *spill,
+        upgrade_admin.upgrade_nonce,
+        code_hash,
+        signatures,
+        signer_proofs,
+        expires_at,
+    ))
+}
+
+/// Upgrades `contract` from `buffer` with signatures made for the current upgrade nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn send_upgrade(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    contract: &Pubkey,
+    buffer: &Pubkey,
+    spill: &Pubkey,
+    code_hash: [u8; 32],
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Result<Signature> {
+    let instruction = upgrade_instruction(rpc, program_id, contract, buffer, spill, code_hash, signatures, signer_proofs, expires_at)?;
+    send_instructions(rpc, payer, &[instruction])
+}
+
+/// Signer set change of UpgradeAdmin of `contract` with signatures made for the current key nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn change_public_key_instruction(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    contract: &Pubkey,
+    new_public_keys: Vec<AdminKey>,
+    new_weights: Vec<u8>,
+    new_threshold: u8,
+    new_signers_root: Option<[u8; 32]>,
+    new_upgrade_key: Option<AdminKey>,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Result<Instruction> {
+    let upgrade_admin = get_upgrade_admin(rpc, program_id, contract)?;
+    Ok(instructions::change_public_key(
+        *program_id,
+        *contract,
+        new_public_keys,
+        new_weights,
+        new_threshold,
+        new_signers_root,
+        new_upgrade_key,
+        upgrade_admin.key_nonce,
+        signatures,
+        signer_proofs,
+        expires_at,
+    ))
+}
+
+/// Stages signer set change of UpgradeAdmin of `contract` with signatures made for the current key nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn send_change_public_key(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    contract: &Pubkey,
+    new_public_keys: Vec<AdminKey>,
+    new_weights: Vec<u8>,
+    new_threshold: u8,
+    new_signers_root: Option<[u8; 32]>,
+    new_upgrade_key: Option<AdminKey>,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Result<Signature> {
+    let instruction = change_public_key_instruction(
+        rpc,
+        program_id,
+        contract,
+        new_public_keys,
+        new_weights,
+        new_threshold,
+        new_signers_root,
+        new_upgrade_key,
+        signatures,
+        signer_proofs,
+        expires_at,
+    )?;
+    send_instructions(rpc, payer, &[instruction])
+}
+
+/// Upgrade authority transfer of `contract` to `new_authority` with signatures made for the current authority nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn change_authority_instruction(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    contract: &Pubkey,
+    new_authority: &Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Result<Instruction> {
+    let upgrade_admin = get_upgrade_admin(rpc, program_id, contract)?;
+    Ok(instructions::change_authority(
+        *program_id,
+        *contract,
+        *new_authority,
+        upgrade_admin.authority_nonce,
+        signatures,
+        signer_proofs,
+        expires_at,
+    ))
+}
+
+/// Transfers upgrade authority of `contract` to `new_authority` with signatures made for the current authority nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn send_change_authority(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    contract: &Pubkey,
+    new_authority: &Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Result<Signature> {
+    let instruction = change_authority_instruction(rpc, program_id, contract, new_authority, signatures, signer_proofs, expires_at)?;
+    send_instructions(rpc, payer, &[instruction])
+}
+
+/// Closing of UpgradeAdmin of `contract` with lamports sent to `destination`, signatures are made
+/// for the current authority nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn close_admin_instruction(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    contract: &Pubkey,
+    destination: &Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Result<Instruction> {
+    let upgrade_admin = get_upgrade_admin(rpc, program_id, contract)?;
+    Ok(instructions::close_admin(
+        *program_id,
+        *contract,
+        *destination,
+        upgrade_admin.authority_nonce,
+        signatures,
+        signer_proofs,
+        expires_at,
+    ))
+}
+
+/// Closes UpgradeAdmin of `contract` and sends its lamports to `destination` with signatures made
+/// for the current authority nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn send_close_admin(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    contract: &Pubkey,
+    destination: &Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Result<Signature> {
+    let instruction = close_admin_instruction(rpc, program_id, contract, destination, signatures, signer_proofs, expires_at)?;
+    send_instructions(rpc, payer, &[instruction])
+}
@@ -0,0 +1,105 @@
+//! Address lookup tables for operations touching more accounts than fit in a legacy transaction, such as BatchUpgrade
+//! of several programs. The payer creates a table, extends it with the accounts of the operation and the operation is
+//! sent as a v0 transaction referencing accounts by their index in the table. Addresses added to a table can be
+//! looked up only from the slot after they were added.
+
+use std::thread;
+use std::time::Duration;
+use solana_program::address_lookup_table::instruction::{create_lookup_table, extend_lookup_table};
+use solana_program::address_lookup_table::state::AddressLookupTable;
+use solana_program::address_lookup_table::AddressLookupTableAccount;
+use solana_program::instruction::Instruction;
+use solana_program::message::{Message, VersionedMessage};
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::signature::{Keypair, Signer};
+use crate::{send_instructions, ClientError, Result};
+
+/// Addresses added by one ExtendLookupTable instruction, keeps its transaction within the packet size
+pub const MAX_ADDRESSES_PER_EXTEND: usize = 20;
+
+/// Interval of polling the slot while waiting for addresses to become usable
+const ACTIVATION_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+/// CreateLookupTable of a table owned by `authority` and the address of the table.
+pub fn create_lookup_table_instruction(authority: &Pubkey, payer: &Pubkey, recent_slot: u64) -> (Instruction, Pubkey) {
+    create_lookup_table(*authority, *payer, recent_slot)
+}
+
+/// ExtendLookupTable instructions adding `addresses` to `table`, split to fit in separate transactions.
+pub fn extend_lookup_table_instructions(table: &Pubkey, authority: &Pubkey, payer: &Pubkey, addresses: &[Pubkey]) -> Vec<Instruction> {
+    addresses.chunks(MAX_ADDRESSES_PER_EXTEND)
+        .map(|chunk| extend_lookup_table(*table, *authority, Some(*payer), chunk.to_vec()))
+        .collect()
+}
+
+/// Accounts of `instructions` that can be looked up: every account except signers and invoked programs,
+/// which the runtime requires in the static account keys.
+pub fn lookup_addresses(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut addresses = vec![];
+    for meta in instructions.iter().flat_map(|instruction| &instruction.accounts) {
+        let invoked = instructions.iter().any(|instruction| instruction.program_id == meta.pubkey);
+        if !meta.is_signer && !invoked && !addresses.contains(&meta.pubkey) {
+            addresses.push(meta.pubkey);
+        }
+    }
+    addresses
+}
+
+pub fn get_lookup_table(rpc: &RpcClient, address: &Pubkey) -> Result<AddressLookupTableAccount> {
+    let data = rpc.get_account_data(address)?;
+    let table = AddressLookupTable::deserialize(&data).map_err(|_| ClientError::InvalidLookupTable(*address))?;
+    Ok(AddressLookupTableAccount { key: *address, addresses: table.addresses.to_vec() })
+}
+
+/// Creates a table owned by `payer` holding `addresses` and waits until they can be looked up.
+pub fn send_create_lookup_table(rpc: &RpcClient, payer: &Keypair, addresses: &[Pubkey]) -> Result<AddressLookupTableAccount> {
+    let (instruction, table) = create_lookup_table_instruction(&payer.pubkey(), &payer.pubkey(), rpc.get_slot()?);
+    send_instructions(rpc, payer, &[instruction])?;
+    send_extend_lookup_table(rpc, payer, &table, addresses)
+}
+
+/// Adds `addresses` missing from `table` owned by `payer` and waits until they can be looked up.
+pub fn send_extend_lookup_table(rpc: &RpcClient, payer: &Keypair, table: &Pubkey, addresses: &[Pubkey]) -> Result<AddressLookupTableAccount> {
+    let current = get_lookup_table(rpc, table)?;
+    let missing: Vec<_> = addresses.iter().filter(|address| !current.addresses.contains(address)).copied().collect();
+    for instruction in extend_lookup_table_instructions(table, &payer.pubkey(), &payer.pubkey(), &missing) {
+        send_instructions(rpc, payer, &[instruction])?;
+    }
+
+    let extended_slot = rpc.get_slot()?;
+    while rpc.get_slot()? <= extended_slot {
+        thread::sleep(ACTIVATION_POLL_INTERVAL);
+    }
+    get_lookup_table(rpc, table)
+}
+
+/// `message` with the looked up accounts resolved into the account keys, in the order the runtime loads them:
+/// static keys, then writable and then readonly looked up accounts. Used to explain failures of v0 transactions.
+pub fn resolve_message(message: &VersionedMessage, lookup_tables: &[AddressLookupTableAccount]) -> Result<Message> {
+    let message = match message {
+        VersionedMessage::Legacy(message) => return Ok(message.clone()),
+        VersionedMessage::V0(message) => message,
+    };
+
+    let mut writable = vec![];
+    let mut readonly = vec![];
+    for lookup in &message.address_table_lookups {
+        let table = lookup_tables.iter()
+            .find(|table| table.key == lookup.account_key)
+            .ok_or(ClientError::InvalidLookupTable(lookup.account_key))?;
+        let address = |index: &u8| table.addresses.get(*index as usize).copied().ok_or(ClientError::InvalidLookupTable(table.key));
+        writable.extend(lookup.writable_indexes.iter().map(address).collect::<Result<Vec<_>>>()?);
+        readonly.extend(lookup.readonly_indexes.iter().map(address).collect::<Result<Vec<_>>>()?);
+    }
+
+    let mut account_keys = message.account_keys.clone();
+    account_keys.extend(writable);
+    account_keys.extend(readonly);
+    Ok(Message {
+        header: message.header,
+        account_keys,
+        recent_blockhash: message.recent_blockhash,
+        instructions: message.instructions.clone(),
+    })
+}
@@ -0,0 +1,207 @@
+//! Decoding of UpgradeAdmin accounts and upgrade program instructions into serde-serializable structures
+//! for block explorers and monitoring.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use upgrade::events::UpgradeEvent;
+use upgrade::instructions::UpgradeInstruction;
+use upgrade::state::VersionedUpgradeAdmin;
+use crate::encoding;
+
+/// Account of the instruction with its role.
+#[derive(Serialize, PartialEq, Debug, Clone)]
+pub struct ParsedAccount {
+    // Role of the account from the instruction documentation, `remaining` for unexpected extra accounts
+    pub name: &'static str,
+    #[serde(serialize_with = "encoding::pubkey")]
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// Instruction of the upgrade program. Serialized as `{"name": .., "args": .., "accounts": [..]}`,
+/// instructions without arguments have no `args`.
+#[derive(Serialize, Clone)]
+pub struct ParsedInstruction {
+    #[serde(flatten)]
+    pub instruction: UpgradeInstruction,
+    pub accounts: Vec<ParsedAccount>,
+}
+
+/// Decodes UpgradeAdmin account data of any supported layout.
+pub fn parse_upgrade_admin(data: &[u8]) -> Result<VersionedUpgradeAdmin, ProgramError> {
+    VersionedUpgradeAdmin::unpack(data)
+}
+
+/// Prefix of `sol_log_data` lines in transaction logs
+const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+
+/// Decodes events emitted through `sol_log_data` from transaction logs, log data of other programs is skipped.
+pub fn parse_events(logs: &[String]) -> Vec<UpgradeEvent> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix(PROGRAM_DATA_LOG_PREFIX))
+        .filter_map(|data| {
+            let fields = data.split(' ').map(|field| BASE64.decode(field)).collect::<Result<Vec<_>, _>>().ok()?;
+            match fields.as_slice() {
+                [event] => UpgradeEvent::try_from_slice(event).ok(),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Decodes instruction data and names the instruction accounts.
+pub fn parse_instruction(data: &[u8], accounts: &[AccountMeta]) -> Result<ParsedInstruction, ProgramError> {
+    let instruction = UpgradeInstruction::unpack(data)?;
+    let names = account_names(&instruction, accounts.len());
+    let accounts = accounts.iter()
+        .enumerate()
+        .map(|(i, meta)| ParsedAccount {
+            name: names.get(i).copied().unwrap_or("remaining"),
+            pubkey: meta.pubkey,
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        })
+        .collect();
+
+    Ok(ParsedInstruction { instruction, accounts })
+}
+
+/// Names of `len` accounts of the instruction, expanding repeated account groups.
+fn account_names(instruction: &UpgradeInstruction, len: usize) -> Vec<&'static str> {
+    match instruction {
+        UpgradeInstruction::BatchUpgrade(args) => {
+            let mut names = fixed_account_names(instruction).to_vec();
+            for _ in 0..args.code_hashes.len() {
+                names.extend(["program_data", "program", "buffer", "upgrade_history", "buffer_staging"]);
+            }
+            names.extend(["instructions_sysvar", "executor"]);
+            names
+        }
+        // ProgramData accounts of every removed contract follow the fixed accounts
+        UpgradeInstruction::SetManagedContracts(_) => {
+            let mut names = fixed_account_names(instruction).to_vec();
+            names.resize(len.max(names.len()), "program_data");
+            names
+        }
+        _ => fixed_account_names(instruction).to_vec(),
+    }
+}
+
+/// Accounts expected by the instruction, as documented in `UpgradeInstruction`.
+fn fixed_account_names(instruction: &UpgradeInstruction) -> &'static [&'static str] {
+    match instruction {
+        UpgradeInstruction::InitializeAdmin(_) => &[
+            "upgrade_admin", "payer", "system_program", "rent_sysvar", "admin_history",
+        ],
+        UpgradeInstruction::ChangePublicKey(_) => &[
+            "upgrade_admin", "revoked_keys", "admin_history", "instructions_sysvar",
+        ],
+        UpgradeInstruction::ChangeAuthority(_) => &[
+            "upgrade_admin", "program_data", "new_authority", "bpf_loader_upgradeable", "admin_history",
+            "instructions_sysvar",
+        ],
+        UpgradeInstruction::Upgrade(_) => &[
+            "upgrade_admin", "program_data", "program", "buffer", "spill", "rent_sysvar", "clock_sysvar",
+            "bpf_loader_upgradeable", "upgrade_history", "buffer_staging", "instructions_sysvar", "batch_approval",
+            "session_key", "executor",
+        ],
+        UpgradeInstruction::ActivateKey => &["upgrade_admin", "revoked_keys"],
+        UpgradeInstruction::CancelKeyChange(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::RevokeKeys(_) => &[
+            "upgrade_admin", "revoked_keys", "payer", "system_program", "rent_sysvar", "instructions_sysvar",
+        ],
+        UpgradeInstruction::VerifyBatch(_) => &[
+            "upgrade_admin", "batch_approval", "payer", "system_program", "rent_sysvar", "instructions_sysvar",
+        ],
+        UpgradeInstruction::CreateSession(_) => &[
+            "upgrade_admin", "revoked_keys", "session_key", "payer", "system_program", "rent_sysvar",
+            "instructions_sysvar",
+        ],
+        UpgradeInstruction::StartRecovery(_) => &["upgrade_admin", "revoked_keys", "instructions_sysvar"],
+        UpgradeInstruction::CompleteRecovery => &["upgrade_admin", "revoked_keys"],
+        UpgradeInstruction::CancelRecovery(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::CloseAdmin(_) => &["upgrade_admin", "program_data", "destination", "instructions_sysvar"],
+        UpgradeInstruction::MigrateAdmin => &[
+            "upgrade_admin", "payer", "system_program", "rent_sysvar", "admin_history",
+        ],
+        UpgradeInstruction::Pause(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::Unpause(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::SetManagedContracts(_) => &["upgrade_admin", "instructions_sysvar", "program_data"],
+        UpgradeInstruction::SetMetadata(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::Deactivate(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::Reactivate(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::ExtendExpiry(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::SetExecutors(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::ProposeUpgrade(_) => &[
+            "upgrade_admin", "pending_upgrade", "program", "buffer", "payer", "system_program", "rent_sysvar",
+            "buffer_staging", "instructions_sysvar", "proposer",
+        ],
+        UpgradeInstruction::ExecuteUpgrade => &[
+            "upgrade_admin", "program_data", "program", "buffer", "spill", "rent_sysvar", "clock_sysvar",
+            "bpf_loader_upgradeable", "pending_upgrade", "upgrade_history", "buffer_staging",
+            "executor",
+        ],
+        UpgradeInstruction::SetUpgradeDelay(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::CancelUpgrade(_) => &[
+            "upgrade_admin", "pending_upgrade", "program", "buffer", "destination", "bpf_loader_upgradeable",
+            "instructions_sysvar",
+        ],
+        UpgradeInstruction::SetBufferAuthority => &[
+            "upgrade_admin", "buffer", "buffer_authority", "bpf_loader_upgradeable", "instructions_sysvar",
+        ],
+        UpgradeInstruction::InitUpgradeHistory => &[
+            "upgrade_admin", "upgrade_history", "program", "payer", "system_program", "rent_sysvar",
+        ],
+        UpgradeInstruction::RegisterSnapshot(_) => &[
+            "upgrade_admin", "rollback_snapshot", "program", "buffer", "payer", "system_program", "rent_sysvar",
+            "instructions_sysvar",
+        ],
+        UpgradeInstruction::Rollback(_) => &[
+            "upgrade_admin", "program_data", "program", "buffer", "spill", "rent_sysvar", "clock_sysvar",
+            "bpf_loader_upgradeable", "upgrade_history", "rollback_snapshot", "instructions_sysvar", "executor",
+        ],
+        UpgradeInstruction::SetUpgradeInterval(_) => &["upgrade_admin", "instructions_sysvar"],
+        // Followed by account groups of every upgrade, Instructions sysvar and the executor
+        UpgradeInstruction::BatchUpgrade(_) => &["upgrade_admin", "spill", "rent_sysvar", "clock_sysvar", "bpf_loader_upgradeable"],
+        UpgradeInstruction::SetImmutable(_) => &[
+            "upgrade_admin", "program_data", "bpf_loader_upgradeable", "admin_history", "instructions_sysvar",
+        ],
+        UpgradeInstruction::Deploy(_) => &[
+            "upgrade_admin", "payer", "program_data", "program", "buffer", "rent_sysvar", "clock_sysvar",
+            "system_program", "bpf_loader_upgradeable", "instructions_sysvar",
+        ],
+        UpgradeInstruction::ExtendProgram(_) => &[
+            "upgrade_admin", "program_data", "program", "system_program", "payer", "bpf_loader_upgradeable",
+            "instructions_sysvar",
+        ],
+        UpgradeInstruction::CreateBuffer(_) => &[
+            "upgrade_admin", "program", "buffer", "buffer_staging", "payer", "system_program", "rent_sysvar",
+            "bpf_loader_upgradeable", "instructions_sysvar",
+        ],
+        UpgradeInstruction::WriteBufferChunk(_) => &[
+            "upgrade_admin", "buffer", "buffer_staging", "bpf_loader_upgradeable", "writer",
+        ],
+        UpgradeInstruction::FinalizeBuffer => &["upgrade_admin", "buffer_staging"],
+        UpgradeInstruction::CloseBuffer(_) => &[
+            "upgrade_admin", "buffer", "recipient", "bpf_loader_upgradeable", "buffer_staging", "instructions_sysvar",
+        ],
+        UpgradeInstruction::SetRoles(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::ApproveUpgrade(_) => &[
+            "upgrade_admin", "pending_upgrade", "program", "instructions_sysvar", "approver",
+        ],
+        UpgradeInstruction::SetVetoKey(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::VetoUpgrade(_) => &[
+            "upgrade_admin", "pending_upgrade", "program", "destination", "instructions_sysvar", "veto_key",
+        ],
+        UpgradeInstruction::SetHealthcheck(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::RunHealthcheck => &["upgrade_admin", "program"],
+        UpgradeInstruction::SetVerifiedBuildHash(_) => &["upgrade_admin", "instructions_sysvar"],
+        UpgradeInstruction::ResetBuffer => &["upgrade_admin", "buffer_staging", "writer"],
+    }
+}
@@ -0,0 +1,218 @@
+//! Digests of UpgradeAdmin operations for external signers such as HSMs or TSS coordinators.
+//! Digests are produced by the same `upgrade::hashing` functions the processor verifies signatures against,
+//! only the nonce is taken from the caller instead of the admin account.
+
+use serde::{Deserialize, Serialize};
+use solana_program::keccak::Hash;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use upgrade::hashing::{self, HashAlgorithm, HashingMode};
+use upgrade::state::{AdminKey, Curve, UpgradeAdmin};
+use crate::encoding;
+
+/// Nonce of UpgradeAdmin consumed by the operation.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum NonceKind {
+    Upgrade,
+    Key,
+    Authority,
+}
+
+/// Signed operation and its payload. Contract-scoped operations carry the target contract,
+/// the other ones sign the contract that seeds the admin address.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Operation {
+    Upgrade {
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        contract: Pubkey,
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        buffer: Pubkey,
+        #[serde(serialize_with = "encoding::bytes", deserialize_with = "encoding::de::bytes")]
+        code_hash: [u8; 32],
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        spill: Pubkey,
+    },
+    ProposeUpgrade {
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        contract: Pubkey,
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        buffer: Pubkey,
+        #[serde(serialize_with = "encoding::bytes", deserialize_with = "encoding::de::bytes")]
+        code_hash: [u8; 32],
+        not_before_slot: Option<u64>,
+        not_after_slot: Option<u64>,
+    },
+    CancelUpgrade {
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        contract: Pubkey,
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        buffer: Pubkey,
+        close_buffer: bool,
+    },
+    Rollback {
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        contract: Pubkey,
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        buffer: Pubkey,
+    },
+    ExtendProgram {
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        contract: Pubkey,
+        additional_bytes: u32,
+    },
+    ChangePublicKey {
+        #[serde(serialize_with = "encoding::admin_keys", deserialize_with = "encoding::de::admin_keys")]
+        new_public_keys: Vec<AdminKey>,
+        new_weights: Vec<u8>,
+        new_threshold: u8,
+        #[serde(serialize_with = "encoding::option_bytes", deserialize_with = "encoding::de::option_bytes")]
+        new_signers_root: Option<[u8; 32]>,
+        #[serde(serialize_with = "encoding::option_admin_key", deserialize_with = "encoding::de::option_admin_key")]
+        new_upgrade_key: Option<AdminKey>,
+    },
+    CancelKeyChange,
+    ChangeAuthority {
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        contract: Pubkey,
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        new_authority: Pubkey,
+    },
+    SetImmutable {
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        contract: Pubkey,
+    },
+    CloseAdmin {
+        #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+        destination: Pubkey,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    SetActive {
+        active: bool,
+    },
+}
+
+impl Operation {
+    /// Nonce the processor checks and increments for the operation.
+    pub fn nonce_kind(&self) -> NonceKind {
+        match self {
+            Operation::Upgrade { .. }
+            | Operation::ProposeUpgrade { .. }
+            | Operation::CancelUpgrade { .. }
+            | Operation::Rollback { .. }
+            | Operation::ExtendProgram { .. } => NonceKind::Upgrade,
+            Operation::ChangeAuthority { .. }
+            | Operation::SetImmutable { .. }
+            | Operation::CloseAdmin { .. } => NonceKind::Authority,
+            Operation::ChangePublicKey { .. }
+            | Operation::CancelKeyChange
+            | Operation::SetPaused { .. }
+            | Operation::SetActive { .. } => NonceKind::Key,
+        }
+    }
+
+    /// Contract included in the signed message.
+    pub fn contract<'a>(&'a self, admin: &'a UpgradeAdmin) -> &'a Pubkey {
+        match self {
+            Operation::Upgrade { contract, .. }
+            | Operation::ProposeUpgrade { contract, .. }
+            | Operation::CancelUpgrade { contract, .. }
+            | Operation::Rollback { contract, .. }
+            | Operation::ExtendProgram { contract, .. }
+            | Operation::ChangeAuthority { contract, .. }
+            | Operation::SetImmutable { contract } => contract,
+            _ => &admin.contract,
+        }
+    }
+}
+
+/// Everything an external signer needs to sign the operation and to show it for review.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct SigningEnvelope {
+    #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+    pub program_id: Pubkey,
+    #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+    pub admin: Pubkey,
+    #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+    pub contract: Pubkey,
+    pub operation: Operation,
+    pub nonce_kind: NonceKind,
+    pub nonce: u128,
+    pub expires_at: i64,
+    pub hashing_mode: String,
+    pub hash_algorithm: String,
+    pub curve: String,
+    // Exact 32 byte digest to sign without further hashing
+    #[serde(serialize_with = "encoding::bytes", deserialize_with = "encoding::de::bytes")]
+    pub digest: [u8; 32],
+}
+
+/// Digest the processor verifies signatures of `operation` against when the admin nonce equals `nonce`.
+pub fn digest(program_id: &Pubkey, admin: &UpgradeAdmin, nonce: u128, operation: &Operation, expires_at: i64) -> Result<Hash, ProgramError> {
+    let mut admin = admin.clone();
+    match operation.nonce_kind() {
+        NonceKind::Upgrade => admin.upgrade_nonce = nonce,
+        NonceKind::Key => admin.key_nonce = nonce,
+        NonceKind::Authority => admin.authority_nonce = nonce,
+    }
+
+    let hash = match operation {
+        Operation::Upgrade { contract, buffer, code_hash, spill } =>
+            hashing::upgrade_hash(program_id, &admin, contract, buffer, code_hash, spill, expires_at),
+        Operation::ProposeUpgrade { contract, buffer, code_hash, not_before_slot, not_after_slot } =>
+            hashing::propose_upgrade_hash(program_id, &admin, contract, buffer, code_hash, *not_before_slot, *not_after_slot, expires_at)?,
+        Operation::CancelUpgrade { contract, buffer, close_buffer } =>
+            hashing::cancel_upgrade_hash(program_id, &admin, contract, buffer, *close_buffer, expires_at),
+        Operation::Rollback { contract, buffer } =>
+            hashing::rollback_hash(program_id, &admin, contract, buffer, expires_at),
+        Operation::ExtendProgram { contract, additional_bytes } =>
+            hashing::extend_program_hash(program_id, &admin, contract, *additional_bytes, expires_at),
+        Operation::ChangePublicKey { new_public_keys, new_weights, new_threshold, new_signers_root, new_upgrade_key } =>
+            hashing::change_public_key_hash(program_id, &admin, new_public_keys, new_weights, *new_threshold, *new_signers_root, new_upgrade_key, expires_at),
+        Operation::CancelKeyChange =>
+            hashing::cancel_key_change_hash(program_id, &admin, expires_at),
+        Operation::ChangeAuthority { contract, new_authority } =>
+            hashing::change_authority_hash(program_id, &admin, contract, new_authority, expires_at),
+        Operation::SetImmutable { contract } =>
+            hashing::set_immutable_hash(program_id, &admin, contract, expires_at),
+        Operation::CloseAdmin { destination } =>
+            hashing::close_admin_hash(program_id, &admin, destination, expires_at),
+        Operation::SetPaused { paused } =>
+            hashing::set_paused_hash(program_id, &admin, *paused, expires_at),
+        Operation::SetActive { active } =>
+            hashing::set_active_hash(program_id, &admin, *active, expires_at),
+    };
+
+    Ok(hash)
+}
+
+/// Digest of `operation` wrapped with the context an HSM or TSS coordinator needs to review and sign it.
+pub fn envelope(program_id: &Pubkey, admin: &UpgradeAdmin, nonce: u128, operation: Operation, expires_at: i64) -> Result<SigningEnvelope, ProgramError> {
+    let digest = digest(program_id, admin, nonce, &operation, expires_at)?;
+
+    Ok(SigningEnvelope {
+        program_id: *program_id,
+        admin: crate::admin_address(program_id, &admin.contract),
+        contract: *operation.contract(admin),
+        nonce_kind: operation.nonce_kind(),
+        operation,
+        nonce,
+        expires_at,
+        hashing_mode: match admin.hashing_mode {
+            HashingMode::Legacy => "legacy",
+            HashingMode::Eip712 => "eip712",
+        }.to_string(),
+        hash_algorithm: match admin.hash_algorithm {
+            HashAlgorithm::Keccak => "keccak",
+            HashAlgorithm::Sha256 => "sha256",
+        }.to_string(),
+        curve: match admin.curve {
+            Curve::Secp256k1 => "secp256k1",
+            Curve::Secp256r1 => "secp256r1",
+        }.to_string(),
+        digest: digest.to_bytes(),
+    })
+}
@@ -0,0 +1,153 @@
+//! Snapshots of every account of an upgrade program deployment for disaster recovery. The export records the exact
+//! data of every UpgradeAdmin and PDA owned by the program together with its decoded state for operators to read,
+//! verification fetches the accounts again and reports every account that was closed, changed or created since.
+
+use std::fmt;
+use borsh::BorshDeserialize;
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+use upgrade::state::{
+    AdminHistory, BatchApproval, BufferStaging, PendingUpgrade, RevokedKeys, RollbackSnapshot, SessionKey, UpgradeHistory,
+    VersionedUpgradeAdmin, MAX_ADMIN_HISTORY_SIZE, MAX_ADMIN_SIZE, MAX_BATCH_APPROVAL_SIZE, MAX_BUFFER_STAGING_SIZE,
+    MAX_PENDING_UPGRADE_SIZE, MAX_REVOKED_KEYS_SIZE, MAX_ROLLBACK_SNAPSHOT_SIZE, MAX_SESSION_KEY_SIZE,
+    MAX_UPGRADE_HISTORY_SIZE, V1_ADMIN_SIZE,
+};
+use crate::{encoding, Result};
+
+/// Kind of an account owned by the program.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountKind {
+    UpgradeAdmin,
+    AdminHistory,
+    RevokedKeys,
+    BatchApproval,
+    SessionKey,
+    PendingUpgrade,
+    UpgradeHistory,
+    RollbackSnapshot,
+    BufferStaging,
+    Unknown,
+}
+
+/// Sizes of the accounts allocated by the program, every kind has its own
+const ACCOUNT_SIZES: &[(usize, AccountKind)] = &[
+    (MAX_ADMIN_SIZE, AccountKind::UpgradeAdmin),
+    (V1_ADMIN_SIZE, AccountKind::UpgradeAdmin),
+    (MAX_ADMIN_HISTORY_SIZE, AccountKind::AdminHistory),
+    (MAX_REVOKED_KEYS_SIZE, AccountKind::RevokedKeys),
+    (MAX_BATCH_APPROVAL_SIZE, AccountKind::BatchApproval),
+    (MAX_SESSION_KEY_SIZE, AccountKind::SessionKey),
+    (MAX_PENDING_UPGRADE_SIZE, AccountKind::PendingUpgrade),
+    (MAX_UPGRADE_HISTORY_SIZE, AccountKind::UpgradeHistory),
+    (MAX_ROLLBACK_SNAPSHOT_SIZE, AccountKind::RollbackSnapshot),
+    (MAX_BUFFER_STAGING_SIZE, AccountKind::BufferStaging),
+];
+
+impl AccountKind {
+    pub fn from_size(size: usize) -> Self {
+        ACCOUNT_SIZES.iter().find(|(kind_size, _)| *kind_size == size).map_or(AccountKind::Unknown, |(_, kind)| *kind)
+    }
+
+    /// Decoded account data as JSON, `None` if the data does not decode as this kind.
+    fn decode(self, data: &[u8]) -> Option<serde_json::Value> {
+        match self {
+            AccountKind::UpgradeAdmin => VersionedUpgradeAdmin::unpack(data).ok().and_then(|admin| serde_json::to_value(admin).ok()),
+            AccountKind::AdminHistory => decode_json::<AdminHistory>(data),
+            AccountKind::RevokedKeys => decode_json::<RevokedKeys>(data),
+            AccountKind::BatchApproval => decode_json::<BatchApproval>(data),
+            AccountKind::SessionKey => decode_json::<SessionKey>(data),
+            AccountKind::PendingUpgrade => decode_json::<PendingUpgrade>(data),
+            AccountKind::UpgradeHistory => decode_json::<UpgradeHistory>(data),
+            AccountKind::RollbackSnapshot => decode_json::<RollbackSnapshot>(data),
+            AccountKind::BufferStaging => decode_json::<BufferStaging>(data),
+            AccountKind::Unknown => None,
+        }
+    }
+}
+
+fn decode_json<T: BorshDeserialize + Serialize>(mut data: &[u8]) -> Option<serde_json::Value> {
+    T::deserialize(&mut data).ok().and_then(|state| serde_json::to_value(state).ok())
+}
+
+/// Account of the program as it was at export.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AccountSnapshot {
+    #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+    pub address: Pubkey,
+    pub kind: AccountKind,
+    pub lamports: u64,
+    /// Decoded data for operators to read, verification compares `data`
+    #[serde(default)]
+    pub state: Option<serde_json::Value>,
+    #[serde(serialize_with = "encoding::base64", deserialize_with = "encoding::de::base64")]
+    pub data: Vec<u8>,
+}
+
+/// Every account owned by the program at `slot`.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Snapshot {
+    #[serde(serialize_with = "encoding::pubkey", deserialize_with = "encoding::de::pubkey")]
+    pub program_id: Pubkey,
+    /// Slot the export started at, accounts are fetched at this slot or later
+    pub slot: u64,
+    pub accounts: Vec<AccountSnapshot>,
+}
+
+/// Difference between a snapshot and the accounts on chain.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Mismatch {
+    /// Account was closed or is no longer owned by the program
+    Missing(Pubkey, AccountKind),
+    /// Account data differs from the snapshot
+    Changed(Pubkey, AccountKind),
+    /// Account was created after the snapshot
+    Unexpected(Pubkey, AccountKind),
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::Missing(address, kind) => write!(f, "{:?} {} is missing", kind, address),
+            Mismatch::Changed(address, kind) => write!(f, "{:?} {} changed", kind, address),
+            Mismatch::Unexpected(address, kind) => write!(f, "{:?} {} is not in the snapshot", kind, address),
+        }
+    }
+}
+
+/// Snapshot of every account owned by `program_id`, ordered by address.
+pub fn export(rpc: &RpcClient, program_id: &Pubkey) -> Result<Snapshot> {
+    let slot = rpc.get_slot()?;
+    let mut accounts: Vec<_> = rpc.get_program_accounts(program_id)?
+        .into_iter()
+        .map(|(address, account)| {
+            let kind = AccountKind::from_size(account.data.len());
+            AccountSnapshot { address, kind, lamports: account.lamports, state: kind.decode(&account.data), data: account.data }
+        })
+        .collect();
+    accounts.sort_by_key(|account| account.address);
+
+    Ok(Snapshot { program_id: *program_id, slot, accounts })
+}
+
+/// Accounts on chain that differ from `snapshot`, empty if the state still matches. Lamports are not compared,
+/// anyone can transfer lamports to an account without changing its state.
+pub fn verify(rpc: &RpcClient, snapshot: &Snapshot) -> Result<Vec<Mismatch>> {
+    let current = export(rpc, &snapshot.program_id)?;
+    let mut mismatches = vec![];
+    for account in &snapshot.accounts {
+        match current.accounts.iter().find(|current| current.address == account.address) {
+            None => mismatches.push(Mismatch::Missing(account.address, account.kind)),
+            Some(current) if current.data != account.data => mismatches.push(Mismatch::Changed(account.address, account.kind)),
+            Some(_) => {}
+        }
+    }
+    for account in &current.accounts {
+        if !snapshot.accounts.iter().any(|expected| expected.address == account.address) {
+            mismatches.push(Mismatch::Unexpected(account.address, account.kind));
+        }
+    }
+
+    Ok(mismatches)
+}
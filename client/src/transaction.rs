@@ -0,0 +1,337 @@
+//! Transaction assembly for online and offline signing. With a durable nonce account the message stays valid
+//! until the nonce is advanced, so it can be assembled online, signed on an air-gapped machine and broadcast later.
+//! Compute budget instructions are prepended with the limit measured by simulation and the price chosen by
+//! the priority fee strategy, so upgrades keep landing during congestion. Transactions are sent only after
+//! a clean simulation, failures are reported with a diagnosis instead of a bare error code. Operations with more
+//! accounts than fit in a legacy transaction are sent as v0 transactions looking accounts up in the tables of
+//! `lookup_table`.
+
+use std::fmt;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use solana_program::hash::Hash;
+use solana_program::instruction::Instruction;
+use solana_program::address_lookup_table::AddressLookupTableAccount;
+use solana_program::message::{v0, Message, VersionedMessage};
+use solana_program::nonce::state::{State as NonceState, Versions as NonceVersions};
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcSimulateTransactionConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::VersionedTransaction;
+use crate::{diagnose, lookup_table, ClientError, Result};
+
+/// Durable nonce account whose stored blockhash replaces the recent blockhash of the transaction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Maximum compute units a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Margin added to the units consumed in simulation, state may change before the transaction lands
+pub const DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT: u32 = 10;
+pub const DEFAULT_PRIORITY_FEE_PERCENTILE: u8 = 50;
+/// Cap of the recent priority fee in micro-lamports per compute unit
+pub const DEFAULT_MAX_COMPUTE_UNIT_PRICE: u64 = 100_000;
+
+/// Compute unit limit requested by the transaction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComputeUnitLimit {
+    /// Runtime default of 200k units per instruction, no instruction is added
+    Default,
+    /// Units consumed in simulation increased by `margin_percent`
+    Simulated { margin_percent: u32 },
+    Fixed(u32),
+}
+
+impl Default for ComputeUnitLimit {
+    fn default() -> Self {
+        ComputeUnitLimit::Simulated { margin_percent: DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT }
+    }
+}
+
+/// Compute unit price in micro-lamports paid for priority.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriorityFee {
+    None,
+    Fixed(u64),
+    /// Percentile of prioritization fees paid in recent slots for the writable accounts of the transaction, capped by `max`
+    Recent { percentile: u8, max: u64 },
+}
+
+impl Default for PriorityFee {
+    fn default() -> Self {
+        PriorityFee::Recent { percentile: DEFAULT_PRIORITY_FEE_PERCENTILE, max: DEFAULT_MAX_COMPUTE_UNIT_PRICE }
+    }
+}
+
+/// Options of transaction assembly.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionConfig {
+    pub durable_nonce: Option<DurableNonce>,
+    /// Blockhash to use instead of fetching it, the stored nonce value if `durable_nonce` is set
+    pub blockhash: Option<Hash>,
+    pub compute_unit_limit: ComputeUnitLimit,
+    pub priority_fee: PriorityFee,
+}
+
+/// Signatures made by the available signers in `--sign-only` mode and the signers still missing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignOnly {
+    pub blockhash: Hash,
+    pub message: Message,
+    pub signers: Vec<(Pubkey, Signature)>,
+    pub absent_signers: Vec<Pubkey>,
+}
+
+impl fmt::Display for SignOnly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Blockhash: {}", self.blockhash)?;
+        writeln!(f, "Message: {}", encode_message(&self.message))?;
+        if !self.signers.is_empty() {
+            writeln!(f, "Signers (Pubkey=Signature):")?;
+            for (pubkey, signature) in &self.signers {
+                writeln!(f, "  {}={}", pubkey, signature)?;
+            }
+        }
+        if !self.absent_signers.is_empty() {
+            writeln!(f, "Absent Signers (Pubkey):")?;
+            for pubkey in &self.absent_signers {
+                writeln!(f, "  {}", pubkey)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Blockhash stored in the initialized durable nonce `account`.
+pub fn get_nonce_blockhash(rpc: &RpcClient, account: &Pubkey) -> Result<Hash> {
+    let data = rpc.get_account_data(account)?;
+    match bincode::deserialize::<NonceVersions>(&data).map(|versions| versions.state().clone()) {
+        Ok(NonceState::Initialized(data)) => Ok(data.blockhash()),
+        _ => Err(ClientError::InvalidNonceAccount(*account)),
+    }
+}
+
+/// Percentile of prioritization fees paid in recent slots by transactions writing to `accounts`.
+pub fn get_recent_priority_fee(rpc: &RpcClient, accounts: &[Pubkey], percentile: u8) -> Result<u64> {
+    let mut fees = rpc.get_recent_prioritization_fees(accounts)?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect::<Vec<_>>();
+    if fees.is_empty() {
+        return Ok(0);
+    }
+
+    fees.sort_unstable();
+    Ok(fees[(fees.len() - 1) * percentile.min(100) as usize / 100])
+}
+
+/// Message of `instructions` paid by `payer` with compute budget instructions prepended. With a durable nonce
+/// AdvanceNonceAccount goes first and the stored nonce value is used as the blockhash.
+pub fn build_message(rpc: &RpcClient, payer: &Pubkey, instructions: &[Instruction], config: &TransactionConfig) -> Result<Message> {
+    let compile = |instructions: &[Instruction], blockhash| Ok(VersionedMessage::Legacy(message(payer, instructions, config, blockhash)));
+    let (instructions, blockhash) = with_budget(rpc, payer, instructions, config, &compile)?;
+    Ok(message(payer, &instructions, config, blockhash))
+}
+
+/// v0 message of `instructions` like `build_message`, accounts found in `lookup_tables` are looked up instead of
+/// being listed in the message.
+pub fn build_versioned_message(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    config: &TransactionConfig,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<VersionedMessage> {
+    let compile = |instructions: &[Instruction], blockhash| v0_message(payer, instructions, config, lookup_tables, blockhash);
+    let (instructions, blockhash) = with_budget(rpc, payer, instructions, config, &compile)?;
+    compile(&instructions, blockhash)
+}
+
+/// `instructions` with compute budget instructions prepended and the blockhash of the transaction. `compile` makes
+/// the message simulated to measure the compute unit limit.
+fn with_budget(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    config: &TransactionConfig,
+    compile: &dyn Fn(&[Instruction], Hash) -> Result<VersionedMessage>,
+) -> Result<(Vec<Instruction>, Hash)> {
+    let blockhash = match (config.blockhash, &config.durable_nonce) {
+        (Some(blockhash), _) => blockhash,
+        (None, Some(nonce)) => get_nonce_blockhash(rpc, &nonce.account)?,
+        (None, None) => rpc.get_latest_blockhash()?,
+    };
+
+    let mut budget = vec![];
+    let price = match config.priority_fee {
+        PriorityFee::None => 0,
+        PriorityFee::Fixed(price) => price,
+        PriorityFee::Recent { percentile, max } => get_recent_priority_fee(rpc, &writable_accounts(payer, instructions), percentile)?.min(max),
+    };
+    if price > 0 {
+        budget.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+
+    let limit = match config.compute_unit_limit {
+        ComputeUnitLimit::Default => None,
+        ComputeUnitLimit::Fixed(limit) => Some(limit),
+        ComputeUnitLimit::Simulated { margin_percent } => {
+            let mut simulated = vec![ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT)];
+            simulated.extend_from_slice(&budget);
+            simulated.extend_from_slice(instructions);
+            let units = simulate(rpc, &compile(&simulated, blockhash)?, config.durable_nonce.is_none())?;
+            Some((units.saturating_mul(100 + margin_percent as u64) / 100).min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32)
+        }
+    };
+    if let Some(limit) = limit {
+        budget.insert(0, ComputeBudgetInstruction::set_compute_unit_limit(limit));
+    }
+
+    budget.extend_from_slice(instructions);
+    Ok((budget, blockhash))
+}
+
+fn message(payer: &Pubkey, instructions: &[Instruction], config: &TransactionConfig, blockhash: Hash) -> Message {
+    match &config.durable_nonce {
+        Some(nonce) => {
+            let mut message = Message::new_with_nonce(instructions.to_vec(), Some(payer), &nonce.account, &nonce.authority);
+            message.recent_blockhash = blockhash;
+            message
+        }
+        None => Message::new_with_blockhash(instructions, Some(payer), &blockhash),
+    }
+}
+
+fn v0_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    config: &TransactionConfig,
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedMessage> {
+    let mut all = vec![];
+    if let Some(nonce) = &config.durable_nonce {
+        all.push(system_instruction::advance_nonce_account(&nonce.account, &nonce.authority));
+    }
+    all.extend_from_slice(instructions);
+
+    v0::Message::try_compile(payer, &all, lookup_tables, blockhash)
+        .map(VersionedMessage::V0)
+        .map_err(|e| ClientError::InvalidMessage(e.to_string()))
+}
+
+fn writable_accounts(payer: &Pubkey, instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts = vec![*payer];
+    for meta in instructions.iter().flat_map(|instruction| &instruction.accounts) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts
+}
+
+/// Compute units consumed by `message` in simulation without signature verification.
+fn simulate(rpc: &RpcClient, message: &VersionedMessage, replace_recent_blockhash: bool) -> Result<u64> {
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash,
+        ..RpcSimulateTransactionConfig::default()
+    };
+    let transaction = VersionedTransaction {
+        signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+        message: message.clone(),
+    };
+    simulate_transaction(rpc, &transaction, config, &[])
+}
+
+/// Compute units consumed by `transaction` in simulation, failures are diagnosed.
+fn simulate_transaction(
+    rpc: &RpcClient,
+    transaction: &VersionedTransaction,
+    config: RpcSimulateTransactionConfig,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<u64> {
+    let result = rpc.simulate_transaction_with_config(transaction, config)?.value;
+    match result.err {
+        Some(err) => {
+            let message = lookup_table::resolve_message(&transaction.message, lookup_tables)?;
+            Err(ClientError::Simulation(Box::new(diagnose::diagnose(rpc, &message, err, result.logs.unwrap_or_default()))))
+        }
+        None => Ok(result.units_consumed.unwrap_or(MAX_COMPUTE_UNIT_LIMIT as u64)),
+    }
+}
+
+/// Base64 of the serialized message, the form offline signers receive it in.
+pub fn encode_message(message: &Message) -> String {
+    BASE64.encode(message.serialize())
+}
+
+pub fn decode_message(message: &str) -> Result<Message> {
+    let data = BASE64.decode(message.trim()).map_err(|e| ClientError::InvalidMessage(e.to_string()))?;
+    bincode::deserialize(&data).map_err(|e| ClientError::InvalidMessage(e.to_string()))
+}
+
+/// Signs `message` with the available `signers` required by it without broadcasting.
+pub fn sign_only(message: &Message, signers: &[&dyn Signer]) -> Result<SignOnly> {
+    let data = message.serialize();
+    let mut result = SignOnly {
+        blockhash: message.recent_blockhash,
+        message: message.clone(),
+        signers: vec![],
+        absent_signers: vec![],
+    };
+
+    for pubkey in message.signer_keys() {
+        match signers.iter().find(|signer| signer.pubkey() == *pubkey) {
+            Some(signer) => result.signers.push((*pubkey, signer.try_sign_message(&data)?)),
+            None => result.absent_signers.push(*pubkey),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Signs `message` with the available `signers`, attaches signatures made offline, simulates the signed transaction
+/// and sends it if the simulation succeeds. Fails if a required signature is missing, an offline signature
+/// does not match the message or the simulation fails.
+pub fn send_message(rpc: &RpcClient, message: &Message, signers: &[&dyn Signer], presigned: &[(Pubkey, Signature)]) -> Result<Signature> {
+    send_versioned_message(rpc, &VersionedMessage::Legacy(message.clone()), &[], signers, presigned)
+}
+
+/// `send_message` of a v0 message, `lookup_tables` it was built with resolve accounts of a failed simulation.
+pub fn send_versioned_message(
+    rpc: &RpcClient,
+    message: &VersionedMessage,
+    lookup_tables: &[AddressLookupTableAccount],
+    signers: &[&dyn Signer],
+    presigned: &[(Pubkey, Signature)],
+) -> Result<Signature> {
+    let data = message.serialize();
+    let signer_keys = &message.static_account_keys()[..message.header().num_required_signatures as usize];
+    let mut transaction = VersionedTransaction { signatures: Vec::with_capacity(signer_keys.len()), message: message.clone() };
+
+    for pubkey in signer_keys {
+        transaction.signatures.push(match signers.iter().find(|signer| signer.pubkey() == *pubkey) {
+            Some(signer) => signer.try_sign_message(&data)?,
+            None => match presigned.iter().find(|(signer, _)| signer == pubkey) {
+                Some((_, signature)) if signature.verify(pubkey.as_ref(), &data) => *signature,
+                Some(_) => return Err(ClientError::InvalidSignature(*pubkey)),
+                None => return Err(ClientError::MissingSigner(*pubkey)),
+            },
+        });
+    }
+
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: true,
+        commitment: Some(rpc.commitment()),
+        ..RpcSimulateTransactionConfig::default()
+    };
+    simulate_transaction(rpc, &transaction, config, lookup_tables)?;
+    Ok(rpc.send_and_confirm_transaction(&transaction)?)
+}
@@ -0,0 +1,56 @@
+//! Generator of TypeScript bindings of the upgrade program. Borsh types are read from the program sources, so the
+//! web client encodes instruction data and decodes accounts byte for byte like `instructions.rs` and `state.rs`.
+
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use clap::Parser;
+
+mod model;
+mod typescript;
+
+/// Program modules with Borsh types, in the order they are emitted
+const MODULES: &[&str] = &["hashing", "sha256", "state", "ecdsa", "merkle", "instructions"];
+
+#[derive(Parser)]
+#[command(name = "upgrade-codegen", about = "Generate TypeScript bindings of the upgrade program types")]
+struct Cli {
+    /// Path of the generated TypeScript module
+    #[arg(long, default_value = "js/upgrade.ts")]
+    out: PathBuf,
+    /// Sources of the upgrade program
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../programs/upgrade-program/src"))]
+    program_src: PathBuf,
+    /// Fail if the module at `--out` differs from the generated one instead of writing it
+    #[arg(long)]
+    check: bool,
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let program = model::parse(&cli.program_src, MODULES)?;
+    let bindings = typescript::generate(&program)?;
+
+    if cli.check {
+        let current = fs::read_to_string(&cli.out).unwrap_or_default();
+        if current != bindings {
+            return Err(format!("{} is out of date, regenerate it with upgrade-codegen", cli.out.display()).into());
+        }
+        return Ok(());
+    }
+
+    if let Some(parent) = cli.out.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&cli.out, bindings)?;
+    println!("{} types written to {}", program.types.len(), cli.out.display());
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
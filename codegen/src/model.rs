@@ -0,0 +1,283 @@
+//! Borsh types and integer constants of the program sources. Only the layout that Borsh serializes is kept:
+//! field order, variant order and types with array lengths resolved.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use syn::{Attribute, BinOp, Expr, Fields as SynFields, GenericArgument, Item, Lit, Meta, PathArguments, Type as SynType, Visibility};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Primitive {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Primitive(Primitive),
+    Pubkey,
+    String,
+    Array(Box<Type>, usize),
+    Vec(Box<Type>),
+    Option(Box<Type>),
+    Tuple(Vec<Type>),
+    // Borsh struct or enum of the program
+    Named(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+}
+
+#[derive(Debug, Clone)]
+pub enum Fields {
+    Unit,
+    Named(Vec<Field>),
+    Unnamed(Vec<Type>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub name: String,
+    pub doc: Vec<String>,
+    pub fields: Fields,
+}
+
+#[derive(Debug, Clone)]
+pub enum Definition {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeDefinition {
+    pub name: String,
+    pub doc: Vec<String>,
+    pub definition: Definition,
+}
+
+#[derive(Debug, Clone)]
+pub struct Constant {
+    pub name: String,
+    pub value: u128,
+}
+
+/// Types and constants of the program modules in source order.
+#[derive(Debug, Default)]
+pub struct Program {
+    pub types: Vec<TypeDefinition>,
+    pub constants: Vec<Constant>,
+}
+
+impl Program {
+    pub fn definition(&self, name: &str) -> Option<&TypeDefinition> {
+        self.types.iter().find(|definition| definition.name == name)
+    }
+}
+
+/// Constants of `solana_program` used in array lengths of the program types.
+fn external_constants() -> HashMap<String, u128> {
+    use solana_program::secp256k1_recover::{SECP256K1_PUBLIC_KEY_LENGTH, SECP256K1_SIGNATURE_LENGTH};
+
+    HashMap::from([
+        ("SECP256K1_PUBLIC_KEY_LENGTH".to_string(), SECP256K1_PUBLIC_KEY_LENGTH as u128),
+        ("SECP256K1_SIGNATURE_LENGTH".to_string(), SECP256K1_SIGNATURE_LENGTH as u128),
+    ])
+}
+
+/// Parses `modules` of the program sources at `src`.
+pub fn parse(src: &Path, modules: &[&str]) -> Result<Program, Box<dyn Error>> {
+    let mut files = vec![];
+    for module in modules {
+        let path = src.join(format!("{}.rs", module));
+        let content = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        files.push(syn::parse_file(&content).map_err(|e| format!("{}: {}", path.display(), e))?);
+    }
+
+    // Constants can refer to constants of other modules, they are evaluated until no more can be resolved
+    let mut values = external_constants();
+    let mut constants = vec![];
+    let mut pending = files.iter()
+        .flat_map(|file| file.items.iter())
+        .filter_map(|item| match item {
+            Item::Const(constant) if matches!(constant.vis, Visibility::Public(_)) => Some(constant),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    loop {
+        let count = pending.len();
+        pending.retain(|constant| match evaluate(&constant.expr, &values) {
+            Some(value) => {
+                values.insert(constant.ident.to_string(), value);
+                constants.push(Constant { name: constant.ident.to_string(), value });
+                false
+            }
+            None => true,
+        });
+        if pending.len() == count {
+            break;
+        }
+    }
+
+    let mut types = vec![];
+    for item in files.iter().flat_map(|file| file.items.iter()) {
+        let (name, attrs, definition) = match item {
+            Item::Struct(item) if is_borsh(&item.attrs) => (item.ident.to_string(), &item.attrs, Definition::Struct(fields(&item.fields, &values)?)),
+            Item::Enum(item) if is_borsh(&item.attrs) => {
+                let mut variants = vec![];
+                for variant in &item.variants {
+                    if variant.discriminant.is_some() {
+                        return Err(format!("explicit discriminant of {}::{} is not supported", item.ident, variant.ident).into());
+                    }
+                    variants.push(Variant { name: variant.ident.to_string(), doc: doc(&variant.attrs), fields: fields(&variant.fields, &values)? });
+                }
+                (item.ident.to_string(), &item.attrs, Definition::Enum(variants))
+            }
+            _ => continue,
+        };
+        types.push(TypeDefinition { name, doc: doc(attrs), definition });
+    }
+
+    let program = Program { types, constants };
+    check_references(&program)?;
+    Ok(program)
+}
+
+fn is_borsh(attrs: &[Attribute]) -> bool {
+    attrs.iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .any(|attr| {
+            let mut borsh = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                borsh |= meta.path.is_ident("BorshSerialize");
+                Ok(())
+            });
+            borsh
+        })
+}
+
+fn doc(attrs: &[Attribute]) -> Vec<String> {
+    attrs.iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(line) => Some(line.value().strip_prefix(' ').map(str::to_string).unwrap_or_else(|| line.value())),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn fields(fields: &SynFields, constants: &HashMap<String, u128>) -> Result<Fields, Box<dyn Error>> {
+    Ok(match fields {
+        SynFields::Unit => Fields::Unit,
+        SynFields::Named(named) => Fields::Named(named.named.iter()
+            .map(|field| Ok(Field { name: field.ident.as_ref().unwrap().to_string(), ty: resolve(&field.ty, constants)? }))
+            .collect::<Result<_, Box<dyn Error>>>()?),
+        SynFields::Unnamed(unnamed) => Fields::Unnamed(unnamed.unnamed.iter()
+            .map(|field| resolve(&field.ty, constants))
+            .collect::<Result<_, _>>()?),
+    })
+}
+
+fn resolve(ty: &SynType, constants: &HashMap<String, u128>) -> Result<Type, Box<dyn Error>> {
+    match ty {
+        SynType::Array(array) => {
+            let length = evaluate(&array.len, constants).ok_or("array length can not be evaluated")?;
+            Ok(Type::Array(Box::new(resolve(&array.elem, constants)?), length as usize))
+        }
+        SynType::Tuple(tuple) => Ok(Type::Tuple(tuple.elems.iter().map(|elem| resolve(elem, constants)).collect::<Result<_, _>>()?)),
+        SynType::Path(path) => {
+            let segment = path.path.segments.last().ok_or("empty type path")?;
+            let argument = || match &segment.arguments {
+                PathArguments::AngleBracketed(arguments) => match arguments.args.first() {
+                    Some(GenericArgument::Type(argument)) => resolve(argument, constants),
+                    _ => Err(format!("unsupported arguments of {}", segment.ident).into()),
+                },
+                _ => Err(format!("{} without type argument", segment.ident).into()),
+            };
+
+            Ok(match segment.ident.to_string().as_str() {
+                "u8" => Type::Primitive(Primitive::U8),
+                "u16" => Type::Primitive(Primitive::U16),
+                "u32" => Type::Primitive(Primitive::U32),
+                "u64" => Type::Primitive(Primitive::U64),
+                "u128" => Type::Primitive(Primitive::U128),
+                "i8" => Type::Primitive(Primitive::I8),
+                "i16" => Type::Primitive(Primitive::I16),
+                "i32" => Type::Primitive(Primitive::I32),
+                "i64" => Type::Primitive(Primitive::I64),
+                "i128" => Type::Primitive(Primitive::I128),
+                "bool" => Type::Primitive(Primitive::Bool),
+                "Pubkey" => Type::Pubkey,
+                "String" => Type::String,
+                "Vec" => Type::Vec(Box::new(argument()?)),
+                "Option" => Type::Option(Box::new(argument()?)),
+                name => Type::Named(name.to_string()),
+            })
+        }
+        _ => Err("unsupported type".into()),
+    }
+}
+
+fn evaluate(expr: &Expr, constants: &HashMap<String, u128>) -> Option<u128> {
+    match expr {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Int(int) => int.base10_parse().ok(),
+            _ => None,
+        },
+        Expr::Path(path) => constants.get(&path.path.segments.last()?.ident.to_string()).copied(),
+        Expr::Paren(paren) => evaluate(&paren.expr, constants),
+        Expr::Binary(binary) => {
+            let left = evaluate(&binary.left, constants)?;
+            let right = evaluate(&binary.right, constants)?;
+            match binary.op {
+                BinOp::Add(_) => left.checked_add(right),
+                BinOp::Sub(_) => left.checked_sub(right),
+                BinOp::Mul(_) => left.checked_mul(right),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn check_references(program: &Program) -> Result<(), Box<dyn Error>> {
+    fn check(ty: &Type, owner: &str, program: &Program) -> Result<(), Box<dyn Error>> {
+        match ty {
+            Type::Array(elem, _) | Type::Vec(elem) | Type::Option(elem) => check(elem, owner, program),
+            Type::Tuple(elems) => elems.iter().try_for_each(|elem| check(elem, owner, program)),
+            Type::Named(name) if program.definition(name).is_none() => Err(format!("{} of {} is not a Borsh type of the program", name, owner).into()),
+            _ => Ok(()),
+        }
+    }
+
+    let check_fields = |fields: &Fields, owner: &str| match fields {
+        Fields::Unit => Ok(()),
+        Fields::Named(named) => named.iter().try_for_each(|field| check(&field.ty, owner, program)),
+        Fields::Unnamed(unnamed) => unnamed.iter().try_for_each(|ty| check(ty, owner, program)),
+    };
+    for definition in &program.types {
+        match &definition.definition {
+            Definition::Struct(fields) => check_fields(fields, &definition.name)?,
+            Definition::Enum(variants) => variants.iter().try_for_each(|variant| check_fields(&variant.fields, &definition.name))?,
+        }
+    }
+
+    Ok(())
+}
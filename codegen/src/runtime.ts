@@ -0,0 +1,236 @@
+/** Borsh encoder: little-endian integers, u32 length prefixes of vectors and strings, 0/1 tags of options. */
+export class BorshWriter {
+    private buffer = new Uint8Array(256);
+    private length = 0;
+
+    private reserve(size: number): DataView {
+        if (this.length + size > this.buffer.length) {
+            const buffer = new Uint8Array(Math.max(this.buffer.length * 2, this.length + size));
+            buffer.set(this.buffer.subarray(0, this.length));
+            this.buffer = buffer;
+        }
+        const view = new DataView(this.buffer.buffer, this.length, size);
+        this.length += size;
+        return view;
+    }
+
+    u8(value: number): void {
+        this.reserve(1).setUint8(0, value);
+    }
+
+    u16(value: number): void {
+        this.reserve(2).setUint16(0, value, true);
+    }
+
+    u32(value: number): void {
+        this.reserve(4).setUint32(0, value, true);
+    }
+
+    u64(value: bigint): void {
+        this.reserve(8).setBigUint64(0, BigInt.asUintN(64, value), true);
+    }
+
+    u128(value: bigint): void {
+        const view = this.reserve(16);
+        const unsigned = BigInt.asUintN(128, value);
+        view.setBigUint64(0, BigInt.asUintN(64, unsigned), true);
+        view.setBigUint64(8, unsigned >> BigInt(64), true);
+    }
+
+    i8(value: number): void {
+        this.reserve(1).setInt8(0, value);
+    }
+
+    i16(value: number): void {
+        this.reserve(2).setInt16(0, value, true);
+    }
+
+    i32(value: number): void {
+        this.reserve(4).setInt32(0, value, true);
+    }
+
+    i64(value: bigint): void {
+        this.reserve(8).setBigInt64(0, BigInt.asIntN(64, value), true);
+    }
+
+    i128(value: bigint): void {
+        this.u128(value);
+    }
+
+    bool(value: boolean): void {
+        this.u8(value ? 1 : 0);
+    }
+
+    fixedBytes(value: Uint8Array, length: number): void {
+        if (value.length !== length) {
+            throw new Error(`expected ${length} bytes, got ${value.length}`);
+        }
+        const view = this.reserve(length);
+        new Uint8Array(view.buffer, view.byteOffset, length).set(value);
+    }
+
+    bytes(value: Uint8Array): void {
+        this.u32(value.length);
+        this.fixedBytes(value, value.length);
+    }
+
+    string(value: string): void {
+        this.bytes(new TextEncoder().encode(value));
+    }
+
+    pubkey(value: PublicKey): void {
+        this.fixedBytes(value.toBytes(), 32);
+    }
+
+    vec<T>(values: T[], encode: (value: T) => void): void {
+        this.u32(values.length);
+        values.forEach(encode);
+    }
+
+    fixedArray<T>(values: T[], length: number, encode: (value: T) => void): void {
+        if (values.length !== length) {
+            throw new Error(`expected ${length} items, got ${values.length}`);
+        }
+        values.forEach(encode);
+    }
+
+    option<T>(value: T | null, encode: (value: T) => void): void {
+        if (value === null) {
+            this.u8(0);
+        } else {
+            this.u8(1);
+            encode(value);
+        }
+    }
+
+    toBytes(): Uint8Array {
+        return this.buffer.slice(0, this.length);
+    }
+}
+
+/** Borsh decoder, fails on truncated data and on invalid bool and option tags. */
+export class BorshReader {
+    private offset = 0;
+
+    constructor(private readonly data: Uint8Array) {}
+
+    private view(size: number): DataView {
+        if (this.offset + size > this.data.length) {
+            throw new Error(`unexpected end of data at offset ${this.offset}`);
+        }
+        const view = new DataView(this.data.buffer, this.data.byteOffset + this.offset, size);
+        this.offset += size;
+        return view;
+    }
+
+    u8(): number {
+        return this.view(1).getUint8(0);
+    }
+
+    u16(): number {
+        return this.view(2).getUint16(0, true);
+    }
+
+    u32(): number {
+        return this.view(4).getUint32(0, true);
+    }
+
+    u64(): bigint {
+        return this.view(8).getBigUint64(0, true);
+    }
+
+    u128(): bigint {
+        const view = this.view(16);
+        return view.getBigUint64(0, true) + (view.getBigUint64(8, true) << BigInt(64));
+    }
+
+    i8(): number {
+        return this.view(1).getInt8(0);
+    }
+
+    i16(): number {
+        return this.view(2).getInt16(0, true);
+    }
+
+    i32(): number {
+        return this.view(4).getInt32(0, true);
+    }
+
+    i64(): bigint {
+        return this.view(8).getBigInt64(0, true);
+    }
+
+    i128(): bigint {
+        return BigInt.asIntN(128, this.u128());
+    }
+
+    bool(): boolean {
+        const value = this.u8();
+        if (value > 1) {
+            throw new Error(`invalid bool ${value}`);
+        }
+        return value === 1;
+    }
+
+    fixedBytes(length: number): Uint8Array {
+        const view = this.view(length);
+        return new Uint8Array(view.buffer, view.byteOffset, length).slice();
+    }
+
+    bytes(): Uint8Array {
+        return this.fixedBytes(this.u32());
+    }
+
+    string(): string {
+        return new TextDecoder('utf-8', { fatal: true }).decode(this.bytes());
+    }
+
+    pubkey(): PublicKey {
+        return new PublicKey(this.fixedBytes(32));
+    }
+
+    vec<T>(decode: () => T): T[] {
+        const length = this.u32();
+        const values: T[] = [];
+        for (let i = 0; i < length; i++) {
+            values.push(decode());
+        }
+        return values;
+    }
+
+    fixedArray<T>(length: number, decode: () => T): T[] {
+        const values: T[] = [];
+        for (let i = 0; i < length; i++) {
+            values.push(decode());
+        }
+        return values;
+    }
+
+    option<T>(decode: () => T): T | null {
+        const tag = this.u8();
+        if (tag > 1) {
+            throw new Error(`invalid option tag ${tag}`);
+        }
+        return tag === 1 ? decode() : null;
+    }
+
+    /** Number of bytes not read yet. */
+    remaining(): number {
+        return this.data.length - this.offset;
+    }
+}
+
+/** Borsh bytes of `value`. */
+export function serialize<T>(value: T, encode: (writer: BorshWriter, value: T) => void): Uint8Array {
+    const writer = new BorshWriter();
+    encode(writer, value);
+    return writer.toBytes();
+}
+
+/**
+ * Value decoded from the beginning of `data`. Trailing bytes are allowed like in `BorshDeserialize::deserialize`,
+ * accounts are allocated for the largest layout.
+ */
+export function deserialize<T>(data: Uint8Array, decode: (reader: BorshReader) => T): T {
+    return decode(new BorshReader(data));
+}
@@ -0,0 +1,300 @@
+//! TypeScript module of the program types: an interface or union type, an encoder and a decoder per Borsh type,
+//! integer constants and the versioned instruction envelope of `UpgradeInstruction::pack`.
+
+use std::error::Error;
+use std::fmt::Write;
+use crate::model::{Definition, Fields, Primitive, Program, Type, TypeDefinition, Variant};
+
+const HEADER: &str = "\
+// Generated by upgrade-codegen from the Borsh types of the upgrade program, do not edit.
+// Regenerate with `npm run codegen:upgrade-program`.
+
+import { PublicKey } from '@solana/web3.js';
+";
+
+const RUNTIME: &str = include_str!("runtime.ts");
+
+/// Enum whose instruction data is prefixed with INSTRUCTION_VERSION by `pack`
+const INSTRUCTION: &str = "UpgradeInstruction";
+
+pub fn generate(program: &Program) -> Result<String, Box<dyn Error>> {
+    let mut out = String::new();
+    writeln!(out, "{}", HEADER)?;
+    writeln!(out, "{}", RUNTIME)?;
+
+    for constant in &program.constants {
+        if constant.value > (1 << 53) {
+            return Err(format!("{} does not fit into a number", constant.name).into());
+        }
+        writeln!(out, "export const {} = {};", constant.name, constant.value)?;
+    }
+
+    for definition in &program.types {
+        writeln!(out)?;
+        match &definition.definition {
+            Definition::Struct(fields) => struct_definition(&mut out, definition, fields)?,
+            Definition::Enum(variants) if variants.iter().all(|variant| matches!(variant.fields, Fields::Unit)) =>
+                unit_enum_definition(&mut out, definition, variants)?,
+            Definition::Enum(variants) => enum_definition(&mut out, definition, variants)?,
+        }
+    }
+
+    if program.definition(INSTRUCTION).is_some() {
+        writeln!(out)?;
+        instruction_envelope(&mut out)?;
+    }
+
+    Ok(out)
+}
+
+fn primitive(primitive: Primitive) -> (&'static str, &'static str) {
+    match primitive {
+        Primitive::U8 => ("u8", "number"),
+        Primitive::U16 => ("u16", "number"),
+        Primitive::U32 => ("u32", "number"),
+        Primitive::U64 => ("u64", "bigint"),
+        Primitive::U128 => ("u128", "bigint"),
+        Primitive::I8 => ("i8", "number"),
+        Primitive::I16 => ("i16", "number"),
+        Primitive::I32 => ("i32", "number"),
+        Primitive::I64 => ("i64", "bigint"),
+        Primitive::I128 => ("i128", "bigint"),
+        Primitive::Bool => ("bool", "boolean"),
+    }
+}
+
+fn ts_type(ty: &Type) -> String {
+    match ty {
+        Type::Primitive(p) => primitive(*p).1.to_string(),
+        Type::Pubkey => "PublicKey".to_string(),
+        Type::String => "string".to_string(),
+        Type::Array(elem, _) | Type::Vec(elem) if **elem == Type::Primitive(Primitive::U8) => "Uint8Array".to_string(),
+        Type::Array(elem, _) | Type::Vec(elem) => match **elem {
+            Type::Option(_) => format!("({})[]", ts_type(elem)),
+            _ => format!("{}[]", ts_type(elem)),
+        },
+        Type::Option(elem) => format!("{} | null", ts_type(elem)),
+        Type::Tuple(elems) => format!("[{}]", elems.iter().map(ts_type).collect::<Vec<_>>().join(", ")),
+        Type::Named(name) => name.clone(),
+    }
+}
+
+/// Statements writing `expr` of type `ty`. Closure parameters are numbered by `depth` to avoid shadowing.
+fn encode(ty: &Type, expr: &str, depth: usize) -> String {
+    let item = format!("item{}", depth);
+    match ty {
+        Type::Primitive(p) => format!("writer.{}({});", primitive(*p).0, expr),
+        Type::Pubkey => format!("writer.pubkey({});", expr),
+        Type::String => format!("writer.string({});", expr),
+        Type::Array(elem, length) if **elem == Type::Primitive(Primitive::U8) => format!("writer.fixedBytes({}, {});", expr, length),
+        Type::Vec(elem) if **elem == Type::Primitive(Primitive::U8) => format!("writer.bytes({});", expr),
+        Type::Array(elem, length) => format!("writer.fixedArray({}, {}, ({}) => {{ {} }});", expr, length, item, encode(elem, &item, depth + 1)),
+        Type::Vec(elem) => format!("writer.vec({}, ({}) => {{ {} }});", expr, item, encode(elem, &item, depth + 1)),
+        Type::Option(elem) => format!("writer.option({}, ({}) => {{ {} }});", expr, item, encode(elem, &item, depth + 1)),
+        Type::Tuple(elems) => elems.iter().enumerate()
+            .map(|(index, elem)| encode(elem, &format!("{}[{}]", expr, index), depth))
+            .collect::<Vec<_>>()
+            .join(" "),
+        Type::Named(name) => format!("encode{}(writer, {});", name, expr),
+    }
+}
+
+/// Expression reading a value of type `ty`.
+fn decode(ty: &Type) -> String {
+    match ty {
+        Type::Primitive(p) => format!("reader.{}()", primitive(*p).0),
+        Type::Pubkey => "reader.pubkey()".to_string(),
+        Type::String => "reader.string()".to_string(),
+        Type::Array(elem, length) if **elem == Type::Primitive(Primitive::U8) => format!("reader.fixedBytes({})", length),
+        Type::Vec(elem) if **elem == Type::Primitive(Primitive::U8) => "reader.bytes()".to_string(),
+        Type::Array(elem, length) => format!("reader.fixedArray<{}>({}, () => {})", ts_type(elem), length, decode(elem)),
+        Type::Vec(elem) => format!("reader.vec<{}>(() => {})", ts_type(elem), decode(elem)),
+        Type::Option(elem) => format!("reader.option<{}>(() => {})", ts_type(elem), decode(elem)),
+        Type::Tuple(elems) => format!("[{}]", elems.iter().map(decode).collect::<Vec<_>>().join(", ")),
+        Type::Named(name) => format!("decode{}(reader)", name),
+    }
+}
+
+/// Tuple type of unnamed fields, a single field is used as is.
+fn unnamed_type(types: &[Type]) -> Type {
+    match types {
+        [ty] => ty.clone(),
+        _ => Type::Tuple(types.to_vec()),
+    }
+}
+
+fn doc_comment(out: &mut String, doc: &[String], indent: &str) -> std::fmt::Result {
+    match doc {
+        [] => Ok(()),
+        [line] => writeln!(out, "{}/** {} */", indent, line.replace("*/", "*\\/")),
+        lines => {
+            writeln!(out, "{}/**", indent)?;
+            for line in lines {
+                match line.is_empty() {
+                    true => writeln!(out, "{} *", indent)?,
+                    false => writeln!(out, "{} * {}", indent, line.replace("*/", "*\\/"))?,
+                }
+            }
+            writeln!(out, "{} */", indent)
+        }
+    }
+}
+
+fn struct_definition(out: &mut String, definition: &TypeDefinition, fields: &Fields) -> Result<(), Box<dyn Error>> {
+    let name = &definition.name;
+    doc_comment(out, &definition.doc, "")?;
+    match fields {
+        Fields::Named(fields) => {
+            writeln!(out, "export interface {} {{", name)?;
+            for field in fields {
+                writeln!(out, "    {}: {};", field.name, ts_type(&field.ty))?;
+            }
+            writeln!(out, "}}")?;
+
+            writeln!(out, "\nexport function encode{}(writer: BorshWriter, value: {}): void {{", name, name)?;
+            for field in fields {
+                writeln!(out, "    {}", encode(&field.ty, &format!("value.{}", field.name), 0))?;
+            }
+            writeln!(out, "}}")?;
+
+            writeln!(out, "\nexport function decode{}(reader: BorshReader): {} {{", name, name)?;
+            writeln!(out, "    return {{")?;
+            for field in fields {
+                writeln!(out, "        {}: {},", field.name, decode(&field.ty))?;
+            }
+            writeln!(out, "    }};")?;
+            writeln!(out, "}}")?;
+        }
+        Fields::Unnamed(types) => {
+            let ty = unnamed_type(types);
+            writeln!(out, "export type {} = {};", name, ts_type(&ty))?;
+            writeln!(out, "\nexport function encode{}(writer: BorshWriter, value: {}): void {{", name, name)?;
+            writeln!(out, "    {}", encode(&ty, "value", 0))?;
+            writeln!(out, "}}")?;
+            writeln!(out, "\nexport function decode{}(reader: BorshReader): {} {{", name, name)?;
+            writeln!(out, "    return {};", decode(&ty))?;
+            writeln!(out, "}}")?;
+        }
+        Fields::Unit => return Err(format!("unit struct {} is not supported", name).into()),
+    }
+
+    Ok(())
+}
+
+fn unit_enum_definition(out: &mut String, definition: &TypeDefinition, variants: &[Variant]) -> Result<(), Box<dyn Error>> {
+    let name = &definition.name;
+    let names = variants.iter().map(|variant| format!("'{}'", variant.name)).collect::<Vec<_>>();
+    doc_comment(out, &definition.doc, "")?;
+    writeln!(out, "export type {} = {};", name, names.join(" | "))?;
+
+    writeln!(out, "\nexport function encode{}(writer: BorshWriter, value: {}): void {{", name, name)?;
+    writeln!(out, "    const variant = ([{}] as {}[]).indexOf(value);", names.join(", "), name)?;
+    writeln!(out, "    if (variant < 0) {{")?;
+    writeln!(out, "        throw new Error(`unknown {} variant ${{value}}`);", name)?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "    writer.u8(variant);")?;
+    writeln!(out, "}}")?;
+
+    writeln!(out, "\nexport function decode{}(reader: BorshReader): {} {{", name, name)?;
+    writeln!(out, "    const variants: {}[] = [{}];", name, names.join(", "))?;
+    writeln!(out, "    const variant = reader.u8();")?;
+    writeln!(out, "    if (variant >= variants.length) {{")?;
+    writeln!(out, "        throw new Error(`invalid {} variant ${{variant}}`);", name)?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "    return variants[variant];")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn enum_definition(out: &mut String, definition: &TypeDefinition, variants: &[Variant]) -> Result<(), Box<dyn Error>> {
+    let name = &definition.name;
+    if variants.len() > u8::MAX as usize + 1 {
+        return Err(format!("{} has more variants than a u8 discriminant can index", name).into());
+    }
+
+    doc_comment(out, &definition.doc, "")?;
+    writeln!(out, "export type {} =", name)?;
+    for (index, variant) in variants.iter().enumerate() {
+        doc_comment(out, &variant.doc, "    ")?;
+        let fields = match &variant.fields {
+            Fields::Unit => String::new(),
+            Fields::Unnamed(types) if types.len() == 1 => format!("; value: {}", ts_type(&types[0])),
+            Fields::Unnamed(types) => format!("; values: {}", ts_type(&Type::Tuple(types.clone()))),
+            Fields::Named(fields) => {
+                if fields.iter().any(|field| field.name == "kind") {
+                    return Err(format!("field `kind` of {}::{} collides with the variant tag", name, variant.name).into());
+                }
+                fields.iter().map(|field| format!("; {}: {}", field.name, ts_type(&field.ty))).collect()
+            }
+        };
+        let end = if index + 1 == variants.len() { ";" } else { "" };
+        writeln!(out, "    | {{ kind: '{}'{} }}{}", variant.name, fields, end)?;
+    }
+
+    writeln!(out, "\nexport function encode{}(writer: BorshWriter, value: {}): void {{", name, name)?;
+    writeln!(out, "    switch (value.kind) {{")?;
+    for (index, variant) in variants.iter().enumerate() {
+        writeln!(out, "        case '{}':", variant.name)?;
+        writeln!(out, "            writer.u8({});", index)?;
+        match &variant.fields {
+            Fields::Unit => {}
+            Fields::Unnamed(types) if types.len() == 1 => writeln!(out, "            {}", encode(&types[0], "value.value", 0))?,
+            Fields::Unnamed(types) => writeln!(out, "            {}", encode(&Type::Tuple(types.clone()), "value.values", 0))?,
+            Fields::Named(fields) => for field in fields {
+                writeln!(out, "            {}", encode(&field.ty, &format!("value.{}", field.name), 0))?;
+            },
+        }
+        writeln!(out, "            break;")?;
+    }
+    writeln!(out, "        default:")?;
+    writeln!(out, "            throw new Error(`unknown {} variant ${{(value as {{ kind: string }}).kind}}`);", name)?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+
+    writeln!(out, "\nexport function decode{}(reader: BorshReader): {} {{", name, name)?;
+    writeln!(out, "    const variant = reader.u8();")?;
+    writeln!(out, "    switch (variant) {{")?;
+    for (index, variant) in variants.iter().enumerate() {
+        let fields = match &variant.fields {
+            Fields::Unit => String::new(),
+            Fields::Unnamed(types) if types.len() == 1 => format!(", value: {}", decode(&types[0])),
+            Fields::Unnamed(types) => format!(", values: {}", decode(&Type::Tuple(types.clone()))),
+            Fields::Named(fields) => fields.iter().map(|field| format!(", {}: {}", field.name, decode(&field.ty))).collect(),
+        };
+        writeln!(out, "        case {}:", index)?;
+        writeln!(out, "            return {{ kind: '{}'{} }};", variant.name, fields)?;
+    }
+    writeln!(out, "        default:")?;
+    writeln!(out, "            throw new Error(`invalid {} variant ${{variant}}`);", name)?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn instruction_envelope(out: &mut String) -> std::fmt::Result {
+    write!(out, "\
+/** Instruction data of `instruction`: INSTRUCTION_VERSION followed by Borsh bytes, as `UpgradeInstruction::pack`. */
+export function pack{0}(instruction: {0}): Uint8Array {{
+    const writer = new BorshWriter();
+    writer.u8(INSTRUCTION_VERSION);
+    encode{0}(writer, instruction);
+    return writer.toBytes();
+}}
+
+/** Decodes instruction data produced by `pack`, rejects other format versions and trailing bytes as `UpgradeInstruction::unpack`. */
+export function unpack{0}(data: Uint8Array): {0} {{
+    if (data.length < 2 || data.length > MAX_INSTRUCTION_DATA_LEN) {{
+        throw new Error(`invalid instruction data length ${{data.length}}`);
+    }}
+    if (data[0] !== INSTRUCTION_VERSION) {{
+        throw new Error(`instruction version ${{data[0]}} is not supported, expected ${{INSTRUCTION_VERSION}}`);
+    }}
+    const reader = new BorshReader(data.subarray(1));
+    const instruction = decode{0}(reader);
+    if (reader.remaining() > 0) {{
+        throw new Error('trailing bytes after instruction data');
+    }}
+    return instruction;
+}}
+", INSTRUCTION)
+}
@@ -0,0 +1,67 @@
+-- Current state of every initialized UpgradeAdmin, `data` is the full decoded account
+CREATE TABLE upgrade_admins (
+    address TEXT PRIMARY KEY,
+    contract TEXT NOT NULL,
+    layout_version SMALLINT NOT NULL,
+    public_keys TEXT[] NOT NULL,
+    weights SMALLINT[] NOT NULL,
+    threshold SMALLINT NOT NULL,
+    managed_contracts TEXT[] NOT NULL,
+    upgrade_nonce BIGINT NOT NULL,
+    authority_nonce BIGINT NOT NULL,
+    key_nonce BIGINT NOT NULL,
+    paused BOOLEAN NOT NULL,
+    active BOOLEAN NOT NULL,
+    immutable BOOLEAN NOT NULL,
+    data JSONB NOT NULL,
+    slot BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX upgrade_admins_contract ON upgrade_admins (contract);
+
+-- Programs and UpgradeAdmin that controls their upgrades
+CREATE VIEW controlled_programs AS
+    SELECT contract AS program, address AS admin FROM upgrade_admins
+    UNION
+    SELECT unnest(managed_contracts) AS program, address AS admin FROM upgrade_admins;
+
+-- Upgrades proposed by ProposeUpgrade, rows are removed once the proposal is executed or cancelled
+CREATE TABLE pending_upgrades (
+    address TEXT PRIMARY KEY,
+    admin TEXT NOT NULL,
+    program TEXT NOT NULL,
+    buffer TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    proposal_slot BIGINT NOT NULL,
+    not_before_slot BIGINT,
+    not_after_slot BIGINT,
+    approvals INTEGER NOT NULL,
+    slot BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX pending_upgrades_admin ON pending_upgrades (admin);
+
+CREATE TABLE upgrade_histories (
+    address TEXT PRIMARY KEY,
+    admin TEXT NOT NULL,
+    program TEXT NOT NULL,
+    upgrade_count BIGINT NOT NULL,
+    slot BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Every upgrade record ever observed, kept after it is overwritten in the on-chain ring buffer.
+-- `seq` is the index of the record among all upgrades of the program by the admin
+CREATE TABLE upgrade_records (
+    admin TEXT NOT NULL,
+    program TEXT NOT NULL,
+    seq BIGINT NOT NULL,
+    slot BIGINT NOT NULL,
+    buffer TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    executor TEXT,
+    spill TEXT NOT NULL,
+    PRIMARY KEY (admin, program, seq)
+);
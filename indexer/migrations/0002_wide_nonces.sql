@@ -0,0 +1,5 @@
+-- Nonces of UpgradeAdmin with U128 nonce width do not fit into BIGINT
+ALTER TABLE upgrade_admins
+    ALTER COLUMN upgrade_nonce TYPE NUMERIC(39),
+    ALTER COLUMN authority_nonce TYPE NUMERIC(39),
+    ALTER COLUMN key_nonce TYPE NUMERIC(39);
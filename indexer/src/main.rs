@@ -0,0 +1,85 @@
+//! Indexer mirroring UpgradeAdmin, PendingUpgrade and UpgradeHistory accounts of the upgrade program into Postgres
+//! for dashboards over who controls which program and which upgrades are pending. Accounts are polled over RPC,
+//! every poll replaces the mirrored state in one transaction, so readers never see a partially applied snapshot.
+
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+
+use clap::Parser;
+use postgres::{Client, NoTls};
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use upgrade_program_client as client;
+
+mod migrations;
+mod store;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(name = "upgrade-indexer", about = "Mirror upgrade program accounts into Postgres")]
+struct Cli {
+    /// JSON RPC URL of the cluster
+    #[arg(long, default_value = "http://localhost:8899")]
+    url: String,
+    /// Address of the upgrade program
+    #[arg(long)]
+    program_id: Pubkey,
+    /// Postgres connection string
+    #[arg(long, env = "DATABASE_URL")]
+    database_url: String,
+    /// Seconds between polls
+    #[arg(long, default_value_t = 10)]
+    interval: u64,
+    /// Apply migrations and index once instead of polling
+    #[arg(long)]
+    once: bool,
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+    let mut db = Client::connect(&cli.database_url, NoTls)?;
+    let applied = migrations::migrate(&mut db)?;
+    if applied > 0 {
+        eprintln!("Applied {} migrations", applied);
+    }
+
+    loop {
+        // RPC and database errors of a poll are reported and retried on the next one
+        match index(&rpc, &mut db, &cli.program_id) {
+            Ok(snapshot) => eprintln!(
+                "Indexed slot {}: {} admins, {} pending upgrades, {} upgrade histories",
+                snapshot.slot, snapshot.admins.len(), snapshot.pending_upgrades.len(), snapshot.upgrade_histories.len(),
+            ),
+            Err(e) if cli.once => return Err(e),
+            Err(e) => eprintln!("error: {}", e),
+        }
+
+        if cli.once {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(cli.interval));
+    }
+}
+
+fn index(rpc: &RpcClient, db: &mut Client, program_id: &Pubkey) -> Result<store::Snapshot> {
+    // Slot is taken before the accounts, so every mirrored row is at least as recent as its slot
+    let snapshot = store::Snapshot {
+        slot: rpc.get_slot()?,
+        admins: client::get_upgrade_admins(rpc, program_id)?,
+        pending_upgrades: client::get_pending_upgrades(rpc, program_id)?,
+        upgrade_histories: client::get_upgrade_histories(rpc, program_id)?,
+    };
+
+    store::store(db, &snapshot)?;
+    Ok(snapshot)
+}
@@ -0,0 +1,32 @@
+//! Schema migrations embedded into the binary and applied in order. Applied versions are recorded in
+//! `schema_migrations`, an advisory lock keeps concurrently started indexers from applying them twice.
+
+use postgres::Client;
+
+/// Key of the advisory lock held while migrating
+const MIGRATION_LOCK: i64 = i64::from_be_bytes(*b"upgrades");
+
+/// Migrations by version, new ones are appended and never edited once released
+const MIGRATIONS: &[(i32, &str)] = &[
+    (1, include_str!("../migrations/0001_initial.sql")),
+    (2, include_str!("../migrations/0002_wide_nonces.sql")),
+];
+
+/// Applies migrations newer than the database schema, returns the number of applied migrations.
+pub fn migrate(client: &mut Client) -> Result<usize, postgres::Error> {
+    let mut transaction = client.transaction()?;
+    transaction.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK])?;
+    transaction.batch_execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (version INTEGER PRIMARY KEY, applied_at TIMESTAMPTZ NOT NULL DEFAULT now())",
+    )?;
+    let current: i32 = transaction.query_one("SELECT COALESCE(MAX(version), 0) FROM schema_migrations", &[])?.get(0);
+
+    let pending = MIGRATIONS.iter().filter(|(version, _)| *version > current).collect::<Vec<_>>();
+    for (version, sql) in &pending {
+        transaction.batch_execute(sql)?;
+        transaction.execute("INSERT INTO schema_migrations (version) VALUES ($1)", &[version])?;
+    }
+
+    transaction.commit()?;
+    Ok(pending.len())
+}
@@ -0,0 +1,151 @@
+//! Mirroring of fetched program accounts into the tables of `migrations`.
+
+use postgres::{Client, Transaction};
+use solana_program::pubkey::Pubkey;
+use upgrade::state::{AdminKey, PendingUpgrade, UpgradeHistory, VersionedUpgradeAdmin, MAX_UPGRADE_RECORDS};
+
+/// Accounts of the upgrade program fetched at `slot`.
+pub struct Snapshot {
+    pub slot: u64,
+    pub admins: Vec<(Pubkey, VersionedUpgradeAdmin)>,
+    pub pending_upgrades: Vec<(Pubkey, PendingUpgrade)>,
+    pub upgrade_histories: Vec<(Pubkey, UpgradeHistory)>,
+}
+
+/// Hex of the key bytes, the key kind follows from the length; base58 for Ed25519 keys.
+fn admin_key_string(key: &AdminKey) -> String {
+    match key {
+        AdminKey::PublicKey(key) => hex::encode(key),
+        AdminKey::EthAddress(address) => hex::encode(address),
+        AdminKey::CompressedPublicKey(key) => hex::encode(key),
+        AdminKey::Ed25519(key) => key.to_string(),
+    }
+}
+
+/// Replaces the mirrored state with the snapshot in one transaction. Admins and pending upgrades missing
+/// from the snapshot were closed and are removed, upgrade records are only added.
+pub fn store(client: &mut Client, snapshot: &Snapshot) -> Result<(), Box<dyn std::error::Error>> {
+    let mut transaction = client.transaction()?;
+    let slot = snapshot.slot as i64;
+
+    for (address, admin) in &snapshot.admins {
+        store_admin(&mut transaction, address, admin, slot)?;
+    }
+    let addresses = snapshot.admins.iter().map(|(address, _)| address.to_string()).collect::<Vec<_>>();
+    transaction.execute("DELETE FROM upgrade_admins WHERE address <> ALL($1)", &[&addresses])?;
+
+    for (address, pending) in &snapshot.pending_upgrades {
+        transaction.execute(
+            "INSERT INTO pending_upgrades (address, admin, program, buffer, code_hash, proposal_slot, not_before_slot, not_after_slot, approvals, slot)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT (address) DO UPDATE SET admin = $2, program = $3, buffer = $4, code_hash = $5, proposal_slot = $6,
+                 not_before_slot = $7, not_after_slot = $8, approvals = $9, slot = $10, updated_at = now()",
+            &[
+                &address.to_string(),
+                &pending.admin.to_string(),
+                &pending.program.to_string(),
+                &pending.buffer.to_string(),
+                &hex::encode(pending.code_hash),
+                &(pending.proposal_slot as i64),
+                &pending.not_before_slot.map(|slot| slot as i64),
+                &pending.not_after_slot.map(|slot| slot as i64),
+                &(pending.approvals.len() as i32),
+                &slot,
+            ],
+        )?;
+    }
+    let addresses = snapshot.pending_upgrades.iter().map(|(address, _)| address.to_string()).collect::<Vec<_>>();
+    transaction.execute("DELETE FROM pending_upgrades WHERE address <> ALL($1)", &[&addresses])?;
+
+    for (address, history) in &snapshot.upgrade_histories {
+        store_history(&mut transaction, address, history, slot)?;
+    }
+
+    transaction.commit()?;
+    Ok(())
+}
+
+fn store_admin(transaction: &mut Transaction, address: &Pubkey, admin: &VersionedUpgradeAdmin, slot: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let data = serde_json::to_value(admin)?;
+    let (contract, version, public_keys, weights, threshold, managed_contracts, nonces, flags) = match admin {
+        // V1 admins have a single key and one nonce shared by all operations
+        VersionedUpgradeAdmin::V1(admin) => (
+            admin.contract,
+            1i16,
+            vec![hex::encode(admin.public_key)],
+            vec![],
+            1,
+            vec![],
+            [admin.nonce.into(); 3],
+            (false, true, false),
+        ),
+        VersionedUpgradeAdmin::V2(admin) => (
+            admin.contract,
+            admin.version as i16,
+            admin.public_keys.iter().map(admin_key_string).collect(),
+            admin.weights.iter().map(|weight| *weight as i16).collect::<Vec<_>>(),
+            admin.threshold as i16,
+            admin.managed_contracts.iter().map(|contract| contract.to_string()).collect(),
+            [admin.upgrade_nonce, admin.authority_nonce, admin.key_nonce],
+            (admin.paused, admin.active, admin.immutable),
+        ),
+    };
+
+    transaction.execute(
+        "INSERT INTO upgrade_admins (address, contract, layout_version, public_keys, weights, threshold, managed_contracts,
+             upgrade_nonce, authority_nonce, key_nonce, paused, active, immutable, data, slot)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8::TEXT::NUMERIC, $9::TEXT::NUMERIC, $10::TEXT::NUMERIC, $11, $12, $13, $14, $15)
+         ON CONFLICT (address) DO UPDATE SET contract = $2, layout_version = $3, public_keys = $4, weights = $5, threshold = $6,
+             managed_contracts = $7, upgrade_nonce = $8::TEXT::NUMERIC, authority_nonce = $9::TEXT::NUMERIC, key_nonce = $10::TEXT::NUMERIC, paused = $11, active = $12,
+             immutable = $13, data = $14, slot = $15, updated_at = now()",
+        &[
+            &address.to_string(),
+            &contract.to_string(),
+            &version,
+            &public_keys,
+            &weights,
+            &threshold,
+            &managed_contracts,
+            &nonces[0].to_string(),
+            &nonces[1].to_string(),
+            &nonces[2].to_string(),
+            &flags.0,
+            &flags.1,
+            &flags.2,
+            &data,
+            &slot,
+        ],
+    )?;
+    Ok(())
+}
+
+fn store_history(transaction: &mut Transaction, address: &Pubkey, history: &UpgradeHistory, slot: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let admin = history.admin.to_string();
+    let program = history.program.to_string();
+    transaction.execute(
+        "INSERT INTO upgrade_histories (address, admin, program, upgrade_count, slot) VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (address) DO UPDATE SET upgrade_count = $4, slot = $5, updated_at = now()",
+        &[&address.to_string(), &admin, &program, &(history.count as i64), &slot],
+    )?;
+
+    // Record `seq` is stored at index `seq % MAX_UPGRADE_RECORDS`, the ring holds the last `records.len()` of them
+    let first = history.count.saturating_sub(history.records.len() as u64);
+    for (index, record) in history.records.iter().enumerate() {
+        let offset = (index as u64 + MAX_UPGRADE_RECORDS as u64 - first % MAX_UPGRADE_RECORDS as u64) % MAX_UPGRADE_RECORDS as u64;
+        transaction.execute(
+            "INSERT INTO upgrade_records (admin, program, seq, slot, buffer, code_hash, executor, spill)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT DO NOTHING",
+            &[
+                &admin,
+                &program,
+                &((first + offset) as i64),
+                &(record.slot as i64),
+                &record.buffer.to_string(),
+                &hex::encode(record.code_hash),
+                &record.executor.map(|executor| executor.to_string()),
+                &record.spill.to_string(),
+            ],
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,3047 @@
+// Generated by upgrade-codegen from the Borsh types of the upgrade program, do not edit.
+// Regenerate with `npm run codegen:upgrade-program`.
+
+import { PublicKey } from '@solana/web3.js';
+
+/** Borsh encoder: little-endian integers, u32 length prefixes of vectors and strings, 0/1 tags of options. */
+export class BorshWriter {
+    private buffer = new Uint8Array(256);
+    private length = 0;
+
+    private reserve(size: number): DataView {
+        if (this.length + size > this.buffer.length) {
+            const buffer = new Uint8Array(Math.max(this.buffer.length * 2, this.length + size));
+            buffer.set(this.buffer.subarray(0, this.length));
+            this.buffer = buffer;
+        }
+        const view = new DataView(this.buffer.buffer, this.length, size);
+        this.length += size;
+        return view;
+    }
+
+    u8(value: number): void {
+        this.reserve(1).setUint8(0, value);
+    }
+
+    u16(value: number): void {
+        this.reserve(2).setUint16(0, value, true);
+    }
+
+    u32(value: number): void {
+        this.reserve(4).setUint32(0, value, true);
+    }
+
+    u64(value: bigint): void {
+        this.reserve(8).setBigUint64(0, BigInt.asUintN(64, value), true);
+    }
+
+    u128(value: bigint): void {
+        const view = this.reserve(16);
+        const unsigned = BigInt.asUintN(128, value);
+        view.setBigUint64(0, BigInt.asUintN(64, unsigned), true);
+        view.setBigUint64(8, unsigned >> BigInt(64), true);
+    }
+
+    i8(value: number): void {
+        this.reserve(1).setInt8(0, value);
+    }
+
+    i16(value: number): void {
+        this.reserve(2).setInt16(0, value, true);
+    }
+
+    i32(value: number): void {
+        this.reserve(4).setInt32(0, value, true);
+    }
+
+    i64(value: bigint): void {
+        this.reserve(8).setBigInt64(0, BigInt.asIntN(64, value), true);
+    }
+
+    i128(value: bigint): void {
+        this.u128(value);
+    }
+
+    bool(value: boolean): void {
+        this.u8(value ? 1 : 0);
+    }
+
+    fixedBytes(value: Uint8Array, length: number): void {
+        if (value.length !== length) {
+            throw new Error(`expected ${length} bytes, got ${value.length}`);
+        }
+        const view = this.reserve(length);
+        new Uint8Array(view.buffer, view.byteOffset, length).set(value);
+    }
+
+    bytes(value: Uint8Array): void {
+        this.u32(value.length);
+        this.fixedBytes(value, value.length);
+    }
+
+    string(value: string): void {
+        this.bytes(new TextEncoder().encode(value));
+    }
+
+    pubkey(value: PublicKey): void {
+        this.fixedBytes(value.toBytes(), 32);
+    }
+
+    vec<T>(values: T[], encode: (value: T) => void): void {
+        this.u32(values.length);
+        values.forEach(encode);
+    }
+
+    fixedArray<T>(values: T[], length: number, encode: (value: T) => void): void {
+        if (values.length !== length) {
+            throw new Error(`expected ${length} items, got ${values.length}`);
+        }
+        values.forEach(encode);
+    }
+
+    option<T>(value: T | null, encode: (value: T) => void): void {
+        if (value === null) {
+            this.u8(0);
+        } else {
+            this.u8(1);
+            encode(value);
+        }
+    }
+
+    toBytes(): Uint8Array {
+        return this.buffer.slice(0, this.length);
+    }
+}
+
+/** Borsh decoder, fails on truncated data and on invalid bool and option tags. */
+export class BorshReader {
+    private offset = 0;
+
+    constructor(private readonly data: Uint8Array) {}
+
+    private view(size: number): DataView {
+        if (this.offset + size > this.data.length) {
+            throw new Error(`unexpected end of data at offset ${this.offset}`);
+        }
+        const view = new DataView(this.data.buffer, this.data.byteOffset + this.offset, size);
+        this.offset += size;
+        return view;
+    }
+
+    u8(): number {
+        return this.view(1).getUint8(0);
+    }
+
+    u16(): number {
+        return this.view(2).getUint16(0, true);
+    }
+
+    u32(): number {
+        return this.view(4).getUint32(0, true);
+    }
+
+    u64(): bigint {
+        return this.view(8).getBigUint64(0, true);
+    }
+
+    u128(): bigint {
+        const view = this.view(16);
+        return view.getBigUint64(0, true) + (view.getBigUint64(8, true) << BigInt(64));
+    }
+
+    i8(): number {
+        return this.view(1).getInt8(0);
+    }
+
+    i16(): number {
+        return this.view(2).getInt16(0, true);
+    }
+
+    i32(): number {
+        return this.view(4).getInt32(0, true);
+    }
+
+    i64(): bigint {
+        return this.view(8).getBigInt64(0, true);
+    }
+
+    i128(): bigint {
+        return BigInt.asIntN(128, this.u128());
+    }
+
+    bool(): boolean {
+        const value = this.u8();
+        if (value > 1) {
+            throw new Error(`invalid bool ${value}`);
+        }
+        return value === 1;
+    }
+
+    fixedBytes(length: number): Uint8Array {
+        const view = this.view(length);
+        return new Uint8Array(view.buffer, view.byteOffset, length).slice();
+    }
+
+    bytes(): Uint8Array {
+        return this.fixedBytes(this.u32());
+    }
+
+    string(): string {
+        return new TextDecoder('utf-8', { fatal: true }).decode(this.bytes());
+    }
+
+    pubkey(): PublicKey {
+        return new PublicKey(this.fixedBytes(32));
+    }
+
+    vec<T>(decode: () => T): T[] {
+        const length = this.u32();
+        const values: T[] = [];
+        for (let i = 0; i < length; i++) {
+            values.push(decode());
+        }
+        return values;
+    }
+
+    fixedArray<T>(length: number, decode: () => T): T[] {
+        const values: T[] = [];
+        for (let i = 0; i < length; i++) {
+            values.push(decode());
+        }
+        return values;
+    }
+
+    option<T>(decode: () => T): T | null {
+        const tag = this.u8();
+        if (tag > 1) {
+            throw new Error(`invalid option tag ${tag}`);
+        }
+        return tag === 1 ? decode() : null;
+    }
+
+    /** Number of bytes not read yet. */
+    remaining(): number {
+        return this.data.length - this.offset;
+    }
+}
+
+/** Borsh bytes of `value`. */
+export function serialize<T>(value: T, encode: (writer: BorshWriter, value: T) => void): Uint8Array {
+    const writer = new BorshWriter();
+    encode(writer, value);
+    return writer.toBytes();
+}
+
+/**
+ * Value decoded from the beginning of `data`. Trailing bytes are allowed like in `BorshDeserialize::deserialize`,
+ * accounts are allocated for the largest layout.
+ */
+export function deserialize<T>(data: Uint8Array, decode: (reader: BorshReader) => T): T {
+    return decode(new BorshReader(data));
+}
+
+export const SHA256_BLOCK_SIZE = 64;
+export const INCREMENTAL_SHA256_SIZE = 105;
+export const ADMIN_VERSION = 2;
+export const V1_ADMIN_SIZE = 105;
+export const ETH_ADDRESS_LENGTH = 20;
+export const SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH = 33;
+export const MAX_ADMIN_KEY_SIZE = 65;
+export const MAX_SIGNERS = 10;
+export const MAX_SIGNER_SET_SIZE = 768;
+export const MAX_PENDING_KEY_CHANGE_SIZE = 776;
+export const MAX_PENDING_RECOVERY_SIZE = 677;
+export const MIN_RECOVERY_DELAY = 216000;
+export const MAX_MANAGED_CONTRACTS = 16;
+export const MAX_URI_LENGTH = 200;
+export const MAX_EXECUTORS = 8;
+export const MAX_BATCH_UPGRADES = 4;
+export const MAX_HISTORY_ENTRIES = 32;
+export const HISTORY_ENTRY_SIZE = 137;
+export const MAX_ADMIN_HISTORY_SIZE = 4429;
+export const MAX_UPGRADE_RECORDS = 32;
+export const UPGRADE_RECORD_SIZE = 137;
+export const MAX_UPGRADE_HISTORY_SIZE = 4461;
+export const MAX_REVOKED_KEYS = 32;
+export const MAX_REVOKED_KEYS_SIZE = 2117;
+export const MAX_BATCH_OPERATIONS = 16;
+export const MAX_ROLES = 8;
+export const MAX_HEALTHCHECKS = 4;
+export const MAX_HEALTHCHECK_DATA_LEN = 64;
+export const HEALTHCHECK_SIZE = 100;
+export const MAX_VERIFIED_BUILDS = 17;
+export const VERIFIED_BUILD_SIZE = 65;
+export const MAX_ROLE_KEY_SIZE = 66;
+export const ROLE_ASSIGNMENT_SIZE = 67;
+export const MAX_PENDING_UPGRADE_SIZE = 415;
+export const MAX_ROLLBACK_SNAPSHOT_SIZE = 137;
+export const MAX_BUFFER_STAGING_SIZE = 283;
+export const MAX_BATCH_APPROVAL_SIZE = 589;
+export const MAX_SESSION_KEY_SIZE = 107;
+export const MAX_ADMIN_SIZE = 5635;
+export const MERKLE_LEAF_PREFIX = 0;
+export const MERKLE_NODE_PREFIX = 1;
+export const INSTRUCTION_VERSION = 1;
+export const REGISTERED_BUFFER_INDEX = 3;
+export const MAX_INSTRUCTION_DATA_LEN = 1232;
+
+/** Format of the message that should be signed by UpgradeAdmin signers. */
+export type HashingMode = 'Legacy' | 'Eip712';
+
+export function encodeHashingMode(writer: BorshWriter, value: HashingMode): void {
+    const variant = (['Legacy', 'Eip712'] as HashingMode[]).indexOf(value);
+    if (variant < 0) {
+        throw new Error(`unknown HashingMode variant ${value}`);
+    }
+    writer.u8(variant);
+}
+
+export function decodeHashingMode(reader: BorshReader): HashingMode {
+    const variants: HashingMode[] = ['Legacy', 'Eip712'];
+    const variant = reader.u8();
+    if (variant >= variants.length) {
+        throw new Error(`invalid HashingMode variant ${variant}`);
+    }
+    return variants[variant];
+}
+
+/** Algorithm of the outer digest of legacy messages. */
+export type HashAlgorithm = 'Keccak' | 'Sha256';
+
+export function encodeHashAlgorithm(writer: BorshWriter, value: HashAlgorithm): void {
+    const variant = (['Keccak', 'Sha256'] as HashAlgorithm[]).indexOf(value);
+    if (variant < 0) {
+        throw new Error(`unknown HashAlgorithm variant ${value}`);
+    }
+    writer.u8(variant);
+}
+
+export function decodeHashAlgorithm(reader: BorshReader): HashAlgorithm {
+    const variants: HashAlgorithm[] = ['Keccak', 'Sha256'];
+    const variant = reader.u8();
+    if (variant >= variants.length) {
+        throw new Error(`invalid HashAlgorithm variant ${variant}`);
+    }
+    return variants[variant];
+}
+
+/** Width of UpgradeAdmin nonces in signed messages. */
+export type NonceWidth = 'U64' | 'U128';
+
+export function encodeNonceWidth(writer: BorshWriter, value: NonceWidth): void {
+    const variant = (['U64', 'U128'] as NonceWidth[]).indexOf(value);
+    if (variant < 0) {
+        throw new Error(`unknown NonceWidth variant ${value}`);
+    }
+    writer.u8(variant);
+}
+
+export function decodeNonceWidth(reader: BorshReader): NonceWidth {
+    const variants: NonceWidth[] = ['U64', 'U128'];
+    const variant = reader.u8();
+    if (variant >= variants.length) {
+        throw new Error(`invalid NonceWidth variant ${variant}`);
+    }
+    return variants[variant];
+}
+
+/**
+ * SHA-256 of data that is supplied by parts in different transactions. The result is equal to
+ * SHA-256 of the concatenated parts.
+ */
+export interface IncrementalSha256 {
+    state: number[];
+    block: Uint8Array;
+    block_len: number;
+    total_len: bigint;
+}
+
+export function encodeIncrementalSha256(writer: BorshWriter, value: IncrementalSha256): void {
+    writer.fixedArray(value.state, 8, (item0) => { writer.u32(item0); });
+    writer.fixedBytes(value.block, 64);
+    writer.u8(value.block_len);
+    writer.u64(value.total_len);
+}
+
+export function decodeIncrementalSha256(reader: BorshReader): IncrementalSha256 {
+    return {
+        state: reader.fixedArray<number>(8, () => reader.u32()),
+        block: reader.fixedBytes(64),
+        block_len: reader.u8(),
+        total_len: reader.u64(),
+    };
+}
+
+/** Identity of the signer that controls UpgradeAdmin. */
+export type AdminKey =
+    /** ECDSA public key (64 byte format) */
+    | { kind: 'PublicKey'; value: Uint8Array }
+    /** Ethereum address: last 20 bytes of keccak_hash(public_key) */
+    | { kind: 'EthAddress'; value: Uint8Array }
+    /** Ed25519 public key, signature is verified by preceding `ed25519_program` instruction */
+    | { kind: 'Ed25519'; value: PublicKey }
+    /** Compressed ECDSA public key (33 byte format: 0x02/0x03 parity prefix and X coordinate) */
+    | { kind: 'CompressedPublicKey'; value: Uint8Array };
+
+export function encodeAdminKey(writer: BorshWriter, value: AdminKey): void {
+    switch (value.kind) {
+        case 'PublicKey':
+            writer.u8(0);
+            writer.fixedBytes(value.value, 64);
+            break;
+        case 'EthAddress':
+            writer.u8(1);
+            writer.fixedBytes(value.value, 20);
+            break;
+        case 'Ed25519':
+            writer.u8(2);
+            writer.pubkey(value.value);
+            break;
+        case 'CompressedPublicKey':
+            writer.u8(3);
+            writer.fixedBytes(value.value, 33);
+            break;
+        default:
+            throw new Error(`unknown AdminKey variant ${(value as { kind: string }).kind}`);
+    }
+}
+
+export function decodeAdminKey(reader: BorshReader): AdminKey {
+    const variant = reader.u8();
+    switch (variant) {
+        case 0:
+            return { kind: 'PublicKey', value: reader.fixedBytes(64) };
+        case 1:
+            return { kind: 'EthAddress', value: reader.fixedBytes(20) };
+        case 2:
+            return { kind: 'Ed25519', value: reader.pubkey() };
+        case 3:
+            return { kind: 'CompressedPublicKey', value: reader.fixedBytes(33) };
+        default:
+            throw new Error(`invalid AdminKey variant ${variant}`);
+    }
+}
+
+/** Instruction without accounts that the upgraded contract should execute successfully, e.g. version query. */
+export interface Healthcheck {
+    contract: PublicKey;
+    data: Uint8Array;
+}
+
+export function encodeHealthcheck(writer: BorshWriter, value: Healthcheck): void {
+    writer.pubkey(value.contract);
+    writer.bytes(value.data);
+}
+
+export function decodeHealthcheck(reader: BorshReader): Healthcheck {
+    return {
+        contract: reader.pubkey(),
+        data: reader.bytes(),
+    };
+}
+
+/**
+ * Hash of the program data produced by a verified build (e.g. solana-verify) of the contract.
+ * If `required` is set, upgrades of the contract should deploy exactly this build.
+ */
+export interface VerifiedBuild {
+    contract: PublicKey;
+    build_hash: Uint8Array;
+    required: boolean;
+}
+
+export function encodeVerifiedBuild(writer: BorshWriter, value: VerifiedBuild): void {
+    writer.pubkey(value.contract);
+    writer.fixedBytes(value.build_hash, 32);
+    writer.bool(value.required);
+}
+
+export function decodeVerifiedBuild(reader: BorshReader): VerifiedBuild {
+    return {
+        contract: reader.pubkey(),
+        build_hash: reader.fixedBytes(32),
+        required: reader.bool(),
+    };
+}
+
+/** Step of the upgrade pipeline a key is allowed to perform. */
+export type Role = 'Proposer' | 'Approver' | 'Executor';
+
+export function encodeRole(writer: BorshWriter, value: Role): void {
+    const variant = (['Proposer', 'Approver', 'Executor'] as Role[]).indexOf(value);
+    if (variant < 0) {
+        throw new Error(`unknown Role variant ${value}`);
+    }
+    writer.u8(variant);
+}
+
+export function decodeRole(reader: BorshReader): Role {
+    const variants: Role[] = ['Proposer', 'Approver', 'Executor'];
+    const variant = reader.u8();
+    if (variant >= variants.length) {
+        throw new Error(`invalid Role variant ${variant}`);
+    }
+    return variants[variant];
+}
+
+/** Key bound to a role. */
+export type RoleKey =
+    /** ECDSA or ed25519 key that signs the operation message */
+    | { kind: 'Ecdsa'; value: AdminKey }
+    /** Solana account that signs the transaction */
+    | { kind: 'Signer'; value: PublicKey };
+
+export function encodeRoleKey(writer: BorshWriter, value: RoleKey): void {
+    switch (value.kind) {
+        case 'Ecdsa':
+            writer.u8(0);
+            encodeAdminKey(writer, value.value);
+            break;
+        case 'Signer':
+            writer.u8(1);
+            writer.pubkey(value.value);
+            break;
+        default:
+            throw new Error(`unknown RoleKey variant ${(value as { kind: string }).kind}`);
+    }
+}
+
+export function decodeRoleKey(reader: BorshReader): RoleKey {
+    const variant = reader.u8();
+    switch (variant) {
+        case 0:
+            return { kind: 'Ecdsa', value: decodeAdminKey(reader) };
+        case 1:
+            return { kind: 'Signer', value: reader.pubkey() };
+        default:
+            throw new Error(`invalid RoleKey variant ${variant}`);
+    }
+}
+
+export interface RoleAssignment {
+    role: Role;
+    key: RoleKey;
+}
+
+export function encodeRoleAssignment(writer: BorshWriter, value: RoleAssignment): void {
+    encodeRole(writer, value.role);
+    encodeRoleKey(writer, value.key);
+}
+
+export function decodeRoleAssignment(reader: BorshReader): RoleAssignment {
+    return {
+        role: decodeRole(reader),
+        key: decodeRoleKey(reader),
+    };
+}
+
+/** Curve of ECDSA signer keys of UpgradeAdmin. */
+export type Curve = 'Secp256k1' | 'Secp256r1';
+
+export function encodeCurve(writer: BorshWriter, value: Curve): void {
+    const variant = (['Secp256k1', 'Secp256r1'] as Curve[]).indexOf(value);
+    if (variant < 0) {
+        throw new Error(`unknown Curve variant ${value}`);
+    }
+    writer.u8(variant);
+}
+
+export function decodeCurve(reader: BorshReader): Curve {
+    const variants: Curve[] = ['Secp256k1', 'Secp256r1'];
+    const variant = reader.u8();
+    if (variant >= variants.length) {
+        throw new Error(`invalid Curve variant ${variant}`);
+    }
+    return variants[variant];
+}
+
+export interface UpgradeAdmin {
+    version: number;
+    public_keys: AdminKey[];
+    weights: Uint8Array;
+    threshold: number;
+    contract: PublicKey;
+    upgrade_nonce: bigint;
+    authority_nonce: bigint;
+    key_nonce: bigint;
+    is_initialized: boolean;
+    hashing_mode: HashingMode;
+    domain: Uint8Array;
+    signers_root: Uint8Array | null;
+    upgrade_key: AdminKey | null;
+    pending_key_change: PendingKeyChange | null;
+    key_change_delay: bigint;
+    curve: Curve;
+    hash_algorithm: HashAlgorithm;
+    guardian: AdminKey | null;
+    recovery_delay: bigint;
+    pending_recovery: PendingRecovery | null;
+    paused: boolean;
+    managed_contracts: PublicKey[];
+    bump: number;
+    name: Uint8Array;
+    uri: string;
+    active: boolean;
+    expires_at_slot: bigint | null;
+    executors: PublicKey[];
+    reimbursement: bigint;
+    min_delay_slots: bigint;
+    min_upgrade_interval_slots: bigint;
+    last_upgrade_slot: bigint | null;
+    immutable: boolean;
+    roles: RoleAssignment[];
+    approval_threshold: number;
+    veto_key: RoleKey | null;
+    healthchecks: Healthcheck[];
+    unverified_upgrade: PublicKey | null;
+    verified_builds: VerifiedBuild[];
+    nonce_width: NonceWidth;
+}
+
+export function encodeUpgradeAdmin(writer: BorshWriter, value: UpgradeAdmin): void {
+    writer.u8(value.version);
+    writer.vec(value.public_keys, (item0) => { encodeAdminKey(writer, item0); });
+    writer.bytes(value.weights);
+    writer.u8(value.threshold);
+    writer.pubkey(value.contract);
+    writer.u128(value.upgrade_nonce);
+    writer.u128(value.authority_nonce);
+    writer.u128(value.key_nonce);
+    writer.bool(value.is_initialized);
+    encodeHashingMode(writer, value.hashing_mode);
+    writer.fixedBytes(value.domain, 32);
+    writer.option(value.signers_root, (item0) => { writer.fixedBytes(item0, 32); });
+    writer.option(value.upgrade_key, (item0) => { encodeAdminKey(writer, item0); });
+    writer.option(value.pending_key_change, (item0) => { encodePendingKeyChange(writer, item0); });
+    writer.u64(value.key_change_delay);
+    encodeCurve(writer, value.curve);
+    encodeHashAlgorithm(writer, value.hash_algorithm);
+    writer.option(value.guardian, (item0) => { encodeAdminKey(writer, item0); });
+    writer.u64(value.recovery_delay);
+    writer.option(value.pending_recovery, (item0) => { encodePendingRecovery(writer, item0); });
+    writer.bool(value.paused);
+    writer.vec(value.managed_contracts, (item0) => { writer.pubkey(item0); });
+    writer.u8(value.bump);
+    writer.fixedBytes(value.name, 32);
+    writer.string(value.uri);
+    writer.bool(value.active);
+    writer.option(value.expires_at_slot, (item0) => { writer.u64(item0); });
+    writer.vec(value.executors, (item0) => { writer.pubkey(item0); });
+    writer.u64(value.reimbursement);
+    writer.u64(value.min_delay_slots);
+    writer.u64(value.min_upgrade_interval_slots);
+    writer.option(value.last_upgrade_slot, (item0) => { writer.u64(item0); });
+    writer.bool(value.immutable);
+    writer.vec(value.roles, (item0) => { encodeRoleAssignment(writer, item0); });
+    writer.u8(value.approval_threshold);
+    writer.option(value.veto_key, (item0) => { encodeRoleKey(writer, item0); });
+    writer.vec(value.healthchecks, (item0) => { encodeHealthcheck(writer, item0); });
+    writer.option(value.unverified_upgrade, (item0) => { writer.pubkey(item0); });
+    writer.vec(value.verified_builds, (item0) => { encodeVerifiedBuild(writer, item0); });
+    encodeNonceWidth(writer, value.nonce_width);
+}
+
+export function decodeUpgradeAdmin(reader: BorshReader): UpgradeAdmin {
+    return {
+        version: reader.u8(),
+        public_keys: reader.vec<AdminKey>(() => decodeAdminKey(reader)),
+        weights: reader.bytes(),
+        threshold: reader.u8(),
+        contract: reader.pubkey(),
+        upgrade_nonce: reader.u128(),
+        authority_nonce: reader.u128(),
+        key_nonce: reader.u128(),
+        is_initialized: reader.bool(),
+        hashing_mode: decodeHashingMode(reader),
+        domain: reader.fixedBytes(32),
+        signers_root: reader.option<Uint8Array>(() => reader.fixedBytes(32)),
+        upgrade_key: reader.option<AdminKey>(() => decodeAdminKey(reader)),
+        pending_key_change: reader.option<PendingKeyChange>(() => decodePendingKeyChange(reader)),
+        key_change_delay: reader.u64(),
+        curve: decodeCurve(reader),
+        hash_algorithm: decodeHashAlgorithm(reader),
+        guardian: reader.option<AdminKey>(() => decodeAdminKey(reader)),
+        recovery_delay: reader.u64(),
+        pending_recovery: reader.option<PendingRecovery>(() => decodePendingRecovery(reader)),
+        paused: reader.bool(),
+        managed_contracts: reader.vec<PublicKey>(() => reader.pubkey()),
+        bump: reader.u8(),
+        name: reader.fixedBytes(32),
+        uri: reader.string(),
+        active: reader.bool(),
+        expires_at_slot: reader.option<bigint>(() => reader.u64()),
+        executors: reader.vec<PublicKey>(() => reader.pubkey()),
+        reimbursement: reader.u64(),
+        min_delay_slots: reader.u64(),
+        min_upgrade_interval_slots: reader.u64(),
+        last_upgrade_slot: reader.option<bigint>(() => reader.u64()),
+        immutable: reader.bool(),
+        roles: reader.vec<RoleAssignment>(() => decodeRoleAssignment(reader)),
+        approval_threshold: reader.u8(),
+        veto_key: reader.option<RoleKey>(() => decodeRoleKey(reader)),
+        healthchecks: reader.vec<Healthcheck>(() => decodeHealthcheck(reader)),
+        unverified_upgrade: reader.option<PublicKey>(() => reader.pubkey()),
+        verified_builds: reader.vec<VerifiedBuild>(() => decodeVerifiedBuild(reader)),
+        nonce_width: decodeNonceWidth(reader),
+    };
+}
+
+/** UpgradeAdmin layout of the first program release with single ECDSA key and shared nonce. */
+export interface UpgradeAdminV1 {
+    public_key: Uint8Array;
+    contract: PublicKey;
+    nonce: bigint;
+    is_initialized: boolean;
+}
+
+export function encodeUpgradeAdminV1(writer: BorshWriter, value: UpgradeAdminV1): void {
+    writer.fixedBytes(value.public_key, 64);
+    writer.pubkey(value.contract);
+    writer.u64(value.nonce);
+    writer.bool(value.is_initialized);
+}
+
+export function decodeUpgradeAdminV1(reader: BorshReader): UpgradeAdminV1 {
+    return {
+        public_key: reader.fixedBytes(64),
+        contract: reader.pubkey(),
+        nonce: reader.u64(),
+        is_initialized: reader.bool(),
+    };
+}
+
+/** Signer set change that can be activated after `activation_slot`. */
+export interface PendingKeyChange {
+    public_keys: AdminKey[];
+    weights: Uint8Array;
+    threshold: number;
+    signers_root: Uint8Array | null;
+    upgrade_key: AdminKey | null;
+    activation_slot: bigint;
+}
+
+export function encodePendingKeyChange(writer: BorshWriter, value: PendingKeyChange): void {
+    writer.vec(value.public_keys, (item0) => { encodeAdminKey(writer, item0); });
+    writer.bytes(value.weights);
+    writer.u8(value.threshold);
+    writer.option(value.signers_root, (item0) => { writer.fixedBytes(item0, 32); });
+    writer.option(value.upgrade_key, (item0) => { encodeAdminKey(writer, item0); });
+    writer.u64(value.activation_slot);
+}
+
+export function decodePendingKeyChange(reader: BorshReader): PendingKeyChange {
+    return {
+        public_keys: reader.vec<AdminKey>(() => decodeAdminKey(reader)),
+        weights: reader.bytes(),
+        threshold: reader.u8(),
+        signers_root: reader.option<Uint8Array>(() => reader.fixedBytes(32)),
+        upgrade_key: reader.option<AdminKey>(() => decodeAdminKey(reader)),
+        activation_slot: reader.u64(),
+    };
+}
+
+/** Signer set replacement started by the guardian that can be completed after `activation_slot`. */
+export interface PendingRecovery {
+    public_keys: AdminKey[];
+    weights: Uint8Array;
+    threshold: number;
+    activation_slot: bigint;
+}
+
+export function encodePendingRecovery(writer: BorshWriter, value: PendingRecovery): void {
+    writer.vec(value.public_keys, (item0) => { encodeAdminKey(writer, item0); });
+    writer.bytes(value.weights);
+    writer.u8(value.threshold);
+    writer.u64(value.activation_slot);
+}
+
+export function decodePendingRecovery(reader: BorshReader): PendingRecovery {
+    return {
+        public_keys: reader.vec<AdminKey>(() => decodeAdminKey(reader)),
+        weights: reader.bytes(),
+        threshold: reader.u8(),
+        activation_slot: reader.u64(),
+    };
+}
+
+/** Operation recorded in AdminHistory. */
+export type HistoryOperation = 'ChangePublicKey' | 'ChangeAuthority' | 'SetImmutable';
+
+export function encodeHistoryOperation(writer: BorshWriter, value: HistoryOperation): void {
+    const variant = (['ChangePublicKey', 'ChangeAuthority', 'SetImmutable'] as HistoryOperation[]).indexOf(value);
+    if (variant < 0) {
+        throw new Error(`unknown HistoryOperation variant ${value}`);
+    }
+    writer.u8(variant);
+}
+
+export function decodeHistoryOperation(reader: BorshReader): HistoryOperation {
+    const variants: HistoryOperation[] = ['ChangePublicKey', 'ChangeAuthority', 'SetImmutable'];
+    const variant = reader.u8();
+    if (variant >= variants.length) {
+        throw new Error(`invalid HistoryOperation variant ${variant}`);
+    }
+    return variants[variant];
+}
+
+export interface HistoryEntry {
+    slot: bigint;
+    operation: HistoryOperation;
+    contract: PublicKey;
+    old_value: Uint8Array;
+    new_value: Uint8Array;
+    signature_hash: Uint8Array;
+}
+
+export function encodeHistoryEntry(writer: BorshWriter, value: HistoryEntry): void {
+    writer.u64(value.slot);
+    encodeHistoryOperation(writer, value.operation);
+    writer.pubkey(value.contract);
+    writer.fixedBytes(value.old_value, 32);
+    writer.fixedBytes(value.new_value, 32);
+    writer.fixedBytes(value.signature_hash, 32);
+}
+
+export function decodeHistoryEntry(reader: BorshReader): HistoryEntry {
+    return {
+        slot: reader.u64(),
+        operation: decodeHistoryOperation(reader),
+        contract: reader.pubkey(),
+        old_value: reader.fixedBytes(32),
+        new_value: reader.fixedBytes(32),
+        signature_hash: reader.fixedBytes(32),
+    };
+}
+
+/** Append-only log of control changes of UpgradeAdmin. Keeps the last MAX_HISTORY_ENTRIES entries in a ring buffer. */
+export interface AdminHistory {
+    admin: PublicKey;
+    count: bigint;
+    entries: HistoryEntry[];
+    is_initialized: boolean;
+}
+
+export function encodeAdminHistory(writer: BorshWriter, value: AdminHistory): void {
+    writer.pubkey(value.admin);
+    writer.u64(value.count);
+    writer.vec(value.entries, (item0) => { encodeHistoryEntry(writer, item0); });
+    writer.bool(value.is_initialized);
+}
+
+export function decodeAdminHistory(reader: BorshReader): AdminHistory {
+    return {
+        admin: reader.pubkey(),
+        count: reader.u64(),
+        entries: reader.vec<HistoryEntry>(() => decodeHistoryEntry(reader)),
+        is_initialized: reader.bool(),
+    };
+}
+
+/** Executed upgrade recorded in UpgradeHistory. */
+export interface UpgradeRecord {
+    slot: bigint;
+    buffer: PublicKey;
+    code_hash: Uint8Array;
+    executor: PublicKey | null;
+    spill: PublicKey;
+}
+
+export function encodeUpgradeRecord(writer: BorshWriter, value: UpgradeRecord): void {
+    writer.u64(value.slot);
+    writer.pubkey(value.buffer);
+    writer.fixedBytes(value.code_hash, 32);
+    writer.option(value.executor, (item0) => { writer.pubkey(item0); });
+    writer.pubkey(value.spill);
+}
+
+export function decodeUpgradeRecord(reader: BorshReader): UpgradeRecord {
+    return {
+        slot: reader.u64(),
+        buffer: reader.pubkey(),
+        code_hash: reader.fixedBytes(32),
+        executor: reader.option<PublicKey>(() => reader.pubkey()),
+        spill: reader.pubkey(),
+    };
+}
+
+/** Deployment log of one contract managed by UpgradeAdmin. Keeps the last MAX_UPGRADE_RECORDS records in a ring buffer. */
+export interface UpgradeHistory {
+    admin: PublicKey;
+    program: PublicKey;
+    count: bigint;
+    records: UpgradeRecord[];
+    is_initialized: boolean;
+}
+
+export function encodeUpgradeHistory(writer: BorshWriter, value: UpgradeHistory): void {
+    writer.pubkey(value.admin);
+    writer.pubkey(value.program);
+    writer.u64(value.count);
+    writer.vec(value.records, (item0) => { encodeUpgradeRecord(writer, item0); });
+    writer.bool(value.is_initialized);
+}
+
+export function decodeUpgradeHistory(reader: BorshReader): UpgradeHistory {
+    return {
+        admin: reader.pubkey(),
+        program: reader.pubkey(),
+        count: reader.u64(),
+        records: reader.vec<UpgradeRecord>(() => decodeUpgradeRecord(reader)),
+        is_initialized: reader.bool(),
+    };
+}
+
+/** Keys that can never be installed into UpgradeAdmin again. */
+export interface RevokedKeys {
+    admin: PublicKey;
+    keys: AdminKey[];
+    is_initialized: boolean;
+}
+
+export function encodeRevokedKeys(writer: BorshWriter, value: RevokedKeys): void {
+    writer.pubkey(value.admin);
+    writer.vec(value.keys, (item0) => { encodeAdminKey(writer, item0); });
+    writer.bool(value.is_initialized);
+}
+
+export function decodeRevokedKeys(reader: BorshReader): RevokedKeys {
+    return {
+        admin: reader.pubkey(),
+        keys: reader.vec<AdminKey>(() => decodeAdminKey(reader)),
+        is_initialized: reader.bool(),
+    };
+}
+
+/** Operation hashes approved by VerifyBatch and waiting to be consumed by Upgrade instructions. */
+export interface BatchApproval {
+    admin: PublicKey;
+    authority_hash: Uint8Array;
+    operations: Uint8Array[];
+    expires_at: bigint;
+    is_initialized: boolean;
+}
+
+export function encodeBatchApproval(writer: BorshWriter, value: BatchApproval): void {
+    writer.pubkey(value.admin);
+    writer.fixedBytes(value.authority_hash, 32);
+    writer.vec(value.operations, (item0) => { writer.fixedBytes(item0, 32); });
+    writer.i64(value.expires_at);
+    writer.bool(value.is_initialized);
+}
+
+export function decodeBatchApproval(reader: BorshReader): BatchApproval {
+    return {
+        admin: reader.pubkey(),
+        authority_hash: reader.fixedBytes(32),
+        operations: reader.vec<Uint8Array>(() => reader.fixedBytes(32)),
+        expires_at: reader.i64(),
+        is_initialized: reader.bool(),
+    };
+}
+
+/** Upgrade approved by ProposeUpgrade and waiting to be performed by ExecuteUpgrade. */
+export interface PendingUpgrade {
+    admin: PublicKey;
+    program: PublicKey;
+    buffer: PublicKey;
+    code_hash: Uint8Array;
+    proposal_slot: bigint;
+    not_before_slot: bigint | null;
+    not_after_slot: bigint | null;
+    approvals: Uint8Array[];
+    is_initialized: boolean;
+}
+
+export function encodePendingUpgrade(writer: BorshWriter, value: PendingUpgrade): void {
+    writer.pubkey(value.admin);
+    writer.pubkey(value.program);
+    writer.pubkey(value.buffer);
+    writer.fixedBytes(value.code_hash, 32);
+    writer.u64(value.proposal_slot);
+    writer.option(value.not_before_slot, (item0) => { writer.u64(item0); });
+    writer.option(value.not_after_slot, (item0) => { writer.u64(item0); });
+    writer.vec(value.approvals, (item0) => { writer.fixedBytes(item0, 32); });
+    writer.bool(value.is_initialized);
+}
+
+export function decodePendingUpgrade(reader: BorshReader): PendingUpgrade {
+    return {
+        admin: reader.pubkey(),
+        program: reader.pubkey(),
+        buffer: reader.pubkey(),
+        code_hash: reader.fixedBytes(32),
+        proposal_slot: reader.u64(),
+        not_before_slot: reader.option<bigint>(() => reader.u64()),
+        not_after_slot: reader.option<bigint>(() => reader.u64()),
+        approvals: reader.vec<Uint8Array>(() => reader.fixedBytes(32)),
+        is_initialized: reader.bool(),
+    };
+}
+
+/** Known good program version registered by RegisterSnapshot that Rollback can restore. */
+export interface RollbackSnapshot {
+    admin: PublicKey;
+    program: PublicKey;
+    buffer: PublicKey;
+    code_hash: Uint8Array;
+    registered_slot: bigint;
+    is_initialized: boolean;
+}
+
+export function encodeRollbackSnapshot(writer: BorshWriter, value: RollbackSnapshot): void {
+    writer.pubkey(value.admin);
+    writer.pubkey(value.program);
+    writer.pubkey(value.buffer);
+    writer.fixedBytes(value.code_hash, 32);
+    writer.u64(value.registered_slot);
+    writer.bool(value.is_initialized);
+}
+
+export function decodeRollbackSnapshot(reader: BorshReader): RollbackSnapshot {
+    return {
+        admin: reader.pubkey(),
+        program: reader.pubkey(),
+        buffer: reader.pubkey(),
+        code_hash: reader.fixedBytes(32),
+        registered_slot: reader.u64(),
+        is_initialized: reader.bool(),
+    };
+}
+
+/** Progress of the buffer written by WriteBufferChunk with UpgradeAdmin as buffer authority. */
+export interface BufferStaging {
+    admin: PublicKey;
+    program: PublicKey;
+    buffer: PublicKey;
+    writer: PublicKey;
+    data_len: bigint;
+    written: bigint;
+    hasher: IncrementalSha256;
+    code_hash: Uint8Array;
+    finalized: boolean;
+    is_initialized: boolean;
+}
+
+export function encodeBufferStaging(writer: BorshWriter, value: BufferStaging): void {
+    writer.pubkey(value.admin);
+    writer.pubkey(value.program);
+    writer.pubkey(value.buffer);
+    writer.pubkey(value.writer);
+    writer.u64(value.data_len);
+    writer.u64(value.written);
+    encodeIncrementalSha256(writer, value.hasher);
+    writer.fixedBytes(value.code_hash, 32);
+    writer.bool(value.finalized);
+    writer.bool(value.is_initialized);
+}
+
+export function decodeBufferStaging(reader: BorshReader): BufferStaging {
+    return {
+        admin: reader.pubkey(),
+        program: reader.pubkey(),
+        buffer: reader.pubkey(),
+        writer: reader.pubkey(),
+        data_len: reader.u64(),
+        written: reader.u64(),
+        hasher: decodeIncrementalSha256(reader),
+        code_hash: reader.fixedBytes(32),
+        finalized: reader.bool(),
+        is_initialized: reader.bool(),
+    };
+}
+
+/** Short-lived key delegated by UpgradeAdmin signers to sign a limited set of operations. */
+export interface SessionKey {
+    admin: PublicKey;
+    key: AdminKey;
+    permissions: number;
+    expiry_slot: bigint;
+    is_initialized: boolean;
+}
+
+export function encodeSessionKey(writer: BorshWriter, value: SessionKey): void {
+    writer.pubkey(value.admin);
+    encodeAdminKey(writer, value.key);
+    writer.u8(value.permissions);
+    writer.u64(value.expiry_slot);
+    writer.bool(value.is_initialized);
+}
+
+export function decodeSessionKey(reader: BorshReader): SessionKey {
+    return {
+        admin: reader.pubkey(),
+        key: decodeAdminKey(reader),
+        permissions: reader.u8(),
+        expiry_slot: reader.u64(),
+        is_initialized: reader.bool(),
+    };
+}
+
+export interface EcdsaSignature {
+    signature: Uint8Array;
+    recovery_id: bigint;
+    personal_sign: boolean;
+}
+
+export function encodeEcdsaSignature(writer: BorshWriter, value: EcdsaSignature): void {
+    writer.fixedBytes(value.signature, 64);
+    writer.u64(value.recovery_id);
+    writer.bool(value.personal_sign);
+}
+
+export function decodeEcdsaSignature(reader: BorshReader): EcdsaSignature {
+    return {
+        signature: reader.fixedBytes(64),
+        recovery_id: reader.u64(),
+        personal_sign: reader.bool(),
+    };
+}
+
+/** Proof that signer key is included into the tree with root stored in UpgradeAdmin. */
+export interface SignerProof {
+    key: AdminKey;
+    proof: Uint8Array[];
+}
+
+export function encodeSignerProof(writer: BorshWriter, value: SignerProof): void {
+    encodeAdminKey(writer, value.key);
+    writer.vec(value.proof, (item0) => { writer.fixedBytes(item0, 32); });
+}
+
+export function decodeSignerProof(reader: BorshReader): SignerProof {
+    return {
+        key: decodeAdminKey(reader),
+        proof: reader.vec<Uint8Array>(() => reader.fixedBytes(32)),
+    };
+}
+
+export interface InitializeAdminArgs {
+    public_keys: AdminKey[];
+    weights: Uint8Array;
+    threshold: number;
+    contract: PublicKey;
+    hashing_mode: HashingMode;
+    domain: Uint8Array;
+    signers_root: Uint8Array | null;
+    upgrade_key: AdminKey | null;
+    key_change_delay: bigint;
+    curve: Curve;
+    hash_algorithm: HashAlgorithm;
+    guardian: AdminKey | null;
+    recovery_delay: bigint;
+    managed_contracts: PublicKey[];
+    expires_at_slot: bigint | null;
+    executors: PublicKey[];
+    reimbursement: bigint;
+    min_delay_slots: bigint;
+    min_upgrade_interval_slots: bigint;
+    nonce_width: NonceWidth;
+}
+
+export function encodeInitializeAdminArgs(writer: BorshWriter, value: InitializeAdminArgs): void {
+    writer.vec(value.public_keys, (item0) => { encodeAdminKey(writer, item0); });
+    writer.bytes(value.weights);
+    writer.u8(value.threshold);
+    writer.pubkey(value.contract);
+    encodeHashingMode(writer, value.hashing_mode);
+    writer.fixedBytes(value.domain, 32);
+    writer.option(value.signers_root, (item0) => { writer.fixedBytes(item0, 32); });
+    writer.option(value.upgrade_key, (item0) => { encodeAdminKey(writer, item0); });
+    writer.u64(value.key_change_delay);
+    encodeCurve(writer, value.curve);
+    encodeHashAlgorithm(writer, value.hash_algorithm);
+    writer.option(value.guardian, (item0) => { encodeAdminKey(writer, item0); });
+    writer.u64(value.recovery_delay);
+    writer.vec(value.managed_contracts, (item0) => { writer.pubkey(item0); });
+    writer.option(value.expires_at_slot, (item0) => { writer.u64(item0); });
+    writer.vec(value.executors, (item0) => { writer.pubkey(item0); });
+    writer.u64(value.reimbursement);
+    writer.u64(value.min_delay_slots);
+    writer.u64(value.min_upgrade_interval_slots);
+    encodeNonceWidth(writer, value.nonce_width);
+}
+
+export function decodeInitializeAdminArgs(reader: BorshReader): InitializeAdminArgs {
+    return {
+        public_keys: reader.vec<AdminKey>(() => decodeAdminKey(reader)),
+        weights: reader.bytes(),
+        threshold: reader.u8(),
+        contract: reader.pubkey(),
+        hashing_mode: decodeHashingMode(reader),
+        domain: reader.fixedBytes(32),
+        signers_root: reader.option<Uint8Array>(() => reader.fixedBytes(32)),
+        upgrade_key: reader.option<AdminKey>(() => decodeAdminKey(reader)),
+        key_change_delay: reader.u64(),
+        curve: decodeCurve(reader),
+        hash_algorithm: decodeHashAlgorithm(reader),
+        guardian: reader.option<AdminKey>(() => decodeAdminKey(reader)),
+        recovery_delay: reader.u64(),
+        managed_contracts: reader.vec<PublicKey>(() => reader.pubkey()),
+        expires_at_slot: reader.option<bigint>(() => reader.u64()),
+        executors: reader.vec<PublicKey>(() => reader.pubkey()),
+        reimbursement: reader.u64(),
+        min_delay_slots: reader.u64(),
+        min_upgrade_interval_slots: reader.u64(),
+        nonce_width: decodeNonceWidth(reader),
+    };
+}
+
+export interface ChangePublicKeyArgs {
+    new_public_keys: AdminKey[];
+    new_weights: Uint8Array;
+    new_threshold: number;
+    new_signers_root: Uint8Array | null;
+    new_upgrade_key: AdminKey | null;
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeChangePublicKeyArgs(writer: BorshWriter, value: ChangePublicKeyArgs): void {
+    writer.vec(value.new_public_keys, (item0) => { encodeAdminKey(writer, item0); });
+    writer.bytes(value.new_weights);
+    writer.u8(value.new_threshold);
+    writer.option(value.new_signers_root, (item0) => { writer.fixedBytes(item0, 32); });
+    writer.option(value.new_upgrade_key, (item0) => { encodeAdminKey(writer, item0); });
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeChangePublicKeyArgs(reader: BorshReader): ChangePublicKeyArgs {
+    return {
+        new_public_keys: reader.vec<AdminKey>(() => decodeAdminKey(reader)),
+        new_weights: reader.bytes(),
+        new_threshold: reader.u8(),
+        new_signers_root: reader.option<Uint8Array>(() => reader.fixedBytes(32)),
+        new_upgrade_key: reader.option<AdminKey>(() => decodeAdminKey(reader)),
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface ChangeAuthorityArgs {
+    contract: PublicKey;
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeChangeAuthorityArgs(writer: BorshWriter, value: ChangeAuthorityArgs): void {
+    writer.pubkey(value.contract);
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeChangeAuthorityArgs(reader: BorshReader): ChangeAuthorityArgs {
+    return {
+        contract: reader.pubkey(),
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface UpgradeArgs {
+    nonce: bigint;
+    code_hash: Uint8Array;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeUpgradeArgs(writer: BorshWriter, value: UpgradeArgs): void {
+    writer.u128(value.nonce);
+    writer.fixedBytes(value.code_hash, 32);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeUpgradeArgs(reader: BorshReader): UpgradeArgs {
+    return {
+        nonce: reader.u128(),
+        code_hash: reader.fixedBytes(32),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface CancelKeyChangeArgs {
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeCancelKeyChangeArgs(writer: BorshWriter, value: CancelKeyChangeArgs): void {
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeCancelKeyChangeArgs(reader: BorshReader): CancelKeyChangeArgs {
+    return {
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface RevokeKeysArgs {
+    keys: AdminKey[];
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeRevokeKeysArgs(writer: BorshWriter, value: RevokeKeysArgs): void {
+    writer.vec(value.keys, (item0) => { encodeAdminKey(writer, item0); });
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeRevokeKeysArgs(reader: BorshReader): RevokeKeysArgs {
+    return {
+        keys: reader.vec<AdminKey>(() => decodeAdminKey(reader)),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface VerifyBatchArgs {
+    operation_hashes: Uint8Array[];
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeVerifyBatchArgs(writer: BorshWriter, value: VerifyBatchArgs): void {
+    writer.vec(value.operation_hashes, (item0) => { writer.fixedBytes(item0, 32); });
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeVerifyBatchArgs(reader: BorshReader): VerifyBatchArgs {
+    return {
+        operation_hashes: reader.vec<Uint8Array>(() => reader.fixedBytes(32)),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface CreateSessionArgs {
+    session_key: AdminKey;
+    permissions: number;
+    expiry_slot: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeCreateSessionArgs(writer: BorshWriter, value: CreateSessionArgs): void {
+    encodeAdminKey(writer, value.session_key);
+    writer.u8(value.permissions);
+    writer.u64(value.expiry_slot);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeCreateSessionArgs(reader: BorshReader): CreateSessionArgs {
+    return {
+        session_key: decodeAdminKey(reader),
+        permissions: reader.u8(),
+        expiry_slot: reader.u64(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface StartRecoveryArgs {
+    new_public_keys: AdminKey[];
+    new_weights: Uint8Array;
+    new_threshold: number;
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    expires_at: bigint;
+}
+
+export function encodeStartRecoveryArgs(writer: BorshWriter, value: StartRecoveryArgs): void {
+    writer.vec(value.new_public_keys, (item0) => { encodeAdminKey(writer, item0); });
+    writer.bytes(value.new_weights);
+    writer.u8(value.new_threshold);
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeStartRecoveryArgs(reader: BorshReader): StartRecoveryArgs {
+    return {
+        new_public_keys: reader.vec<AdminKey>(() => decodeAdminKey(reader)),
+        new_weights: reader.bytes(),
+        new_threshold: reader.u8(),
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface CancelRecoveryArgs {
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeCancelRecoveryArgs(writer: BorshWriter, value: CancelRecoveryArgs): void {
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeCancelRecoveryArgs(reader: BorshReader): CancelRecoveryArgs {
+    return {
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface CloseAdminArgs {
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeCloseAdminArgs(writer: BorshWriter, value: CloseAdminArgs): void {
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeCloseAdminArgs(reader: BorshReader): CloseAdminArgs {
+    return {
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface PauseArgs {
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodePauseArgs(writer: BorshWriter, value: PauseArgs): void {
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodePauseArgs(reader: BorshReader): PauseArgs {
+    return {
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetManagedContractsArgs {
+    contracts: PublicKey[];
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetManagedContractsArgs(writer: BorshWriter, value: SetManagedContractsArgs): void {
+    writer.vec(value.contracts, (item0) => { writer.pubkey(item0); });
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetManagedContractsArgs(reader: BorshReader): SetManagedContractsArgs {
+    return {
+        contracts: reader.vec<PublicKey>(() => reader.pubkey()),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetMetadataArgs {
+    name: Uint8Array;
+    uri: string;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetMetadataArgs(writer: BorshWriter, value: SetMetadataArgs): void {
+    writer.fixedBytes(value.name, 32);
+    writer.string(value.uri);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetMetadataArgs(reader: BorshReader): SetMetadataArgs {
+    return {
+        name: reader.fixedBytes(32),
+        uri: reader.string(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface DeactivateArgs {
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeDeactivateArgs(writer: BorshWriter, value: DeactivateArgs): void {
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeDeactivateArgs(reader: BorshReader): DeactivateArgs {
+    return {
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface ExtendExpiryArgs {
+    expires_at_slot: bigint | null;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeExtendExpiryArgs(writer: BorshWriter, value: ExtendExpiryArgs): void {
+    writer.option(value.expires_at_slot, (item0) => { writer.u64(item0); });
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeExtendExpiryArgs(reader: BorshReader): ExtendExpiryArgs {
+    return {
+        expires_at_slot: reader.option<bigint>(() => reader.u64()),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetExecutorsArgs {
+    executors: PublicKey[];
+    reimbursement: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetExecutorsArgs(writer: BorshWriter, value: SetExecutorsArgs): void {
+    writer.vec(value.executors, (item0) => { writer.pubkey(item0); });
+    writer.u64(value.reimbursement);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetExecutorsArgs(reader: BorshReader): SetExecutorsArgs {
+    return {
+        executors: reader.vec<PublicKey>(() => reader.pubkey()),
+        reimbursement: reader.u64(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetUpgradeDelayArgs {
+    min_delay_slots: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetUpgradeDelayArgs(writer: BorshWriter, value: SetUpgradeDelayArgs): void {
+    writer.u64(value.min_delay_slots);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetUpgradeDelayArgs(reader: BorshReader): SetUpgradeDelayArgs {
+    return {
+        min_delay_slots: reader.u64(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetUpgradeIntervalArgs {
+    min_upgrade_interval_slots: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetUpgradeIntervalArgs(writer: BorshWriter, value: SetUpgradeIntervalArgs): void {
+    writer.u64(value.min_upgrade_interval_slots);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetUpgradeIntervalArgs(reader: BorshReader): SetUpgradeIntervalArgs {
+    return {
+        min_upgrade_interval_slots: reader.u64(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface ProposeUpgradeArgs {
+    nonce: bigint;
+    code_hash: Uint8Array;
+    not_before_slot: bigint | null;
+    not_after_slot: bigint | null;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeProposeUpgradeArgs(writer: BorshWriter, value: ProposeUpgradeArgs): void {
+    writer.u128(value.nonce);
+    writer.fixedBytes(value.code_hash, 32);
+    writer.option(value.not_before_slot, (item0) => { writer.u64(item0); });
+    writer.option(value.not_after_slot, (item0) => { writer.u64(item0); });
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeProposeUpgradeArgs(reader: BorshReader): ProposeUpgradeArgs {
+    return {
+        nonce: reader.u128(),
+        code_hash: reader.fixedBytes(32),
+        not_before_slot: reader.option<bigint>(() => reader.u64()),
+        not_after_slot: reader.option<bigint>(() => reader.u64()),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface CancelUpgradeArgs {
+    nonce: bigint;
+    close_buffer: boolean;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeCancelUpgradeArgs(writer: BorshWriter, value: CancelUpgradeArgs): void {
+    writer.u128(value.nonce);
+    writer.bool(value.close_buffer);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeCancelUpgradeArgs(reader: BorshReader): CancelUpgradeArgs {
+    return {
+        nonce: reader.u128(),
+        close_buffer: reader.bool(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface RegisterSnapshotArgs {
+    code_hash: Uint8Array;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeRegisterSnapshotArgs(writer: BorshWriter, value: RegisterSnapshotArgs): void {
+    writer.fixedBytes(value.code_hash, 32);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeRegisterSnapshotArgs(reader: BorshReader): RegisterSnapshotArgs {
+    return {
+        code_hash: reader.fixedBytes(32),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface RollbackArgs {
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    expires_at: bigint;
+}
+
+export function encodeRollbackArgs(writer: BorshWriter, value: RollbackArgs): void {
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeRollbackArgs(reader: BorshReader): RollbackArgs {
+    return {
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface BatchUpgradeArgs {
+    nonce: bigint;
+    code_hashes: Uint8Array[];
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeBatchUpgradeArgs(writer: BorshWriter, value: BatchUpgradeArgs): void {
+    writer.u128(value.nonce);
+    writer.vec(value.code_hashes, (item0) => { writer.fixedBytes(item0, 32); });
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeBatchUpgradeArgs(reader: BorshReader): BatchUpgradeArgs {
+    return {
+        nonce: reader.u128(),
+        code_hashes: reader.vec<Uint8Array>(() => reader.fixedBytes(32)),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetImmutableArgs {
+    contract: PublicKey;
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetImmutableArgs(writer: BorshWriter, value: SetImmutableArgs): void {
+    writer.pubkey(value.contract);
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetImmutableArgs(reader: BorshReader): SetImmutableArgs {
+    return {
+        contract: reader.pubkey(),
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface DeployArgs {
+    max_data_len: bigint;
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeDeployArgs(writer: BorshWriter, value: DeployArgs): void {
+    writer.u64(value.max_data_len);
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeDeployArgs(reader: BorshReader): DeployArgs {
+    return {
+        max_data_len: reader.u64(),
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface ExtendProgramArgs {
+    additional_bytes: number;
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeExtendProgramArgs(writer: BorshWriter, value: ExtendProgramArgs): void {
+    writer.u32(value.additional_bytes);
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeExtendProgramArgs(reader: BorshReader): ExtendProgramArgs {
+    return {
+        additional_bytes: reader.u32(),
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface CreateBufferArgs {
+    writer: PublicKey;
+    data_len: bigint;
+    code_hash: Uint8Array;
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeCreateBufferArgs(writer: BorshWriter, value: CreateBufferArgs): void {
+    writer.pubkey(value.writer);
+    writer.u64(value.data_len);
+    writer.fixedBytes(value.code_hash, 32);
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeCreateBufferArgs(reader: BorshReader): CreateBufferArgs {
+    return {
+        writer: reader.pubkey(),
+        data_len: reader.u64(),
+        code_hash: reader.fixedBytes(32),
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface WriteBufferChunkArgs {
+    offset: number;
+    bytes: Uint8Array;
+}
+
+export function encodeWriteBufferChunkArgs(writer: BorshWriter, value: WriteBufferChunkArgs): void {
+    writer.u32(value.offset);
+    writer.bytes(value.bytes);
+}
+
+export function decodeWriteBufferChunkArgs(reader: BorshReader): WriteBufferChunkArgs {
+    return {
+        offset: reader.u32(),
+        bytes: reader.bytes(),
+    };
+}
+
+export interface CloseBufferArgs {
+    nonce: bigint;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeCloseBufferArgs(writer: BorshWriter, value: CloseBufferArgs): void {
+    writer.u128(value.nonce);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeCloseBufferArgs(reader: BorshReader): CloseBufferArgs {
+    return {
+        nonce: reader.u128(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetRolesArgs {
+    roles: RoleAssignment[];
+    approval_threshold: number;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetRolesArgs(writer: BorshWriter, value: SetRolesArgs): void {
+    writer.vec(value.roles, (item0) => { encodeRoleAssignment(writer, item0); });
+    writer.u8(value.approval_threshold);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetRolesArgs(reader: BorshReader): SetRolesArgs {
+    return {
+        roles: reader.vec<RoleAssignment>(() => decodeRoleAssignment(reader)),
+        approval_threshold: reader.u8(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface ApproveUpgradeArgs {
+    signatures: EcdsaSignature[];
+    expires_at: bigint;
+}
+
+export function encodeApproveUpgradeArgs(writer: BorshWriter, value: ApproveUpgradeArgs): void {
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeApproveUpgradeArgs(reader: BorshReader): ApproveUpgradeArgs {
+    return {
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetVetoKeyArgs {
+    veto_key: RoleKey | null;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetVetoKeyArgs(writer: BorshWriter, value: SetVetoKeyArgs): void {
+    writer.option(value.veto_key, (item0) => { encodeRoleKey(writer, item0); });
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetVetoKeyArgs(reader: BorshReader): SetVetoKeyArgs {
+    return {
+        veto_key: reader.option<RoleKey>(() => decodeRoleKey(reader)),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface VetoUpgradeArgs {
+    signatures: EcdsaSignature[];
+    expires_at: bigint;
+}
+
+export function encodeVetoUpgradeArgs(writer: BorshWriter, value: VetoUpgradeArgs): void {
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeVetoUpgradeArgs(reader: BorshReader): VetoUpgradeArgs {
+    return {
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetHealthcheckArgs {
+    contract: PublicKey;
+    data: Uint8Array | null;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetHealthcheckArgs(writer: BorshWriter, value: SetHealthcheckArgs): void {
+    writer.pubkey(value.contract);
+    writer.option(value.data, (item0) => { writer.bytes(item0); });
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetHealthcheckArgs(reader: BorshReader): SetHealthcheckArgs {
+    return {
+        contract: reader.pubkey(),
+        data: reader.option<Uint8Array>(() => reader.bytes()),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export interface SetVerifiedBuildHashArgs {
+    contract: PublicKey;
+    build_hash: Uint8Array | null;
+    required: boolean;
+    signatures: EcdsaSignature[];
+    signer_proofs: SignerProof[];
+    expires_at: bigint;
+}
+
+export function encodeSetVerifiedBuildHashArgs(writer: BorshWriter, value: SetVerifiedBuildHashArgs): void {
+    writer.pubkey(value.contract);
+    writer.option(value.build_hash, (item0) => { writer.fixedBytes(item0, 32); });
+    writer.bool(value.required);
+    writer.vec(value.signatures, (item0) => { encodeEcdsaSignature(writer, item0); });
+    writer.vec(value.signer_proofs, (item0) => { encodeSignerProof(writer, item0); });
+    writer.i64(value.expires_at);
+}
+
+export function decodeSetVerifiedBuildHashArgs(reader: BorshReader): SetVerifiedBuildHashArgs {
+    return {
+        contract: reader.pubkey(),
+        build_hash: reader.option<Uint8Array>(() => reader.fixedBytes(32)),
+        required: reader.bool(),
+        signatures: reader.vec<EcdsaSignature>(() => decodeEcdsaSignature(reader)),
+        signer_proofs: reader.vec<SignerProof>(() => decodeSignerProof(reader)),
+        expires_at: reader.i64(),
+    };
+}
+
+export type UpgradeInstruction =
+    /**
+     * Initialize new UpgradeAdmin that will be an authority for target upgradable program.
+     * Admin is a set of signers with threshold: every operation should be signed by at least `threshold` of them.
+     * Every stored signer can carry a voting weight, then `threshold` is a quorum: cumulative weight of signers
+     * required to authorize operation. Without weights every signer has weight 1.
+     * Signer can be identified by 64 byte or 33 byte compressed ECDSA public key, by 20 byte Ethereum address
+     * or by ed25519 public key.
+     * Besides the stored signers, UpgradeAdmin can commit a merkle root of eligible signers: such signers
+     * provide merkle membership proof together with the signature in every operation.
+     * Optional operational hot `upgrade_key` authorizes Upgrade alone, while ChangePublicKey and ChangeAuthority
+     * always require the signer set.
+     * Ed25519 signers sign the same Keccak Hash in `ed25519_program` instruction preceding the current one.
+     * ECDSA signers can either pass signatures in instruction args or verify them with `secp256k1_program`
+     * instruction preceding the current one, with the hashed message as signed data.
+     * Depending on `hashing_mode` signers sign either legacy Keccak Hash described below or EIP-712 typed data hash.
+     * ECDSA signers can also sign EIP-191 `personal_sign` message of that hash to use standard Ethereum wallets.
+     * With `Secp256r1` curve ECDSA signers are compressed P-256 keys (e.g. HSMs or passkeys) that sign the hash
+     * in `secp256r1_program` instruction preceding the current one.
+     * Legacy hash is Keccak-256 or SHA-256 depending on `hash_algorithm`. SHA-256 digests can not be verified
+     * by `secp256k1_program` because it always hashes the message with Keccak-256.
+     * Optional secp256k1 `guardian` key can replace the signer set with StartRecovery if signer keys are lost.
+     * Besides the primary `contract` that seeds the admin address, the admin can manage up to MAX_MANAGED_CONTRACTS
+     * other contracts with the same signers and nonces. Their upgrade authority should be set to the admin address.
+     * With `expires_at_slot` set, every operation except ExtendExpiry is rejected after that slot.
+     * With `U128` nonce width the nonces are not limited by u64::MAX, legacy hashes take them as 16 big-endian bytes
+     * and EIP-712 types declare them as `uint128 nonce`.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account to initialize
+     *   1. `[writable,signer]` The fee payer
+     *   2. `[]` System program
+     *   3. `[]` Rent sysvar
+     *   4. `[writable]` The AdminHistory account to initialize
+     */
+    | { kind: 'InitializeAdmin'; value: InitializeAdminArgs }
+    /**
+     * Stage signer set and hot key change in UpgradeAdmin. New signers can be activated by ActivateKey
+     * after `key_change_delay` slots, until then the change can be canceled by CancelKeyChange. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, new_threshold, new_public_keys.borsh_bytes, new_weights.borsh_bytes, new_signers_root.borsh_bytes, new_upgrade_key.borsh_bytes, expires_at]`
+     * should be signed by old signers with cumulative weight of at least `threshold` to perform that operation.
+     * New keys should not be present in the RevokedKeys list. The change is recorded in AdminHistory.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` The RevokedKeys account (may be not created yet)
+     *   2. `[writable]` The AdminHistory account
+     *   3. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'ChangePublicKey'; value: ChangePublicKeyArgs }
+    /**
+     * Change upgrade authority of the primary or managed contract. The Keccak Hash of `[contract, authority_nonce, "solana-upgrade-program".bytes, program_id, domain, "change-authority".bytes, new_authority, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation. The transfer is recorded in AdminHistory.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The ProgramData account of the contract
+     *   2. `[]` The new authority account
+     *   3. `[]` BPFLoaderUpgradable program
+     *   4. `[writable]` The AdminHistory account
+     *   5. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'ChangeAuthority'; value: ChangeAuthorityArgs }
+    /**
+     * Upgrade the primary or managed contract. The Keccak Hash of `[upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, buffer_address, code_hash, spill, expires_at]`
+     * should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+     * Instead of signatures the hash can be approved in advance by VerifyBatch, then BatchApproval account is passed.
+     * Fails if `min_upgrade_interval_slots` of UpgradeAdmin have not passed since the last upgrade, or if UpgradeAdmin
+     * has upgrade delay, roles or veto key, then the upgrade should go through ProposeUpgrade and ExecuteUpgrade.
+     * If UpgradeAdmin has executors allowlist, one of the executors should sign the transaction. The executor receives
+     * `reimbursement` lamports from the admin account if the admin stays rent exempt after the payment.
+     * Buffer staged by CreateBuffer should be finalized, its code hash is taken from BufferStaging instead of hashing
+     * the buffer again.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The ProgramData account.
+     *   2. `[writable]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
+     *   3. `[writable]` The Buffer account where the program data has been
+     *      written.  The buffer account's authority must match the program's
+     *      authority. SHA-256 of the program data should be equal to `code_hash`
+     *   4. `[writable]` The spill account.
+     *   5. `[]` Rent sysvar.
+     *   6. `[]` Clock sysvar.
+     *   7. `[]` BPFLoaderUpgradable program
+     *   8. `[writable]` The UpgradeHistory account of the upgraded contract
+     *   9. `[writable]` The BufferStaging account of the buffer, closed to the spill account if the buffer is staged
+     *  10. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     *  11. `[writable]` The BatchApproval account (optional, consumed instead of signatures)
+     *  12. `[]` The SessionKey account (optional, session key signs instead of UpgradeAdmin signers)
+     *  13. `[writable,signer]` The executor (optional, required if UpgradeAdmin has executors allowlist)
+     */
+    | { kind: 'Upgrade'; value: UpgradeArgs }
+    /**
+     * Activate signer set change staged by ChangePublicKey after its activation slot.
+     * New keys should not be present in the RevokedKeys list.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` The RevokedKeys account (may be not created yet)
+     */
+    | { kind: 'ActivateKey' }
+    /**
+     * Cancel signer set change staged by ChangePublicKey. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "cancel-key-change".bytes, expires_at]`
+     * should be signed by at least `threshold` of current signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'CancelKeyChange'; value: CancelKeyChangeArgs }
+    /**
+     * Append keys to the RevokedKeys list of UpgradeAdmin, so they can never be installed by ChangePublicKey again.
+     * The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "revoke-keys".bytes, keys.borsh_bytes, expires_at]`
+     * should be signed by at least `threshold` of current signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The RevokedKeys account, created if it does not exist
+     *   2. `[writable,signer]` The fee payer
+     *   3. `[]` System program
+     *   4. `[]` Rent sysvar
+     *   5. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'RevokeKeys'; value: RevokeKeysArgs }
+    /**
+     * Approve several operations with one signature. The Keccak Hash of `[target_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "verify-batch".bytes, operations_root, expires_at]`
+     * should be signed by the same signers as Upgrade (hot `upgrade_key` if it is set). `operations_root` is the merkle root
+     * of `operation_hashes`. Approved hashes are stored in the BatchApproval account replacing the previous batch and can be
+     * consumed by Upgrade of any UpgradeAdmin with the same upgrade authority. Upgrade hashes of this UpgradeAdmin itself
+     * should be computed with the upgrade nonce incremented by VerifyBatch.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The BatchApproval account, created if it does not exist
+     *   2. `[writable,signer]` The fee payer
+     *   3. `[]` System program
+     *   4. `[]` Rent sysvar
+     *   5. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'VerifyBatch'; value: VerifyBatchArgs }
+    /**
+     * Delegate short-lived session key that can sign operations allowed by `permissions` bitmask until `expiry_slot`.
+     * Only Upgrade can be delegated. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "create-session".bytes, session_key.borsh_bytes, permissions, expiry_slot, expires_at]`
+     * should be signed by at least `threshold` of stored signers. Previous session key of UpgradeAdmin is replaced.
+     * Session key should not be present in the RevokedKeys list.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` The RevokedKeys account (may be not created yet)
+     *   2. `[writable]` The SessionKey account, created if it does not exist
+     *   3. `[writable,signer]` The fee payer
+     *   4. `[]` System program
+     *   5. `[]` Rent sysvar
+     *   6. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'CreateSession'; value: CreateSessionArgs }
+    /**
+     * Start replacement of the signer set by the guardian. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "start-recovery".bytes, new_threshold, new_public_keys.borsh_bytes, new_weights.borsh_bytes, expires_at]`
+     * should be signed by the guardian secp256k1 key. Recovery can be completed by CompleteRecovery after `recovery_delay` slots,
+     * until then it can be canceled by current signers with CancelRecovery. New keys should not be present in the RevokedKeys list.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` The RevokedKeys account (may be not created yet)
+     *   2. `[]` Instructions sysvar (optional, required for secp256k1_program signature)
+     */
+    | { kind: 'StartRecovery'; value: StartRecoveryArgs }
+    /**
+     * Complete recovery started by StartRecovery after its activation slot. Signer set is replaced, merkle signers root,
+     * hot upgrade key and pending key change are dropped because they could be controlled by the lost keys.
+     * New keys should not be present in the RevokedKeys list.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` The RevokedKeys account (may be not created yet)
+     */
+    | { kind: 'CompleteRecovery' }
+    /**
+     * Cancel recovery started by StartRecovery. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "cancel-recovery".bytes, expires_at]`
+     * should be signed by at least `threshold` of current signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'CancelRecovery'; value: CancelRecoveryArgs }
+    /**
+     * Close UpgradeAdmin that is no longer the upgrade authority of the contract and send its lamports to the destination.
+     * Managed contracts should be removed by SetManagedContracts first.
+     * The Keccak Hash of `[target_contract, authority_nonce, "solana-upgrade-program".bytes, program_id, domain, "close-admin".bytes, destination, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` The ProgramData account
+     *   2. `[writable]` The destination account for reclaimed lamports
+     *   3. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'CloseAdmin'; value: CloseAdminArgs }
+    /**
+     * Rewrite UpgradeAdmin account of V1 layout to the current one. The account is reallocated and rent difference is paid
+     * by the fee payer. V1 public key becomes the only signer with threshold 1 and all operation nonces continue from
+     * V1 nonce. Migration does not change who controls the admin, so it requires no signatures.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable,signer]` The fee payer
+     *   2. `[]` System program
+     *   3. `[]` Rent sysvar
+     *   4. `[writable]` The AdminHistory account to initialize
+     */
+    | { kind: 'MigrateAdmin' }
+    /**
+     * Freeze Upgrade, ChangeAuthority, VerifyBatch, CreateSession and CloseAdmin during an incident without rotating keys.
+     * Signer set management and recovery stay available. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "pause".bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'Pause'; value: PauseArgs }
+    /**
+     * Lift the freeze set by Pause. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "unpause".bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'Unpause'; value: PauseArgs }
+    /**
+     * Replace the list of contracts managed besides the primary one. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-managed-contracts".bytes, contracts.borsh_bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     * UpgradeAdmin should not be the upgrade authority of removed contracts, their ProgramData accounts are passed to check that.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     *   2. `[]` ProgramData accounts of removed contracts
+     */
+    | { kind: 'SetManagedContracts'; value: SetManagedContractsArgs }
+    /**
+     * Set human-readable name and off-chain URI of UpgradeAdmin for explorers and dashboards. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-metadata".bytes, name, uri.borsh_bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'SetMetadata'; value: SetMetadataArgs }
+    /**
+     * Temporarily retire UpgradeAdmin: every operation except Reactivate is rejected, nonces and other state are kept.
+     * The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "deactivate".bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'Deactivate'; value: DeactivateArgs }
+    /**
+     * Bring deactivated UpgradeAdmin back. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "reactivate".bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'Reactivate'; value: DeactivateArgs }
+    /**
+     * Move expiry slot of UpgradeAdmin later or remove it. Allowed after the admin expired, so signers can renew lapsed authority.
+     * The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "extend-expiry".bytes, expires_at_slot.borsh_bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'ExtendExpiry'; value: ExtendExpiryArgs }
+    /**
+     * Replace executors allowlist and reimbursement of Upgrade. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-executors".bytes, executors.borsh_bytes, reimbursement, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'SetExecutors'; value: SetExecutorsArgs }
+    /**
+     * Record upgrade of the primary or managed contract in PendingUpgrade account, so the buffer can be inspected
+     * before ExecuteUpgrade. The buffer authority should already be UpgradeAdmin, so the buffer can not be changed
+     * after `code_hash` is checked. Buffer staged by CreateBuffer should be finalized. The Keccak Hash of `[upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "propose-upgrade".bytes, buffer_address, code_hash, not_before_slot.borsh_bytes, not_after_slot.borsh_bytes, expires_at]`
+     * should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+     * If UpgradeAdmin has roles, the hash should be signed by a proposer key instead or a proposer should sign the transaction.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The PendingUpgrade account
+     *   2. `[]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
+     *   3. `[]` The Buffer account where the program data has been written.
+     *   4. `[writable,signer]` Fee payer
+     *   5. `[]` System program
+     *   6. `[]` Rent sysvar
+     *   7. `[]` The BufferStaging account of the buffer
+     *   8. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     *   9. `[signer]` The proposer (optional, required for proposer bound to Solana signer)
+     */
+    | { kind: 'ProposeUpgrade'; value: ProposeUpgradeArgs }
+    /**
+     * Upgrade the contract to the buffer recorded by ProposeUpgrade after `min_delay_slots` of UpgradeAdmin have passed
+     * since the proposal and `min_upgrade_interval_slots` since the last upgrade, within the proposed slot window.
+     * The buffer contents should still match the proposed code hash. PendingUpgrade account is closed and its lamports are sent to the spill account.
+     * If UpgradeAdmin has roles, the upgrade should have `approval_threshold` approvals and an executor should sign the transaction.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The ProgramData account.
+     *   2. `[writable]` The Program account.
+     *   3. `[writable]` The Buffer account recorded in PendingUpgrade.
+     *   4. `[writable]` The spill account.
+     *   5. `[]` Rent sysvar.
+     *   6. `[]` Clock sysvar.
+     *   7. `[]` BPFLoaderUpgradable program
+     *   8. `[writable]` The PendingUpgrade account
+     *   9. `[writable]` The UpgradeHistory account of the upgraded contract
+     *  10. `[writable]` The BufferStaging account of the buffer, closed to the spill account if the buffer is staged
+     *  11. `[writable,signer]` The executor (optional, required if UpgradeAdmin has executors allowlist or roles)
+     */
+    | { kind: 'ExecuteUpgrade' }
+    /**
+     * Change the delay between ProposeUpgrade and ExecuteUpgrade. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-upgrade-delay".bytes, min_delay_slots, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'SetUpgradeDelay'; value: SetUpgradeDelayArgs }
+    /**
+     * Close PendingUpgrade account of the proposal and optionally the proposed buffer. Allowed while the admin is paused.
+     * The Keccak Hash of `[upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "cancel-upgrade".bytes, buffer_address, close_buffer, expires_at]`
+     * should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The PendingUpgrade account
+     *   2. `[]` The Program account of the proposal.
+     *   3. `[writable]` The Buffer account recorded in PendingUpgrade.
+     *   4. `[writable]` Destination of PendingUpgrade and buffer lamports
+     *   5. `[]` BPFLoaderUpgradable program
+     *   6. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'CancelUpgrade'; value: CancelUpgradeArgs }
+    /**
+     * Hand the buffer over to UpgradeAdmin with the checked loader instruction signed by UpgradeAdmin.
+     * The next instruction of the transaction should be ProposeUpgrade or Upgrade of the same buffer, so the buffer
+     * is never left with UpgradeAdmin authority but without a registered upgrade.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[]` The UpgradeAdmin account
+     *   1. `[writable]` The Buffer account
+     *   2. `[signer]` Current buffer authority
+     *   3. `[]` BPFLoaderUpgradable program
+     *   4. `[]` Instructions sysvar
+     */
+    | { kind: 'SetBufferAuthority' }
+    /**
+     * Create UpgradeHistory account of the primary or managed contract. Upgrade and ExecuteUpgrade of the contract
+     * append a record to it and fail until it is created. Can be called by anyone.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[]` The UpgradeAdmin account
+     *   1. `[writable]` The UpgradeHistory account
+     *   2. `[]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
+     *   3. `[writable,signer]` Fee payer
+     *   4. `[]` System program
+     *   5. `[]` Rent sysvar
+     */
+    | { kind: 'InitUpgradeHistory' }
+    /**
+     * Register the buffer with known good program data of the primary or managed contract, so Rollback can restore it.
+     * The buffer authority should be UpgradeAdmin. Replaces the previous snapshot of the contract.
+     * The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "register-snapshot".bytes, program, buffer_address, code_hash, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The RollbackSnapshot account
+     *   2. `[]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
+     *   3. `[]` The Buffer account with known good program data.
+     *   4. `[writable,signer]` Fee payer
+     *   5. `[]` System program
+     *   6. `[]` Rent sysvar
+     *   7. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'RegisterSnapshot'; value: RegisterSnapshotArgs }
+    /**
+     * Upgrade the contract back to the registered snapshot buffer. The buffer contents should still match the
+     * snapshot code hash. The Keccak Hash of `[upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "rollback".bytes, buffer_address, expires_at]`
+     * should be signed by a single stored signer or the hot upgrade key. A single signature is enough, since RegisterSnapshot
+     * requires `threshold` of stored signers for the snapshot. The snapshot is closed, as the loader consumes the buffer.
+     * Fails if UpgradeAdmin has upgrade delay, roles or veto key, is paused, has an unverified upgrade of another contract
+     * or if `min_upgrade_interval_slots` have not passed since the last upgrade.
+     * If UpgradeAdmin has executors allowlist, one of the executors should sign the transaction and receives `reimbursement`.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The ProgramData account.
+     *   2. `[writable]` The Program account.
+     *   3. `[writable]` The Buffer account recorded in RollbackSnapshot.
+     *   4. `[writable]` The spill account.
+     *   5. `[]` Rent sysvar.
+     *   6. `[]` Clock sysvar.
+     *   7. `[]` BPFLoaderUpgradable program
+     *   8. `[writable]` The UpgradeHistory account of the upgraded contract
+     *   9. `[writable]` The RollbackSnapshot account
+     *  10. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     *  11. `[writable,signer]` The executor (optional, required if UpgradeAdmin has executors allowlist)
+     */
+    | { kind: 'Rollback'; value: RollbackArgs }
+    /**
+     * Change the minimal interval between consecutive upgrades. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-upgrade-interval".bytes, min_upgrade_interval_slots, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'SetUpgradeInterval'; value: SetUpgradeIntervalArgs }
+    /**
+     * Upgrade several contracts managed by UpgradeAdmin with one authorization. The transaction fails as a whole
+     * if any upgrade fails. The Keccak Hash of `[admin_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "batch-upgrade".bytes, spill, [contract, buffer_address, code_hash]..., expires_at]`
+     * should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+     * Fails if UpgradeAdmin has upgrade delay, roles or veto key. Buffers staged by CreateBuffer should be finalized.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The spill account.
+     *   2. `[]` Rent sysvar.
+     *   3. `[]` Clock sysvar.
+     *   4. `[]` BPFLoaderUpgradable program
+     *   5. `[writable]` The ProgramData account of the i-th upgrade, at index 5 + 5 * i.
+     *   6. `[writable]` The Program account of the i-th upgrade, at index 6 + 5 * i.
+     *   7. `[writable]` The Buffer account of the i-th upgrade, at index 7 + 5 * i.
+     *   8. `[writable]` The UpgradeHistory account of the i-th upgrade, at index 8 + 5 * i.
+     *   9. `[writable]` The BufferStaging account of the i-th buffer, at index 9 + 5 * i.
+     *   10. `[]` Instructions sysvar after the last group (optional, required for ed25519 and secp256k1_program signers)
+     *   11. `[writable,signer]` The executor after the last group (optional, required if UpgradeAdmin has executors allowlist)
+     */
+    | { kind: 'BatchUpgrade'; value: BatchUpgradeArgs }
+    /**
+     * Remove upgrade authority of the primary or managed contract, making it immutable forever.
+     * The Keccak Hash of `[contract, authority_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-immutable".bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation. A managed contract is removed
+     * from managed contracts. For the primary contract managed contracts should be removed first, and UpgradeAdmin is
+     * deactivated and can not be reactivated. The removal is recorded in AdminHistory.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The ProgramData account of the contract
+     *   2. `[]` BPFLoaderUpgradable program
+     *   3. `[writable]` The AdminHistory account
+     *   4. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'SetImmutable'; value: SetImmutableArgs }
+    /**
+     * Deploy a new program from the buffer with UpgradeAdmin as its upgrade authority. The program account should be
+     * created for BPFLoaderUpgradable by the program keypair earlier in the same transaction. A program other than
+     * the primary contract is added to managed contracts.
+     * The Keccak Hash of `[admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "deploy".bytes, program, max_data_len, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable,signer]` The fee payer for the ProgramData account
+     *   2. `[writable]` The ProgramData account
+     *   3. `[writable]` The Program account
+     *   4. `[writable]` The Buffer account with UpgradeAdmin authority
+     *   5. `[]` Rent sysvar.
+     *   6. `[]` Clock sysvar.
+     *   7. `[]` System program
+     *   8. `[]` BPFLoaderUpgradable program
+     *   9. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'Deploy'; value: DeployArgs }
+    /**
+     * Grow ProgramData of the primary or managed contract before upgrade to a larger build, the fee payer funds the rent.
+     * The Keccak Hash of `[contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "extend-program".bytes, additional_bytes, expires_at]`
+     * should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+     * The extension consumes upgrade nonce, so the following Upgrade should be signed for the next nonce.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The ProgramData account
+     *   2. `[writable]` The Program account
+     *   3. `[]` System program
+     *   4. `[writable,signer]` The fee payer
+     *   5. `[]` BPFLoaderUpgradable program
+     *   6. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'ExtendProgram'; value: ExtendProgramArgs }
+    /**
+     * Initialize the buffer with UpgradeAdmin as its authority and stage writing of the program data committed by signers.
+     * The buffer account should be created for BPFLoaderUpgradable earlier in the same transaction.
+     * The Keccak Hash of `[contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "create-buffer".bytes, buffer_address, writer, data_len, code_hash, expires_at]`
+     * should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+     * The following Upgrade should be signed for the next nonce.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
+     *   2. `[writable]` The Buffer account
+     *   3. `[writable]` The BufferStaging account
+     *   4. `[writable,signer]` Fee payer
+     *   5. `[]` System program
+     *   6. `[]` Rent sysvar
+     *   7. `[]` BPFLoaderUpgradable program
+     *   8. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'CreateBuffer'; value: CreateBufferArgs }
+    /**
+     * Write the next chunk of the staged buffer. Chunks are written by the writer committed in CreateBuffer, so
+     * other accounts can not append data that would fail FinalizeBuffer.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[]` The UpgradeAdmin account
+     *   1. `[writable]` The Buffer account
+     *   2. `[writable]` The BufferStaging account
+     *   3. `[]` BPFLoaderUpgradable program
+     *   4. `[signer]` The writer
+     */
+    | { kind: 'WriteBufferChunk'; value: WriteBufferChunkArgs }
+    /**
+     * Check that the whole program data is written and its SHA-256 equals the signed commitment.
+     * Finalized buffer can be used by Upgrade with the same code hash.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[]` The UpgradeAdmin account
+     *   1. `[writable]` The BufferStaging account
+     */
+    | { kind: 'FinalizeBuffer' }
+    /**
+     * Close the buffer with UpgradeAdmin authority and send its lamports to the recipient.
+     * The Keccak Hash of `[admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "close-buffer".bytes, buffer_address, recipient, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[writable]` The Buffer account
+     *   2. `[writable]` The recipient account for reclaimed lamports
+     *   3. `[]` BPFLoaderUpgradable program
+     *   4. `[writable]` The BufferStaging account of the buffer, closed to the recipient if the buffer is staged
+     *   5. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'CloseBuffer'; value: CloseBufferArgs }
+    /**
+     * Separate proposers, approvers and executors of ProposeUpgrade, ApproveUpgrade and ExecuteUpgrade. Executors should
+     * be Solana signers, since ExecuteUpgrade carries no signatures. While roles are set, Upgrade, BatchUpgrade and Rollback are rejected.
+     * The Keccak Hash of `[admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-roles".bytes, roles.borsh_bytes, approval_threshold, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'SetRoles'; value: SetRolesArgs }
+    /**
+     * Approve the upgrade recorded by ProposeUpgrade. The Keccak Hash of `[contract, proposal_slot, "solana-upgrade-program".bytes, program_id, domain, "approve-upgrade".bytes, buffer_address, code_hash, expires_at]`
+     * should be signed by an approver key or an approver should sign the transaction.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[]` The UpgradeAdmin account
+     *   1. `[writable]` The PendingUpgrade account
+     *   2. `[]` The Program account of the proposed upgrade
+     *   3. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     *   4. `[signer]` The approver (optional, required for approver bound to Solana signer)
+     */
+    | { kind: 'ApproveUpgrade'; value: ApproveUpgradeArgs }
+    /**
+     * Set or remove the security council key that can veto pending upgrades. The key should not be a signer of UpgradeAdmin.
+     * While the key is set, Upgrade, BatchUpgrade and Rollback are rejected, so every upgrade can be vetoed.
+     * The Keccak Hash of `[admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-veto-key".bytes, veto_key.borsh_bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'SetVetoKey'; value: SetVetoKeyArgs }
+    /**
+     * Close PendingUpgrade account of the proposal before it is executed. Allowed while the admin is paused.
+     * The Keccak Hash of `[contract, proposal_slot, "solana-upgrade-program".bytes, program_id, domain, "veto-upgrade".bytes, buffer_address, code_hash, destination, expires_at]`
+     * should be signed by the veto key or the veto key should sign the transaction.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[]` The UpgradeAdmin account
+     *   1. `[writable]` The PendingUpgrade account
+     *   2. `[]` The Program account of the proposal
+     *   3. `[writable]` Destination of PendingUpgrade lamports
+     *   4. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     *   5. `[signer]` The veto key (optional, required for veto key bound to Solana signer)
+     */
+    | { kind: 'VetoUpgrade'; value: VetoUpgradeArgs }
+    /**
+     * Set or remove the healthcheck instruction of the primary or managed contract.
+     * The Keccak Hash of `[contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-healthcheck".bytes, data.borsh_bytes, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'SetHealthcheck'; value: SetHealthcheckArgs }
+    /**
+     * Invoke the healthcheck of the contract upgraded by Upgrade, BatchUpgrade or ExecuteUpgrade. Upgraded program can be
+     * invoked only after the upgrade slot, so the check runs in a separate transaction and every following upgrade is rejected
+     * until it passes. Rollback of the unverified contract is allowed. BatchUpgrade may include one contract with healthcheck.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` The upgraded Program account
+     */
+    | { kind: 'RunHealthcheck' }
+    /**
+     * Bind the verified build attestation of the primary or managed contract to the admin account.
+     * If the build is required, Upgrade, BatchUpgrade, ProposeUpgrade and ExecuteUpgrade accept only a buffer with its hash.
+     * The Keccak Hash of `[contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-verified-build-hash".bytes, build_hash or zero bytes, required, expires_at]`
+     * should be signed by at least `threshold` of stored signers to perform that operation.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[writable]` The UpgradeAdmin account
+     *   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+     */
+    | { kind: 'SetVerifiedBuildHash'; value: SetVerifiedBuildHashArgs }
+    /**
+     * Discard the chunks of the staged buffer that is not finalized, so the writer can write it again from offset 0.
+     *
+     * Accounts expected by this instruction:
+     *
+     *   0. `[]` The UpgradeAdmin account
+     *   1. `[writable]` The BufferStaging account
+     *   2. `[signer]` The writer
+     */
+    | { kind: 'ResetBuffer' };
+
+export function encodeUpgradeInstruction(writer: BorshWriter, value: UpgradeInstruction): void {
+    switch (value.kind) {
+        case 'InitializeAdmin':
+            writer.u8(0);
+            encodeInitializeAdminArgs(writer, value.value);
+            break;
+        case 'ChangePublicKey':
+            writer.u8(1);
+            encodeChangePublicKeyArgs(writer, value.value);
+            break;
+        case 'ChangeAuthority':
+            writer.u8(2);
+            encodeChangeAuthorityArgs(writer, value.value);
+            break;
+        case 'Upgrade':
+            writer.u8(3);
+            encodeUpgradeArgs(writer, value.value);
+            break;
+        case 'ActivateKey':
+            writer.u8(4);
+            break;
+        case 'CancelKeyChange':
+            writer.u8(5);
+            encodeCancelKeyChangeArgs(writer, value.value);
+            break;
+        case 'RevokeKeys':
+            writer.u8(6);
+            encodeRevokeKeysArgs(writer, value.value);
+            break;
+        case 'VerifyBatch':
+            writer.u8(7);
+            encodeVerifyBatchArgs(writer, value.value);
+            break;
+        case 'CreateSession':
+            writer.u8(8);
+            encodeCreateSessionArgs(writer, value.value);
+            break;
+        case 'StartRecovery':
+            writer.u8(9);
+            encodeStartRecoveryArgs(writer, value.value);
+            break;
+        case 'CompleteRecovery':
+            writer.u8(10);
+            break;
+        case 'CancelRecovery':
+            writer.u8(11);
+            encodeCancelRecoveryArgs(writer, value.value);
+            break;
+        case 'CloseAdmin':
+            writer.u8(12);
+            encodeCloseAdminArgs(writer, value.value);
+            break;
+        case 'MigrateAdmin':
+            writer.u8(13);
+            break;
+        case 'Pause':
+            writer.u8(14);
+            encodePauseArgs(writer, value.value);
+            break;
+        case 'Unpause':
+            writer.u8(15);
+            encodePauseArgs(writer, value.value);
+            break;
+        case 'SetManagedContracts':
+            writer.u8(16);
+            encodeSetManagedContractsArgs(writer, value.value);
+            break;
+        case 'SetMetadata':
+            writer.u8(17);
+            encodeSetMetadataArgs(writer, value.value);
+            break;
+        case 'Deactivate':
+            writer.u8(18);
+            encodeDeactivateArgs(writer, value.value);
+            break;
+        case 'Reactivate':
+            writer.u8(19);
+            encodeDeactivateArgs(writer, value.value);
+            break;
+        case 'ExtendExpiry':
+            writer.u8(20);
+            encodeExtendExpiryArgs(writer, value.value);
+            break;
+        case 'SetExecutors':
+            writer.u8(21);
+            encodeSetExecutorsArgs(writer, value.value);
+            break;
+        case 'ProposeUpgrade':
+            writer.u8(22);
+            encodeProposeUpgradeArgs(writer, value.value);
+            break;
+        case 'ExecuteUpgrade':
+            writer.u8(23);
+            break;
+        case 'SetUpgradeDelay':
+            writer.u8(24);
+            encodeSetUpgradeDelayArgs(writer, value.value);
+            break;
+        case 'CancelUpgrade':
+            writer.u8(25);
+            encodeCancelUpgradeArgs(writer, value.value);
+            break;
+        case 'SetBufferAuthority':
+            writer.u8(26);
+            break;
+        case 'InitUpgradeHistory':
+            writer.u8(27);
+            break;
+        case 'RegisterSnapshot':
+            writer.u8(28);
+            encodeRegisterSnapshotArgs(writer, value.value);
+            break;
+        case 'Rollback':
+            writer.u8(29);
+            encodeRollbackArgs(writer, value.value);
+            break;
+        case 'SetUpgradeInterval':
+            writer.u8(30);
+            encodeSetUpgradeIntervalArgs(writer, value.value);
+            break;
+        case 'BatchUpgrade':
+            writer.u8(31);
+            encodeBatchUpgradeArgs(writer, value.value);
+            break;
+        case 'SetImmutable':
+            writer.u8(32);
+            encodeSetImmutableArgs(writer, value.value);
+            break;
+        case 'Deploy':
+            writer.u8(33);
+            encodeDeployArgs(writer, value.value);
+            break;
+        case 'ExtendProgram':
+            writer.u8(34);
+            encodeExtendProgramArgs(writer, value.value);
+            break;
+        case 'CreateBuffer':
+            writer.u8(35);
+            encodeCreateBufferArgs(writer, value.value);
+            break;
+        case 'WriteBufferChunk':
+            writer.u8(36);
+            encodeWriteBufferChunkArgs(writer, value.value);
+            break;
+        case 'FinalizeBuffer':
+            writer.u8(37);
+            break;
+        case 'CloseBuffer':
+            writer.u8(38);
+            encodeCloseBufferArgs(writer, value.value);
+            break;
+        case 'SetRoles':
+            writer.u8(39);
+            encodeSetRolesArgs(writer, value.value);
+            break;
+        case 'ApproveUpgrade':
+            writer.u8(40);
+            encodeApproveUpgradeArgs(writer, value.value);
+            break;
+        case 'SetVetoKey':
+            writer.u8(41);
+            encodeSetVetoKeyArgs(writer, value.value);
+            break;
+        case 'VetoUpgrade':
+            writer.u8(42);
+            encodeVetoUpgradeArgs(writer, value.value);
+            break;
+        case 'SetHealthcheck':
+            writer.u8(43);
+            encodeSetHealthcheckArgs(writer, value.value);
+            break;
+        case 'RunHealthcheck':
+            writer.u8(44);
+            break;
+        case 'SetVerifiedBuildHash':
+            writer.u8(45);
+            encodeSetVerifiedBuildHashArgs(writer, value.value);
+            break;
+        case 'ResetBuffer':
+            writer.u8(46);
+            break;
+        default:
+            throw new Error(`unknown UpgradeInstruction variant ${(value as { kind: string }).kind}`);
+    }
+}
+
+export function decodeUpgradeInstruction(reader: BorshReader): UpgradeInstruction {
+    const variant = reader.u8();
+    switch (variant) {
+        case 0:
+            return { kind: 'InitializeAdmin', value: decodeInitializeAdminArgs(reader) };
+        case 1:
+            return { kind: 'ChangePublicKey', value: decodeChangePublicKeyArgs(reader) };
+        case 2:
+            return { kind: 'ChangeAuthority', value: decodeChangeAuthorityArgs(reader) };
+        case 3:
+            return { kind: 'Upgrade', value: decodeUpgradeArgs(reader) };
+        case 4:
+            return { kind: 'ActivateKey' };
+        case 5:
+            return { kind: 'CancelKeyChange', value: decodeCancelKeyChangeArgs(reader) };
+        case 6:
+            return { kind: 'RevokeKeys', value: decodeRevokeKeysArgs(reader) };
+        case 7:
+            return { kind: 'VerifyBatch', value: decodeVerifyBatchArgs(reader) };
+        case 8:
+            return { kind: 'CreateSession', value: decodeCreateSessionArgs(reader) };
+        case 9:
+            return { kind: 'StartRecovery', value: decodeStartRecoveryArgs(reader) };
+        case 10:
+            return { kind: 'CompleteRecovery' };
+        case 11:
+            return { kind: 'CancelRecovery', value: decodeCancelRecoveryArgs(reader) };
+        case 12:
+            return { kind: 'CloseAdmin', value: decodeCloseAdminArgs(reader) };
+        case 13:
+            return { kind: 'MigrateAdmin' };
+        case 14:
+            return { kind: 'Pause', value: decodePauseArgs(reader) };
+        case 15:
+            return { kind: 'Unpause', value: decodePauseArgs(reader) };
+        case 16:
+            return { kind: 'SetManagedContracts', value: decodeSetManagedContractsArgs(reader) };
+        case 17:
+            return { kind: 'SetMetadata', value: decodeSetMetadataArgs(reader) };
+        case 18:
+            return { kind: 'Deactivate', value: decodeDeactivateArgs(reader) };
+        case 19:
+            return { kind: 'Reactivate', value: decodeDeactivateArgs(reader) };
+        case 20:
+            return { kind: 'ExtendExpiry', value: decodeExtendExpiryArgs(reader) };
+        case 21:
+            return { kind: 'SetExecutors', value: decodeSetExecutorsArgs(reader) };
+        case 22:
+            return { kind: 'ProposeUpgrade', value: decodeProposeUpgradeArgs(reader) };
+        case 23:
+            return { kind: 'ExecuteUpgrade' };
+        case 24:
+            return { kind: 'SetUpgradeDelay', value: decodeSetUpgradeDelayArgs(reader) };
+        case 25:
+            return { kind: 'CancelUpgrade', value: decodeCancelUpgradeArgs(reader) };
+        case 26:
+            return { kind: 'SetBufferAuthority' };
+        case 27:
+            return { kind: 'InitUpgradeHistory' };
+        case 28:
+            return { kind: 'RegisterSnapshot', value: decodeRegisterSnapshotArgs(reader) };
+        case 29:
+            return { kind: 'Rollback', value: decodeRollbackArgs(reader) };
+        case 30:
+            return { kind: 'SetUpgradeInterval', value: decodeSetUpgradeIntervalArgs(reader) };
+        case 31:
+            return { kind: 'BatchUpgrade', value: decodeBatchUpgradeArgs(reader) };
+        case 32:
+            return { kind: 'SetImmutable', value: decodeSetImmutableArgs(reader) };
+        case 33:
+            return { kind: 'Deploy', value: decodeDeployArgs(reader) };
+        case 34:
+            return { kind: 'ExtendProgram', value: decodeExtendProgramArgs(reader) };
+        case 35:
+            return { kind: 'CreateBuffer', value: decodeCreateBufferArgs(reader) };
+        case 36:
+            return { kind: 'WriteBufferChunk', value: decodeWriteBufferChunkArgs(reader) };
+        case 37:
+            return { kind: 'FinalizeBuffer' };
+        case 38:
+            return { kind: 'CloseBuffer', value: decodeCloseBufferArgs(reader) };
+        case 39:
+            return { kind: 'SetRoles', value: decodeSetRolesArgs(reader) };
+        case 40:
+            return { kind: 'ApproveUpgrade', value: decodeApproveUpgradeArgs(reader) };
+        case 41:
+            return { kind: 'SetVetoKey', value: decodeSetVetoKeyArgs(reader) };
+        case 42:
+            return { kind: 'VetoUpgrade', value: decodeVetoUpgradeArgs(reader) };
+        case 43:
+            return { kind: 'SetHealthcheck', value: decodeSetHealthcheckArgs(reader) };
+        case 44:
+            return { kind: 'RunHealthcheck' };
+        case 45:
+            return { kind: 'SetVerifiedBuildHash', value: decodeSetVerifiedBuildHashArgs(reader) };
+        case 46:
+            return { kind: 'ResetBuffer' };
+        default:
+            throw new Error(`invalid UpgradeInstruction variant ${variant}`);
+    }
+}
+
+/** Instruction data of `instruction`: INSTRUCTION_VERSION followed by Borsh bytes, as `UpgradeInstruction::pack`. */
+export function packUpgradeInstruction(instruction: UpgradeInstruction): Uint8Array {
+    const writer = new BorshWriter();
+    writer.u8(INSTRUCTION_VERSION);
+    encodeUpgradeInstruction(writer, instruction);
+    return writer.toBytes();
+}
+
+/** Decodes instruction data produced by `pack`, rejects other format versions and trailing bytes as `UpgradeInstruction::unpack`. */
+export function unpackUpgradeInstruction(data: Uint8Array): UpgradeInstruction {
+    if (data.length < 2 || data.length > MAX_INSTRUCTION_DATA_LEN) {
+        throw new Error(`invalid instruction data length ${data.length}`);
+    }
+    if (data[0] !== INSTRUCTION_VERSION) {
+        throw new Error(`instruction version ${data[0]} is not supported, expected ${INSTRUCTION_VERSION}`);
+    }
+    const reader = new BorshReader(data.subarray(1));
+    const instruction = decodeUpgradeInstruction(reader);
+    if (reader.remaining() > 0) {
+        throw new Error('trailing bytes after instruction data');
+    }
+    return instruction;
+}
@@ -0,0 +1,147 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, msg, program_error::{PrintProgramError, ProgramError}};
+use thiserror::Error;
+use upgrade::utils::{CommonError, TokenError};
+
+/// Errors that may be returned by the bridge program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum BridgeError {
+    /// 0 The account cannot be initialized because it is already being used.
+    #[error("Already in use")]
+    AlreadyInUse,
+    /// 1 The account hasn't been initialized
+    #[error("Not initialized")]
+    NotInitialized,
+    /// 2 Account address does not match the seeds
+    #[error("Wrong seeds")]
+    WrongSeeds,
+    /// 3 Bridge account is owned by another program
+    #[error("Bridge owner mismatch")]
+    BridgeOwnerMismatch,
+    /// 4 Signer set is empty, too large, contains unsupported keys or has wrong threshold
+    #[error("Invalid signer set")]
+    InvalidSignerSet,
+    /// 5 Signature is malformed or does not recover to a signer of the bridge
+    #[error("Invalid signature")]
+    InvalidSignature,
+    /// 6 Several signatures recovered to the same signer
+    #[error("Duplicate signature")]
+    DuplicateSignature,
+    /// 7 Number of valid signatures is less than threshold
+    #[error("Not enough signatures")]
+    NotEnoughSignatures,
+    /// 8 Signed operation has expired
+    #[error("Signature expired")]
+    SignatureExpired,
+    /// 9 Operation is signed for another nonce
+    #[error("Nonce mismatch")]
+    NonceMismatch,
+    /// 10 Amount is zero
+    #[error("Invalid amount")]
+    InvalidAmount,
+    /// 11 Destination address is empty or too long
+    #[error("Invalid destination")]
+    InvalidDestination,
+    /// 12 Token account or program is not of SPL Token
+    #[error("Invalid token program")]
+    InvalidTokenProgram,
+    /// 13 Account that is modified by the instruction is passed as readonly
+    #[error("Account not writable")]
+    AccountNotWritable,
+    /// 14 Account that should sign the transaction did not sign it
+    #[error("Missing signer")]
+    MissingSigner,
+    /// 15 Arithmetic overflow
+    #[error("Math overflow")]
+    MathOverflow,
+    /// 16 Instruction data is encoded with unsupported format version
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    /// 17 Account passed in place of a sysvar is not that sysvar
+    #[error("Invalid sysvar account")]
+    InvalidSysvarAccount,
+    /// 18 Account passed in place of the system program is not the system program
+    #[error("Invalid system program")]
+    InvalidSystemProgram,
+    /// 19 Vault holds less than the withdrawn amount
+    #[error("Insufficient funds")]
+    InsufficientFunds,
+    /// 20 Withdrawal of the transaction id was already executed
+    #[error("Withdrawal already executed")]
+    WithdrawalAlreadyExecuted,
+    /// 21 Transaction id is empty or too long
+    #[error("Invalid transaction id")]
+    InvalidTxId,
+}
+
+impl From<BridgeError> for ProgramError {
+    fn from(e: BridgeError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl CommonError for BridgeError {
+    fn already_in_use() -> Self {
+        BridgeError::AlreadyInUse
+    }
+
+    fn invalid_sysvar_account() -> Self {
+        BridgeError::InvalidSysvarAccount
+    }
+
+    fn invalid_system_program() -> Self {
+        BridgeError::InvalidSystemProgram
+    }
+
+    fn signature_expired() -> Self {
+        BridgeError::SignatureExpired
+    }
+
+    fn nonce_mismatch() -> Self {
+        BridgeError::NonceMismatch
+    }
+
+    fn duplicate_signature() -> Self {
+        BridgeError::DuplicateSignature
+    }
+
+    fn not_enough_signatures() -> Self {
+        BridgeError::NotEnoughSignatures
+    }
+
+    fn invalid_signature() -> Self {
+        BridgeError::InvalidSignature
+    }
+
+    fn invalid_signer_set() -> Self {
+        BridgeError::InvalidSignerSet
+    }
+
+    fn math_overflow() -> Self {
+        BridgeError::MathOverflow
+    }
+
+    fn account_not_writable() -> Self {
+        BridgeError::AccountNotWritable
+    }
+}
+
+impl TokenError for BridgeError {
+    fn invalid_token_program() -> Self {
+        BridgeError::InvalidTokenProgram
+    }
+}
+
+impl PrintProgramError for BridgeError {
+    fn print<E>(&self) {
+        msg!(&self.to_string());
+    }
+}
+
+impl<T> DecodeError<T> for BridgeError {
+    fn type_of() -> &'static str {
+        "BridgeError"
+    }
+}
@@ -0,0 +1,73 @@
+//! Borsh-encoded events emitted through `sol_log_data`, the oracle decodes deposits from transaction logs
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+
+/// Tokens were locked by Deposit to be minted or released on the destination chain.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Deposited {
+    pub bridge: Pubkey,
+    pub mint: Pubkey,
+    // Owner of the source token account
+    pub sender: Pubkey,
+    pub amount: u64,
+    pub destination_chain: u64,
+    pub destination_address: Vec<u8>,
+    // Deposit nonce, unique within the bridge
+    pub nonce: u64,
+}
+
+/// Locked tokens were released by Withdraw.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Withdrawn {
+    pub bridge: Pubkey,
+    pub mint: Pubkey,
+    // Recipient token account
+    pub recipient: Pubkey,
+    pub amount: u64,
+    // Id of the transaction on the source chain the withdrawal was signed for
+    pub tx_id: Vec<u8>,
+}
+
+/// Lamports were locked by DepositNative to be minted or released on the destination chain.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct NativeDeposited {
+    pub bridge: Pubkey,
+    pub sender: Pubkey,
+    pub amount: u64,
+    pub destination_chain: u64,
+    pub destination_address: Vec<u8>,
+    // Deposit nonce, unique within the bridge
+    pub nonce: u64,
+}
+
+/// Locked lamports were released by WithdrawNative.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct NativeWithdrawn {
+    pub bridge: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    // Id of the transaction on the source chain the withdrawal was signed for
+    pub tx_id: Vec<u8>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub enum BridgeEvent {
+    Deposited(Deposited),
+    Withdrawn(Withdrawn),
+    NativeDeposited(NativeDeposited),
+    NativeWithdrawn(NativeWithdrawn),
+}
+
+impl BridgeEvent {
+    /// Writes the event to the transaction log.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+}
@@ -0,0 +1,71 @@
+//! Hashes of the messages that should be signed by the oracle signers
+
+use solana_program::keccak::{hashv, Hash};
+use solana_program::pubkey::Pubkey;
+use crate::state::Bridge;
+use crate::HASH_CONSTANT;
+
+/// Payload prefix for Withdraw, separates bridge messages from messages of other operations signed by the same keys.
+pub const WITHDRAW_TAG: &str = "withdraw";
+
+/// Payload prefix for WithdrawNative, so a signed token withdrawal can not release lamports and vice versa.
+pub const WITHDRAW_NATIVE_TAG: &str = "withdraw-native";
+
+/// Identifier of the withdrawal of the source chain transaction, seeds the address of its Withdrawal:
+/// `keccak_hash(tx_id)`. Ids longer than a seed, like Solana signatures, are hashed to fit it.
+pub fn withdrawal_id(tx_id: &[u8]) -> Hash {
+    hashv(&[tx_id])
+}
+
+/// Hash to sign for Withdraw instruction:
+/// `keccak_hash(["solana-bridge-program".bytes, program_id, bridge, domain, "withdraw".bytes, mint, recipient, amount, keccak_hash(tx_id), expires_at])`
+/// with integers in big-endian.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_hash(
+    program_id: &Pubkey,
+    bridge_address: &Pubkey,
+    bridge: &Bridge,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    tx_id: &[u8],
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        bridge_address.as_ref(),
+        bridge.domain.as_ref(),
+        WITHDRAW_TAG.as_bytes(),
+        mint.as_ref(),
+        recipient.as_ref(),
+        amount.to_be_bytes().as_ref(),
+        withdrawal_id(tx_id).as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
+
+/// Hash to sign for WithdrawNative instruction:
+/// `keccak_hash(["solana-bridge-program".bytes, program_id, bridge, domain, "withdraw-native".bytes, recipient, amount, keccak_hash(tx_id), expires_at])`
+/// with integers in big-endian.
+pub fn withdraw_native_hash(
+    program_id: &Pubkey,
+    bridge_address: &Pubkey,
+    bridge: &Bridge,
+    recipient: &Pubkey,
+    amount: u64,
+    tx_id: &[u8],
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        bridge_address.as_ref(),
+        bridge.domain.as_ref(),
+        WITHDRAW_NATIVE_TAG.as_bytes(),
+        recipient.as_ref(),
+        amount.to_be_bytes().as_ref(),
+        withdrawal_id(tx_id).as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
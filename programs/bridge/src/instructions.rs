@@ -0,0 +1,378 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use solana_program::instruction::{Instruction, AccountMeta};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use shank::ShankInstruction;
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::state::AdminKey;
+use crate::pda;
+use crate::error::BridgeError;
+
+/// Version of the instruction data format, stored in the first byte of every instruction.
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Instruction data can not be larger than the transaction packet.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 1232;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct InitializeBridgeArgs {
+    // Seed of the bridge address
+    pub seed: [u8; 32],
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the oracle signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize withdrawal
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct DepositArgs {
+    // Amount of tokens to lock, in base units of the mint
+    pub amount: u64,
+    // Identifier of the destination chain, interpreted by the oracle
+    pub destination_chain: u64,
+    // Recipient address on the destination chain, at most MAX_DESTINATION_ADDRESS_LENGTH bytes
+    pub destination_address: Vec<u8>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct WithdrawArgs {
+    // Amount of tokens to release, in base units of the mint
+    pub amount: u64,
+    // Id of the transaction or event on the source chain that locked or burned the bridged asset,
+    // at most MAX_TX_ID_LENGTH bytes
+    pub tx_id: Vec<u8>,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the oracle signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct DepositNativeArgs {
+    // Amount of lamports to lock
+    pub amount: u64,
+    // Identifier of the destination chain, interpreted by the oracle
+    pub destination_chain: u64,
+    // Recipient address on the destination chain, at most MAX_DESTINATION_ADDRESS_LENGTH bytes
+    pub destination_address: Vec<u8>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct WithdrawNativeArgs {
+    // Amount of lamports to release
+    pub amount: u64,
+    // Id of the transaction or event on the source chain that locked or burned the bridged asset,
+    // at most MAX_TX_ID_LENGTH bytes
+    pub tx_id: Vec<u8>,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the oracle signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankInstruction)]
+pub enum BridgeInstruction {
+    /// Initialize new Bridge controlled by the oracle signer set with threshold.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Bridge account to initialize
+    ///   1. `[writable,signer]` The fee payer
+    ///   2. `[]` System program
+    ///   3. `[]` Rent sysvar
+    #[account(0, writable, name="bridge", desc="The Bridge account to initialize")]
+    #[account(1, writable, signer, name="payer", desc="The fee payer")]
+    #[account(2, name="system_program", desc="System program")]
+    #[account(3, name="rent", desc="Rent sysvar")]
+    InitializeBridge(InitializeBridgeArgs),
+
+    /// Lock `amount` of tokens in the vault of the bridge for the mint and emit Deposited event with the
+    /// destination chain and address. The vault is created on the first deposit of the mint, paid by the owner.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Bridge account
+    ///   1. `[writable]` The vault token account of the mint
+    ///   2. `[]` The mint
+    ///   3. `[writable]` The source token account
+    ///   4. `[writable,signer]` The owner of the source token account
+    ///   5. `[]` Token program
+    ///   6. `[]` System program
+    ///   7. `[]` Rent sysvar
+    #[account(0, writable, name="bridge", desc="The Bridge account")]
+    #[account(1, writable, name="vault", desc="The vault token account of the mint")]
+    #[account(2, name="mint", desc="The mint")]
+    #[account(3, writable, name="source", desc="The source token account")]
+    #[account(4, writable, signer, name="owner", desc="The owner of the source token account")]
+    #[account(5, name="token_program", desc="Token program")]
+    #[account(6, name="system_program", desc="System program")]
+    #[account(7, name="rent", desc="Rent sysvar")]
+    Deposit(DepositArgs),
+
+    /// Release `amount` of locked tokens to the recipient token account. The Keccak Hash of
+    /// `["solana-bridge-program".bytes, program_id, bridge, domain, "withdraw".bytes, mint, recipient, amount, keccak_hash(tx_id), expires_at]`
+    /// should be signed by `threshold` of the oracle signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one. The withdrawal creates Withdrawal account of `tx_id`, paid by the fee
+    /// payer, so it can be executed only once. The Bridge account is not modified, withdrawals of different
+    /// transactions are executed in parallel.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The Bridge account
+    ///   1. `[writable]` The Withdrawal account of the transaction id
+    ///   2. `[writable]` The vault token account of the mint
+    ///   3. `[]` The mint
+    ///   4. `[writable]` The recipient token account
+    ///   5. `[writable,signer]` The fee payer
+    ///   6. `[]` Token program
+    ///   7. `[]` System program
+    ///   8. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, name="bridge", desc="The Bridge account")]
+    #[account(1, writable, name="withdrawal", desc="The Withdrawal account of the transaction id")]
+    #[account(2, writable, name="vault", desc="The vault token account of the mint")]
+    #[account(3, name="mint", desc="The mint")]
+    #[account(4, writable, name="recipient", desc="The recipient token account")]
+    #[account(5, writable, signer, name="payer", desc="The fee payer")]
+    #[account(6, name="token_program", desc="Token program")]
+    #[account(7, name="system_program", desc="System program")]
+    #[account(8, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    Withdraw(WithdrawArgs),
+
+    /// Lock `amount` of lamports in the native vault of the bridge and emit NativeDeposited event with the
+    /// destination chain and address. The first deposit also pays the rent-exempt reserve of the vault,
+    /// the reserve is never withdrawn.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Bridge account
+    ///   1. `[writable]` The native vault
+    ///   2. `[writable,signer]` The depositor
+    ///   3. `[]` System program
+    #[account(0, writable, name="bridge", desc="The Bridge account")]
+    #[account(1, writable, name="native_vault", desc="The native vault")]
+    #[account(2, writable, signer, name="owner", desc="The depositor")]
+    #[account(3, name="system_program", desc="System program")]
+    DepositNative(DepositNativeArgs),
+
+    /// Release `amount` of locked lamports to the recipient. The Keccak Hash of
+    /// `["solana-bridge-program".bytes, program_id, bridge, domain, "withdraw-native".bytes, recipient, amount, keccak_hash(tx_id), expires_at]`
+    /// should be signed by `threshold` of the oracle signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one. Withdrawal account of `tx_id` is shared with Withdraw, so a transfer
+    /// on the source chain releases either tokens or lamports, once.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The Bridge account
+    ///   1. `[writable]` The Withdrawal account of the transaction id
+    ///   2. `[writable]` The native vault
+    ///   3. `[writable]` The recipient
+    ///   4. `[writable,signer]` The fee payer
+    ///   5. `[]` System program
+    ///   6. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, name="bridge", desc="The Bridge account")]
+    #[account(1, writable, name="withdrawal", desc="The Withdrawal account of the transaction id")]
+    #[account(2, writable, name="native_vault", desc="The native vault")]
+    #[account(3, writable, name="recipient", desc="The recipient")]
+    #[account(4, writable, signer, name="payer", desc="The fee payer")]
+    #[account(5, name="system_program", desc="System program")]
+    #[account(6, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    WithdrawNative(WithdrawNativeArgs),
+}
+
+impl BridgeInstruction {
+    /// Encodes the instruction prefixed with INSTRUCTION_VERSION.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![INSTRUCTION_VERSION];
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Decodes instruction data produced by `pack`. Data of another format version is rejected instead of being
+    /// misparsed, and trailing bytes are not allowed.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < 2 || input.len() > MAX_INSTRUCTION_DATA_LEN {
+            msg!("Invalid instruction data length: {}", input.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if input[0] != INSTRUCTION_VERSION {
+            msg!("Instruction version {} is not supported, expected {}", input[0], INSTRUCTION_VERSION);
+            return Err(BridgeError::UnsupportedInstructionVersion.into());
+        }
+
+        Ok(Self::try_from_slice(&input[1..])?)
+    }
+}
+
+pub fn initialize_bridge(
+    program_id: Pubkey,
+    fee_payer: Pubkey,
+    seed: [u8; 32],
+    public_keys: Vec<AdminKey>,
+    threshold: u8,
+    domain: [u8; 32],
+) -> Instruction {
+    let (bridge, _) = pda::find_bridge_address(&program_id, &seed);
+    Instruction{
+        program_id,
+        data: BridgeInstruction::InitializeBridge(
+            InitializeBridgeArgs {
+                seed,
+                public_keys,
+                threshold,
+                domain,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(bridge, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn deposit(
+    program_id: Pubkey,
+    bridge: Pubkey,
+    mint: Pubkey,
+    source: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+    destination_chain: u64,
+    destination_address: Vec<u8>,
+) -> Instruction {
+    let (vault, _) = pda::find_vault_address(&program_id, &bridge, &mint);
+    Instruction{
+        program_id,
+        data: BridgeInstruction::Deposit(
+            DepositArgs {
+                amount,
+                destination_chain,
+                destination_address,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(bridge, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(owner, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// Withdraw with signatures in instruction args. Signatures verified by a preceding `secp256k1_program`
+/// instruction require the instructions sysvar appended to the accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw(
+    program_id: Pubkey,
+    bridge: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    fee_payer: Pubkey,
+    amount: u64,
+    tx_id: Vec<u8>,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    let (vault, _) = pda::find_vault_address(&program_id, &bridge, &mint);
+    let (withdrawal, _) = pda::find_withdrawal_address(&program_id, &bridge, &tx_id);
+    Instruction{
+        program_id,
+        data: BridgeInstruction::Withdraw(
+            WithdrawArgs {
+                amount,
+                tx_id,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(bridge, false),
+            AccountMeta::new(withdrawal, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(recipient, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+    }
+}
+
+pub fn deposit_native(
+    program_id: Pubkey,
+    bridge: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+    destination_chain: u64,
+    destination_address: Vec<u8>,
+) -> Instruction {
+    let (native_vault, _) = pda::find_native_vault_address(&program_id, &bridge);
+    Instruction{
+        program_id,
+        data: BridgeInstruction::DepositNative(
+            DepositNativeArgs {
+                amount,
+                destination_chain,
+                destination_address,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(bridge, false),
+            AccountMeta::new(native_vault, false),
+            AccountMeta::new(owner, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+    }
+}
+
+/// WithdrawNative with signatures in instruction args. Signatures verified by a preceding `secp256k1_program`
+/// instruction require the instructions sysvar appended to the accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_native(
+    program_id: Pubkey,
+    bridge: Pubkey,
+    recipient: Pubkey,
+    fee_payer: Pubkey,
+    amount: u64,
+    tx_id: Vec<u8>,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    let (native_vault, _) = pda::find_native_vault_address(&program_id, &bridge);
+    let (withdrawal, _) = pda::find_withdrawal_address(&program_id, &bridge, &tx_id);
+    Instruction{
+        program_id,
+        data: BridgeInstruction::WithdrawNative(
+            WithdrawNativeArgs {
+                amount,
+                tx_id,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(bridge, false),
+            AccountMeta::new(withdrawal, false),
+            AccountMeta::new(native_vault, false),
+            AccountMeta::new(recipient, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+    }
+}
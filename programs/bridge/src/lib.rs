@@ -0,0 +1,21 @@
+//! Bridge of SPL tokens: Deposit locks tokens in a vault of the bridge and emits an event with the destination
+//! chain and address for the oracle, Withdraw releases locked tokens once the oracle signer set signed the amount,
+//! recipient and id of the source chain transaction. Every executed withdrawal creates a Withdrawal account at the
+//! address derived from the hash of the transaction id, so it can be executed only once. Native SOL is bridged
+//! the same way by DepositNative and WithdrawNative with a lamport vault.
+//! Signatures are verified with `upgrade::ecdsa`, the scheme of the upgrade program.
+
+pub mod entrypoint;
+pub mod error;
+pub mod events;
+pub mod hashing;
+pub mod instructions;
+pub mod pda;
+pub mod processor;
+pub mod state;
+
+const HASH_CONSTANT: &str = "solana-bridge-program";
+const PDA_BRIDGE_SEED: &str = "bridge-account";
+const PDA_VAULT_SEED: &str = "bridge-vault-account";
+const PDA_NATIVE_VAULT_SEED: &str = "bridge-native-vault-account";
+const PDA_WITHDRAWAL_SEED: &str = "bridge-withdrawal-account";
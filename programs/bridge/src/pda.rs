@@ -0,0 +1,26 @@
+//! Addresses of the program derived accounts. Off-chain builders and the processor derive them here, so the seed
+//! strings and their ordering are defined once.
+
+use solana_program::pubkey::Pubkey;
+use crate::hashing::withdrawal_id;
+use crate::{PDA_BRIDGE_SEED, PDA_NATIVE_VAULT_SEED, PDA_VAULT_SEED, PDA_WITHDRAWAL_SEED};
+
+/// Address and bump of Bridge created with `seed`.
+pub fn find_bridge_address(program_id: &Pubkey, seed: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_BRIDGE_SEED.as_bytes(), seed], program_id)
+}
+
+/// Address and bump of the token account of Bridge at `bridge` that holds locked tokens of `mint`.
+pub fn find_vault_address(program_id: &Pubkey, bridge: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_VAULT_SEED.as_bytes(), bridge.as_ref(), mint.as_ref()], program_id)
+}
+
+/// Address and bump of the system account of Bridge at `bridge` that holds locked lamports.
+pub fn find_native_vault_address(program_id: &Pubkey, bridge: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_NATIVE_VAULT_SEED.as_bytes(), bridge.as_ref()], program_id)
+}
+
+/// Address and bump of Withdrawal of the source chain transaction `tx_id` in Bridge at `bridge`.
+pub fn find_withdrawal_address(program_id: &Pubkey, bridge: &Pubkey, tx_id: &[u8]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_WITHDRAWAL_SEED.as_bytes(), bridge.as_ref(), withdrawal_id(tx_id).as_ref()], program_id)
+}
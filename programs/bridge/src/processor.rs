@@ -0,0 +1,413 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult, msg,
+    program::{invoke, invoke_signed}, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey, system_instruction,
+    sysvar::{self, rent::Rent, Sysvar},
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::state::{Bridge, Withdrawal, MAX_BRIDGE_SIZE, MAX_DESTINATION_ADDRESS_LENGTH, MAX_TX_ID_LENGTH, MAX_WITHDRAWAL_SIZE};
+use crate::instructions::{BridgeInstruction, DepositArgs, DepositNativeArgs, InitializeBridgeArgs, WithdrawArgs, WithdrawNativeArgs};
+use crate::hashing::{withdraw_hash, withdraw_native_hash, withdrawal_id};
+use crate::pda;
+use crate::{PDA_BRIDGE_SEED, PDA_NATIVE_VAULT_SEED, PDA_VAULT_SEED, PDA_WITHDRAWAL_SEED};
+use crate::error::BridgeError;
+use upgrade::utils::{create_pda_account, next_nonce, validate_signer_set, verify_not_expired, verify_signatures, verify_system_program, verify_sysvar, verify_token_program, verify_writable};
+use crate::events::{BridgeEvent, Deposited, NativeDeposited, NativeWithdrawn, Withdrawn};
+
+pub fn process_instruction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let instruction = BridgeInstruction::unpack(input)?;
+    match instruction {
+        BridgeInstruction::InitializeBridge(args) => {
+            msg!("Instruction: Create bridge");
+            process_init_bridge(program_id, accounts, args)
+        }
+        BridgeInstruction::Deposit(args) => {
+            msg!("Instruction: Deposit");
+            process_deposit(program_id, accounts, args)
+        }
+        BridgeInstruction::Withdraw(args) => {
+            msg!("Instruction: Withdraw");
+            process_withdraw(program_id, accounts, args)
+        }
+        BridgeInstruction::DepositNative(args) => {
+            msg!("Instruction: Deposit native");
+            process_deposit_native(program_id, accounts, args)
+        }
+        BridgeInstruction::WithdrawNative(args) => {
+            msg!("Instruction: Withdraw native");
+            process_withdraw_native(program_id, accounts, args)
+        }
+    }
+}
+
+pub fn process_init_bridge<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: InitializeBridgeArgs,
+) -> ProgramResult {
+    validate_signer_set::<BridgeError>(&args.public_keys, args.threshold)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(BridgeError::MissingSigner.into());
+    }
+
+    verify_system_program::<BridgeError>(system_program)?;
+    verify_sysvar::<BridgeError>(rent_info, &sysvar::rent::id())?;
+
+    let (bridge_key, bump) = pda::find_bridge_address(program_id, &args.seed);
+    if bridge_key != *bridge_info.key {
+        return Err(BridgeError::WrongSeeds.into());
+    }
+
+    let rent = Rent::from_account_info(rent_info)?;
+
+    create_pda_account::<BridgeError>(bridge_info, fee_payer_info, system_program, &rent, MAX_BRIDGE_SIZE, program_id, &[PDA_BRIDGE_SEED.as_bytes(), args.seed.as_ref(), &[bump]])?;
+
+    let mut bridge: Bridge = BorshDeserialize::deserialize(&mut bridge_info.data.borrow_mut().as_ref())?;
+    if bridge.is_initialized {
+        return Err(BridgeError::AlreadyInUse.into());
+    }
+
+    bridge.seed = args.seed;
+    bridge.bump = bump;
+    bridge.public_keys = args.public_keys;
+    bridge.threshold = args.threshold;
+    bridge.domain = args.domain;
+    bridge.deposit_nonce = 0;
+    bridge.is_initialized = true;
+    bridge.serialize(&mut *bridge_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_deposit<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: DepositArgs,
+) -> ProgramResult {
+    if args.amount == 0 {
+        return Err(BridgeError::InvalidAmount.into());
+    }
+
+    validate_destination(&args.destination_address)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !owner_info.is_signer {
+        return Err(BridgeError::MissingSigner.into());
+    }
+
+    verify_token_program::<BridgeError>(token_program)?;
+    verify_system_program::<BridgeError>(system_program)?;
+    verify_sysvar::<BridgeError>(rent_info, &sysvar::rent::id())?;
+
+    let mut bridge = load_bridge(program_id, bridge_info)?;
+    verify_writable::<BridgeError>(bridge_info)?;
+    let decimals = mint_decimals(mint_info)?;
+
+    let (vault_key, vault_bump) = pda::find_vault_address(program_id, bridge_info.key, mint_info.key);
+    if vault_key != *vault_info.key {
+        return Err(BridgeError::WrongSeeds.into());
+    }
+
+    // Vault of the mint is created by its first deposit
+    if vault_info.data_is_empty() {
+        let rent = Rent::from_account_info(rent_info)?;
+        create_pda_account::<BridgeError>(vault_info, owner_info, system_program, &rent, spl_token::state::Account::LEN, &spl_token::id(), &[PDA_VAULT_SEED.as_bytes(), bridge_info.key.as_ref(), mint_info.key.as_ref(), &[vault_bump]])?;
+        invoke(
+            &spl_token::instruction::initialize_account3(&spl_token::id(), vault_info.key, mint_info.key, bridge_info.key)?,
+            &[vault_info.clone(), mint_info.clone()],
+        )?;
+    }
+
+    invoke(
+        &spl_token::instruction::transfer_checked(&spl_token::id(), source_info.key, mint_info.key, vault_info.key, owner_info.key, &[], args.amount, decimals)?,
+        &[source_info.clone(), mint_info.clone(), vault_info.clone(), owner_info.clone()],
+    )?;
+
+    let nonce = next_nonce::<BridgeError>(&mut bridge.deposit_nonce)?;
+    bridge.serialize(&mut *bridge_info.data.borrow_mut())?;
+
+    BridgeEvent::Deposited(Deposited {
+        bridge: *bridge_info.key,
+        mint: *mint_info.key,
+        sender: *owner_info.key,
+        amount: args.amount,
+        destination_chain: args.destination_chain,
+        destination_address: args.destination_address,
+        nonce,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_withdraw<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: WithdrawArgs,
+) -> ProgramResult {
+    if args.amount == 0 {
+        return Err(BridgeError::InvalidAmount.into());
+    }
+
+    validate_tx_id(&args.tx_id)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let withdrawal_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(BridgeError::MissingSigner.into());
+    }
+
+    verify_token_program::<BridgeError>(token_program)?;
+    verify_system_program::<BridgeError>(system_program)?;
+
+    let bridge = load_bridge(program_id, bridge_info)?;
+    let decimals = mint_decimals(mint_info)?;
+
+    let (vault_key, _) = pda::find_vault_address(program_id, bridge_info.key, mint_info.key);
+    if vault_key != *vault_info.key {
+        return Err(BridgeError::WrongSeeds.into());
+    }
+
+    verify_not_expired::<BridgeError>(args.expires_at)?;
+
+    let hash = withdraw_hash(program_id, bridge_info.key, &bridge, mint_info.key, recipient_info.key, args.amount, &args.tx_id, args.expires_at);
+    verify_signatures::<BridgeError>(hash.as_ref(), &args.signatures, accounts, &bridge.public_keys, bridge.threshold)?;
+
+    // Withdrawal is recorded before the transfer, so the signatures can not be replayed by a reentrant call
+    record_withdrawal(program_id, bridge_info, withdrawal_info, fee_payer_info, system_program, &args.tx_id)?;
+
+    invoke_signed(
+        &spl_token::instruction::transfer_checked(&spl_token::id(), vault_info.key, mint_info.key, recipient_info.key, bridge_info.key, &[], args.amount, decimals)?,
+        &[vault_info.clone(), mint_info.clone(), recipient_info.clone(), bridge_info.clone()],
+        &[&[PDA_BRIDGE_SEED.as_bytes(), bridge.seed.as_ref(), &[bridge.bump]]],
+    )?;
+
+    BridgeEvent::Withdrawn(Withdrawn {
+        bridge: *bridge_info.key,
+        mint: *mint_info.key,
+        recipient: *recipient_info.key,
+        amount: args.amount,
+        tx_id: args.tx_id,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_deposit_native<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: DepositNativeArgs,
+) -> ProgramResult {
+    if args.amount == 0 {
+        return Err(BridgeError::InvalidAmount.into());
+    }
+
+    validate_destination(&args.destination_address)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let native_vault_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !owner_info.is_signer {
+        return Err(BridgeError::MissingSigner.into());
+    }
+
+    verify_system_program::<BridgeError>(system_program)?;
+
+    let mut bridge = load_bridge(program_id, bridge_info)?;
+    verify_writable::<BridgeError>(bridge_info)?;
+
+    let (native_vault_key, _) = pda::find_native_vault_address(program_id, bridge_info.key);
+    if native_vault_key != *native_vault_info.key {
+        return Err(BridgeError::WrongSeeds.into());
+    }
+
+    // Vault is a system account without data, it stays rent-exempt with the reserve paid by the first deposit
+    let reserve = Rent::get()?.minimum_balance(0).saturating_sub(native_vault_info.lamports());
+    let lamports = args.amount.checked_add(reserve).ok_or(BridgeError::MathOverflow)?;
+    invoke(
+        &system_instruction::transfer(owner_info.key, native_vault_info.key, lamports),
+        &[owner_info.clone(), native_vault_info.clone(), system_program.clone()],
+    )?;
+
+    let nonce = next_nonce::<BridgeError>(&mut bridge.deposit_nonce)?;
+    bridge.serialize(&mut *bridge_info.data.borrow_mut())?;
+
+    BridgeEvent::NativeDeposited(NativeDeposited {
+        bridge: *bridge_info.key,
+        sender: *owner_info.key,
+        amount: args.amount,
+        destination_chain: args.destination_chain,
+        destination_address: args.destination_address,
+        nonce,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_withdraw_native<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: WithdrawNativeArgs,
+) -> ProgramResult {
+    if args.amount == 0 {
+        return Err(BridgeError::InvalidAmount.into());
+    }
+
+    validate_tx_id(&args.tx_id)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let withdrawal_info = next_account_info(account_info_iter)?;
+    let native_vault_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(BridgeError::MissingSigner.into());
+    }
+
+    verify_system_program::<BridgeError>(system_program)?;
+
+    let bridge = load_bridge(program_id, bridge_info)?;
+
+    let (native_vault_key, native_vault_bump) = pda::find_native_vault_address(program_id, bridge_info.key);
+    if native_vault_key != *native_vault_info.key {
+        return Err(BridgeError::WrongSeeds.into());
+    }
+
+    let available = native_vault_info.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+    if args.amount > available {
+        msg!("Native vault holds {} lamports above the reserve", available);
+        return Err(BridgeError::InsufficientFunds.into());
+    }
+
+    verify_not_expired::<BridgeError>(args.expires_at)?;
+
+    let hash = withdraw_native_hash(program_id, bridge_info.key, &bridge, recipient_info.key, args.amount, &args.tx_id, args.expires_at);
+    verify_signatures::<BridgeError>(hash.as_ref(), &args.signatures, accounts, &bridge.public_keys, bridge.threshold)?;
+
+    record_withdrawal(program_id, bridge_info, withdrawal_info, fee_payer_info, system_program, &args.tx_id)?;
+
+    invoke_signed(
+        &system_instruction::transfer(native_vault_info.key, recipient_info.key, args.amount),
+        &[native_vault_info.clone(), recipient_info.clone(), system_program.clone()],
+        &[&[PDA_NATIVE_VAULT_SEED.as_bytes(), bridge_info.key.as_ref(), &[native_vault_bump]]],
+    )?;
+
+    BridgeEvent::NativeWithdrawn(NativeWithdrawn {
+        bridge: *bridge_info.key,
+        recipient: *recipient_info.key,
+        amount: args.amount,
+        tx_id: args.tx_id,
+    }).emit();
+    Ok(())
+}
+
+
+/// Loads initialized Bridge owned by the program from `bridge_info`.
+fn load_bridge(program_id: &Pubkey, bridge_info: &AccountInfo) -> Result<Bridge, ProgramError> {
+    if bridge_info.owner != program_id {
+        return Err(BridgeError::BridgeOwnerMismatch.into());
+    }
+
+    let bridge: Bridge = BorshDeserialize::deserialize(&mut bridge_info.data.borrow().as_ref())?;
+    if !bridge.is_initialized {
+        return Err(BridgeError::NotInitialized.into());
+    }
+
+    Ok(bridge)
+}
+
+
+/// Creates Withdrawal of `tx_id` at the address derived from its hash. The account exists once the withdrawal was
+/// executed, so each signed withdrawal is executed only once without a nonce shared by all withdrawals.
+fn record_withdrawal<'a>(
+    program_id: &Pubkey,
+    bridge_info: &AccountInfo<'a>,
+    withdrawal_info: &AccountInfo<'a>,
+    fee_payer_info: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    tx_id: &[u8],
+) -> ProgramResult {
+    let (withdrawal_key, bump) = pda::find_withdrawal_address(program_id, bridge_info.key, tx_id);
+    if withdrawal_key != *withdrawal_info.key {
+        return Err(BridgeError::WrongSeeds.into());
+    }
+
+    if withdrawal_info.owner == program_id {
+        msg!("Withdrawal {} is already executed", withdrawal_info.key);
+        return Err(BridgeError::WithdrawalAlreadyExecuted.into());
+    }
+
+    let rent = Rent::get()?;
+    create_pda_account::<BridgeError>(withdrawal_info, fee_payer_info, system_program, &rent, MAX_WITHDRAWAL_SIZE, program_id, &[PDA_WITHDRAWAL_SEED.as_bytes(), bridge_info.key.as_ref(), withdrawal_id(tx_id).as_ref(), &[bump]])?;
+
+    let withdrawal = Withdrawal {
+        bridge: *bridge_info.key,
+        tx_id: tx_id.to_vec(),
+        is_initialized: true,
+    };
+    withdrawal.serialize(&mut *withdrawal_info.data.borrow_mut())?;
+    Ok(())
+}
+
+
+/// Decimals of SPL Token `mint_info`, required by `transfer_checked`.
+fn mint_decimals(mint_info: &AccountInfo) -> Result<u8, ProgramError> {
+    if mint_info.owner != &spl_token::id() {
+        return Err(BridgeError::InvalidTokenProgram.into());
+    }
+
+    Ok(spl_token::state::Mint::unpack(&mint_info.data.borrow())?.decimals)
+}
+
+
+fn validate_tx_id(tx_id: &[u8]) -> ProgramResult {
+    if tx_id.is_empty() || tx_id.len() > MAX_TX_ID_LENGTH {
+        return Err(BridgeError::InvalidTxId.into());
+    }
+
+    Ok(())
+}
+
+
+fn validate_destination(destination_address: &[u8]) -> ProgramResult {
+    if destination_address.is_empty() || destination_address.len() > MAX_DESTINATION_ADDRESS_LENGTH {
+        return Err(BridgeError::InvalidDestination.into());
+    }
+
+    Ok(())
+}
+
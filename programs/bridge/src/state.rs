@@ -0,0 +1,41 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::pubkey::Pubkey;
+use upgrade::state::{AdminKey, MAX_ADMIN_KEY_SIZE, MAX_SIGNERS};
+
+/// Maximal length of the address on the destination chain, fits addresses of EVM, Solana and Cosmos chains
+pub const MAX_DESTINATION_ADDRESS_LENGTH: usize = 64;
+
+/// Maximal length of the source chain transaction id, fits Solana signatures and EVM transaction hash with log index
+pub const MAX_TX_ID_LENGTH: usize = 64;
+
+pub const MAX_BRIDGE_SIZE: usize = 32 + 1 + 4 + MAX_SIGNERS * MAX_ADMIN_KEY_SIZE + 1 + 32 + 8 + 1;
+
+pub const MAX_WITHDRAWAL_SIZE: usize = 32 + 4 + MAX_TX_ID_LENGTH + 1;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct Bridge {
+    // Seed of the bridge address, several bridges can be deployed by one program
+    pub seed: [u8; 32],
+    pub bump: u8,
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the oracle signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize withdrawal
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+    // Nonce of the next deposit, identifies the deposit for the oracle
+    pub deposit_nonce: u64,
+    pub is_initialized: bool,
+}
+
+/// Receipt of an executed withdrawal, the address is derived from the hash of the transaction id.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct Withdrawal {
+    pub bridge: Pubkey,
+    // Id of the transaction or event on the source chain the withdrawal was signed for
+    pub tx_id: Vec<u8>,
+    pub is_initialized: bool,
+}
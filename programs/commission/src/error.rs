@@ -0,0 +1,144 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, msg, program_error::{PrintProgramError, ProgramError}};
+use thiserror::Error;
+use upgrade::utils::{CommonError, TokenError};
+
+/// Errors that may be returned by the commission program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum CommissionError {
+    /// 0 The account cannot be initialized because it is already being used.
+    #[error("Already in use")]
+    AlreadyInUse,
+    /// 1 The account hasn't been initialized
+    #[error("Not initialized")]
+    NotInitialized,
+    /// 2 Account address does not match the seeds
+    #[error("Wrong seeds")]
+    WrongSeeds,
+    /// 3 Commission or FeeSchedule account is owned by another program
+    #[error("Account owner mismatch")]
+    AccountOwnerMismatch,
+    /// 4 Signer set is empty, too large, contains unsupported keys or has wrong threshold
+    #[error("Invalid signer set")]
+    InvalidSignerSet,
+    /// 5 Signature is malformed or does not recover to a signer of the commission
+    #[error("Invalid signature")]
+    InvalidSignature,
+    /// 6 Several signatures recovered to the same signer
+    #[error("Duplicate signature")]
+    DuplicateSignature,
+    /// 7 Number of valid signatures is less than threshold
+    #[error("Not enough signatures")]
+    NotEnoughSignatures,
+    /// 8 Signed operation has expired
+    #[error("Signature expired")]
+    SignatureExpired,
+    /// 9 Operation is signed for another nonce
+    #[error("Nonce mismatch")]
+    NonceMismatch,
+    /// 10 Amount is zero
+    #[error("Invalid amount")]
+    InvalidAmount,
+    /// 11 Fee in basis points is larger than 100%
+    #[error("Invalid fee")]
+    InvalidFee,
+    /// 12 Fee is larger than the charged amount
+    #[error("Fee exceeds amount")]
+    FeeExceedsAmount,
+    /// 13 Treasury holds less than the withdrawn amount
+    #[error("Insufficient funds")]
+    InsufficientFunds,
+    /// 14 Token account or program is not of SPL Token
+    #[error("Invalid token program")]
+    InvalidTokenProgram,
+    /// 15 Account that is modified by the instruction is passed as readonly
+    #[error("Account not writable")]
+    AccountNotWritable,
+    /// 16 Account that should sign the transaction did not sign it
+    #[error("Missing signer")]
+    MissingSigner,
+    /// 17 Arithmetic overflow
+    #[error("Math overflow")]
+    MathOverflow,
+    /// 18 Instruction data is encoded with unsupported format version
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    /// 19 Account passed in place of a sysvar is not that sysvar
+    #[error("Invalid sysvar account")]
+    InvalidSysvarAccount,
+    /// 20 Account passed in place of the system program is not the system program
+    #[error("Invalid system program")]
+    InvalidSystemProgram,
+}
+
+impl From<CommissionError> for ProgramError {
+    fn from(e: CommissionError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl CommonError for CommissionError {
+    fn already_in_use() -> Self {
+        CommissionError::AlreadyInUse
+    }
+
+    fn invalid_sysvar_account() -> Self {
+        CommissionError::InvalidSysvarAccount
+    }
+
+    fn invalid_system_program() -> Self {
+        CommissionError::InvalidSystemProgram
+    }
+
+    fn signature_expired() -> Self {
+        CommissionError::SignatureExpired
+    }
+
+    fn nonce_mismatch() -> Self {
+        CommissionError::NonceMismatch
+    }
+
+    fn duplicate_signature() -> Self {
+        CommissionError::DuplicateSignature
+    }
+
+    fn not_enough_signatures() -> Self {
+        CommissionError::NotEnoughSignatures
+    }
+
+    fn invalid_signature() -> Self {
+        CommissionError::InvalidSignature
+    }
+
+    fn invalid_signer_set() -> Self {
+        CommissionError::InvalidSignerSet
+    }
+
+    fn math_overflow() -> Self {
+        CommissionError::MathOverflow
+    }
+
+    fn account_not_writable() -> Self {
+        CommissionError::AccountNotWritable
+    }
+}
+
+impl TokenError for CommissionError {
+    fn invalid_token_program() -> Self {
+        CommissionError::InvalidTokenProgram
+    }
+}
+
+impl PrintProgramError for CommissionError {
+    fn print<E>(&self) {
+        msg!(&self.to_string());
+    }
+}
+
+impl<T> DecodeError<T> for CommissionError {
+    fn type_of() -> &'static str {
+        "CommissionError"
+    }
+}
@@ -0,0 +1,58 @@
+//! Borsh-encoded events emitted through `sol_log_data`, accounting services decode charged fees from transaction logs
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+use crate::state::Fee;
+
+/// Fee of the mint was set by SetFee.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct FeeUpdated {
+    pub commission: Pubkey,
+    pub mint: Pubkey,
+    pub fee: Fee,
+    // Nonce the signatures were made for
+    pub nonce: u64,
+}
+
+/// Fee was charged by ChargeFee and accrued in the treasury.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct FeeCharged {
+    pub commission: Pubkey,
+    pub mint: Pubkey,
+    // Owner of the source token account
+    pub payer: Pubkey,
+    // Amount the fee was calculated for
+    pub amount: u64,
+    pub fee: u64,
+}
+
+/// Accrued fees were withdrawn from the treasury by Withdraw.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Withdrawn {
+    pub commission: Pubkey,
+    pub mint: Pubkey,
+    // Recipient token account
+    pub recipient: Pubkey,
+    pub amount: u64,
+    // Nonce the signatures were made for
+    pub nonce: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub enum CommissionEvent {
+    FeeUpdated(FeeUpdated),
+    FeeCharged(FeeCharged),
+    Withdrawn(Withdrawn),
+}
+
+impl CommissionEvent {
+    /// Writes the event to the transaction log.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+}
@@ -0,0 +1,66 @@
+//! Hashes of the messages that should be signed by the commission signers
+
+use borsh::BorshSerialize;
+use solana_program::keccak::{hashv, Hash};
+use solana_program::pubkey::Pubkey;
+use crate::state::{Commission, Fee};
+use crate::HASH_CONSTANT;
+
+/// Payload prefix for SetFee.
+pub const SET_FEE_TAG: &str = "set-fee";
+
+/// Payload prefix for Withdraw.
+pub const WITHDRAW_TAG: &str = "withdraw";
+
+/// Hash to sign for SetFee instruction:
+/// `keccak_hash(["solana-commission-program".bytes, program_id, commission, domain, "set-fee".bytes, mint, borsh(fee), nonce, expires_at])`
+/// with integers in big-endian.
+pub fn set_fee_hash(
+    program_id: &Pubkey,
+    commission_address: &Pubkey,
+    commission: &Commission,
+    mint: &Pubkey,
+    fee: &Fee,
+    nonce: u64,
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        commission_address.as_ref(),
+        commission.domain.as_ref(),
+        SET_FEE_TAG.as_bytes(),
+        mint.as_ref(),
+        fee.try_to_vec().unwrap().as_ref(),
+        nonce.to_be_bytes().as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
+
+/// Hash to sign for Withdraw instruction:
+/// `keccak_hash(["solana-commission-program".bytes, program_id, commission, domain, "withdraw".bytes, mint, recipient, amount, nonce, expires_at])`
+/// with integers in big-endian.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_hash(
+    program_id: &Pubkey,
+    commission_address: &Pubkey,
+    commission: &Commission,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        commission_address.as_ref(),
+        commission.domain.as_ref(),
+        WITHDRAW_TAG.as_bytes(),
+        mint.as_ref(),
+        recipient.as_ref(),
+        amount.to_be_bytes().as_ref(),
+        nonce.to_be_bytes().as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
@@ -0,0 +1,304 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use solana_program::instruction::{Instruction, AccountMeta};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use shank::ShankInstruction;
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::state::AdminKey;
+use crate::pda;
+use crate::error::CommissionError;
+use crate::state::Fee;
+
+/// Version of the instruction data format, stored in the first byte of every instruction.
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Instruction data can not be larger than the transaction packet.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 1232;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct InitializeCommissionArgs {
+    // Seed of the commission address
+    pub seed: [u8; 32],
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize fee updates and withdrawals
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct SetFeeArgs {
+    // Mint the fee is charged in
+    pub mint: Pubkey,
+    // Flat fee or fee in basis points of the charged amount
+    pub fee: Fee,
+    // Nonce of the commission the signatures were made for
+    pub nonce: u64,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the commission signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ChargeFeeArgs {
+    // Amount the fee is charged for, e.g. the amount of a bridge deposit, in base units of the mint
+    pub amount: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct WithdrawArgs {
+    // Amount of accrued fees to withdraw, in base units of the mint
+    pub amount: u64,
+    // Nonce of the commission the signatures were made for
+    pub nonce: u64,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the commission signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankInstruction)]
+pub enum CommissionInstruction {
+    /// Initialize new Commission controlled by the signer set with threshold.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Commission account to initialize
+    ///   1. `[writable,signer]` The fee payer
+    ///   2. `[]` System program
+    ///   3. `[]` Rent sysvar
+    #[account(0, writable, name="commission", desc="The Commission account to initialize")]
+    #[account(1, writable, signer, name="payer", desc="The fee payer")]
+    #[account(2, name="system_program", desc="System program")]
+    #[account(3, name="rent", desc="Rent sysvar")]
+    InitializeCommission(InitializeCommissionArgs),
+
+    /// Set the fee of the mint, the FeeSchedule account of the mint is created by the first update. The Keccak Hash of
+    /// `["solana-commission-program".bytes, program_id, commission, domain, "set-fee".bytes, mint, borsh(fee), nonce, expires_at]`
+    /// should be signed by `threshold` of the signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Commission account
+    ///   1. `[writable]` The FeeSchedule account of the mint
+    ///   2. `[writable,signer]` The fee payer
+    ///   3. `[]` System program
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, writable, name="commission", desc="The Commission account")]
+    #[account(1, writable, name="fee_schedule", desc="The FeeSchedule account of the mint")]
+    #[account(2, writable, signer, name="payer", desc="The fee payer")]
+    #[account(3, name="system_program", desc="System program")]
+    #[account(4, name="rent", desc="Rent sysvar")]
+    #[account(5, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    SetFee(SetFeeArgs),
+
+    /// Charge the fee of the mint for `amount` from the source token account to the treasury and emit FeeCharged.
+    /// The charged fee is set as little-endian u64 return data for programs that charge fees by CPI. The treasury
+    /// is created on the first charge of the mint, paid by the owner. The Commission account is not modified.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The Commission account
+    ///   1. `[]` The FeeSchedule account of the mint
+    ///   2. `[writable]` The treasury token account of the mint
+    ///   3. `[]` The mint
+    ///   4. `[writable]` The source token account
+    ///   5. `[writable,signer]` The owner of the source token account
+    ///   6. `[]` Token program
+    ///   7. `[]` System program
+    ///   8. `[]` Rent sysvar
+    #[account(0, name="commission", desc="The Commission account")]
+    #[account(1, name="fee_schedule", desc="The FeeSchedule account of the mint")]
+    #[account(2, writable, name="treasury", desc="The treasury token account of the mint")]
+    #[account(3, name="mint", desc="The mint")]
+    #[account(4, writable, name="source", desc="The source token account")]
+    #[account(5, writable, signer, name="owner", desc="The owner of the source token account")]
+    #[account(6, name="token_program", desc="Token program")]
+    #[account(7, name="system_program", desc="System program")]
+    #[account(8, name="rent", desc="Rent sysvar")]
+    ChargeFee(ChargeFeeArgs),
+
+    /// Withdraw `amount` of accrued fees from the treasury to the recipient token account. The Keccak Hash of
+    /// `["solana-commission-program".bytes, program_id, commission, domain, "withdraw".bytes, mint, recipient, amount, nonce, expires_at]`
+    /// should be signed by `threshold` of the signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Commission account
+    ///   1. `[writable]` The treasury token account of the mint
+    ///   2. `[]` The mint
+    ///   3. `[writable]` The recipient token account
+    ///   4. `[]` Token program
+    ///   5. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, writable, name="commission", desc="The Commission account")]
+    #[account(1, writable, name="treasury", desc="The treasury token account of the mint")]
+    #[account(2, name="mint", desc="The mint")]
+    #[account(3, writable, name="recipient", desc="The recipient token account")]
+    #[account(4, name="token_program", desc="Token program")]
+    #[account(5, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    Withdraw(WithdrawArgs),
+}
+
+impl CommissionInstruction {
+    /// Encodes the instruction prefixed with INSTRUCTION_VERSION.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![INSTRUCTION_VERSION];
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Decodes instruction data produced by `pack`. Data of another format version is rejected instead of being
+    /// misparsed, and trailing bytes are not allowed.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < 2 || input.len() > MAX_INSTRUCTION_DATA_LEN {
+            msg!("Invalid instruction data length: {}", input.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if input[0] != INSTRUCTION_VERSION {
+            msg!("Instruction version {} is not supported, expected {}", input[0], INSTRUCTION_VERSION);
+            return Err(CommissionError::UnsupportedInstructionVersion.into());
+        }
+
+        Ok(Self::try_from_slice(&input[1..])?)
+    }
+}
+
+pub fn initialize_commission(
+    program_id: Pubkey,
+    fee_payer: Pubkey,
+    seed: [u8; 32],
+    public_keys: Vec<AdminKey>,
+    threshold: u8,
+    domain: [u8; 32],
+) -> Instruction {
+    let (commission, _) = pda::find_commission_address(&program_id, &seed);
+    Instruction{
+        program_id,
+        data: CommissionInstruction::InitializeCommission(
+            InitializeCommissionArgs {
+                seed,
+                public_keys,
+                threshold,
+                domain,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(commission, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// SetFee with signatures in instruction args. Signatures verified by a preceding `secp256k1_program`
+/// instruction require the instructions sysvar appended to the accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn set_fee(
+    program_id: Pubkey,
+    commission: Pubkey,
+    fee_payer: Pubkey,
+    mint: Pubkey,
+    fee: Fee,
+    nonce: u64,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    let (fee_schedule, _) = pda::find_fee_schedule_address(&program_id, &commission, &mint);
+    Instruction{
+        program_id,
+        data: CommissionInstruction::SetFee(
+            SetFeeArgs {
+                mint,
+                fee,
+                nonce,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(commission, false),
+            AccountMeta::new(fee_schedule, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+pub fn charge_fee(
+    program_id: Pubkey,
+    commission: Pubkey,
+    mint: Pubkey,
+    source: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (fee_schedule, _) = pda::find_fee_schedule_address(&program_id, &commission, &mint);
+    let (treasury, _) = pda::find_treasury_address(&program_id, &commission, &mint);
+    Instruction{
+        program_id,
+        data: CommissionInstruction::ChargeFee(
+            ChargeFeeArgs {
+                amount,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(commission, false),
+            AccountMeta::new_readonly(fee_schedule, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(owner, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// Withdraw with signatures in instruction args. Signatures verified by a preceding `secp256k1_program`
+/// instruction require the instructions sysvar appended to the accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw(
+    program_id: Pubkey,
+    commission: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+    nonce: u64,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    let (treasury, _) = pda::find_treasury_address(&program_id, &commission, &mint);
+    Instruction{
+        program_id,
+        data: CommissionInstruction::Withdraw(
+            WithdrawArgs {
+                amount,
+                nonce,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(commission, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(recipient, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
@@ -0,0 +1,18 @@
+//! Collector of protocol fees: ChargeFee takes the fee of the token from the fee schedule of the collector, flat or
+//! in basis points of the charged amount, and accrues it in a treasury token account of the collector. Fee schedule
+//! updates and withdrawals from the treasury are authorized by the signer set with `upgrade::ecdsa`, the signature
+//! scheme of the upgrade program.
+
+pub mod entrypoint;
+pub mod error;
+pub mod events;
+pub mod hashing;
+pub mod instructions;
+pub mod pda;
+pub mod processor;
+pub mod state;
+
+const HASH_CONSTANT: &str = "solana-commission-program";
+const PDA_COMMISSION_SEED: &str = "commission-account";
+const PDA_FEE_SCHEDULE_SEED: &str = "commission-fee-account";
+const PDA_TREASURY_SEED: &str = "commission-treasury-account";
@@ -0,0 +1,20 @@
+//! Addresses of the program derived accounts. Off-chain builders and the processor derive them here, so the seed
+//! strings and their ordering are defined once.
+
+use solana_program::pubkey::Pubkey;
+use crate::{PDA_COMMISSION_SEED, PDA_FEE_SCHEDULE_SEED, PDA_TREASURY_SEED};
+
+/// Address and bump of Commission created with `seed`.
+pub fn find_commission_address(program_id: &Pubkey, seed: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_COMMISSION_SEED.as_bytes(), seed], program_id)
+}
+
+/// Address and bump of FeeSchedule of `mint` in Commission at `commission`.
+pub fn find_fee_schedule_address(program_id: &Pubkey, commission: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_FEE_SCHEDULE_SEED.as_bytes(), commission.as_ref(), mint.as_ref()], program_id)
+}
+
+/// Address and bump of the token account of Commission at `commission` that accrues fees in `mint`.
+pub fn find_treasury_address(program_id: &Pubkey, commission: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_TREASURY_SEED.as_bytes(), commission.as_ref(), mint.as_ref()], program_id)
+}
@@ -0,0 +1,342 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult, msg,
+    program::{invoke, invoke_signed, set_return_data}, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    sysvar::{self, rent::Rent, Sysvar},
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::state::{Commission, Fee, FeeSchedule, MAX_BASIS_POINTS, MAX_COMMISSION_SIZE, MAX_FEE_SCHEDULE_SIZE};
+use crate::instructions::{ChargeFeeArgs, CommissionInstruction, InitializeCommissionArgs, SetFeeArgs, WithdrawArgs};
+use crate::hashing::{set_fee_hash, withdraw_hash};
+use crate::pda;
+use crate::{PDA_COMMISSION_SEED, PDA_FEE_SCHEDULE_SEED, PDA_TREASURY_SEED};
+use crate::error::CommissionError;
+use upgrade::utils::{create_pda_account, next_nonce, validate_signer_set, verify_nonce, verify_not_expired, verify_signatures, verify_system_program, verify_sysvar, verify_token_program, verify_writable};
+use crate::events::{CommissionEvent, FeeCharged, FeeUpdated, Withdrawn};
+
+pub fn process_instruction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let instruction = CommissionInstruction::unpack(input)?;
+    match instruction {
+        CommissionInstruction::InitializeCommission(args) => {
+            msg!("Instruction: Create commission");
+            process_init_commission(program_id, accounts, args)
+        }
+        CommissionInstruction::SetFee(args) => {
+            msg!("Instruction: Set fee");
+            process_set_fee(program_id, accounts, args)
+        }
+        CommissionInstruction::ChargeFee(args) => {
+            msg!("Instruction: Charge fee");
+            process_charge_fee(program_id, accounts, args)
+        }
+        CommissionInstruction::Withdraw(args) => {
+            msg!("Instruction: Withdraw");
+            process_withdraw(program_id, accounts, args)
+        }
+    }
+}
+
+pub fn process_init_commission<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: InitializeCommissionArgs,
+) -> ProgramResult {
+    validate_signer_set::<CommissionError>(&args.public_keys, args.threshold)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let commission_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(CommissionError::MissingSigner.into());
+    }
+
+    verify_system_program::<CommissionError>(system_program)?;
+    verify_sysvar::<CommissionError>(rent_info, &sysvar::rent::id())?;
+
+    let (commission_key, bump) = pda::find_commission_address(program_id, &args.seed);
+    if commission_key != *commission_info.key {
+        return Err(CommissionError::WrongSeeds.into());
+    }
+
+    let rent = Rent::from_account_info(rent_info)?;
+
+    create_pda_account::<CommissionError>(commission_info, fee_payer_info, system_program, &rent, MAX_COMMISSION_SIZE, program_id, &[PDA_COMMISSION_SEED.as_bytes(), args.seed.as_ref(), &[bump]])?;
+
+    let mut commission: Commission = BorshDeserialize::deserialize(&mut commission_info.data.borrow_mut().as_ref())?;
+    if commission.is_initialized {
+        return Err(CommissionError::AlreadyInUse.into());
+    }
+
+    commission.seed = args.seed;
+    commission.bump = bump;
+    commission.public_keys = args.public_keys;
+    commission.threshold = args.threshold;
+    commission.domain = args.domain;
+    commission.nonce = 0;
+    commission.is_initialized = true;
+    commission.serialize(&mut *commission_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_set_fee<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetFeeArgs,
+) -> ProgramResult {
+    validate_fee(&args.fee)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let commission_info = next_account_info(account_info_iter)?;
+    let fee_schedule_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(CommissionError::MissingSigner.into());
+    }
+
+    verify_system_program::<CommissionError>(system_program)?;
+    verify_sysvar::<CommissionError>(rent_info, &sysvar::rent::id())?;
+
+    let mut commission = load_commission(program_id, commission_info)?;
+    verify_writable::<CommissionError>(commission_info)?;
+
+    let (fee_schedule_key, bump) = pda::find_fee_schedule_address(program_id, commission_info.key, &args.mint);
+    if fee_schedule_key != *fee_schedule_info.key {
+        return Err(CommissionError::WrongSeeds.into());
+    }
+
+    verify_not_expired::<CommissionError>(args.expires_at)?;
+    verify_nonce::<CommissionError>(args.nonce, commission.nonce)?;
+
+    let hash = set_fee_hash(program_id, commission_info.key, &commission, &args.mint, &args.fee, args.nonce, args.expires_at);
+    verify_signatures::<CommissionError>(hash.as_ref(), &args.signatures, accounts, &commission.public_keys, commission.threshold)?;
+
+    next_nonce::<CommissionError>(&mut commission.nonce)?;
+    commission.serialize(&mut *commission_info.data.borrow_mut())?;
+
+    // FeeSchedule of the mint is created by its first update
+    if fee_schedule_info.owner != program_id {
+        let rent = Rent::from_account_info(rent_info)?;
+        create_pda_account::<CommissionError>(fee_schedule_info, fee_payer_info, system_program, &rent, MAX_FEE_SCHEDULE_SIZE, program_id, &[PDA_FEE_SCHEDULE_SEED.as_bytes(), commission_info.key.as_ref(), args.mint.as_ref(), &[bump]])?;
+    }
+
+    let fee_schedule = FeeSchedule {
+        commission: *commission_info.key,
+        mint: args.mint,
+        fee: args.fee,
+        is_initialized: true,
+    };
+    fee_schedule.serialize(&mut *fee_schedule_info.data.borrow_mut())?;
+
+    CommissionEvent::FeeUpdated(FeeUpdated {
+        commission: *commission_info.key,
+        mint: args.mint,
+        fee: args.fee,
+        nonce: args.nonce,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_charge_fee<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ChargeFeeArgs,
+) -> ProgramResult {
+    if args.amount == 0 {
+        return Err(CommissionError::InvalidAmount.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let commission_info = next_account_info(account_info_iter)?;
+    let fee_schedule_info = next_account_info(account_info_iter)?;
+    let treasury_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !owner_info.is_signer {
+        return Err(CommissionError::MissingSigner.into());
+    }
+
+    verify_token_program::<CommissionError>(token_program)?;
+    verify_system_program::<CommissionError>(system_program)?;
+    verify_sysvar::<CommissionError>(rent_info, &sysvar::rent::id())?;
+
+    load_commission(program_id, commission_info)?;
+    let fee_schedule = load_fee_schedule(program_id, commission_info.key, mint_info.key, fee_schedule_info)?;
+    let decimals = mint_decimals(mint_info)?;
+
+    let fee = fee_schedule.fee.calculate(args.amount).ok_or(CommissionError::MathOverflow)?;
+    if fee > args.amount {
+        msg!("Fee {} exceeds amount {}", fee, args.amount);
+        return Err(CommissionError::FeeExceedsAmount.into());
+    }
+
+    let (treasury_key, treasury_bump) = pda::find_treasury_address(program_id, commission_info.key, mint_info.key);
+    if treasury_key != *treasury_info.key {
+        return Err(CommissionError::WrongSeeds.into());
+    }
+
+    // Treasury of the mint is created by its first charge
+    if treasury_info.data_is_empty() {
+        let rent = Rent::from_account_info(rent_info)?;
+        create_pda_account::<CommissionError>(treasury_info, owner_info, system_program, &rent, spl_token::state::Account::LEN, &spl_token::id(), &[PDA_TREASURY_SEED.as_bytes(), commission_info.key.as_ref(), mint_info.key.as_ref(), &[treasury_bump]])?;
+        invoke(
+            &spl_token::instruction::initialize_account3(&spl_token::id(), treasury_info.key, mint_info.key, commission_info.key)?,
+            &[treasury_info.clone(), mint_info.clone()],
+        )?;
+    }
+
+    if fee > 0 {
+        invoke(
+            &spl_token::instruction::transfer_checked(&spl_token::id(), source_info.key, mint_info.key, treasury_info.key, owner_info.key, &[], fee, decimals)?,
+            &[source_info.clone(), mint_info.clone(), treasury_info.clone(), owner_info.clone()],
+        )?;
+    }
+
+    set_return_data(&fee.to_le_bytes());
+
+    CommissionEvent::FeeCharged(FeeCharged {
+        commission: *commission_info.key,
+        mint: *mint_info.key,
+        payer: *owner_info.key,
+        amount: args.amount,
+        fee,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_withdraw<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: WithdrawArgs,
+) -> ProgramResult {
+    if args.amount == 0 {
+        return Err(CommissionError::InvalidAmount.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let commission_info = next_account_info(account_info_iter)?;
+    let treasury_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    verify_token_program::<CommissionError>(token_program)?;
+
+    let mut commission = load_commission(program_id, commission_info)?;
+    verify_writable::<CommissionError>(commission_info)?;
+    let decimals = mint_decimals(mint_info)?;
+
+    let (treasury_key, _) = pda::find_treasury_address(program_id, commission_info.key, mint_info.key);
+    if treasury_key != *treasury_info.key {
+        return Err(CommissionError::WrongSeeds.into());
+    }
+
+    if treasury_info.owner != &spl_token::id() {
+        return Err(CommissionError::InsufficientFunds.into());
+    }
+
+    let available = spl_token::state::Account::unpack(&treasury_info.data.borrow())?.amount;
+    if args.amount > available {
+        msg!("Treasury holds {}", available);
+        return Err(CommissionError::InsufficientFunds.into());
+    }
+
+    verify_not_expired::<CommissionError>(args.expires_at)?;
+    verify_nonce::<CommissionError>(args.nonce, commission.nonce)?;
+
+    let hash = withdraw_hash(program_id, commission_info.key, &commission, mint_info.key, recipient_info.key, args.amount, args.nonce, args.expires_at);
+    verify_signatures::<CommissionError>(hash.as_ref(), &args.signatures, accounts, &commission.public_keys, commission.threshold)?;
+
+    // Advanced before the transfer: a Withdraw reentering from the token program fails the nonce check
+    next_nonce::<CommissionError>(&mut commission.nonce)?;
+    commission.serialize(&mut *commission_info.data.borrow_mut())?;
+
+    invoke_signed(
+        &spl_token::instruction::transfer_checked(&spl_token::id(), treasury_info.key, mint_info.key, recipient_info.key, commission_info.key, &[], args.amount, decimals)?,
+        &[treasury_info.clone(), mint_info.clone(), recipient_info.clone(), commission_info.clone()],
+        &[&[PDA_COMMISSION_SEED.as_bytes(), commission.seed.as_ref(), &[commission.bump]]],
+    )?;
+
+    CommissionEvent::Withdrawn(Withdrawn {
+        commission: *commission_info.key,
+        mint: *mint_info.key,
+        recipient: *recipient_info.key,
+        amount: args.amount,
+        nonce: args.nonce,
+    }).emit();
+    Ok(())
+}
+
+
+/// Loads initialized Commission owned by the program from `commission_info`.
+fn load_commission(program_id: &Pubkey, commission_info: &AccountInfo) -> Result<Commission, ProgramError> {
+    if commission_info.owner != program_id {
+        return Err(CommissionError::AccountOwnerMismatch.into());
+    }
+
+    let commission: Commission = BorshDeserialize::deserialize(&mut commission_info.data.borrow().as_ref())?;
+    if !commission.is_initialized {
+        return Err(CommissionError::NotInitialized.into());
+    }
+
+    Ok(commission)
+}
+
+
+/// Loads FeeSchedule of `mint` in Commission at `commission`, a mint without fee schedule can not be charged.
+fn load_fee_schedule(program_id: &Pubkey, commission: &Pubkey, mint: &Pubkey, fee_schedule_info: &AccountInfo) -> Result<FeeSchedule, ProgramError> {
+    if *fee_schedule_info.key != pda::find_fee_schedule_address(program_id, commission, mint).0 {
+        return Err(CommissionError::WrongSeeds.into());
+    }
+
+    if fee_schedule_info.owner != program_id {
+        return Err(CommissionError::NotInitialized.into());
+    }
+
+    let fee_schedule: FeeSchedule = BorshDeserialize::deserialize(&mut fee_schedule_info.data.borrow().as_ref())?;
+    if !fee_schedule.is_initialized {
+        return Err(CommissionError::NotInitialized.into());
+    }
+
+    Ok(fee_schedule)
+}
+
+
+/// Decimals of SPL Token `mint_info`, required by `transfer_checked`.
+fn mint_decimals(mint_info: &AccountInfo) -> Result<u8, ProgramError> {
+    if mint_info.owner != &spl_token::id() {
+        return Err(CommissionError::InvalidTokenProgram.into());
+    }
+
+    Ok(spl_token::state::Mint::unpack(&mint_info.data.borrow())?.decimals)
+}
+
+
+fn validate_fee(fee: &Fee) -> ProgramResult {
+    if let Fee::BasisPoints(basis_points) = fee {
+        if *basis_points > MAX_BASIS_POINTS {
+            return Err(CommissionError::InvalidFee.into());
+        }
+    }
+
+    Ok(())
+}
+
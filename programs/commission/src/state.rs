@@ -0,0 +1,59 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::pubkey::Pubkey;
+use upgrade::state::{AdminKey, MAX_ADMIN_KEY_SIZE, MAX_SIGNERS};
+
+/// Denominator of fees in basis points, a fee of MAX_BASIS_POINTS takes the whole amount
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+pub const MAX_COMMISSION_SIZE: usize = 32 + 1 + 4 + MAX_SIGNERS * MAX_ADMIN_KEY_SIZE + 1 + 32 + 8 + 1;
+
+pub const MAX_FEE_SCHEDULE_SIZE: usize = 32 + 32 + 1 + 8 + 1;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct Commission {
+    // Seed of the commission address, several collectors can be deployed by one program
+    pub seed: [u8; 32],
+    pub bump: u8,
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize fee updates and withdrawals
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+    // Nonce of the next signed operation, shared by SetFee and Withdraw
+    pub nonce: u64,
+    pub is_initialized: bool,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Fee {
+    // Fixed amount in base units of the mint, charged regardless of the charged amount
+    Flat(u64),
+    // Share of the charged amount in basis points, rounded down
+    BasisPoints(u16),
+}
+
+impl Fee {
+    /// Fee charged from `amount`, None if it does not fit u64.
+    pub fn calculate(&self, amount: u64) -> Option<u64> {
+        match *self {
+            Fee::Flat(fee) => Some(fee),
+            Fee::BasisPoints(basis_points) => {
+                let fee = amount as u128 * basis_points as u128 / MAX_BASIS_POINTS as u128;
+                u64::try_from(fee).ok()
+            }
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct FeeSchedule {
+    pub commission: Pubkey,
+    pub mint: Pubkey,
+    pub fee: Fee,
+    pub is_initialized: bool,
+}
@@ -0,0 +1,147 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, msg, program_error::{PrintProgramError, ProgramError}};
+use thiserror::Error;
+use upgrade::utils::{CommonError, TokenError};
+
+/// Errors that may be returned by the merkle distributor program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum DistributorError {
+    /// 0 The account cannot be initialized because it is already being used.
+    #[error("Already in use")]
+    AlreadyInUse,
+    /// 1 The account hasn't been initialized
+    #[error("Not initialized")]
+    NotInitialized,
+    /// 2 Account address does not match the seeds
+    #[error("Wrong seeds")]
+    WrongSeeds,
+    /// 3 Distributor account is owned by another program
+    #[error("Account owner mismatch")]
+    AccountOwnerMismatch,
+    /// 4 Signer set is empty, too large, contains unsupported keys or has wrong threshold
+    #[error("Invalid signer set")]
+    InvalidSignerSet,
+    /// 5 Signature is malformed or does not recover to a signer of the distributor
+    #[error("Invalid signature")]
+    InvalidSignature,
+    /// 6 Several signatures recovered to the same signer
+    #[error("Duplicate signature")]
+    DuplicateSignature,
+    /// 7 Number of valid signatures is less than threshold
+    #[error("Not enough signatures")]
+    NotEnoughSignatures,
+    /// 8 Signed operation has expired
+    #[error("Signature expired")]
+    SignatureExpired,
+    /// 9 Operation is signed for another nonce
+    #[error("Nonce mismatch")]
+    NonceMismatch,
+    /// 10 Amount is zero
+    #[error("Invalid amount")]
+    InvalidAmount,
+    /// 11 Merkle root is not set by SetRoot yet
+    #[error("Root not set")]
+    RootNotSet,
+    /// 12 Proof does not lead from the claimed leaf to the root, or is too long
+    #[error("Invalid proof")]
+    InvalidProof,
+    /// 13 Leaf of the index was already claimed
+    #[error("Already claimed")]
+    AlreadyClaimed,
+    /// 14 Mint does not match the mint of the distributor
+    #[error("Invalid mint")]
+    InvalidMint,
+    /// 15 Token account or program is not of SPL Token
+    #[error("Invalid token program")]
+    InvalidTokenProgram,
+    /// 16 Account that is modified by the instruction is passed as readonly
+    #[error("Account not writable")]
+    AccountNotWritable,
+    /// 17 Account that should sign the transaction did not sign it
+    #[error("Missing signer")]
+    MissingSigner,
+    /// 18 Arithmetic overflow
+    #[error("Math overflow")]
+    MathOverflow,
+    /// 19 Instruction data is encoded with unsupported format version
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    /// 20 Account passed in place of a sysvar is not that sysvar
+    #[error("Invalid sysvar account")]
+    InvalidSysvarAccount,
+    /// 21 Account passed in place of the system program is not the system program
+    #[error("Invalid system program")]
+    InvalidSystemProgram,
+}
+
+impl From<DistributorError> for ProgramError {
+    fn from(e: DistributorError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl CommonError for DistributorError {
+    fn already_in_use() -> Self {
+        DistributorError::AlreadyInUse
+    }
+
+    fn invalid_sysvar_account() -> Self {
+        DistributorError::InvalidSysvarAccount
+    }
+
+    fn invalid_system_program() -> Self {
+        DistributorError::InvalidSystemProgram
+    }
+
+    fn signature_expired() -> Self {
+        DistributorError::SignatureExpired
+    }
+
+    fn nonce_mismatch() -> Self {
+        DistributorError::NonceMismatch
+    }
+
+    fn duplicate_signature() -> Self {
+        DistributorError::DuplicateSignature
+    }
+
+    fn not_enough_signatures() -> Self {
+        DistributorError::NotEnoughSignatures
+    }
+
+    fn invalid_signature() -> Self {
+        DistributorError::InvalidSignature
+    }
+
+    fn invalid_signer_set() -> Self {
+        DistributorError::InvalidSignerSet
+    }
+
+    fn math_overflow() -> Self {
+        DistributorError::MathOverflow
+    }
+
+    fn account_not_writable() -> Self {
+        DistributorError::AccountNotWritable
+    }
+}
+
+impl TokenError for DistributorError {
+    fn invalid_token_program() -> Self {
+        DistributorError::InvalidTokenProgram
+    }
+}
+
+impl PrintProgramError for DistributorError {
+    fn print<E>(&self) {
+        msg!(&self.to_string());
+    }
+}
+
+impl<T> DecodeError<T> for DistributorError {
+    fn type_of() -> &'static str {
+        "DistributorError"
+    }
+}
@@ -0,0 +1,42 @@
+//! Borsh-encoded events emitted through `sol_log_data`, claim services decode root updates and claims from transaction logs
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+
+/// Merkle root of the claims was set by SetRoot.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct RootUpdated {
+    pub distributor: Pubkey,
+    pub root: [u8; 32],
+    // Nonce the signatures were made for
+    pub nonce: u64,
+}
+
+/// Leaf was claimed by Claim.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Claimed {
+    pub distributor: Pubkey,
+    pub index: u64,
+    // Recipient of the leaf, signer of the claim
+    pub recipient: Pubkey,
+    // Token account the tokens were transferred to
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub enum DistributorEvent {
+    RootUpdated(RootUpdated),
+    Claimed(Claimed),
+}
+
+impl DistributorEvent {
+    /// Writes the event to the transaction log.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+}
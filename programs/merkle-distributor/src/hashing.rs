@@ -0,0 +1,33 @@
+//! Hashes of the messages that should be signed by the distributor signers
+
+use solana_program::keccak::{hashv, Hash};
+use solana_program::pubkey::Pubkey;
+use crate::state::Distributor;
+use crate::HASH_CONSTANT;
+
+/// Payload prefix for SetRoot.
+pub const SET_ROOT_TAG: &str = "set-root";
+
+/// Hash to sign for SetRoot instruction:
+/// `keccak_hash(["solana-merkle-distributor-program".bytes, program_id, distributor, domain, "set-root".bytes, mint, root, nonce, expires_at])`
+/// with integers in big-endian.
+pub fn set_root_hash(
+    program_id: &Pubkey,
+    distributor_address: &Pubkey,
+    distributor: &Distributor,
+    root: &[u8; 32],
+    nonce: u64,
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        distributor_address.as_ref(),
+        distributor.domain.as_ref(),
+        SET_ROOT_TAG.as_bytes(),
+        distributor.mint.as_ref(),
+        root.as_ref(),
+        nonce.to_be_bytes().as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
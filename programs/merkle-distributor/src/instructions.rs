@@ -0,0 +1,234 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use solana_program::instruction::{Instruction, AccountMeta};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use shank::ShankInstruction;
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::state::AdminKey;
+use crate::pda;
+use crate::error::DistributorError;
+
+/// Version of the instruction data format, stored in the first byte of every instruction.
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Instruction data can not be larger than the transaction packet.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 1232;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct InitializeDistributorArgs {
+    // Seed of the distributor address
+    pub seed: [u8; 32],
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize root updates
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct SetRootArgs {
+    // Merkle root of the claims, built by `merkle::root`
+    pub root: [u8; 32],
+    // Nonce of the distributor the signatures were made for
+    pub nonce: u64,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the distributor signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ClaimArgs {
+    // Index of the leaf, unique across all roots of the distributor
+    pub index: u64,
+    // Amount of the leaf, in base units of the mint
+    pub amount: u64,
+    // Sibling hashes from the leaf to the root, at most MAX_PROOF_LENGTH
+    pub proof: Vec<[u8; 32]>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankInstruction)]
+pub enum DistributorInstruction {
+    /// Initialize new Distributor of the mint controlled by the signer set with threshold, and its vault token
+    /// account. The vault is funded by plain token transfers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Distributor account to initialize
+    ///   1. `[writable]` The vault token account
+    ///   2. `[]` The mint
+    ///   3. `[writable,signer]` The fee payer
+    ///   4. `[]` Token program
+    ///   5. `[]` System program
+    ///   6. `[]` Rent sysvar
+    #[account(0, writable, name="distributor", desc="The Distributor account to initialize")]
+    #[account(1, writable, name="vault", desc="The vault token account")]
+    #[account(2, name="mint", desc="The mint")]
+    #[account(3, writable, signer, name="payer", desc="The fee payer")]
+    #[account(4, name="token_program", desc="Token program")]
+    #[account(5, name="system_program", desc="System program")]
+    #[account(6, name="rent", desc="Rent sysvar")]
+    InitializeDistributor(InitializeDistributorArgs),
+
+    /// Set the merkle root of the claims. The Keccak Hash of
+    /// `["solana-merkle-distributor-program".bytes, program_id, distributor, domain, "set-root".bytes, mint, root, nonce, expires_at]`
+    /// should be signed by `threshold` of the signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one. Leaves claimed under previous roots stay claimed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Distributor account
+    ///   1. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, writable, name="distributor", desc="The Distributor account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    SetRoot(SetRootArgs),
+
+    /// Claim `amount` of the leaf `index` of the recipient to the destination token account with an inclusion
+    /// proof of the leaf in the current root. Creates ClaimReceipt of the index, paid by the recipient, so the leaf
+    /// can be claimed only once. The Distributor account is not modified.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The Distributor account
+    ///   1. `[writable]` The ClaimReceipt account of the leaf index
+    ///   2. `[writable]` The vault token account
+    ///   3. `[]` The mint
+    ///   4. `[writable]` The destination token account
+    ///   5. `[writable,signer]` The recipient of the leaf
+    ///   6. `[]` Token program
+    ///   7. `[]` System program
+    #[account(0, name="distributor", desc="The Distributor account")]
+    #[account(1, writable, name="claim_receipt", desc="The ClaimReceipt account of the leaf index")]
+    #[account(2, writable, name="vault", desc="The vault token account")]
+    #[account(3, name="mint", desc="The mint")]
+    #[account(4, writable, name="destination", desc="The destination token account")]
+    #[account(5, writable, signer, name="recipient", desc="The recipient of the leaf")]
+    #[account(6, name="token_program", desc="Token program")]
+    #[account(7, name="system_program", desc="System program")]
+    Claim(ClaimArgs),
+}
+
+impl DistributorInstruction {
+    /// Encodes the instruction prefixed with INSTRUCTION_VERSION.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![INSTRUCTION_VERSION];
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Decodes instruction data produced by `pack`. Data of another format version is rejected instead of being
+    /// misparsed, and trailing bytes are not allowed.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < 2 || input.len() > MAX_INSTRUCTION_DATA_LEN {
+            msg!("Invalid instruction data length: {}", input.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if input[0] != INSTRUCTION_VERSION {
+            msg!("Instruction version {} is not supported, expected {}", input[0], INSTRUCTION_VERSION);
+            return Err(DistributorError::UnsupportedInstructionVersion.into());
+        }
+
+        Ok(Self::try_from_slice(&input[1..])?)
+    }
+}
+
+pub fn initialize_distributor(
+    program_id: Pubkey,
+    mint: Pubkey,
+    fee_payer: Pubkey,
+    seed: [u8; 32],
+    public_keys: Vec<AdminKey>,
+    threshold: u8,
+    domain: [u8; 32],
+) -> Instruction {
+    let (distributor, _) = pda::find_distributor_address(&program_id, &seed);
+    let (vault, _) = pda::find_vault_address(&program_id, &distributor);
+    Instruction{
+        program_id,
+        data: DistributorInstruction::InitializeDistributor(
+            InitializeDistributorArgs {
+                seed,
+                public_keys,
+                threshold,
+                domain,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(distributor, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// SetRoot with signatures in instruction args. Signatures verified by a preceding `secp256k1_program`
+/// instruction require the instructions sysvar appended to the accounts.
+pub fn set_root(
+    program_id: Pubkey,
+    distributor: Pubkey,
+    root: [u8; 32],
+    nonce: u64,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    Instruction{
+        program_id,
+        data: DistributorInstruction::SetRoot(
+            SetRootArgs {
+                root,
+                nonce,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(distributor, false),
+        ],
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn claim(
+    program_id: Pubkey,
+    distributor: Pubkey,
+    mint: Pubkey,
+    destination: Pubkey,
+    recipient: Pubkey,
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let (claim_receipt, _) = pda::find_claim_receipt_address(&program_id, &distributor, index);
+    let (vault, _) = pda::find_vault_address(&program_id, &distributor);
+    Instruction{
+        program_id,
+        data: DistributorInstruction::Claim(
+            ClaimArgs {
+                index,
+                amount,
+                proof,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(distributor, false),
+            AccountMeta::new(claim_receipt, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new(recipient, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+    }
+}
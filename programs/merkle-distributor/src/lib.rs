@@ -0,0 +1,19 @@
+//! Merkle distributor of SPL token airdrops: the signer set authorizes the merkle root of (index, recipient, amount)
+//! claims by SetRoot, recipients claim their amount from the vault of the distributor with an inclusion proof.
+//! Every claimed leaf index gets a ClaimReceipt account, so a leaf is claimed only once, also after root updates.
+//! Signatures are verified with `upgrade::ecdsa`, the scheme of the upgrade program.
+
+pub mod entrypoint;
+pub mod error;
+pub mod events;
+pub mod hashing;
+pub mod instructions;
+pub mod merkle;
+pub mod pda;
+pub mod processor;
+pub mod state;
+
+const HASH_CONSTANT: &str = "solana-merkle-distributor-program";
+const PDA_DISTRIBUTOR_SEED: &str = "distributor-account";
+const PDA_VAULT_SEED: &str = "distributor-vault-account";
+const PDA_CLAIM_RECEIPT_SEED: &str = "distributor-claim-account";
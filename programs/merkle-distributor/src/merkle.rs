@@ -0,0 +1,65 @@
+//! Merkle tree of the claims. Leaves and inner nodes are hashed with different prefixes, so an inner node can not be
+//! claimed as a leaf, and pairs are sorted before hashing, so a proof is a plain list of sibling hashes.
+//!
+//! `root` and `proof` build the tree off-chain the same way `verify` walks it on-chain: a node without a sibling
+//! is moved to the next level unchanged.
+
+use solana_program::keccak::hashv;
+use solana_program::pubkey::Pubkey;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Leaf of the claim of `amount` by `recipient`:
+/// `keccak_hash([0x00, index, recipient, amount])` with integers in big-endian.
+pub fn leaf(index: u64, recipient: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, index.to_be_bytes().as_ref(), recipient.as_ref(), amount.to_be_bytes().as_ref()]).to_bytes()
+}
+
+/// Parent of two nodes: `keccak_hash([0x01, min(a, b), max(a, b)])`.
+pub fn parent(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left.as_ref(), right.as_ref()]).to_bytes()
+}
+
+/// Whether `proof` leads from `leaf` to `root`.
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: &[u8; 32]) -> bool {
+    let computed = proof.iter().fold(*leaf, |node, sibling| parent(&node, sibling));
+    computed == *root
+}
+
+/// Root of the tree of `leaves`, zero for no leaves.
+pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+
+    level.first().copied().unwrap_or_default()
+}
+
+/// Inclusion proof of the leaf at `position` in `leaves`.
+pub fn proof(leaves: &[[u8; 32]], mut position: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(position ^ 1) {
+            proof.push(*sibling);
+        }
+
+        level = next_level(&level);
+        position /= 2;
+    }
+
+    proof
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level.chunks(2)
+        .map(|pair| match pair {
+            [a, b] => parent(a, b),
+            [a] => *a,
+            _ => unreachable!(),
+        })
+        .collect()
+}
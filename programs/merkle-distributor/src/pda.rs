@@ -0,0 +1,20 @@
+//! Addresses of the program derived accounts. Off-chain builders and the processor derive them here, so the seed
+//! strings and their ordering are defined once.
+
+use solana_program::pubkey::Pubkey;
+use crate::{PDA_CLAIM_RECEIPT_SEED, PDA_DISTRIBUTOR_SEED, PDA_VAULT_SEED};
+
+/// Address and bump of Distributor created with `seed`.
+pub fn find_distributor_address(program_id: &Pubkey, seed: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_DISTRIBUTOR_SEED.as_bytes(), seed], program_id)
+}
+
+/// Address and bump of the token account of Distributor at `distributor` that holds the distributed tokens.
+pub fn find_vault_address(program_id: &Pubkey, distributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_VAULT_SEED.as_bytes(), distributor.as_ref()], program_id)
+}
+
+/// Address and bump of ClaimReceipt of the leaf `index` in Distributor at `distributor`.
+pub fn find_claim_receipt_address(program_id: &Pubkey, distributor: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_CLAIM_RECEIPT_SEED.as_bytes(), distributor.as_ref(), index.to_be_bytes().as_ref()], program_id)
+}
@@ -0,0 +1,243 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult, msg,
+    program::{invoke, invoke_signed}, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    sysvar::{self, rent::Rent, Sysvar},
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::state::{ClaimReceipt, Distributor, MAX_CLAIM_RECEIPT_SIZE, MAX_DISTRIBUTOR_SIZE, MAX_PROOF_LENGTH};
+use crate::instructions::{ClaimArgs, DistributorInstruction, InitializeDistributorArgs, SetRootArgs};
+use crate::hashing::set_root_hash;
+use crate::merkle;
+use crate::pda;
+use crate::{PDA_CLAIM_RECEIPT_SEED, PDA_DISTRIBUTOR_SEED, PDA_VAULT_SEED};
+use crate::error::DistributorError;
+use upgrade::utils::{create_pda_account, next_nonce, validate_signer_set, verify_nonce, verify_not_expired, verify_signatures, verify_system_program, verify_sysvar, verify_token_program, verify_writable};
+use crate::events::{Claimed, DistributorEvent, RootUpdated};
+
+pub fn process_instruction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let instruction = DistributorInstruction::unpack(input)?;
+    match instruction {
+        DistributorInstruction::InitializeDistributor(args) => {
+            msg!("Instruction: Create distributor");
+            process_init_distributor(program_id, accounts, args)
+        }
+        DistributorInstruction::SetRoot(args) => {
+            msg!("Instruction: Set root");
+            process_set_root(program_id, accounts, args)
+        }
+        DistributorInstruction::Claim(args) => {
+            msg!("Instruction: Claim");
+            process_claim(program_id, accounts, args)
+        }
+    }
+}
+
+pub fn process_init_distributor<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: InitializeDistributorArgs,
+) -> ProgramResult {
+    validate_signer_set::<DistributorError>(&args.public_keys, args.threshold)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let distributor_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(DistributorError::MissingSigner.into());
+    }
+
+    verify_token_program::<DistributorError>(token_program)?;
+    verify_system_program::<DistributorError>(system_program)?;
+    verify_sysvar::<DistributorError>(rent_info, &sysvar::rent::id())?;
+
+    if mint_info.owner != &spl_token::id() {
+        return Err(DistributorError::InvalidTokenProgram.into());
+    }
+
+    let (distributor_key, bump) = pda::find_distributor_address(program_id, &args.seed);
+    if distributor_key != *distributor_info.key {
+        return Err(DistributorError::WrongSeeds.into());
+    }
+
+    let (vault_key, vault_bump) = pda::find_vault_address(program_id, distributor_info.key);
+    if vault_key != *vault_info.key {
+        return Err(DistributorError::WrongSeeds.into());
+    }
+
+    let rent = Rent::from_account_info(rent_info)?;
+
+    create_pda_account::<DistributorError>(distributor_info, fee_payer_info, system_program, &rent, MAX_DISTRIBUTOR_SIZE, program_id, &[PDA_DISTRIBUTOR_SEED.as_bytes(), args.seed.as_ref(), &[bump]])?;
+
+    let mut distributor: Distributor = BorshDeserialize::deserialize(&mut distributor_info.data.borrow_mut().as_ref())?;
+    if distributor.is_initialized {
+        return Err(DistributorError::AlreadyInUse.into());
+    }
+
+    create_pda_account::<DistributorError>(vault_info, fee_payer_info, system_program, &rent, spl_token::state::Account::LEN, &spl_token::id(), &[PDA_VAULT_SEED.as_bytes(), distributor_info.key.as_ref(), &[vault_bump]])?;
+    invoke(
+        &spl_token::instruction::initialize_account3(&spl_token::id(), vault_info.key, mint_info.key, distributor_info.key)?,
+        &[vault_info.clone(), mint_info.clone()],
+    )?;
+
+    distributor.seed = args.seed;
+    distributor.bump = bump;
+    distributor.public_keys = args.public_keys;
+    distributor.threshold = args.threshold;
+    distributor.domain = args.domain;
+    distributor.mint = *mint_info.key;
+    distributor.root = [0; 32];
+    distributor.nonce = 0;
+    distributor.is_initialized = true;
+    distributor.serialize(&mut *distributor_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_set_root<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetRootArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let distributor_info = next_account_info(account_info_iter)?;
+
+    let mut distributor = load_distributor(program_id, distributor_info)?;
+    verify_writable::<DistributorError>(distributor_info)?;
+
+    verify_not_expired::<DistributorError>(args.expires_at)?;
+    verify_nonce::<DistributorError>(args.nonce, distributor.nonce)?;
+
+    let hash = set_root_hash(program_id, distributor_info.key, &distributor, &args.root, args.nonce, args.expires_at);
+    verify_signatures::<DistributorError>(hash.as_ref(), &args.signatures, accounts, &distributor.public_keys, distributor.threshold)?;
+
+    next_nonce::<DistributorError>(&mut distributor.nonce)?;
+    distributor.root = args.root;
+    distributor.serialize(&mut *distributor_info.data.borrow_mut())?;
+
+    DistributorEvent::RootUpdated(RootUpdated {
+        distributor: *distributor_info.key,
+        root: args.root,
+        nonce: args.nonce,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_claim<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ClaimArgs,
+) -> ProgramResult {
+    if args.amount == 0 {
+        return Err(DistributorError::InvalidAmount.into());
+    }
+
+    if args.proof.len() > MAX_PROOF_LENGTH {
+        return Err(DistributorError::InvalidProof.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let distributor_info = next_account_info(account_info_iter)?;
+    let claim_receipt_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !recipient_info.is_signer {
+        return Err(DistributorError::MissingSigner.into());
+    }
+
+    verify_token_program::<DistributorError>(token_program)?;
+    verify_system_program::<DistributorError>(system_program)?;
+
+    let distributor = load_distributor(program_id, distributor_info)?;
+
+    if distributor.mint != *mint_info.key {
+        return Err(DistributorError::InvalidMint.into());
+    }
+
+    let decimals = spl_token::state::Mint::unpack(&mint_info.data.borrow())?.decimals;
+
+    let (vault_key, _) = pda::find_vault_address(program_id, distributor_info.key);
+    if vault_key != *vault_info.key {
+        return Err(DistributorError::WrongSeeds.into());
+    }
+
+    if distributor.root == [0; 32] {
+        return Err(DistributorError::RootNotSet.into());
+    }
+
+    let leaf = merkle::leaf(args.index, recipient_info.key, args.amount);
+    if !merkle::verify(&args.proof, &distributor.root, &leaf) {
+        return Err(DistributorError::InvalidProof.into());
+    }
+
+    let (claim_receipt_key, claim_receipt_bump) = pda::find_claim_receipt_address(program_id, distributor_info.key, args.index);
+    if claim_receipt_key != *claim_receipt_info.key {
+        return Err(DistributorError::WrongSeeds.into());
+    }
+
+    if claim_receipt_info.owner == program_id {
+        msg!("Leaf {} is already claimed", args.index);
+        return Err(DistributorError::AlreadyClaimed.into());
+    }
+
+    // Receipt is created before the transfer, so the leaf can not be claimed again by a reentrant call
+    let rent = Rent::get()?;
+    create_pda_account::<DistributorError>(claim_receipt_info, recipient_info, system_program, &rent, MAX_CLAIM_RECEIPT_SIZE, program_id, &[PDA_CLAIM_RECEIPT_SEED.as_bytes(), distributor_info.key.as_ref(), args.index.to_be_bytes().as_ref(), &[claim_receipt_bump]])?;
+
+    let claim_receipt = ClaimReceipt {
+        distributor: *distributor_info.key,
+        index: args.index,
+        recipient: *recipient_info.key,
+        amount: args.amount,
+        is_initialized: true,
+    };
+    claim_receipt.serialize(&mut *claim_receipt_info.data.borrow_mut())?;
+
+    invoke_signed(
+        &spl_token::instruction::transfer_checked(&spl_token::id(), vault_info.key, mint_info.key, destination_info.key, distributor_info.key, &[], args.amount, decimals)?,
+        &[vault_info.clone(), mint_info.clone(), destination_info.clone(), distributor_info.clone()],
+        &[&[PDA_DISTRIBUTOR_SEED.as_bytes(), distributor.seed.as_ref(), &[distributor.bump]]],
+    )?;
+
+    DistributorEvent::Claimed(Claimed {
+        distributor: *distributor_info.key,
+        index: args.index,
+        recipient: *recipient_info.key,
+        destination: *destination_info.key,
+        amount: args.amount,
+    }).emit();
+    Ok(())
+}
+
+
+/// Loads initialized Distributor owned by the program from `distributor_info`.
+fn load_distributor(program_id: &Pubkey, distributor_info: &AccountInfo) -> Result<Distributor, ProgramError> {
+    if distributor_info.owner != program_id {
+        return Err(DistributorError::AccountOwnerMismatch.into());
+    }
+
+    let distributor: Distributor = BorshDeserialize::deserialize(&mut distributor_info.data.borrow().as_ref())?;
+    if !distributor.is_initialized {
+        return Err(DistributorError::NotInitialized.into());
+    }
+
+    Ok(distributor)
+}
+
@@ -0,0 +1,43 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::pubkey::Pubkey;
+use upgrade::state::{AdminKey, MAX_ADMIN_KEY_SIZE, MAX_SIGNERS};
+
+/// Maximal number of hashes in an inclusion proof, fits trees of 2^32 leaves
+pub const MAX_PROOF_LENGTH: usize = 32;
+
+pub const MAX_DISTRIBUTOR_SIZE: usize = 32 + 1 + 4 + MAX_SIGNERS * MAX_ADMIN_KEY_SIZE + 1 + 32 + 32 + 32 + 8 + 1;
+
+pub const MAX_CLAIM_RECEIPT_SIZE: usize = 32 + 8 + 32 + 8 + 1;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct Distributor {
+    // Seed of the distributor address, several distributors can be deployed by one program
+    pub seed: [u8; 32],
+    pub bump: u8,
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize root updates
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+    // Mint of the distributed tokens
+    pub mint: Pubkey,
+    // Merkle root of the claims, zero until the first SetRoot
+    pub root: [u8; 32],
+    // Nonce of the next root update
+    pub nonce: u64,
+    pub is_initialized: bool,
+}
+
+/// Receipt of the claimed leaf, the address is derived from the leaf index.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct ClaimReceipt {
+    pub distributor: Pubkey,
+    pub index: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub is_initialized: bool,
+}
@@ -0,0 +1,126 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, msg, program_error::{PrintProgramError, ProgramError}};
+use thiserror::Error;
+use upgrade::utils::CommonError;
+
+/// Errors that may be returned by the message inbox program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum InboxError {
+    /// 0 The account cannot be initialized because it is already being used.
+    #[error("Already in use")]
+    AlreadyInUse,
+    /// 1 The account hasn't been initialized
+    #[error("Not initialized")]
+    NotInitialized,
+    /// 2 Account address does not match the seeds
+    #[error("Wrong seeds")]
+    WrongSeeds,
+    /// 3 Inbox account is owned by another program
+    #[error("Account owner mismatch")]
+    AccountOwnerMismatch,
+    /// 4 Signer set is empty, too large, contains unsupported keys or has wrong threshold
+    #[error("Invalid signer set")]
+    InvalidSignerSet,
+    /// 5 Signature is malformed or does not recover to a signer of the inbox
+    #[error("Invalid signature")]
+    InvalidSignature,
+    /// 6 Several signatures recovered to the same signer
+    #[error("Duplicate signature")]
+    DuplicateSignature,
+    /// 7 Number of valid signatures is less than threshold
+    #[error("Not enough signatures")]
+    NotEnoughSignatures,
+    /// 8 Signed operation has expired
+    #[error("Signature expired")]
+    SignatureExpired,
+    /// 9 Operation is signed for another nonce
+    #[error("Nonce mismatch")]
+    NonceMismatch,
+    /// 10 Target program of the message is not executable or is the inbox program itself
+    #[error("Invalid target program")]
+    InvalidTargetProgram,
+    /// 11 Account that is modified by the instruction is passed as readonly
+    #[error("Account not writable")]
+    AccountNotWritable,
+    /// 12 Account that should sign the transaction did not sign it
+    #[error("Missing signer")]
+    MissingSigner,
+    /// 13 Arithmetic overflow
+    #[error("Math overflow")]
+    MathOverflow,
+    /// 14 Instruction data is encoded with unsupported format version
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    /// 15 Account passed in place of a sysvar is not that sysvar
+    #[error("Invalid sysvar account")]
+    InvalidSysvarAccount,
+    /// 16 Account passed in place of the system program is not the system program
+    #[error("Invalid system program")]
+    InvalidSystemProgram,
+}
+
+impl From<InboxError> for ProgramError {
+    fn from(e: InboxError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl CommonError for InboxError {
+    fn already_in_use() -> Self {
+        InboxError::AlreadyInUse
+    }
+
+    fn invalid_sysvar_account() -> Self {
+        InboxError::InvalidSysvarAccount
+    }
+
+    fn invalid_system_program() -> Self {
+        InboxError::InvalidSystemProgram
+    }
+
+    fn signature_expired() -> Self {
+        InboxError::SignatureExpired
+    }
+
+    fn nonce_mismatch() -> Self {
+        InboxError::NonceMismatch
+    }
+
+    fn duplicate_signature() -> Self {
+        InboxError::DuplicateSignature
+    }
+
+    fn not_enough_signatures() -> Self {
+        InboxError::NotEnoughSignatures
+    }
+
+    fn invalid_signature() -> Self {
+        InboxError::InvalidSignature
+    }
+
+    fn invalid_signer_set() -> Self {
+        InboxError::InvalidSignerSet
+    }
+
+    fn math_overflow() -> Self {
+        InboxError::MathOverflow
+    }
+
+    fn account_not_writable() -> Self {
+        InboxError::AccountNotWritable
+    }
+}
+
+impl PrintProgramError for InboxError {
+    fn print<E>(&self) {
+        msg!(&self.to_string());
+    }
+}
+
+impl<T> DecodeError<T> for InboxError {
+    fn type_of() -> &'static str {
+        "InboxError"
+    }
+}
@@ -0,0 +1,31 @@
+//! Borsh-encoded events emitted through `sol_log_data`, relayers decode executed messages from transaction logs
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+
+/// Message was executed by Execute.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Executed {
+    pub inbox: Pubkey,
+    // Program invoked with the message
+    pub program: Pubkey,
+    // Keccak hash of the instruction data of the message
+    pub data_hash: [u8; 32],
+    // Nonce the signatures were made for
+    pub nonce: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub enum InboxEvent {
+    Executed(Executed),
+}
+
+impl InboxEvent {
+    /// Writes the event to the transaction log.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+}
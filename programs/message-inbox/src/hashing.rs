@@ -0,0 +1,48 @@
+//! Hashes of the messages that should be signed by the inbox signers
+
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::keccak::{hash, hashv, Hash};
+use solana_program::pubkey::Pubkey;
+use crate::state::Inbox;
+use crate::HASH_CONSTANT;
+
+/// Payload prefix for Execute.
+pub const EXECUTE_TAG: &str = "execute";
+
+/// Digest of the accounts of a message: `keccak_hash([pubkey, is_signer, is_writable, ...])` with flags as one byte.
+/// The authority of the inbox is a signer of the message, the processor marks it as signer of the invocation.
+pub fn accounts_digest(accounts: &[AccountMeta]) -> Hash {
+    let mut data = Vec::with_capacity(accounts.len() * 34);
+    for meta in accounts {
+        data.extend_from_slice(meta.pubkey.as_ref());
+        data.push(meta.is_signer as u8);
+        data.push(meta.is_writable as u8);
+    }
+
+    hash(&data)
+}
+
+/// Hash to sign for Execute instruction:
+/// `keccak_hash(["solana-message-inbox-program".bytes, program_id, inbox, domain, "execute".bytes, target_program, keccak_hash(data), accounts_digest, nonce, expires_at])`
+/// with integers in big-endian.
+pub fn execute_hash(
+    program_id: &Pubkey,
+    inbox_address: &Pubkey,
+    inbox: &Inbox,
+    message: &Instruction,
+    nonce: u64,
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        inbox_address.as_ref(),
+        inbox.domain.as_ref(),
+        EXECUTE_TAG.as_bytes(),
+        message.program_id.as_ref(),
+        hash(&message.data).as_ref(),
+        accounts_digest(&message.accounts).as_ref(),
+        nonce.to_be_bytes().as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
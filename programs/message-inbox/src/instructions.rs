@@ -0,0 +1,170 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use solana_program::instruction::{Instruction, AccountMeta};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use shank::ShankInstruction;
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::state::AdminKey;
+use crate::pda;
+use crate::error::InboxError;
+
+/// Version of the instruction data format, stored in the first byte of every instruction.
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Instruction data can not be larger than the transaction packet.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 1232;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct InitializeInboxArgs {
+    // Seed of the inbox address
+    pub seed: [u8; 32],
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize a message
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ExecuteArgs {
+    // Instruction data the target program is invoked with
+    pub data: Vec<u8>,
+    // Nonce of the inbox the signatures were made for
+    pub nonce: u64,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the inbox signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankInstruction)]
+pub enum InboxInstruction {
+    /// Initialize new Inbox controlled by the signer set with threshold.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Inbox account to initialize
+    ///   1. `[writable,signer]` The fee payer
+    ///   2. `[]` System program
+    ///   3. `[]` Rent sysvar
+    #[account(0, writable, name="inbox", desc="The Inbox account to initialize")]
+    #[account(1, writable, signer, name="payer", desc="The fee payer")]
+    #[account(2, name="system_program", desc="System program")]
+    #[account(3, name="rent", desc="Rent sysvar")]
+    InitializeInbox(InitializeInboxArgs),
+
+    /// Invoke the target program with `data` and the message accounts, the authority of the inbox signs the
+    /// invocation. The Keccak Hash of
+    /// `["solana-message-inbox-program".bytes, program_id, inbox, domain, "execute".bytes, target_program, keccak_hash(data), accounts_digest, nonce, expires_at]`
+    /// should be signed by `threshold` of the signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one. See `hashing::accounts_digest` for the digest of the message accounts.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Inbox account
+    ///   1. `[]` The authority of the inbox
+    ///   2. `[]` The target program
+    ///   3. `[]` Instructions sysvar
+    ///   4. `[]` The accounts of the message in its order and with its flags, the authority is passed as non-signer
+    #[account(0, writable, name="inbox", desc="The Inbox account")]
+    #[account(1, name="authority", desc="The authority of the inbox")]
+    #[account(2, name="target_program", desc="The target program")]
+    #[account(3, name="instructions", desc="Instructions sysvar")]
+    Execute(ExecuteArgs),
+}
+
+impl InboxInstruction {
+    /// Encodes the instruction prefixed with INSTRUCTION_VERSION.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![INSTRUCTION_VERSION];
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Decodes instruction data produced by `pack`. Data of another format version is rejected instead of being
+    /// misparsed, and trailing bytes are not allowed.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < 2 || input.len() > MAX_INSTRUCTION_DATA_LEN {
+            msg!("Invalid instruction data length: {}", input.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if input[0] != INSTRUCTION_VERSION {
+            msg!("Instruction version {} is not supported, expected {}", input[0], INSTRUCTION_VERSION);
+            return Err(InboxError::UnsupportedInstructionVersion.into());
+        }
+
+        Ok(Self::try_from_slice(&input[1..])?)
+    }
+}
+
+pub fn initialize_inbox(
+    program_id: Pubkey,
+    fee_payer: Pubkey,
+    seed: [u8; 32],
+    public_keys: Vec<AdminKey>,
+    threshold: u8,
+    domain: [u8; 32],
+) -> Instruction {
+    let (inbox, _) = pda::find_inbox_address(&program_id, &seed);
+    Instruction{
+        program_id,
+        data: InboxInstruction::InitializeInbox(
+            InitializeInboxArgs {
+                seed,
+                public_keys,
+                threshold,
+                domain,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(inbox, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// Execute of `message` with signatures in instruction args, the authority of the inbox is a signer of the message.
+/// Signatures may also be verified by a preceding `secp256k1_program` instruction of the transaction.
+pub fn execute(
+    program_id: Pubkey,
+    inbox: Pubkey,
+    message: Instruction,
+    nonce: u64,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    let (authority, _) = pda::find_authority_address(&program_id, &inbox);
+    let mut accounts = vec![
+        AccountMeta::new(inbox, false),
+        AccountMeta::new_readonly(authority, false),
+        AccountMeta::new_readonly(message.program_id, false),
+        AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+    ];
+
+    // The authority signs by the program, not by the transaction
+    accounts.extend(message.accounts.into_iter().map(|meta| AccountMeta {
+        is_signer: meta.is_signer && meta.pubkey != authority,
+        ..meta
+    }));
+
+    Instruction{
+        program_id,
+        data: InboxInstruction::Execute(
+            ExecuteArgs {
+                data: message.data,
+                nonce,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts,
+    }
+}
@@ -0,0 +1,17 @@
+//! Inbox of cross-chain messages: Execute invokes the target program of a message with its instruction data and
+//! accounts, signed by the authority account of the inbox. Messages are authorized by the signer set with
+//! `upgrade::ecdsa`, the signature scheme of the upgrade program, so the signer set governs any program that accepts
+//! the inbox authority, not only program upgrades.
+
+pub mod entrypoint;
+pub mod error;
+pub mod events;
+pub mod hashing;
+pub mod instructions;
+pub mod pda;
+pub mod processor;
+pub mod state;
+
+const HASH_CONSTANT: &str = "solana-message-inbox-program";
+const PDA_INBOX_SEED: &str = "inbox-account";
+const PDA_AUTHORITY_SEED: &str = "inbox-authority-account";
@@ -0,0 +1,16 @@
+//! Addresses of the program derived accounts. Off-chain builders and the processor derive them here, so the seed
+//! strings and their ordering are defined once.
+
+use solana_program::pubkey::Pubkey;
+use crate::{PDA_AUTHORITY_SEED, PDA_INBOX_SEED};
+
+/// Address and bump of Inbox created with `seed`.
+pub fn find_inbox_address(program_id: &Pubkey, seed: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_INBOX_SEED.as_bytes(), seed], program_id)
+}
+
+/// Address and bump of the authority of Inbox at `inbox`, the account that signs invocations of the messages. It
+/// holds no data, so it can own tokens, pay lamports and be set as upgrade or mint authority of other programs.
+pub fn find_authority_address(program_id: &Pubkey, inbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_AUTHORITY_SEED.as_bytes(), inbox.as_ref()], program_id)
+}
@@ -0,0 +1,167 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult, msg,
+    instruction::{AccountMeta, Instruction}, keccak,
+    program::invoke_signed, program_error::ProgramError, pubkey::Pubkey,
+    sysvar::{self, rent::Rent, Sysvar},
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::state::{Inbox, MAX_INBOX_SIZE};
+use crate::instructions::{ExecuteArgs, InboxInstruction, InitializeInboxArgs};
+use crate::hashing::execute_hash;
+use crate::pda;
+use crate::{PDA_AUTHORITY_SEED, PDA_INBOX_SEED};
+use crate::error::InboxError;
+use upgrade::utils::{create_pda_account, next_nonce, validate_signer_set, verify_nonce, verify_not_expired, verify_signatures, verify_system_program, verify_sysvar, verify_writable};
+use crate::events::{Executed, InboxEvent};
+
+pub fn process_instruction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let instruction = InboxInstruction::unpack(input)?;
+    match instruction {
+        InboxInstruction::InitializeInbox(args) => {
+            msg!("Instruction: Create inbox");
+            process_init_inbox(program_id, accounts, args)
+        }
+        InboxInstruction::Execute(args) => {
+            msg!("Instruction: Execute");
+            process_execute(program_id, accounts, args)
+        }
+    }
+}
+
+pub fn process_init_inbox<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: InitializeInboxArgs,
+) -> ProgramResult {
+    validate_signer_set::<InboxError>(&args.public_keys, args.threshold)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let inbox_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(InboxError::MissingSigner.into());
+    }
+
+    verify_system_program::<InboxError>(system_program)?;
+    verify_sysvar::<InboxError>(rent_info, &sysvar::rent::id())?;
+
+    let (inbox_key, bump) = pda::find_inbox_address(program_id, &args.seed);
+    if inbox_key != *inbox_info.key {
+        return Err(InboxError::WrongSeeds.into());
+    }
+
+    let rent = Rent::from_account_info(rent_info)?;
+
+    create_pda_account::<InboxError>(inbox_info, fee_payer_info, system_program, &rent, MAX_INBOX_SIZE, program_id, &[PDA_INBOX_SEED.as_bytes(), args.seed.as_ref(), &[bump]])?;
+
+    let mut inbox: Inbox = BorshDeserialize::deserialize(&mut inbox_info.data.borrow_mut().as_ref())?;
+    if inbox.is_initialized {
+        return Err(InboxError::AlreadyInUse.into());
+    }
+
+    inbox.seed = args.seed;
+    inbox.bump = bump;
+    inbox.public_keys = args.public_keys;
+    inbox.threshold = args.threshold;
+    inbox.domain = args.domain;
+    inbox.nonce = 0;
+    inbox.is_initialized = true;
+    inbox.serialize(&mut *inbox_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_execute<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ExecuteArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let inbox_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let target_program = next_account_info(account_info_iter)?;
+    let instructions_info = next_account_info(account_info_iter)?;
+
+    let mut inbox = load_inbox(program_id, inbox_info)?;
+    verify_writable::<InboxError>(inbox_info)?;
+    verify_sysvar::<InboxError>(instructions_info, &sysvar::instructions::id())?;
+
+    let (authority_key, authority_bump) = pda::find_authority_address(program_id, inbox_info.key);
+    if authority_key != *authority_info.key {
+        return Err(InboxError::WrongSeeds.into());
+    }
+
+    // Invocation of the inbox itself would execute messages without their own signatures
+    if !target_program.executable || target_program.key == program_id {
+        msg!("Program {} can not be a message target", target_program.key);
+        return Err(InboxError::InvalidTargetProgram.into());
+    }
+
+    let message = Instruction {
+        program_id: *target_program.key,
+        accounts: message_accounts(&accounts[4..], &authority_key),
+        data: args.data,
+    };
+
+    verify_not_expired::<InboxError>(args.expires_at)?;
+    verify_nonce::<InboxError>(args.nonce, inbox.nonce)?;
+
+    let hash = execute_hash(program_id, inbox_info.key, &inbox, &message, args.nonce, args.expires_at);
+    verify_signatures::<InboxError>(hash.as_ref(), &args.signatures, core::slice::from_ref(instructions_info), &inbox.public_keys, inbox.threshold)?;
+
+    // The target program may call Execute again with the same signatures, by then they are bound to a used nonce
+    next_nonce::<InboxError>(&mut inbox.nonce)?;
+    inbox.serialize(&mut *inbox_info.data.borrow_mut())?;
+
+    invoke_signed(
+        &message,
+        accounts,
+        &[&[PDA_AUTHORITY_SEED.as_bytes(), inbox_info.key.as_ref(), &[authority_bump]]],
+    )?;
+
+    InboxEvent::Executed(Executed {
+        inbox: *inbox_info.key,
+        program: message.program_id,
+        data_hash: keccak::hash(&message.data).to_bytes(),
+        nonce: args.nonce,
+    }).emit();
+    Ok(())
+}
+
+
+/// Loads initialized Inbox owned by the program from `inbox_info`.
+fn load_inbox(program_id: &Pubkey, inbox_info: &AccountInfo) -> Result<Inbox, ProgramError> {
+    if inbox_info.owner != program_id {
+        return Err(InboxError::AccountOwnerMismatch.into());
+    }
+
+    let inbox: Inbox = BorshDeserialize::deserialize(&mut inbox_info.data.borrow().as_ref())?;
+    if !inbox.is_initialized {
+        return Err(InboxError::NotInitialized.into());
+    }
+
+    Ok(inbox)
+}
+
+
+/// Accounts of the message from the accounts following the instructions sysvar, the message may pass the sysvar
+/// to the target program as well. The authority is marked as signer, it signs the invocation by the seeds of the inbox.
+fn message_accounts(account_infos: &[AccountInfo], authority: &Pubkey) -> Vec<AccountMeta> {
+    account_infos.iter()
+        .map(|info| AccountMeta {
+            pubkey: *info.key,
+            is_signer: info.is_signer || info.key == authority,
+            is_writable: info.is_writable,
+        })
+        .collect()
+}
+
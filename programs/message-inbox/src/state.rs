@@ -0,0 +1,22 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use upgrade::state::{AdminKey, MAX_ADMIN_KEY_SIZE, MAX_SIGNERS};
+
+pub const MAX_INBOX_SIZE: usize = 32 + 1 + 4 + MAX_SIGNERS * MAX_ADMIN_KEY_SIZE + 1 + 32 + 8 + 1;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct Inbox {
+    // Seed of the inbox address, several inboxes can be deployed by one program
+    pub seed: [u8; 32],
+    pub bump: u8,
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize a message
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+    // Nonce of the next message, messages are executed in the order they were signed
+    pub nonce: u64,
+    pub is_initialized: bool,
+}
@@ -0,0 +1,156 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, msg, program_error::{PrintProgramError, ProgramError}};
+use thiserror::Error;
+use upgrade::utils::{CommonError, TokenError};
+
+/// Errors that may be returned by the NFT bridge program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum NftBridgeError {
+    /// 0 The account cannot be initialized because it is already being used.
+    #[error("Already in use")]
+    AlreadyInUse,
+    /// 1 The account hasn't been initialized
+    #[error("Not initialized")]
+    NotInitialized,
+    /// 2 Account address does not match the seeds
+    #[error("Wrong seeds")]
+    WrongSeeds,
+    /// 3 Program account is owned by another program
+    #[error("Account owner mismatch")]
+    AccountOwnerMismatch,
+    /// 4 Signer set is empty, too large, contains unsupported keys or has wrong threshold
+    #[error("Invalid signer set")]
+    InvalidSignerSet,
+    /// 5 Signature is malformed or does not recover to a signer of the bridge
+    #[error("Invalid signature")]
+    InvalidSignature,
+    /// 6 Several signatures recovered to the same signer
+    #[error("Duplicate signature")]
+    DuplicateSignature,
+    /// 7 Number of valid signatures is less than threshold
+    #[error("Not enough signatures")]
+    NotEnoughSignatures,
+    /// 8 Signed operation has expired
+    #[error("Signature expired")]
+    SignatureExpired,
+    /// 9 Operation is signed for another nonce
+    #[error("Nonce mismatch")]
+    NonceMismatch,
+    /// 10 Mint is not an NFT: its supply is not 1 or it has decimals
+    #[error("Not an NFT")]
+    NotAnNft,
+    /// 11 Metadata account is not the Metaplex metadata of the mint or exceeds the Metaplex limits
+    #[error("Invalid metadata")]
+    InvalidMetadata,
+    /// 12 NFT is not a verified member of its collection
+    #[error("Unverified collection")]
+    UnverifiedCollection,
+    /// 13 Collection of the NFT is not registered in the bridge
+    #[error("Collection not registered")]
+    CollectionNotRegistered,
+    /// 14 Origin token address is empty or too long
+    #[error("Invalid origin token")]
+    InvalidOriginToken,
+    /// 15 Destination address is empty or too long
+    #[error("Invalid destination")]
+    InvalidDestination,
+    /// 16 Token account or program is not of SPL Token
+    #[error("Invalid token program")]
+    InvalidTokenProgram,
+    /// 17 Account passed in place of Metaplex Token Metadata program is not that program
+    #[error("Invalid metadata program")]
+    InvalidMetadataProgram,
+    /// 18 Account passed in place of Associated Token Account program is not that program
+    #[error("Invalid associated token program")]
+    InvalidAssociatedTokenProgram,
+    /// 19 Account that is modified by the instruction is passed as readonly
+    #[error("Account not writable")]
+    AccountNotWritable,
+    /// 20 Account that should sign the transaction did not sign it
+    #[error("Missing signer")]
+    MissingSigner,
+    /// 21 Arithmetic overflow
+    #[error("Math overflow")]
+    MathOverflow,
+    /// 22 Instruction data is encoded with unsupported format version
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    /// 23 Account passed in place of a sysvar is not that sysvar
+    #[error("Invalid sysvar account")]
+    InvalidSysvarAccount,
+    /// 24 Account passed in place of the system program is not the system program
+    #[error("Invalid system program")]
+    InvalidSystemProgram,
+}
+
+impl From<NftBridgeError> for ProgramError {
+    fn from(e: NftBridgeError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl CommonError for NftBridgeError {
+    fn already_in_use() -> Self {
+        NftBridgeError::AlreadyInUse
+    }
+
+    fn invalid_sysvar_account() -> Self {
+        NftBridgeError::InvalidSysvarAccount
+    }
+
+    fn invalid_system_program() -> Self {
+        NftBridgeError::InvalidSystemProgram
+    }
+
+    fn signature_expired() -> Self {
+        NftBridgeError::SignatureExpired
+    }
+
+    fn nonce_mismatch() -> Self {
+        NftBridgeError::NonceMismatch
+    }
+
+    fn duplicate_signature() -> Self {
+        NftBridgeError::DuplicateSignature
+    }
+
+    fn not_enough_signatures() -> Self {
+        NftBridgeError::NotEnoughSignatures
+    }
+
+    fn invalid_signature() -> Self {
+        NftBridgeError::InvalidSignature
+    }
+
+    fn invalid_signer_set() -> Self {
+        NftBridgeError::InvalidSignerSet
+    }
+
+    fn math_overflow() -> Self {
+        NftBridgeError::MathOverflow
+    }
+
+    fn account_not_writable() -> Self {
+        NftBridgeError::AccountNotWritable
+    }
+}
+
+impl TokenError for NftBridgeError {
+    fn invalid_token_program() -> Self {
+        NftBridgeError::InvalidTokenProgram
+    }
+}
+
+impl PrintProgramError for NftBridgeError {
+    fn print<E>(&self) {
+        msg!(&self.to_string());
+    }
+}
+
+impl<T> DecodeError<T> for NftBridgeError {
+    fn type_of() -> &'static str {
+        "NftBridgeError"
+    }
+}
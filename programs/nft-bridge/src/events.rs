@@ -0,0 +1,74 @@
+//! Borsh-encoded events emitted through `sol_log_data`, the oracle decodes deposits from transaction logs
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+
+/// Collection was registered by RegisterCollection.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct CollectionRegistered {
+    pub bridge: Pubkey,
+    pub collection: Pubkey,
+}
+
+/// NFT was moved to custody by Deposit to be minted or released on the destination chain.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct NftDeposited {
+    pub bridge: Pubkey,
+    pub mint: Pubkey,
+    // Verified collection of a Solana NFT, None for a wrapped NFT returning to its origin chain
+    pub collection: Option<Pubkey>,
+    // Owner of the source token account
+    pub sender: Pubkey,
+    pub destination_chain: u64,
+    pub destination_address: Vec<u8>,
+    // Deposit nonce, unique within the bridge
+    pub nonce: u64,
+}
+
+/// NFT was released from custody by Release.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct NftReleased {
+    pub bridge: Pubkey,
+    pub mint: Pubkey,
+    // Recipient token account
+    pub recipient: Pubkey,
+    // Id of the transaction on the source chain the release was signed for
+    pub tx_id: [u8; 32],
+    pub nonce: u64,
+}
+
+/// Wrapped NFT of an origin NFT was minted by MintWrapped.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct WrappedNftMinted {
+    pub bridge: Pubkey,
+    pub mint: Pubkey,
+    // Recipient wallet
+    pub recipient: Pubkey,
+    pub origin_chain: u64,
+    pub origin_token: Vec<u8>,
+    pub token_id: [u8; 32],
+    // Id of the transaction on the origin chain the mint was signed for
+    pub tx_id: [u8; 32],
+    pub nonce: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub enum NftBridgeEvent {
+    CollectionRegistered(CollectionRegistered),
+    NftDeposited(NftDeposited),
+    NftReleased(NftReleased),
+    WrappedNftMinted(WrappedNftMinted),
+}
+
+impl NftBridgeEvent {
+    /// Writes the event to the transaction log.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+}
@@ -0,0 +1,108 @@
+//! Hashes of the messages that should be signed by the oracle signers
+
+use borsh::BorshSerialize;
+use solana_program::keccak::{hashv, Hash};
+use solana_program::pubkey::Pubkey;
+use crate::instructions::NftMetadata;
+use crate::state::NftBridge;
+use crate::HASH_CONSTANT;
+
+/// Payload prefix for RegisterCollection.
+pub const REGISTER_COLLECTION_TAG: &str = "register-collection";
+
+/// Payload prefix for Release.
+pub const RELEASE_TAG: &str = "release";
+
+/// Payload prefix for MintWrapped.
+pub const MINT_WRAPPED_TAG: &str = "mint-wrapped";
+
+/// Identifier of the origin NFT, seeds the address of its wrapped mint:
+/// `keccak_hash([origin_chain, token_id, origin_token])` with the chain in big-endian.
+pub fn origin_id(origin_chain: u64, origin_token: &[u8], token_id: &[u8; 32]) -> Hash {
+    hashv(&[origin_chain.to_be_bytes().as_ref(), token_id.as_ref(), origin_token])
+}
+
+/// Hash to sign for RegisterCollection instruction:
+/// `keccak_hash(["solana-nft-bridge-program".bytes, program_id, bridge, domain, "register-collection".bytes, collection, nonce, expires_at])`
+/// with integers in big-endian.
+pub fn register_collection_hash(
+    program_id: &Pubkey,
+    bridge_address: &Pubkey,
+    bridge: &NftBridge,
+    collection: &Pubkey,
+    nonce: u64,
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        bridge_address.as_ref(),
+        bridge.domain.as_ref(),
+        REGISTER_COLLECTION_TAG.as_bytes(),
+        collection.as_ref(),
+        nonce.to_be_bytes().as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
+
+/// Hash to sign for Release instruction:
+/// `keccak_hash(["solana-nft-bridge-program".bytes, program_id, bridge, domain, "release".bytes, mint, recipient, tx_id, nonce, expires_at])`
+/// with integers in big-endian.
+#[allow(clippy::too_many_arguments)]
+pub fn release_hash(
+    program_id: &Pubkey,
+    bridge_address: &Pubkey,
+    bridge: &NftBridge,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    tx_id: &[u8; 32],
+    nonce: u64,
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        bridge_address.as_ref(),
+        bridge.domain.as_ref(),
+        RELEASE_TAG.as_bytes(),
+        mint.as_ref(),
+        recipient.as_ref(),
+        tx_id.as_ref(),
+        nonce.to_be_bytes().as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
+
+/// Hash to sign for MintWrapped instruction:
+/// `keccak_hash(["solana-nft-bridge-program".bytes, program_id, bridge, domain, "mint-wrapped".bytes, origin_chain, origin_token.borsh_bytes, token_id, metadata.borsh_bytes, recipient, tx_id, nonce, expires_at])`
+/// with integers in big-endian.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_wrapped_hash(
+    program_id: &Pubkey,
+    bridge_address: &Pubkey,
+    bridge: &NftBridge,
+    origin_chain: u64,
+    origin_token: &[u8],
+    token_id: &[u8; 32],
+    metadata: &NftMetadata,
+    recipient: &Pubkey,
+    tx_id: &[u8; 32],
+    nonce: u64,
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        bridge_address.as_ref(),
+        bridge.domain.as_ref(),
+        MINT_WRAPPED_TAG.as_bytes(),
+        origin_chain.to_be_bytes().as_ref(),
+        origin_token.to_vec().try_to_vec().unwrap().as_ref(),
+        token_id.as_ref(),
+        metadata.try_to_vec().unwrap().as_ref(),
+        recipient.as_ref(),
+        tx_id.as_ref(),
+        nonce.to_be_bytes().as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
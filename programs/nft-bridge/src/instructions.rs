@@ -0,0 +1,437 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use solana_program::instruction::{Instruction, AccountMeta};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use shank::ShankInstruction;
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::state::AdminKey;
+use crate::pda;
+use crate::error::NftBridgeError;
+
+/// Version of the instruction data format, stored in the first byte of every instruction.
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Instruction data can not be larger than the transaction packet.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 1232;
+
+/// Metaplex token metadata of a wrapped NFT, as it is on the origin chain.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct NftMetadata {
+    // At most MAX_NAME_LENGTH bytes
+    pub name: String,
+    // At most MAX_SYMBOL_LENGTH bytes
+    pub symbol: String,
+    // At most MAX_URI_LENGTH bytes
+    pub uri: String,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct InitializeBridgeArgs {
+    // Seed of the bridge address
+    pub seed: [u8; 32],
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the oracle signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize operation
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct RegisterCollectionArgs {
+    // Mint of the collection NFT
+    pub collection: Pubkey,
+    // Nonce of the bridge the signatures were made for
+    pub nonce: u64,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the oracle signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct DepositArgs {
+    // Identifier of the destination chain, interpreted by the oracle
+    pub destination_chain: u64,
+    // Recipient address on the destination chain, at most MAX_DESTINATION_ADDRESS_LENGTH bytes
+    pub destination_address: Vec<u8>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ReleaseArgs {
+    // Id of the transaction on the source chain that locked or burned the bridged NFT
+    pub tx_id: [u8; 32],
+    // Nonce of the bridge the signatures were made for
+    pub nonce: u64,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the oracle signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct MintWrappedArgs {
+    // Identifier of the origin chain, interpreted by the oracle
+    pub origin_chain: u64,
+    // NFT contract address on the origin chain, at most MAX_ORIGIN_TOKEN_LENGTH bytes
+    pub origin_token: Vec<u8>,
+    // Token id on the origin chain
+    pub token_id: [u8; 32],
+    pub metadata: NftMetadata,
+    // Id of the transaction on the origin chain that locked the NFT
+    pub tx_id: [u8; 32],
+    // Nonce of the bridge the signatures were made for
+    pub nonce: u64,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the oracle signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankInstruction)]
+pub enum NftBridgeInstruction {
+    /// Initialize new NftBridge controlled by the oracle signer set with threshold.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The NftBridge account to initialize
+    ///   1. `[writable,signer]` The fee payer
+    ///   2. `[]` System program
+    ///   3. `[]` Rent sysvar
+    #[account(0, writable, name="bridge", desc="The NftBridge account to initialize")]
+    #[account(1, writable, signer, name="payer", desc="The fee payer")]
+    #[account(2, name="system_program", desc="System program")]
+    #[account(3, name="rent", desc="Rent sysvar")]
+    InitializeBridge(InitializeBridgeArgs),
+
+    /// Allow deposits of verified members of the collection. The Keccak Hash of
+    /// `["solana-nft-bridge-program".bytes, program_id, bridge, domain, "register-collection".bytes, collection, nonce, expires_at]`
+    /// should be signed by `threshold` of the oracle signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The NftBridge account
+    ///   1. `[writable]` The RegisteredCollection account to initialize
+    ///   2. `[writable,signer]` The fee payer
+    ///   3. `[]` System program
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, writable, name="bridge", desc="The NftBridge account")]
+    #[account(1, writable, name="registered_collection", desc="The RegisteredCollection account to initialize")]
+    #[account(2, writable, signer, name="payer", desc="The fee payer")]
+    #[account(3, name="system_program", desc="System program")]
+    #[account(4, name="rent", desc="Rent sysvar")]
+    #[account(5, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    RegisterCollection(RegisterCollectionArgs),
+
+    /// Move the NFT to the custody of the bridge and emit NftDeposited event with the destination chain and address.
+    /// The NFT should be either a verified member of a registered collection, so NFTs claiming a collection they
+    /// do not belong to can not be bridged, or a wrapped NFT of the bridge returning to its origin chain.
+    /// Programmable NFTs are not supported. The custody account is created by the first deposit of the NFT.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The NftBridge account
+    ///   1. `[]` The NFT mint
+    ///   2. `[]` The Metaplex metadata account of the mint
+    ///   3. `[writable]` The custody token account of the mint
+    ///   4. `[writable]` The source token account
+    ///   5. `[writable,signer]` The owner of the source token account
+    ///   6. `[]` RegisteredCollection of the NFT collection or WrappedNft of the mint
+    ///   7. `[]` Token program
+    ///   8. `[]` System program
+    ///   9. `[]` Rent sysvar
+    #[account(0, writable, name="bridge", desc="The NftBridge account")]
+    #[account(1, name="mint", desc="The NFT mint")]
+    #[account(2, name="metadata", desc="The Metaplex metadata account of the mint")]
+    #[account(3, writable, name="custody", desc="The custody token account of the mint")]
+    #[account(4, writable, name="source", desc="The source token account")]
+    #[account(5, writable, signer, name="owner", desc="The owner of the source token account")]
+    #[account(6, name="origin", desc="RegisteredCollection of the NFT collection or WrappedNft of the mint")]
+    #[account(7, name="token_program", desc="Token program")]
+    #[account(8, name="system_program", desc="System program")]
+    #[account(9, name="rent", desc="Rent sysvar")]
+    Deposit(DepositArgs),
+
+    /// Release the NFT from custody to the recipient token account. The Keccak Hash of
+    /// `["solana-nft-bridge-program".bytes, program_id, bridge, domain, "release".bytes, mint, recipient, tx_id, nonce, expires_at]`
+    /// should be signed by `threshold` of the oracle signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The NftBridge account
+    ///   1. `[]` The NFT mint
+    ///   2. `[writable]` The custody token account of the mint
+    ///   3. `[writable]` The recipient token account
+    ///   4. `[]` Token program
+    ///   5. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, writable, name="bridge", desc="The NftBridge account")]
+    #[account(1, name="mint", desc="The NFT mint")]
+    #[account(2, writable, name="custody", desc="The custody token account of the mint")]
+    #[account(3, writable, name="recipient", desc="The recipient token account")]
+    #[account(4, name="token_program", desc="Token program")]
+    #[account(5, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    Release(ReleaseArgs),
+
+    /// Mint the wrapped NFT of `token_id` of `origin_token` on `origin_chain` to the associated token account of the
+    /// recipient. The mint address is derived from the origin, the NFT gets Metaplex metadata updated by the bridge
+    /// and a master edition with zero max supply, so no more tokens of the mint can be minted. Once minted, the
+    /// wrapped NFT returns to Solana by Release from custody. The Keccak Hash of
+    /// `["solana-nft-bridge-program".bytes, program_id, bridge, domain, "mint-wrapped".bytes, origin_chain, origin_token.borsh_bytes, token_id, metadata.borsh_bytes, recipient, tx_id, nonce, expires_at]`
+    /// should be signed by `threshold` of the oracle signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The NftBridge account
+    ///   1. `[writable]` The wrapped mint to create
+    ///   2. `[writable]` The WrappedNft account to initialize
+    ///   3. `[writable]` The Metaplex metadata account of the mint
+    ///   4. `[writable]` The Metaplex master edition account of the mint
+    ///   5. `[writable]` The associated token account of the recipient
+    ///   6. `[]` The recipient
+    ///   7. `[writable,signer]` The fee payer
+    ///   8. `[]` Token program
+    ///   9. `[]` Associated Token Account program
+    ///   10. `[]` Metaplex Token Metadata program
+    ///   11. `[]` System program
+    ///   12. `[]` Rent sysvar
+    ///   13. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, writable, name="bridge", desc="The NftBridge account")]
+    #[account(1, writable, name="mint", desc="The wrapped mint to create")]
+    #[account(2, writable, name="wrapped_nft", desc="The WrappedNft account to initialize")]
+    #[account(3, writable, name="metadata", desc="The Metaplex metadata account of the mint")]
+    #[account(4, writable, name="master_edition", desc="The Metaplex master edition account of the mint")]
+    #[account(5, writable, name="recipient_token", desc="The associated token account of the recipient")]
+    #[account(6, name="recipient", desc="The recipient")]
+    #[account(7, writable, signer, name="payer", desc="The fee payer")]
+    #[account(8, name="token_program", desc="Token program")]
+    #[account(9, name="associated_token_program", desc="Associated Token Account program")]
+    #[account(10, name="metadata_program", desc="Metaplex Token Metadata program")]
+    #[account(11, name="system_program", desc="System program")]
+    #[account(12, name="rent", desc="Rent sysvar")]
+    #[account(13, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    MintWrapped(MintWrappedArgs),
+}
+
+impl NftBridgeInstruction {
+    /// Encodes the instruction prefixed with INSTRUCTION_VERSION.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![INSTRUCTION_VERSION];
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Decodes instruction data produced by `pack`. Data of another format version is rejected instead of being
+    /// misparsed, and trailing bytes are not allowed.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < 2 || input.len() > MAX_INSTRUCTION_DATA_LEN {
+            msg!("Invalid instruction data length: {}", input.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if input[0] != INSTRUCTION_VERSION {
+            msg!("Instruction version {} is not supported, expected {}", input[0], INSTRUCTION_VERSION);
+            return Err(NftBridgeError::UnsupportedInstructionVersion.into());
+        }
+
+        Ok(Self::try_from_slice(&input[1..])?)
+    }
+}
+
+pub fn initialize_bridge(
+    program_id: Pubkey,
+    fee_payer: Pubkey,
+    seed: [u8; 32],
+    public_keys: Vec<AdminKey>,
+    threshold: u8,
+    domain: [u8; 32],
+) -> Instruction {
+    let (bridge, _) = pda::find_bridge_address(&program_id, &seed);
+    Instruction{
+        program_id,
+        data: NftBridgeInstruction::InitializeBridge(
+            InitializeBridgeArgs {
+                seed,
+                public_keys,
+                threshold,
+                domain,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(bridge, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+pub fn register_collection(
+    program_id: Pubkey,
+    bridge: Pubkey,
+    fee_payer: Pubkey,
+    collection: Pubkey,
+    nonce: u64,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    let (registered_collection, _) = pda::find_collection_address(&program_id, &bridge, &collection);
+    Instruction{
+        program_id,
+        data: NftBridgeInstruction::RegisterCollection(
+            RegisterCollectionArgs {
+                collection,
+                nonce,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(bridge, false),
+            AccountMeta::new(registered_collection, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// Deposit of a member of `collection`, or of a wrapped NFT of the bridge if `collection` is None.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit(
+    program_id: Pubkey,
+    bridge: Pubkey,
+    mint: Pubkey,
+    source: Pubkey,
+    owner: Pubkey,
+    collection: Option<Pubkey>,
+    destination_chain: u64,
+    destination_address: Vec<u8>,
+) -> Instruction {
+    let (custody, _) = pda::find_custody_address(&program_id, &bridge, &mint);
+    let (metadata, _) = pda::find_metadata_address(&mint);
+    let (origin, _) = match collection {
+        Some(collection) => pda::find_collection_address(&program_id, &bridge, &collection),
+        None => pda::find_wrapped_nft_address(&program_id, &mint),
+    };
+    Instruction{
+        program_id,
+        data: NftBridgeInstruction::Deposit(
+            DepositArgs {
+                destination_chain,
+                destination_address,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(bridge, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(metadata, false),
+            AccountMeta::new(custody, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(owner, true),
+            AccountMeta::new_readonly(origin, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// Release with signatures in instruction args. Signatures verified by a preceding `secp256k1_program`
+/// instruction require the instructions sysvar appended to the accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn release(
+    program_id: Pubkey,
+    bridge: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    tx_id: [u8; 32],
+    nonce: u64,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    let (custody, _) = pda::find_custody_address(&program_id, &bridge, &mint);
+    Instruction{
+        program_id,
+        data: NftBridgeInstruction::Release(
+            ReleaseArgs {
+                tx_id,
+                nonce,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(bridge, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(custody, false),
+            AccountMeta::new(recipient, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// MintWrapped with signatures in instruction args. Signatures verified by a preceding `secp256k1_program`
+/// instruction require the instructions sysvar appended to the accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_wrapped(
+    program_id: Pubkey,
+    bridge: Pubkey,
+    recipient: Pubkey,
+    fee_payer: Pubkey,
+    origin_chain: u64,
+    origin_token: Vec<u8>,
+    token_id: [u8; 32],
+    metadata: NftMetadata,
+    tx_id: [u8; 32],
+    nonce: u64,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    let (mint, _) = pda::find_wrapped_mint_address(&program_id, &bridge, origin_chain, &origin_token, &token_id);
+    let (wrapped_nft, _) = pda::find_wrapped_nft_address(&program_id, &mint);
+    let (metadata_account, _) = pda::find_metadata_address(&mint);
+    let (master_edition, _) = pda::find_master_edition_address(&mint);
+    let recipient_token = spl_associated_token_account::get_associated_token_address(&recipient, &mint);
+    Instruction{
+        program_id,
+        data: NftBridgeInstruction::MintWrapped(
+            MintWrappedArgs {
+                origin_chain,
+                origin_token,
+                token_id,
+                metadata,
+                tx_id,
+                nonce,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(bridge, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(wrapped_nft, false),
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new(master_edition, false),
+            AccountMeta::new(recipient_token, false),
+            AccountMeta::new_readonly(recipient, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
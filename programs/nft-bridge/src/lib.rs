@@ -0,0 +1,20 @@
+//! Bridge of Metaplex NFTs. Deposit moves an NFT of a registered verified collection into the custody of the bridge
+//! and emits an event with the destination chain and address for the oracle. NFTs arriving from other chains are
+//! either released from custody, if they left Solana through the bridge before, or minted as wrapped master editions.
+//! Signatures are verified with `upgrade::ecdsa`, the scheme of the upgrade program.
+
+pub mod entrypoint;
+pub mod error;
+pub mod events;
+pub mod hashing;
+pub mod instructions;
+pub mod pda;
+pub mod processor;
+pub mod state;
+
+const HASH_CONSTANT: &str = "solana-nft-bridge-program";
+const PDA_BRIDGE_SEED: &str = "nft-bridge-account";
+const PDA_CUSTODY_SEED: &str = "nft-custody-account";
+const PDA_COLLECTION_SEED: &str = "nft-collection-account";
+const PDA_WRAPPED_MINT_SEED: &str = "nft-wrapped-mint-account";
+const PDA_WRAPPED_NFT_SEED: &str = "nft-wrapped-account";
@@ -0,0 +1,41 @@
+//! Addresses of the program derived accounts. Off-chain builders and the processor derive them here, so the seed
+//! strings and their ordering are defined once.
+
+use solana_program::pubkey::Pubkey;
+use crate::hashing::origin_id;
+use crate::{PDA_BRIDGE_SEED, PDA_COLLECTION_SEED, PDA_CUSTODY_SEED, PDA_WRAPPED_MINT_SEED, PDA_WRAPPED_NFT_SEED};
+
+/// Address and bump of NftBridge created with `seed`.
+pub fn find_bridge_address(program_id: &Pubkey, seed: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_BRIDGE_SEED.as_bytes(), seed], program_id)
+}
+
+/// Address and bump of the token account of NftBridge at `bridge` that holds the deposited NFT of `mint`.
+pub fn find_custody_address(program_id: &Pubkey, bridge: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_CUSTODY_SEED.as_bytes(), bridge.as_ref(), mint.as_ref()], program_id)
+}
+
+/// Address and bump of RegisteredCollection of `collection` mint in NftBridge at `bridge`.
+pub fn find_collection_address(program_id: &Pubkey, bridge: &Pubkey, collection: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_COLLECTION_SEED.as_bytes(), bridge.as_ref(), collection.as_ref()], program_id)
+}
+
+/// Address and bump of the wrapped mint of `token_id` of `origin_token` on `origin_chain`.
+pub fn find_wrapped_mint_address(program_id: &Pubkey, bridge: &Pubkey, origin_chain: u64, origin_token: &[u8], token_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_WRAPPED_MINT_SEED.as_bytes(), bridge.as_ref(), origin_id(origin_chain, origin_token, token_id).as_ref()], program_id)
+}
+
+/// Address and bump of WrappedNft of the wrapped `mint`.
+pub fn find_wrapped_nft_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_WRAPPED_NFT_SEED.as_bytes(), mint.as_ref()], program_id)
+}
+
+/// Address of Metaplex token metadata of `mint`.
+pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    mpl_token_metadata::accounts::Metadata::find_pda(mint)
+}
+
+/// Address of Metaplex master edition of `mint`.
+pub fn find_master_edition_address(mint: &Pubkey) -> (Pubkey, u8) {
+    mpl_token_metadata::accounts::MasterEdition::find_pda(mint)
+}
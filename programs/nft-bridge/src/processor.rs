@@ -0,0 +1,511 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult, msg,
+    program::{invoke, invoke_signed}, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    sysvar::{self, rent::Rent, Sysvar},
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use mpl_token_metadata::accounts::Metadata;
+use mpl_token_metadata::instructions::{CreateMasterEditionV3, CreateMasterEditionV3InstructionArgs, CreateMetadataAccountV3, CreateMetadataAccountV3InstructionArgs};
+use mpl_token_metadata::types::DataV2;
+use mpl_token_metadata::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+use crate::state::{NftBridge, RegisteredCollection, WrappedNft, MAX_DESTINATION_ADDRESS_LENGTH, MAX_NFT_BRIDGE_SIZE, MAX_ORIGIN_TOKEN_LENGTH, MAX_REGISTERED_COLLECTION_SIZE, MAX_WRAPPED_NFT_SIZE};
+use crate::instructions::{DepositArgs, InitializeBridgeArgs, MintWrappedArgs, NftBridgeInstruction, NftMetadata, RegisterCollectionArgs, ReleaseArgs};
+use crate::hashing::{mint_wrapped_hash, origin_id, register_collection_hash, release_hash};
+use crate::pda;
+use crate::{PDA_BRIDGE_SEED, PDA_COLLECTION_SEED, PDA_CUSTODY_SEED, PDA_WRAPPED_MINT_SEED, PDA_WRAPPED_NFT_SEED};
+use crate::error::NftBridgeError;
+use upgrade::utils::{create_pda_account, next_nonce, validate_signer_set, verify_nonce, verify_not_expired, verify_signatures, verify_system_program, verify_sysvar, verify_token_program};
+use crate::events::{CollectionRegistered, NftBridgeEvent, NftDeposited, NftReleased, WrappedNftMinted};
+
+pub fn process_instruction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let instruction = NftBridgeInstruction::unpack(input)?;
+    match instruction {
+        NftBridgeInstruction::InitializeBridge(args) => {
+            msg!("Instruction: Create NFT bridge");
+            process_init_bridge(program_id, accounts, args)
+        }
+        NftBridgeInstruction::RegisterCollection(args) => {
+            msg!("Instruction: Register collection");
+            process_register_collection(program_id, accounts, args)
+        }
+        NftBridgeInstruction::Deposit(args) => {
+            msg!("Instruction: Deposit");
+            process_deposit(program_id, accounts, args)
+        }
+        NftBridgeInstruction::Release(args) => {
+            msg!("Instruction: Release");
+            process_release(program_id, accounts, args)
+        }
+        NftBridgeInstruction::MintWrapped(args) => {
+            msg!("Instruction: Mint wrapped");
+            process_mint_wrapped(program_id, accounts, args)
+        }
+    }
+}
+
+pub fn process_init_bridge<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: InitializeBridgeArgs,
+) -> ProgramResult {
+    validate_signer_set::<NftBridgeError>(&args.public_keys, args.threshold)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(NftBridgeError::MissingSigner.into());
+    }
+
+    verify_system_program::<NftBridgeError>(system_program)?;
+    verify_sysvar::<NftBridgeError>(rent_info, &sysvar::rent::id())?;
+
+    let (bridge_key, bump) = pda::find_bridge_address(program_id, &args.seed);
+    if bridge_key != *bridge_info.key {
+        return Err(NftBridgeError::WrongSeeds.into());
+    }
+
+    let rent = Rent::from_account_info(rent_info)?;
+
+    create_pda_account::<NftBridgeError>(bridge_info, fee_payer_info, system_program, &rent, MAX_NFT_BRIDGE_SIZE, program_id, &[PDA_BRIDGE_SEED.as_bytes(), args.seed.as_ref(), &[bump]])?;
+
+    let mut bridge: NftBridge = BorshDeserialize::deserialize(&mut bridge_info.data.borrow_mut().as_ref())?;
+    if bridge.is_initialized {
+        return Err(NftBridgeError::AlreadyInUse.into());
+    }
+
+    bridge.seed = args.seed;
+    bridge.bump = bump;
+    bridge.public_keys = args.public_keys;
+    bridge.threshold = args.threshold;
+    bridge.domain = args.domain;
+    bridge.nonce = 0;
+    bridge.deposit_nonce = 0;
+    bridge.is_initialized = true;
+    bridge.serialize(&mut *bridge_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_register_collection<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: RegisterCollectionArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let registered_collection_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(NftBridgeError::MissingSigner.into());
+    }
+
+    verify_system_program::<NftBridgeError>(system_program)?;
+    verify_sysvar::<NftBridgeError>(rent_info, &sysvar::rent::id())?;
+
+    let mut bridge = load_bridge(program_id, bridge_info)?;
+
+    let (registered_collection_key, bump) = pda::find_collection_address(program_id, bridge_info.key, &args.collection);
+    if registered_collection_key != *registered_collection_info.key {
+        return Err(NftBridgeError::WrongSeeds.into());
+    }
+
+    verify_not_expired::<NftBridgeError>(args.expires_at)?;
+    verify_nonce::<NftBridgeError>(args.nonce, bridge.nonce)?;
+
+    let hash = register_collection_hash(program_id, bridge_info.key, &bridge, &args.collection, args.nonce, args.expires_at);
+    verify_signatures::<NftBridgeError>(hash.as_ref(), &args.signatures, accounts, &bridge.public_keys, bridge.threshold)?;
+
+    next_nonce::<NftBridgeError>(&mut bridge.nonce)?;
+    bridge.serialize(&mut *bridge_info.data.borrow_mut())?;
+
+    let rent = Rent::from_account_info(rent_info)?;
+    create_pda_account::<NftBridgeError>(registered_collection_info, fee_payer_info, system_program, &rent, MAX_REGISTERED_COLLECTION_SIZE, program_id, &[PDA_COLLECTION_SEED.as_bytes(), bridge_info.key.as_ref(), args.collection.as_ref(), &[bump]])?;
+
+    let registered_collection = RegisteredCollection {
+        bridge: *bridge_info.key,
+        collection: args.collection,
+        is_initialized: true,
+    };
+    registered_collection.serialize(&mut *registered_collection_info.data.borrow_mut())?;
+
+    NftBridgeEvent::CollectionRegistered(CollectionRegistered {
+        bridge: *bridge_info.key,
+        collection: args.collection,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_deposit<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: DepositArgs,
+) -> ProgramResult {
+    if args.destination_address.is_empty() || args.destination_address.len() > MAX_DESTINATION_ADDRESS_LENGTH {
+        return Err(NftBridgeError::InvalidDestination.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let metadata_info = next_account_info(account_info_iter)?;
+    let custody_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let origin_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !owner_info.is_signer {
+        return Err(NftBridgeError::MissingSigner.into());
+    }
+
+    verify_token_program::<NftBridgeError>(token_program)?;
+    verify_system_program::<NftBridgeError>(system_program)?;
+    verify_sysvar::<NftBridgeError>(rent_info, &sysvar::rent::id())?;
+
+    let mut bridge = load_bridge(program_id, bridge_info)?;
+    verify_nft_mint(mint_info)?;
+    let collection = verify_origin(program_id, bridge_info.key, mint_info, metadata_info, origin_info)?;
+
+    let (custody_key, custody_bump) = pda::find_custody_address(program_id, bridge_info.key, mint_info.key);
+    if custody_key != *custody_info.key {
+        return Err(NftBridgeError::WrongSeeds.into());
+    }
+
+    // Custody of the NFT is created by its first deposit and reused when it leaves Solana again
+    if custody_info.data_is_empty() {
+        let rent = Rent::from_account_info(rent_info)?;
+        create_pda_account::<NftBridgeError>(custody_info, owner_info, system_program, &rent, spl_token::state::Account::LEN, &spl_token::id(), &[PDA_CUSTODY_SEED.as_bytes(), bridge_info.key.as_ref(), mint_info.key.as_ref(), &[custody_bump]])?;
+        invoke(
+            &spl_token::instruction::initialize_account3(&spl_token::id(), custody_info.key, mint_info.key, bridge_info.key)?,
+            &[custody_info.clone(), mint_info.clone()],
+        )?;
+    }
+
+    invoke(
+        &spl_token::instruction::transfer_checked(&spl_token::id(), source_info.key, mint_info.key, custody_info.key, owner_info.key, &[], 1, 0)?,
+        &[source_info.clone(), mint_info.clone(), custody_info.clone(), owner_info.clone()],
+    )?;
+
+    let nonce = next_nonce::<NftBridgeError>(&mut bridge.deposit_nonce)?;
+    bridge.serialize(&mut *bridge_info.data.borrow_mut())?;
+
+    NftBridgeEvent::NftDeposited(NftDeposited {
+        bridge: *bridge_info.key,
+        mint: *mint_info.key,
+        collection,
+        sender: *owner_info.key,
+        destination_chain: args.destination_chain,
+        destination_address: args.destination_address,
+        nonce,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_release<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ReleaseArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let custody_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    verify_token_program::<NftBridgeError>(token_program)?;
+
+    let mut bridge = load_bridge(program_id, bridge_info)?;
+
+    let (custody_key, _) = pda::find_custody_address(program_id, bridge_info.key, mint_info.key);
+    if custody_key != *custody_info.key {
+        return Err(NftBridgeError::WrongSeeds.into());
+    }
+
+    verify_not_expired::<NftBridgeError>(args.expires_at)?;
+    verify_nonce::<NftBridgeError>(args.nonce, bridge.nonce)?;
+
+    let hash = release_hash(program_id, bridge_info.key, &bridge, mint_info.key, recipient_info.key, &args.tx_id, args.nonce, args.expires_at);
+    verify_signatures::<NftBridgeError>(hash.as_ref(), &args.signatures, accounts, &bridge.public_keys, bridge.threshold)?;
+
+    next_nonce::<NftBridgeError>(&mut bridge.nonce)?;
+    bridge.serialize(&mut *bridge_info.data.borrow_mut())?;
+
+    invoke_signed(
+        &spl_token::instruction::transfer_checked(&spl_token::id(), custody_info.key, mint_info.key, recipient_info.key, bridge_info.key, &[], 1, 0)?,
+        &[custody_info.clone(), mint_info.clone(), recipient_info.clone(), bridge_info.clone()],
+        &[&[PDA_BRIDGE_SEED.as_bytes(), bridge.seed.as_ref(), &[bridge.bump]]],
+    )?;
+
+    NftBridgeEvent::NftReleased(NftReleased {
+        bridge: *bridge_info.key,
+        mint: *mint_info.key,
+        recipient: *recipient_info.key,
+        tx_id: args.tx_id,
+        nonce: args.nonce,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_mint_wrapped<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: MintWrappedArgs,
+) -> ProgramResult {
+    if args.origin_token.is_empty() || args.origin_token.len() > MAX_ORIGIN_TOKEN_LENGTH {
+        return Err(NftBridgeError::InvalidOriginToken.into());
+    }
+
+    validate_metadata(&args.metadata)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bridge_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let wrapped_nft_info = next_account_info(account_info_iter)?;
+    let metadata_info = next_account_info(account_info_iter)?;
+    let master_edition_info = next_account_info(account_info_iter)?;
+    let recipient_token_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let associated_token_program = next_account_info(account_info_iter)?;
+    let metadata_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(NftBridgeError::MissingSigner.into());
+    }
+
+    verify_token_program::<NftBridgeError>(token_program)?;
+    verify_system_program::<NftBridgeError>(system_program)?;
+    verify_sysvar::<NftBridgeError>(rent_info, &sysvar::rent::id())?;
+
+    if associated_token_program.key != &spl_associated_token_account::id() {
+        return Err(NftBridgeError::InvalidAssociatedTokenProgram.into());
+    }
+
+    if metadata_program.key != &mpl_token_metadata::ID {
+        return Err(NftBridgeError::InvalidMetadataProgram.into());
+    }
+
+    let mut bridge = load_bridge(program_id, bridge_info)?;
+
+    let (mint_key, mint_bump) = pda::find_wrapped_mint_address(program_id, bridge_info.key, args.origin_chain, &args.origin_token, &args.token_id);
+    if mint_key != *mint_info.key {
+        return Err(NftBridgeError::WrongSeeds.into());
+    }
+
+    let (wrapped_nft_key, wrapped_nft_bump) = pda::find_wrapped_nft_address(program_id, mint_info.key);
+    if wrapped_nft_key != *wrapped_nft_info.key
+        || pda::find_metadata_address(mint_info.key).0 != *metadata_info.key
+        || pda::find_master_edition_address(mint_info.key).0 != *master_edition_info.key
+        || spl_associated_token_account::get_associated_token_address(recipient_info.key, mint_info.key) != *recipient_token_info.key {
+        return Err(NftBridgeError::WrongSeeds.into());
+    }
+
+    verify_not_expired::<NftBridgeError>(args.expires_at)?;
+    verify_nonce::<NftBridgeError>(args.nonce, bridge.nonce)?;
+
+    let hash = mint_wrapped_hash(program_id, bridge_info.key, &bridge, args.origin_chain, &args.origin_token, &args.token_id, &args.metadata, recipient_info.key, &args.tx_id, args.nonce, args.expires_at);
+    verify_signatures::<NftBridgeError>(hash.as_ref(), &args.signatures, accounts, &bridge.public_keys, bridge.threshold)?;
+
+    next_nonce::<NftBridgeError>(&mut bridge.nonce)?;
+    bridge.serialize(&mut *bridge_info.data.borrow_mut())?;
+
+    let rent = Rent::from_account_info(rent_info)?;
+    let origin = origin_id(args.origin_chain, &args.origin_token, &args.token_id);
+    let bridge_seeds: &[&[u8]] = &[PDA_BRIDGE_SEED.as_bytes(), bridge.seed.as_ref(), &[bridge.bump]];
+
+    create_pda_account::<NftBridgeError>(mint_info, fee_payer_info, system_program, &rent, spl_token::state::Mint::LEN, &spl_token::id(), &[PDA_WRAPPED_MINT_SEED.as_bytes(), bridge_info.key.as_ref(), origin.as_ref(), &[mint_bump]])?;
+    invoke(
+        &spl_token::instruction::initialize_mint2(&spl_token::id(), mint_info.key, bridge_info.key, None, 0)?,
+        std::slice::from_ref(mint_info),
+    )?;
+
+    create_pda_account::<NftBridgeError>(wrapped_nft_info, fee_payer_info, system_program, &rent, MAX_WRAPPED_NFT_SIZE, program_id, &[PDA_WRAPPED_NFT_SEED.as_bytes(), mint_info.key.as_ref(), &[wrapped_nft_bump]])?;
+    let wrapped_nft = WrappedNft {
+        bridge: *bridge_info.key,
+        mint: *mint_info.key,
+        origin_chain: args.origin_chain,
+        origin_token: args.origin_token.clone(),
+        token_id: args.token_id,
+        is_initialized: true,
+    };
+    wrapped_nft.serialize(&mut *wrapped_nft_info.data.borrow_mut())?;
+
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(fee_payer_info.key, recipient_info.key, mint_info.key, &spl_token::id()),
+        &[fee_payer_info.clone(), recipient_token_info.clone(), recipient_info.clone(), mint_info.clone(), system_program.clone(), token_program.clone(), associated_token_program.clone()],
+    )?;
+    invoke_signed(
+        &spl_token::instruction::mint_to(&spl_token::id(), mint_info.key, recipient_token_info.key, bridge_info.key, &[], 1)?,
+        &[mint_info.clone(), recipient_token_info.clone(), bridge_info.clone()],
+        &[bridge_seeds],
+    )?;
+
+    let create_metadata = CreateMetadataAccountV3 {
+        metadata: *metadata_info.key,
+        mint: *mint_info.key,
+        mint_authority: *bridge_info.key,
+        payer: *fee_payer_info.key,
+        update_authority: (*bridge_info.key, true),
+        system_program: *system_program.key,
+        rent: None,
+    };
+    invoke_signed(
+        &create_metadata.instruction(CreateMetadataAccountV3InstructionArgs {
+            data: DataV2 {
+                name: args.metadata.name,
+                symbol: args.metadata.symbol,
+                uri: args.metadata.uri,
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            is_mutable: true,
+            collection_details: None,
+        }),
+        &[metadata_info.clone(), mint_info.clone(), bridge_info.clone(), fee_payer_info.clone(), system_program.clone(), metadata_program.clone()],
+        &[bridge_seeds],
+    )?;
+
+    // Master edition takes the mint authority over, the supply of the wrapped NFT stays 1
+    let create_master_edition = CreateMasterEditionV3 {
+        edition: *master_edition_info.key,
+        mint: *mint_info.key,
+        update_authority: *bridge_info.key,
+        mint_authority: *bridge_info.key,
+        payer: *fee_payer_info.key,
+        metadata: *metadata_info.key,
+        token_program: *token_program.key,
+        system_program: *system_program.key,
+        rent: None,
+    };
+    invoke_signed(
+        &create_master_edition.instruction(CreateMasterEditionV3InstructionArgs { max_supply: Some(0) }),
+        &[master_edition_info.clone(), mint_info.clone(), bridge_info.clone(), fee_payer_info.clone(), metadata_info.clone(), token_program.clone(), system_program.clone(), metadata_program.clone()],
+        &[bridge_seeds],
+    )?;
+
+    NftBridgeEvent::WrappedNftMinted(WrappedNftMinted {
+        bridge: *bridge_info.key,
+        mint: *mint_info.key,
+        recipient: *recipient_info.key,
+        origin_chain: args.origin_chain,
+        origin_token: args.origin_token,
+        token_id: args.token_id,
+        tx_id: args.tx_id,
+        nonce: args.nonce,
+    }).emit();
+    Ok(())
+}
+
+
+/// Loads initialized NftBridge owned by the program from writable `bridge_info`.
+fn load_bridge(program_id: &Pubkey, bridge_info: &AccountInfo) -> Result<NftBridge, ProgramError> {
+    if bridge_info.owner != program_id {
+        return Err(NftBridgeError::AccountOwnerMismatch.into());
+    }
+
+    if !bridge_info.is_writable {
+        return Err(NftBridgeError::AccountNotWritable.into());
+    }
+
+    let bridge: NftBridge = BorshDeserialize::deserialize(&mut bridge_info.data.borrow().as_ref())?;
+    if !bridge.is_initialized {
+        return Err(NftBridgeError::NotInitialized.into());
+    }
+
+    Ok(bridge)
+}
+
+
+/// Rejects mints that are not NFTs: SPL Token mint with supply of 1 and without decimals.
+fn verify_nft_mint(mint_info: &AccountInfo) -> ProgramResult {
+    if mint_info.owner != &spl_token::id() {
+        return Err(NftBridgeError::InvalidTokenProgram.into());
+    }
+
+    let mint = spl_token::state::Mint::unpack(&mint_info.data.borrow())?;
+    if mint.supply != 1 || mint.decimals != 0 {
+        return Err(NftBridgeError::NotAnNft.into());
+    }
+
+    Ok(())
+}
+
+
+/// Verifies that the NFT can be deposited and returns its collection: a wrapped NFT of the bridge is accepted by its
+/// WrappedNft, a Solana NFT should be a verified member of the collection registered by `origin_info`. Collection
+/// membership is verified by the collection update authority in Metaplex, so it can not be claimed by counterfeits.
+fn verify_origin(
+    program_id: &Pubkey,
+    bridge: &Pubkey,
+    mint_info: &AccountInfo,
+    metadata_info: &AccountInfo,
+    origin_info: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    if origin_info.owner != program_id {
+        return Err(NftBridgeError::CollectionNotRegistered.into());
+    }
+
+    if *origin_info.key == pda::find_wrapped_nft_address(program_id, mint_info.key).0 {
+        let wrapped_nft: WrappedNft = BorshDeserialize::deserialize(&mut origin_info.data.borrow().as_ref())?;
+        if !wrapped_nft.is_initialized || wrapped_nft.bridge != *bridge {
+            return Err(NftBridgeError::NotInitialized.into());
+        }
+
+        return Ok(None);
+    }
+
+    if metadata_info.owner != &mpl_token_metadata::ID || *metadata_info.key != pda::find_metadata_address(mint_info.key).0 {
+        return Err(NftBridgeError::InvalidMetadata.into());
+    }
+
+    let metadata = Metadata::from_bytes(&metadata_info.data.borrow()).map_err(|_| NftBridgeError::InvalidMetadata)?;
+    let collection = match metadata.collection {
+        Some(collection) if collection.verified => collection.key,
+        _ => return Err(NftBridgeError::UnverifiedCollection.into()),
+    };
+
+    if *origin_info.key != pda::find_collection_address(program_id, bridge, &collection).0 {
+        return Err(NftBridgeError::CollectionNotRegistered.into());
+    }
+
+    let registered_collection: RegisteredCollection = BorshDeserialize::deserialize(&mut origin_info.data.borrow().as_ref())?;
+    if !registered_collection.is_initialized {
+        return Err(NftBridgeError::CollectionNotRegistered.into());
+    }
+
+    Ok(Some(collection))
+}
+
+
+fn validate_metadata(metadata: &NftMetadata) -> ProgramResult {
+    if metadata.name.len() > MAX_NAME_LENGTH || metadata.symbol.len() > MAX_SYMBOL_LENGTH || metadata.uri.len() > MAX_URI_LENGTH {
+        return Err(NftBridgeError::InvalidMetadata.into());
+    }
+
+    Ok(())
+}
+
@@ -0,0 +1,60 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::pubkey::Pubkey;
+use upgrade::state::{AdminKey, MAX_ADMIN_KEY_SIZE, MAX_SIGNERS};
+
+/// Maximal length of the NFT contract address on the origin chain, fits addresses of EVM, Solana and Cosmos chains
+pub const MAX_ORIGIN_TOKEN_LENGTH: usize = 64;
+
+/// Maximal length of the address on the destination chain
+pub const MAX_DESTINATION_ADDRESS_LENGTH: usize = 64;
+
+pub const MAX_NFT_BRIDGE_SIZE: usize = 32 + 1 + 4 + MAX_SIGNERS * MAX_ADMIN_KEY_SIZE + 1 + 32 + 8 + 8 + 1;
+
+pub const MAX_REGISTERED_COLLECTION_SIZE: usize = 32 + 32 + 1;
+
+pub const MAX_WRAPPED_NFT_SIZE: usize = 32 + 32 + 8 + 4 + MAX_ORIGIN_TOKEN_LENGTH + 32 + 1;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct NftBridge {
+    // Seed of the bridge address, several bridges can be deployed by one program
+    pub seed: [u8; 32],
+    pub bump: u8,
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the oracle signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize operation
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+    // Nonce of the next signed operation: RegisterCollection, Release or MintWrapped
+    pub nonce: u64,
+    // Nonce of the next deposit, identifies the deposit for the oracle
+    pub deposit_nonce: u64,
+    pub is_initialized: bool,
+}
+
+/// Collection whose verified members can be deposited to the bridge.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct RegisteredCollection {
+    pub bridge: Pubkey,
+    // Mint of the collection NFT
+    pub collection: Pubkey,
+    pub is_initialized: bool,
+}
+
+/// Origin of a wrapped NFT minted by the bridge.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct WrappedNft {
+    pub bridge: Pubkey,
+    pub mint: Pubkey,
+    // Identifier of the origin chain, interpreted by the oracle
+    pub origin_chain: u64,
+    // NFT contract address on the origin chain
+    pub origin_token: Vec<u8>,
+    // Token id on the origin chain (uint256 in big-endian for EVM chains)
+    pub token_id: [u8; 32],
+    pub is_initialized: bool,
+}
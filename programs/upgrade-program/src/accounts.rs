@@ -0,0 +1,123 @@
+//! Typed wrappers of instruction accounts that are validated when constructed, so processor functions
+//! do not repeat owner, writability, signer and address checks
+
+use solana_program::account_info::AccountInfo;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use crate::error::UpgradeError;
+use crate::pda;
+use crate::utils::verify_writable;
+use crate::state::{UpgradeAdmin, VersionedUpgradeAdmin, MAX_ADMIN_SIZE, V1_ADMIN_SIZE};
+
+/// Initialized UpgradeAdmin account.
+pub(crate) struct AdminAccount<'a> {
+    pub info: &'a AccountInfo<'a>,
+    // Address derived from the admin contract and bump
+    pub key: Pubkey,
+    pub admin: UpgradeAdmin,
+}
+
+impl<'a> AdminAccount<'a> {
+    /// Loads writable UpgradeAdmin that is owned by the program, initialized and stored at its PDA.
+    pub fn load(program_id: &Pubkey, info: &'a AccountInfo<'a>) -> Result<Self, ProgramError> {
+        verify_writable::<UpgradeError>(info)?;
+        Self::load_readonly(program_id, info)
+    }
+
+    /// Loads UpgradeAdmin like `load` for instructions that only read it or sign with it.
+    pub fn load_readonly(program_id: &Pubkey, info: &'a AccountInfo<'a>) -> Result<Self, ProgramError> {
+        let admin = load_admin(program_id, info)?;
+        if !admin.is_initialized {
+            return Err(UpgradeError::NotInitialized.into());
+        }
+
+        let key = verify_admin_address(program_id, info, &admin)?;
+        Ok(Self { info, key, admin })
+    }
+}
+
+/// ProgramData account of an upgradeable program.
+pub(crate) struct ProgramDataAccount<'a> {
+    pub info: &'a AccountInfo<'a>,
+}
+
+impl<'a> ProgramDataAccount<'a> {
+    /// Checks that writable `info` is derived from `contract` and owned by the upgradeable loader.
+    pub fn load(contract: &Pubkey, info: &'a AccountInfo<'a>) -> Result<Self, ProgramError> {
+        verify_program_data(contract, info)?;
+        if !bpf_loader_upgradeable::check_id(info.owner) {
+            return Err(UpgradeError::ProgramDataOwnerMismatch.into());
+        }
+
+        verify_writable::<UpgradeError>(info)?;
+        Ok(Self { info })
+    }
+}
+
+/// Account that pays for created accounts.
+pub(crate) struct PayerAccount<'a> {
+    pub info: &'a AccountInfo<'a>,
+}
+
+impl<'a> PayerAccount<'a> {
+    /// Checks that `info` is a writable signer.
+    pub fn load(info: &'a AccountInfo<'a>) -> Result<Self, ProgramError> {
+        if !info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        verify_writable::<UpgradeError>(info)?;
+        Ok(Self { info })
+    }
+}
+
+/// Rejects UpgradeAdmin account owned by another program, its data should not be deserialized or trusted.
+pub(crate) fn verify_admin_owner(program_id: &Pubkey, upgrade_admin_info: &AccountInfo) -> ProgramResult {
+    if upgrade_admin_info.owner != program_id {
+        msg!("UpgradeAdmin {} is owned by {}", upgrade_admin_info.key, upgrade_admin_info.owner);
+        return Err(UpgradeError::AdminOwnerMismatch.into());
+    }
+
+    Ok(())
+}
+
+/// Deserializes UpgradeAdmin of the current layout. Accounts of older layouts should be migrated by MigrateAdmin first.
+/// The account should be owned by the program and have the full admin size.
+fn load_admin(program_id: &Pubkey, upgrade_admin_info: &AccountInfo) -> Result<UpgradeAdmin, ProgramError> {
+    verify_admin_owner(program_id, upgrade_admin_info)?;
+
+    let data_len = upgrade_admin_info.data_len();
+    if data_len != MAX_ADMIN_SIZE && data_len != V1_ADMIN_SIZE {
+        msg!("Invalid admin account size: {}, expected {}", data_len, MAX_ADMIN_SIZE);
+        return Err(UpgradeError::InvalidAccountSize.into());
+    }
+
+    match VersionedUpgradeAdmin::unpack(&upgrade_admin_info.data.borrow())? {
+        VersionedUpgradeAdmin::V1(_) => Err(UpgradeError::OutdatedAdminVersion.into()),
+        VersionedUpgradeAdmin::V2(upgrade_admin) => Ok(upgrade_admin),
+    }
+}
+
+/// Verifies UpgradeAdmin account address with the bump stored at init instead of searching for it.
+fn verify_admin_address(program_id: &Pubkey, upgrade_admin_info: &AccountInfo, upgrade_admin: &UpgradeAdmin) -> Result<Pubkey, ProgramError> {
+    let upgrade_admin_key = upgrade_admin.address(program_id)?;
+    if upgrade_admin_key != *upgrade_admin_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    Ok(upgrade_admin_key)
+}
+
+/// Rejects a ProgramData account that is not derived from `contract`, which would otherwise fail inside the loader with an opaque error.
+pub(crate) fn verify_program_data(contract: &Pubkey, program_data_info: &AccountInfo) -> ProgramResult {
+    let (program_data_key, _) = pda::find_program_data_address(contract);
+    if program_data_key != *program_data_info.key {
+        msg!("Expected ProgramData account {}, got {}", program_data_key, program_data_info.key);
+        return Err(UpgradeError::WrongProgramDataAccount.into());
+    }
+
+    Ok(())
+}
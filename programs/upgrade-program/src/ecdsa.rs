@@ -1,22 +1,275 @@
-use solana_program::secp256k1_recover::{SECP256K1_PUBLIC_KEY_LENGTH, secp256k1_recover};
-use solana_program::{entrypoint::ProgramResult, msg};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::secp256k1_recover::{SECP256K1_PUBLIC_KEY_LENGTH, SECP256K1_SIGNATURE_LENGTH, secp256k1_recover};
+use solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
 use crate::error::UpgradeError;
+use crate::state::{AdminKey, ETH_ADDRESS_LENGTH, SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH};
 
-pub fn verify_ecdsa_signature(hash: &[u8], sig: &[u8], reid: u8, target_key: [u8; SECP256K1_PUBLIC_KEY_LENGTH]) -> ProgramResult {
-    let recovered_key = secp256k1_recover(hash, reid, sig);
-    if recovered_key.is_err() {
+const SECP256K1_SIGNATURE_OFFSETS_START: usize = 1;
+
+const SECP256K1_SIGNATURE_OFFSETS_SIZE: usize = 11;
+
+/// Half of the secp256k1 curve order (big-endian), upper bound for the S component of canonical signature
+pub const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// EIP-191 prefix that Ethereum wallets prepend to the message in `personal_sign`
+pub const ETH_SIGNED_MESSAGE_PREFIX: &str = "\x19Ethereum Signed Message:\n";
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EcdsaSignature {
+    // ECDSA signature (64 byte format)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub signature: [u8; SECP256K1_SIGNATURE_LENGTH],
+    // Signature recovery id: 0/1, Ethereum-style 27/28 or EIP-155 `chain_id * 2 + 35/36`
+    pub recovery_id: u64,
+    // Signature is made over EIP-191 `personal_sign` message of the hash instead of the hash itself
+    pub personal_sign: bool,
+}
+
+pub fn verify_ecdsa_signature(hash: &[u8], sig: &[u8], reid: u64, target_key: &AdminKey) -> ProgramResult {
+    let key = recover_public_key(hash, sig, reid)?;
+
+    msg!("Required public key: {}", bs58::encode(target_key.as_ref()).into_string().as_str());
+
+    if !is_key_matches(&key, target_key) {
+        return ProgramResult::Err(UpgradeError::RecoveredKeyMismatch.into());
+    }
+
+    msg!("Public keys are equal");
+    Ok(())
+}
+
+/// Verifies that distinct signers from `target_keys` with cumulative weight of at least `threshold` signed the hash.
+/// Every provided signature should belong to the signer set and every signer can be counted only once.
+pub fn verify_ecdsa_signatures(hash: &[u8], signatures: &[EcdsaSignature], target_keys: &[AdminKey], weights: &[u8], threshold: u8) -> ProgramResult {
+    let mut signed = vec![false; target_keys.len()];
+    mark_ecdsa_signers(hash, signatures, target_keys, &mut signed)?;
+    verify_threshold(&signed, weights, threshold)
+}
+
+/// Marks in `signed` every signer from `target_keys` that produced one of `signatures`.
+pub fn mark_ecdsa_signers(hash: &[u8], signatures: &[EcdsaSignature], target_keys: &[AdminKey], signed: &mut [bool]) -> ProgramResult {
+    for sig in signatures {
+        let key = if sig.personal_sign {
+            recover_public_key(personal_sign_hash(hash).as_ref(), sig.signature.as_slice(), sig.recovery_id)?
+        } else {
+            recover_public_key(hash, sig.signature.as_slice(), sig.recovery_id)?
+        };
+
+        let index = target_keys.iter().position(|target_key| is_key_matches(&key, target_key));
+        if index.is_none() {
+            return ProgramResult::Err(UpgradeError::RecoveredKeyMismatch.into());
+        }
+
+        let index = index.unwrap();
+        if signed[index] {
+            return ProgramResult::Err(UpgradeError::DuplicateSignature.into());
+        }
+
+        signed[index] = true;
+    }
+
+    Ok(())
+}
+
+/// Marks in `signed` every signer from `target_keys` which signature over `hash` was verified by
+/// `secp256k1_program` instructions preceding the current one. This path is cheaper than `secp256k1_recover`
+/// because signature verification is performed by the precompile outside of program compute budget.
+pub fn mark_secp256k1_precompile_signers(instructions_info: &AccountInfo, hash: &[u8], target_keys: &[AdminKey], signed: &mut [bool]) -> ProgramResult {
+    for address in load_secp256k1_signers(instructions_info, hash)? {
+        let index = target_keys.iter().position(|target_key| is_address_matches(&address, target_key));
+        if index.is_none() {
+            return ProgramResult::Err(UpgradeError::PrecompileSignerMismatch.into());
+        }
+
+        let index = index.unwrap();
+        if signed[index] {
+            return ProgramResult::Err(UpgradeError::DuplicateSignature.into());
+        }
+
+        signed[index] = true;
+    }
+
+    Ok(())
+}
+
+/// Collects Ethereum addresses verified by `secp256k1_program` instructions preceding the current one
+/// for messages which keccak hash equals to `hash` or for EIP-191 `personal_sign` message of `hash`. Only signatures that store address, signature and message
+/// inside the same `secp256k1_program` instruction are taken into account.
+pub fn load_secp256k1_signers(instructions_info: &AccountInfo, hash: &[u8]) -> Result<Vec<[u8; ETH_ADDRESS_LENGTH]>, ProgramError> {
+    let current_index = load_current_index_checked(instructions_info)?;
+    let mut signers = Vec::new();
+
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_info)?;
+        if instruction.program_id != solana_program::secp256k1_program::id() {
+            continue;
+        }
+
+        let data = instruction.data.as_slice();
+        if data.len() < SECP256K1_SIGNATURE_OFFSETS_START {
+            return Err(UpgradeError::InvalidSecp256k1Instruction.into());
+        }
+
+        for i in 0..data[0] as usize {
+            let start = SECP256K1_SIGNATURE_OFFSETS_START + i * SECP256K1_SIGNATURE_OFFSETS_SIZE;
+            let offsets = data.get(start..start + SECP256K1_SIGNATURE_OFFSETS_SIZE)
+                .ok_or(UpgradeError::InvalidSecp256k1Instruction)?;
+
+            let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+            let signature_offset = read_u16(0) as usize;
+            let signature_instruction_index = offsets[2];
+            let eth_address_offset = read_u16(3) as usize;
+            let eth_address_instruction_index = offsets[5];
+            let message_data_offset = read_u16(6) as usize;
+            let message_data_size = read_u16(8) as usize;
+            let message_instruction_index = offsets[10];
+
+            let own_index = index as u8;
+            if signature_instruction_index != own_index || eth_address_instruction_index != own_index || message_instruction_index != own_index {
+                continue;
+            }
+
+            let message = data.get(message_data_offset..message_data_offset + message_data_size)
+                .ok_or(UpgradeError::InvalidSecp256k1Instruction)?;
+            if solana_program::keccak::hash(message).as_ref() != hash && message != personal_sign_message(hash) {
+                continue;
+            }
+
+            let signature = data.get(signature_offset..signature_offset + SECP256K1_SIGNATURE_LENGTH)
+                .ok_or(UpgradeError::InvalidSecp256k1Instruction)?;
+            verify_low_s(signature)?;
+
+            let address = data.get(eth_address_offset..eth_address_offset + ETH_ADDRESS_LENGTH)
+                .ok_or(UpgradeError::InvalidSecp256k1Instruction)?;
+
+            let mut signer = [0u8; ETH_ADDRESS_LENGTH];
+            signer.copy_from_slice(address);
+            msg!("Found secp256k1 signer: {}", bs58::encode(signer.as_ref()).into_string().as_str());
+            signers.push(signer);
+        }
+    }
+
+    Ok(signers)
+}
+
+/// Verifies that cumulative weight of signers marked in `signed` is at least `threshold`.
+/// Signers without weight in `weights` (e.g. proven by merkle proof) have weight 1.
+pub fn verify_threshold(signed: &[bool], weights: &[u8], threshold: u8) -> ProgramResult {
+    let weight: u64 = signed.iter().enumerate()
+        .filter(|(_, s)| **s)
+        .map(|(i, _)| weights.get(i).copied().unwrap_or(1) as u64)
+        .sum();
+    msg!("Valid signatures weight: {}, threshold: {}", weight, threshold);
+
+    if weight < threshold as u64 {
+        return ProgramResult::Err(UpgradeError::NotEnoughSignatures.into());
+    }
+
+    Ok(())
+}
+
+/// Checks that 64 byte or 33 byte compressed public key is a valid secp256k1 curve point.
+pub fn is_valid_secp256k1_key(key: &AdminKey) -> bool {
+    match key {
+        AdminKey::PublicKey(public_key) => {
+            let mut full = [0u8; SECP256K1_PUBLIC_KEY_LENGTH + 1];
+            full[0] = 0x04;
+            full[1..].copy_from_slice(public_key);
+            libsecp256k1::PublicKey::parse(&full).is_ok()
+        }
+        AdminKey::CompressedPublicKey(public_key) => libsecp256k1::PublicKey::parse_compressed(public_key).is_ok(),
+        _ => false,
+    }
+}
+
+/// EIP-191 `personal_sign` message for the hash: "\x19Ethereum Signed Message:\n32" + hash
+pub fn personal_sign_message(hash: &[u8]) -> Vec<u8> {
+    [ETH_SIGNED_MESSAGE_PREFIX.as_bytes(), hash.len().to_string().as_bytes(), hash].concat()
+}
+
+/// Digest that Ethereum wallets sign in `personal_sign` for the hash
+pub fn personal_sign_hash(hash: &[u8]) -> solana_program::keccak::Hash {
+    solana_program::keccak::hash(&personal_sign_message(hash))
+}
+
+/// Compressed form of 64 byte public key: 0x02 or 0x03 depending on Y coordinate parity followed by X coordinate
+pub fn compress_public_key(public_key: &[u8; SECP256K1_PUBLIC_KEY_LENGTH]) -> [u8; SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH] {
+    let mut compressed = [0u8; SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH];
+    compressed[0] = if public_key[SECP256K1_PUBLIC_KEY_LENGTH - 1] & 1 == 0 { 0x02 } else { 0x03 };
+    compressed[1..].copy_from_slice(&public_key[..32]);
+    compressed
+}
+
+/// Ethereum address derived from 64 byte public key: last 20 bytes of keccak_hash(public_key)
+pub fn eth_address(public_key: &[u8]) -> [u8; ETH_ADDRESS_LENGTH] {
+    let hash = solana_program::keccak::hash(public_key).to_bytes();
+    let mut address = [0u8; ETH_ADDRESS_LENGTH];
+    address.copy_from_slice(&hash[32 - ETH_ADDRESS_LENGTH..]);
+    address
+}
+
+/// Rejects signatures with S component in the upper half of the curve order. Both (r, s) and (r, n - s)
+/// are valid for the same message, so only the lower one is accepted to keep signatures non-malleable.
+pub fn verify_low_s(sig: &[u8]) -> ProgramResult {
+    if sig.len() != SECP256K1_SIGNATURE_LENGTH {
         return ProgramResult::Err(UpgradeError::InvalidSignature.into());
     }
 
+    if sig[32..] > SECP256K1_HALF_ORDER[..] {
+        return ProgramResult::Err(UpgradeError::MalleableSignature.into());
+    }
+
+    Ok(())
+}
+
+/// Maps Ethereum-style `v` values to 0/1 recovery id expected by `secp256k1_recover`:
+/// 27/28 are used by `eth_sign` and legacy transactions, `chain_id * 2 + 35/36` by EIP-155 transactions.
+pub fn normalize_recovery_id(v: u64) -> Result<u8, ProgramError> {
+    match v {
+        0 | 1 => Ok(v as u8),
+        27 | 28 => Ok((v - 27) as u8),
+        v if v >= 35 => Ok(((v - 35) % 2) as u8),
+        _ => Err(UpgradeError::InvalidRecoveryId.into()),
+    }
+}
+
+fn recover_public_key(hash: &[u8], sig: &[u8], reid: u64) -> Result<[u8; SECP256K1_PUBLIC_KEY_LENGTH], ProgramError> {
+    verify_low_s(sig)?;
+
+    let recovered_key = secp256k1_recover(hash, normalize_recovery_id(reid)?, sig);
+    if recovered_key.is_err() {
+        return Err(UpgradeError::SignatureRecoveryFailed.into());
+    }
+
     let key =  recovered_key.unwrap().0;
 
     msg!("Recovered public key from signature: {}", bs58::encode(key.as_ref()).into_string().as_str());
-    msg!("Required public key: {}", bs58::encode(target_key.as_ref()).into_string().as_str());
+    Ok(key)
+}
 
-    if key != target_key {
-        return ProgramResult::Err(UpgradeError::WrongSignature.into());
+fn is_key_matches(key: &[u8; SECP256K1_PUBLIC_KEY_LENGTH], target_key: &AdminKey) -> bool {
+    match target_key {
+        AdminKey::PublicKey(public_key) => key == public_key,
+        AdminKey::EthAddress(address) => eth_address(key) == *address,
+        AdminKey::Ed25519(_) => false,
+        AdminKey::CompressedPublicKey(public_key) => compress_public_key(key) == *public_key,
     }
+}
 
-    msg!("Public keys are equal");
-    Ok(())
-}
\ No newline at end of file
+/// Compressed public keys can not be matched against the address without decompression,
+/// so signers with compressed keys should pass signatures in instruction args.
+fn is_address_matches(address: &[u8; ETH_ADDRESS_LENGTH], target_key: &AdminKey) -> bool {
+    match target_key {
+        AdminKey::PublicKey(public_key) => eth_address(public_key) == *address,
+        AdminKey::EthAddress(target_address) => target_address == address,
+        AdminKey::Ed25519(_) | AdminKey::CompressedPublicKey(_) => false,
+    }
+}
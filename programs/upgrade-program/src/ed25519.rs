@@ -0,0 +1,95 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::{PUBKEY_BYTES, Pubkey};
+use solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use solana_program::msg;
+use solana_program::entrypoint::ProgramResult;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use crate::error::UpgradeError;
+use crate::state::AdminKey;
+
+const ED25519_SIGNATURE_OFFSETS_START: usize = 2;
+
+const ED25519_SIGNATURE_OFFSETS_SIZE: usize = 14;
+
+/// Checks that ed25519 public key is a valid curve point that is not of small order (e.g. the identity).
+pub fn is_valid_ed25519_key(public_key: &Pubkey) -> bool {
+    match CompressedEdwardsY(public_key.to_bytes()).decompress() {
+        Some(point) => !point.is_small_order(),
+        None => false,
+    }
+}
+
+/// Marks in `signed` every ed25519 signer from `target_keys` that signed `message`
+/// in preceding `ed25519_program` instructions.
+pub fn mark_ed25519_signers(instructions_info: &AccountInfo, message: &[u8], target_keys: &[AdminKey], signed: &mut [bool]) -> ProgramResult {
+    for signer in load_ed25519_signers(instructions_info, message)? {
+        let index = target_keys.iter().position(|target_key| *target_key == AdminKey::Ed25519(signer));
+        if index.is_none() {
+            return ProgramResult::Err(UpgradeError::PrecompileSignerMismatch.into());
+        }
+
+        let index = index.unwrap();
+        if signed[index] {
+            return ProgramResult::Err(UpgradeError::DuplicateSignature.into());
+        }
+
+        signed[index] = true;
+    }
+
+    Ok(())
+}
+
+/// Collects ed25519 public keys that signed `message` in `ed25519_program` instructions
+/// preceding the current one. Only signatures that store public key, signature and message
+/// inside the same `ed25519_program` instruction are taken into account.
+pub fn load_ed25519_signers(instructions_info: &AccountInfo, message: &[u8]) -> Result<Vec<Pubkey>, ProgramError> {
+    let current_index = load_current_index_checked(instructions_info)?;
+    let mut signers = Vec::new();
+
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_info)?;
+        if instruction.program_id != solana_program::ed25519_program::id() {
+            continue;
+        }
+
+        let data = instruction.data.as_slice();
+        if data.len() < ED25519_SIGNATURE_OFFSETS_START {
+            return Err(UpgradeError::InvalidEd25519Instruction.into());
+        }
+
+        for i in 0..data[0] as usize {
+            let start = ED25519_SIGNATURE_OFFSETS_START + i * ED25519_SIGNATURE_OFFSETS_SIZE;
+            let offsets = data.get(start..start + ED25519_SIGNATURE_OFFSETS_SIZE)
+                .ok_or(UpgradeError::InvalidEd25519Instruction)?;
+
+            let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+            let signature_instruction_index = read_u16(2);
+            let public_key_offset = read_u16(4) as usize;
+            let public_key_instruction_index = read_u16(6);
+            let message_data_offset = read_u16(8) as usize;
+            let message_data_size = read_u16(10) as usize;
+            let message_instruction_index = read_u16(12);
+
+            if signature_instruction_index != u16::MAX || public_key_instruction_index != u16::MAX || message_instruction_index != u16::MAX {
+                continue;
+            }
+
+            let signed_message = data.get(message_data_offset..message_data_offset + message_data_size)
+                .ok_or(UpgradeError::InvalidEd25519Instruction)?;
+            if signed_message != message {
+                continue;
+            }
+
+            let public_key = data.get(public_key_offset..public_key_offset + PUBKEY_BYTES)
+                .ok_or(UpgradeError::InvalidEd25519Instruction)?;
+
+            let signer = Pubkey::try_from(public_key).map_err(|_| UpgradeError::InvalidEd25519Instruction)?;
+            msg!("Found ed25519 signer: {}", signer);
+            signers.push(signer);
+        }
+    }
+
+    Ok(signers)
+}
@@ -1,8 +1,13 @@
 //! Error types
 
+// num-derive 0.3 places the FromPrimitive impl inside an anonymous const
+#![allow(non_local_definitions)]
+
 use num_derive::FromPrimitive;
 use solana_program::{decode_error::DecodeError, msg, program_error::{PrintProgramError, ProgramError}};
 use thiserror::Error;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::utils::CommonError;
 
 /// Errors that may be returned by the Token program.
 #[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
@@ -19,12 +24,276 @@ pub enum UpgradeError {
     /// 3 Wrong seeds for admin account
     #[error("Wrong seeds")]
     WrongSeeds,
-    /// 4 Wrong signature key
+    /// 4 Wrong signature key. Not returned anymore, replaced by RecoveredKeyMismatch and PrecompileSignerMismatch
     #[error("Wrong signature public key")]
     WrongSignature,
     /// 5 Invalid signature
     #[error("Invalid signature")]
     InvalidSignature,
+    /// 6 Several signatures recovered to the same signer
+    #[error("Duplicate signature")]
+    DuplicateSignature,
+    /// 7 Number of valid signatures is less than threshold
+    #[error("Not enough signatures")]
+    NotEnoughSignatures,
+    /// 8 Signer set is empty, too large, contains duplicates or has wrong threshold
+    #[error("Invalid signer set")]
+    InvalidSignerSet,
+    /// 9 Malformed ed25519_program instruction
+    #[error("Invalid ed25519 instruction")]
+    InvalidEd25519Instruction,
+    /// 10 Malformed secp256k1_program instruction
+    #[error("Invalid secp256k1 instruction")]
+    InvalidSecp256k1Instruction,
+    /// 11 Signed operation has expired
+    #[error("Signature expired")]
+    SignatureExpired,
+    /// 12 Signature S component is not in the lower half of the curve order
+    #[error("Malleable signature")]
+    MalleableSignature,
+    /// 13 Signer merkle proof does not match stored root
+    #[error("Invalid signer proof")]
+    InvalidSignerProof,
+    /// 14 Another signer set change is already staged
+    #[error("Key change pending")]
+    KeyChangePending,
+    /// 15 There is no staged signer set change
+    #[error("No pending key change")]
+    NoPendingKeyChange,
+    /// 16 Key change activation slot has not been reached yet
+    #[error("Key change delay has not elapsed")]
+    KeyChangeDelayNotElapsed,
+    /// 17 Key is in the revoked keys list
+    #[error("Key revoked")]
+    KeyRevoked,
+    /// 18 Revoked keys list is full
+    #[error("Too many revoked keys")]
+    TooManyRevokedKeys,
+    /// 19 Batch contains too many operations
+    #[error("Too many batch operations")]
+    TooManyBatchOperations,
+    /// 20 Operation is not approved by the batch
+    #[error("Operation not approved")]
+    OperationNotApproved,
+    /// 21 Session permissions contain unknown or privileged operations
+    #[error("Invalid session permissions")]
+    InvalidSessionPermissions,
+    /// 22 Session key expiry slot has passed
+    #[error("Session expired")]
+    SessionExpired,
+    /// 23 Session key is not permitted to sign the operation
+    #[error("Operation not permitted for session")]
+    SessionNotPermitted,
+    /// 24 Invalid secp256r1 program instruction
+    #[error("Invalid secp256r1 instruction")]
+    InvalidSecp256r1Instruction,
+    /// 25 Key or signature can not be used with UpgradeAdmin curve
+    #[error("Curve mismatch")]
+    CurveMismatch,
+    /// 26 Hash algorithm is not supported by hashing mode
+    #[error("Invalid hash algorithm")]
+    InvalidHashAlgorithm,
+    /// 27 Public key is all zeros
+    #[error("Zero public key")]
+    ZeroPublicKey,
+    /// 28 Public key is not a valid point of the signer curve or is the identity/small order point
+    #[error("Invalid curve point")]
+    InvalidCurvePoint,
+    /// 29 Nonce in instruction args differs from the stored one
+    #[error("Nonce mismatch")]
+    NonceMismatch,
+    /// 30 UpgradeAdmin has no guardian key
+    #[error("No guardian")]
+    NoGuardian,
+    /// 31 Recovery delay is shorter than the minimal one or overflows the activation slot
+    #[error("Invalid recovery delay")]
+    InvalidRecoveryDelay,
+    /// 32 Recovery is already started
+    #[error("Recovery pending")]
+    RecoveryPending,
+    /// 33 There is no started recovery
+    #[error("No pending recovery")]
+    NoPendingRecovery,
+    /// 34 Recovery can not be completed before its activation slot
+    #[error("Recovery delay not elapsed")]
+    RecoveryDelayNotElapsed,
+    /// 35 UpgradeAdmin is still the upgrade authority of the contract
+    #[error("Admin is upgrade authority")]
+    AdminIsAuthority,
+    /// 36 UpgradeAdmin account has outdated layout and should be migrated by MigrateAdmin
+    #[error("Outdated admin version")]
+    OutdatedAdminVersion,
+    /// 37 UpgradeAdmin account already has the current layout
+    #[error("Admin already migrated")]
+    AdminAlreadyMigrated,
+    /// 38 Operation is frozen by Pause
+    #[error("Admin is paused")]
+    Paused,
+    /// 39 Contract is not managed by UpgradeAdmin
+    #[error("Contract not managed")]
+    ContractNotManaged,
+    /// 40 Managed contracts list is too long or has duplicates
+    #[error("Invalid managed contracts")]
+    InvalidManagedContracts,
+    /// 41 UpgradeAdmin still lists managed contracts
+    #[error("Managed contracts not empty")]
+    ManagedContractsNotEmpty,
+    /// 42 Metadata URI is longer than MAX_URI_LENGTH
+    #[error("URI too long")]
+    UriTooLong,
+    /// 43 UpgradeAdmin is deactivated, only Reactivate is allowed
+    #[error("Admin deactivated")]
+    AdminDeactivated,
+    /// 44 UpgradeAdmin authority lapsed at its expiry slot, only ExtendExpiry is allowed
+    #[error("Admin expired")]
+    AdminExpired,
+    /// 45 New expiry slot is not later than the current one
+    #[error("Invalid expiry")]
+    InvalidExpiry,
+    /// 46 Upgrade is not signed by an allowed executor
+    #[error("Executor not allowed")]
+    ExecutorNotAllowed,
+    /// 47 Executors list is too long or has duplicates
+    #[error("Invalid executors")]
+    InvalidExecutors,
+    /// 48 Operation nonce reached its maximum value
+    #[error("Nonce overflow")]
+    NonceOverflow,
+    /// 49 Account data has unexpected size
+    #[error("Invalid account size")]
+    InvalidAccountSize,
+    /// 50 Account is not a buffer of the upgradeable loader
+    #[error("Invalid buffer")]
+    InvalidBuffer,
+    /// 51 Buffer contents differ from the approved code hash
+    #[error("Code hash mismatch")]
+    CodeHashMismatch,
+    /// 52 Upgrade of the contract is already proposed
+    #[error("Upgrade pending")]
+    UpgradePending,
+    /// 53 No proposed upgrade matches the accounts
+    #[error("No pending upgrade")]
+    NoPendingUpgrade,
+    /// 54 Upgrade delay has not elapsed since the proposal
+    #[error("Upgrade delay has not elapsed")]
+    UpgradeDelayNotElapsed,
+    /// 55 Buffer authority is not the admin
+    #[error("Wrong buffer authority")]
+    WrongBufferAuthority,
+    /// 56 No rollback snapshot matches the accounts
+    #[error("No rollback snapshot")]
+    NoRollbackSnapshot,
+    /// 57 Minimal interval since the last upgrade has not elapsed
+    #[error("Upgrade too soon")]
+    UpgradeTooSoon,
+    /// 58 Upgrade window is empty or already closed
+    #[error("Invalid upgrade window")]
+    InvalidUpgradeWindow,
+    /// 59 Current slot is outside of the proposed upgrade window
+    #[error("Outside of upgrade window")]
+    OutsideUpgradeWindow,
+    /// 60 Batch upgrade is empty, too long or upgrades the same contract twice
+    #[error("Invalid batch upgrade")]
+    InvalidBatchUpgrade,
+    /// 61 Primary contract of UpgradeAdmin was made immutable
+    #[error("Admin is immutable")]
+    AdminImmutable,
+    /// 62 Chunk does not continue staged buffer or exceeds its length
+    #[error("Invalid chunk")]
+    InvalidChunk,
+    /// 63 Staged buffer is not completely written
+    #[error("Buffer not complete")]
+    BufferNotComplete,
+    /// 64 Staged buffer is already finalized
+    #[error("Buffer finalized")]
+    BufferFinalized,
+    /// 65 Role table is too long, has duplicates, misses a role or executor is not a Solana signer
+    #[error("Invalid roles")]
+    InvalidRoles,
+    /// 66 Operation is not authorized by a key bound to the required role
+    #[error("Role not authorized")]
+    RoleNotAuthorized,
+    /// 67 Approver already approved the pending upgrade
+    #[error("Already approved")]
+    AlreadyApproved,
+    /// 68 Pending upgrade has fewer approvals than approval threshold
+    #[error("Not enough approvals")]
+    NotEnoughApprovals,
+    /// 69 UpgradeAdmin has no role table
+    #[error("Roles not set")]
+    RolesNotSet,
+    /// 70 Veto key is a signer of UpgradeAdmin or is not a valid key
+    #[error("Invalid veto key")]
+    InvalidVetoKey,
+    /// 71 UpgradeAdmin has no veto key or veto is not signed by it
+    #[error("Veto not authorized")]
+    VetoNotAuthorized,
+    /// 72 Previous upgrade did not pass its healthcheck yet
+    #[error("Healthcheck pending")]
+    HealthcheckPending,
+    /// 73 Healthcheck is too long, there are too many healthchecks or no healthcheck is pending
+    #[error("Invalid healthcheck")]
+    InvalidHealthcheck,
+    /// 74 Buffer hash does not match the verified build hash of the contract
+    #[error("Build hash not verified")]
+    BuildHashNotVerified,
+    /// 75 Account passed in place of a sysvar has a different key
+    #[error("Invalid sysvar account")]
+    InvalidSysvarAccount,
+    /// 76 Account passed in place of the system program has a different key
+    #[error("Invalid system program")]
+    InvalidSystemProgram,
+    /// 77 ProgramData account is not derived from the contract
+    #[error("Wrong ProgramData account")]
+    WrongProgramDataAccount,
+    /// 78 secp256k1_recover failed to recover a public key from the signature
+    #[error("Signature recovery failed")]
+    SignatureRecoveryFailed,
+    /// 79 Recovery id is not 0/1, 27/28 or EIP-155 value
+    #[error("Invalid recovery id")]
+    InvalidRecoveryId,
+    /// 80 Public key recovered from the signature does not belong to the expected signers
+    #[error("Recovered key mismatch")]
+    RecoveredKeyMismatch,
+    /// 81 Signer verified by a precompile instruction does not belong to the expected signers
+    #[error("Precompile signer mismatch")]
+    PrecompileSignerMismatch,
+    /// 82 UpgradeAdmin account is owned by another program
+    #[error("Admin owner mismatch")]
+    AdminOwnerMismatch,
+    /// 83 ProgramData account is not owned by the upgradeable loader
+    #[error("ProgramData owner mismatch")]
+    ProgramDataOwnerMismatch,
+    /// 84 Instruction data is encoded with an unknown format version
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    /// 85 Account that is modified by the instruction is passed as readonly
+    #[error("Account not writable")]
+    AccountNotWritable,
+    /// 86 Program account does not match the contract of the admin
+    #[error("Contract mismatch")]
+    ContractMismatch,
+    /// 87 Same account is passed for several roles of the instruction
+    #[error("Duplicate account")]
+    DuplicateAccount,
+    /// 88 UpgradeAdmin has upgrade delay, so the upgrade should be proposed and executed after the delay
+    #[error("Timelock required")]
+    TimelockRequired,
+    /// 89 UpgradeAdmin has roles or veto key, so the upgrade should be proposed and executed after approvals and veto period
+    #[error("Proposal required")]
+    ProposalRequired,
+    /// 90 Staged buffer is written without the signature of its writer
+    #[error("Buffer writer mismatch")]
+    BufferWriterMismatch,
+    /// 91 SetBufferAuthority is not followed by ProposeUpgrade or Upgrade of the same buffer
+    #[error("Buffer not registered")]
+    BufferNotRegistered,
+    /// 92 Buffer staged by CreateBuffer is upgraded before FinalizeBuffer
+    #[error("Buffer not finalized")]
+    BufferNotFinalized,
+    /// 93 Activation slot of the pending change overflows u64
+    #[error("Math overflow")]
+    MathOverflow,
 }
 
 
@@ -34,6 +303,53 @@ impl From<UpgradeError> for ProgramError {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl CommonError for UpgradeError {
+    fn already_in_use() -> Self {
+        UpgradeError::AlreadyInUse
+    }
+
+    fn invalid_sysvar_account() -> Self {
+        UpgradeError::InvalidSysvarAccount
+    }
+
+    fn invalid_system_program() -> Self {
+        UpgradeError::InvalidSystemProgram
+    }
+
+    fn signature_expired() -> Self {
+        UpgradeError::SignatureExpired
+    }
+
+    fn nonce_mismatch() -> Self {
+        UpgradeError::NonceMismatch
+    }
+
+    fn duplicate_signature() -> Self {
+        UpgradeError::DuplicateSignature
+    }
+
+    fn not_enough_signatures() -> Self {
+        UpgradeError::NotEnoughSignatures
+    }
+
+    fn invalid_signature() -> Self {
+        UpgradeError::InvalidSignature
+    }
+
+    fn invalid_signer_set() -> Self {
+        UpgradeError::InvalidSignerSet
+    }
+
+    fn math_overflow() -> Self {
+        UpgradeError::MathOverflow
+    }
+
+    fn account_not_writable() -> Self {
+        UpgradeError::AccountNotWritable
+    }
+}
+
 impl PrintProgramError for UpgradeError {
     fn print<E>(&self) {
         msg!(&self.to_string());
@@ -0,0 +1,93 @@
+//! Borsh-encoded events emitted through `sol_log_data`, so indexers can decode them from transaction logs
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+use crate::state::AdminKey;
+
+/// UpgradeAdmin of `contract` was created by InitAdmin.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AdminInitialized {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    pub public_keys: Vec<AdminKey>,
+    pub threshold: u8,
+}
+
+/// Signer set of UpgradeAdmin was replaced by ActivateKey or ActivateRecovery.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KeyChanged {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    pub public_keys: Vec<AdminKey>,
+    pub threshold: u8,
+    // Key nonce at the moment of the change
+    pub nonce: u128,
+}
+
+/// Upgrade authority of `contract` was transferred from UpgradeAdmin. `None` means the contract became immutable.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AuthorityChanged {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::option_display"))]
+    pub authority: Option<Pubkey>,
+    // Authority nonce consumed by the operation
+    pub nonce: u128,
+}
+
+/// `contract` was upgraded from `buffer` by Upgrade, BatchUpgrade, ExecuteUpgrade or Rollback.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Upgraded {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub buffer: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    // Upgrade nonce consumed by the operation or current one for ExecuteUpgrade
+    pub nonce: u128,
+    pub slot: u64,
+}
+
+/// Every event emitted by the program. Log data is the Borsh encoding of this enum, so the first byte is the event index.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(tag = "name", content = "data"))]
+pub enum UpgradeEvent {
+    AdminInitialized(AdminInitialized),
+    KeyChanged(KeyChanged),
+    AuthorityChanged(AuthorityChanged),
+    Upgraded(Upgraded),
+}
+
+impl UpgradeEvent {
+    /// UpgradeAdmin the event belongs to.
+    pub fn admin(&self) -> &Pubkey {
+        match self {
+            UpgradeEvent::AdminInitialized(event) => &event.admin,
+            UpgradeEvent::KeyChanged(event) => &event.admin,
+            UpgradeEvent::AuthorityChanged(event) => &event.admin,
+            UpgradeEvent::Upgraded(event) => &event.admin,
+        }
+    }
+
+    /// Writes the event to the transaction log.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+}
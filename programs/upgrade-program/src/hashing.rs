@@ -0,0 +1,1068 @@
+//! Hashes of the messages that should be signed by UpgradeAdmin signers
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::keccak::{hash, hashv, Hash};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use crate::message::{
+    change_authority_message_hash, change_key_message_hash, eip712_hash, encode_i64, encode_keys, encode_u128,
+    encode_u64, encode_weights, legacy_hash, type_hash, upgrade_message_hash, MessageContext,
+};
+use crate::state::{AdminKey, RoleAssignment, RoleKey, UpgradeAdmin};
+use crate::HASH_CONSTANT;
+
+pub const EIP712_DOMAIN_VERSION: &str = "1";
+
+pub const EIP712_DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)";
+
+pub const EIP712_UPGRADE_TYPE: &str = "Upgrade(bytes32 contract,uint64 nonce,bytes32 buffer,bytes32 codeHash,bytes32 spill,int64 expiresAt)";
+
+pub const EIP712_CHANGE_AUTHORITY_TYPE: &str = "ChangeAuthority(bytes32 contract,uint64 nonce,bytes32 authority,int64 expiresAt)";
+
+/// Legacy payload prefix for ChangeAuthority. Upgrade and ChangeAuthority nonces are independent,
+/// so the prefix prevents Upgrade signature of the buffer from being reused to set authority to the same address.
+pub const CHANGE_AUTHORITY_TAG: &str = "change-authority";
+
+pub const EIP712_CANCEL_KEY_CHANGE_TYPE: &str = "CancelKeyChange(bytes32 contract,uint64 nonce,int64 expiresAt)";
+
+/// Legacy payload for CancelKeyChange. Its length differs from payloads of Upgrade and ChangeAuthority,
+/// so cancel signature can not be reused as Upgrade or ChangeAuthority signature.
+pub const CANCEL_KEY_CHANGE_TAG: &str = "cancel-key-change";
+
+pub const EIP712_REVOKE_KEYS_TYPE: &str = "RevokeKeys(bytes32 contract,uint64 nonce,bytes[] keys,int64 expiresAt)";
+
+/// Legacy payload prefix for RevokeKeys.
+pub const REVOKE_KEYS_TAG: &str = "revoke-keys";
+
+pub const EIP712_VERIFY_BATCH_TYPE: &str = "VerifyBatch(bytes32 contract,uint64 nonce,bytes32 operationsRoot,int64 expiresAt)";
+
+/// Legacy payload prefix for VerifyBatch.
+pub const VERIFY_BATCH_TAG: &str = "verify-batch";
+
+pub const EIP712_CREATE_SESSION_TYPE: &str = "CreateSession(bytes32 contract,uint64 nonce,bytes sessionKey,uint8 permissions,uint64 expirySlot,int64 expiresAt)";
+
+/// Legacy payload prefix for CreateSession.
+pub const CREATE_SESSION_TAG: &str = "create-session";
+
+pub const EIP712_START_RECOVERY_TYPE: &str = "StartRecovery(bytes32 contract,uint64 nonce,uint8 threshold,bytes[] publicKeys,uint8[] weights,int64 expiresAt)";
+
+/// Legacy payload prefix for StartRecovery.
+pub const START_RECOVERY_TAG: &str = "start-recovery";
+
+pub const EIP712_CANCEL_RECOVERY_TYPE: &str = "CancelRecovery(bytes32 contract,uint64 nonce,int64 expiresAt)";
+
+/// Legacy payload for CancelRecovery.
+pub const CANCEL_RECOVERY_TAG: &str = "cancel-recovery";
+
+pub const EIP712_CLOSE_ADMIN_TYPE: &str = "CloseAdmin(bytes32 contract,uint64 nonce,bytes32 destination,int64 expiresAt)";
+
+/// Legacy payload prefix for CloseAdmin.
+pub const CLOSE_ADMIN_TAG: &str = "close-admin";
+
+pub const EIP712_SET_IMMUTABLE_TYPE: &str = "SetImmutable(bytes32 contract,uint64 nonce,int64 expiresAt)";
+
+/// Legacy payload for SetImmutable.
+pub const SET_IMMUTABLE_TAG: &str = "set-immutable";
+
+pub const EIP712_SET_PAUSED_TYPE: &str = "SetPaused(bytes32 contract,uint64 nonce,bool paused,int64 expiresAt)";
+
+/// Legacy payload for Pause.
+pub const PAUSE_TAG: &str = "pause";
+
+/// Legacy payload for Unpause.
+pub const UNPAUSE_TAG: &str = "unpause";
+
+pub const EIP712_SET_MANAGED_CONTRACTS_TYPE: &str = "SetManagedContracts(bytes32 contract,uint64 nonce,bytes32[] contracts,int64 expiresAt)";
+
+/// Legacy payload prefix for SetManagedContracts.
+pub const SET_MANAGED_CONTRACTS_TAG: &str = "set-managed-contracts";
+
+pub const EIP712_SET_METADATA_TYPE: &str = "SetMetadata(bytes32 contract,uint64 nonce,bytes32 name,string uri,int64 expiresAt)";
+
+/// Legacy payload prefix for SetMetadata.
+pub const SET_METADATA_TAG: &str = "set-metadata";
+
+pub const EIP712_SET_ACTIVE_TYPE: &str = "SetActive(bytes32 contract,uint64 nonce,bool active,int64 expiresAt)";
+
+/// Legacy payload for Deactivate.
+pub const DEACTIVATE_TAG: &str = "deactivate";
+
+/// Legacy payload for Reactivate.
+pub const REACTIVATE_TAG: &str = "reactivate";
+
+pub const EIP712_EXTEND_EXPIRY_TYPE: &str = "ExtendExpiry(bytes32 contract,uint64 nonce,uint64 expiresAtSlot,int64 expiresAt)";
+
+/// Legacy payload prefix for ExtendExpiry.
+pub const EXTEND_EXPIRY_TAG: &str = "extend-expiry";
+
+pub const EIP712_SET_EXECUTORS_TYPE: &str = "SetExecutors(bytes32 contract,uint64 nonce,bytes32[] executors,uint64 reimbursement,int64 expiresAt)";
+
+/// Legacy payload prefix for SetExecutors.
+pub const SET_EXECUTORS_TAG: &str = "set-executors";
+
+pub const EIP712_SET_UPGRADE_DELAY_TYPE: &str = "SetUpgradeDelay(bytes32 contract,uint64 nonce,uint64 minDelaySlots,int64 expiresAt)";
+
+/// Legacy payload prefix for SetUpgradeDelay.
+pub const SET_UPGRADE_DELAY_TAG: &str = "set-upgrade-delay";
+
+pub const EIP712_SET_UPGRADE_INTERVAL_TYPE: &str = "SetUpgradeInterval(bytes32 contract,uint64 nonce,uint64 minUpgradeIntervalSlots,int64 expiresAt)";
+
+/// Legacy payload prefix for SetUpgradeInterval.
+pub const SET_UPGRADE_INTERVAL_TAG: &str = "set-upgrade-interval";
+
+pub const EIP712_PROPOSE_UPGRADE_TYPE: &str = "ProposeUpgrade(bytes32 contract,uint64 nonce,bytes32 buffer,bytes32 codeHash,uint64 notBeforeSlot,uint64 notAfterSlot,int64 expiresAt)";
+
+/// Legacy payload prefix for ProposeUpgrade. It shares upgrade nonce with Upgrade, so the prefix prevents
+/// the proposal signature from being used as Upgrade signature of the same buffer.
+pub const PROPOSE_UPGRADE_TAG: &str = "propose-upgrade";
+
+pub const EIP712_CANCEL_UPGRADE_TYPE: &str = "CancelUpgrade(bytes32 contract,uint64 nonce,bytes32 buffer,bool closeBuffer,int64 expiresAt)";
+
+/// Legacy payload prefix for CancelUpgrade.
+pub const CANCEL_UPGRADE_TAG: &str = "cancel-upgrade";
+
+pub const EIP712_REGISTER_SNAPSHOT_TYPE: &str = "RegisterSnapshot(bytes32 contract,uint64 nonce,bytes32 program,bytes32 buffer,bytes32 codeHash,int64 expiresAt)";
+
+/// Legacy payload prefix for RegisterSnapshot.
+pub const REGISTER_SNAPSHOT_TAG: &str = "register-snapshot";
+
+pub const EIP712_ROLLBACK_TYPE: &str = "Rollback(bytes32 contract,uint64 nonce,bytes32 buffer,int64 expiresAt)";
+
+/// Legacy payload prefix for Rollback. Rollback requires a single signature, so the prefix prevents
+/// it from being used as Upgrade signature of the same buffer.
+pub const ROLLBACK_TAG: &str = "rollback";
+
+pub const EIP712_BATCH_UPGRADE_TYPE: &str = "BatchUpgrade(bytes32 contract,uint64 nonce,bytes32 upgradesHash,bytes32 spill,int64 expiresAt)";
+
+/// Legacy payload prefix for BatchUpgrade.
+pub const BATCH_UPGRADE_TAG: &str = "batch-upgrade";
+
+pub const EIP712_DEPLOY_TYPE: &str = "Deploy(bytes32 contract,uint64 nonce,bytes32 program,uint64 maxDataLen,int64 expiresAt)";
+
+/// Legacy payload prefix for Deploy.
+pub const DEPLOY_TAG: &str = "deploy";
+
+pub const EIP712_EXTEND_PROGRAM_TYPE: &str = "ExtendProgram(bytes32 contract,uint64 nonce,uint32 additionalBytes,int64 expiresAt)";
+
+/// Legacy payload prefix for ExtendProgram.
+pub const EXTEND_PROGRAM_TAG: &str = "extend-program";
+
+pub const EIP712_CREATE_BUFFER_TYPE: &str = "CreateBuffer(bytes32 contract,uint64 nonce,bytes32 buffer,bytes32 writer,uint64 dataLen,bytes32 codeHash,int64 expiresAt)";
+
+/// Legacy payload prefix for CreateBuffer.
+pub const CREATE_BUFFER_TAG: &str = "create-buffer";
+
+pub const EIP712_CLOSE_BUFFER_TYPE: &str = "CloseBuffer(bytes32 contract,uint64 nonce,bytes32 buffer,bytes32 recipient,int64 expiresAt)";
+
+/// Legacy payload prefix for CloseBuffer.
+pub const CLOSE_BUFFER_TAG: &str = "close-buffer";
+
+pub const EIP712_SET_ROLES_TYPE: &str = "SetRoles(bytes32 contract,uint64 nonce,bytes32 rolesHash,uint8 approvalThreshold,int64 expiresAt)";
+
+/// Legacy payload prefix for SetRoles.
+pub const SET_ROLES_TAG: &str = "set-roles";
+
+pub const EIP712_APPROVE_UPGRADE_TYPE: &str = "ApproveUpgrade(bytes32 contract,uint64 proposalSlot,bytes32 buffer,bytes32 codeHash,int64 expiresAt)";
+
+/// Legacy payload prefix for ApproveUpgrade.
+pub const APPROVE_UPGRADE_TAG: &str = "approve-upgrade";
+
+pub const EIP712_SET_VETO_KEY_TYPE: &str = "SetVetoKey(bytes32 contract,uint64 nonce,bytes32 vetoKeyHash,int64 expiresAt)";
+
+/// Legacy payload prefix for SetVetoKey.
+pub const SET_VETO_KEY_TAG: &str = "set-veto-key";
+
+pub const EIP712_VETO_UPGRADE_TYPE: &str = "VetoUpgrade(bytes32 contract,uint64 proposalSlot,bytes32 buffer,bytes32 codeHash,bytes32 destination,int64 expiresAt)";
+
+/// Legacy payload prefix for VetoUpgrade.
+pub const VETO_UPGRADE_TAG: &str = "veto-upgrade";
+
+pub const EIP712_SET_HEALTHCHECK_TYPE: &str = "SetHealthcheck(bytes32 contract,uint64 nonce,bytes32 dataHash,int64 expiresAt)";
+
+/// Legacy payload prefix for SetHealthcheck.
+pub const SET_HEALTHCHECK_TAG: &str = "set-healthcheck";
+
+pub const EIP712_SET_VERIFIED_BUILD_HASH_TYPE: &str = "SetVerifiedBuildHash(bytes32 contract,uint64 nonce,bytes32 buildHash,bool required,int64 expiresAt)";
+
+/// Legacy payload prefix for SetVerifiedBuildHash.
+pub const SET_VERIFIED_BUILD_HASH_TAG: &str = "set-verified-build-hash";
+
+pub const EIP712_CHANGE_PUBLIC_KEY_TYPE: &str = "ChangePublicKey(bytes32 contract,uint64 nonce,uint8 threshold,bytes[] publicKeys,uint8[] weights,bytes32 signersRoot,bytes upgradeKey,int64 expiresAt)";
+
+/// Format of the message that should be signed by UpgradeAdmin signers.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum HashingMode {
+    /// keccak_hash of concatenated program id, domain tag and operation fields
+    Legacy,
+    /// EIP-712 typed structured data hash
+    Eip712,
+}
+
+/// Algorithm of the outer digest of legacy messages.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum HashAlgorithm {
+    /// Keccak-256, the only algorithm supported by EIP-712 hashing mode and `secp256k1_program` signers
+    Keccak,
+    /// SHA-256 for signing infrastructure that signs SHA-256 digests only (e.g. Bitcoin-derived TSS)
+    Sha256,
+}
+
+/// Width of UpgradeAdmin nonces in signed messages.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NonceWidth {
+    /// 8 byte nonce of legacy messages and `uint64 nonce` of EIP-712 types
+    U64,
+    /// 16 byte nonce of legacy messages and `uint128 nonce` of EIP-712 types, for long-lived admins
+    /// that can exhaust u64 nonces
+    U128,
+}
+
+/// Hash to sign for Upgrade instruction of `contract` managed by the admin.
+#[allow(clippy::too_many_arguments)]
+pub fn upgrade_hash(
+    program_id: &Pubkey,
+    admin: &UpgradeAdmin,
+    contract: &Pubkey,
+    buffer: &Pubkey,
+    code_hash: &[u8; 32],
+    spill: &Pubkey,
+    expires_at: i64,
+) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    upgrade_message_hash(&ctx, admin.upgrade_nonce, buffer, code_hash, spill, expires_at)
+}
+
+/// Hash to sign for ProposeUpgrade instruction of `contract` managed by the admin.
+#[allow(clippy::too_many_arguments)]
+pub fn propose_upgrade_hash(
+    program_id: &Pubkey,
+    admin: &UpgradeAdmin,
+    contract: &Pubkey,
+    buffer: &Pubkey,
+    code_hash: &[u8; 32],
+    not_before_slot: Option<u64>,
+    not_after_slot: Option<u64>,
+    expires_at: i64,
+) -> Result<Hash, ProgramError> {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &ctx,
+            admin.upgrade_nonce,
+            &[
+                PROPOSE_UPGRADE_TAG.as_bytes(),
+                buffer.as_ref(),
+                code_hash.as_ref(),
+                not_before_slot.try_to_vec()?.as_slice(),
+                not_after_slot.try_to_vec()?.as_slice(),
+            ].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => Ok(eip712_hash(
+            &ctx,
+            &[
+                type_hash(admin.nonce_width, EIP712_PROPOSE_UPGRADE_TYPE).as_ref(),
+                contract.as_ref(),
+                &encode_u128(admin.upgrade_nonce),
+                buffer.as_ref(),
+                code_hash.as_ref(),
+                &encode_u64(not_before_slot.unwrap_or_default()),
+                &encode_u64(not_after_slot.unwrap_or(u64::MAX)),
+                &encode_i64(expires_at),
+            ],
+        )),
+    }
+}
+
+/// Hash to sign for CancelUpgrade instruction of `contract` managed by the admin.
+pub fn cancel_upgrade_hash(program_id: &Pubkey, admin: &UpgradeAdmin, contract: &Pubkey, buffer: &Pubkey, close_buffer: bool, expires_at: i64) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &ctx,
+            admin.upgrade_nonce,
+            &[CANCEL_UPGRADE_TAG.as_bytes(), buffer.as_ref(), &[close_buffer as u8]].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &ctx,
+            &[
+                type_hash(admin.nonce_width, EIP712_CANCEL_UPGRADE_TYPE).as_ref(),
+                contract.as_ref(),
+                &encode_u128(admin.upgrade_nonce),
+                buffer.as_ref(),
+                &encode_u64(close_buffer as u64),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for Rollback instruction of `contract` managed by the admin.
+pub fn rollback_hash(program_id: &Pubkey, admin: &UpgradeAdmin, contract: &Pubkey, buffer: &Pubkey, expires_at: i64) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &ctx,
+            admin.upgrade_nonce,
+            &[ROLLBACK_TAG.as_bytes(), buffer.as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &ctx,
+            &[
+                type_hash(admin.nonce_width, EIP712_ROLLBACK_TYPE).as_ref(),
+                contract.as_ref(),
+                &encode_u128(admin.upgrade_nonce),
+                buffer.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for BatchUpgrade instruction of ordered (contract, buffer, code hash) `upgrades` with rent difference sent to `spill`.
+pub fn batch_upgrade_hash(program_id: &Pubkey, admin: &UpgradeAdmin, upgrades: &[(Pubkey, Pubkey, [u8; 32])], spill: &Pubkey, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => {
+            let mut payload = BATCH_UPGRADE_TAG.as_bytes().to_vec();
+            payload.extend_from_slice(spill.as_ref());
+            for (contract, buffer, code_hash) in upgrades {
+                payload.extend_from_slice(contract.as_ref());
+                payload.extend_from_slice(buffer.as_ref());
+                payload.extend_from_slice(code_hash);
+            }
+
+            legacy_hash(&MessageContext::new(program_id, admin), admin.upgrade_nonce, &payload, expires_at)
+        }
+        HashingMode::Eip712 => {
+            let upgrade_hashes: Vec<Hash> = upgrades.iter()
+                .map(|(contract, buffer, code_hash)| hashv(&[contract.as_ref(), buffer.as_ref(), code_hash.as_ref()]))
+                .collect();
+            let upgrade_hashes: Vec<&[u8]> = upgrade_hashes.iter().map(|h| h.as_ref()).collect();
+            eip712_hash(
+                &MessageContext::new(program_id, admin),
+                &[
+                    type_hash(admin.nonce_width, EIP712_BATCH_UPGRADE_TYPE).as_ref(),
+                    admin.contract.as_ref(),
+                    &encode_u128(admin.upgrade_nonce),
+                    hashv(&upgrade_hashes).as_ref(),
+                    spill.as_ref(),
+                    &encode_i64(expires_at),
+                ],
+            )
+        }
+    }
+}
+
+/// Hash to sign for Deploy instruction of `program` with `max_data_len` bytes of program data.
+pub fn deploy_hash(program_id: &Pubkey, admin: &UpgradeAdmin, program: &Pubkey, max_data_len: u64, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[DEPLOY_TAG.as_bytes(), program.as_ref(), max_data_len.to_be_bytes().as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_DEPLOY_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                program.as_ref(),
+                &encode_u64(max_data_len),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for ExtendProgram instruction of `contract` managed by the admin.
+pub fn extend_program_hash(program_id: &Pubkey, admin: &UpgradeAdmin, contract: &Pubkey, additional_bytes: u32, expires_at: i64) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &ctx,
+            admin.upgrade_nonce,
+            &[EXTEND_PROGRAM_TAG.as_bytes(), additional_bytes.to_be_bytes().as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &ctx,
+            &[
+                type_hash(admin.nonce_width, EIP712_EXTEND_PROGRAM_TYPE).as_ref(),
+                contract.as_ref(),
+                &encode_u128(admin.upgrade_nonce),
+                &encode_u64(additional_bytes as u64),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for CreateBuffer instruction committing `data_len` bytes of program data with `code_hash` for `contract`
+/// written by `writer`.
+#[allow(clippy::too_many_arguments)]
+pub fn create_buffer_hash(
+    program_id: &Pubkey,
+    admin: &UpgradeAdmin,
+    contract: &Pubkey,
+    buffer: &Pubkey,
+    writer: &Pubkey,
+    data_len: u64,
+    code_hash: &[u8; 32],
+    expires_at: i64,
+) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &ctx,
+            admin.upgrade_nonce,
+            &[CREATE_BUFFER_TAG.as_bytes(), buffer.as_ref(), writer.as_ref(), data_len.to_be_bytes().as_ref(), code_hash.as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &ctx,
+            &[
+                type_hash(admin.nonce_width, EIP712_CREATE_BUFFER_TYPE).as_ref(),
+                contract.as_ref(),
+                &encode_u128(admin.upgrade_nonce),
+                buffer.as_ref(),
+                writer.as_ref(),
+                &encode_u64(data_len),
+                code_hash.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for CloseBuffer instruction sending lamports of `buffer` to `recipient`.
+pub fn close_buffer_hash(program_id: &Pubkey, admin: &UpgradeAdmin, buffer: &Pubkey, recipient: &Pubkey, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[CLOSE_BUFFER_TAG.as_bytes(), buffer.as_ref(), recipient.as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_CLOSE_BUFFER_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                buffer.as_ref(),
+                recipient.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for ChangeAuthority instruction of `contract` managed by the admin.
+pub fn change_authority_hash(program_id: &Pubkey, admin: &UpgradeAdmin, contract: &Pubkey, authority: &Pubkey, expires_at: i64) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    change_authority_message_hash(&ctx, admin.authority_nonce, authority, expires_at)
+}
+
+/// Hash to sign for ChangePublicKey instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn change_public_key_hash(
+    program_id: &Pubkey,
+    admin: &UpgradeAdmin,
+    new_public_keys: &[AdminKey],
+    new_weights: &[u8],
+    new_threshold: u8,
+    new_signers_root: Option<[u8; 32]>,
+    new_upgrade_key: &Option<AdminKey>,
+    expires_at: i64,
+) -> Hash {
+    change_key_message_hash(
+        &MessageContext::new(program_id, admin),
+        admin.key_nonce,
+        new_public_keys,
+        new_weights,
+        new_threshold,
+        new_signers_root,
+        new_upgrade_key,
+        expires_at,
+    )
+}
+
+/// Hash to sign for CancelKeyChange instruction.
+pub fn cancel_key_change_hash(program_id: &Pubkey, admin: &UpgradeAdmin, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(&MessageContext::new(program_id, admin), admin.key_nonce, CANCEL_KEY_CHANGE_TAG.as_bytes(), expires_at),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_CANCEL_KEY_CHANGE_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for RevokeKeys instruction.
+pub fn revoke_keys_hash(program_id: &Pubkey, admin: &UpgradeAdmin, keys: &[AdminKey], expires_at: i64) -> Result<Hash, ProgramError> {
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[REVOKE_KEYS_TAG.as_bytes(), keys.try_to_vec()?.as_slice()].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => Ok(eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_REVOKE_KEYS_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                encode_keys(keys).as_ref(),
+                &encode_i64(expires_at),
+            ],
+        )),
+    }
+}
+
+/// Hash to sign for VerifyBatch instruction.
+pub fn verify_batch_hash(program_id: &Pubkey, admin: &UpgradeAdmin, operations_root: &[u8; 32], expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.upgrade_nonce,
+            &[VERIFY_BATCH_TAG.as_bytes(), operations_root.as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_VERIFY_BATCH_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.upgrade_nonce),
+                operations_root.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for CreateSession instruction.
+pub fn create_session_hash(
+    program_id: &Pubkey,
+    admin: &UpgradeAdmin,
+    session_key: &AdminKey,
+    permissions: u8,
+    expiry_slot: u64,
+    expires_at: i64,
+) -> Result<Hash, ProgramError> {
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[
+                CREATE_SESSION_TAG.as_bytes(),
+                session_key.try_to_vec()?.as_slice(),
+                &[permissions],
+                expiry_slot.to_be_bytes().as_ref(),
+            ].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => Ok(eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_CREATE_SESSION_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                hash(session_key.as_ref()).as_ref(),
+                &encode_u64(permissions as u64),
+                &encode_u64(expiry_slot),
+                &encode_i64(expires_at),
+            ],
+        )),
+    }
+}
+
+/// Hash to sign by the guardian for StartRecovery instruction.
+pub fn start_recovery_hash(
+    program_id: &Pubkey,
+    admin: &UpgradeAdmin,
+    new_public_keys: &[AdminKey],
+    new_weights: &[u8],
+    new_threshold: u8,
+    expires_at: i64,
+) -> Result<Hash, ProgramError> {
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[
+                START_RECOVERY_TAG.as_bytes(),
+                &[new_threshold],
+                new_public_keys.try_to_vec()?.as_slice(),
+                new_weights.try_to_vec()?.as_slice(),
+            ].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => Ok(eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_START_RECOVERY_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                &encode_u64(new_threshold as u64),
+                encode_keys(new_public_keys).as_ref(),
+                encode_weights(new_weights).as_ref(),
+                &encode_i64(expires_at),
+            ],
+        )),
+    }
+}
+
+/// Hash to sign for CancelRecovery instruction.
+pub fn cancel_recovery_hash(program_id: &Pubkey, admin: &UpgradeAdmin, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(&MessageContext::new(program_id, admin), admin.key_nonce, CANCEL_RECOVERY_TAG.as_bytes(), expires_at),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_CANCEL_RECOVERY_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for CloseAdmin instruction.
+pub fn close_admin_hash(program_id: &Pubkey, admin: &UpgradeAdmin, destination: &Pubkey, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.authority_nonce,
+            &[CLOSE_ADMIN_TAG.as_bytes(), destination.as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_CLOSE_ADMIN_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.authority_nonce),
+                destination.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for SetImmutable instruction of `contract` managed by the admin.
+pub fn set_immutable_hash(program_id: &Pubkey, admin: &UpgradeAdmin, contract: &Pubkey, expires_at: i64) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(&ctx, admin.authority_nonce, SET_IMMUTABLE_TAG.as_bytes(), expires_at),
+        HashingMode::Eip712 => eip712_hash(
+            &ctx,
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_IMMUTABLE_TYPE).as_ref(),
+                contract.as_ref(),
+                &encode_u128(admin.authority_nonce),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for Pause (`paused` is true) or Unpause instruction.
+pub fn set_paused_hash(program_id: &Pubkey, admin: &UpgradeAdmin, paused: bool, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            if paused { PAUSE_TAG.as_bytes() } else { UNPAUSE_TAG.as_bytes() },
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_PAUSED_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                &encode_u64(paused as u64),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for SetManagedContracts instruction.
+pub fn set_managed_contracts_hash(program_id: &Pubkey, admin: &UpgradeAdmin, contracts: &[Pubkey], expires_at: i64) -> Result<Hash, ProgramError> {
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[SET_MANAGED_CONTRACTS_TAG.as_bytes(), contracts.try_to_vec()?.as_slice()].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => {
+            // `bytes32[]` is encoded as keccak_hash of concatenated elements
+            let contracts: Vec<&[u8]> = contracts.iter().map(|contract| contract.as_ref()).collect();
+            Ok(eip712_hash(
+                &MessageContext::new(program_id, admin),
+                &[
+                    type_hash(admin.nonce_width, EIP712_SET_MANAGED_CONTRACTS_TYPE).as_ref(),
+                    admin.contract.as_ref(),
+                    &encode_u128(admin.key_nonce),
+                    hashv(&contracts).as_ref(),
+                    &encode_i64(expires_at),
+                ],
+            ))
+        }
+    }
+}
+
+/// Hash to sign for SetMetadata instruction.
+pub fn set_metadata_hash(program_id: &Pubkey, admin: &UpgradeAdmin, name: &[u8; 32], uri: &str, expires_at: i64) -> Result<Hash, ProgramError> {
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[SET_METADATA_TAG.as_bytes(), name.as_ref(), uri.try_to_vec()?.as_slice()].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => Ok(eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_METADATA_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                name.as_ref(),
+                hash(uri.as_bytes()).as_ref(),
+                &encode_i64(expires_at),
+            ],
+        )),
+    }
+}
+
+/// Hash to sign for Reactivate (`active` is true) or Deactivate instruction.
+pub fn set_active_hash(program_id: &Pubkey, admin: &UpgradeAdmin, active: bool, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            if active { REACTIVATE_TAG.as_bytes() } else { DEACTIVATE_TAG.as_bytes() },
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_ACTIVE_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                &encode_u64(active as u64),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for ExtendExpiry instruction. In EIP-712 mode removed expiry is encoded as zero slot.
+pub fn extend_expiry_hash(program_id: &Pubkey, admin: &UpgradeAdmin, expires_at_slot: Option<u64>, expires_at: i64) -> Result<Hash, ProgramError> {
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[EXTEND_EXPIRY_TAG.as_bytes(), expires_at_slot.try_to_vec()?.as_slice()].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => Ok(eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_EXTEND_EXPIRY_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                &encode_u64(expires_at_slot.unwrap_or_default()),
+                &encode_i64(expires_at),
+            ],
+        )),
+    }
+}
+
+/// Hash to sign for SetExecutors instruction.
+pub fn set_executors_hash(program_id: &Pubkey, admin: &UpgradeAdmin, executors: &[Pubkey], reimbursement: u64, expires_at: i64) -> Result<Hash, ProgramError> {
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[SET_EXECUTORS_TAG.as_bytes(), executors.try_to_vec()?.as_slice(), reimbursement.to_be_bytes().as_ref()].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => {
+            let executors: Vec<&[u8]> = executors.iter().map(|executor| executor.as_ref()).collect();
+            Ok(eip712_hash(
+                &MessageContext::new(program_id, admin),
+                &[
+                    type_hash(admin.nonce_width, EIP712_SET_EXECUTORS_TYPE).as_ref(),
+                    admin.contract.as_ref(),
+                    &encode_u128(admin.key_nonce),
+                    hashv(&executors).as_ref(),
+                    &encode_u64(reimbursement),
+                    &encode_i64(expires_at),
+                ],
+            ))
+        }
+    }
+}
+
+/// Hash to sign for SetRoles instruction.
+pub fn set_roles_hash(program_id: &Pubkey, admin: &UpgradeAdmin, roles: &[RoleAssignment], approval_threshold: u8, expires_at: i64) -> Result<Hash, ProgramError> {
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[SET_ROLES_TAG.as_bytes(), roles.try_to_vec()?.as_slice(), &[approval_threshold]].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => Ok(eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_ROLES_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                hash(&roles.try_to_vec()?).as_ref(),
+                &encode_u64(approval_threshold as u64),
+                &encode_i64(expires_at),
+            ],
+        )),
+    }
+}
+
+/// Hash to sign for ApproveUpgrade instruction of the upgrade of `contract` proposed at `proposal_slot`.
+/// Proposal slot takes the place of the nonce, so approvals of a replaced proposal are not valid.
+pub fn approve_upgrade_hash(
+    program_id: &Pubkey,
+    admin: &UpgradeAdmin,
+    contract: &Pubkey,
+    buffer: &Pubkey,
+    code_hash: &[u8; 32],
+    proposal_slot: u64,
+    expires_at: i64,
+) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &ctx,
+            proposal_slot.into(),
+            &[APPROVE_UPGRADE_TAG.as_bytes(), buffer.as_ref(), code_hash.as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &ctx,
+            &[
+                hash(EIP712_APPROVE_UPGRADE_TYPE.as_bytes()).as_ref(),
+                contract.as_ref(),
+                &encode_u64(proposal_slot),
+                buffer.as_ref(),
+                code_hash.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for SetVetoKey instruction.
+pub fn set_veto_key_hash(program_id: &Pubkey, admin: &UpgradeAdmin, veto_key: &Option<RoleKey>, expires_at: i64) -> Result<Hash, ProgramError> {
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[SET_VETO_KEY_TAG.as_bytes(), veto_key.try_to_vec()?.as_slice()].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => Ok(eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_VETO_KEY_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                hash(&veto_key.try_to_vec()?).as_ref(),
+                &encode_i64(expires_at),
+            ],
+        )),
+    }
+}
+
+/// Hash to sign for VetoUpgrade instruction of the upgrade of `contract` proposed at `proposal_slot`.
+#[allow(clippy::too_many_arguments)]
+pub fn veto_upgrade_hash(
+    program_id: &Pubkey,
+    admin: &UpgradeAdmin,
+    contract: &Pubkey,
+    buffer: &Pubkey,
+    code_hash: &[u8; 32],
+    proposal_slot: u64,
+    destination: &Pubkey,
+    expires_at: i64,
+) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &ctx,
+            proposal_slot.into(),
+            &[VETO_UPGRADE_TAG.as_bytes(), buffer.as_ref(), code_hash.as_ref(), destination.as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &ctx,
+            &[
+                hash(EIP712_VETO_UPGRADE_TYPE.as_bytes()).as_ref(),
+                contract.as_ref(),
+                &encode_u64(proposal_slot),
+                buffer.as_ref(),
+                code_hash.as_ref(),
+                destination.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for SetHealthcheck instruction of `contract` managed by the admin.
+pub fn set_healthcheck_hash(program_id: &Pubkey, admin: &UpgradeAdmin, contract: &Pubkey, data: &Option<Vec<u8>>, expires_at: i64) -> Result<Hash, ProgramError> {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    match admin.hashing_mode {
+        HashingMode::Legacy => Ok(legacy_hash(
+            &ctx,
+            admin.key_nonce,
+            &[SET_HEALTHCHECK_TAG.as_bytes(), data.try_to_vec()?.as_slice()].concat(),
+            expires_at,
+        )),
+        HashingMode::Eip712 => Ok(eip712_hash(
+            &ctx,
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_HEALTHCHECK_TYPE).as_ref(),
+                contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                hash(&data.try_to_vec()?).as_ref(),
+                &encode_i64(expires_at),
+            ],
+        )),
+    }
+}
+
+/// Hash to sign for SetVerifiedBuildHash instruction of `contract` managed by the admin.
+/// Removal of the verified build is signed with the zero `build_hash`.
+pub fn set_verified_build_hash_hash(program_id: &Pubkey, admin: &UpgradeAdmin, contract: &Pubkey, build_hash: &Option<[u8; 32]>, required: bool, expires_at: i64) -> Hash {
+    let ctx = MessageContext { contract, ..MessageContext::new(program_id, admin) };
+    let build_hash = build_hash.unwrap_or_default();
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &ctx,
+            admin.key_nonce,
+            &[SET_VERIFIED_BUILD_HASH_TAG.as_bytes(), build_hash.as_ref(), &[required as u8]].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &ctx,
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_VERIFIED_BUILD_HASH_TYPE).as_ref(),
+                contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                build_hash.as_ref(),
+                &encode_u64(required as u64),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for SetUpgradeDelay instruction.
+pub fn set_upgrade_delay_hash(program_id: &Pubkey, admin: &UpgradeAdmin, min_delay_slots: u64, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[SET_UPGRADE_DELAY_TAG.as_bytes(), min_delay_slots.to_be_bytes().as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_UPGRADE_DELAY_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                &encode_u64(min_delay_slots),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for SetUpgradeInterval instruction.
+pub fn set_upgrade_interval_hash(program_id: &Pubkey, admin: &UpgradeAdmin, min_upgrade_interval_slots: u64, expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[SET_UPGRADE_INTERVAL_TAG.as_bytes(), min_upgrade_interval_slots.to_be_bytes().as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_SET_UPGRADE_INTERVAL_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                &encode_u64(min_upgrade_interval_slots),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for RegisterSnapshot instruction of `program` managed by the admin.
+pub fn register_snapshot_hash(program_id: &Pubkey, admin: &UpgradeAdmin, program: &Pubkey, buffer: &Pubkey, code_hash: &[u8; 32], expires_at: i64) -> Hash {
+    match admin.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            &MessageContext::new(program_id, admin),
+            admin.key_nonce,
+            &[REGISTER_SNAPSHOT_TAG.as_bytes(), program.as_ref(), buffer.as_ref(), code_hash.as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            &MessageContext::new(program_id, admin),
+            &[
+                type_hash(admin.nonce_width, EIP712_REGISTER_SNAPSHOT_TYPE).as_ref(),
+                admin.contract.as_ref(),
+                &encode_u128(admin.key_nonce),
+                program.as_ref(),
+                buffer.as_ref(),
+                code_hash.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// EIP-712 domain separator. Solana has no EIP-155 chain id, so admin domain tag is used as `chainId`.
+/// Verifying program id is passed as domain salt because it does not fit into 20 byte `verifyingContract` address.
+pub fn eip712_domain_separator(program_id: &Pubkey, domain: &[u8; 32]) -> Hash {
+    hashv(&[
+        hash(EIP712_DOMAIN_TYPE.as_bytes()).as_ref(),
+        hash(HASH_CONSTANT.as_bytes()).as_ref(),
+        hash(EIP712_DOMAIN_VERSION.as_bytes()).as_ref(),
+        domain.as_ref(),
+        program_id.as_ref(),
+    ])
+}
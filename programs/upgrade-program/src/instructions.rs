@@ -1,51 +1,650 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
-use solana_program::secp256k1_recover::{SECP256K1_PUBLIC_KEY_LENGTH, SECP256K1_SIGNATURE_LENGTH};
 use solana_program::instruction::{Instruction, AccountMeta};
-use crate::PDA_ADMIN_SEED;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use shank::ShankInstruction;
+use crate::pda;
+use crate::ecdsa::EcdsaSignature;
+use crate::hashing::{HashAlgorithm, HashingMode, NonceWidth};
+use crate::merkle::SignerProof;
+use crate::state::{AdminKey, Curve, RoleAssignment, RoleKey};
+use crate::error::UpgradeError;
+
+/// Version of the instruction data format, stored in the first byte of every instruction.
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Index of the Buffer account in ProposeUpgrade and Upgrade, checked by SetBufferAuthority.
+pub const REGISTERED_BUFFER_INDEX: usize = 3;
+
+/// Instruction data can not be larger than the transaction packet.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 1232;
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct InitializeAdminArgs {
-    // ECDSA public key (64 byte format)
-    pub public_key: [u8; SECP256K1_PUBLIC_KEY_LENGTH],
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of signers
+    pub public_keys: Vec<AdminKey>,
+    // Voting weights of signers, empty means weight 1 for all signers
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub weights: Vec<u8>,
+    // Minimal cumulative weight of signers required to authorize operation
+    pub threshold: u8,
     // Contract to manage
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
     pub contract: Pubkey,
+    // Format of the messages that should be signed
+    pub hashing_mode: HashingMode,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub domain: [u8; 32],
+    // Merkle root of additional eligible signers
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::option_hex"))]
+    pub signers_root: Option<[u8; 32]>,
+    // Operational hot key that authorizes Upgrade instead of signer set
+    pub upgrade_key: Option<AdminKey>,
+    // Number of slots between ChangePublicKey and activation of the new signer set
+    pub key_change_delay: u64,
+    // Curve of ECDSA signer keys
+    pub curve: Curve,
+    // Digest algorithm of legacy messages, SHA-256 is supported only in legacy hashing mode
+    pub hash_algorithm: HashAlgorithm,
+    // secp256k1 recovery key that can replace signers after `recovery_delay`
+    pub guardian: Option<AdminKey>,
+    // Number of slots between StartRecovery and CompleteRecovery, at least MIN_RECOVERY_DELAY if guardian is set
+    pub recovery_delay: u64,
+    // Contracts upgraded by the same signers besides `contract`
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_display"))]
+    pub managed_contracts: Vec<Pubkey>,
+    // Slot after which the admin authority lapses unless extended, None for no expiry
+    pub expires_at_slot: Option<u64>,
+    // Accounts allowed to submit Upgrade, empty allows anyone
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_display"))]
+    pub executors: Vec<Pubkey>,
+    // Lamports paid to the allowed executor after successful Upgrade
+    pub reimbursement: u64,
+    // Slots between ProposeUpgrade and ExecuteUpgrade
+    pub min_delay_slots: u64,
+    // Slots between consecutive upgrades
+    pub min_upgrade_interval_slots: u64,
+    // Width of nonces in signed messages, U128 for long-lived admins
+    pub nonce_width: NonceWidth,
 }
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ChangePublicKeyArgs {
-    // New ECDSA public key (64 byte format)
-    pub new_public_key: [u8; SECP256K1_PUBLIC_KEY_LENGTH],
-    // Signature of keccak_hash(nonce, "solana-upgrade-program".bytes, new_public_key) by old public key
-    pub signature: [u8; SECP256K1_SIGNATURE_LENGTH],
-    // Signature recovery id
-    pub recovery_id: u8,
+    // New ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of signers
+    pub new_public_keys: Vec<AdminKey>,
+    // New voting weights of signers
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub new_weights: Vec<u8>,
+    // New threshold
+    pub new_threshold: u8,
+    // New merkle root of additional eligible signers
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::option_hex"))]
+    pub new_signers_root: Option<[u8; 32]>,
+    // New operational hot key
+    pub new_upgrade_key: Option<AdminKey>,
+    // Key change nonce the signatures were made for
+    pub nonce: u128,
+    // Signatures of keccak_hash(key_nonce, "solana-upgrade-program".bytes, new_threshold, new_public_keys.borsh_bytes, new_weights.borsh_bytes, new_signers_root.borsh_bytes, new_upgrade_key.borsh_bytes) by old signers
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
 }
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ChangeAuthorityArgs {
-    // Signature of keccak_hash(nonce, "solana-upgrade-program".bytes, new_authority)
-    pub signature: [u8; SECP256K1_SIGNATURE_LENGTH],
-    // Signature recovery id
-    pub recovery_id: u8,
+    // Contract to transfer: UpgradeAdmin primary contract or one of its managed contracts
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    // Authority change nonce the signatures were made for
+    pub nonce: u128,
+    // Signatures of keccak_hash(contract, authority_nonce, "solana-upgrade-program".bytes, program_id, domain, "change-authority".bytes, new_authority, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
 }
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct UpgradeArgs {
-    // Signature for keccak_hash(target_contract, nonce, "solana-upgrade-program".bytes, buffer_address)
-    pub signature: [u8; SECP256K1_PUBLIC_KEY_LENGTH],
-    // Corresponding seed to use in PDA for admin account
-    pub recovery_id: u8,
+    // Upgrade nonce the signatures were made for
+    pub nonce: u128,
+    // SHA-256 of the buffer program data
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    // Signatures for keccak_hash(upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, buffer_address, code_hash, spill, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CancelKeyChangeArgs {
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "cancel-key-change".bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RevokeKeysArgs {
+    // Keys that can never be installed again
+    pub keys: Vec<AdminKey>,
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "revoke-keys".bytes, keys.borsh_bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VerifyBatchArgs {
+    // Hashes of operations to approve, e.g. Upgrade hashes of several UpgradeAdmin accounts
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_hex"))]
+    pub operation_hashes: Vec<[u8; 32]>,
+    // Signatures of keccak_hash(target_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "verify-batch".bytes, operations_root, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures and approvals are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CreateSessionArgs {
+    // Key that signs delegated operations
+    pub session_key: AdminKey,
+    // Bitmask of SESSION_PERMISSION_* operations the key can sign
+    pub permissions: u8,
+    // Last slot when the key can be used
+    pub expiry_slot: u64,
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "create-session".bytes, session_key.borsh_bytes, permissions, expiry_slot, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StartRecoveryArgs {
+    // Signer set that replaces the lost one
+    pub new_public_keys: Vec<AdminKey>,
+    // Voting weights of new signers
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub new_weights: Vec<u8>,
+    // New threshold
+    pub new_threshold: u8,
+    // Key change nonce the signature was made for
+    pub nonce: u128,
+    // Guardian signature of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "start-recovery".bytes, new_threshold, new_public_keys.borsh_bytes, new_weights.borsh_bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Unix timestamp after which signature is no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CancelRecoveryArgs {
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "cancel-recovery".bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CloseAdminArgs {
+    // Authority change nonce the signatures were made for
+    pub nonce: u128,
+    // Signatures of keccak_hash(target_contract, authority_nonce, "solana-upgrade-program".bytes, program_id, domain, "close-admin".bytes, destination, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PauseArgs {
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "pause".bytes or "unpause".bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetManagedContractsArgs {
+    // New list of contracts managed besides the primary one
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_display"))]
+    pub contracts: Vec<Pubkey>,
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-managed-contracts".bytes, contracts.borsh_bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetMetadataArgs {
+    // Human-readable label, zero bytes to clear
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub name: [u8; 32],
+    // Off-chain metadata URI, empty to clear
+    pub uri: String,
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-metadata".bytes, name, uri.borsh_bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DeactivateArgs {
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "deactivate".bytes or "reactivate".bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExtendExpiryArgs {
+    // New expiry slot later than the current one, None to remove expiry
+    pub expires_at_slot: Option<u64>,
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "extend-expiry".bytes, expires_at_slot.borsh_bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetExecutorsArgs {
+    // Accounts allowed to submit Upgrade, empty allows anyone
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_display"))]
+    pub executors: Vec<Pubkey>,
+    // Lamports paid to the allowed executor after successful Upgrade
+    pub reimbursement: u64,
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-executors".bytes, executors.borsh_bytes, reimbursement, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetUpgradeDelayArgs {
+    // Slots between ProposeUpgrade and ExecuteUpgrade
+    pub min_delay_slots: u64,
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-upgrade-delay".bytes, min_delay_slots, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetUpgradeIntervalArgs {
+    // Slots between consecutive upgrades
+    pub min_upgrade_interval_slots: u64,
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-upgrade-interval".bytes, min_upgrade_interval_slots, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ProposeUpgradeArgs {
+    // Upgrade nonce the signatures were made for
+    pub nonce: u128,
+    // SHA-256 of the buffer program data
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    // First slot ExecuteUpgrade is allowed at, None for no lower bound
+    pub not_before_slot: Option<u64>,
+    // Last slot ExecuteUpgrade is allowed at, None for no upper bound
+    pub not_after_slot: Option<u64>,
+    // Signatures for keccak_hash(upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "propose-upgrade".bytes, buffer_address, code_hash, not_before_slot.borsh_bytes, not_after_slot.borsh_bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CancelUpgradeArgs {
+    // Upgrade nonce the signatures were made for
+    pub nonce: u128,
+    // Close the proposed buffer and send its lamports to the destination
+    pub close_buffer: bool,
+    // Signatures for keccak_hash(upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "cancel-upgrade".bytes, buffer_address, close_buffer, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RegisterSnapshotArgs {
+    // SHA-256 of the buffer program data
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    // Signatures of keccak_hash(target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "register-snapshot".bytes, program, buffer_address, code_hash, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RollbackArgs {
+    // Upgrade nonce the signature was made for
+    pub nonce: u128,
+    // Signature for keccak_hash(upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "rollback".bytes, buffer_address, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Unix timestamp after which the signature is no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BatchUpgradeArgs {
+    // Upgrade nonce the signatures were made for
+    pub nonce: u128,
+    // SHA-256 of the program data of every buffer, in the order of account groups
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_hex"))]
+    pub code_hashes: Vec<[u8; 32]>,
+    // Signatures for keccak_hash(admin_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "batch-upgrade".bytes, spill, [contract, buffer_address, code_hash]..., expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetImmutableArgs {
+    // Contract to make immutable: UpgradeAdmin primary contract or one of its managed contracts
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    // Authority change nonce the signatures were made for
+    pub nonce: u128,
+    // Signatures of keccak_hash(contract, authority_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-immutable".bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DeployArgs {
+    // Maximum length of the program data, limits future upgrades
+    pub max_data_len: u64,
+    // Key change nonce the signatures were made for
+    pub nonce: u128,
+    // Signatures of keccak_hash(admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "deploy".bytes, program, max_data_len, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExtendProgramArgs {
+    // Number of bytes to add to the ProgramData account
+    pub additional_bytes: u32,
+    // Upgrade nonce the signatures were made for
+    pub nonce: u128,
+    // Signatures of keccak_hash(contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "extend-program".bytes, additional_bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CreateBufferArgs {
+    // Solana signer allowed to write and reset the chunks
+    pub writer: Pubkey,
+    // Length of the program data to write
+    pub data_len: u64,
+    // SHA-256 of the program data to write
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    // Upgrade nonce the signatures were made for
+    pub nonce: u128,
+    // Signatures of keccak_hash(contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "create-buffer".bytes, buffer_address, writer, data_len, code_hash, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WriteBufferChunkArgs {
+    // Offset of the chunk in program data, equal to the length written so far
+    pub offset: u32,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub bytes: Vec<u8>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CloseBufferArgs {
+    // Key change nonce the signatures were made for
+    pub nonce: u128,
+    // Signatures of keccak_hash(admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "close-buffer".bytes, buffer_address, recipient, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetRolesArgs {
+    // Keys of proposers, approvers and executors, empty to stop separating roles
+    pub roles: Vec<RoleAssignment>,
+    // Distinct approvers required by ExecuteUpgrade, zero if roles are empty
+    pub approval_threshold: u8,
+    // Signatures of keccak_hash(admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-roles".bytes, roles.borsh_bytes, approval_threshold, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ApproveUpgradeArgs {
+    // Signature of keccak_hash(contract, proposal_slot, "solana-upgrade-program".bytes, program_id, domain, "approve-upgrade".bytes, buffer_address, code_hash, expires_at)
+    // by ECDSA approver, empty for approver that signs the transaction
+    pub signatures: Vec<EcdsaSignature>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetVetoKeyArgs {
+    // Security council key, None to remove veto
+    pub veto_key: Option<RoleKey>,
+    // Signatures of keccak_hash(admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-veto-key".bytes, veto_key.borsh_bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VetoUpgradeArgs {
+    // Signature of keccak_hash(contract, proposal_slot, "solana-upgrade-program".bytes, program_id, domain, "veto-upgrade".bytes, buffer_address, code_hash, destination, expires_at)
+    // by ECDSA veto key, empty for veto key that signs the transaction
+    pub signatures: Vec<EcdsaSignature>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetHealthcheckArgs {
+    // UpgradeAdmin primary contract or one of its managed contracts
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    // Instruction data of the healthcheck, None to remove it
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::option_hex"))]
+    pub data: Option<Vec<u8>>,
+    // Signatures of keccak_hash(contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-healthcheck".bytes, data.borsh_bytes, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SetVerifiedBuildHashArgs {
+    // UpgradeAdmin primary contract or one of its managed contracts
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    // SHA-256 of the program produced by the verified build, None to remove it
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::option_hex"))]
+    pub build_hash: Option<[u8; 32]>,
+    // Reject upgrades of the contract to any other code
+    pub required: bool,
+    // Signatures of keccak_hash(contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-verified-build-hash".bytes, build_hash or zero bytes, required, expires_at)
+    pub signatures: Vec<EcdsaSignature>,
+    // Membership proofs for signers from merkle tree
+    pub signer_proofs: Vec<SignerProof>,
+    // Unix timestamp after which signatures are no longer valid
+    pub expires_at: i64,
 }
 
 #[repr(C)]
-#[derive(BorshSerialize, BorshDeserialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, ShankInstruction)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(tag = "name", content = "args"))]
 pub enum UpgradeInstruction {
     /// Initialize new UpgradeAdmin that will be an authority for target upgradable program.
+    /// Admin is a set of signers with threshold: every operation should be signed by at least `threshold` of them.
+    /// Every stored signer can carry a voting weight, then `threshold` is a quorum: cumulative weight of signers
+    /// required to authorize operation. Without weights every signer has weight 1.
+    /// Signer can be identified by 64 byte or 33 byte compressed ECDSA public key, by 20 byte Ethereum address
+    /// or by ed25519 public key.
+    /// Besides the stored signers, UpgradeAdmin can commit a merkle root of eligible signers: such signers
+    /// provide merkle membership proof together with the signature in every operation.
+    /// Optional operational hot `upgrade_key` authorizes Upgrade alone, while ChangePublicKey and ChangeAuthority
+    /// always require the signer set.
+    /// Ed25519 signers sign the same Keccak Hash in `ed25519_program` instruction preceding the current one.
+    /// ECDSA signers can either pass signatures in instruction args or verify them with `secp256k1_program`
+    /// instruction preceding the current one, with the hashed message as signed data.
+    /// Depending on `hashing_mode` signers sign either legacy Keccak Hash described below or EIP-712 typed data hash.
+    /// ECDSA signers can also sign EIP-191 `personal_sign` message of that hash to use standard Ethereum wallets.
+    /// With `Secp256r1` curve ECDSA signers are compressed P-256 keys (e.g. HSMs or passkeys) that sign the hash
+    /// in `secp256r1_program` instruction preceding the current one.
+    /// Legacy hash is Keccak-256 or SHA-256 depending on `hash_algorithm`. SHA-256 digests can not be verified
+    /// by `secp256k1_program` because it always hashes the message with Keccak-256.
+    /// Optional secp256k1 `guardian` key can replace the signer set with StartRecovery if signer keys are lost.
+    /// Besides the primary `contract` that seeds the admin address, the admin can manage up to MAX_MANAGED_CONTRACTS
+    /// other contracts with the same signers and nonces. Their upgrade authority should be set to the admin address.
+    /// With `expires_at_slot` set, every operation except ExtendExpiry is rejected after that slot.
+    /// With `U128` nonce width the nonces are not limited by u64::MAX, legacy hashes take them as 16 big-endian bytes
+    /// and EIP-712 types declare them as `uint128 nonce`.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -53,148 +652,2703 @@ pub enum UpgradeInstruction {
     ///   1. `[writable,signer]` The fee payer
     ///   2. `[]` System program
     ///   3. `[]` Rent sysvar
+    ///   4. `[writable]` The AdminHistory account to initialize
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account to initialize")]
+    #[account(1, writable, signer, name="payer", desc="The fee payer")]
+    #[account(2, name="system_program", desc="System program")]
+    #[account(3, name="rent", desc="Rent sysvar")]
+    #[account(4, writable, name="admin_history", desc="The AdminHistory account to initialize")]
     InitializeAdmin(InitializeAdminArgs),
 
-    /// Change pubkey in UpgradeAdmin. The Keccak Hash of `[target_contract, nonce, "solana-upgrade-program".bytes, new_public_key]`
-    /// should be signed by old public key to perform that operation.
+    /// Stage signer set and hot key change in UpgradeAdmin. New signers can be activated by ActivateKey
+    /// after `key_change_delay` slots, until then the change can be canceled by CancelKeyChange. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, new_threshold, new_public_keys.borsh_bytes, new_weights.borsh_bytes, new_signers_root.borsh_bytes, new_upgrade_key.borsh_bytes, expires_at]`
+    /// should be signed by old signers with cumulative weight of at least `threshold` to perform that operation.
+    /// New keys should not be present in the RevokedKeys list. The change is recorded in AdminHistory.
     ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` The RevokedKeys account (may be not created yet)
+    ///   2. `[writable]` The AdminHistory account
+    ///   3. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, name="revoked_keys", desc="The RevokedKeys account (may be not created yet)")]
+    #[account(2, writable, name="admin_history", desc="The AdminHistory account")]
+    #[account(3, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
     ChangePublicKey(ChangePublicKeyArgs),
 
-    /// Change contract upgrade authority. The Keccak Hash of `[target_contract, nonce, "solana-upgrade-program".bytes, new_authority]`
-    /// should be signed by stored public key to perform that operation.
+    /// Change upgrade authority of the primary or managed contract. The Keccak Hash of `[contract, authority_nonce, "solana-upgrade-program".bytes, program_id, domain, "change-authority".bytes, new_authority, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation. The transfer is recorded in AdminHistory.
     ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[writable]` The UpgradeAdmin account
-    ///   1. `[writable]` The ProgramData account.
+    ///   1. `[writable]` The ProgramData account of the contract
     ///   2. `[]` The new authority account
     ///   3. `[]` BPFLoaderUpgradable program
+    ///   4. `[writable]` The AdminHistory account
+    ///   5. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="program_data", desc="The ProgramData account of the contract")]
+    #[account(2, name="new_authority", desc="The new authority account")]
+    #[account(3, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(4, writable, name="admin_history", desc="The AdminHistory account")]
+    #[account(5, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
     ChangeAuthority(ChangeAuthorityArgs),
 
-    /// Upgrade contract. The Keccak Hash of `[target_contract, nonce, "solana-upgrade-program".bytes, buffer_address]`
-    /// should be signed by stored public key to perform that operation.
+    /// Upgrade the primary or managed contract. The Keccak Hash of `[upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, buffer_address, code_hash, spill, expires_at]`
+    /// should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+    /// Instead of signatures the hash can be approved in advance by VerifyBatch, then BatchApproval account is passed.
+    /// Fails if `min_upgrade_interval_slots` of UpgradeAdmin have not passed since the last upgrade, or if UpgradeAdmin
+    /// has upgrade delay, roles or veto key, then the upgrade should go through ProposeUpgrade and ExecuteUpgrade.
+    /// If UpgradeAdmin has executors allowlist, one of the executors should sign the transaction. The executor receives
+    /// `reimbursement` lamports from the admin account if the admin stays rent exempt after the payment.
+    /// Buffer staged by CreateBuffer should be finalized, its code hash is taken from BufferStaging instead of hashing
+    /// the buffer again.
     ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[writable]` The UpgradeAdmin account
     ///   1. `[writable]` The ProgramData account.
-    ///   2. `[writable]` The Program account corresponding to stores address in UpgradeAdmin.
+    ///   2. `[writable]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
     ///   3. `[writable]` The Buffer account where the program data has been
     ///      written.  The buffer account's authority must match the program's
-    ///      authority
+    ///      authority. SHA-256 of the program data should be equal to `code_hash`
     ///   4. `[writable]` The spill account.
     ///   5. `[]` Rent sysvar.
     ///   6. `[]` Clock sysvar.
     ///   7. `[]` BPFLoaderUpgradable program
+    ///   8. `[writable]` The UpgradeHistory account of the upgraded contract
+    ///   9. `[writable]` The BufferStaging account of the buffer, closed to the spill account if the buffer is staged
+    ///  10. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    ///  11. `[writable]` The BatchApproval account (optional, consumed instead of signatures)
+    ///  12. `[]` The SessionKey account (optional, session key signs instead of UpgradeAdmin signers)
+    ///  13. `[writable,signer]` The executor (optional, required if UpgradeAdmin has executors allowlist)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="program_data", desc="The ProgramData account")]
+    #[account(2, writable, name="program", desc="The Program account of UpgradeAdmin primary contract or one of its managed contracts")]
+    #[account(3, writable, name="buffer", desc="The Buffer account where the program data has been written. The buffer account's authority must match the program's authority. SHA-256 of the program data should be equal to `code_hash`")]
+    #[account(4, writable, name="spill", desc="The spill account")]
+    #[account(5, name="rent", desc="Rent sysvar")]
+    #[account(6, name="clock", desc="Clock sysvar")]
+    #[account(7, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(8, writable, name="upgrade_history", desc="The UpgradeHistory account of the upgraded contract")]
+    #[account(9, writable, name="buffer_staging", desc="The BufferStaging account of the buffer, closed to the spill account if the buffer is staged")]
+    #[account(10, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    #[account(11, writable, optional, name="batch_approval", desc="The BatchApproval account (optional, consumed instead of signatures)")]
+    #[account(12, optional, name="session_key", desc="The SessionKey account (optional, session key signs instead of UpgradeAdmin signers)")]
+    #[account(13, writable, signer, optional, name="executor", desc="The executor (optional, required if UpgradeAdmin has executors allowlist)")]
     Upgrade(UpgradeArgs),
-}
 
-pub fn initialize_admin(
-    program_id: Pubkey,
-    contract: Pubkey,
-    fee_payer: Pubkey,
-    public_key: [u8; SECP256K1_PUBLIC_KEY_LENGTH],
-) -> Instruction {
-    let (admin, _) = Pubkey::find_program_address(&[PDA_ADMIN_SEED.as_bytes(), contract.as_ref()], &program_id);
-    Instruction{
-        program_id,
-        data: UpgradeInstruction::InitializeAdmin(
-            InitializeAdminArgs {
-                public_key,
-                contract: Default::default(),
-            }
-        ).try_to_vec().unwrap(),
-        accounts: vec![
-            AccountMeta::new(admin, false),
-            AccountMeta::new(fee_payer, true),
-            AccountMeta::new_readonly(solana_program::system_program::id(), false),
-            AccountMeta::new_readonly(solana_program::sysvar::clock::id(), false),
-        ],
-    }
-}
+    /// Activate signer set change staged by ChangePublicKey after its activation slot.
+    /// New keys should not be present in the RevokedKeys list.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` The RevokedKeys account (may be not created yet)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, name="revoked_keys", desc="The RevokedKeys account (may be not created yet)")]
+    ActivateKey,
 
+    /// Cancel signer set change staged by ChangePublicKey. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "cancel-key-change".bytes, expires_at]`
+    /// should be signed by at least `threshold` of current signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    CancelKeyChange(CancelKeyChangeArgs),
 
-pub fn change_public_key(
-    program_id: Pubkey,
-    contract: Pubkey,
-    new_public_key: [u8; SECP256K1_PUBLIC_KEY_LENGTH],
-    signature: [u8; SECP256K1_SIGNATURE_LENGTH],
-    recovery_id: u8,
-) -> Instruction {
-    let (admin, _) = Pubkey::find_program_address(&[PDA_ADMIN_SEED.as_bytes(), contract.as_ref()], &program_id);
-    Instruction{
-        program_id,
-        data: UpgradeInstruction::ChangePublicKey(
-            ChangePublicKeyArgs {
-                new_public_key,
-                signature,
-                recovery_id,
-            }
-        ).try_to_vec().unwrap(),
-        accounts: vec![
-            AccountMeta::new(admin, false),
-        ],
-    }
-}
+    /// Append keys to the RevokedKeys list of UpgradeAdmin, so they can never be installed by ChangePublicKey again.
+    /// The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "revoke-keys".bytes, keys.borsh_bytes, expires_at]`
+    /// should be signed by at least `threshold` of current signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The RevokedKeys account, created if it does not exist
+    ///   2. `[writable,signer]` The fee payer
+    ///   3. `[]` System program
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="revoked_keys", desc="The RevokedKeys account, created if it does not exist")]
+    #[account(2, writable, signer, name="payer", desc="The fee payer")]
+    #[account(3, name="system_program", desc="System program")]
+    #[account(4, name="rent", desc="Rent sysvar")]
+    #[account(5, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    RevokeKeys(RevokeKeysArgs),
 
-pub fn change_authority(
-    program_id: Pubkey,
-    contract: Pubkey,
-    new_authority: Pubkey,
-    signature: [u8; SECP256K1_SIGNATURE_LENGTH],
-    recovery_id: u8,
-) -> Instruction {
-    let (admin, _) = Pubkey::find_program_address(&[PDA_ADMIN_SEED.as_bytes(), contract.as_ref()], &program_id);
-    let (program_data, _) = Pubkey::find_program_address(&[contract.as_ref()], &solana_program::bpf_loader_upgradeable::id());
+    /// Approve several operations with one signature. The Keccak Hash of `[target_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "verify-batch".bytes, operations_root, expires_at]`
+    /// should be signed by the same signers as Upgrade (hot `upgrade_key` if it is set). `operations_root` is the merkle root
+    /// of `operation_hashes`. Approved hashes are stored in the BatchApproval account replacing the previous batch and can be
+    /// consumed by Upgrade of any UpgradeAdmin with the same upgrade authority. Upgrade hashes of this UpgradeAdmin itself
+    /// should be computed with the upgrade nonce incremented by VerifyBatch.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The BatchApproval account, created if it does not exist
+    ///   2. `[writable,signer]` The fee payer
+    ///   3. `[]` System program
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="batch_approval", desc="The BatchApproval account, created if it does not exist")]
+    #[account(2, writable, signer, name="payer", desc="The fee payer")]
+    #[account(3, name="system_program", desc="System program")]
+    #[account(4, name="rent", desc="Rent sysvar")]
+    #[account(5, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    VerifyBatch(VerifyBatchArgs),
 
-    Instruction{
-        program_id,
-        data: UpgradeInstruction::ChangeAuthority(
-            ChangeAuthorityArgs {
-                signature,
-                recovery_id,
-            }
-        ).try_to_vec().unwrap(),
-        accounts: vec![
-            AccountMeta::new(admin, false),
-            AccountMeta::new(program_data, false),
-            AccountMeta::new(new_authority, false),
-            AccountMeta::new(solana_program::bpf_loader_upgradeable::id(), false),
-        ],
-    }
-}
+    /// Delegate short-lived session key that can sign operations allowed by `permissions` bitmask until `expiry_slot`.
+    /// Only Upgrade can be delegated. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "create-session".bytes, session_key.borsh_bytes, permissions, expiry_slot, expires_at]`
+    /// should be signed by at least `threshold` of stored signers. Previous session key of UpgradeAdmin is replaced.
+    /// Session key should not be present in the RevokedKeys list.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` The RevokedKeys account (may be not created yet)
+    ///   2. `[writable]` The SessionKey account, created if it does not exist
+    ///   3. `[writable,signer]` The fee payer
+    ///   4. `[]` System program
+    ///   5. `[]` Rent sysvar
+    ///   6. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, name="revoked_keys", desc="The RevokedKeys account (may be not created yet)")]
+    #[account(2, writable, name="session_key", desc="The SessionKey account, created if it does not exist")]
+    #[account(3, writable, signer, name="payer", desc="The fee payer")]
+    #[account(4, name="system_program", desc="System program")]
+    #[account(5, name="rent", desc="Rent sysvar")]
+    #[account(6, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    CreateSession(CreateSessionArgs),
 
-pub fn upgrade(
-    program_id: Pubkey,
-    contract: Pubkey,
-    buffer: Pubkey,
-    spill: Pubkey,
-    signature: [u8; SECP256K1_SIGNATURE_LENGTH],
-    recovery_id: u8,
-) -> Instruction {
-    let (admin, _) = Pubkey::find_program_address(&[PDA_ADMIN_SEED.as_bytes(), contract.as_ref()], &program_id);
-    let (program_data, _) = Pubkey::find_program_address(&[contract.as_ref()], &solana_program::bpf_loader_upgradeable::id());
+    /// Start replacement of the signer set by the guardian. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "start-recovery".bytes, new_threshold, new_public_keys.borsh_bytes, new_weights.borsh_bytes, expires_at]`
+    /// should be signed by the guardian secp256k1 key. Recovery can be completed by CompleteRecovery after `recovery_delay` slots,
+    /// until then it can be canceled by current signers with CancelRecovery. New keys should not be present in the RevokedKeys list.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` The RevokedKeys account (may be not created yet)
+    ///   2. `[]` Instructions sysvar (optional, required for secp256k1_program signature)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, name="revoked_keys", desc="The RevokedKeys account (may be not created yet)")]
+    #[account(2, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signature)")]
+    StartRecovery(StartRecoveryArgs),
 
-    Instruction {
-        program_id,
-        data: UpgradeInstruction::Upgrade(
-            UpgradeArgs {
-                signature,
-                recovery_id,
-            }
-        ).try_to_vec().unwrap(),
-        accounts: vec![
-            AccountMeta::new(admin, false),
-            AccountMeta::new(program_data, false),
-            AccountMeta::new(contract, false),
-            AccountMeta::new(buffer, false),
-            AccountMeta::new(spill, false),
-            AccountMeta::new(solana_program::sysvar::rent::id(), false),
-            AccountMeta::new(solana_program::sysvar::clock::id(), false),
-            AccountMeta::new(solana_program::bpf_loader_upgradeable::id(), false),
-        ],
-    }
-}
\ No newline at end of file
+    /// Complete recovery started by StartRecovery after its activation slot. Signer set is replaced, merkle signers root,
+    /// hot upgrade key and pending key change are dropped because they could be controlled by the lost keys.
+    /// New keys should not be present in the RevokedKeys list.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` The RevokedKeys account (may be not created yet)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, name="revoked_keys", desc="The RevokedKeys account (may be not created yet)")]
+    CompleteRecovery,
+
+    /// Cancel recovery started by StartRecovery. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "cancel-recovery".bytes, expires_at]`
+    /// should be signed by at least `threshold` of current signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    CancelRecovery(CancelRecoveryArgs),
+
+    /// Close UpgradeAdmin that is no longer the upgrade authority of the contract and send its lamports to the destination.
+    /// Managed contracts should be removed by SetManagedContracts first.
+    /// The Keccak Hash of `[target_contract, authority_nonce, "solana-upgrade-program".bytes, program_id, domain, "close-admin".bytes, destination, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` The ProgramData account
+    ///   2. `[writable]` The destination account for reclaimed lamports
+    ///   3. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, name="program_data", desc="The ProgramData account")]
+    #[account(2, writable, name="destination", desc="The destination account for reclaimed lamports")]
+    #[account(3, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    CloseAdmin(CloseAdminArgs),
+
+    /// Rewrite UpgradeAdmin account of V1 layout to the current one. The account is reallocated and rent difference is paid
+    /// by the fee payer. V1 public key becomes the only signer with threshold 1 and all operation nonces continue from
+    /// V1 nonce. Migration does not change who controls the admin, so it requires no signatures.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable,signer]` The fee payer
+    ///   2. `[]` System program
+    ///   3. `[]` Rent sysvar
+    ///   4. `[writable]` The AdminHistory account to initialize
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, signer, name="payer", desc="The fee payer")]
+    #[account(2, name="system_program", desc="System program")]
+    #[account(3, name="rent", desc="Rent sysvar")]
+    #[account(4, writable, name="admin_history", desc="The AdminHistory account to initialize")]
+    MigrateAdmin,
+
+    /// Freeze Upgrade, ChangeAuthority, VerifyBatch, CreateSession and CloseAdmin during an incident without rotating keys.
+    /// Signer set management and recovery stay available. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "pause".bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    Pause(PauseArgs),
+
+    /// Lift the freeze set by Pause. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "unpause".bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    Unpause(PauseArgs),
+
+    /// Replace the list of contracts managed besides the primary one. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-managed-contracts".bytes, contracts.borsh_bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    /// UpgradeAdmin should not be the upgrade authority of removed contracts, their ProgramData accounts are passed to check that.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    ///   2. `[]` ProgramData accounts of removed contracts
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    #[account(2, name="program_data", desc="ProgramData accounts of removed contracts")]
+    SetManagedContracts(SetManagedContractsArgs),
+
+    /// Set human-readable name and off-chain URI of UpgradeAdmin for explorers and dashboards. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-metadata".bytes, name, uri.borsh_bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    SetMetadata(SetMetadataArgs),
+
+    /// Temporarily retire UpgradeAdmin: every operation except Reactivate is rejected, nonces and other state are kept.
+    /// The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "deactivate".bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    Deactivate(DeactivateArgs),
+
+    /// Bring deactivated UpgradeAdmin back. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "reactivate".bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    Reactivate(DeactivateArgs),
+
+    /// Move expiry slot of UpgradeAdmin later or remove it. Allowed after the admin expired, so signers can renew lapsed authority.
+    /// The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "extend-expiry".bytes, expires_at_slot.borsh_bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    ExtendExpiry(ExtendExpiryArgs),
+
+    /// Replace executors allowlist and reimbursement of Upgrade. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-executors".bytes, executors.borsh_bytes, reimbursement, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    SetExecutors(SetExecutorsArgs),
+
+    /// Record upgrade of the primary or managed contract in PendingUpgrade account, so the buffer can be inspected
+    /// before ExecuteUpgrade. The buffer authority should already be UpgradeAdmin, so the buffer can not be changed
+    /// after `code_hash` is checked. Buffer staged by CreateBuffer should be finalized. The Keccak Hash of `[upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "propose-upgrade".bytes, buffer_address, code_hash, not_before_slot.borsh_bytes, not_after_slot.borsh_bytes, expires_at]`
+    /// should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+    /// If UpgradeAdmin has roles, the hash should be signed by a proposer key instead or a proposer should sign the transaction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The PendingUpgrade account
+    ///   2. `[]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
+    ///   3. `[]` The Buffer account where the program data has been written.
+    ///   4. `[writable,signer]` Fee payer
+    ///   5. `[]` System program
+    ///   6. `[]` Rent sysvar
+    ///   7. `[]` The BufferStaging account of the buffer
+    ///   8. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    ///   9. `[signer]` The proposer (optional, required for proposer bound to Solana signer)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="pending_upgrade", desc="The PendingUpgrade account")]
+    #[account(2, name="program", desc="The Program account of UpgradeAdmin primary contract or one of its managed contracts")]
+    #[account(3, name="buffer", desc="The Buffer account where the program data has been written")]
+    #[account(4, writable, signer, name="payer", desc="Fee payer")]
+    #[account(5, name="system_program", desc="System program")]
+    #[account(6, name="rent", desc="Rent sysvar")]
+    #[account(7, name="buffer_staging", desc="The BufferStaging account of the buffer")]
+    #[account(8, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    #[account(9, signer, optional, name="proposer", desc="The proposer (optional, required for proposer bound to Solana signer)")]
+    ProposeUpgrade(ProposeUpgradeArgs),
+
+    /// Upgrade the contract to the buffer recorded by ProposeUpgrade after `min_delay_slots` of UpgradeAdmin have passed
+    /// since the proposal and `min_upgrade_interval_slots` since the last upgrade, within the proposed slot window.
+    /// The buffer contents should still match the proposed code hash. PendingUpgrade account is closed and its lamports are sent to the spill account.
+    /// If UpgradeAdmin has roles, the upgrade should have `approval_threshold` approvals and an executor should sign the transaction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The ProgramData account.
+    ///   2. `[writable]` The Program account.
+    ///   3. `[writable]` The Buffer account recorded in PendingUpgrade.
+    ///   4. `[writable]` The spill account.
+    ///   5. `[]` Rent sysvar.
+    ///   6. `[]` Clock sysvar.
+    ///   7. `[]` BPFLoaderUpgradable program
+    ///   8. `[writable]` The PendingUpgrade account
+    ///   9. `[writable]` The UpgradeHistory account of the upgraded contract
+    ///  10. `[writable]` The BufferStaging account of the buffer, closed to the spill account if the buffer is staged
+    ///  11. `[writable,signer]` The executor (optional, required if UpgradeAdmin has executors allowlist or roles)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="program_data", desc="The ProgramData account")]
+    #[account(2, writable, name="program", desc="The Program account")]
+    #[account(3, writable, name="buffer", desc="The Buffer account recorded in PendingUpgrade")]
+    #[account(4, writable, name="spill", desc="The spill account")]
+    #[account(5, name="rent", desc="Rent sysvar")]
+    #[account(6, name="clock", desc="Clock sysvar")]
+    #[account(7, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(8, writable, name="pending_upgrade", desc="The PendingUpgrade account")]
+    #[account(9, writable, name="upgrade_history", desc="The UpgradeHistory account of the upgraded contract")]
+    #[account(10, writable, name="buffer_staging", desc="The BufferStaging account of the buffer, closed to the spill account if the buffer is staged")]
+    #[account(11, writable, signer, optional, name="executor", desc="The executor (optional, required if UpgradeAdmin has executors allowlist or roles)")]
+    ExecuteUpgrade,
+
+    /// Change the delay between ProposeUpgrade and ExecuteUpgrade. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-upgrade-delay".bytes, min_delay_slots, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    SetUpgradeDelay(SetUpgradeDelayArgs),
+
+    /// Close PendingUpgrade account of the proposal and optionally the proposed buffer. Allowed while the admin is paused.
+    /// The Keccak Hash of `[upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "cancel-upgrade".bytes, buffer_address, close_buffer, expires_at]`
+    /// should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The PendingUpgrade account
+    ///   2. `[]` The Program account of the proposal.
+    ///   3. `[writable]` The Buffer account recorded in PendingUpgrade.
+    ///   4. `[writable]` Destination of PendingUpgrade and buffer lamports
+    ///   5. `[]` BPFLoaderUpgradable program
+    ///   6. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="pending_upgrade", desc="The PendingUpgrade account")]
+    #[account(2, name="program", desc="The Program account of the proposal")]
+    #[account(3, writable, name="buffer", desc="The Buffer account recorded in PendingUpgrade")]
+    #[account(4, writable, name="destination", desc="Destination of PendingUpgrade and buffer lamports")]
+    #[account(5, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(6, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    CancelUpgrade(CancelUpgradeArgs),
+
+    /// Hand the buffer over to UpgradeAdmin with the checked loader instruction signed by UpgradeAdmin.
+    /// The next instruction of the transaction should be ProposeUpgrade or Upgrade of the same buffer, so the buffer
+    /// is never left with UpgradeAdmin authority but without a registered upgrade.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The UpgradeAdmin account
+    ///   1. `[writable]` The Buffer account
+    ///   2. `[signer]` Current buffer authority
+    ///   3. `[]` BPFLoaderUpgradable program
+    ///   4. `[]` Instructions sysvar
+    #[account(0, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="buffer", desc="The Buffer account")]
+    #[account(2, signer, name="buffer_authority", desc="Current buffer authority")]
+    #[account(3, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(4, name="instructions_sysvar", desc="Instructions sysvar")]
+    SetBufferAuthority,
+
+    /// Create UpgradeHistory account of the primary or managed contract. Upgrade and ExecuteUpgrade of the contract
+    /// append a record to it and fail until it is created. Can be called by anyone.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The UpgradeAdmin account
+    ///   1. `[writable]` The UpgradeHistory account
+    ///   2. `[]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
+    ///   3. `[writable,signer]` Fee payer
+    ///   4. `[]` System program
+    ///   5. `[]` Rent sysvar
+    #[account(0, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="upgrade_history", desc="The UpgradeHistory account")]
+    #[account(2, name="program", desc="The Program account of UpgradeAdmin primary contract or one of its managed contracts")]
+    #[account(3, writable, signer, name="payer", desc="Fee payer")]
+    #[account(4, name="system_program", desc="System program")]
+    #[account(5, name="rent", desc="Rent sysvar")]
+    InitUpgradeHistory,
+
+    /// Register the buffer with known good program data of the primary or managed contract, so Rollback can restore it.
+    /// The buffer authority should be UpgradeAdmin. Replaces the previous snapshot of the contract.
+    /// The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "register-snapshot".bytes, program, buffer_address, code_hash, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The RollbackSnapshot account
+    ///   2. `[]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
+    ///   3. `[]` The Buffer account with known good program data.
+    ///   4. `[writable,signer]` Fee payer
+    ///   5. `[]` System program
+    ///   6. `[]` Rent sysvar
+    ///   7. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="rollback_snapshot", desc="The RollbackSnapshot account")]
+    #[account(2, name="program", desc="The Program account of UpgradeAdmin primary contract or one of its managed contracts")]
+    #[account(3, name="buffer", desc="The Buffer account with known good program data")]
+    #[account(4, writable, signer, name="payer", desc="Fee payer")]
+    #[account(5, name="system_program", desc="System program")]
+    #[account(6, name="rent", desc="Rent sysvar")]
+    #[account(7, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    RegisterSnapshot(RegisterSnapshotArgs),
+
+    /// Upgrade the contract back to the registered snapshot buffer. The buffer contents should still match the
+    /// snapshot code hash. The Keccak Hash of `[upgraded_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "rollback".bytes, buffer_address, expires_at]`
+    /// should be signed by a single stored signer or the hot upgrade key. A single signature is enough, since RegisterSnapshot
+    /// requires `threshold` of stored signers for the snapshot. The snapshot is closed, as the loader consumes the buffer.
+    /// Fails if UpgradeAdmin has upgrade delay, roles or veto key, is paused, has an unverified upgrade of another contract
+    /// or if `min_upgrade_interval_slots` have not passed since the last upgrade.
+    /// If UpgradeAdmin has executors allowlist, one of the executors should sign the transaction and receives `reimbursement`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The ProgramData account.
+    ///   2. `[writable]` The Program account.
+    ///   3. `[writable]` The Buffer account recorded in RollbackSnapshot.
+    ///   4. `[writable]` The spill account.
+    ///   5. `[]` Rent sysvar.
+    ///   6. `[]` Clock sysvar.
+    ///   7. `[]` BPFLoaderUpgradable program
+    ///   8. `[writable]` The UpgradeHistory account of the upgraded contract
+    ///   9. `[writable]` The RollbackSnapshot account
+    ///  10. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    ///  11. `[writable,signer]` The executor (optional, required if UpgradeAdmin has executors allowlist)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="program_data", desc="The ProgramData account")]
+    #[account(2, writable, name="program", desc="The Program account")]
+    #[account(3, writable, name="buffer", desc="The Buffer account recorded in RollbackSnapshot")]
+    #[account(4, writable, name="spill", desc="The spill account")]
+    #[account(5, name="rent", desc="Rent sysvar")]
+    #[account(6, name="clock", desc="Clock sysvar")]
+    #[account(7, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(8, writable, name="upgrade_history", desc="The UpgradeHistory account of the upgraded contract")]
+    #[account(9, writable, name="rollback_snapshot", desc="The RollbackSnapshot account")]
+    #[account(10, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    #[account(11, writable, signer, optional, name="executor", desc="The executor (optional, required if UpgradeAdmin has executors allowlist)")]
+    Rollback(RollbackArgs),
+
+    /// Change the minimal interval between consecutive upgrades. The Keccak Hash of `[target_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-upgrade-interval".bytes, min_upgrade_interval_slots, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    SetUpgradeInterval(SetUpgradeIntervalArgs),
+
+    /// Upgrade several contracts managed by UpgradeAdmin with one authorization. The transaction fails as a whole
+    /// if any upgrade fails. The Keccak Hash of `[admin_contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "batch-upgrade".bytes, spill, [contract, buffer_address, code_hash]..., expires_at]`
+    /// should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+    /// Fails if UpgradeAdmin has upgrade delay, roles or veto key. Buffers staged by CreateBuffer should be finalized.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The spill account.
+    ///   2. `[]` Rent sysvar.
+    ///   3. `[]` Clock sysvar.
+    ///   4. `[]` BPFLoaderUpgradable program
+    ///   5. `[writable]` The ProgramData account of the i-th upgrade, at index 5 + 5 * i.
+    ///   6. `[writable]` The Program account of the i-th upgrade, at index 6 + 5 * i.
+    ///   7. `[writable]` The Buffer account of the i-th upgrade, at index 7 + 5 * i.
+    ///   8. `[writable]` The UpgradeHistory account of the i-th upgrade, at index 8 + 5 * i.
+    ///   9. `[writable]` The BufferStaging account of the i-th buffer, at index 9 + 5 * i.
+    ///   10. `[]` Instructions sysvar after the last group (optional, required for ed25519 and secp256k1_program signers)
+    ///   11. `[writable,signer]` The executor after the last group (optional, required if UpgradeAdmin has executors allowlist)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="spill", desc="The spill account")]
+    #[account(2, name="rent", desc="Rent sysvar")]
+    #[account(3, name="clock", desc="Clock sysvar")]
+    #[account(4, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(5, writable, name="program_data", desc="The ProgramData account of the i-th upgrade, at index 5 + 5 * i")]
+    #[account(6, writable, name="program", desc="The Program account of the i-th upgrade, at index 6 + 5 * i")]
+    #[account(7, writable, name="buffer", desc="The Buffer account of the i-th upgrade, at index 7 + 5 * i")]
+    #[account(8, writable, name="upgrade_history", desc="The UpgradeHistory account of the i-th upgrade, at index 8 + 5 * i")]
+    #[account(9, writable, name="buffer_staging", desc="The BufferStaging account of the i-th buffer, at index 9 + 5 * i")]
+    #[account(10, optional, name="instructions_sysvar", desc="Instructions sysvar after the last group (optional, required for ed25519 and secp256k1_program signers)")]
+    #[account(11, writable, signer, optional, name="executor", desc="The executor after the last group (optional, required if UpgradeAdmin has executors allowlist)")]
+    BatchUpgrade(BatchUpgradeArgs),
+
+    /// Remove upgrade authority of the primary or managed contract, making it immutable forever.
+    /// The Keccak Hash of `[contract, authority_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-immutable".bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation. A managed contract is removed
+    /// from managed contracts. For the primary contract managed contracts should be removed first, and UpgradeAdmin is
+    /// deactivated and can not be reactivated. The removal is recorded in AdminHistory.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The ProgramData account of the contract
+    ///   2. `[]` BPFLoaderUpgradable program
+    ///   3. `[writable]` The AdminHistory account
+    ///   4. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="program_data", desc="The ProgramData account of the contract")]
+    #[account(2, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(3, writable, name="admin_history", desc="The AdminHistory account")]
+    #[account(4, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    SetImmutable(SetImmutableArgs),
+
+    /// Deploy a new program from the buffer with UpgradeAdmin as its upgrade authority. The program account should be
+    /// created for BPFLoaderUpgradable by the program keypair earlier in the same transaction. A program other than
+    /// the primary contract is added to managed contracts.
+    /// The Keccak Hash of `[admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "deploy".bytes, program, max_data_len, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable,signer]` The fee payer for the ProgramData account
+    ///   2. `[writable]` The ProgramData account
+    ///   3. `[writable]` The Program account
+    ///   4. `[writable]` The Buffer account with UpgradeAdmin authority
+    ///   5. `[]` Rent sysvar.
+    ///   6. `[]` Clock sysvar.
+    ///   7. `[]` System program
+    ///   8. `[]` BPFLoaderUpgradable program
+    ///   9. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, signer, name="payer", desc="The fee payer for the ProgramData account")]
+    #[account(2, writable, name="program_data", desc="The ProgramData account")]
+    #[account(3, writable, name="program", desc="The Program account")]
+    #[account(4, writable, name="buffer", desc="The Buffer account with UpgradeAdmin authority")]
+    #[account(5, name="rent", desc="Rent sysvar")]
+    #[account(6, name="clock", desc="Clock sysvar")]
+    #[account(7, name="system_program", desc="System program")]
+    #[account(8, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(9, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    Deploy(DeployArgs),
+
+    /// Grow ProgramData of the primary or managed contract before upgrade to a larger build, the fee payer funds the rent.
+    /// The Keccak Hash of `[contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "extend-program".bytes, additional_bytes, expires_at]`
+    /// should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+    /// The extension consumes upgrade nonce, so the following Upgrade should be signed for the next nonce.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The ProgramData account
+    ///   2. `[writable]` The Program account
+    ///   3. `[]` System program
+    ///   4. `[writable,signer]` The fee payer
+    ///   5. `[]` BPFLoaderUpgradable program
+    ///   6. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="program_data", desc="The ProgramData account")]
+    #[account(2, writable, name="program", desc="The Program account")]
+    #[account(3, name="system_program", desc="System program")]
+    #[account(4, writable, signer, name="payer", desc="The fee payer")]
+    #[account(5, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(6, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    ExtendProgram(ExtendProgramArgs),
+
+    /// Initialize the buffer with UpgradeAdmin as its authority and stage writing of the program data committed by signers.
+    /// The buffer account should be created for BPFLoaderUpgradable earlier in the same transaction.
+    /// The Keccak Hash of `[contract, upgrade_nonce, "solana-upgrade-program".bytes, program_id, domain, "create-buffer".bytes, buffer_address, writer, data_len, code_hash, expires_at]`
+    /// should be signed by the hot upgrade key if it is set, otherwise by at least `threshold` of stored signers.
+    /// The following Upgrade should be signed for the next nonce.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` The Program account of UpgradeAdmin primary contract or one of its managed contracts.
+    ///   2. `[writable]` The Buffer account
+    ///   3. `[writable]` The BufferStaging account
+    ///   4. `[writable,signer]` Fee payer
+    ///   5. `[]` System program
+    ///   6. `[]` Rent sysvar
+    ///   7. `[]` BPFLoaderUpgradable program
+    ///   8. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, name="program", desc="The Program account of UpgradeAdmin primary contract or one of its managed contracts")]
+    #[account(2, writable, name="buffer", desc="The Buffer account")]
+    #[account(3, writable, name="buffer_staging", desc="The BufferStaging account")]
+    #[account(4, writable, signer, name="payer", desc="Fee payer")]
+    #[account(5, name="system_program", desc="System program")]
+    #[account(6, name="rent", desc="Rent sysvar")]
+    #[account(7, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(8, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    CreateBuffer(CreateBufferArgs),
+
+    /// Write the next chunk of the staged buffer. Chunks are written by the writer committed in CreateBuffer, so
+    /// other accounts can not append data that would fail FinalizeBuffer.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The UpgradeAdmin account
+    ///   1. `[writable]` The Buffer account
+    ///   2. `[writable]` The BufferStaging account
+    ///   3. `[]` BPFLoaderUpgradable program
+    ///   4. `[signer]` The writer
+    #[account(0, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="buffer", desc="The Buffer account")]
+    #[account(2, writable, name="buffer_staging", desc="The BufferStaging account")]
+    #[account(3, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(4, signer, name="writer", desc="The writer")]
+    WriteBufferChunk(WriteBufferChunkArgs),
+
+    /// Check that the whole program data is written and its SHA-256 equals the signed commitment.
+    /// Finalized buffer can be used by Upgrade with the same code hash.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The UpgradeAdmin account
+    ///   1. `[writable]` The BufferStaging account
+    #[account(0, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="buffer_staging", desc="The BufferStaging account")]
+    FinalizeBuffer,
+
+    /// Close the buffer with UpgradeAdmin authority and send its lamports to the recipient.
+    /// The Keccak Hash of `[admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "close-buffer".bytes, buffer_address, recipient, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[writable]` The Buffer account
+    ///   2. `[writable]` The recipient account for reclaimed lamports
+    ///   3. `[]` BPFLoaderUpgradable program
+    ///   4. `[writable]` The BufferStaging account of the buffer, closed to the recipient if the buffer is staged
+    ///   5. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="buffer", desc="The Buffer account")]
+    #[account(2, writable, name="recipient", desc="The recipient account for reclaimed lamports")]
+    #[account(3, name="bpf_loader_upgradeable", desc="BPFLoaderUpgradable program")]
+    #[account(4, writable, name="buffer_staging", desc="The BufferStaging account of the buffer, closed to the recipient if the buffer is staged")]
+    #[account(5, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    CloseBuffer(CloseBufferArgs),
+
+    /// Separate proposers, approvers and executors of ProposeUpgrade, ApproveUpgrade and ExecuteUpgrade. Executors should
+    /// be Solana signers, since ExecuteUpgrade carries no signatures. While roles are set, Upgrade, BatchUpgrade and Rollback are rejected.
+    /// The Keccak Hash of `[admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-roles".bytes, roles.borsh_bytes, approval_threshold, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    SetRoles(SetRolesArgs),
+
+    /// Approve the upgrade recorded by ProposeUpgrade. The Keccak Hash of `[contract, proposal_slot, "solana-upgrade-program".bytes, program_id, domain, "approve-upgrade".bytes, buffer_address, code_hash, expires_at]`
+    /// should be signed by an approver key or an approver should sign the transaction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The UpgradeAdmin account
+    ///   1. `[writable]` The PendingUpgrade account
+    ///   2. `[]` The Program account of the proposed upgrade
+    ///   3. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    ///   4. `[signer]` The approver (optional, required for approver bound to Solana signer)
+    #[account(0, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="pending_upgrade", desc="The PendingUpgrade account")]
+    #[account(2, name="program", desc="The Program account of the proposed upgrade")]
+    #[account(3, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    #[account(4, signer, optional, name="approver", desc="The approver (optional, required for approver bound to Solana signer)")]
+    ApproveUpgrade(ApproveUpgradeArgs),
+
+    /// Set or remove the security council key that can veto pending upgrades. The key should not be a signer of UpgradeAdmin.
+    /// While the key is set, Upgrade, BatchUpgrade and Rollback are rejected, so every upgrade can be vetoed.
+    /// The Keccak Hash of `[admin_contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-veto-key".bytes, veto_key.borsh_bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    SetVetoKey(SetVetoKeyArgs),
+
+    /// Close PendingUpgrade account of the proposal before it is executed. Allowed while the admin is paused.
+    /// The Keccak Hash of `[contract, proposal_slot, "solana-upgrade-program".bytes, program_id, domain, "veto-upgrade".bytes, buffer_address, code_hash, destination, expires_at]`
+    /// should be signed by the veto key or the veto key should sign the transaction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The UpgradeAdmin account
+    ///   1. `[writable]` The PendingUpgrade account
+    ///   2. `[]` The Program account of the proposal
+    ///   3. `[writable]` Destination of PendingUpgrade lamports
+    ///   4. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    ///   5. `[signer]` The veto key (optional, required for veto key bound to Solana signer)
+    #[account(0, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="pending_upgrade", desc="The PendingUpgrade account")]
+    #[account(2, name="program", desc="The Program account of the proposal")]
+    #[account(3, writable, name="destination", desc="Destination of PendingUpgrade lamports")]
+    #[account(4, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    #[account(5, signer, optional, name="veto_key", desc="The veto key (optional, required for veto key bound to Solana signer)")]
+    VetoUpgrade(VetoUpgradeArgs),
+
+    /// Set or remove the healthcheck instruction of the primary or managed contract.
+    /// The Keccak Hash of `[contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-healthcheck".bytes, data.borsh_bytes, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    SetHealthcheck(SetHealthcheckArgs),
+
+    /// Invoke the healthcheck of the contract upgraded by Upgrade, BatchUpgrade or ExecuteUpgrade. Upgraded program can be
+    /// invoked only after the upgrade slot, so the check runs in a separate transaction and every following upgrade is rejected
+    /// until it passes. Rollback of the unverified contract is allowed. BatchUpgrade may include one contract with healthcheck.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` The upgraded Program account
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, name="program", desc="The upgraded Program account")]
+    RunHealthcheck,
+
+    /// Bind the verified build attestation of the primary or managed contract to the admin account.
+    /// If the build is required, Upgrade, BatchUpgrade, ProposeUpgrade and ExecuteUpgrade accept only a buffer with its hash.
+    /// The Keccak Hash of `[contract, key_nonce, "solana-upgrade-program".bytes, program_id, domain, "set-verified-build-hash".bytes, build_hash or zero bytes, required, expires_at]`
+    /// should be signed by at least `threshold` of stored signers to perform that operation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The UpgradeAdmin account
+    ///   1. `[]` Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)
+    #[account(0, writable, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for ed25519 and secp256k1_program signers)")]
+    SetVerifiedBuildHash(SetVerifiedBuildHashArgs),
+
+    /// Discard the chunks of the staged buffer that is not finalized, so the writer can write it again from offset 0.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The UpgradeAdmin account
+    ///   1. `[writable]` The BufferStaging account
+    ///   2. `[signer]` The writer
+    #[account(0, name="upgrade_admin", desc="The UpgradeAdmin account")]
+    #[account(1, writable, name="buffer_staging", desc="The BufferStaging account")]
+    #[account(2, signer, name="writer", desc="The writer")]
+    ResetBuffer,
+}
+
+impl UpgradeInstruction {
+    /// Encodes the instruction prefixed with INSTRUCTION_VERSION.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![INSTRUCTION_VERSION];
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Decodes instruction data produced by `pack`. Data of another format version is rejected instead of being
+    /// misparsed, and trailing bytes are not allowed.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < 2 || input.len() > MAX_INSTRUCTION_DATA_LEN {
+            msg!("Invalid instruction data length: {}", input.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if input[0] != INSTRUCTION_VERSION {
+            msg!("Instruction version {} is not supported, expected {}", input[0], INSTRUCTION_VERSION);
+            return Err(UpgradeError::UnsupportedInstructionVersion.into());
+        }
+
+        Ok(Self::try_from_slice(&input[1..])?)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_admin(
+    program_id: Pubkey,
+    contract: Pubkey,
+    fee_payer: Pubkey,
+    public_keys: Vec<AdminKey>,
+    weights: Vec<u8>,
+    threshold: u8,
+    hashing_mode: HashingMode,
+    domain: [u8; 32],
+    signers_root: Option<[u8; 32]>,
+    upgrade_key: Option<AdminKey>,
+    key_change_delay: u64,
+    curve: Curve,
+    hash_algorithm: HashAlgorithm,
+    guardian: Option<AdminKey>,
+    recovery_delay: u64,
+    managed_contracts: Vec<Pubkey>,
+    expires_at_slot: Option<u64>,
+    executors: Vec<Pubkey>,
+    reimbursement: u64,
+    min_delay_slots: u64,
+    min_upgrade_interval_slots: u64,
+    nonce_width: NonceWidth,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (admin_history, _) = pda::find_admin_history_address(&program_id, &admin);
+    Instruction{
+        program_id,
+        data: UpgradeInstruction::InitializeAdmin(
+            InitializeAdminArgs {
+                public_keys,
+                weights,
+                threshold,
+                contract,
+                hashing_mode,
+                domain,
+                signers_root,
+                upgrade_key,
+                key_change_delay,
+                curve,
+                hash_algorithm,
+                guardian,
+                recovery_delay,
+                managed_contracts,
+                expires_at_slot,
+                executors,
+                reimbursement,
+                min_delay_slots,
+                min_upgrade_interval_slots,
+                nonce_width,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new(admin_history, false),
+        ],
+    }
+}
+
+
+#[allow(clippy::too_many_arguments)]
+pub fn change_public_key(
+    program_id: Pubkey,
+    contract: Pubkey,
+    new_public_keys: Vec<AdminKey>,
+    new_weights: Vec<u8>,
+    new_threshold: u8,
+    new_signers_root: Option<[u8; 32]>,
+    new_upgrade_key: Option<AdminKey>,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (revoked_keys, _) = pda::find_revoked_keys_address(&program_id, &admin);
+    let (admin_history, _) = pda::find_admin_history_address(&program_id, &admin);
+    Instruction{
+        program_id,
+        data: UpgradeInstruction::ChangePublicKey(
+            ChangePublicKeyArgs {
+                new_public_keys,
+                new_weights,
+                new_threshold,
+                new_signers_root,
+                new_upgrade_key,
+                nonce,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(revoked_keys, false),
+            AccountMeta::new(admin_history, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn change_authority(
+    program_id: Pubkey,
+    contract: Pubkey,
+    new_authority: Pubkey,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    change_managed_authority(program_id, contract, contract, new_authority, nonce, signatures, signer_proofs, expires_at)
+}
+
+/// Change upgrade authority of `contract` managed by UpgradeAdmin of `admin_contract`.
+#[allow(clippy::too_many_arguments)]
+pub fn change_managed_authority(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    new_authority: Pubkey,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (program_data, _) = pda::find_program_data_address(&contract);
+    let (admin_history, _) = pda::find_admin_history_address(&program_id, &admin);
+
+    Instruction{
+        program_id,
+        data: UpgradeInstruction::ChangeAuthority(
+            ChangeAuthorityArgs {
+                contract,
+                nonce,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(program_data, false),
+            AccountMeta::new_readonly(new_authority, false),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new(admin_history, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn upgrade(
+    program_id: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    spill: Pubkey,
+    nonce: u128,
+    code_hash: [u8; 32],
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    upgrade_managed(program_id, contract, contract, buffer, spill, nonce, code_hash, signatures, signer_proofs, expires_at)
+}
+
+/// Upgrade `contract` managed by UpgradeAdmin of `admin_contract`.
+#[allow(clippy::too_many_arguments)]
+pub fn upgrade_managed(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    spill: Pubkey,
+    nonce: u128,
+    code_hash: [u8; 32],
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (program_data, _) = pda::find_program_data_address(&contract);
+    let (upgrade_history, _) = pda::find_upgrade_history_address(&program_id, &admin, &contract);
+    let (buffer_staging, _) = pda::find_buffer_staging_address(&program_id, &admin, &buffer);
+
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::Upgrade(
+            UpgradeArgs {
+                nonce,
+                code_hash,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(program_data, false),
+            AccountMeta::new(contract, false),
+            AccountMeta::new(buffer, false),
+            AccountMeta::new(spill, false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::id(), false),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new(upgrade_history, false),
+            AccountMeta::new(buffer_staging, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn activate_key(
+    program_id: Pubkey,
+    contract: Pubkey,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (revoked_keys, _) = pda::find_revoked_keys_address(&program_id, &admin);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::ActivateKey.pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(revoked_keys, false),
+        ],
+    }
+}
+
+pub fn cancel_key_change(
+    program_id: Pubkey,
+    contract: Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::CancelKeyChange(
+            CancelKeyChangeArgs {
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn revoke_keys(
+    program_id: Pubkey,
+    contract: Pubkey,
+    fee_payer: Pubkey,
+    keys: Vec<AdminKey>,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (revoked_keys, _) = pda::find_revoked_keys_address(&program_id, &admin);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::RevokeKeys(
+            RevokeKeysArgs {
+                keys,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(revoked_keys, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn verify_batch(
+    program_id: Pubkey,
+    contract: Pubkey,
+    fee_payer: Pubkey,
+    operation_hashes: Vec<[u8; 32]>,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (batch_approval, _) = pda::find_batch_approval_address(&program_id, &admin);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::VerifyBatch(
+            VerifyBatchArgs {
+                operation_hashes,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(batch_approval, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Upgrade a contract using the approval stored by VerifyBatch of `batch_contract` UpgradeAdmin instead of signatures.
+#[allow(clippy::too_many_arguments)]
+pub fn upgrade_from_batch(
+    program_id: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    spill: Pubkey,
+    batch_contract: Pubkey,
+    nonce: u128,
+    code_hash: [u8; 32],
+    expires_at: i64,
+) -> Instruction {
+    let (batch_admin, _) = pda::find_admin_address(&program_id, &batch_contract);
+    let (batch_approval, _) = pda::find_batch_approval_address(&program_id, &batch_admin);
+
+    let mut instruction = upgrade(program_id, contract, buffer, spill, nonce, code_hash, vec![], vec![], expires_at);
+    instruction.accounts.push(AccountMeta::new(batch_approval, false));
+    instruction
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_session(
+    program_id: Pubkey,
+    contract: Pubkey,
+    fee_payer: Pubkey,
+    session_key: AdminKey,
+    permissions: u8,
+    expiry_slot: u64,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (revoked_keys, _) = pda::find_revoked_keys_address(&program_id, &admin);
+    let (session, _) = pda::find_session_key_address(&program_id, &admin);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::CreateSession(
+            CreateSessionArgs {
+                session_key,
+                permissions,
+                expiry_slot,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(revoked_keys, false),
+            AccountMeta::new(session, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Upgrade a contract with signatures of the session key delegated by CreateSession.
+#[allow(clippy::too_many_arguments)]
+pub fn upgrade_with_session(
+    program_id: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    spill: Pubkey,
+    nonce: u128,
+    code_hash: [u8; 32],
+    signatures: Vec<EcdsaSignature>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (session, _) = pda::find_session_key_address(&program_id, &admin);
+
+    let mut instruction = upgrade(program_id, contract, buffer, spill, nonce, code_hash, signatures, vec![], expires_at);
+    instruction.accounts.push(AccountMeta::new_readonly(session, false));
+    instruction
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn start_recovery(
+    program_id: Pubkey,
+    contract: Pubkey,
+    new_public_keys: Vec<AdminKey>,
+    new_weights: Vec<u8>,
+    new_threshold: u8,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (revoked_keys, _) = pda::find_revoked_keys_address(&program_id, &admin);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::StartRecovery(
+            StartRecoveryArgs {
+                new_public_keys,
+                new_weights,
+                new_threshold,
+                nonce,
+                signatures,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(revoked_keys, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn complete_recovery(
+    program_id: Pubkey,
+    contract: Pubkey,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (revoked_keys, _) = pda::find_revoked_keys_address(&program_id, &admin);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::CompleteRecovery.pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(revoked_keys, false),
+        ],
+    }
+}
+
+pub fn cancel_recovery(
+    program_id: Pubkey,
+    contract: Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::CancelRecovery(
+            CancelRecoveryArgs {
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn close_admin(
+    program_id: Pubkey,
+    contract: Pubkey,
+    destination: Pubkey,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (program_data, _) = pda::find_program_data_address(&contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::CloseAdmin(
+            CloseAdminArgs {
+                nonce,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(program_data, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn migrate_admin(
+    program_id: Pubkey,
+    contract: Pubkey,
+    fee_payer: Pubkey,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let (admin_history, _) = pda::find_admin_history_address(&program_id, &admin);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::MigrateAdmin.pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new(admin_history, false),
+        ],
+    }
+}
+
+pub fn pause(
+    program_id: Pubkey,
+    contract: Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::Pause(
+            PauseArgs {
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn unpause(
+    program_id: Pubkey,
+    contract: Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::Unpause(
+            PauseArgs {
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Replace managed contracts of UpgradeAdmin. `removed_contracts` are the contracts dropped from the current list.
+#[allow(clippy::too_many_arguments)]
+pub fn set_managed_contracts(
+    program_id: Pubkey,
+    contract: Pubkey,
+    contracts: Vec<Pubkey>,
+    removed_contracts: &[Pubkey],
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    let mut accounts = vec![
+        AccountMeta::new(admin, false),
+        AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+    ];
+
+    for removed in removed_contracts {
+        let (program_data, _) = pda::find_program_data_address(removed);
+        accounts.push(AccountMeta::new_readonly(program_data, false));
+    }
+
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetManagedContracts(
+            SetManagedContractsArgs {
+                contracts,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn set_metadata(
+    program_id: Pubkey,
+    contract: Pubkey,
+    name: [u8; 32],
+    uri: String,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetMetadata(
+            SetMetadataArgs {
+                name,
+                uri,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn deactivate(
+    program_id: Pubkey,
+    contract: Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::Deactivate(
+            DeactivateArgs {
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn reactivate(
+    program_id: Pubkey,
+    contract: Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::Reactivate(
+            DeactivateArgs {
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn extend_expiry(
+    program_id: Pubkey,
+    contract: Pubkey,
+    expires_at_slot: Option<u64>,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::ExtendExpiry(
+            ExtendExpiryArgs {
+                expires_at_slot,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Add allowed `executor` signer of Upgrade to the instruction built by `upgrade`, `execute_upgrade` or other Upgrade builders.
+pub fn with_executor(mut instruction: Instruction, executor: Pubkey) -> Instruction {
+    instruction.accounts.push(AccountMeta::new(executor, true));
+    instruction
+}
+
+/// Add proposer, approver or veto `signer` bound to Solana signer to the instruction built by `propose_upgrade`, `approve_upgrade` or `veto_upgrade`.
+pub fn with_role_signer(mut instruction: Instruction, signer: Pubkey) -> Instruction {
+    instruction.accounts.push(AccountMeta::new_readonly(signer, true));
+    instruction
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn set_executors(
+    program_id: Pubkey,
+    contract: Pubkey,
+    executors: Vec<Pubkey>,
+    reimbursement: u64,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetExecutors(
+            SetExecutorsArgs {
+                executors,
+                reimbursement,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Propose upgrade of `contract` managed by UpgradeAdmin of `admin_contract`.
+#[allow(clippy::too_many_arguments)]
+pub fn propose_upgrade(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    fee_payer: Pubkey,
+    nonce: u128,
+    code_hash: [u8; 32],
+    not_before_slot: Option<u64>,
+    not_after_slot: Option<u64>,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (pending_upgrade, _) = pda::find_pending_upgrade_address(&program_id, &admin, &contract);
+    let (buffer_staging, _) = pda::find_buffer_staging_address(&program_id, &admin, &buffer);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::ProposeUpgrade(
+            ProposeUpgradeArgs {
+                nonce,
+                code_hash,
+                not_before_slot,
+                not_after_slot,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(pending_upgrade, false),
+            AccountMeta::new_readonly(contract, false),
+            AccountMeta::new_readonly(buffer, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new_readonly(buffer_staging, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Execute upgrade of `contract` proposed by UpgradeAdmin of `admin_contract`.
+pub fn execute_upgrade(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    spill: Pubkey,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (program_data, _) = pda::find_program_data_address(&contract);
+    let (pending_upgrade, _) = pda::find_pending_upgrade_address(&program_id, &admin, &contract);
+    let (upgrade_history, _) = pda::find_upgrade_history_address(&program_id, &admin, &contract);
+    let (buffer_staging, _) = pda::find_buffer_staging_address(&program_id, &admin, &buffer);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::ExecuteUpgrade.pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(program_data, false),
+            AccountMeta::new(contract, false),
+            AccountMeta::new(buffer, false),
+            AccountMeta::new(spill, false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::id(), false),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new(pending_upgrade, false),
+            AccountMeta::new(upgrade_history, false),
+            AccountMeta::new(buffer_staging, false),
+        ],
+    }
+}
+
+pub fn set_upgrade_delay(
+    program_id: Pubkey,
+    contract: Pubkey,
+    min_delay_slots: u64,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetUpgradeDelay(
+            SetUpgradeDelayArgs {
+                min_delay_slots,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Cancel upgrade of `contract` proposed by UpgradeAdmin of `admin_contract`.
+#[allow(clippy::too_many_arguments)]
+pub fn cancel_upgrade(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    destination: Pubkey,
+    nonce: u128,
+    close_buffer: bool,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (pending_upgrade, _) = pda::find_pending_upgrade_address(&program_id, &admin, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::CancelUpgrade(
+            CancelUpgradeArgs {
+                nonce,
+                close_buffer,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(pending_upgrade, false),
+            AccountMeta::new_readonly(contract, false),
+            AccountMeta::new(buffer, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn set_buffer_authority(
+    program_id: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    authority: Pubkey,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetBufferAuthority.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, false),
+            AccountMeta::new(buffer, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Hand `buffer` over to UpgradeAdmin of `admin_contract` and propose upgrade of `contract` to it in one transaction.
+#[allow(clippy::too_many_arguments)]
+pub fn set_buffer_authority_and_propose(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    authority: Pubkey,
+    fee_payer: Pubkey,
+    nonce: u128,
+    code_hash: [u8; 32],
+    not_before_slot: Option<u64>,
+    not_after_slot: Option<u64>,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Vec<Instruction> {
+    vec![
+        set_buffer_authority(program_id, admin_contract, buffer, authority),
+        propose_upgrade(program_id, admin_contract, contract, buffer, fee_payer, nonce, code_hash, not_before_slot, not_after_slot, signatures, signer_proofs, expires_at),
+    ]
+}
+
+/// Create UpgradeHistory of `contract` managed by UpgradeAdmin of `admin_contract`.
+pub fn init_upgrade_history(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    fee_payer: Pubkey,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (upgrade_history, _) = pda::find_upgrade_history_address(&program_id, &admin, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::InitUpgradeHistory.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, false),
+            AccountMeta::new(upgrade_history, false),
+            AccountMeta::new_readonly(contract, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// Register snapshot `buffer` of `contract` managed by UpgradeAdmin of `admin_contract`.
+#[allow(clippy::too_many_arguments)]
+pub fn register_snapshot(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    fee_payer: Pubkey,
+    code_hash: [u8; 32],
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (rollback_snapshot, _) = pda::find_rollback_snapshot_address(&program_id, &admin, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::RegisterSnapshot(
+            RegisterSnapshotArgs {
+                code_hash,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(rollback_snapshot, false),
+            AccountMeta::new_readonly(contract, false),
+            AccountMeta::new_readonly(buffer, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Roll `contract` managed by UpgradeAdmin of `admin_contract` back to its snapshot `buffer`.
+#[allow(clippy::too_many_arguments)]
+pub fn rollback(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    spill: Pubkey,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (program_data, _) = pda::find_program_data_address(&contract);
+    let (upgrade_history, _) = pda::find_upgrade_history_address(&program_id, &admin, &contract);
+    let (rollback_snapshot, _) = pda::find_rollback_snapshot_address(&program_id, &admin, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::Rollback(
+            RollbackArgs {
+                nonce,
+                signatures,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(program_data, false),
+            AccountMeta::new(contract, false),
+            AccountMeta::new(buffer, false),
+            AccountMeta::new(spill, false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::id(), false),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new(upgrade_history, false),
+            AccountMeta::new(rollback_snapshot, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn set_upgrade_interval(
+    program_id: Pubkey,
+    contract: Pubkey,
+    min_upgrade_interval_slots: u64,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetUpgradeInterval(
+            SetUpgradeIntervalArgs {
+                min_upgrade_interval_slots,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Upgrade `upgrades` (contract, buffer, code hash) managed by UpgradeAdmin of `admin_contract` in one instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn batch_upgrade(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    spill: Pubkey,
+    upgrades: Vec<(Pubkey, Pubkey, [u8; 32])>,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let mut accounts = vec![
+        AccountMeta::new(admin, false),
+        AccountMeta::new(spill, false),
+        AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        AccountMeta::new_readonly(solana_program::sysvar::clock::id(), false),
+        AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+    ];
+
+    for (contract, buffer, _) in &upgrades {
+        let (program_data, _) = pda::find_program_data_address(contract);
+        let (upgrade_history, _) = pda::find_upgrade_history_address(&program_id, &admin, contract);
+        let (buffer_staging, _) = pda::find_buffer_staging_address(&program_id, &admin, buffer);
+        accounts.push(AccountMeta::new(program_data, false));
+        accounts.push(AccountMeta::new(*contract, false));
+        accounts.push(AccountMeta::new(*buffer, false));
+        accounts.push(AccountMeta::new(upgrade_history, false));
+        accounts.push(AccountMeta::new(buffer_staging, false));
+    }
+
+    accounts.push(AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false));
+
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::BatchUpgrade(
+            BatchUpgradeArgs {
+                nonce,
+                code_hashes: upgrades.iter().map(|(_, _, code_hash)| *code_hash).collect(),
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts,
+    }
+}
+
+/// Remove upgrade authority of `contract` managed by UpgradeAdmin of `admin_contract`.
+pub fn set_immutable(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (program_data, _) = pda::find_program_data_address(&contract);
+    let (admin_history, _) = pda::find_admin_history_address(&program_id, &admin);
+
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetImmutable(
+            SetImmutableArgs {
+                contract,
+                nonce,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(program_data, false),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new(admin_history, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Create `program` account and deploy it from `buffer` with UpgradeAdmin of `admin_contract` as upgrade authority.
+/// The transaction should be signed by the program keypair.
+#[allow(clippy::too_many_arguments)]
+pub fn deploy(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    program: Pubkey,
+    buffer: Pubkey,
+    fee_payer: Pubkey,
+    program_lamports: u64,
+    max_data_len: u64,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Vec<Instruction> {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (program_data, _) = pda::find_program_data_address(&program);
+
+    vec![
+        solana_program::system_instruction::create_account(
+            &fee_payer,
+            &program,
+            program_lamports,
+            solana_program::bpf_loader_upgradeable::UpgradeableLoaderState::size_of_program() as u64,
+            &solana_program::bpf_loader_upgradeable::id(),
+        ),
+        Instruction {
+            program_id,
+            data: UpgradeInstruction::Deploy(
+                DeployArgs {
+                    max_data_len,
+                    nonce,
+                    signatures,
+                    signer_proofs,
+                    expires_at,
+                }
+            ).pack(),
+            accounts: vec![
+                AccountMeta::new(admin, false),
+                AccountMeta::new(fee_payer, true),
+                AccountMeta::new(program_data, false),
+                AccountMeta::new(program, false),
+                AccountMeta::new(buffer, false),
+                AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::id(), false),
+                AccountMeta::new_readonly(solana_program::system_program::id(), false),
+                AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+                AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+            ],
+        },
+    ]
+}
+
+/// Extend ProgramData of `contract` managed by UpgradeAdmin of `admin_contract` by `additional_bytes`.
+#[allow(clippy::too_many_arguments)]
+pub fn extend_program(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    fee_payer: Pubkey,
+    additional_bytes: u32,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (program_data, _) = pda::find_program_data_address(&contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::ExtendProgram(
+            ExtendProgramArgs {
+                additional_bytes,
+                nonce,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(program_data, false),
+            AccountMeta::new(contract, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Create `buffer` account for `data_len` bytes of program data of `contract` managed by UpgradeAdmin of `admin_contract`
+/// and stage its writing by `writer`. The transaction should be signed by the buffer keypair.
+#[allow(clippy::too_many_arguments)]
+pub fn create_buffer(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    buffer: Pubkey,
+    writer: Pubkey,
+    fee_payer: Pubkey,
+    buffer_lamports: u64,
+    data_len: u64,
+    code_hash: [u8; 32],
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Vec<Instruction> {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (buffer_staging, _) = pda::find_buffer_staging_address(&program_id, &admin, &buffer);
+
+    vec![
+        solana_program::system_instruction::create_account(
+            &fee_payer,
+            &buffer,
+            buffer_lamports,
+            solana_program::bpf_loader_upgradeable::UpgradeableLoaderState::size_of_buffer(data_len as usize) as u64,
+            &solana_program::bpf_loader_upgradeable::id(),
+        ),
+        Instruction {
+            program_id,
+            data: UpgradeInstruction::CreateBuffer(
+                CreateBufferArgs {
+                    writer,
+                    data_len,
+                    code_hash,
+                    nonce,
+                    signatures,
+                    signer_proofs,
+                    expires_at,
+                }
+            ).pack(),
+            accounts: vec![
+                AccountMeta::new(admin, false),
+                AccountMeta::new_readonly(contract, false),
+                AccountMeta::new(buffer, false),
+                AccountMeta::new(buffer_staging, false),
+                AccountMeta::new(fee_payer, true),
+                AccountMeta::new_readonly(solana_program::system_program::id(), false),
+                AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+                AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+                AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+            ],
+        },
+    ]
+}
+
+/// Write `bytes` at `offset` of the `buffer` staged by UpgradeAdmin of `admin_contract`, signed by its `writer`.
+pub fn write_buffer_chunk(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    buffer: Pubkey,
+    writer: Pubkey,
+    offset: u32,
+    bytes: Vec<u8>,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (buffer_staging, _) = pda::find_buffer_staging_address(&program_id, &admin, &buffer);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::WriteBufferChunk(
+            WriteBufferChunkArgs {
+                offset,
+                bytes,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, false),
+            AccountMeta::new(buffer, false),
+            AccountMeta::new(buffer_staging, false),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new_readonly(writer, true),
+        ],
+    }
+}
+
+/// Discard the chunks of the `buffer` staged by UpgradeAdmin of `admin_contract`, signed by its `writer`.
+pub fn reset_buffer(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    buffer: Pubkey,
+    writer: Pubkey,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (buffer_staging, _) = pda::find_buffer_staging_address(&program_id, &admin, &buffer);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::ResetBuffer.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, false),
+            AccountMeta::new(buffer_staging, false),
+            AccountMeta::new_readonly(writer, true),
+        ],
+    }
+}
+
+/// Finalize the `buffer` staged by UpgradeAdmin of `admin_contract`.
+pub fn finalize_buffer(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    buffer: Pubkey,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (buffer_staging, _) = pda::find_buffer_staging_address(&program_id, &admin, &buffer);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::FinalizeBuffer.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, false),
+            AccountMeta::new(buffer_staging, false),
+        ],
+    }
+}
+
+/// Close `buffer` owned by UpgradeAdmin of `admin_contract` and send its lamports to `recipient`.
+#[allow(clippy::too_many_arguments)]
+pub fn close_buffer(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    buffer: Pubkey,
+    recipient: Pubkey,
+    nonce: u128,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (buffer_staging, _) = pda::find_buffer_staging_address(&program_id, &admin, &buffer);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::CloseBuffer(
+            CloseBufferArgs {
+                nonce,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new(buffer, false),
+            AccountMeta::new(recipient, false),
+            AccountMeta::new_readonly(solana_program::bpf_loader_upgradeable::id(), false),
+            AccountMeta::new(buffer_staging, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn set_roles(
+    program_id: Pubkey,
+    contract: Pubkey,
+    roles: Vec<RoleAssignment>,
+    approval_threshold: u8,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetRoles(
+            SetRolesArgs {
+                roles,
+                approval_threshold,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Approve pending upgrade of `contract` managed by UpgradeAdmin of `admin_contract`.
+pub fn approve_upgrade(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (pending_upgrade, _) = pda::find_pending_upgrade_address(&program_id, &admin, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::ApproveUpgrade(
+            ApproveUpgradeArgs {
+                signatures,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, false),
+            AccountMeta::new(pending_upgrade, false),
+            AccountMeta::new_readonly(contract, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+pub fn set_veto_key(
+    program_id: Pubkey,
+    contract: Pubkey,
+    veto_key: Option<RoleKey>,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetVetoKey(
+            SetVetoKeyArgs {
+                veto_key,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Veto pending upgrade of `contract` managed by UpgradeAdmin of `admin_contract` and send proposal lamports to `destination`.
+pub fn veto_upgrade(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    destination: Pubkey,
+    signatures: Vec<EcdsaSignature>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    let (pending_upgrade, _) = pda::find_pending_upgrade_address(&program_id, &admin, &contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::VetoUpgrade(
+            VetoUpgradeArgs {
+                signatures,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, false),
+            AccountMeta::new(pending_upgrade, false),
+            AccountMeta::new_readonly(contract, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Set healthcheck `data` of `contract` managed by UpgradeAdmin of `admin_contract`.
+#[allow(clippy::too_many_arguments)]
+pub fn set_healthcheck(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    data: Option<Vec<u8>>,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetHealthcheck(
+            SetHealthcheckArgs {
+                contract,
+                data,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+/// Run healthcheck of `contract` upgraded by UpgradeAdmin of `admin_contract`.
+pub fn run_healthcheck(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::RunHealthcheck.pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(contract, false),
+        ],
+    }
+}
+
+/// Set verified `build_hash` of `contract` managed by UpgradeAdmin of `admin_contract`.
+#[allow(clippy::too_many_arguments)]
+pub fn set_verified_build_hash(
+    program_id: Pubkey,
+    admin_contract: Pubkey,
+    contract: Pubkey,
+    build_hash: Option<[u8; 32]>,
+    required: bool,
+    signatures: Vec<EcdsaSignature>,
+    signer_proofs: Vec<SignerProof>,
+    expires_at: i64,
+) -> Instruction {
+    let (admin, _) = pda::find_admin_address(&program_id, &admin_contract);
+    Instruction {
+        program_id,
+        data: UpgradeInstruction::SetVerifiedBuildHash(
+            SetVerifiedBuildHashArgs {
+                contract,
+                build_hash,
+                required,
+                signatures,
+                signer_proofs,
+                expires_at,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(admin, false),
+            AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Role;
+
+    fn signatures() -> Vec<EcdsaSignature> {
+        vec![EcdsaSignature { signature: [1; 64], recovery_id: 0, personal_sign: false }]
+    }
+
+    /// Checks that the accounts of `instruction` have `accounts` flags, whitespace separated `R`, `W`, `RS` or `WS`
+    /// in the order they are read by the processor, and decodes its data.
+    fn unpack_checked(program_id: &Pubkey, instruction: &Instruction, accounts: &str) -> UpgradeInstruction {
+        assert_eq!(instruction.program_id, *program_id);
+        let flags: Vec<String> = instruction.accounts.iter()
+            .map(|meta| format!("{}{}", if meta.is_writable { "W" } else { "R" }, if meta.is_signer { "S" } else { "" }))
+            .collect();
+        assert_eq!(flags.join(" "), accounts);
+        UpgradeInstruction::unpack(&instruction.data).unwrap()
+    }
+
+    #[test]
+    fn admin_instructions() {
+        let program_id = Pubkey::new_unique();
+        let contract = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let key = AdminKey::PublicKey([2; 64]);
+
+        let instruction = initialize_admin(
+            program_id, contract, payer, vec![key.clone()], vec![], 1, HashingMode::Legacy, [0; 32], None, None, 0,
+            Curve::Secp256k1, HashAlgorithm::Keccak, None, 0, vec![], None, vec![], 0, 0, 0, NonceWidth::U128,
+        );
+        match unpack_checked(&program_id, &instruction, "W WS R R W") {
+            UpgradeInstruction::InitializeAdmin(args) => {
+                assert_eq!((args.public_keys, args.nonce_width), (vec![key.clone()], NonceWidth::U128))
+            }
+            _ => panic!("unexpected instruction"),
+        }
+
+        let instruction = change_public_key(program_id, contract, vec![key.clone()], vec![], 1, None, None, 3, signatures(), vec![], 10);
+        match unpack_checked(&program_id, &instruction, "W R W R") {
+            UpgradeInstruction::ChangePublicKey(args) => assert_eq!((args.nonce, args.expires_at), (3, 10)),
+            _ => panic!("unexpected instruction"),
+        }
+
+        let instruction = change_authority(program_id, contract, Pubkey::new_unique(), 0, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W R R W R"), UpgradeInstruction::ChangeAuthority(_)));
+        let instruction = change_managed_authority(program_id, contract, Pubkey::new_unique(), Pubkey::new_unique(), 0, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W R R W R"), UpgradeInstruction::ChangeAuthority(_)));
+
+        assert!(matches!(unpack_checked(&program_id, &activate_key(program_id, contract), "W R"), UpgradeInstruction::ActivateKey));
+        let instruction = cancel_key_change(program_id, contract, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::CancelKeyChange(_)));
+        let instruction = revoke_keys(program_id, contract, payer, vec![key.clone()], signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W WS R R R"), UpgradeInstruction::RevokeKeys(_)));
+        let instruction = verify_batch(program_id, contract, payer, vec![[3; 32]], signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W WS R R R"), UpgradeInstruction::VerifyBatch(_)));
+        let instruction = create_session(program_id, contract, payer, key.clone(), 1, 100, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R W WS R R R"), UpgradeInstruction::CreateSession(_)));
+
+        let instruction = start_recovery(program_id, contract, vec![key], vec![], 1, 0, signatures(), 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R R"), UpgradeInstruction::StartRecovery(_)));
+        assert!(matches!(unpack_checked(&program_id, &complete_recovery(program_id, contract), "W R"), UpgradeInstruction::CompleteRecovery));
+        let instruction = cancel_recovery(program_id, contract, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::CancelRecovery(_)));
+
+        let instruction = close_admin(program_id, contract, payer, 0, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R W R"), UpgradeInstruction::CloseAdmin(_)));
+        let instruction = migrate_admin(program_id, contract, payer);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W WS R R W"), UpgradeInstruction::MigrateAdmin));
+    }
+
+    #[test]
+    fn settings_instructions() {
+        let program_id = Pubkey::new_unique();
+        let contract = Pubkey::new_unique();
+
+        let instruction = pause(program_id, contract, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::Pause(_)));
+        let instruction = unpause(program_id, contract, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::Unpause(_)));
+
+        // ProgramData of every removed contract follows the Instructions sysvar
+        let instruction = set_managed_contracts(program_id, contract, vec![], &[Pubkey::new_unique(), Pubkey::new_unique()], signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R R R"), UpgradeInstruction::SetManagedContracts(_)));
+
+        let instruction = set_metadata(program_id, contract, [4; 32], "uri".to_string(), signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::SetMetadata(_)));
+        let instruction = deactivate(program_id, contract, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::Deactivate(_)));
+        let instruction = reactivate(program_id, contract, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::Reactivate(_)));
+        let instruction = extend_expiry(program_id, contract, Some(100), signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::ExtendExpiry(_)));
+        let instruction = set_executors(program_id, contract, vec![Pubkey::new_unique()], 10, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::SetExecutors(_)));
+        let instruction = set_upgrade_delay(program_id, contract, 10, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::SetUpgradeDelay(_)));
+        let instruction = set_upgrade_interval(program_id, contract, 10, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::SetUpgradeInterval(_)));
+
+        let roles = vec![RoleAssignment { role: Role::Executor, key: RoleKey::Signer(Pubkey::new_unique()) }];
+        let instruction = set_roles(program_id, contract, roles, 1, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::SetRoles(_)));
+        let instruction = set_veto_key(program_id, contract, Some(RoleKey::Signer(Pubkey::new_unique())), signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::SetVetoKey(_)));
+        let instruction = set_healthcheck(program_id, contract, contract, Some(vec![1]), signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::SetHealthcheck(_)));
+        let instruction = set_verified_build_hash(program_id, contract, contract, Some([5; 32]), true, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::SetVerifiedBuildHash(_)));
+    }
+
+    #[test]
+    fn upgrade_instructions() {
+        let program_id = Pubkey::new_unique();
+        let contract = Pubkey::new_unique();
+        let buffer = Pubkey::new_unique();
+        let spill = Pubkey::new_unique();
+        let executor = Pubkey::new_unique();
+
+        let instruction = upgrade(program_id, contract, buffer, spill, 7, [6; 32], signatures(), vec![], 10);
+        match unpack_checked(&program_id, &instruction, "W W W W W R R R W W R") {
+            UpgradeInstruction::Upgrade(args) => assert_eq!((args.nonce, args.code_hash, args.expires_at), (7, [6; 32], 10)),
+            _ => panic!("unexpected instruction"),
+        }
+
+        let instruction = with_executor(upgrade_managed(program_id, contract, Pubkey::new_unique(), buffer, spill, 0, [6; 32], signatures(), vec![], 0), executor);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W W W W R R R W W R WS"), UpgradeInstruction::Upgrade(_)));
+        let instruction = upgrade_from_batch(program_id, contract, buffer, spill, contract, 0, [6; 32], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W W W W R R R W W R W"), UpgradeInstruction::Upgrade(_)));
+        let instruction = upgrade_with_session(program_id, contract, buffer, spill, 0, [6; 32], signatures(), 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W W W W R R R W W R R"), UpgradeInstruction::Upgrade(_)));
+
+        let upgrades = vec![(contract, buffer, [6; 32]), (Pubkey::new_unique(), Pubkey::new_unique(), [7; 32])];
+        let instruction = batch_upgrade(program_id, contract, spill, upgrades, 0, signatures(), vec![], 0);
+        match unpack_checked(&program_id, &instruction, "W W R R R W W W W W W W W W W R") {
+            UpgradeInstruction::BatchUpgrade(args) => assert_eq!(args.code_hashes, vec![[6; 32], [7; 32]]),
+            _ => panic!("unexpected instruction"),
+        }
+
+        let instruction = register_snapshot(program_id, contract, contract, buffer, spill, [6; 32], signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W R R WS R R R"), UpgradeInstruction::RegisterSnapshot(_)));
+        let instruction = with_executor(rollback(program_id, contract, contract, buffer, spill, 0, signatures(), 0), executor);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W W W W R R R W W R WS"), UpgradeInstruction::Rollback(_)));
+
+        let instruction = init_upgrade_history(program_id, contract, contract, spill);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "R W R WS R R"), UpgradeInstruction::InitUpgradeHistory));
+        let instruction = set_immutable(program_id, contract, contract, 0, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W R W R"), UpgradeInstruction::SetImmutable(_)));
+        let instruction = run_healthcheck(program_id, contract, contract);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W R"), UpgradeInstruction::RunHealthcheck));
+
+        // Program account is created by the system program in the same transaction
+        let instructions = deploy(program_id, contract, Pubkey::new_unique(), buffer, spill, 1, 100, 0, signatures(), vec![], 0);
+        assert_eq!(instructions.len(), 2);
+        assert!(matches!(unpack_checked(&program_id, &instructions[1], "W WS W W W R R R R R"), UpgradeInstruction::Deploy(_)));
+        let instruction = extend_program(program_id, contract, contract, spill, 1024, 0, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W W R WS R R"), UpgradeInstruction::ExtendProgram(_)));
+    }
+
+    #[test]
+    fn proposal_instructions() {
+        let program_id = Pubkey::new_unique();
+        let contract = Pubkey::new_unique();
+        let buffer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+
+        let instruction = propose_upgrade(program_id, contract, contract, buffer, payer, 0, [6; 32], Some(1), Some(2), signatures(), vec![], 0);
+        match unpack_checked(&program_id, &instruction, "W W R R WS R R R R") {
+            UpgradeInstruction::ProposeUpgrade(args) => assert_eq!((args.not_before_slot, args.not_after_slot), (Some(1), Some(2))),
+            _ => panic!("unexpected instruction"),
+        }
+
+        let instruction = with_role_signer(propose_upgrade(program_id, contract, contract, buffer, payer, 0, [6; 32], None, None, vec![], vec![], 0), signer);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W R R WS R R R R RS"), UpgradeInstruction::ProposeUpgrade(_)));
+        let instruction = with_executor(execute_upgrade(program_id, contract, contract, buffer, payer), signer);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W W W W R R R W W W WS"), UpgradeInstruction::ExecuteUpgrade));
+        let instruction = with_role_signer(approve_upgrade(program_id, contract, contract, vec![], 0), signer);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "R W R R RS"), UpgradeInstruction::ApproveUpgrade(_)));
+        let instruction = with_role_signer(veto_upgrade(program_id, contract, contract, payer, vec![], 0), signer);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "R W R W R RS"), UpgradeInstruction::VetoUpgrade(_)));
+        let instruction = cancel_upgrade(program_id, contract, contract, buffer, payer, 0, true, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W R W W R R"), UpgradeInstruction::CancelUpgrade(_)));
+
+        let instruction = set_buffer_authority(program_id, contract, buffer, signer);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "R W RS R R"), UpgradeInstruction::SetBufferAuthority));
+    }
+
+    #[test]
+    fn set_buffer_authority_is_followed_by_proposal() {
+        let program_id = Pubkey::new_unique();
+        let contract = Pubkey::new_unique();
+        let buffer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        let instructions = set_buffer_authority_and_propose(
+            program_id, contract, contract, buffer, authority, authority, 0, [6; 32], None, None, signatures(), vec![], 0,
+        );
+        assert_eq!(instructions.len(), 2);
+        assert!(matches!(unpack_checked(&program_id, &instructions[0], "R W RS R R"), UpgradeInstruction::SetBufferAuthority));
+        assert!(matches!(unpack_checked(&program_id, &instructions[1], "W W R R WS R R R R"), UpgradeInstruction::ProposeUpgrade(_)));
+
+        // SetBufferAuthority looks for the buffer at this index of the next instruction
+        assert_eq!(instructions[0].accounts[1].pubkey, buffer);
+        assert_eq!(instructions[1].accounts[REGISTERED_BUFFER_INDEX].pubkey, buffer);
+    }
+
+    #[test]
+    fn buffer_instructions() {
+        let program_id = Pubkey::new_unique();
+        let contract = Pubkey::new_unique();
+        let buffer = Pubkey::new_unique();
+        let writer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+
+        // Buffer account is created for the loader in the same transaction
+        let instructions = create_buffer(program_id, contract, contract, buffer, writer, payer, 1, 100, [6; 32], 0, signatures(), vec![], 0);
+        assert_eq!(instructions.len(), 2);
+        match unpack_checked(&program_id, &instructions[1], "W R W W WS R R R R") {
+            UpgradeInstruction::CreateBuffer(args) => assert_eq!((args.writer, args.data_len), (writer, 100)),
+            _ => panic!("unexpected instruction"),
+        }
+
+        let instruction = write_buffer_chunk(program_id, contract, buffer, writer, 0, vec![1, 2, 3]);
+        match unpack_checked(&program_id, &instruction, "R W W R RS") {
+            UpgradeInstruction::WriteBufferChunk(args) => assert_eq!((args.offset, args.bytes), (0, vec![1, 2, 3])),
+            _ => panic!("unexpected instruction"),
+        }
+
+        assert!(matches!(unpack_checked(&program_id, &reset_buffer(program_id, contract, buffer, writer), "R W RS"), UpgradeInstruction::ResetBuffer));
+        assert!(matches!(unpack_checked(&program_id, &finalize_buffer(program_id, contract, buffer), "R W"), UpgradeInstruction::FinalizeBuffer));
+        let instruction = close_buffer(program_id, contract, buffer, payer, 0, signatures(), vec![], 0);
+        assert!(matches!(unpack_checked(&program_id, &instruction, "W W W R W R"), UpgradeInstruction::CloseBuffer(_)));
+    }
+
+    #[test]
+    fn unpack_rejects_other_version_and_trailing_bytes() {
+        let mut data = activate_key(Pubkey::new_unique(), Pubkey::new_unique()).data;
+        data.push(0);
+        assert!(matches!(UpgradeInstruction::unpack(&data), Err(ProgramError::BorshIoError(_))));
+
+        data.pop();
+        data[0] = INSTRUCTION_VERSION + 1;
+        assert_eq!(UpgradeInstruction::unpack(&data).err(), Some(UpgradeError::UnsupportedInstructionVersion.into()));
+    }
+}
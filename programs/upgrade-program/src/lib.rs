@@ -1,9 +1,38 @@
+extern crate alloc;
+
+// On-chain execution is not part of the wasm32 build, browser clients use builders, hashing and state only
+#[cfg(not(target_arch = "wasm32"))]
+mod accounts;
 pub mod entrypoint;
 pub mod state;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod processor;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod utils;
 pub mod instructions;
 pub mod ecdsa;
+pub mod ed25519;
+pub mod secp256r1;
+pub mod hashing;
+pub mod message;
+pub mod merkle;
+pub mod pda;
+pub mod sha256;
 pub mod error;
+pub mod events;
+pub mod results;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 const HASH_CONSTANT: &str = "solana-upgrade-program";
-const PDA_ADMIN_SEED: &str = "admin-upgrade-account";
\ No newline at end of file
+const PDA_ADMIN_SEED: &str = "admin-upgrade-account";
+const PDA_REVOKED_KEYS_SEED: &str = "revoked-keys-account";
+const PDA_BATCH_APPROVAL_SEED: &str = "batch-approval-account";
+const PDA_SESSION_KEY_SEED: &str = "session-key-account";
+const PDA_ADMIN_HISTORY_SEED: &str = "admin-history-account";
+const PDA_PENDING_UPGRADE_SEED: &str = "pending-upgrade-account";
+const PDA_UPGRADE_HISTORY_SEED: &str = "upgrade-history-account";
+const PDA_ROLLBACK_SNAPSHOT_SEED: &str = "rollback-snapshot-account";
+const PDA_BUFFER_STAGING_SEED: &str = "buffer-staging-account";
\ No newline at end of file
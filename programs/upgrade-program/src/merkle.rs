@@ -0,0 +1,58 @@
+//! Merkle trees of eligible signers committed in UpgradeAdmin and of batched operation hashes
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::keccak::hashv;
+use crate::state::AdminKey;
+
+pub const MERKLE_LEAF_PREFIX: u8 = 0;
+
+pub const MERKLE_NODE_PREFIX: u8 = 1;
+
+/// Proof that signer key is included into the tree with root stored in UpgradeAdmin.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SignerProof {
+    // Signer key that is the tree leaf
+    pub key: AdminKey,
+    // Sibling hashes from the leaf up to the root
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_hex"))]
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// keccak_hash(0x00, key.bytes)
+pub fn leaf_hash(key: &AdminKey) -> [u8; 32] {
+    hashv(&[&[MERKLE_LEAF_PREFIX], key.as_ref()]).to_bytes()
+}
+
+/// keccak_hash(0x01, min(left, right), max(left, right)). Children are sorted, so proof does not need
+/// to contain the position of every sibling.
+pub fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    if left <= right {
+        hashv(&[&[MERKLE_NODE_PREFIX], left, right]).to_bytes()
+    } else {
+        hashv(&[&[MERKLE_NODE_PREFIX], right, left]).to_bytes()
+    }
+}
+
+/// keccak_hash(0x00, operation_hash)
+pub fn operation_leaf_hash(operation_hash: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&[MERKLE_LEAF_PREFIX], operation_hash]).to_bytes()
+}
+
+/// Root of the tree with operation hashes as leaves. Node without a sibling is promoted to the next level.
+pub fn operations_root(operation_hashes: &[[u8; 32]]) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> = operation_hashes.iter().map(operation_leaf_hash).collect();
+    while level.len() > 1 {
+        level = level.chunks(2)
+            .map(|pair| if pair.len() == 2 { node_hash(&pair[0], &pair[1]) } else { pair[0] })
+            .collect();
+    }
+
+    level.first().copied().unwrap_or_default()
+}
+
+pub fn verify_proof(proof: &SignerProof, root: &[u8; 32]) -> bool {
+    let computed = proof.proof.iter().fold(leaf_hash(&proof.key), |hash, sibling| node_hash(&hash, sibling));
+    computed == *root
+}
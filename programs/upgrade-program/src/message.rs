@@ -0,0 +1,239 @@
+//! Signable message hashes shared by the program and off-chain clients. The module uses only `core` and `alloc`,
+//! so coordinators can compute exactly the same preimages instead of reimplementing the layout by hand.
+
+use alloc::vec::Vec;
+use solana_program::keccak::{hash, hashv, Hash};
+use solana_program::hash as sha256;
+use solana_program::pubkey::Pubkey;
+use crate::hashing::{
+    eip712_domain_separator, HashAlgorithm, HashingMode, NonceWidth, CHANGE_AUTHORITY_TAG, EIP712_CHANGE_AUTHORITY_TYPE,
+    EIP712_CHANGE_PUBLIC_KEY_TYPE, EIP712_UPGRADE_TYPE,
+};
+use crate::state::{AdminKey, UpgradeAdmin};
+use crate::HASH_CONSTANT;
+
+/// UpgradeAdmin fields every signed message is bound to.
+#[derive(Clone, Copy, Debug)]
+pub struct MessageContext<'a> {
+    // Upgrade program id
+    pub program_id: &'a Pubkey,
+    // Contract the operation targets: UpgradeAdmin primary contract or one of its managed contracts
+    pub contract: &'a Pubkey,
+    // Chain/deployment tag of UpgradeAdmin
+    pub domain: &'a [u8; 32],
+    pub hashing_mode: HashingMode,
+    pub hash_algorithm: HashAlgorithm,
+    pub nonce_width: NonceWidth,
+}
+
+impl<'a> MessageContext<'a> {
+    pub fn new(program_id: &'a Pubkey, admin: &'a UpgradeAdmin) -> Self {
+        MessageContext {
+            program_id,
+            contract: &admin.contract,
+            domain: &admin.domain,
+            hashing_mode: admin.hashing_mode,
+            hash_algorithm: admin.hash_algorithm,
+            nonce_width: admin.nonce_width,
+        }
+    }
+}
+
+/// Hash to sign for Upgrade of `buffer` holding program data with SHA-256 `code_hash` with upgrade nonce.
+/// Rent difference of the upgrade is sent to `spill`.
+pub fn upgrade_message_hash(ctx: &MessageContext, nonce: u128, buffer: &Pubkey, code_hash: &[u8; 32], spill: &Pubkey, expires_at: i64) -> Hash {
+    match ctx.hashing_mode {
+        HashingMode::Legacy => legacy_hash(ctx, nonce, &[buffer.as_ref(), code_hash.as_ref(), spill.as_ref()].concat(), expires_at),
+        HashingMode::Eip712 => eip712_hash(
+            ctx,
+            &[
+                type_hash(ctx.nonce_width, EIP712_UPGRADE_TYPE).as_ref(),
+                ctx.contract.as_ref(),
+                &encode_u128(nonce),
+                buffer.as_ref(),
+                code_hash.as_ref(),
+                spill.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for ChangeAuthority to `authority` with authority change nonce.
+pub fn change_authority_message_hash(ctx: &MessageContext, nonce: u128, authority: &Pubkey, expires_at: i64) -> Hash {
+    match ctx.hashing_mode {
+        HashingMode::Legacy => legacy_hash(
+            ctx,
+            nonce,
+            &[CHANGE_AUTHORITY_TAG.as_bytes(), authority.as_ref()].concat(),
+            expires_at,
+        ),
+        HashingMode::Eip712 => eip712_hash(
+            ctx,
+            &[
+                type_hash(ctx.nonce_width, EIP712_CHANGE_AUTHORITY_TYPE).as_ref(),
+                ctx.contract.as_ref(),
+                &encode_u128(nonce),
+                authority.as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// Hash to sign for ChangePublicKey with key change nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn change_key_message_hash(
+    ctx: &MessageContext,
+    nonce: u128,
+    new_public_keys: &[AdminKey],
+    new_weights: &[u8],
+    new_threshold: u8,
+    new_signers_root: Option<[u8; 32]>,
+    new_upgrade_key: &Option<AdminKey>,
+    expires_at: i64,
+) -> Hash {
+    match ctx.hashing_mode {
+        HashingMode::Legacy => {
+            let mut payload = Vec::new();
+            payload.push(new_threshold);
+            borsh_keys(&mut payload, new_public_keys);
+            borsh_bytes(&mut payload, new_weights);
+            borsh_option(&mut payload, new_signers_root.as_ref().map(|root| root.as_ref()));
+            borsh_option(&mut payload, new_upgrade_key.as_ref().map(borsh_key).as_deref());
+            legacy_hash(ctx, nonce, &payload, expires_at)
+        }
+        HashingMode::Eip712 => eip712_hash(
+            ctx,
+            &[
+                type_hash(ctx.nonce_width, EIP712_CHANGE_PUBLIC_KEY_TYPE).as_ref(),
+                ctx.contract.as_ref(),
+                &encode_u128(nonce),
+                &encode_u64(new_threshold as u64),
+                encode_keys(new_public_keys).as_ref(),
+                encode_weights(new_weights).as_ref(),
+                new_signers_root.unwrap_or_default().as_ref(),
+                hash(new_upgrade_key.as_ref().map(|key| key.as_ref()).unwrap_or_default()).as_ref(),
+                &encode_i64(expires_at),
+            ],
+        ),
+    }
+}
+
+/// hash(target_contract, nonce, "solana-upgrade-program".bytes, program_id, domain, payload, expires_at)
+/// with Keccak-256 or SHA-256 depending on admin hash algorithm. `nonce` is the nonce of the operation type,
+/// encoded in 8 or 16 big-endian bytes depending on admin nonce width.
+pub(crate) fn legacy_hash(ctx: &MessageContext, nonce: u128, payload: &[u8], expires_at: i64) -> Hash {
+    let nonce = nonce.to_be_bytes();
+    let nonce = match ctx.nonce_width {
+        NonceWidth::U64 => &nonce[8..],
+        NonceWidth::U128 => &nonce[..],
+    };
+
+    digest(ctx.hash_algorithm, &[
+        ctx.contract.as_ref(),
+        nonce,
+        HASH_CONSTANT.as_bytes(),
+        ctx.program_id.as_ref(),
+        ctx.domain.as_ref(),
+        payload,
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
+
+/// keccak_hash("\x19\x01", domain_separator, keccak_hash(encoded_struct))
+pub(crate) fn eip712_hash(ctx: &MessageContext, encoded_struct: &[&[u8]]) -> Hash {
+    hashv(&[
+        b"\x19\x01",
+        eip712_domain_separator(ctx.program_id, ctx.domain).as_ref(),
+        hashv(encoded_struct).as_ref(),
+    ])
+}
+
+/// keccak_hash of EIP-712 `eip712_type`, its `uint64 nonce` member is declared as `uint128 nonce` for wide nonces
+pub(crate) fn type_hash(nonce_width: NonceWidth, eip712_type: &str) -> Hash {
+    match nonce_width {
+        NonceWidth::U64 => hash(eip712_type.as_bytes()),
+        NonceWidth::U128 => hash(eip712_type.replacen("uint64 nonce", "uint128 nonce", 1).as_bytes()),
+    }
+}
+
+/// Digest of concatenated `parts` with `algorithm`
+fn digest(algorithm: HashAlgorithm, parts: &[&[u8]]) -> Hash {
+    match algorithm {
+        HashAlgorithm::Keccak => hashv(parts),
+        HashAlgorithm::Sha256 => Hash::new_from_array(sha256::hashv(parts).to_bytes()),
+    }
+}
+
+/// Borsh encoding of AdminKey: variant index and key bytes
+fn borsh_key(key: &AdminKey) -> Vec<u8> {
+    let variant = match key {
+        AdminKey::PublicKey(_) => 0u8,
+        AdminKey::EthAddress(_) => 1,
+        AdminKey::Ed25519(_) => 2,
+        AdminKey::CompressedPublicKey(_) => 3,
+    };
+
+    [&[variant], key.as_ref()].concat()
+}
+
+/// Borsh encoding of `Vec<AdminKey>`: u32 little-endian length and encoded elements
+fn borsh_keys(out: &mut Vec<u8>, keys: &[AdminKey]) {
+    out.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+    for key in keys {
+        out.extend_from_slice(&borsh_key(key));
+    }
+}
+
+/// Borsh encoding of `Vec<u8>`: u32 little-endian length and bytes
+fn borsh_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Borsh encoding of `Option`: 0 for None, 1 and encoded value for Some
+fn borsh_option(out: &mut Vec<u8>, value: Option<&[u8]>) {
+    match value {
+        Some(value) => {
+            out.push(1);
+            out.extend_from_slice(value);
+        }
+        None => out.push(0),
+    }
+}
+
+/// EIP-712 encoding of `bytes[]`: keccak_hash of concatenated keccak hashes of elements
+pub(crate) fn encode_keys(keys: &[AdminKey]) -> Hash {
+    let key_hashes: Vec<Hash> = keys.iter().map(|key| hash(key.as_ref())).collect();
+    let key_hashes: Vec<&[u8]> = key_hashes.iter().map(|h| h.as_ref()).collect();
+    hashv(&key_hashes)
+}
+
+/// EIP-712 encoding of `uint8[]`: keccak_hash of concatenated ABI encoded elements
+pub(crate) fn encode_weights(weights: &[u8]) -> Hash {
+    let words: Vec<[u8; 32]> = weights.iter().map(|weight| encode_u64(*weight as u64)).collect();
+    let words: Vec<&[u8]> = words.iter().map(|w| w.as_ref()).collect();
+    hashv(&words)
+}
+
+/// ABI encoding of unsigned integer: 32 byte big-endian word
+pub(crate) fn encode_u64(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// ABI encoding of wide unsigned integer: 32 byte big-endian word
+pub(crate) fn encode_u128(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// ABI encoding of signed integer: 32 byte big-endian two's complement word
+pub(crate) fn encode_i64(value: i64) -> [u8; 32] {
+    let mut word = if value < 0 { [0xffu8; 32] } else { [0u8; 32] };
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
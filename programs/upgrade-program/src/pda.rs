@@ -0,0 +1,56 @@
+//! Addresses of the program derived accounts. Off-chain builders and the processor derive them here, so the seed
+//! strings and their ordering are defined once.
+
+use solana_program::bpf_loader_upgradeable;
+use solana_program::pubkey::Pubkey;
+use crate::{PDA_ADMIN_HISTORY_SEED, PDA_ADMIN_SEED, PDA_BATCH_APPROVAL_SEED, PDA_BUFFER_STAGING_SEED, PDA_PENDING_UPGRADE_SEED, PDA_REVOKED_KEYS_SEED, PDA_ROLLBACK_SNAPSHOT_SEED, PDA_SESSION_KEY_SEED, PDA_UPGRADE_HISTORY_SEED};
+
+/// Address and bump of UpgradeAdmin of `contract`.
+pub fn find_admin_address(program_id: &Pubkey, contract: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_ADMIN_SEED.as_bytes(), contract.as_ref()], program_id)
+}
+
+/// Address and bump of the ProgramData account of upgradeable `contract`.
+pub fn find_program_data_address(contract: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[contract.as_ref()], &bpf_loader_upgradeable::id())
+}
+
+/// Address and bump of RevokedKeys of UpgradeAdmin at `upgrade_admin`.
+pub fn find_revoked_keys_address(program_id: &Pubkey, upgrade_admin: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_REVOKED_KEYS_SEED.as_bytes(), upgrade_admin.as_ref()], program_id)
+}
+
+/// Address and bump of BatchApproval of UpgradeAdmin at `upgrade_admin`.
+pub fn find_batch_approval_address(program_id: &Pubkey, upgrade_admin: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_BATCH_APPROVAL_SEED.as_bytes(), upgrade_admin.as_ref()], program_id)
+}
+
+/// Address and bump of SessionKey of UpgradeAdmin at `upgrade_admin`.
+pub fn find_session_key_address(program_id: &Pubkey, upgrade_admin: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_SESSION_KEY_SEED.as_bytes(), upgrade_admin.as_ref()], program_id)
+}
+
+/// Address and bump of AdminHistory of UpgradeAdmin at `upgrade_admin`.
+pub fn find_admin_history_address(program_id: &Pubkey, upgrade_admin: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_ADMIN_HISTORY_SEED.as_bytes(), upgrade_admin.as_ref()], program_id)
+}
+
+/// Address and bump of PendingUpgrade of `program` managed by UpgradeAdmin at `upgrade_admin`.
+pub fn find_pending_upgrade_address(program_id: &Pubkey, upgrade_admin: &Pubkey, program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_PENDING_UPGRADE_SEED.as_bytes(), upgrade_admin.as_ref(), program.as_ref()], program_id)
+}
+
+/// Address and bump of UpgradeHistory of `program` managed by UpgradeAdmin at `upgrade_admin`.
+pub fn find_upgrade_history_address(program_id: &Pubkey, upgrade_admin: &Pubkey, program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_UPGRADE_HISTORY_SEED.as_bytes(), upgrade_admin.as_ref(), program.as_ref()], program_id)
+}
+
+/// Address and bump of RollbackSnapshot of `program` managed by UpgradeAdmin at `upgrade_admin`.
+pub fn find_rollback_snapshot_address(program_id: &Pubkey, upgrade_admin: &Pubkey, program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_ROLLBACK_SNAPSHOT_SEED.as_bytes(), upgrade_admin.as_ref(), program.as_ref()], program_id)
+}
+
+/// Address and bump of BufferStaging of `buffer` staged by UpgradeAdmin at `upgrade_admin`.
+pub fn find_buffer_staging_address(program_id: &Pubkey, upgrade_admin: &Pubkey, buffer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_BUFFER_STAGING_SEED.as_bytes(), upgrade_admin.as_ref(), buffer.as_ref()], program_id)
+}
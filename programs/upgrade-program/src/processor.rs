@@ -1,39 +1,230 @@
 use solana_program::{
     account_info::{AccountInfo, next_account_info},
-    entrypoint::ProgramResult, msg,
-    program::{invoke_signed}, pubkey::Pubkey, system_instruction,
-    sysvar::{rent::Rent, Sysvar},
+    bpf_loader_upgradeable, entrypoint::ProgramResult, instruction::Instruction, keccak, msg,
+    program::{invoke, invoke_signed, set_return_data}, program_error::ProgramError, pubkey::Pubkey, system_instruction,
+    sysvar::{self, clock::Clock, instructions::{load_current_index_checked, load_instruction_at_checked}, rent::Rent, Sysvar},
 };
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::secp256k1_recover::{SECP256K1_PUBLIC_KEY_LENGTH, SECP256K1_SIGNATURE_LENGTH};
-use crate::state::{MAX_ADMIN_SIZE, UpgradeAdmin};
-use crate::instructions::UpgradeInstruction;
-use crate::ecdsa::verify_ecdsa_signature;
-use crate::{HASH_CONSTANT, PDA_ADMIN_SEED};
+use crate::state::{AdminHistory, AdminKey, BatchApproval, BufferStaging, Healthcheck, VerifiedBuild, MAX_HEALTHCHECKS, MAX_HEALTHCHECK_DATA_LEN, Role, RoleAssignment, RoleKey, MAX_ROLES, HistoryEntry, HistoryOperation, MAX_ADMIN_HISTORY_SIZE, Curve, MAX_ADMIN_SIZE, MAX_BATCH_APPROVAL_SIZE, MAX_BATCH_OPERATIONS, MAX_BATCH_UPGRADES, MAX_BUFFER_STAGING_SIZE, MAX_EXECUTORS, MAX_MANAGED_CONTRACTS, MAX_PENDING_UPGRADE_SIZE, MAX_REVOKED_KEYS, MAX_REVOKED_KEYS_SIZE, MAX_ROLLBACK_SNAPSHOT_SIZE, MAX_SESSION_KEY_SIZE, MAX_SIGNERS, MAX_UPGRADE_HISTORY_SIZE, MAX_URI_LENGTH, MIN_RECOVERY_DELAY, PendingKeyChange, PendingRecovery, PendingUpgrade, RevokedKeys, RollbackSnapshot, SESSION_ALLOWED_PERMISSIONS, SESSION_PERMISSION_UPGRADE, SessionKey, UpgradeAdmin, UpgradeHistory, UpgradeRecord, VersionedUpgradeAdmin, ADMIN_VERSION};
+use crate::instructions::{ApproveUpgradeArgs, SetHealthcheckArgs, SetVerifiedBuildHashArgs, BatchUpgradeArgs, SetRolesArgs, SetVetoKeyArgs, VetoUpgradeArgs, CloseBufferArgs, CreateBufferArgs, DeployArgs, ExtendProgramArgs, CancelKeyChangeArgs, CancelRecoveryArgs, CancelUpgradeArgs, ChangeAuthorityArgs, CloseAdminArgs, CreateSessionArgs, DeactivateArgs, ExtendExpiryArgs, ChangePublicKeyArgs, InitializeAdminArgs, PauseArgs, ProposeUpgradeArgs, RegisterSnapshotArgs, RevokeKeysArgs, RollbackArgs, SetExecutorsArgs, SetImmutableArgs, SetUpgradeDelayArgs, SetUpgradeIntervalArgs, SetManagedContractsArgs, SetMetadataArgs, StartRecoveryArgs, UpgradeArgs, UpgradeInstruction, VerifyBatchArgs, WriteBufferChunkArgs, REGISTERED_BUFFER_INDEX};
+use crate::ecdsa::{EcdsaSignature, is_valid_secp256k1_key, mark_ecdsa_signers, mark_secp256k1_precompile_signers, verify_threshold};
+use crate::ed25519::{is_valid_ed25519_key, mark_ed25519_signers};
+use crate::secp256r1::{is_valid_secp256r1_key, mark_secp256r1_signers};
+use crate::hashing::{HashAlgorithm, HashingMode, NonceWidth, approve_upgrade_hash, set_healthcheck_hash, set_verified_build_hash_hash, set_roles_hash, set_veto_key_hash, veto_upgrade_hash, batch_upgrade_hash, cancel_key_change_hash, cancel_recovery_hash, cancel_upgrade_hash, change_authority_hash, close_admin_hash, close_buffer_hash, change_public_key_hash, create_buffer_hash, create_session_hash, deploy_hash, extend_expiry_hash, extend_program_hash, propose_upgrade_hash, register_snapshot_hash, revoke_keys_hash, rollback_hash, set_active_hash, set_executors_hash, set_immutable_hash, set_managed_contracts_hash, set_metadata_hash, set_paused_hash, set_upgrade_delay_hash, set_upgrade_interval_hash, start_recovery_hash, upgrade_hash, verify_batch_hash};
+use crate::merkle::{operations_root, verify_proof, SignerProof};
+use crate::sha256::IncrementalSha256;
+use crate::pda;
+use crate::{PDA_ADMIN_HISTORY_SEED, PDA_ADMIN_SEED, PDA_BATCH_APPROVAL_SEED, PDA_BUFFER_STAGING_SEED, PDA_PENDING_UPGRADE_SEED, PDA_REVOKED_KEYS_SEED, PDA_ROLLBACK_SNAPSHOT_SEED, PDA_SESSION_KEY_SEED, PDA_UPGRADE_HISTORY_SEED};
+use crate::accounts::{verify_admin_owner, verify_program_data, AdminAccount, PayerAccount, ProgramDataAccount};
 use crate::error::UpgradeError;
+use crate::utils::{create_pda_account, verify_nonce, verify_not_expired, verify_system_program, verify_sysvar};
+use crate::results::{AdminResult, NonceResult, UpgradeResult};
+use crate::events::{AdminInitialized, AuthorityChanged, KeyChanged, UpgradeEvent, Upgraded};
+
+/// Discriminator of `UpgradeableLoaderState::ProgramData`
+const PROGRAM_DATA_DISCRIMINATOR: u32 = 3;
+
+const PROGRAM_DATA_AUTHORITY_OFFSET: usize = 4 + 8;
+
+/// Discriminator of `UpgradeableLoaderState::Buffer`
+const BUFFER_DISCRIMINATOR: u32 = 1;
+
+const BUFFER_METADATA_SIZE: usize = 4 + 1 + 32;
 
 pub fn process_instruction<'a>(
     program_id: &'a Pubkey,
     accounts: &'a [AccountInfo<'a>],
     input: &[u8],
 ) -> ProgramResult {
-    let instruction = UpgradeInstruction::try_from_slice(input)?;
+    let instruction = UpgradeInstruction::unpack(input)?;
     match instruction {
         UpgradeInstruction::InitializeAdmin(args) => {
             msg!("Instruction: Create upgrade admin");
-            process_init_admin(program_id, accounts, args.public_key, args.contract)
+            process_init_admin(program_id, accounts, args)
         }
         UpgradeInstruction::ChangePublicKey(args) => {
             msg!("Instruction: Change public key");
-            process_change_public_key(program_id, accounts, args.new_public_key, args.signature, args.recovery_id)
+            process_change_public_key(program_id, accounts, args)
         }
         UpgradeInstruction::ChangeAuthority(args) => {
             msg!("Instruction: Transfer upgrade authority");
-            process_change_authority(program_id, accounts, args.signature, args.recovery_id)
+            process_change_authority(program_id, accounts, args)
         }
         UpgradeInstruction::Upgrade(args) => {
             msg!("Instruction: Upgrade");
-            process_upgrade(program_id, accounts, args.signature, args.recovery_id)
+            process_upgrade(program_id, accounts, args)
+        }
+        UpgradeInstruction::ActivateKey => {
+            msg!("Instruction: Activate key");
+            process_activate_key(program_id, accounts)
+        }
+        UpgradeInstruction::CancelKeyChange(args) => {
+            msg!("Instruction: Cancel key change");
+            process_cancel_key_change(program_id, accounts, args)
+        }
+        UpgradeInstruction::RevokeKeys(args) => {
+            msg!("Instruction: Revoke keys");
+            process_revoke_keys(program_id, accounts, args)
+        }
+        UpgradeInstruction::VerifyBatch(args) => {
+            msg!("Instruction: Verify batch");
+            process_verify_batch(program_id, accounts, args)
+        }
+        UpgradeInstruction::CreateSession(args) => {
+            msg!("Instruction: Create session");
+            process_create_session(program_id, accounts, args)
+        }
+        UpgradeInstruction::StartRecovery(args) => {
+            msg!("Instruction: Start recovery");
+            process_start_recovery(program_id, accounts, args)
+        }
+        UpgradeInstruction::CompleteRecovery => {
+            msg!("Instruction: Complete recovery");
+            process_complete_recovery(program_id, accounts)
+        }
+        UpgradeInstruction::CancelRecovery(args) => {
+            msg!("Instruction: Cancel recovery");
+            process_cancel_recovery(program_id, accounts, args)
+        }
+        UpgradeInstruction::CloseAdmin(args) => {
+            msg!("Instruction: Close admin");
+            process_close_admin(program_id, accounts, args)
+        }
+        UpgradeInstruction::MigrateAdmin => {
+            msg!("Instruction: Migrate admin");
+            process_migrate_admin(program_id, accounts)
+        }
+        UpgradeInstruction::Pause(args) => {
+            msg!("Instruction: Pause");
+            process_set_paused(program_id, accounts, args, true)
+        }
+        UpgradeInstruction::Unpause(args) => {
+            msg!("Instruction: Unpause");
+            process_set_paused(program_id, accounts, args, false)
+        }
+        UpgradeInstruction::SetManagedContracts(args) => {
+            msg!("Instruction: Set managed contracts");
+            process_set_managed_contracts(program_id, accounts, args)
+        }
+        UpgradeInstruction::SetMetadata(args) => {
+            msg!("Instruction: Set metadata");
+            process_set_metadata(program_id, accounts, args)
+        }
+        UpgradeInstruction::Deactivate(args) => {
+            msg!("Instruction: Deactivate");
+            process_set_active(program_id, accounts, args, false)
+        }
+        UpgradeInstruction::Reactivate(args) => {
+            msg!("Instruction: Reactivate");
+            process_set_active(program_id, accounts, args, true)
+        }
+        UpgradeInstruction::ExtendExpiry(args) => {
+            msg!("Instruction: Extend expiry");
+            process_extend_expiry(program_id, accounts, args)
+        }
+        UpgradeInstruction::SetExecutors(args) => {
+            msg!("Instruction: Set executors");
+            process_set_executors(program_id, accounts, args)
+        }
+        UpgradeInstruction::ProposeUpgrade(args) => {
+            msg!("Instruction: Propose upgrade");
+            process_propose_upgrade(program_id, accounts, args)
+        }
+        UpgradeInstruction::ExecuteUpgrade => {
+            msg!("Instruction: Execute upgrade");
+            process_execute_upgrade(program_id, accounts)
+        }
+        UpgradeInstruction::SetUpgradeDelay(args) => {
+            msg!("Instruction: Set upgrade delay");
+            process_set_upgrade_delay(program_id, accounts, args)
+        }
+        UpgradeInstruction::CancelUpgrade(args) => {
+            msg!("Instruction: Cancel upgrade");
+            process_cancel_upgrade(program_id, accounts, args)
+        }
+        UpgradeInstruction::SetBufferAuthority => {
+            msg!("Instruction: Set buffer authority");
+            process_set_buffer_authority(program_id, accounts)
+        }
+        UpgradeInstruction::InitUpgradeHistory => {
+            msg!("Instruction: Init upgrade history");
+            process_init_upgrade_history(program_id, accounts)
+        }
+        UpgradeInstruction::RegisterSnapshot(args) => {
+            msg!("Instruction: Register snapshot");
+            process_register_snapshot(program_id, accounts, args)
+        }
+        UpgradeInstruction::Rollback(args) => {
+            msg!("Instruction: Rollback");
+            process_rollback(program_id, accounts, args)
+        }
+        UpgradeInstruction::SetUpgradeInterval(args) => {
+            msg!("Instruction: Set upgrade interval");
+            process_set_upgrade_interval(program_id, accounts, args)
+        }
+        UpgradeInstruction::BatchUpgrade(args) => {
+            msg!("Instruction: Batch upgrade");
+            process_batch_upgrade(program_id, accounts, args)
+        }
+        UpgradeInstruction::SetImmutable(args) => {
+            msg!("Instruction: Set immutable");
+            process_set_immutable(program_id, accounts, args)
+        }
+        UpgradeInstruction::Deploy(args) => {
+            msg!("Instruction: Deploy");
+            process_deploy(program_id, accounts, args)
+        }
+        UpgradeInstruction::ExtendProgram(args) => {
+            msg!("Instruction: Extend program");
+            process_extend_program(program_id, accounts, args)
+        }
+        UpgradeInstruction::CreateBuffer(args) => {
+            msg!("Instruction: Create buffer");
+            process_create_buffer(program_id, accounts, args)
+        }
+        UpgradeInstruction::WriteBufferChunk(args) => {
+            msg!("Instruction: Write buffer chunk");
+            process_write_buffer_chunk(program_id, accounts, args)
+        }
+        UpgradeInstruction::FinalizeBuffer => {
+            msg!("Instruction: Finalize buffer");
+            process_finalize_buffer(program_id, accounts)
+        }
+        UpgradeInstruction::CloseBuffer(args) => {
+            msg!("Instruction: Close buffer");
+            process_close_buffer(program_id, accounts, args)
+        }
+        UpgradeInstruction::SetRoles(args) => {
+            msg!("Instruction: Set roles");
+            process_set_roles(program_id, accounts, args)
+        }
+        UpgradeInstruction::ApproveUpgrade(args) => {
+            msg!("Instruction: Approve upgrade");
+            process_approve_upgrade(program_id, accounts, args)
+        }
+        UpgradeInstruction::SetVetoKey(args) => {
+            msg!("Instruction: Set veto key");
+            process_set_veto_key(program_id, accounts, args)
+        }
+        UpgradeInstruction::VetoUpgrade(args) => {
+            msg!("Instruction: Veto upgrade");
+            process_veto_upgrade(program_id, accounts, args)
+        }
+        UpgradeInstruction::SetHealthcheck(args) => {
+            msg!("Instruction: Set healthcheck");
+            process_set_healthcheck(program_id, accounts, args)
+        }
+        UpgradeInstruction::RunHealthcheck => {
+            msg!("Instruction: Run healthcheck");
+            process_run_healthcheck(program_id, accounts)
+        }
+        UpgradeInstruction::SetVerifiedBuildHash(args) => {
+            msg!("Instruction: Set verified build hash");
+            process_set_verified_build_hash(program_id, accounts, args)
+        }
+        UpgradeInstruction::ResetBuffer => {
+            msg!("Instruction: Reset buffer");
+            process_reset_buffer(program_id, accounts)
         }
     }
 }
@@ -42,51 +233,109 @@ pub fn process_instruction<'a>(
 pub fn process_init_admin<'a>(
     program_id: &'a Pubkey,
     accounts: &'a [AccountInfo<'a>],
-    public_key: [u8; SECP256K1_PUBLIC_KEY_LENGTH],
-    upgrade_program: Pubkey,
+    args: InitializeAdminArgs,
 ) -> ProgramResult {
+    validate_signer_set(&args.public_keys, &args.weights, args.threshold, args.signers_root, &args.upgrade_key, args.curve)?;
+
+    // EIP-712 typed data hash is defined with Keccak-256 only
+    if args.hashing_mode == HashingMode::Eip712 && args.hash_algorithm != HashAlgorithm::Keccak {
+        return Err(UpgradeError::InvalidHashAlgorithm.into());
+    }
+
+    validate_managed_contracts(&args.contract, &args.managed_contracts)?;
+    validate_executors(&args.executors)?;
+
+    if let Some(expires_at_slot) = args.expires_at_slot {
+        if expires_at_slot <= Clock::get()?.slot {
+            return Err(UpgradeError::InvalidExpiry.into());
+        }
+    }
+
+    if Clock::get()?.slot.checked_add(args.key_change_delay).is_none() {
+        return Err(UpgradeError::MathOverflow.into());
+    }
+
+    if let Some(guardian) = &args.guardian {
+        validate_guardian(guardian, &args.public_keys, &args.upgrade_key)?;
+        if args.recovery_delay < MIN_RECOVERY_DELAY || Clock::get()?.slot.checked_add(args.recovery_delay).is_none() {
+            return Err(UpgradeError::InvalidRecoveryDelay.into());
+        }
+    }
+
     let account_info_iter = &mut accounts.iter();
 
     let upgrade_admin_info = next_account_info(account_info_iter)?;
-    let fee_payer_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = PayerAccount::load(next_account_info(account_info_iter)?)?.info;
     let system_program = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
+    let admin_history_info = next_account_info(account_info_iter)?;
 
-    let (upgrade_key, bump) = Pubkey::find_program_address(&[PDA_ADMIN_SEED.as_bytes(), upgrade_program.as_ref()], &program_id);
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+
+    let (upgrade_key, bump) = pda::find_admin_address(program_id, &args.contract);
     if upgrade_key != *upgrade_admin_info.key {
         return Err(UpgradeError::WrongAdmin.into());
     }
 
     let rent = Rent::from_account_info(rent_info)?;
 
-    let instruction = system_instruction::create_account(
-        fee_payer_info.key,
-        upgrade_admin_info.key,
-        rent.minimum_balance(MAX_ADMIN_SIZE),
-        MAX_ADMIN_SIZE as u64,
-        program_id,
-    );
+    create_pda_account::<UpgradeError>(upgrade_admin_info, fee_payer_info, system_program, &rent, MAX_ADMIN_SIZE, program_id, &[PDA_ADMIN_SEED.as_bytes(), args.contract.as_ref(), &[bump]])?;
 
-    invoke_signed(
-        &instruction,
-        &[
-            fee_payer_info.clone(),
-            upgrade_admin_info.clone(),
-            system_program.clone(),
-        ],
-        &[&[PDA_ADMIN_SEED.as_bytes(), upgrade_program.as_ref(), &[bump]]],
-    )?;
+    create_admin_history(program_id, admin_history_info, upgrade_admin_info.key, fee_payer_info, system_program, &rent)?;
 
     let mut upgrade_admin: UpgradeAdmin = BorshDeserialize::deserialize(&mut upgrade_admin_info.data.borrow_mut().as_ref())?;
     if upgrade_admin.is_initialized {
         return Err(UpgradeError::AlreadyInUse.into());
     }
 
-    upgrade_admin.contract = upgrade_program;
-    upgrade_admin.public_key = public_key;
+    upgrade_admin.version = ADMIN_VERSION;
+    upgrade_admin.contract = args.contract;
+    upgrade_admin.public_keys = args.public_keys;
+    upgrade_admin.weights = args.weights;
+    upgrade_admin.threshold = args.threshold;
     upgrade_admin.is_initialized = true;
-    upgrade_admin.nonce = 0;
-    upgrade_admin.serialize(&mut *upgrade_admin_info.data.borrow_mut())?;
+    upgrade_admin.upgrade_nonce = 0;
+    upgrade_admin.authority_nonce = 0;
+    upgrade_admin.key_nonce = 0;
+    upgrade_admin.hashing_mode = args.hashing_mode;
+    upgrade_admin.domain = args.domain;
+    upgrade_admin.signers_root = args.signers_root;
+    upgrade_admin.upgrade_key = args.upgrade_key;
+    upgrade_admin.pending_key_change = None;
+    upgrade_admin.key_change_delay = args.key_change_delay;
+    upgrade_admin.curve = args.curve;
+    upgrade_admin.hash_algorithm = args.hash_algorithm;
+    upgrade_admin.guardian = args.guardian;
+    upgrade_admin.recovery_delay = args.recovery_delay;
+    upgrade_admin.pending_recovery = None;
+    upgrade_admin.paused = false;
+    upgrade_admin.managed_contracts = args.managed_contracts;
+    upgrade_admin.bump = bump;
+    upgrade_admin.active = true;
+    upgrade_admin.expires_at_slot = args.expires_at_slot;
+    upgrade_admin.executors = args.executors;
+    upgrade_admin.reimbursement = args.reimbursement;
+    upgrade_admin.min_delay_slots = args.min_delay_slots;
+    upgrade_admin.min_upgrade_interval_slots = args.min_upgrade_interval_slots;
+    upgrade_admin.last_upgrade_slot = None;
+    upgrade_admin.immutable = false;
+    upgrade_admin.roles = vec![];
+    upgrade_admin.approval_threshold = 0;
+    upgrade_admin.veto_key = None;
+    upgrade_admin.healthchecks = vec![];
+    upgrade_admin.unverified_upgrade = None;
+    upgrade_admin.verified_builds = vec![];
+    upgrade_admin.nonce_width = args.nonce_width;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+
+    UpgradeEvent::AdminInitialized(AdminInitialized {
+        admin: *upgrade_admin_info.key,
+        contract: upgrade_admin.contract,
+        public_keys: upgrade_admin.public_keys,
+        threshold: upgrade_admin.threshold,
+    }).emit();
+    set_result(&AdminResult { admin: *upgrade_admin_info.key })?;
     Ok(())
 }
 
@@ -94,165 +343,3305 @@ pub fn process_init_admin<'a>(
 pub fn process_change_public_key<'a>(
     program_id: &'a Pubkey,
     accounts: &'a [AccountInfo<'a>],
-    new_public_key: [u8; SECP256K1_PUBLIC_KEY_LENGTH],
-    signature: [u8; SECP256K1_SIGNATURE_LENGTH],
-    recovery_id: u8,
+    args: ChangePublicKeyArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let AdminAccount { info: upgrade_admin_info, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, next_account_info(account_info_iter)?)?;
+    let revoked_keys_info = next_account_info(account_info_iter)?;
+    let admin_history_info = next_account_info(account_info_iter)?;
+
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    validate_signer_set(&args.new_public_keys, &args.new_weights, args.new_threshold, args.new_signers_root, &args.new_upgrade_key, upgrade_admin.curve)?;
+    verify_not_revoked(program_id, revoked_keys_info, upgrade_admin_info.key, &args.new_public_keys, &args.new_upgrade_key)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.key_nonce)?;
+
+    if upgrade_admin.pending_key_change.is_some() {
+        return Err(UpgradeError::KeyChangePending.into());
+    }
+
+    let hash = change_public_key_hash(program_id, &upgrade_admin, &args.new_public_keys, &args.new_weights, args.new_threshold, args.new_signers_root, &args.new_upgrade_key, args.expires_at);
+    verify_signatures(
+        hash.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    let slot = Clock::get()?.slot;
+    record_history(program_id, admin_history_info, upgrade_admin_info.key, HistoryEntry {
+        slot,
+        operation: HistoryOperation::ChangePublicKey,
+        contract: upgrade_admin.contract,
+        old_value: signer_set_hash(&upgrade_admin.public_keys, &upgrade_admin.weights, upgrade_admin.threshold, upgrade_admin.signers_root, &upgrade_admin.upgrade_key)?,
+        new_value: signer_set_hash(&args.new_public_keys, &args.new_weights, args.new_threshold, args.new_signers_root, &args.new_upgrade_key)?,
+        signature_hash: hash.to_bytes(),
+    })?;
+
+    let activation_slot = slot.checked_add(upgrade_admin.key_change_delay).ok_or(UpgradeError::MathOverflow)?;
+    msg!("Key change can be activated at slot {}", activation_slot);
+
+    upgrade_admin.pending_key_change = Some(PendingKeyChange {
+        public_keys: args.new_public_keys,
+        weights: args.new_weights,
+        threshold: args.new_threshold,
+        signers_root: args.new_signers_root,
+        upgrade_key: args.new_upgrade_key,
+        activation_slot,
+    });
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_activate_key<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let revoked_keys_info = next_account_info(account_info_iter)?;
 
-    let mut upgrade_admin: UpgradeAdmin = BorshDeserialize::deserialize(&mut upgrade_admin_info.data.borrow_mut().as_ref())?;
-    if !upgrade_admin.is_initialized {
-        return Err(UpgradeError::NotInitialized.into());
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    let pending = upgrade_admin.pending_key_change.take().ok_or(UpgradeError::NoPendingKeyChange)?;
+    if Clock::get()?.slot < pending.activation_slot {
+        return Err(UpgradeError::KeyChangeDelayNotElapsed.into());
     }
 
-    let (upgrade_admin_key, _) = Pubkey::find_program_address(&[PDA_ADMIN_SEED.as_bytes(),  upgrade_admin.contract.as_ref()], &program_id);
-    if upgrade_admin_key != *upgrade_admin_info.key {
-        return Err(UpgradeError::WrongSeeds.into());
+    // Keys could be revoked while the change was pending
+    verify_not_revoked(program_id, revoked_keys_info, upgrade_admin_info.key, &pending.public_keys, &pending.upgrade_key)?;
+
+    upgrade_admin.public_keys = pending.public_keys;
+    upgrade_admin.weights = pending.weights;
+    upgrade_admin.threshold = pending.threshold;
+    upgrade_admin.signers_root = pending.signers_root;
+    upgrade_admin.upgrade_key = pending.upgrade_key;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+
+    UpgradeEvent::KeyChanged(KeyChanged {
+        admin: *upgrade_admin_info.key,
+        public_keys: upgrade_admin.public_keys,
+        threshold: upgrade_admin.threshold,
+        nonce: upgrade_admin.key_nonce,
+    }).emit();
+    Ok(())
+}
+
+
+pub fn process_cancel_key_change<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: CancelKeyChangeArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    if upgrade_admin.pending_key_change.is_none() {
+        return Err(UpgradeError::NoPendingKeyChange.into());
     }
 
-    verify_ecdsa_signature(
-        solana_program::keccak::hash(
-            &[
-                upgrade_admin.contract.as_ref(),
-                upgrade_admin.nonce.to_be_bytes().as_ref(),
-                HASH_CONSTANT.as_bytes(),
-                new_public_key.as_ref(),
-            ].concat()
-        ).as_ref(),
-        signature.as_slice(),
-        recovery_id,
-        upgrade_admin.public_key,
+    verify_signatures(
+        cancel_key_change_hash(program_id, &upgrade_admin, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
     )?;
 
-    upgrade_admin.public_key = new_public_key;
-    upgrade_admin.nonce = upgrade_admin.nonce + 1;
-    upgrade_admin.serialize(&mut *upgrade_admin_info.data.borrow_mut())?;
+    upgrade_admin.pending_key_change = None;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
     Ok(())
 }
 
 
-pub fn process_change_authority<'a>(
+pub fn process_revoke_keys<'a>(
     program_id: &'a Pubkey,
     accounts: &'a [AccountInfo<'a>],
-    signature: [u8; SECP256K1_SIGNATURE_LENGTH],
-    recovery_id: u8,
+    args: RevokeKeysArgs,
 ) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
     let account_info_iter = &mut accounts.iter();
     let upgrade_admin_info = next_account_info(account_info_iter)?;
-    let upgrade_program_data = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
+    let revoked_keys_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
 
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
 
-    let mut upgrade_admin: UpgradeAdmin = BorshDeserialize::deserialize(&mut upgrade_admin_info.data.borrow_mut().as_ref())?;
-    if !upgrade_admin.is_initialized {
-        return Err(UpgradeError::NotInitialized.into());
-    }
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
 
-    let (upgrade_admin_key, bump) = Pubkey::find_program_address(&[PDA_ADMIN_SEED.as_bytes(), upgrade_admin.contract.as_ref()], &program_id);
-    if upgrade_admin_key != *upgrade_admin_info.key {
+    let (revoked_keys_key, bump) = pda::find_revoked_keys_address(program_id, upgrade_admin_info.key);
+    if revoked_keys_key != *revoked_keys_info.key {
         return Err(UpgradeError::WrongSeeds.into());
     }
 
-    verify_ecdsa_signature(
-        solana_program::keccak::hash(
-            &[
-                upgrade_admin.contract.as_ref(),
-                upgrade_admin.nonce.to_be_bytes().as_ref(),
-                HASH_CONSTANT.as_bytes(),
-                authority.key.as_ref(),
-            ].concat()
-        ).as_ref(),
-        signature.as_slice(),
-        recovery_id,
-        upgrade_admin.public_key,
+    verify_signatures(
+        revoke_keys_hash(program_id, &upgrade_admin, &args.keys, args.expires_at)?.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
     )?;
 
+    if revoked_keys_info.data_is_empty() {
+        let rent = Rent::from_account_info(rent_info)?;
 
-    let instruction = solana_program::bpf_loader_upgradeable::set_upgrade_authority(
-        &upgrade_admin.contract,
-        upgrade_admin_info.key,
-        Some(authority.key),
-    );
+        create_pda_account::<UpgradeError>(revoked_keys_info, fee_payer_info, system_program, &rent, MAX_REVOKED_KEYS_SIZE, program_id, &[PDA_REVOKED_KEYS_SEED.as_bytes(), upgrade_admin_info.key.as_ref(), &[bump]])?;
+    }
 
-    invoke_signed(
-        &instruction,
-        &[
-            upgrade_program_data.clone(),
-            upgrade_admin_info.clone(),
-            authority.clone(),
-        ],
-        &[&[PDA_ADMIN_SEED.as_bytes(),  upgrade_admin.contract.as_ref(), &[bump]]],
+    let mut revoked_keys: RevokedKeys = BorshDeserialize::deserialize(&mut revoked_keys_info.data.borrow_mut().as_ref())?;
+    if !revoked_keys.is_initialized {
+        revoked_keys.admin = *upgrade_admin_info.key;
+        revoked_keys.is_initialized = true;
+    }
+
+    for key in args.keys {
+        if !revoked_keys.keys.contains(&key) {
+            revoked_keys.keys.push(key);
+        }
+    }
+
+    if revoked_keys.keys.len() > MAX_REVOKED_KEYS {
+        return Err(UpgradeError::TooManyRevokedKeys.into());
+    }
+
+    revoked_keys.serialize(&mut *revoked_keys_info.data.borrow_mut())?;
+
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_verify_batch<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: VerifyBatchArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    if args.operation_hashes.is_empty() || args.operation_hashes.len() > MAX_BATCH_OPERATIONS {
+        return Err(UpgradeError::TooManyBatchOperations.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let batch_approval_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    let (batch_approval_key, bump) = pda::find_batch_approval_address(program_id, upgrade_admin_info.key);
+    if batch_approval_key != *batch_approval_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    verify_not_paused(&upgrade_admin)?;
+
+    // One signature over the merkle root approves all operations of the batch
+    verify_upgrade_signatures(
+        verify_batch_hash(program_id, &upgrade_admin, &operations_root(&args.operation_hashes), args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
     )?;
 
+    if batch_approval_info.data_is_empty() {
+        let rent = Rent::from_account_info(rent_info)?;
+
+        create_pda_account::<UpgradeError>(batch_approval_info, fee_payer_info, system_program, &rent, MAX_BATCH_APPROVAL_SIZE, program_id, &[PDA_BATCH_APPROVAL_SEED.as_bytes(), upgrade_admin_info.key.as_ref(), &[bump]])?;
+    }
+
+    let batch_approval = BatchApproval {
+        admin: *upgrade_admin_info.key,
+        authority_hash: upgrade_authority_hash(&upgrade_admin)?,
+        operations: args.operation_hashes,
+        expires_at: args.expires_at,
+        is_initialized: true,
+    };
+    batch_approval.serialize(&mut *batch_approval_info.data.borrow_mut())?;
 
-    upgrade_admin.nonce = upgrade_admin.nonce + 1;
-    upgrade_admin.serialize(&mut *upgrade_admin_info.data.borrow_mut())?;
+    increment_nonce(&mut upgrade_admin.upgrade_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.upgrade_nonce })?;
     Ok(())
 }
 
 
-pub fn process_upgrade<'a>(
+pub fn process_create_session<'a>(
     program_id: &'a Pubkey,
     accounts: &'a [AccountInfo<'a>],
-    signature: [u8; SECP256K1_SIGNATURE_LENGTH],
-    recovery_id: u8,
+    args: CreateSessionArgs,
 ) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    if args.permissions == 0 || args.permissions & !SESSION_ALLOWED_PERMISSIONS != 0 {
+        return Err(UpgradeError::InvalidSessionPermissions.into());
+    }
+
+    if args.expiry_slot < Clock::get()?.slot {
+        return Err(UpgradeError::SessionExpired.into());
+    }
+
     let account_info_iter = &mut accounts.iter();
     let upgrade_admin_info = next_account_info(account_info_iter)?;
-    let upgrade_program_data = next_account_info(account_info_iter)?;
-    let upgrade_program = next_account_info(account_info_iter)?;
-    let upgrade_buffer = next_account_info(account_info_iter)?;
-    let upgrade_spill = next_account_info(account_info_iter)?;
+    let revoked_keys_info = next_account_info(account_info_iter)?;
+    let session_key_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
-    let clock_info = next_account_info(account_info_iter)?;
 
-    let (upgrade_admin_key, bump) = Pubkey::find_program_address(&[PDA_ADMIN_SEED.as_bytes(), upgrade_program.key.as_ref()], &program_id);
-    if upgrade_admin_key != *upgrade_admin_info.key {
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    let (session_key_address, bump) = pda::find_session_key_address(program_id, upgrade_admin_info.key);
+    if session_key_address != *session_key_info.key {
         return Err(UpgradeError::WrongSeeds.into());
     }
 
-    let mut upgrade_admin: UpgradeAdmin = BorshDeserialize::deserialize(&mut upgrade_admin_info.data.borrow_mut().as_ref())?;
-    if !upgrade_admin.is_initialized {
-        return Err(UpgradeError::NotInitialized.into());
+    verify_not_paused(&upgrade_admin)?;
+    validate_key(&args.session_key, upgrade_admin.curve)?;
+    verify_not_revoked(program_id, revoked_keys_info, upgrade_admin_info.key, core::slice::from_ref(&args.session_key), &None)?;
+
+    verify_signatures(
+        create_session_hash(program_id, &upgrade_admin, &args.session_key, args.permissions, args.expiry_slot, args.expires_at)?.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    if session_key_info.data_is_empty() {
+        let rent = Rent::from_account_info(rent_info)?;
+
+        create_pda_account::<UpgradeError>(session_key_info, fee_payer_info, system_program, &rent, MAX_SESSION_KEY_SIZE, program_id, &[PDA_SESSION_KEY_SEED.as_bytes(), upgrade_admin_info.key.as_ref(), &[bump]])?;
     }
 
-    verify_ecdsa_signature(
-        solana_program::keccak::hash(
-            &[
-                upgrade_admin.contract.as_ref(),
-                upgrade_admin.nonce.to_be_bytes().as_ref(),
-                HASH_CONSTANT.as_bytes(),
-                upgrade_buffer.key.as_ref(),
-            ].concat()
-        ).as_ref(),
-        signature.as_slice(),
-        recovery_id,
-        upgrade_admin.public_key,
-    )?;
-
-    let instruction = solana_program::bpf_loader_upgradeable::upgrade(
-        upgrade_program.key,
-        upgrade_buffer.key,
-        &upgrade_admin_key,
-        upgrade_spill.key,
-    );
+    msg!("Session key can be used until slot {}", args.expiry_slot);
 
-    invoke_signed(
-        &instruction,
-        &[
-            upgrade_program_data.clone(),
-            upgrade_program.clone(),
-            upgrade_buffer.clone(),
-            upgrade_spill.clone(),
-            rent_info.clone(),
-            clock_info.clone(),
-            upgrade_admin_info.clone(),
-        ],
-        &[&[PDA_ADMIN_SEED.as_bytes(), upgrade_program.key.as_ref(), &[bump]]],
+    let session_key = SessionKey {
+        admin: *upgrade_admin_info.key,
+        key: args.session_key,
+        permissions: args.permissions,
+        expiry_slot: args.expiry_slot,
+        is_initialized: true,
+    };
+    session_key.serialize(&mut *session_key_info.data.borrow_mut())?;
+
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_start_recovery<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: StartRecoveryArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let revoked_keys_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    let guardian = upgrade_admin.guardian.clone().ok_or(UpgradeError::NoGuardian)?;
+
+    validate_signer_set(&args.new_public_keys, &args.new_weights, args.new_threshold, None, &None, upgrade_admin.curve)?;
+    validate_guardian(&guardian, &args.new_public_keys, &None)?;
+    verify_not_revoked(program_id, revoked_keys_info, upgrade_admin_info.key, &args.new_public_keys, &None)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.key_nonce)?;
+
+    if upgrade_admin.pending_recovery.is_some() {
+        return Err(UpgradeError::RecoveryPending.into());
+    }
+
+    verify_signers(
+        start_recovery_hash(program_id, &upgrade_admin, &args.new_public_keys, &args.new_weights, args.new_threshold, args.expires_at)?.as_ref(),
+        &args.signatures,
+        accounts,
+        core::slice::from_ref(&guardian),
+        &[],
+        1,
+        Curve::Secp256k1,
+    )?;
+
+    let activation_slot = Clock::get()?.slot.checked_add(upgrade_admin.recovery_delay).ok_or(UpgradeError::MathOverflow)?;
+    msg!("Recovery can be completed at slot {}", activation_slot);
+
+    upgrade_admin.pending_recovery = Some(PendingRecovery {
+        public_keys: args.new_public_keys,
+        weights: args.new_weights,
+        threshold: args.new_threshold,
+        activation_slot,
+    });
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_complete_recovery<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let revoked_keys_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    let recovery = upgrade_admin.pending_recovery.take().ok_or(UpgradeError::NoPendingRecovery)?;
+    if Clock::get()?.slot < recovery.activation_slot {
+        return Err(UpgradeError::RecoveryDelayNotElapsed.into());
+    }
+
+    // Keys could be revoked while the recovery was pending
+    verify_not_revoked(program_id, revoked_keys_info, upgrade_admin_info.key, &recovery.public_keys, &None)?;
+
+    // Merkle signers, hot key and staged key change could be controlled by the lost keys
+    upgrade_admin.public_keys = recovery.public_keys;
+    upgrade_admin.weights = recovery.weights;
+    upgrade_admin.threshold = recovery.threshold;
+    upgrade_admin.signers_root = None;
+    upgrade_admin.upgrade_key = None;
+    upgrade_admin.pending_key_change = None;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+
+    UpgradeEvent::KeyChanged(KeyChanged {
+        admin: *upgrade_admin_info.key,
+        public_keys: upgrade_admin.public_keys,
+        threshold: upgrade_admin.threshold,
+        nonce: upgrade_admin.key_nonce,
+    }).emit();
+    Ok(())
+}
+
+
+pub fn process_cancel_recovery<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: CancelRecoveryArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    if upgrade_admin.pending_recovery.is_none() {
+        return Err(UpgradeError::NoPendingRecovery.into());
+    }
+
+    verify_signatures(
+        cancel_recovery_hash(program_id, &upgrade_admin, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
     )?;
 
-    upgrade_admin.nonce = upgrade_admin.nonce + 1;
-    upgrade_admin.serialize(&mut *upgrade_admin_info.data.borrow_mut())?;
+    upgrade_admin.pending_recovery = None;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
     Ok(())
-}
\ No newline at end of file
+}
+
+
+pub fn process_change_authority<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ChangeAuthorityArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let AdminAccount { info: upgrade_admin_info, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, next_account_info(account_info_iter)?)?;
+    let upgrade_program_data = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let _bpf_loader = next_account_info(account_info_iter)?;
+    let admin_history_info = next_account_info(account_info_iter)?;
+
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, &args.contract)?;
+    let upgrade_program_data = ProgramDataAccount::load(&args.contract, upgrade_program_data)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.authority_nonce)?;
+
+    let hash = change_authority_hash(program_id, &upgrade_admin, &args.contract, authority.key, args.expires_at);
+    verify_signatures(
+        hash.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    record_history(program_id, admin_history_info, upgrade_admin_info.key, HistoryEntry {
+        slot: Clock::get()?.slot,
+        operation: HistoryOperation::ChangeAuthority,
+        contract: args.contract,
+        old_value: upgrade_admin_info.key.to_bytes(),
+        new_value: authority.key.to_bytes(),
+        signature_hash: hash.to_bytes(),
+    })?;
+
+
+    let instruction = solana_program::bpf_loader_upgradeable::set_upgrade_authority(
+        &args.contract,
+        upgrade_admin_info.key,
+        Some(authority.key),
+    );
+
+    invoke_signed(
+        &instruction,
+        &[
+            upgrade_program_data.info.clone(),
+            upgrade_admin_info.clone(),
+            authority.clone(),
+        ],
+        &[&[PDA_ADMIN_SEED.as_bytes(),  upgrade_admin.contract.as_ref(), &[upgrade_admin.bump]]],
+    )?;
+
+    UpgradeEvent::AuthorityChanged(AuthorityChanged {
+        admin: *upgrade_admin_info.key,
+        contract: args.contract,
+        authority: Some(*authority.key),
+        nonce: upgrade_admin.authority_nonce,
+    }).emit();
+
+    increment_nonce(&mut upgrade_admin.authority_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.authority_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_set_immutable<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetImmutableArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let upgrade_program_data = next_account_info(account_info_iter)?;
+    let _bpf_loader = next_account_info(account_info_iter)?;
+    let admin_history_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, &args.contract)?;
+    verify_program_data(&args.contract, upgrade_program_data)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.authority_nonce)?;
+
+    // Deactivated admin would leave managed contracts without upgrades
+    let is_primary = args.contract == upgrade_admin.contract;
+    if is_primary && !upgrade_admin.managed_contracts.is_empty() {
+        return Err(UpgradeError::ManagedContractsNotEmpty.into());
+    }
+
+    let hash = set_immutable_hash(program_id, &upgrade_admin, &args.contract, args.expires_at);
+    verify_signatures(
+        hash.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    record_history(program_id, admin_history_info, upgrade_admin_info.key, HistoryEntry {
+        slot: Clock::get()?.slot,
+        operation: HistoryOperation::SetImmutable,
+        contract: args.contract,
+        old_value: upgrade_admin_info.key.to_bytes(),
+        new_value: [0; 32],
+        signature_hash: hash.to_bytes(),
+    })?;
+
+    let instruction = solana_program::bpf_loader_upgradeable::set_upgrade_authority(
+        &args.contract,
+        upgrade_admin_info.key,
+        None,
+    );
+
+    invoke_signed(
+        &instruction,
+        &[
+            upgrade_program_data.clone(),
+            upgrade_admin_info.clone(),
+        ],
+        &[&[PDA_ADMIN_SEED.as_bytes(),  upgrade_admin.contract.as_ref(), &[upgrade_admin.bump]]],
+    )?;
+
+    if is_primary {
+        upgrade_admin.immutable = true;
+        upgrade_admin.active = false;
+    } else {
+        upgrade_admin.managed_contracts.retain(|contract| *contract != args.contract);
+    }
+
+    UpgradeEvent::AuthorityChanged(AuthorityChanged {
+        admin: *upgrade_admin_info.key,
+        contract: args.contract,
+        authority: None,
+        nonce: upgrade_admin.authority_nonce,
+    }).emit();
+
+    increment_nonce(&mut upgrade_admin.authority_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.authority_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_close_admin<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: CloseAdminArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let upgrade_program_data = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    if !upgrade_admin.managed_contracts.is_empty() {
+        return Err(UpgradeError::ManagedContractsNotEmpty.into());
+    }
+
+    // Closing the authority would make the contract non-upgradable forever
+    if program_upgrade_authority(&upgrade_admin.contract, upgrade_program_data)? == Some(upgrade_admin_key) {
+        return Err(UpgradeError::AdminIsAuthority.into());
+    }
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.authority_nonce)?;
+
+    verify_signatures(
+        close_admin_hash(program_id, &upgrade_admin, destination_info.key, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    let lamports = upgrade_admin_info.lamports();
+    **destination_info.lamports.borrow_mut() = destination_info.lamports().checked_add(lamports).ok_or(ProgramError::ArithmeticOverflow)?;
+    **upgrade_admin_info.lamports.borrow_mut() = 0;
+    upgrade_admin_info.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+
+pub fn process_migrate_admin<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let admin_history_info = next_account_info(account_info_iter)?;
+
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+
+    verify_admin_owner(program_id, upgrade_admin_info)?;
+
+    let v1 = match VersionedUpgradeAdmin::unpack(&upgrade_admin_info.data.borrow())? {
+        VersionedUpgradeAdmin::V1(v1) => v1,
+        VersionedUpgradeAdmin::V2(_) => return Err(UpgradeError::AdminAlreadyMigrated.into()),
+    };
+
+    if !v1.is_initialized {
+        return Err(UpgradeError::NotInitialized.into());
+    }
+
+    // V1 layout has no bump, so it is searched once here and stored
+    let (upgrade_admin_key, bump) = pda::find_admin_address(program_id, &v1.contract);
+    if upgrade_admin_key != *upgrade_admin_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    let rent = Rent::from_account_info(rent_info)?;
+    let required_lamports = rent.minimum_balance(MAX_ADMIN_SIZE).saturating_sub(upgrade_admin_info.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(fee_payer_info.key, upgrade_admin_info.key, required_lamports),
+            &[
+                fee_payer_info.clone(),
+                upgrade_admin_info.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
+    upgrade_admin_info.realloc(MAX_ADMIN_SIZE, true)?;
+
+    create_admin_history(program_id, admin_history_info, upgrade_admin_info.key, fee_payer_info, system_program, &rent)?;
+
+    // V1 nonce was shared by all operations, so every nonce continues from it to keep old signatures unusable
+    let upgrade_admin = UpgradeAdmin {
+        version: ADMIN_VERSION,
+        public_keys: vec![AdminKey::PublicKey(v1.public_key)],
+        weights: vec![],
+        threshold: 1,
+        contract: v1.contract,
+        upgrade_nonce: v1.nonce.into(),
+        authority_nonce: v1.nonce.into(),
+        key_nonce: v1.nonce.into(),
+        is_initialized: true,
+        hashing_mode: HashingMode::Legacy,
+        domain: [0u8; 32],
+        signers_root: None,
+        upgrade_key: None,
+        pending_key_change: None,
+        key_change_delay: 0,
+        curve: Curve::Secp256k1,
+        hash_algorithm: HashAlgorithm::Keccak,
+        guardian: None,
+        recovery_delay: 0,
+        pending_recovery: None,
+        paused: false,
+        managed_contracts: vec![],
+        bump,
+        name: [0u8; 32],
+        uri: String::new(),
+        active: true,
+        expires_at_slot: None,
+        executors: vec![],
+        reimbursement: 0,
+        min_delay_slots: 0,
+        min_upgrade_interval_slots: 0,
+        last_upgrade_slot: None,
+        immutable: false,
+        roles: vec![],
+        approval_threshold: 0,
+        veto_key: None,
+        healthchecks: vec![],
+        unverified_upgrade: None,
+        verified_builds: vec![],
+        // V1 signers sign 8 byte nonces
+        nonce_width: NonceWidth::U64,
+    };
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    Ok(())
+}
+
+
+pub fn process_set_paused<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: PauseArgs,
+    paused: bool,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_signatures(
+        set_paused_hash(program_id, &upgrade_admin, paused, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.paused = paused;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_set_managed_contracts<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetManagedContractsArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    validate_managed_contracts(&upgrade_admin.contract, &args.contracts)?;
+
+    // Removed contract that still has the admin as authority could never be upgraded again
+    for removed in upgrade_admin.managed_contracts.iter().filter(|contract| !args.contracts.contains(contract)) {
+        let (program_data_key, _) = pda::find_program_data_address(removed);
+        let program_data_info = accounts.iter().find(|info| *info.key == program_data_key).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if program_upgrade_authority(removed, program_data_info)? == Some(upgrade_admin_key) {
+            return Err(UpgradeError::AdminIsAuthority.into());
+        }
+    }
+
+    verify_signatures(
+        set_managed_contracts_hash(program_id, &upgrade_admin, &args.contracts, args.expires_at)?.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.managed_contracts = args.contracts;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_set_metadata<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetMetadataArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    if args.uri.len() > MAX_URI_LENGTH {
+        return Err(UpgradeError::UriTooLong.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_signatures(
+        set_metadata_hash(program_id, &upgrade_admin, &args.name, &args.uri, args.expires_at)?.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.name = args.name;
+    upgrade_admin.uri = args.uri;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_set_active<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: DeactivateArgs,
+    active: bool,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+
+    // Deactivation of deactivated admin is rejected as any other operation
+    if !active {
+        verify_active(&upgrade_admin)?;
+    } else if upgrade_admin.immutable {
+        return Err(UpgradeError::AdminImmutable.into());
+    }
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_signatures(
+        set_active_hash(program_id, &upgrade_admin, active, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.active = active;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_extend_expiry<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ExtendExpiryArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+
+    // Expiry can only be moved later (no expiry is the latest one), lapsed authority is renewed with a slot in the future
+    if let Some(expires_at_slot) = args.expires_at_slot {
+        let current = upgrade_admin.expires_at_slot.unwrap_or(u64::MAX);
+        if expires_at_slot <= current || expires_at_slot <= Clock::get()?.slot {
+            return Err(UpgradeError::InvalidExpiry.into());
+        }
+    }
+
+    verify_signatures(
+        extend_expiry_hash(program_id, &upgrade_admin, args.expires_at_slot, args.expires_at)?.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    if let Some(expires_at_slot) = args.expires_at_slot {
+        msg!("Admin expires at slot {}", expires_at_slot);
+    }
+
+    upgrade_admin.expires_at_slot = args.expires_at_slot;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_set_executors<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetExecutorsArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+    validate_executors(&args.executors)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_signatures(
+        set_executors_hash(program_id, &upgrade_admin, &args.executors, args.reimbursement, args.expires_at)?.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.executors = args.executors;
+    upgrade_admin.reimbursement = args.reimbursement;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_upgrade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: UpgradeArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let AdminAccount { info: upgrade_admin_info, key: upgrade_admin_key, admin: mut upgrade_admin } = AdminAccount::load(program_id, next_account_info(account_info_iter)?)?;
+    let upgrade_program_data = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+    let upgrade_buffer = next_account_info(account_info_iter)?;
+    let upgrade_spill = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let _upgrade_loader = next_account_info(account_info_iter)?;
+    let upgrade_history_info = next_account_info(account_info_iter)?;
+    let buffer_staging_info = next_account_info(account_info_iter)?;
+
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+    verify_sysvar::<UpgradeError>(clock_info, &sysvar::clock::id())?;
+    verify_distinct_accounts(&[upgrade_admin_info, upgrade_program_data, upgrade_program, upgrade_buffer, upgrade_spill, upgrade_history_info, buffer_staging_info])?;
+
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_direct_upgrade(&upgrade_admin)?;
+    verify_healthcheck_passed(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, upgrade_program.key)?;
+    let upgrade_program_data = ProgramDataAccount::load(upgrade_program.key, upgrade_program_data)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.upgrade_nonce)?;
+
+    let slot = Clock::from_account_info(clock_info)?.slot;
+    verify_upgrade_interval(&upgrade_admin, slot)?;
+
+    // Signatures cover the buffer contents, so rewriting the buffer after signing is rejected here instead of deployed
+    verify_buffer_code_hash(program_id, &upgrade_admin_key, upgrade_buffer, buffer_staging_info, &args.code_hash)?;
+
+    verify_build_hash(&upgrade_admin, upgrade_program.key, &args.code_hash)?;
+
+    let hash = upgrade_hash(program_id, &upgrade_admin, upgrade_program.key, upgrade_buffer.key, &args.code_hash, upgrade_spill.key, args.expires_at);
+    let (session_key_address, _) = pda::find_session_key_address(program_id, upgrade_admin_info.key);
+    let optional_accounts: Vec<&AccountInfo> = account_info_iter.collect();
+    let executor_info = find_executor(&upgrade_admin, &optional_accounts)?;
+
+    if let Some(session_key_info) = optional_accounts.iter().find(|info| *info.key == session_key_address) {
+        verify_session_signatures(hash.as_ref(), &args.signatures, accounts, session_key_info, SESSION_PERMISSION_UPGRADE, upgrade_admin.curve)?;
+    } else if let Some(batch_approval_info) = optional_accounts.iter().find(|info| info.owner == program_id) {
+        consume_batch_approval(program_id, batch_approval_info, &upgrade_admin, &hash.to_bytes())?;
+    } else {
+        verify_upgrade_signatures(hash.as_ref(), &args.signatures, &args.signer_proofs, accounts, &upgrade_admin)?;
+    }
+
+    invoke_upgrade(
+        &upgrade_admin,
+        &upgrade_admin_key,
+        &[
+            upgrade_program_data.info.clone(),
+            upgrade_program.clone(),
+            upgrade_buffer.clone(),
+            upgrade_spill.clone(),
+            rent_info.clone(),
+            clock_info.clone(),
+            upgrade_admin_info.clone(),
+        ],
+    )?;
+
+    record_upgrade(program_id, upgrade_history_info, &upgrade_admin_key, upgrade_program.key, UpgradeRecord {
+        slot,
+        buffer: *upgrade_buffer.key,
+        code_hash: args.code_hash,
+        executor: executor_info.map(|info| *info.key),
+        spill: *upgrade_spill.key,
+    })?;
+
+    UpgradeEvent::Upgraded(Upgraded {
+        admin: upgrade_admin_key,
+        contract: *upgrade_program.key,
+        buffer: *upgrade_buffer.key,
+        code_hash: args.code_hash,
+        nonce: upgrade_admin.upgrade_nonce,
+        slot,
+    }).emit();
+
+    close_buffer_staging(program_id, buffer_staging_info, upgrade_spill)?;
+
+    mark_unverified(&mut upgrade_admin, upgrade_program.key);
+    upgrade_admin.last_upgrade_slot = Some(slot);
+    increment_nonce(&mut upgrade_admin.upgrade_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+
+    if let Some(executor_info) = executor_info {
+        reimburse_executor(upgrade_admin_info, executor_info, &Rent::from_account_info(rent_info)?, upgrade_admin.reimbursement)?;
+    }
+
+    set_result(&UpgradeResult {
+        contract: *upgrade_program.key,
+        buffer: *upgrade_buffer.key,
+        code_hash: args.code_hash,
+        nonce: upgrade_admin.upgrade_nonce,
+    })?;
+    Ok(())
+}
+
+
+pub fn process_batch_upgrade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: BatchUpgradeArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    if args.code_hashes.is_empty() || args.code_hashes.len() > MAX_BATCH_UPGRADES {
+        return Err(UpgradeError::InvalidBatchUpgrade.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let upgrade_spill = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let _upgrade_loader = next_account_info(account_info_iter)?;
+
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+    verify_sysvar::<UpgradeError>(clock_info, &sysvar::clock::id())?;
+
+    // ProgramData, Program, Buffer, UpgradeHistory and BufferStaging accounts of every upgrade
+    let mut upgrade_groups = Vec::with_capacity(args.code_hashes.len());
+    for _ in 0..args.code_hashes.len() {
+        upgrade_groups.push([
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+        ]);
+    }
+
+    let mut upgrade_accounts = vec![upgrade_admin_info, upgrade_spill];
+    upgrade_accounts.extend(upgrade_groups.iter().flatten());
+    verify_distinct_accounts(&upgrade_accounts)?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_direct_upgrade(&upgrade_admin)?;
+    verify_healthcheck_passed(&upgrade_admin)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.upgrade_nonce)?;
+
+    let slot = Clock::from_account_info(clock_info)?.slot;
+    verify_upgrade_interval(&upgrade_admin, slot)?;
+
+    let mut upgrades: Vec<(Pubkey, Pubkey, [u8; 32])> = Vec::with_capacity(upgrade_groups.len());
+    for ([upgrade_program_data, upgrade_program, upgrade_buffer, _, buffer_staging_info], code_hash) in upgrade_groups.iter().zip(&args.code_hashes) {
+        verify_managed(&upgrade_admin, upgrade_program.key)?;
+        verify_program_data(upgrade_program.key, upgrade_program_data)?;
+        if upgrades.iter().any(|(program, _, _)| program == upgrade_program.key) {
+            return Err(UpgradeError::InvalidBatchUpgrade.into());
+        }
+
+        verify_buffer_code_hash(program_id, &upgrade_admin_key, upgrade_buffer, buffer_staging_info, code_hash)?;
+
+        verify_build_hash(&upgrade_admin, upgrade_program.key, code_hash)?;
+
+        upgrades.push((*upgrade_program.key, *upgrade_buffer.key, *code_hash));
+    }
+
+    // UpgradeAdmin tracks a single unverified upgrade, so healthchecks of several contracts can not be pending at once
+    let healthchecked = upgrades.iter()
+        .filter(|(program, _, _)| upgrade_admin.healthchecks.iter().any(|healthcheck| healthcheck.contract == *program))
+        .count();
+    if healthchecked > 1 {
+        return Err(UpgradeError::InvalidBatchUpgrade.into());
+    }
+
+    let optional_accounts: Vec<&AccountInfo> = account_info_iter.collect();
+    let executor_info = find_executor(&upgrade_admin, &optional_accounts)?;
+
+    verify_upgrade_signatures(
+        batch_upgrade_hash(program_id, &upgrade_admin, &upgrades, upgrade_spill.key, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    for ([upgrade_program_data, upgrade_program, upgrade_buffer, upgrade_history_info, buffer_staging_info], code_hash) in upgrade_groups.iter().zip(&args.code_hashes) {
+        invoke_upgrade(
+            &upgrade_admin,
+            &upgrade_admin_key,
+            &[
+                (*upgrade_program_data).clone(),
+                (*upgrade_program).clone(),
+                (*upgrade_buffer).clone(),
+                upgrade_spill.clone(),
+                rent_info.clone(),
+                clock_info.clone(),
+                upgrade_admin_info.clone(),
+            ],
+        )?;
+
+        record_upgrade(program_id, upgrade_history_info, &upgrade_admin_key, upgrade_program.key, UpgradeRecord {
+            slot,
+            buffer: *upgrade_buffer.key,
+            code_hash: *code_hash,
+            executor: executor_info.map(|info| *info.key),
+            spill: *upgrade_spill.key,
+        })?;
+
+        UpgradeEvent::Upgraded(Upgraded {
+            admin: upgrade_admin_key,
+            contract: *upgrade_program.key,
+            buffer: *upgrade_buffer.key,
+            code_hash: *code_hash,
+            nonce: upgrade_admin.upgrade_nonce,
+            slot,
+        }).emit();
+
+        close_buffer_staging(program_id, buffer_staging_info, upgrade_spill)?;
+        mark_unverified(&mut upgrade_admin, upgrade_program.key);
+    }
+
+    upgrade_admin.last_upgrade_slot = Some(slot);
+    increment_nonce(&mut upgrade_admin.upgrade_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+
+    if let Some(executor_info) = executor_info {
+        reimburse_executor(upgrade_admin_info, executor_info, &Rent::from_account_info(rent_info)?, upgrade_admin.reimbursement)?;
+    }
+
+    set_result(&NonceResult { nonce: upgrade_admin.upgrade_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_deploy<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: DeployArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let program_data_info = next_account_info(account_info_iter)?;
+    let program_info = next_account_info(account_info_iter)?;
+    let buffer_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let _bpf_loader = next_account_info(account_info_iter)?;
+
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+    verify_sysvar::<UpgradeError>(clock_info, &sysvar::clock::id())?;
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_distinct_accounts(&[upgrade_admin_info, fee_payer_info, program_data_info, program_info, buffer_info])?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.key_nonce)?;
+
+    // Deployed program other than the primary contract is upgraded by the same admin as managed one
+    if *program_info.key != upgrade_admin.contract {
+        upgrade_admin.managed_contracts.push(*program_info.key);
+        validate_managed_contracts(&upgrade_admin.contract, &upgrade_admin.managed_contracts)?;
+    }
+
+    verify_buffer_authority(buffer_info, &upgrade_admin_key)?;
+    let max_data_len = usize::try_from(args.max_data_len).map_err(|_| ProgramError::InvalidArgument)?;
+
+    verify_signatures(
+        deploy_hash(program_id, &upgrade_admin, program_info.key, args.max_data_len, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    let (program_data_key, _) = pda::find_program_data_address(program_info.key);
+    let instruction = solana_program::instruction::Instruction::new_with_bincode(
+        bpf_loader_upgradeable::id(),
+        &solana_program::loader_upgradeable_instruction::UpgradeableLoaderInstruction::DeployWithMaxDataLen { max_data_len },
+        vec![
+            solana_program::instruction::AccountMeta::new(*fee_payer_info.key, true),
+            solana_program::instruction::AccountMeta::new(program_data_key, false),
+            solana_program::instruction::AccountMeta::new(*program_info.key, false),
+            solana_program::instruction::AccountMeta::new(*buffer_info.key, false),
+            solana_program::instruction::AccountMeta::new_readonly(sysvar::rent::id(), false),
+            solana_program::instruction::AccountMeta::new_readonly(sysvar::clock::id(), false),
+            solana_program::instruction::AccountMeta::new_readonly(*system_program.key, false),
+            solana_program::instruction::AccountMeta::new_readonly(upgrade_admin_key, true),
+        ],
+    );
+
+    invoke_signed(
+        &instruction,
+        &[
+            fee_payer_info.clone(),
+            program_data_info.clone(),
+            program_info.clone(),
+            buffer_info.clone(),
+            rent_info.clone(),
+            clock_info.clone(),
+            system_program.clone(),
+            upgrade_admin_info.clone(),
+        ],
+        &[&[PDA_ADMIN_SEED.as_bytes(),  upgrade_admin.contract.as_ref(), &[upgrade_admin.bump]]],
+    )?;
+
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_extend_program<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ExtendProgramArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let program_data_info = next_account_info(account_info_iter)?;
+    let program_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let _bpf_loader = next_account_info(account_info_iter)?;
+
+    verify_system_program::<UpgradeError>(system_program)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, program_info.key)?;
+    verify_program_data(program_info.key, program_data_info)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.upgrade_nonce)?;
+
+    verify_upgrade_signatures(
+        extend_program_hash(program_id, &upgrade_admin, program_info.key, args.additional_bytes, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    invoke(
+        &bpf_loader_upgradeable::extend_program(program_info.key, Some(fee_payer_info.key), args.additional_bytes),
+        &[
+            program_data_info.clone(),
+            program_info.clone(),
+            system_program.clone(),
+            fee_payer_info.clone(),
+        ],
+    )?;
+
+    increment_nonce(&mut upgrade_admin.upgrade_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.upgrade_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_create_buffer<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: CreateBufferArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+    let buffer_info = next_account_info(account_info_iter)?;
+    let buffer_staging_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let _bpf_loader = next_account_info(account_info_iter)?;
+
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, upgrade_program.key)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.upgrade_nonce)?;
+
+    // Loader writes chunks only inside the account, so the committed length should fit exactly
+    if (buffer_info.data_len() as u64) != (BUFFER_METADATA_SIZE as u64).saturating_add(args.data_len) {
+        return Err(UpgradeError::InvalidAccountSize.into());
+    }
+
+    verify_upgrade_signatures(
+        create_buffer_hash(program_id, &upgrade_admin, upgrade_program.key, buffer_info.key, &args.writer, args.data_len, &args.code_hash, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    let (buffer_staging_key, bump) = pda::find_buffer_staging_address(program_id, &upgrade_admin_key, buffer_info.key);
+    if buffer_staging_key != *buffer_staging_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    let rent = Rent::from_account_info(rent_info)?;
+    create_pda_account::<UpgradeError>(buffer_staging_info, fee_payer_info, system_program, &rent, MAX_BUFFER_STAGING_SIZE, program_id, &[PDA_BUFFER_STAGING_SEED.as_bytes(), upgrade_admin_key.as_ref(), buffer_info.key.as_ref(), &[bump]])?;
+
+    let instruction = solana_program::instruction::Instruction::new_with_bincode(
+        bpf_loader_upgradeable::id(),
+        &solana_program::loader_upgradeable_instruction::UpgradeableLoaderInstruction::InitializeBuffer,
+        vec![
+            solana_program::instruction::AccountMeta::new(*buffer_info.key, false),
+            solana_program::instruction::AccountMeta::new_readonly(upgrade_admin_key, false),
+        ],
+    );
+
+    invoke(
+        &instruction,
+        &[
+            buffer_info.clone(),
+            upgrade_admin_info.clone(),
+        ],
+    )?;
+
+    let buffer_staging = BufferStaging {
+        admin: upgrade_admin_key,
+        program: *upgrade_program.key,
+        buffer: *buffer_info.key,
+        writer: args.writer,
+        data_len: args.data_len,
+        written: 0,
+        hasher: IncrementalSha256::new(),
+        code_hash: args.code_hash,
+        finalized: false,
+        is_initialized: true,
+    };
+    buffer_staging.serialize(&mut *buffer_staging_info.data.borrow_mut())?;
+
+    increment_nonce(&mut upgrade_admin.upgrade_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.upgrade_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_write_buffer_chunk<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: WriteBufferChunkArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let buffer_info = next_account_info(account_info_iter)?;
+    let buffer_staging_info = next_account_info(account_info_iter)?;
+    let _bpf_loader = next_account_info(account_info_iter)?;
+    let writer_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: upgrade_admin, .. } = AdminAccount::load_readonly(program_id, upgrade_admin_info)?;
+    let mut buffer_staging = load_buffer_staging(program_id, buffer_staging_info, &upgrade_admin_key)?;
+    if buffer_staging.buffer != *buffer_info.key {
+        return Err(UpgradeError::InvalidBuffer.into());
+    }
+
+    verify_buffer_writer(&buffer_staging, writer_info)?;
+
+    if buffer_staging.finalized {
+        return Err(UpgradeError::BufferFinalized.into());
+    }
+
+    // Chunks are hashed in order, so every chunk should continue the written data
+    let end = u64::from(args.offset).saturating_add(args.bytes.len() as u64);
+    if u64::from(args.offset) != buffer_staging.written || end > buffer_staging.data_len {
+        return Err(UpgradeError::InvalidChunk.into());
+    }
+
+    invoke_signed(
+        &bpf_loader_upgradeable::write(buffer_info.key, &upgrade_admin_key, args.offset, args.bytes.clone()),
+        &[
+            buffer_info.clone(),
+            upgrade_admin_info.clone(),
+        ],
+        &[&[PDA_ADMIN_SEED.as_bytes(),  upgrade_admin.contract.as_ref(), &[upgrade_admin.bump]]],
+    )?;
+
+    buffer_staging.hasher.update(&args.bytes);
+    buffer_staging.written = end;
+    buffer_staging.serialize(&mut *buffer_staging_info.data.borrow_mut())?;
+    Ok(())
+}
+
+
+pub fn process_finalize_buffer<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let buffer_staging_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { key: upgrade_admin_key, .. } = AdminAccount::load_readonly(program_id, upgrade_admin_info)?;
+    let mut buffer_staging = load_buffer_staging(program_id, buffer_staging_info, &upgrade_admin_key)?;
+    if buffer_staging.finalized {
+        return Err(UpgradeError::BufferFinalized.into());
+    }
+
+    if buffer_staging.written != buffer_staging.data_len {
+        return Err(UpgradeError::BufferNotComplete.into());
+    }
+
+    if buffer_staging.hasher.finalize() != buffer_staging.code_hash {
+        return Err(UpgradeError::CodeHashMismatch.into());
+    }
+
+    msg!("Buffer {} finalized for {}", buffer_staging.buffer, buffer_staging.program);
+
+    buffer_staging.finalized = true;
+    buffer_staging.serialize(&mut *buffer_staging_info.data.borrow_mut())?;
+    Ok(())
+}
+
+
+pub fn process_close_buffer<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: CloseBufferArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let buffer_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+    let _bpf_loader = next_account_info(account_info_iter)?;
+    let buffer_staging_info = next_account_info(account_info_iter)?;
+
+    verify_distinct_accounts(&[upgrade_admin_info, buffer_info, recipient_info, buffer_staging_info])?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.key_nonce)?;
+    verify_buffer_authority(buffer_info, &upgrade_admin_key)?;
+    verify_buffer_staging_address(program_id, &upgrade_admin_key, buffer_info, buffer_staging_info)?;
+
+    verify_signatures(
+        close_buffer_hash(program_id, &upgrade_admin, buffer_info.key, recipient_info.key, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    invoke_signed(
+        &bpf_loader_upgradeable::close(buffer_info.key, recipient_info.key, &upgrade_admin_key),
+        &[
+            buffer_info.clone(),
+            recipient_info.clone(),
+            upgrade_admin_info.clone(),
+        ],
+        &[&[PDA_ADMIN_SEED.as_bytes(),  upgrade_admin.contract.as_ref(), &[upgrade_admin.bump]]],
+    )?;
+
+    close_buffer_staging(program_id, buffer_staging_info, recipient_info)?;
+
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_reset_buffer<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let buffer_staging_info = next_account_info(account_info_iter)?;
+    let writer_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { key: upgrade_admin_key, .. } = AdminAccount::load_readonly(program_id, upgrade_admin_info)?;
+    let mut buffer_staging = load_buffer_staging(program_id, buffer_staging_info, &upgrade_admin_key)?;
+    if buffer_staging.finalized {
+        return Err(UpgradeError::BufferFinalized.into());
+    }
+
+    verify_buffer_writer(&buffer_staging, writer_info)?;
+
+    // Buffer keeps the written bytes, they are overwritten by the next chunks before FinalizeBuffer
+    buffer_staging.written = 0;
+    buffer_staging.hasher = IncrementalSha256::new();
+    buffer_staging.serialize(&mut *buffer_staging_info.data.borrow_mut())?;
+
+    msg!("Buffer {} reset", buffer_staging.buffer);
+    Ok(())
+}
+
+
+/// Rejects chunks that are not signed by the writer committed in CreateBuffer.
+fn verify_buffer_writer(buffer_staging: &BufferStaging, writer_info: &AccountInfo) -> ProgramResult {
+    if !writer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if buffer_staging.writer != *writer_info.key {
+        return Err(UpgradeError::BufferWriterMismatch.into());
+    }
+
+    Ok(())
+}
+
+
+/// Rejects SetBufferAuthority that is not followed by ProposeUpgrade or Upgrade of `buffer` in the same transaction.
+fn verify_buffer_registered(program_id: &Pubkey, instructions_info: &AccountInfo, buffer: &Pubkey) -> ProgramResult {
+    let next_index = load_current_index_checked(instructions_info)? as usize + 1;
+    let instruction = load_instruction_at_checked(next_index, instructions_info)
+        .map_err(|_| UpgradeError::BufferNotRegistered)?;
+
+    let registers_buffer = instruction.program_id == *program_id
+        && matches!(UpgradeInstruction::unpack(&instruction.data), Ok(UpgradeInstruction::ProposeUpgrade(_) | UpgradeInstruction::Upgrade(_)))
+        && instruction.accounts.get(REGISTERED_BUFFER_INDEX).map(|meta| meta.pubkey) == Some(*buffer);
+    if !registers_buffer {
+        return Err(UpgradeError::BufferNotRegistered.into());
+    }
+
+    Ok(())
+}
+
+
+/// Reads BufferStaging of `upgrade_admin` checking that the account is owned by the program.
+fn load_buffer_staging(program_id: &Pubkey, buffer_staging_info: &AccountInfo, upgrade_admin: &Pubkey) -> Result<BufferStaging, ProgramError> {
+    if buffer_staging_info.owner != program_id {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    let buffer_staging: BufferStaging = BorshDeserialize::deserialize(&mut buffer_staging_info.data.borrow().as_ref())?;
+    if !buffer_staging.is_initialized {
+        return Err(UpgradeError::NotInitialized.into());
+    }
+
+    if buffer_staging.admin != *upgrade_admin {
+        return Err(UpgradeError::WrongAdmin.into());
+    }
+
+    Ok(buffer_staging)
+}
+
+
+pub fn process_propose_upgrade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ProposeUpgradeArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let pending_upgrade_info = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+    let upgrade_buffer = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let buffer_staging_info = next_account_info(account_info_iter)?;
+
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, upgrade_program.key)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.upgrade_nonce)?;
+
+    verify_buffer_code_hash(program_id, &upgrade_admin_key, upgrade_buffer, buffer_staging_info, &args.code_hash)?;
+
+    verify_build_hash(&upgrade_admin, upgrade_program.key, &args.code_hash)?;
+
+    let slot = Clock::get()?.slot;
+    if let Some(not_after_slot) = args.not_after_slot {
+        // Window should stay reachable after the timelock delay
+        if not_after_slot < slot.saturating_add(upgrade_admin.min_delay_slots) || not_after_slot < args.not_before_slot.unwrap_or_default() {
+            return Err(UpgradeError::InvalidUpgradeWindow.into());
+        }
+    }
+
+    let hash = propose_upgrade_hash(
+        program_id,
+        &upgrade_admin,
+        upgrade_program.key,
+        upgrade_buffer.key,
+        &args.code_hash,
+        args.not_before_slot,
+        args.not_after_slot,
+        args.expires_at,
+    )?;
+
+    // Separated roles replace the upgrade authority with proposers, approvals are collected afterwards
+    if upgrade_admin.roles.is_empty() {
+        verify_upgrade_signatures(hash.as_ref(), &args.signatures, &args.signer_proofs, accounts, &upgrade_admin)?;
+    } else {
+        verify_role(&upgrade_admin, Role::Proposer, hash.as_ref(), &args.signatures, accounts)?;
+    }
+
+    let (pending_upgrade_key, bump) = pda::find_pending_upgrade_address(program_id, &upgrade_admin_key, upgrade_program.key);
+    if pending_upgrade_key != *pending_upgrade_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    if pending_upgrade_info.data_is_empty() {
+        let rent = Rent::from_account_info(rent_info)?;
+        create_pda_account::<UpgradeError>(pending_upgrade_info, fee_payer_info, system_program, &rent, MAX_PENDING_UPGRADE_SIZE, program_id, &[PDA_PENDING_UPGRADE_SEED.as_bytes(), upgrade_admin_key.as_ref(), upgrade_program.key.as_ref(), &[bump]])?;
+    }
+
+    let pending_upgrade: PendingUpgrade = BorshDeserialize::deserialize(&mut pending_upgrade_info.data.borrow().as_ref())?;
+    if pending_upgrade.is_initialized {
+        return Err(UpgradeError::UpgradePending.into());
+    }
+
+    let pending_upgrade = PendingUpgrade {
+        admin: upgrade_admin_key,
+        program: *upgrade_program.key,
+        buffer: *upgrade_buffer.key,
+        code_hash: args.code_hash,
+        proposal_slot: slot,
+        not_before_slot: args.not_before_slot,
+        not_after_slot: args.not_after_slot,
+        approvals: vec![],
+        is_initialized: true,
+    };
+    pending_upgrade.serialize(&mut *pending_upgrade_info.data.borrow_mut())?;
+
+    msg!("Upgrade of {} to buffer {} proposed", upgrade_program.key, upgrade_buffer.key);
+
+    increment_nonce(&mut upgrade_admin.upgrade_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.upgrade_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_execute_upgrade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let upgrade_program_data = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+    let upgrade_buffer = next_account_info(account_info_iter)?;
+    let upgrade_spill = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let _upgrade_loader = next_account_info(account_info_iter)?;
+    let pending_upgrade_info = next_account_info(account_info_iter)?;
+    let upgrade_history_info = next_account_info(account_info_iter)?;
+    let buffer_staging_info = next_account_info(account_info_iter)?;
+
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+    verify_sysvar::<UpgradeError>(clock_info, &sysvar::clock::id())?;
+    verify_distinct_accounts(&[upgrade_admin_info, upgrade_program_data, upgrade_program, upgrade_buffer, upgrade_spill, pending_upgrade_info, upgrade_history_info, buffer_staging_info])?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, upgrade_program.key)?;
+    verify_program_data(upgrade_program.key, upgrade_program_data)?;
+
+    let (pending_upgrade_key, _) = pda::find_pending_upgrade_address(program_id, &upgrade_admin_key, upgrade_program.key);
+    if pending_upgrade_key != *pending_upgrade_info.key || pending_upgrade_info.owner != program_id {
+        return Err(UpgradeError::NoPendingUpgrade.into());
+    }
+
+    let pending_upgrade: PendingUpgrade = BorshDeserialize::deserialize(&mut pending_upgrade_info.data.borrow().as_ref())?;
+    if !pending_upgrade.is_initialized || pending_upgrade.buffer != *upgrade_buffer.key {
+        return Err(UpgradeError::NoPendingUpgrade.into());
+    }
+
+    verify_healthcheck_passed(&upgrade_admin)?;
+
+    let execution_slot = pending_upgrade.proposal_slot.saturating_add(upgrade_admin.min_delay_slots);
+    let slot = Clock::get()?.slot;
+    if slot < execution_slot {
+        msg!("Upgrade can be executed at slot {}, current slot {}", execution_slot, slot);
+        return Err(UpgradeError::UpgradeDelayNotElapsed.into());
+    }
+
+    verify_upgrade_interval(&upgrade_admin, slot)?;
+
+    if slot < pending_upgrade.not_before_slot.unwrap_or_default() || slot > pending_upgrade.not_after_slot.unwrap_or(u64::MAX) {
+        msg!("Upgrade window is {:?} - {:?}, current slot {}", pending_upgrade.not_before_slot, pending_upgrade.not_after_slot, slot);
+        return Err(UpgradeError::OutsideUpgradeWindow.into());
+    }
+
+    verify_buffer_code_hash(program_id, &upgrade_admin_key, upgrade_buffer, buffer_staging_info, &pending_upgrade.code_hash)?;
+
+    // Verified build could be changed after the proposal
+    verify_build_hash(&upgrade_admin, upgrade_program.key, &pending_upgrade.code_hash)?;
+
+    if !upgrade_admin.roles.is_empty() {
+        if pending_upgrade.approvals.len() < upgrade_admin.approval_threshold as usize {
+            return Err(UpgradeError::NotEnoughApprovals.into());
+        }
+
+        verify_role(&upgrade_admin, Role::Executor, &[], &[], accounts)?;
+    }
+
+    let optional_accounts: Vec<&AccountInfo> = account_info_iter.collect();
+    let executor_info = find_executor(&upgrade_admin, &optional_accounts)?;
+
+    invoke_upgrade(
+        &upgrade_admin,
+        &upgrade_admin_key,
+        &[
+            upgrade_program_data.clone(),
+            upgrade_program.clone(),
+            upgrade_buffer.clone(),
+            upgrade_spill.clone(),
+            rent_info.clone(),
+            clock_info.clone(),
+            upgrade_admin_info.clone(),
+        ],
+    )?;
+
+    record_upgrade(program_id, upgrade_history_info, &upgrade_admin_key, upgrade_program.key, UpgradeRecord {
+        slot,
+        buffer: *upgrade_buffer.key,
+        code_hash: pending_upgrade.code_hash,
+        executor: executor_info.map(|info| *info.key),
+        spill: *upgrade_spill.key,
+    })?;
+
+    UpgradeEvent::Upgraded(Upgraded {
+        admin: upgrade_admin_key,
+        contract: *upgrade_program.key,
+        buffer: *upgrade_buffer.key,
+        code_hash: pending_upgrade.code_hash,
+        nonce: upgrade_admin.upgrade_nonce,
+        slot,
+    }).emit();
+
+    close_account(pending_upgrade_info, upgrade_spill)?;
+    close_buffer_staging(program_id, buffer_staging_info, upgrade_spill)?;
+
+    mark_unverified(&mut upgrade_admin, upgrade_program.key);
+
+    upgrade_admin.last_upgrade_slot = Some(slot);
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+
+    if let Some(executor_info) = executor_info {
+        reimburse_executor(upgrade_admin_info, executor_info, &Rent::from_account_info(rent_info)?, upgrade_admin.reimbursement)?;
+    }
+
+    set_result(&UpgradeResult {
+        contract: *upgrade_program.key,
+        buffer: *upgrade_buffer.key,
+        code_hash: pending_upgrade.code_hash,
+        nonce: upgrade_admin.upgrade_nonce,
+    })?;
+    Ok(())
+}
+
+
+pub fn process_set_roles<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetRolesArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+    validate_roles(&args.roles, args.approval_threshold)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_signatures(
+        set_roles_hash(program_id, &upgrade_admin, &args.roles, args.approval_threshold, args.expires_at)?.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.roles = args.roles;
+    upgrade_admin.approval_threshold = args.approval_threshold;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_approve_upgrade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ApproveUpgradeArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let pending_upgrade_info = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: upgrade_admin, .. } = AdminAccount::load_readonly(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    if upgrade_admin.roles.is_empty() {
+        return Err(UpgradeError::RolesNotSet.into());
+    }
+
+    let (pending_upgrade_key, _) = pda::find_pending_upgrade_address(program_id, &upgrade_admin_key, upgrade_program.key);
+    if pending_upgrade_key != *pending_upgrade_info.key || pending_upgrade_info.owner != program_id {
+        return Err(UpgradeError::NoPendingUpgrade.into());
+    }
+
+    let mut pending_upgrade: PendingUpgrade = BorshDeserialize::deserialize(&mut pending_upgrade_info.data.borrow().as_ref())?;
+    if !pending_upgrade.is_initialized {
+        return Err(UpgradeError::NoPendingUpgrade.into());
+    }
+
+    let hash = approve_upgrade_hash(
+        program_id,
+        &upgrade_admin,
+        upgrade_program.key,
+        &pending_upgrade.buffer,
+        &pending_upgrade.code_hash,
+        pending_upgrade.proposal_slot,
+        args.expires_at,
+    );
+    let approver = verify_role(&upgrade_admin, Role::Approver, hash.as_ref(), &args.signatures, accounts)?;
+
+    let approver_id = keccak::hash(&approver.try_to_vec()?).to_bytes();
+    if pending_upgrade.approvals.contains(&approver_id) {
+        return Err(UpgradeError::AlreadyApproved.into());
+    }
+
+    pending_upgrade.approvals.push(approver_id);
+    pending_upgrade.serialize(&mut *pending_upgrade_info.data.borrow_mut())?;
+
+    msg!("Upgrade of {} approved {} of {}", upgrade_program.key, pending_upgrade.approvals.len(), upgrade_admin.approval_threshold);
+    Ok(())
+}
+
+
+pub fn process_set_veto_key<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetVetoKeyArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    // Veto key can not initiate operations, so it should not be able to sign them as a member of the signer set
+    if let Some(RoleKey::Ecdsa(veto_key)) = &args.veto_key {
+        if validate_key(veto_key, upgrade_admin.curve).is_err()
+            || upgrade_admin.public_keys.contains(veto_key)
+            || upgrade_admin.upgrade_key.as_ref() == Some(veto_key) {
+            return Err(UpgradeError::InvalidVetoKey.into());
+        }
+    }
+
+    verify_signatures(
+        set_veto_key_hash(program_id, &upgrade_admin, &args.veto_key, args.expires_at)?.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.veto_key = args.veto_key;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_veto_upgrade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: VetoUpgradeArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let pending_upgrade_info = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+
+    verify_distinct_accounts(&[upgrade_admin_info, pending_upgrade_info, upgrade_program, destination_info])?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: upgrade_admin, .. } = AdminAccount::load_readonly(program_id, upgrade_admin_info)?;
+    let veto_key = upgrade_admin.veto_key.as_ref().ok_or(UpgradeError::VetoNotAuthorized)?;
+
+    let (pending_upgrade_key, _) = pda::find_pending_upgrade_address(program_id, &upgrade_admin_key, upgrade_program.key);
+    if pending_upgrade_key != *pending_upgrade_info.key || pending_upgrade_info.owner != program_id {
+        return Err(UpgradeError::NoPendingUpgrade.into());
+    }
+
+    let pending_upgrade: PendingUpgrade = BorshDeserialize::deserialize(&mut pending_upgrade_info.data.borrow().as_ref())?;
+    if !pending_upgrade.is_initialized {
+        return Err(UpgradeError::NoPendingUpgrade.into());
+    }
+
+    let hash = veto_upgrade_hash(
+        program_id,
+        &upgrade_admin,
+        upgrade_program.key,
+        &pending_upgrade.buffer,
+        &pending_upgrade.code_hash,
+        pending_upgrade.proposal_slot,
+        destination_info.key,
+        args.expires_at,
+    );
+    if !is_authorized_by(&upgrade_admin, veto_key, hash.as_ref(), &args.signatures, accounts) {
+        return Err(UpgradeError::VetoNotAuthorized.into());
+    }
+
+    close_account(pending_upgrade_info, destination_info)?;
+
+    msg!("Upgrade of {} to buffer {} vetoed", upgrade_program.key, pending_upgrade.buffer);
+    Ok(())
+}
+
+
+pub fn process_set_upgrade_delay<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetUpgradeDelayArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_signatures(
+        set_upgrade_delay_hash(program_id, &upgrade_admin, args.min_delay_slots, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.min_delay_slots = args.min_delay_slots;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_cancel_upgrade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: CancelUpgradeArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let pending_upgrade_info = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+    let upgrade_buffer = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+
+    verify_distinct_accounts(&[upgrade_admin_info, pending_upgrade_info, upgrade_program, upgrade_buffer, destination_info])?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.upgrade_nonce)?;
+
+    let (pending_upgrade_key, _) = pda::find_pending_upgrade_address(program_id, &upgrade_admin_key, upgrade_program.key);
+    if pending_upgrade_key != *pending_upgrade_info.key || pending_upgrade_info.owner != program_id {
+        return Err(UpgradeError::NoPendingUpgrade.into());
+    }
+
+    let pending_upgrade: PendingUpgrade = BorshDeserialize::deserialize(&mut pending_upgrade_info.data.borrow().as_ref())?;
+    if !pending_upgrade.is_initialized || pending_upgrade.buffer != *upgrade_buffer.key {
+        return Err(UpgradeError::NoPendingUpgrade.into());
+    }
+
+    verify_upgrade_signatures(
+        cancel_upgrade_hash(program_id, &upgrade_admin, upgrade_program.key, upgrade_buffer.key, args.close_buffer, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    close_account(pending_upgrade_info, destination_info)?;
+
+    if args.close_buffer {
+        invoke_signed(
+            &bpf_loader_upgradeable::close_any(upgrade_buffer.key, destination_info.key, Some(&upgrade_admin_key), None),
+            &[
+                upgrade_buffer.clone(),
+                destination_info.clone(),
+                upgrade_admin_info.clone(),
+            ],
+            &[&[PDA_ADMIN_SEED.as_bytes(), upgrade_admin.contract.as_ref(), &[upgrade_admin.bump]]],
+        )?;
+    }
+
+    msg!("Upgrade of {} to buffer {} cancelled", upgrade_program.key, upgrade_buffer.key);
+
+    increment_nonce(&mut upgrade_admin.upgrade_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.upgrade_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_set_buffer_authority<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let upgrade_buffer = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let _bpf_loader = next_account_info(account_info_iter)?;
+    let instructions_info = next_account_info(account_info_iter)?;
+
+    verify_sysvar::<UpgradeError>(instructions_info, &sysvar::instructions::id())?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: upgrade_admin, .. } = AdminAccount::load_readonly(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+    verify_buffer_registered(program_id, instructions_info, upgrade_buffer.key)?;
+
+    if buffer_authority(upgrade_buffer)? != Some(*authority_info.key) {
+        return Err(UpgradeError::WrongBufferAuthority.into());
+    }
+
+    invoke_signed(
+        &bpf_loader_upgradeable::set_buffer_authority_checked(upgrade_buffer.key, authority_info.key, &upgrade_admin_key),
+        &[
+            upgrade_buffer.clone(),
+            authority_info.clone(),
+            upgrade_admin_info.clone(),
+        ],
+        &[&[PDA_ADMIN_SEED.as_bytes(), upgrade_admin.contract.as_ref(), &[upgrade_admin.bump]]],
+    )?;
+
+    msg!("Buffer {} authority set to admin {}", upgrade_buffer.key, upgrade_admin_key);
+    Ok(())
+}
+
+
+pub fn process_init_upgrade_history<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let upgrade_history_info = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: upgrade_admin, .. } = AdminAccount::load_readonly(program_id, upgrade_admin_info)?;
+    verify_managed(&upgrade_admin, upgrade_program.key)?;
+
+    let (upgrade_history_key, bump) = pda::find_upgrade_history_address(program_id, &upgrade_admin_key, upgrade_program.key);
+    if upgrade_history_key != *upgrade_history_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    if !upgrade_history_info.data_is_empty() {
+        return Err(UpgradeError::AlreadyInUse.into());
+    }
+
+    let rent = Rent::from_account_info(rent_info)?;
+    create_pda_account::<UpgradeError>(upgrade_history_info, fee_payer_info, system_program, &rent, MAX_UPGRADE_HISTORY_SIZE, program_id, &[PDA_UPGRADE_HISTORY_SEED.as_bytes(), upgrade_admin_key.as_ref(), upgrade_program.key.as_ref(), &[bump]])?;
+
+    let upgrade_history = UpgradeHistory {
+        admin: upgrade_admin_key,
+        program: *upgrade_program.key,
+        count: 0,
+        records: vec![],
+        is_initialized: true,
+    };
+    upgrade_history.serialize(&mut *upgrade_history_info.data.borrow_mut())?;
+    Ok(())
+}
+
+
+pub fn process_register_snapshot<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: RegisterSnapshotArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let rollback_snapshot_info = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+    let upgrade_buffer = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    verify_system_program::<UpgradeError>(system_program)?;
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, upgrade_program.key)?;
+
+    if buffer_code_hash(upgrade_buffer, &upgrade_admin_key)? != args.code_hash {
+        return Err(UpgradeError::CodeHashMismatch.into());
+    }
+
+    verify_signatures(
+        register_snapshot_hash(program_id, &upgrade_admin, upgrade_program.key, upgrade_buffer.key, &args.code_hash, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    let (rollback_snapshot_key, bump) = pda::find_rollback_snapshot_address(program_id, &upgrade_admin_key, upgrade_program.key);
+    if rollback_snapshot_key != *rollback_snapshot_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    if rollback_snapshot_info.data_is_empty() {
+        let rent = Rent::from_account_info(rent_info)?;
+        create_pda_account::<UpgradeError>(rollback_snapshot_info, fee_payer_info, system_program, &rent, MAX_ROLLBACK_SNAPSHOT_SIZE, program_id, &[PDA_ROLLBACK_SNAPSHOT_SEED.as_bytes(), upgrade_admin_key.as_ref(), upgrade_program.key.as_ref(), &[bump]])?;
+    }
+
+    let rollback_snapshot = RollbackSnapshot {
+        admin: upgrade_admin_key,
+        program: *upgrade_program.key,
+        buffer: *upgrade_buffer.key,
+        code_hash: args.code_hash,
+        registered_slot: Clock::get()?.slot,
+        is_initialized: true,
+    };
+    rollback_snapshot.serialize(&mut *rollback_snapshot_info.data.borrow_mut())?;
+
+    msg!("Snapshot buffer {} registered for {}", upgrade_buffer.key, upgrade_program.key);
+
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_rollback<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: RollbackArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let upgrade_program_data = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+    let upgrade_buffer = next_account_info(account_info_iter)?;
+    let upgrade_spill = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let _upgrade_loader = next_account_info(account_info_iter)?;
+    let upgrade_history_info = next_account_info(account_info_iter)?;
+    let rollback_snapshot_info = next_account_info(account_info_iter)?;
+
+    verify_sysvar::<UpgradeError>(rent_info, &sysvar::rent::id())?;
+    verify_sysvar::<UpgradeError>(clock_info, &sysvar::clock::id())?;
+    verify_distinct_accounts(&[upgrade_admin_info, upgrade_program_data, upgrade_program, upgrade_buffer, upgrade_spill, upgrade_history_info, rollback_snapshot_info])?;
+
+    let AdminAccount { key: upgrade_admin_key, admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_not_paused(&upgrade_admin)?;
+    verify_direct_upgrade(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, upgrade_program.key)?;
+    verify_program_data(upgrade_program.key, upgrade_program_data)?;
+    verify_nonce::<UpgradeError>(args.nonce, upgrade_admin.upgrade_nonce)?;
+
+    let slot = Clock::from_account_info(clock_info)?.slot;
+    verify_upgrade_interval(&upgrade_admin, slot)?;
+
+    // Rollback restores the contract that failed its healthcheck, other contracts wait for the check
+    if upgrade_admin.unverified_upgrade != Some(*upgrade_program.key) {
+        verify_healthcheck_passed(&upgrade_admin)?;
+    }
+
+    let (rollback_snapshot_key, _) = pda::find_rollback_snapshot_address(program_id, &upgrade_admin_key, upgrade_program.key);
+    if rollback_snapshot_key != *rollback_snapshot_info.key || rollback_snapshot_info.owner != program_id {
+        return Err(UpgradeError::NoRollbackSnapshot.into());
+    }
+
+    let rollback_snapshot: RollbackSnapshot = BorshDeserialize::deserialize(&mut rollback_snapshot_info.data.borrow().as_ref())?;
+    if !rollback_snapshot.is_initialized || rollback_snapshot.buffer != *upgrade_buffer.key {
+        return Err(UpgradeError::NoRollbackSnapshot.into());
+    }
+
+    if buffer_code_hash(upgrade_buffer, &upgrade_admin_key)? != rollback_snapshot.code_hash {
+        return Err(UpgradeError::CodeHashMismatch.into());
+    }
+
+    let optional_accounts: Vec<&AccountInfo> = account_info_iter.collect();
+    let executor_info = find_executor(&upgrade_admin, &optional_accounts)?;
+
+    // Snapshot is approved by the threshold in advance, so any single signer can restore it
+    let mut target_keys = upgrade_admin.public_keys.clone();
+    target_keys.extend(upgrade_admin.upgrade_key.clone());
+    verify_signers(
+        rollback_hash(program_id, &upgrade_admin, upgrade_program.key, upgrade_buffer.key, args.expires_at).as_ref(),
+        &args.signatures,
+        accounts,
+        &target_keys,
+        &[],
+        1,
+        upgrade_admin.curve,
+    )?;
+
+    invoke_upgrade(
+        &upgrade_admin,
+        &upgrade_admin_key,
+        &[
+            upgrade_program_data.clone(),
+            upgrade_program.clone(),
+            upgrade_buffer.clone(),
+            upgrade_spill.clone(),
+            rent_info.clone(),
+            clock_info.clone(),
+            upgrade_admin_info.clone(),
+        ],
+    )?;
+
+    record_upgrade(program_id, upgrade_history_info, &upgrade_admin_key, upgrade_program.key, UpgradeRecord {
+        slot,
+        buffer: *upgrade_buffer.key,
+        code_hash: rollback_snapshot.code_hash,
+        executor: executor_info.map(|info| *info.key),
+        spill: *upgrade_spill.key,
+    })?;
+
+    UpgradeEvent::Upgraded(Upgraded {
+        admin: upgrade_admin_key,
+        contract: *upgrade_program.key,
+        buffer: *upgrade_buffer.key,
+        code_hash: rollback_snapshot.code_hash,
+        nonce: upgrade_admin.upgrade_nonce,
+        slot,
+    }).emit();
+
+    close_account(rollback_snapshot_info, upgrade_spill)?;
+
+    msg!("Contract {} rolled back to buffer {}", upgrade_program.key, upgrade_buffer.key);
+
+    // Previous code is trusted, so the failed healthcheck should not block further upgrades
+    if upgrade_admin.unverified_upgrade == Some(*upgrade_program.key) {
+        upgrade_admin.unverified_upgrade = None;
+    }
+
+    upgrade_admin.last_upgrade_slot = Some(slot);
+    increment_nonce(&mut upgrade_admin.upgrade_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+
+    if let Some(executor_info) = executor_info {
+        reimburse_executor(upgrade_admin_info, executor_info, &Rent::from_account_info(rent_info)?, upgrade_admin.reimbursement)?;
+    }
+
+    set_result(&UpgradeResult {
+        contract: *upgrade_program.key,
+        buffer: *upgrade_buffer.key,
+        code_hash: rollback_snapshot.code_hash,
+        nonce: upgrade_admin.upgrade_nonce,
+    })?;
+    Ok(())
+}
+
+pub fn process_set_healthcheck<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetHealthcheckArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, &args.contract)?;
+
+    verify_signatures(
+        set_healthcheck_hash(program_id, &upgrade_admin, &args.contract, &args.data, args.expires_at)?.as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.healthchecks.retain(|healthcheck| healthcheck.contract != args.contract);
+    match args.data {
+        Some(data) => {
+            if data.len() > MAX_HEALTHCHECK_DATA_LEN || upgrade_admin.healthchecks.len() >= MAX_HEALTHCHECKS {
+                return Err(UpgradeError::InvalidHealthcheck.into());
+            }
+
+            upgrade_admin.healthchecks.push(Healthcheck { contract: args.contract, data });
+        }
+        None => {
+            if upgrade_admin.unverified_upgrade == Some(args.contract) {
+                upgrade_admin.unverified_upgrade = None;
+            }
+        }
+    }
+
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+pub fn process_run_healthcheck<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+    let upgrade_program = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+
+    if upgrade_admin.unverified_upgrade != Some(*upgrade_program.key) {
+        return Err(UpgradeError::InvalidHealthcheck.into());
+    }
+
+    let healthcheck = upgrade_admin.healthchecks.iter()
+        .find(|healthcheck| healthcheck.contract == *upgrade_program.key)
+        .ok_or(UpgradeError::InvalidHealthcheck)?;
+
+    // Program upgraded in the current slot is not invocable until the next one
+    let slot = Clock::get()?.slot;
+    if Some(slot) <= upgrade_admin.last_upgrade_slot {
+        msg!("Healthcheck can be run after slot {:?}, current slot {}", upgrade_admin.last_upgrade_slot, slot);
+        return Err(UpgradeError::HealthcheckPending.into());
+    }
+
+    invoke(
+        &Instruction {
+            program_id: *upgrade_program.key,
+            accounts: vec![],
+            data: healthcheck.data.clone(),
+        },
+        std::slice::from_ref(upgrade_program),
+    )?;
+
+    msg!("Contract {} passed healthcheck", upgrade_program.key);
+
+    upgrade_admin.unverified_upgrade = None;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_set_verified_build_hash<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetVerifiedBuildHashArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+    verify_managed(&upgrade_admin, &args.contract)?;
+
+    verify_signatures(
+        set_verified_build_hash_hash(program_id, &upgrade_admin, &args.contract, &args.build_hash, args.required, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    // Builds of contracts that are no longer managed are dropped, so the list always fits MAX_VERIFIED_BUILDS
+    let UpgradeAdmin { contract, managed_contracts, verified_builds, .. } = &mut upgrade_admin;
+    verified_builds.retain(|build| build.contract != args.contract && (build.contract == *contract || managed_contracts.contains(&build.contract)));
+    if let Some(build_hash) = args.build_hash {
+        verified_builds.push(VerifiedBuild { contract: args.contract, build_hash, required: args.required });
+    }
+
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+/// Appends `record` to UpgradeHistory of `program` managed by `upgrade_admin`.
+fn record_upgrade(program_id: &Pubkey, upgrade_history_info: &AccountInfo, upgrade_admin: &Pubkey, program: &Pubkey, record: UpgradeRecord) -> ProgramResult {
+    let (upgrade_history_key, _) = pda::find_upgrade_history_address(program_id, upgrade_admin, program);
+    if upgrade_history_key != *upgrade_history_info.key || upgrade_history_info.owner != program_id {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    let mut upgrade_history: UpgradeHistory = BorshDeserialize::deserialize(&mut upgrade_history_info.data.borrow().as_ref())?;
+    if !upgrade_history.is_initialized {
+        return Err(UpgradeError::NotInitialized.into());
+    }
+
+    upgrade_history.push(record);
+    upgrade_history.serialize(&mut *upgrade_history_info.data.borrow_mut())?;
+    Ok(())
+}
+
+
+/// Closes program owned `account_info` (PendingUpgrade or RollbackSnapshot) and sends its lamports to `destination_info`.
+fn close_account(account_info: &AccountInfo, destination_info: &AccountInfo) -> ProgramResult {
+    let lamports = account_info.lamports();
+    **destination_info.lamports.borrow_mut() = destination_info.lamports().checked_add(lamports).ok_or(ProgramError::ArithmeticOverflow)?;
+    **account_info.lamports.borrow_mut() = 0;
+    account_info.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+
+pub fn process_set_upgrade_interval<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetUpgradeIntervalArgs,
+) -> ProgramResult {
+    verify_not_expired::<UpgradeError>(args.expires_at)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let upgrade_admin_info = next_account_info(account_info_iter)?;
+
+    let AdminAccount { admin: mut upgrade_admin, .. } = AdminAccount::load(program_id, upgrade_admin_info)?;
+    verify_active(&upgrade_admin)?;
+    verify_admin_not_expired(&upgrade_admin)?;
+
+    verify_signatures(
+        set_upgrade_interval_hash(program_id, &upgrade_admin, args.min_upgrade_interval_slots, args.expires_at).as_ref(),
+        &args.signatures,
+        &args.signer_proofs,
+        accounts,
+        &upgrade_admin,
+    )?;
+
+    upgrade_admin.min_upgrade_interval_slots = args.min_upgrade_interval_slots;
+    increment_nonce(&mut upgrade_admin.key_nonce, upgrade_admin.nonce_width)?;
+    upgrade_admin.pack(&mut upgrade_admin_info.data.borrow_mut())?;
+    set_result(&NonceResult { nonce: upgrade_admin.key_nonce })?;
+    Ok(())
+}
+
+
+/// Upgrades the program with UpgradeAdmin as the upgrade authority. `upgrade_accounts` are ProgramData, Program,
+/// Buffer, spill, Rent sysvar, Clock sysvar and UpgradeAdmin accounts.
+fn invoke_upgrade<'a>(upgrade_admin: &UpgradeAdmin, upgrade_admin_key: &Pubkey, upgrade_accounts: &[AccountInfo<'a>; 7]) -> ProgramResult {
+    let instruction = bpf_loader_upgradeable::upgrade(
+        upgrade_accounts[1].key,
+        upgrade_accounts[2].key,
+        upgrade_admin_key,
+        upgrade_accounts[3].key,
+    );
+
+    invoke_signed(
+        &instruction,
+        upgrade_accounts,
+        &[&[PDA_ADMIN_SEED.as_bytes(), upgrade_admin.contract.as_ref(), &[upgrade_admin.bump]]],
+    )
+}
+
+
+/// Finds the executor signer among `optional_accounts`. It is required only if UpgradeAdmin has executors allowlist.
+fn find_executor<'a, 'b>(upgrade_admin: &UpgradeAdmin, optional_accounts: &[&'b AccountInfo<'a>]) -> Result<Option<&'b AccountInfo<'a>>, ProgramError> {
+    let executor_info = optional_accounts.iter()
+        .find(|info| info.is_signer && upgrade_admin.executors.contains(info.key))
+        .copied();
+    if !upgrade_admin.executors.is_empty() && executor_info.is_none() {
+        return Err(UpgradeError::ExecutorNotAllowed.into());
+    }
+
+    Ok(executor_info)
+}
+
+
+/// Upgrade authority of `buffer_info` buffer, None if the buffer is immutable.
+fn buffer_authority(buffer_info: &AccountInfo) -> Result<Option<Pubkey>, ProgramError> {
+    if !bpf_loader_upgradeable::check_id(buffer_info.owner) {
+        return Err(UpgradeError::InvalidBuffer.into());
+    }
+
+    // Buffer metadata: u32 state discriminator, Option<Pubkey> authority
+    let data = buffer_info.data.borrow();
+    if data.len() < BUFFER_METADATA_SIZE || data[..4] != BUFFER_DISCRIMINATOR.to_le_bytes() {
+        return Err(UpgradeError::InvalidBuffer.into());
+    }
+
+    match data[4] {
+        0 => Ok(None),
+        _ => Ok(Some(Pubkey::new_from_array(data[5..BUFFER_METADATA_SIZE].try_into().unwrap()))),
+    }
+}
+
+
+/// Checks that `authority` can write and deploy `buffer_info`, so the loader does not fail inside the CPI.
+fn verify_buffer_authority(buffer_info: &AccountInfo, authority: &Pubkey) -> ProgramResult {
+    let buffer_authority = buffer_authority(buffer_info)?;
+    if buffer_authority != Some(*authority) {
+        msg!("Buffer authority {:?} is not the admin {}", buffer_authority, authority);
+        return Err(UpgradeError::WrongBufferAuthority.into());
+    }
+
+    Ok(())
+}
+
+
+/// SHA-256 of program data in `buffer_info`. The buffer authority should be `authority`,
+/// otherwise the buffer could be rewritten after its hash is checked.
+fn buffer_code_hash(buffer_info: &AccountInfo, authority: &Pubkey) -> Result<[u8; 32], ProgramError> {
+    verify_buffer_authority(buffer_info, authority)?;
+    Ok(solana_program::hash::hash(&buffer_info.data.borrow()[BUFFER_METADATA_SIZE..]).to_bytes())
+}
+
+
+/// Verifies that the buffer holds code of `code_hash`. Buffer staged by CreateBuffer should be finalized, its chunks
+/// were hashed on write and can not be rewritten after FinalizeBuffer, so it is not hashed again. Other buffers are
+/// hashed in full.
+fn verify_buffer_code_hash(program_id: &Pubkey, upgrade_admin_key: &Pubkey, buffer_info: &AccountInfo, buffer_staging_info: &AccountInfo, code_hash: &[u8; 32]) -> ProgramResult {
+    verify_buffer_staging_address(program_id, upgrade_admin_key, buffer_info, buffer_staging_info)?;
+
+    let buffer_code_hash = if buffer_staging_info.owner == program_id {
+        verify_buffer_authority(buffer_info, upgrade_admin_key)?;
+        let buffer_staging = load_buffer_staging(program_id, buffer_staging_info, upgrade_admin_key)?;
+        if !buffer_staging.finalized {
+            return Err(UpgradeError::BufferNotFinalized.into());
+        }
+
+        buffer_staging.code_hash
+    } else {
+        buffer_code_hash(buffer_info, upgrade_admin_key)?
+    };
+
+    if buffer_code_hash != *code_hash {
+        return Err(UpgradeError::CodeHashMismatch.into());
+    }
+
+    Ok(())
+}
+
+
+/// Rejects BufferStaging account that is not derived from the buffer, so a staged buffer can not skip its checks.
+fn verify_buffer_staging_address(program_id: &Pubkey, upgrade_admin_key: &Pubkey, buffer_info: &AccountInfo, buffer_staging_info: &AccountInfo) -> ProgramResult {
+    let (buffer_staging_key, _) = pda::find_buffer_staging_address(program_id, upgrade_admin_key, buffer_info.key);
+    if buffer_staging_key != *buffer_staging_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    Ok(())
+}
+
+
+/// Closes BufferStaging of the buffer closed by the loader, so a new buffer at the same address is hashed in full.
+fn close_buffer_staging(program_id: &Pubkey, buffer_staging_info: &AccountInfo, destination_info: &AccountInfo) -> ProgramResult {
+    if buffer_staging_info.owner == program_id {
+        close_account(buffer_staging_info, destination_info)?;
+    }
+
+    Ok(())
+}
+
+
+fn validate_signer_set(
+    public_keys: &[AdminKey],
+    weights: &[u8],
+    threshold: u8,
+    signers_root: Option<[u8; 32]>,
+    upgrade_key: &Option<AdminKey>,
+    curve: Curve,
+) -> ProgramResult {
+    if public_keys.len() > MAX_SIGNERS {
+        return Err(UpgradeError::InvalidSignerSet.into());
+    }
+
+    // Empty weights mean weight 1 for every signer, otherwise every signer should have non-zero weight
+    if !weights.is_empty() && (weights.len() != public_keys.len() || weights.contains(&0)) {
+        return Err(UpgradeError::InvalidSignerSet.into());
+    }
+
+    let total_weight: u64 = if weights.is_empty() {
+        public_keys.len() as u64
+    } else {
+        weights.iter().map(|weight| *weight as u64).sum()
+    };
+
+    // Number of signers committed by merkle root is unknown, so threshold can be checked only for stored signers
+    if threshold == 0 || (signers_root.is_none() && threshold as u64 > total_weight) {
+        return Err(UpgradeError::InvalidSignerSet.into());
+    }
+
+    for (i, key) in public_keys.iter().enumerate() {
+        if public_keys[..i].contains(key) {
+            return Err(UpgradeError::InvalidSignerSet.into());
+        }
+
+        validate_key(key, curve)?;
+    }
+
+    // Hot key should be distinct from the signer set keys
+    if let Some(upgrade_key) = upgrade_key {
+        if public_keys.contains(upgrade_key) {
+            return Err(UpgradeError::InvalidSignerSet.into());
+        }
+
+        validate_key(upgrade_key, curve)?;
+    }
+
+    Ok(())
+}
+
+
+/// Checks that the key is not zero, is a valid point of its curve and can be used with UpgradeAdmin `curve`.
+/// Such key can never sign, so installing it by mistake would brick the admin.
+fn validate_key(key: &AdminKey, curve: Curve) -> ProgramResult {
+    // Compressed keys are prefixed with parity byte, so only X coordinate can be zero
+    let key_bytes = match key {
+        AdminKey::CompressedPublicKey(public_key) => &public_key[1..],
+        _ => key.as_ref(),
+    };
+
+    if key_bytes.iter().all(|byte| *byte == 0) {
+        return Err(UpgradeError::ZeroPublicKey.into());
+    }
+
+    let is_valid = match (key, curve) {
+        // Uncompressed keys and Ethereum addresses are supported only for secp256k1 signers
+        (AdminKey::PublicKey(_), Curve::Secp256r1) | (AdminKey::EthAddress(_), Curve::Secp256r1) => {
+            return Err(UpgradeError::CurveMismatch.into());
+        }
+        (AdminKey::PublicKey(_), Curve::Secp256k1) | (AdminKey::CompressedPublicKey(_), Curve::Secp256k1) => is_valid_secp256k1_key(key),
+        (AdminKey::CompressedPublicKey(public_key), Curve::Secp256r1) => is_valid_secp256r1_key(public_key),
+        (AdminKey::Ed25519(public_key), _) => is_valid_ed25519_key(public_key),
+        (AdminKey::EthAddress(_), Curve::Secp256k1) => true,
+    };
+
+    if !is_valid {
+        return Err(UpgradeError::InvalidCurvePoint.into());
+    }
+
+    Ok(())
+}
+
+
+/// Checks that managed contracts list fits into the account and has no duplicates or the primary contract.
+fn validate_managed_contracts(contract: &Pubkey, managed_contracts: &[Pubkey]) -> ProgramResult {
+    if managed_contracts.len() > MAX_MANAGED_CONTRACTS {
+        return Err(UpgradeError::InvalidManagedContracts.into());
+    }
+
+    for (i, managed) in managed_contracts.iter().enumerate() {
+        if managed == contract || managed_contracts[..i].contains(managed) {
+            return Err(UpgradeError::InvalidManagedContracts.into());
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Checks that executors allowlist fits into the account and has no duplicates.
+fn validate_executors(executors: &[Pubkey]) -> ProgramResult {
+    if executors.len() > MAX_EXECUTORS {
+        return Err(UpgradeError::InvalidExecutors.into());
+    }
+
+    for (i, executor) in executors.iter().enumerate() {
+        if executors[..i].contains(executor) {
+            return Err(UpgradeError::InvalidExecutors.into());
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Checks that role table fits into the account, has no duplicates, binds executors to Solana signers and
+/// assigns every role, so the pipeline can not get stuck. Approval threshold should be reachable.
+fn validate_roles(roles: &[RoleAssignment], approval_threshold: u8) -> ProgramResult {
+    if roles.is_empty() {
+        return match approval_threshold {
+            0 => Ok(()),
+            _ => Err(UpgradeError::InvalidRoles.into()),
+        };
+    }
+
+    if roles.len() > MAX_ROLES {
+        return Err(UpgradeError::InvalidRoles.into());
+    }
+
+    for (i, assignment) in roles.iter().enumerate() {
+        if roles[..i].contains(assignment) {
+            return Err(UpgradeError::InvalidRoles.into());
+        }
+
+        if assignment.role == Role::Executor && !matches!(assignment.key, RoleKey::Signer(_)) {
+            return Err(UpgradeError::InvalidRoles.into());
+        }
+    }
+
+    let approvers = roles.iter().filter(|assignment| assignment.role == Role::Approver).count();
+    let has_role = |role: Role| roles.iter().any(|assignment| assignment.role == role);
+    if !has_role(Role::Proposer) || !has_role(Role::Executor) || approval_threshold == 0 || approval_threshold as usize > approvers {
+        return Err(UpgradeError::InvalidRoles.into());
+    }
+
+    Ok(())
+}
+
+
+/// Finds the key bound to `role` that authorized the operation: a Solana signer of the transaction
+/// or an ECDSA key that signed `hash`.
+fn verify_role(
+    upgrade_admin: &UpgradeAdmin,
+    role: Role,
+    hash: &[u8],
+    signatures: &[EcdsaSignature],
+    accounts: &[AccountInfo],
+) -> Result<RoleKey, ProgramError> {
+    upgrade_admin.roles.iter()
+        .filter(|assignment| assignment.role == role)
+        .find(|assignment| is_authorized_by(upgrade_admin, &assignment.key, hash, signatures, accounts))
+        .map(|assignment| assignment.key.clone())
+        .ok_or_else(|| UpgradeError::RoleNotAuthorized.into())
+}
+
+
+/// Checks whether `key` signed the transaction or, for ECDSA key, signed `hash`.
+fn is_authorized_by(upgrade_admin: &UpgradeAdmin, key: &RoleKey, hash: &[u8], signatures: &[EcdsaSignature], accounts: &[AccountInfo]) -> bool {
+    match key {
+        RoleKey::Signer(key) => accounts.iter().any(|info| info.is_signer && info.key == key),
+        RoleKey::Ecdsa(key) => verify_signers(hash, signatures, accounts, core::slice::from_ref(key), &[], 1, upgrade_admin.curve).is_ok(),
+    }
+}
+
+
+/// Pays `reimbursement` lamports from UpgradeAdmin to the executor. The payment is skipped if UpgradeAdmin
+/// would drop below rent exemption, so an unfunded admin never blocks the upgrade.
+fn reimburse_executor(upgrade_admin_info: &AccountInfo, executor_info: &AccountInfo, rent: &Rent, reimbursement: u64) -> ProgramResult {
+    if reimbursement == 0 {
+        return Ok(());
+    }
+
+    let available = upgrade_admin_info.lamports().saturating_sub(rent.minimum_balance(upgrade_admin_info.data_len()));
+    if available < reimbursement {
+        msg!("Not enough lamports for reimbursement: available {}, required {}", available, reimbursement);
+        return Ok(());
+    }
+
+    **upgrade_admin_info.try_borrow_mut_lamports()? -= reimbursement;
+    **executor_info.try_borrow_mut_lamports()? += reimbursement;
+    msg!("Executor {} reimbursed with {} lamports", executor_info.key, reimbursement);
+    Ok(())
+}
+
+
+/// Passes Borsh-encoded `result` of the instruction to the caller.
+fn set_result<T: BorshSerialize>(result: &T) -> ProgramResult {
+    set_return_data(&result.try_to_vec()?);
+    Ok(())
+}
+
+
+/// Rejects the same account passed for several roles, e.g. spill that is also the buffer, which would break
+/// lamport flows between them.
+fn verify_distinct_accounts(accounts: &[&AccountInfo]) -> ProgramResult {
+    for (i, account) in accounts.iter().enumerate() {
+        if accounts[i + 1..].iter().any(|other| other.key == account.key) {
+            msg!("Account {} is passed several times", account.key);
+            return Err(UpgradeError::DuplicateAccount.into());
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Rejects `code_hash` of `contract` that differs from its required verified build.
+fn verify_build_hash(upgrade_admin: &UpgradeAdmin, contract: &Pubkey, code_hash: &[u8; 32]) -> ProgramResult {
+    let verified_build = upgrade_admin.verified_builds.iter().find(|build| build.contract == *contract);
+    if let Some(VerifiedBuild { build_hash, required: true, .. }) = verified_build {
+        if build_hash != code_hash {
+            msg!("Verified build hash of {} is {:?}", contract, build_hash);
+            return Err(UpgradeError::BuildHashNotVerified.into());
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Rejects `contract` that is neither the primary contract of the admin nor one of its managed contracts.
+/// Admin of a single contract reports ContractMismatch, so program accounts passed by mistake are easy to spot.
+fn verify_managed(upgrade_admin: &UpgradeAdmin, contract: &Pubkey) -> ProgramResult {
+    if upgrade_admin.contract == *contract {
+        return Ok(());
+    }
+
+    if upgrade_admin.managed_contracts.is_empty() {
+        msg!("Admin contract is {}, got {}", upgrade_admin.contract, contract);
+        return Err(UpgradeError::ContractMismatch.into());
+    }
+
+    if !upgrade_admin.managed_contracts.contains(contract) {
+        return Err(UpgradeError::ContractNotManaged.into());
+    }
+
+    Ok(())
+}
+
+
+/// Reads upgrade authority of `contract` from its ProgramData account. `None` means the contract is immutable.
+fn program_upgrade_authority(contract: &Pubkey, program_data_info: &AccountInfo) -> Result<Option<Pubkey>, ProgramError> {
+    verify_program_data(contract, program_data_info)?;
+    if !bpf_loader_upgradeable::check_id(program_data_info.owner) {
+        return Err(UpgradeError::ProgramDataOwnerMismatch.into());
+    }
+
+    // ProgramData metadata: u32 state discriminator, u64 deployment slot, Option<Pubkey> upgrade authority
+    let data = program_data_info.data.borrow();
+    if data.len() < PROGRAM_DATA_AUTHORITY_OFFSET + 33 || data[..4] != PROGRAM_DATA_DISCRIMINATOR.to_le_bytes() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    match data[PROGRAM_DATA_AUTHORITY_OFFSET] {
+        0 => Ok(None),
+        _ => Ok(Some(Pubkey::new_from_array(
+            data[PROGRAM_DATA_AUTHORITY_OFFSET + 1..PROGRAM_DATA_AUTHORITY_OFFSET + 33].try_into().unwrap(),
+        ))),
+    }
+}
+
+
+/// Checks that the guardian is a valid secp256k1 key distinct from the signer set and hot key,
+/// so recovery does not depend on the keys it is supposed to replace.
+fn validate_guardian(guardian: &AdminKey, public_keys: &[AdminKey], upgrade_key: &Option<AdminKey>) -> ProgramResult {
+    if let AdminKey::Ed25519(_) = guardian {
+        return Err(UpgradeError::CurveMismatch.into());
+    }
+
+    validate_key(guardian, Curve::Secp256k1)?;
+
+    if public_keys.contains(guardian) || upgrade_key.as_ref() == Some(guardian) {
+        return Err(UpgradeError::InvalidSignerSet.into());
+    }
+
+    Ok(())
+}
+
+
+/// Verifies that none of `public_keys` and `upgrade_key` is present in the RevokedKeys list of UpgradeAdmin.
+/// Not created RevokedKeys account means that no keys were revoked.
+fn verify_not_revoked(
+    program_id: &Pubkey,
+    revoked_keys_info: &AccountInfo,
+    upgrade_admin: &Pubkey,
+    public_keys: &[AdminKey],
+    upgrade_key: &Option<AdminKey>,
+) -> ProgramResult {
+    let (revoked_keys_key, _) = pda::find_revoked_keys_address(program_id, upgrade_admin);
+    if revoked_keys_key != *revoked_keys_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    if revoked_keys_info.data_is_empty() {
+        return Ok(());
+    }
+
+    let revoked_keys: RevokedKeys = BorshDeserialize::deserialize(&mut revoked_keys_info.data.borrow().as_ref())?;
+    if public_keys.iter().chain(upgrade_key.iter()).any(|key| revoked_keys.keys.contains(key)) {
+        return Err(UpgradeError::KeyRevoked.into());
+    }
+
+    Ok(())
+}
+
+
+/// Verifies that `hash` is signed by the hot upgrade key if it is set, otherwise by admin signers.
+fn verify_upgrade_signatures(
+    hash: &[u8],
+    signatures: &[EcdsaSignature],
+    signer_proofs: &[SignerProof],
+    accounts: &[AccountInfo],
+    upgrade_admin: &UpgradeAdmin,
+) -> ProgramResult {
+    match &upgrade_admin.upgrade_key {
+        Some(upgrade_key) => verify_signers(hash, signatures, accounts, core::slice::from_ref(upgrade_key), &[], 1, upgrade_admin.curve),
+        None => verify_signatures(hash, signatures, signer_proofs, accounts, upgrade_admin),
+    }
+}
+
+
+/// Verifies that `hash` is signed by the session key that is not expired and has `permission`.
+/// Session key account address is expected to be already checked by the caller.
+fn verify_session_signatures(
+    hash: &[u8],
+    signatures: &[EcdsaSignature],
+    accounts: &[AccountInfo],
+    session_key_info: &AccountInfo,
+    permission: u8,
+    curve: Curve,
+) -> ProgramResult {
+    let session_key: SessionKey = BorshDeserialize::deserialize(&mut session_key_info.data.borrow().as_ref())?;
+    if !session_key.is_initialized {
+        return Err(UpgradeError::NotInitialized.into());
+    }
+
+    if Clock::get()?.slot > session_key.expiry_slot {
+        return Err(UpgradeError::SessionExpired.into());
+    }
+
+    if session_key.permissions & permission == 0 {
+        return Err(UpgradeError::SessionNotPermitted.into());
+    }
+
+    verify_signers(hash, signatures, accounts, core::slice::from_ref(&session_key.key), &[], 1, curve)
+}
+
+
+/// Creates and initializes empty AdminHistory account of UpgradeAdmin.
+fn create_admin_history<'a>(
+    program_id: &Pubkey,
+    admin_history_info: &AccountInfo<'a>,
+    upgrade_admin: &Pubkey,
+    fee_payer_info: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+) -> ProgramResult {
+    let (admin_history_key, bump) = pda::find_admin_history_address(program_id, upgrade_admin);
+    if admin_history_key != *admin_history_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    create_pda_account::<UpgradeError>(admin_history_info, fee_payer_info, system_program, rent, MAX_ADMIN_HISTORY_SIZE, program_id, &[PDA_ADMIN_HISTORY_SEED.as_bytes(), upgrade_admin.as_ref(), &[bump]])?;
+
+    let admin_history = AdminHistory {
+        admin: *upgrade_admin,
+        count: 0,
+        entries: vec![],
+        is_initialized: true,
+    };
+    admin_history.serialize(&mut *admin_history_info.data.borrow_mut())?;
+    Ok(())
+}
+
+
+/// Appends `entry` to AdminHistory of UpgradeAdmin.
+fn record_history(program_id: &Pubkey, admin_history_info: &AccountInfo, upgrade_admin: &Pubkey, entry: HistoryEntry) -> ProgramResult {
+    let (admin_history_key, _) = pda::find_admin_history_address(program_id, upgrade_admin);
+    if admin_history_key != *admin_history_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    let mut admin_history: AdminHistory = BorshDeserialize::deserialize(&mut admin_history_info.data.borrow().as_ref())?;
+    if !admin_history.is_initialized {
+        return Err(UpgradeError::NotInitialized.into());
+    }
+
+    admin_history.push(entry);
+    admin_history.serialize(&mut *admin_history_info.data.borrow_mut())?;
+    Ok(())
+}
+
+
+/// Hash of the whole signer set recorded in AdminHistory.
+fn signer_set_hash(
+    public_keys: &[AdminKey],
+    weights: &[u8],
+    threshold: u8,
+    signers_root: Option<[u8; 32]>,
+    upgrade_key: &Option<AdminKey>,
+) -> Result<[u8; 32], ProgramError> {
+    let signer_set = (public_keys, weights, threshold, signers_root, upgrade_key).try_to_vec()?;
+    Ok(keccak::hash(&signer_set).to_bytes())
+}
+
+
+/// Hash of the keys authorizing Upgrade: hot upgrade key if it is set, otherwise signer set with threshold.
+fn upgrade_authority_hash(upgrade_admin: &UpgradeAdmin) -> Result<[u8; 32], ProgramError> {
+    let authority = match &upgrade_admin.upgrade_key {
+        Some(upgrade_key) => upgrade_key.try_to_vec()?,
+        None => (&upgrade_admin.public_keys, &upgrade_admin.weights, upgrade_admin.threshold, upgrade_admin.signers_root).try_to_vec()?,
+    };
+
+    Ok(keccak::hash(&authority).to_bytes())
+}
+
+
+/// Removes `hash` from the operations approved by VerifyBatch. Approval can be consumed only by UpgradeAdmin
+/// with the same upgrade authority as the one that approved the batch.
+fn consume_batch_approval(
+    program_id: &Pubkey,
+    batch_approval_info: &AccountInfo,
+    upgrade_admin: &UpgradeAdmin,
+    hash: &[u8; 32],
+) -> ProgramResult {
+    let mut batch_approval: BatchApproval = BorshDeserialize::deserialize(&mut batch_approval_info.data.borrow().as_ref())?;
+    if !batch_approval.is_initialized {
+        return Err(UpgradeError::NotInitialized.into());
+    }
+
+    let (batch_approval_key, _) = pda::find_batch_approval_address(program_id, &batch_approval.admin);
+    if batch_approval_key != *batch_approval_info.key {
+        return Err(UpgradeError::WrongSeeds.into());
+    }
+
+    verify_not_expired::<UpgradeError>(batch_approval.expires_at)?;
+
+    if batch_approval.authority_hash != upgrade_authority_hash(upgrade_admin)? {
+        return Err(UpgradeError::OperationNotApproved.into());
+    }
+
+    let position = batch_approval.operations.iter().position(|operation| operation == hash).ok_or(UpgradeError::OperationNotApproved)?;
+    batch_approval.operations.remove(position);
+    batch_approval.serialize(&mut *batch_approval_info.data.borrow_mut())?;
+    Ok(())
+}
+
+
+/// Verifies that `hash` is signed by admin signers with cumulative weight of at least `threshold`. Signers are the stored ones and
+/// the ones proven to be included into the stored merkle root. ECDSA signatures are passed in instruction args,
+/// ed25519 signatures and ECDSA signatures verified by `secp256k1_program` precompile are loaded from
+/// the instructions sysvar if it is provided in accounts.
+fn verify_signatures(
+    hash: &[u8],
+    signatures: &[EcdsaSignature],
+    signer_proofs: &[SignerProof],
+    accounts: &[AccountInfo],
+    upgrade_admin: &UpgradeAdmin,
+) -> ProgramResult {
+    let mut target_keys = upgrade_admin.public_keys.clone();
+    for proof in signer_proofs {
+        let root = upgrade_admin.signers_root.ok_or(UpgradeError::InvalidSignerProof)?;
+        if !verify_proof(proof, &root) {
+            return Err(UpgradeError::InvalidSignerProof.into());
+        }
+
+        if !target_keys.contains(&proof.key) {
+            target_keys.push(proof.key.clone());
+        }
+    }
+
+    verify_signers(hash, signatures, accounts, &target_keys, &upgrade_admin.weights, upgrade_admin.threshold, upgrade_admin.curve)
+}
+
+
+/// Verifies that `hash` is signed by `target_keys` with cumulative `weights` of at least `threshold` using signatures
+/// from instruction args and from the instructions sysvar if it is provided in accounts. ECDSA keys are verified
+/// on `curve`: P-256 public keys can not be recovered from signature, so they are verified only by `secp256r1_program`.
+fn verify_signers(
+    hash: &[u8],
+    signatures: &[EcdsaSignature],
+    accounts: &[AccountInfo],
+    target_keys: &[AdminKey],
+    weights: &[u8],
+    threshold: u8,
+    curve: Curve,
+) -> ProgramResult {
+    let mut signed = vec![false; target_keys.len()];
+    match curve {
+        Curve::Secp256k1 => mark_ecdsa_signers(hash, signatures, target_keys, &mut signed)?,
+        Curve::Secp256r1 => {
+            if !signatures.is_empty() {
+                return Err(UpgradeError::CurveMismatch.into());
+            }
+        }
+    }
+
+    if let Some(instructions_info) = accounts.iter().find(|info| sysvar::instructions::check_id(info.key)) {
+        mark_ed25519_signers(instructions_info, hash, target_keys, &mut signed)?;
+        match curve {
+            Curve::Secp256k1 => mark_secp256k1_precompile_signers(instructions_info, hash, target_keys, &mut signed)?,
+            Curve::Secp256r1 => mark_secp256r1_signers(instructions_info, hash, target_keys, &mut signed)?,
+        }
+    }
+
+    verify_threshold(&signed, weights, threshold)
+}
+
+
+/// Advances operation nonce, overflow is reported instead of wrapping so old signatures never become valid again.
+/// Nonces of U64 admins are limited by u64::MAX, so they always fit into their signed messages.
+fn increment_nonce(nonce: &mut u128, nonce_width: NonceWidth) -> ProgramResult {
+    let max = match nonce_width {
+        NonceWidth::U64 => u64::MAX as u128,
+        NonceWidth::U128 => u128::MAX,
+    };
+
+    *nonce = nonce.checked_add(1).filter(|nonce| *nonce <= max).ok_or(UpgradeError::NonceOverflow)?;
+    Ok(())
+}
+
+
+/// Rejects every operation of deactivated UpgradeAdmin.
+fn verify_active(upgrade_admin: &UpgradeAdmin) -> ProgramResult {
+    if !upgrade_admin.active {
+        return Err(UpgradeError::AdminDeactivated.into());
+    }
+
+    Ok(())
+}
+
+
+/// Rejects operations after the expiry slot of UpgradeAdmin.
+fn verify_admin_not_expired(upgrade_admin: &UpgradeAdmin) -> ProgramResult {
+    if let Some(expires_at_slot) = upgrade_admin.expires_at_slot {
+        let slot = Clock::get()?.slot;
+        if slot > expires_at_slot {
+            msg!("Admin expired at slot {}, current slot {}", expires_at_slot, slot);
+            return Err(UpgradeError::AdminExpired.into());
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Rejects an upgrade within `min_upgrade_interval_slots` after the last one.
+fn verify_upgrade_interval(upgrade_admin: &UpgradeAdmin, slot: u64) -> ProgramResult {
+    if let Some(last_upgrade_slot) = upgrade_admin.last_upgrade_slot {
+        let next_upgrade_slot = last_upgrade_slot.saturating_add(upgrade_admin.min_upgrade_interval_slots);
+        if slot < next_upgrade_slot {
+            msg!("Next upgrade is allowed at slot {}, current slot {}", next_upgrade_slot, slot);
+            return Err(UpgradeError::UpgradeTooSoon.into());
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Rejects Upgrade, BatchUpgrade and Rollback that would skip the ProposeUpgrade delay, role approvals or veto.
+fn verify_direct_upgrade(upgrade_admin: &UpgradeAdmin) -> ProgramResult {
+    if upgrade_admin.min_delay_slots > 0 {
+        msg!("Upgrade delay is {} slots, use ProposeUpgrade and ExecuteUpgrade", upgrade_admin.min_delay_slots);
+        return Err(UpgradeError::TimelockRequired.into());
+    }
+
+    if !upgrade_admin.roles.is_empty() {
+        msg!("UpgradeAdmin has roles, use ProposeUpgrade, ApproveUpgrade and ExecuteUpgrade");
+        return Err(UpgradeError::ProposalRequired.into());
+    }
+
+    if upgrade_admin.veto_key.is_some() {
+        msg!("UpgradeAdmin has veto key, use ProposeUpgrade and ExecuteUpgrade");
+        return Err(UpgradeError::ProposalRequired.into());
+    }
+
+    Ok(())
+}
+
+
+/// Rejects an upgrade while the previous upgrade has not passed its healthcheck.
+fn verify_healthcheck_passed(upgrade_admin: &UpgradeAdmin) -> ProgramResult {
+    if let Some(unverified_upgrade) = upgrade_admin.unverified_upgrade {
+        msg!("Upgrade of {} did not pass healthcheck", unverified_upgrade);
+        return Err(UpgradeError::HealthcheckPending.into());
+    }
+
+    Ok(())
+}
+
+
+/// Blocks further upgrades until RunHealthcheck passes if `contract` has a healthcheck.
+fn mark_unverified(upgrade_admin: &mut UpgradeAdmin, contract: &Pubkey) {
+    if upgrade_admin.healthchecks.iter().any(|healthcheck| healthcheck.contract == *contract) {
+        upgrade_admin.unverified_upgrade = Some(*contract);
+    }
+}
+
+
+/// Rejects operations frozen by Pause.
+fn verify_not_paused(upgrade_admin: &UpgradeAdmin) -> ProgramResult {
+    if upgrade_admin.paused {
+        return Err(UpgradeError::Paused.into());
+    }
+
+    Ok(())
+}
+
@@ -0,0 +1,40 @@
+//! Borsh-encoded results of instructions passed through `set_return_data`, so CPI callers and simulators
+//! can read them without parsing the account data. Instructions without a result leave return data empty.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+/// Result of InitAdmin.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AdminResult {
+    // Created UpgradeAdmin account
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+}
+
+/// Result of every instruction that consumes a nonce, except the ones returning UpgradeResult.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NonceResult {
+    // Nonce that the next operation of the same kind should be signed with
+    pub nonce: u128,
+}
+
+/// Result of Upgrade, ExecuteUpgrade and Rollback.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UpgradeResult {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    // Buffer deployed to the contract
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub buffer: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    // Upgrade nonce that the next upgrade should be signed with
+    pub nonce: u128,
+}
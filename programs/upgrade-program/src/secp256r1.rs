@@ -0,0 +1,105 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use solana_program::msg;
+use solana_program::entrypoint::ProgramResult;
+use crate::error::UpgradeError;
+use crate::state::{AdminKey, SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH};
+
+/// Native program that verifies secp256r1 (P-256) signatures
+pub const SECP256R1_PROGRAM_ID: Pubkey = solana_program::pubkey!("Secp256r1SigVerify1111111111111111111111111");
+
+/// P-256 public key in compressed format has the same length as compressed secp256k1 key
+pub const SECP256R1_COMPRESSED_PUBLIC_KEY_LENGTH: usize = SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH;
+
+/// Field prime of the P-256 curve (big-endian)
+pub const SECP256R1_FIELD_PRIME: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+const SECP256R1_SIGNATURE_OFFSETS_START: usize = 2;
+
+const SECP256R1_SIGNATURE_OFFSETS_SIZE: usize = 14;
+
+/// Checks that compressed P-256 public key has valid parity prefix and X coordinate is a field element.
+/// Whether X has a matching Y is verified by `secp256r1_program` together with the signature.
+pub fn is_valid_secp256r1_key(public_key: &[u8; SECP256R1_COMPRESSED_PUBLIC_KEY_LENGTH]) -> bool {
+    (public_key[0] == 0x02 || public_key[0] == 0x03) && public_key[1..] < SECP256R1_FIELD_PRIME[..]
+}
+
+/// Marks in `signed` every P-256 signer from `target_keys` that signed `message`
+/// in preceding `secp256r1_program` instructions.
+pub fn mark_secp256r1_signers(instructions_info: &AccountInfo, message: &[u8], target_keys: &[AdminKey], signed: &mut [bool]) -> ProgramResult {
+    for signer in load_secp256r1_signers(instructions_info, message)? {
+        let index = target_keys.iter().position(|target_key| *target_key == AdminKey::CompressedPublicKey(signer));
+        if index.is_none() {
+            return ProgramResult::Err(UpgradeError::PrecompileSignerMismatch.into());
+        }
+
+        let index = index.unwrap();
+        if signed[index] {
+            return ProgramResult::Err(UpgradeError::DuplicateSignature.into());
+        }
+
+        signed[index] = true;
+    }
+
+    Ok(())
+}
+
+/// Collects compressed P-256 public keys that signed `message` in `secp256r1_program` instructions
+/// preceding the current one. Only signatures that store public key, signature and message
+/// inside the same `secp256r1_program` instruction are taken into account.
+pub fn load_secp256r1_signers(instructions_info: &AccountInfo, message: &[u8]) -> Result<Vec<[u8; SECP256R1_COMPRESSED_PUBLIC_KEY_LENGTH]>, ProgramError> {
+    let current_index = load_current_index_checked(instructions_info)?;
+    let mut signers = Vec::new();
+
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_info)?;
+        if instruction.program_id != SECP256R1_PROGRAM_ID {
+            continue;
+        }
+
+        let data = instruction.data.as_slice();
+        if data.len() < SECP256R1_SIGNATURE_OFFSETS_START {
+            return Err(UpgradeError::InvalidSecp256r1Instruction.into());
+        }
+
+        for i in 0..data[0] as usize {
+            let start = SECP256R1_SIGNATURE_OFFSETS_START + i * SECP256R1_SIGNATURE_OFFSETS_SIZE;
+            let offsets = data.get(start..start + SECP256R1_SIGNATURE_OFFSETS_SIZE)
+                .ok_or(UpgradeError::InvalidSecp256r1Instruction)?;
+
+            let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+            let signature_instruction_index = read_u16(2);
+            let public_key_offset = read_u16(4) as usize;
+            let public_key_instruction_index = read_u16(6);
+            let message_data_offset = read_u16(8) as usize;
+            let message_data_size = read_u16(10) as usize;
+            let message_instruction_index = read_u16(12);
+
+            if signature_instruction_index != u16::MAX || public_key_instruction_index != u16::MAX || message_instruction_index != u16::MAX {
+                continue;
+            }
+
+            let signed_message = data.get(message_data_offset..message_data_offset + message_data_size)
+                .ok_or(UpgradeError::InvalidSecp256r1Instruction)?;
+            if signed_message != message {
+                continue;
+            }
+
+            let public_key = data.get(public_key_offset..public_key_offset + SECP256R1_COMPRESSED_PUBLIC_KEY_LENGTH)
+                .ok_or(UpgradeError::InvalidSecp256r1Instruction)?;
+
+            let mut signer = [0u8; SECP256R1_COMPRESSED_PUBLIC_KEY_LENGTH];
+            signer.copy_from_slice(public_key);
+            msg!("Found secp256r1 signer: {}", bs58::encode(signer.as_ref()).into_string().as_str());
+            signers.push(signer);
+        }
+    }
+
+    Ok(signers)
+}
@@ -0,0 +1,38 @@
+//! Serializers of the `serde` feature: addresses are rendered in base58 and byte strings in hex.
+
+use core::fmt::Display;
+use serde::ser::{SerializeSeq, Serializer};
+
+pub fn display<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+pub fn option_display<T: Display, S: Serializer>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.collect_str(value),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn seq_display<T: Display, S: Serializer>(value: &[T], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(value.iter().map(|value| value.to_string()))
+}
+
+pub fn hex<T: AsRef<[u8]>, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(value))
+}
+
+pub fn option_hex<T: AsRef<[u8]>, S: Serializer>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.serialize_str(&hex::encode(value)),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn seq_hex<T: AsRef<[u8]>, S: Serializer>(value: &[T], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(value.len()))?;
+    for value in value {
+        seq.serialize_element(&hex::encode(value))?;
+    }
+    seq.end()
+}
@@ -0,0 +1,101 @@
+//! Resumable SHA-256 whose state is kept in an account between instructions
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use sha2::compress256;
+
+pub const SHA256_BLOCK_SIZE: usize = 64;
+
+/// Initial chaining value defined by FIPS 180-4
+pub const SHA256_INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Serialized size of IncrementalSha256
+pub const INCREMENTAL_SHA256_SIZE: usize = 8 * 4 + SHA256_BLOCK_SIZE + 1 + 8;
+
+/// SHA-256 of data that is supplied by parts in different transactions. The result is equal to
+/// SHA-256 of the concatenated parts.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct IncrementalSha256 {
+    // Chaining value after the last complete block
+    pub state: [u32; 8],
+    // Bytes of the incomplete block
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub block: [u8; SHA256_BLOCK_SIZE],
+    pub block_len: u8,
+    // Number of hashed bytes
+    pub total_len: u64,
+}
+
+impl Default for IncrementalSha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IncrementalSha256 {
+    pub fn new() -> Self {
+        Self {
+            state: SHA256_INITIAL_STATE,
+            block: [0; SHA256_BLOCK_SIZE],
+            block_len: 0,
+            total_len: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len = self.total_len.wrapping_add(data.len() as u64);
+
+        if self.block_len > 0 {
+            let block_len = self.block_len as usize;
+            let take = core::cmp::min(SHA256_BLOCK_SIZE - block_len, data.len());
+            self.block[block_len..block_len + take].copy_from_slice(&data[..take]);
+            self.block_len += take as u8;
+            data = &data[take..];
+
+            if (self.block_len as usize) < SHA256_BLOCK_SIZE {
+                return;
+            }
+
+            compress(&mut self.state, &self.block);
+            self.block_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(SHA256_BLOCK_SIZE);
+        for block in &mut blocks {
+            compress(&mut self.state, block);
+        }
+
+        let rest = blocks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+        self.block_len = rest.len() as u8;
+    }
+
+    pub fn finalize(&self) -> [u8; 32] {
+        // Padding: 0x80, zeros up to 56 bytes modulo block size and message length in bits
+        let mut tail = self.block[..self.block_len as usize].to_vec();
+        tail.push(0x80);
+        while tail.len() % SHA256_BLOCK_SIZE != SHA256_BLOCK_SIZE - 8 {
+            tail.push(0);
+        }
+        tail.extend_from_slice(&self.total_len.wrapping_mul(8).to_be_bytes());
+
+        let mut state = self.state;
+        for block in tail.chunks_exact(SHA256_BLOCK_SIZE) {
+            compress(&mut state, block);
+        }
+
+        let mut result = [0u8; 32];
+        for (chunk, word) in result.chunks_exact_mut(4).zip(state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+}
+
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let block: [u8; SHA256_BLOCK_SIZE] = block.try_into().unwrap();
+    compress256(state, &[block.into()]);
+}
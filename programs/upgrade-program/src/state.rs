@@ -1,14 +1,630 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use solana_program::secp256k1_recover::SECP256K1_PUBLIC_KEY_LENGTH;
+use shank::ShankAccount;
+use crate::error::UpgradeError;
+use crate::hashing::{HashAlgorithm, HashingMode, NonceWidth};
+use crate::sha256::{IncrementalSha256, INCREMENTAL_SHA256_SIZE};
+use crate::PDA_ADMIN_SEED;
 
-pub const MAX_ADMIN_SIZE: usize = SECP256K1_PUBLIC_KEY_LENGTH + (32 as usize) + (8 as usize) + (1 as usize);
+/// Version of the current UpgradeAdmin layout stored in its first byte. V1 layout has no version byte.
+pub const ADMIN_VERSION: u8 = 2;
 
+/// Size of V1 UpgradeAdmin account: public key, contract, nonce and initialization flag
+pub const V1_ADMIN_SIZE: usize = SECP256K1_PUBLIC_KEY_LENGTH + 32 + 8 + 1;
+
+pub const ETH_ADDRESS_LENGTH: usize = 20;
+
+pub const SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH: usize = 33;
+
+pub const MAX_ADMIN_KEY_SIZE: usize = 1 + SECP256K1_PUBLIC_KEY_LENGTH;
+
+pub const MAX_SIGNERS: usize = 10;
+
+/// Signer keys vector, weights vector, threshold, signers merkle root and hot upgrade key
+pub const MAX_SIGNER_SET_SIZE: usize = 4 + MAX_SIGNERS * MAX_ADMIN_KEY_SIZE + 4 + MAX_SIGNERS + 1 + 33 + 1 + MAX_ADMIN_KEY_SIZE;
+
+pub const MAX_PENDING_KEY_CHANGE_SIZE: usize = MAX_SIGNER_SET_SIZE + 8;
+
+/// Signer keys vector, weights vector, threshold and activation slot
+pub const MAX_PENDING_RECOVERY_SIZE: usize = 4 + MAX_SIGNERS * MAX_ADMIN_KEY_SIZE + 4 + MAX_SIGNERS + 1 + 8;
+
+/// Minimal number of slots between StartRecovery and CompleteRecovery (about one day),
+/// so signers have time to notice the recovery and cancel it if they still hold their keys
+pub const MIN_RECOVERY_DELAY: u64 = 216_000;
+
+/// Maximal number of contracts managed by UpgradeAdmin besides its primary contract
+pub const MAX_MANAGED_CONTRACTS: usize = 16;
+
+pub const MAX_URI_LENGTH: usize = 200;
+
+pub const MAX_EXECUTORS: usize = 8;
+
+/// Contracts upgraded by one BatchUpgrade, limited by transaction account count
+pub const MAX_BATCH_UPGRADES: usize = 4;
+
+pub const MAX_HISTORY_ENTRIES: usize = 32;
+
+/// Slot, operation, contract, old value, new value and signature hash
+pub const HISTORY_ENTRY_SIZE: usize = 8 + 1 + 32 + 32 + 32 + 32;
+
+pub const MAX_ADMIN_HISTORY_SIZE: usize = 32 + 8 + 4 + MAX_HISTORY_ENTRIES * HISTORY_ENTRY_SIZE + 1;
+
+pub const MAX_UPGRADE_RECORDS: usize = 32;
+
+/// Slot, buffer, code hash, optional executor and spill
+pub const UPGRADE_RECORD_SIZE: usize = 8 + 32 + 32 + 1 + 32 + 32;
+
+pub const MAX_UPGRADE_HISTORY_SIZE: usize = 32 + 32 + 8 + 4 + MAX_UPGRADE_RECORDS * UPGRADE_RECORD_SIZE + 1;
+
+pub const MAX_REVOKED_KEYS: usize = 32;
+
+pub const MAX_REVOKED_KEYS_SIZE: usize = 32 + 4 + MAX_REVOKED_KEYS * MAX_ADMIN_KEY_SIZE + 1;
+
+pub const MAX_BATCH_OPERATIONS: usize = 16;
+
+pub const MAX_ROLES: usize = 8;
+
+pub const MAX_HEALTHCHECKS: usize = 4;
+
+pub const MAX_HEALTHCHECK_DATA_LEN: usize = 64;
+
+/// Contract and instruction data
+pub const HEALTHCHECK_SIZE: usize = 32 + 4 + MAX_HEALTHCHECK_DATA_LEN;
+
+/// Primary contract and every managed contract can have a verified build
+pub const MAX_VERIFIED_BUILDS: usize = MAX_MANAGED_CONTRACTS + 1;
+
+/// Contract, build hash and required flag
+pub const VERIFIED_BUILD_SIZE: usize = 32 + 32 + 1;
+
+/// Key kind and the largest key
+pub const MAX_ROLE_KEY_SIZE: usize = 1 + MAX_ADMIN_KEY_SIZE;
+
+pub const ROLE_ASSIGNMENT_SIZE: usize = 1 + MAX_ROLE_KEY_SIZE;
+
+/// Admin, program, buffer, code hash, proposal slot, execution window, approvals and initialization flag
+pub const MAX_PENDING_UPGRADE_SIZE: usize = 32 + 32 + 32 + 32 + 8 + 9 + 9 + 4 + MAX_ROLES * 32 + 1;
+
+/// Admin, program, buffer, code hash, registration slot and initialization flag
+pub const MAX_ROLLBACK_SNAPSHOT_SIZE: usize = 32 + 32 + 32 + 32 + 8 + 1;
+
+/// Admin, program, buffer, data length, written length, hasher state, code hash, finalization and initialization flags
+pub const MAX_BUFFER_STAGING_SIZE: usize = 32 + 32 + 32 + 32 + 8 + 8 + INCREMENTAL_SHA256_SIZE + 32 + 1 + 1;
+
+pub const MAX_BATCH_APPROVAL_SIZE: usize = 32 + 32 + 4 + MAX_BATCH_OPERATIONS * 32 + 8 + 1;
+
+pub const MAX_SESSION_KEY_SIZE: usize = 32 + MAX_ADMIN_KEY_SIZE + 1 + 8 + 1;
+
+/// Session key can sign Upgrade
+pub const SESSION_PERMISSION_UPGRADE: u8 = 1 << 0;
+
+/// Operations that can be delegated to session key. ChangePublicKey, ChangeAuthority and other signer set
+/// management operations always require the signer set.
+pub const SESSION_ALLOWED_PERMISSIONS: u8 = SESSION_PERMISSION_UPGRADE;
+
+pub const MAX_ADMIN_SIZE: usize = 1 + MAX_SIGNER_SET_SIZE + 32 + 8 + 1 + 1 + 32 + 1 + MAX_PENDING_KEY_CHANGE_SIZE + 8 + 1 + 1 + 8 + 8
+    + 1 + MAX_ADMIN_KEY_SIZE + 8 + 1 + MAX_PENDING_RECOVERY_SIZE + 1 + 4 + MAX_MANAGED_CONTRACTS * 32 + 1
+    + 32 + 4 + MAX_URI_LENGTH + 1 + 1 + 8 + 4 + MAX_EXECUTORS * 32 + 8 + 8 + 8 + 1 + 8 + 1
+    + 4 + MAX_ROLES * ROLE_ASSIGNMENT_SIZE + 1 + 1 + MAX_ROLE_KEY_SIZE + 4 + MAX_HEALTHCHECKS * HEALTHCHECK_SIZE + 1 + 32
+    + 4 + MAX_VERIFIED_BUILDS * VERIFIED_BUILD_SIZE + 3 * 8 + 1;
+
+/// Identity of the signer that controls UpgradeAdmin.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AdminKey {
+    /// ECDSA public key (64 byte format)
+    PublicKey(#[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))] [u8; SECP256K1_PUBLIC_KEY_LENGTH]),
+    /// Ethereum address: last 20 bytes of keccak_hash(public_key)
+    EthAddress(#[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))] [u8; ETH_ADDRESS_LENGTH]),
+    /// Ed25519 public key, signature is verified by preceding `ed25519_program` instruction
+    Ed25519(#[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))] Pubkey),
+    /// Compressed ECDSA public key (33 byte format: 0x02/0x03 parity prefix and X coordinate)
+    CompressedPublicKey(#[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))] [u8; SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH]),
+}
+
+/// Instruction without accounts that the upgraded contract should execute successfully, e.g. version query.
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Healthcheck {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub data: Vec<u8>,
+}
+
+/// Hash of the program data produced by a verified build (e.g. solana-verify) of the contract.
+/// If `required` is set, upgrades of the contract should deploy exactly this build.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VerifiedBuild {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub build_hash: [u8; 32],
+    pub required: bool,
+}
+
+/// Step of the upgrade pipeline a key is allowed to perform.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Role {
+    /// Stages upgrades by ProposeUpgrade
+    Proposer,
+    /// Approves staged upgrades by ApproveUpgrade
+    Approver,
+    /// Submits ExecuteUpgrade
+    Executor,
+}
+
+/// Key bound to a role.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum RoleKey {
+    /// ECDSA or ed25519 key that signs the operation message
+    Ecdsa(AdminKey),
+    /// Solana account that signs the transaction
+    Signer(#[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))] Pubkey),
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RoleAssignment {
+    pub role: Role,
+    pub key: RoleKey,
+}
+
+/// Curve of ECDSA signer keys of UpgradeAdmin.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Curve {
+    /// secp256k1: keys are 64 byte, compressed 33 byte public keys or Ethereum addresses, signatures are passed
+    /// in instruction args or verified by `secp256k1_program`
+    Secp256k1,
+    /// secp256r1 (P-256) used by HSMs and WebAuthn passkeys: keys are compressed 33 byte public keys,
+    /// signatures are verified by `secp256r1_program`
+    Secp256r1,
+}
+
+impl AsRef<[u8]> for AdminKey {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            AdminKey::PublicKey(public_key) => public_key.as_ref(),
+            AdminKey::EthAddress(address) => address.as_ref(),
+            AdminKey::Ed25519(public_key) => public_key.as_ref(),
+            AdminKey::CompressedPublicKey(public_key) => public_key.as_ref(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct UpgradeAdmin {
+    // Layout version, ADMIN_VERSION for initialized accounts
+    pub version: u8,
+    // Set of signers allowed to authorize operations
+    pub public_keys: Vec<AdminKey>,
+    // Voting weight of every stored signer, empty means weight 1 for all signers
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub weights: Vec<u8>,
+    // Quorum: minimal cumulative weight of distinct signers required to authorize operation
+    pub threshold: u8,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    // Nonce of Upgrade, VerifyBatch, ProposeUpgrade, CancelUpgrade, Rollback, BatchUpgrade, ExtendProgram and CreateBuffer, independent of other operation types
+    pub upgrade_nonce: u128,
+    // Nonce of ChangeAuthority, CloseAdmin and SetImmutable
+    pub authority_nonce: u128,
+    // Nonce of ChangePublicKey, CancelKeyChange, RevokeKeys, CreateSession, Pause, Unpause, SetManagedContracts, SetMetadata, Deactivate, Reactivate, ExtendExpiry, SetExecutors, SetUpgradeDelay, SetUpgradeInterval, RegisterSnapshot, Deploy, CloseBuffer, SetRoles, SetVetoKey, SetHealthcheck and SetVerifiedBuildHash
+    pub key_nonce: u128,
+    pub is_initialized: bool,
+    // Format of the messages that should be signed
+    pub hashing_mode: HashingMode,
+    // Chain/deployment tag included in signed messages to prevent cross-cluster replay (e.g. cluster genesis hash)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub domain: [u8; 32],
+    // Merkle root of additional eligible signers that prove membership in every operation
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::option_hex"))]
+    pub signers_root: Option<[u8; 32]>,
+    // Operational hot key: if set, only this key can authorize Upgrade, other operations still require signer set
+    pub upgrade_key: Option<AdminKey>,
+    // Signer set change staged by ChangePublicKey and waiting for activation
+    pub pending_key_change: Option<PendingKeyChange>,
+    // Number of slots between ChangePublicKey and the moment new signer set can be activated
+    pub key_change_delay: u64,
+    // Curve of ECDSA signer keys
+    pub curve: Curve,
+    // Digest algorithm of legacy messages
+    pub hash_algorithm: HashAlgorithm,
+    // secp256k1 recovery key that can replace the signer set if signer keys are lost
+    pub guardian: Option<AdminKey>,
+    // Number of slots between StartRecovery and the moment recovery can be completed
+    pub recovery_delay: u64,
+    // Signer set replacement started by the guardian and waiting for completion
+    pub pending_recovery: Option<PendingRecovery>,
+    // Emergency freeze of Upgrade, ChangeAuthority and operations approving or delegating them
+    pub paused: bool,
+    // Contracts upgraded by the same signers and nonce stream besides `contract` that seeds the admin address
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_display"))]
+    pub managed_contracts: Vec<Pubkey>,
+    // Bump seed of the admin address found at init
+    pub bump: u8,
+    // Human-readable label of the admin owner (team, bridge), zero bytes if not set
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub name: [u8; 32],
+    // Off-chain metadata URI, empty if not set
+    pub uri: String,
+    // Deactivated admin rejects every operation except Reactivate, while keeping its state for audit
+    pub active: bool,
+    // Slot after which every operation except ExtendExpiry is rejected, None for no expiry
+    pub expires_at_slot: Option<u64>,
+    // Accounts allowed to submit Upgrade as transaction signers, empty allows anyone
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_display"))]
+    pub executors: Vec<Pubkey>,
+    // Lamports paid from the admin account to the allowed executor after successful Upgrade
+    pub reimbursement: u64,
+    // Slots between ProposeUpgrade and ExecuteUpgrade
+    pub min_delay_slots: u64,
+    // Slots between consecutive upgrades or rollbacks of any contract managed by the admin
+    pub min_upgrade_interval_slots: u64,
+    // Slot of the last Upgrade, ExecuteUpgrade or Rollback
+    pub last_upgrade_slot: Option<u64>,
+    // Upgrade authority of the primary contract was removed by SetImmutable, the admin stays deactivated forever
+    pub immutable: bool,
+    // Keys staging, approving and executing upgrades through ProposeUpgrade, ApproveUpgrade and ExecuteUpgrade, empty if roles are not separated
+    pub roles: Vec<RoleAssignment>,
+    // Distinct approvers required by ExecuteUpgrade when roles are set
+    pub approval_threshold: u8,
+    // Security council key that can only veto pending upgrades
+    pub veto_key: Option<RoleKey>,
+    // Instructions of managed contracts that RunHealthcheck invokes after ExecuteUpgrade
+    pub healthchecks: Vec<Healthcheck>,
+    // Contract upgraded by ExecuteUpgrade that did not pass its healthcheck yet, blocks the next ExecuteUpgrade
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::option_display"))]
+    pub unverified_upgrade: Option<Pubkey>,
+    // Attested hashes of reproducible builds of the primary and managed contracts
+    pub verified_builds: Vec<VerifiedBuild>,
+    // Width of the nonces in signed messages, nonces of U64 admins are limited by u64::MAX
+    pub nonce_width: NonceWidth,
+}
+
+impl UpgradeAdmin {
+    /// Bump seed of the admin address, clients can derive the address with it without searching.
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    /// Admin address derived from the primary contract and the stored bump.
+    pub fn address(&self, program_id: &Pubkey) -> Result<Pubkey, ProgramError> {
+        Pubkey::create_program_address(&[PDA_ADMIN_SEED.as_bytes(), self.contract.as_ref(), &[self.bump]], program_id)
+            .map_err(|_| UpgradeError::WrongSeeds.into())
+    }
+
+    /// Writes the admin into account `data` and zeroes the rest, so shrinking fields leave no stale bytes.
+    pub fn pack(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let encoded = self.try_to_vec()?;
+        if encoded.len() > data.len() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        data[..encoded.len()].copy_from_slice(&encoded);
+        data[encoded.len()..].fill(0);
+        Ok(())
+    }
+}
+
+/// UpgradeAdmin layout of the first program release with single ECDSA key and shared nonce.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UpgradeAdminV1 {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
     pub public_key: [u8; SECP256K1_PUBLIC_KEY_LENGTH],
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
     pub contract: Pubkey,
     pub nonce: u64,
     pub is_initialized: bool,
-}
\ No newline at end of file
+}
+
+/// UpgradeAdmin account data of any supported layout.
+#[allow(clippy::large_enum_variant)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(tag = "version", content = "admin"))]
+pub enum VersionedUpgradeAdmin {
+    V1(UpgradeAdminV1),
+    V2(UpgradeAdmin),
+}
+
+impl VersionedUpgradeAdmin {
+    /// Detects layout of UpgradeAdmin account data. V1 accounts have no version byte and are identified by their size,
+    /// zeroed data of not initialized account is decoded with the current layout.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() == V1_ADMIN_SIZE {
+            return Ok(VersionedUpgradeAdmin::V1(UpgradeAdminV1::deserialize(&mut &data[..])?));
+        }
+
+        match data.first() {
+            Some(&0) | Some(&ADMIN_VERSION) => {
+                let rest = &mut &data[..];
+                let upgrade_admin = UpgradeAdmin::deserialize(rest)?;
+                // Account is allocated for the largest layout, bytes after the encoded admin are kept zeroed by `pack`
+                if rest.iter().any(|byte| *byte != 0) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                Ok(VersionedUpgradeAdmin::V2(upgrade_admin))
+            }
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// Signer set change that can be activated after `activation_slot`.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PendingKeyChange {
+    pub public_keys: Vec<AdminKey>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub weights: Vec<u8>,
+    pub threshold: u8,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::option_hex"))]
+    pub signers_root: Option<[u8; 32]>,
+    pub upgrade_key: Option<AdminKey>,
+    pub activation_slot: u64,
+}
+
+/// Signer set replacement started by the guardian that can be completed after `activation_slot`.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PendingRecovery {
+    pub public_keys: Vec<AdminKey>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub weights: Vec<u8>,
+    pub threshold: u8,
+    pub activation_slot: u64,
+}
+
+/// Operation recorded in AdminHistory.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum HistoryOperation {
+    /// Signer set change staged by ChangePublicKey, values are signer set hashes
+    ChangePublicKey,
+    /// Upgrade authority transfer, values are old and new authority addresses
+    ChangeAuthority,
+    /// Upgrade authority removal, old value is the admin address and new value is zero
+    SetImmutable,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HistoryEntry {
+    pub slot: u64,
+    pub operation: HistoryOperation,
+    // Contract the operation applies to
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub contract: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub old_value: [u8; 32],
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub new_value: [u8; 32],
+    // Hash signed by UpgradeAdmin signers to authorize the operation
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub signature_hash: [u8; 32],
+}
+
+/// Append-only log of control changes of UpgradeAdmin. Keeps the last MAX_HISTORY_ENTRIES entries in a ring buffer.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AdminHistory {
+    // UpgradeAdmin account the history belongs to
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    // Number of entries ever recorded, entry `i` is stored at index `i % MAX_HISTORY_ENTRIES`
+    pub count: u64,
+    pub entries: Vec<HistoryEntry>,
+    pub is_initialized: bool,
+}
+
+impl AdminHistory {
+    /// Records `entry` overwriting the oldest one when the buffer is full.
+    pub fn push(&mut self, entry: HistoryEntry) {
+        if self.entries.len() < MAX_HISTORY_ENTRIES {
+            self.entries.push(entry);
+        } else {
+            self.entries[(self.count % MAX_HISTORY_ENTRIES as u64) as usize] = entry;
+        }
+
+        self.count += 1;
+    }
+}
+
+/// Executed upgrade recorded in UpgradeHistory.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UpgradeRecord {
+    pub slot: u64,
+    // Buffer the program was upgraded from
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub buffer: Pubkey,
+    // SHA-256 of the deployed program data
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    // Allowed executor that submitted the upgrade, None if UpgradeAdmin has no executors allowlist
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::option_display"))]
+    pub executor: Option<Pubkey>,
+    // Account that received the buffer lamports
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub spill: Pubkey,
+}
+
+/// Deployment log of one contract managed by UpgradeAdmin. Keeps the last MAX_UPGRADE_RECORDS records in a ring buffer.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UpgradeHistory {
+    // UpgradeAdmin account the history belongs to
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    // Upgraded contract
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub program: Pubkey,
+    // Number of records ever made, record `i` is stored at index `i % MAX_UPGRADE_RECORDS`
+    pub count: u64,
+    pub records: Vec<UpgradeRecord>,
+    pub is_initialized: bool,
+}
+
+impl UpgradeHistory {
+    /// Records `record` overwriting the oldest one when the buffer is full.
+    pub fn push(&mut self, record: UpgradeRecord) {
+        if self.records.len() < MAX_UPGRADE_RECORDS {
+            self.records.push(record);
+        } else {
+            self.records[(self.count % MAX_UPGRADE_RECORDS as u64) as usize] = record;
+        }
+
+        self.count += 1;
+    }
+}
+
+/// Keys that can never be installed into UpgradeAdmin again.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RevokedKeys {
+    // UpgradeAdmin account the list belongs to
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    pub keys: Vec<AdminKey>,
+    pub is_initialized: bool,
+}
+
+/// Operation hashes approved by VerifyBatch and waiting to be consumed by Upgrade instructions.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BatchApproval {
+    // UpgradeAdmin whose signers approved the batch
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    // Hash of the upgrade authority that approved the batch, only UpgradeAdmin with the same authority can consume it
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub authority_hash: [u8; 32],
+    // Approved operation hashes that are not consumed yet
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_hex"))]
+    pub operations: Vec<[u8; 32]>,
+    // Unix timestamp after which approvals are no longer valid
+    pub expires_at: i64,
+    pub is_initialized: bool,
+}
+
+/// Upgrade approved by ProposeUpgrade and waiting to be performed by ExecuteUpgrade.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PendingUpgrade {
+    // UpgradeAdmin that approved the upgrade
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    // Upgraded contract: UpgradeAdmin primary contract or one of its managed contracts
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub program: Pubkey,
+    // Buffer with the new program data
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub buffer: Pubkey,
+    // SHA-256 of the buffer program data at proposal time
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    pub proposal_slot: u64,
+    // First slot ExecuteUpgrade is allowed at, None for no lower bound
+    pub not_before_slot: Option<u64>,
+    // Last slot ExecuteUpgrade is allowed at, None for no upper bound
+    pub not_after_slot: Option<u64>,
+    // keccak_hash(role_key.borsh_bytes) of approvers that approved the upgrade by ApproveUpgrade
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::seq_hex"))]
+    pub approvals: Vec<[u8; 32]>,
+    pub is_initialized: bool,
+}
+
+/// Known good program version registered by RegisterSnapshot that Rollback can restore.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RollbackSnapshot {
+    // UpgradeAdmin that registered the snapshot
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    // Contract the snapshot restores
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub program: Pubkey,
+    // Buffer with the known good program data, its authority is UpgradeAdmin
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub buffer: Pubkey,
+    // SHA-256 of the buffer program data at registration time
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    pub registered_slot: u64,
+    pub is_initialized: bool,
+}
+
+/// Progress of the buffer written by WriteBufferChunk with UpgradeAdmin as buffer authority.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BufferStaging {
+    // UpgradeAdmin that is the buffer authority
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    // Contract the buffer is prepared for
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub program: Pubkey,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub buffer: Pubkey,
+    // Solana signer committed by signers that writes and resets the chunks
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub writer: Pubkey,
+    // Length of the program data committed by signers
+    pub data_len: u64,
+    // Length of the program data written so far, chunks are written sequentially
+    pub written: u64,
+    // SHA-256 of the program data written so far
+    pub hasher: IncrementalSha256,
+    // SHA-256 of the program data committed by signers
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::hex"))]
+    pub code_hash: [u8; 32],
+    // Whole program data is written and matches `code_hash`
+    pub finalized: bool,
+    pub is_initialized: bool,
+}
+
+/// Short-lived key delegated by UpgradeAdmin signers to sign a limited set of operations.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SessionKey {
+    // UpgradeAdmin account that delegated the key
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialization::display"))]
+    pub admin: Pubkey,
+    pub key: AdminKey,
+    // Bitmask of SESSION_PERMISSION_* operations the key can sign
+    pub permissions: u8,
+    // Last slot when the key can be used
+    pub expiry_slot: u64,
+    pub is_initialized: bool,
+}
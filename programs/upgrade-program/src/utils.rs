@@ -0,0 +1,212 @@
+//! Account creation and checks shared by the programs of the workspace. Every program reports failures with its own
+//! error codes by implementing CommonError for its error type.
+
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program::{invoke, invoke_signed},
+    program_error::ProgramError, pubkey::Pubkey, system_instruction, sysvar::{self, clock::Clock, rent::Rent, Sysvar},
+};
+use crate::ecdsa::{is_valid_secp256k1_key, mark_ecdsa_signers, mark_secp256k1_precompile_signers, verify_threshold, EcdsaSignature};
+use crate::error::UpgradeError;
+use crate::state::{AdminKey, MAX_SIGNERS};
+
+/// Errors returned by the shared checks.
+pub trait CommonError: Into<ProgramError> {
+    /// Account to create is already allocated or assigned
+    fn already_in_use() -> Self;
+    /// Account passed in place of a sysvar has a different key
+    fn invalid_sysvar_account() -> Self;
+    /// Account passed in place of the system program has a different key
+    fn invalid_system_program() -> Self;
+    /// Signed operation has expired
+    fn signature_expired() -> Self;
+    /// Nonce in instruction args differs from the stored one
+    fn nonce_mismatch() -> Self;
+    /// The same signer signed the operation twice
+    fn duplicate_signature() -> Self;
+    /// Signers did not reach the threshold
+    fn not_enough_signatures() -> Self;
+    /// Signature is malformed or made by a key that is not a signer
+    fn invalid_signature() -> Self;
+    /// Signer keys or threshold are out of bounds, malformed or duplicated
+    fn invalid_signer_set() -> Self;
+    /// Counter overflows its type
+    fn math_overflow() -> Self;
+    /// Account modified by the instruction is passed read-only
+    fn account_not_writable() -> Self;
+}
+
+/// Errors of the programs that move SPL tokens.
+pub trait TokenError: CommonError {
+    /// Account passed in place of the token program has a different key
+    fn invalid_token_program() -> Self;
+}
+
+/// Creates account at PDA `seeds` owned by `owner`, also if someone sent lamports to the address in advance.
+#[allow(clippy::too_many_arguments)]
+pub fn create_pda_account<'a, E: CommonError>(
+    account_info: &AccountInfo<'a>,
+    fee_payer_info: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+    size: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let lamports = rent.minimum_balance(size);
+    if account_info.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(fee_payer_info.key, account_info.key, lamports, size as u64, owner),
+            &[
+                fee_payer_info.clone(),
+                account_info.clone(),
+                system_program.clone(),
+            ],
+            &[seeds],
+        );
+    }
+
+    // Only lamports could have been sent in advance, allocated or assigned account is not taken over
+    if !solana_program::system_program::check_id(account_info.owner) || !account_info.data_is_empty() {
+        msg!("Pre-funded account {} is not an empty system account", account_info.key);
+        return Err(E::already_in_use().into());
+    }
+
+    let required_lamports = lamports.saturating_sub(account_info.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(fee_payer_info.key, account_info.key, required_lamports),
+            &[
+                fee_payer_info.clone(),
+                account_info.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
+    invoke_signed(
+        &system_instruction::allocate(account_info.key, size as u64),
+        &[account_info.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+
+    invoke_signed(
+        &system_instruction::assign(account_info.key, owner),
+        &[account_info.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+/// Rejects an account passed in place of the `expected` sysvar.
+pub fn verify_sysvar<E: CommonError>(account_info: &AccountInfo, expected: &Pubkey) -> ProgramResult {
+    if account_info.key != expected {
+        msg!("Expected sysvar {}, got {}", expected, account_info.key);
+        return Err(E::invalid_sysvar_account().into());
+    }
+
+    Ok(())
+}
+
+/// Rejects an account passed in place of the system program.
+pub fn verify_system_program<E: CommonError>(account_info: &AccountInfo) -> ProgramResult {
+    if !solana_program::system_program::check_id(account_info.key) {
+        return Err(E::invalid_system_program().into());
+    }
+
+    Ok(())
+}
+
+/// Rejects signatures that expired at `expires_at` unix timestamp.
+pub fn verify_not_expired<E: CommonError>(expires_at: i64) -> ProgramResult {
+    let now = Clock::get()?.unix_timestamp;
+    if now > expires_at {
+        msg!("Signatures expired at {}, current timestamp {}", expires_at, now);
+        return Err(E::signature_expired().into());
+    }
+
+    Ok(())
+}
+
+/// Verifies that signatures were made for the stored nonce. Both values are logged, so the caller can see
+/// that the nonce was changed by another operation instead of getting a signature error.
+pub fn verify_nonce<E: CommonError>(expected: impl Into<u128>, stored: impl Into<u128>) -> ProgramResult {
+    let (expected, stored) = (expected.into(), stored.into());
+    if expected != stored {
+        msg!("Nonce mismatch: signed for {}, stored {}", expected, stored);
+        return Err(E::nonce_mismatch().into());
+    }
+
+    Ok(())
+}
+
+/// Verifies that `hash` is signed by `threshold` of `public_keys` using signatures from instruction args
+/// and from the instructions sysvar if it is provided in `accounts`.
+pub fn verify_signatures<E: CommonError>(
+    hash: &[u8],
+    signatures: &[EcdsaSignature],
+    accounts: &[AccountInfo],
+    public_keys: &[AdminKey],
+    threshold: u8,
+) -> ProgramResult {
+    let mut signed = vec![false; public_keys.len()];
+    mark_ecdsa_signers(hash, signatures, public_keys, &mut signed).map_err(signature_error::<E>)?;
+
+    if let Some(instructions_info) = accounts.iter().find(|info| sysvar::instructions::check_id(info.key)) {
+        mark_secp256k1_precompile_signers(instructions_info, hash, public_keys, &mut signed).map_err(signature_error::<E>)?;
+    }
+
+    verify_threshold(&signed, &[], threshold).map_err(signature_error::<E>)
+}
+
+/// Reports signature errors of `ecdsa`, that uses UpgradeError codes, with the codes of `E`.
+fn signature_error<E: CommonError>(error: ProgramError) -> ProgramError {
+    match error {
+        ProgramError::Custom(code) if code == UpgradeError::DuplicateSignature as u32 => E::duplicate_signature().into(),
+        ProgramError::Custom(code) if code == UpgradeError::NotEnoughSignatures as u32 => E::not_enough_signatures().into(),
+        ProgramError::Custom(_) => E::invalid_signature().into(),
+        error => error,
+    }
+}
+
+/// Advances `nonce` and returns its previous value, overflow is reported instead of wrapping.
+pub fn next_nonce<E: CommonError>(nonce: &mut u64) -> Result<u64, ProgramError> {
+    let current = *nonce;
+    *nonce = current.checked_add(1).ok_or_else(|| E::math_overflow().into())?;
+    Ok(current)
+}
+
+/// Accepts 1 to MAX_SIGNERS distinct secp256k1 keys with threshold reachable by them.
+pub fn validate_signer_set<E: CommonError>(public_keys: &[AdminKey], threshold: u8) -> ProgramResult {
+    if public_keys.is_empty() || public_keys.len() > MAX_SIGNERS {
+        return Err(E::invalid_signer_set().into());
+    }
+
+    if threshold == 0 || threshold as usize > public_keys.len() {
+        return Err(E::invalid_signer_set().into());
+    }
+
+    for (i, key) in public_keys.iter().enumerate() {
+        if !is_valid_secp256k1_key(key) || public_keys[..i].contains(key) {
+            return Err(E::invalid_signer_set().into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects a read-only account that the instruction modifies.
+pub fn verify_writable<E: CommonError>(account_info: &AccountInfo) -> ProgramResult {
+    if !account_info.is_writable {
+        return Err(E::account_not_writable().into());
+    }
+
+    Ok(())
+}
+
+/// Rejects an account passed in place of the SPL token program.
+pub fn verify_token_program<E: TokenError>(account_info: &AccountInfo) -> ProgramResult {
+    if account_info.key != &spl_token::id() {
+        return Err(E::invalid_token_program().into());
+    }
+
+    Ok(())
+}
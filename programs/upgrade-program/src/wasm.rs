@@ -0,0 +1,255 @@
+//! Bindings of the `wasm` feature for browser clients: admin address derivation, UpgradeAdmin decoding,
+//! hashes to sign and instruction builders. Addresses are passed as base58 strings, keys and hashes as hex,
+//! signatures as concatenated 65 byte `r || s || v` chunks as produced by Ethereum wallets.
+//! Instructions and decoded accounts are returned as JSON with base58 addresses and hex bytes.
+
+use core::str::FromStr;
+use serde::Serialize;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_program::secp256k1_recover::SECP256K1_PUBLIC_KEY_LENGTH;
+use wasm_bindgen::prelude::*;
+use crate::ecdsa::EcdsaSignature;
+use crate::hashing::{self, HashAlgorithm, HashingMode, NonceWidth};
+use crate::instructions;
+use crate::pda;
+use crate::state::{AdminKey, Curve, UpgradeAdmin, VersionedUpgradeAdmin, ETH_ADDRESS_LENGTH, SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH};
+
+/// Length of signature chunk: 64 byte signature and recovery id byte
+const SIGNATURE_CHUNK_LENGTH: usize = 65;
+
+#[derive(Serialize)]
+struct AccountMetaJson {
+    #[serde(serialize_with = "crate::serialization::display")]
+    pubkey: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Serialize)]
+struct InstructionJson {
+    #[serde(serialize_with = "crate::serialization::display")]
+    program_id: Pubkey,
+    accounts: Vec<AccountMetaJson>,
+    #[serde(serialize_with = "crate::serialization::hex")]
+    data: Vec<u8>,
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, JsError> {
+    serde_json::to_string(value).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn instruction_json(instruction: Instruction) -> Result<String, JsError> {
+    to_json(&InstructionJson {
+        program_id: instruction.program_id,
+        accounts: instruction.accounts.into_iter()
+            .map(|meta| AccountMetaJson { pubkey: meta.pubkey, is_signer: meta.is_signer, is_writable: meta.is_writable })
+            .collect(),
+        data: instruction.data,
+    })
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, JsError> {
+    Pubkey::from_str(value).map_err(|_| JsError::new(&format!("invalid address {}", value)))
+}
+
+fn parse_hash(value: &str) -> Result<[u8; 32], JsError> {
+    hex::decode(value.trim_start_matches("0x")).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| JsError::new(&format!("invalid 32 byte hash {}", value)))
+}
+
+/// Hex of 64 byte public key, 33 byte compressed public key or 20 byte Ethereum address.
+fn parse_admin_key(value: &str) -> Result<AdminKey, JsError> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).map_err(|e| JsError::new(&e.to_string()))?;
+    match bytes.len() {
+        SECP256K1_PUBLIC_KEY_LENGTH => Ok(AdminKey::PublicKey(bytes.try_into().unwrap())),
+        SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH => Ok(AdminKey::CompressedPublicKey(bytes.try_into().unwrap())),
+        ETH_ADDRESS_LENGTH => Ok(AdminKey::EthAddress(bytes.try_into().unwrap())),
+        len => Err(JsError::new(&format!("invalid key length {}", len))),
+    }
+}
+
+fn parse_admin_keys(values: &[String]) -> Result<Vec<AdminKey>, JsError> {
+    values.iter().map(|value| parse_admin_key(value)).collect()
+}
+
+fn parse_signatures(signatures: &[u8]) -> Result<Vec<EcdsaSignature>, JsError> {
+    if !signatures.len().is_multiple_of(SIGNATURE_CHUNK_LENGTH) {
+        return Err(JsError::new("signatures should be concatenated 65 byte chunks"));
+    }
+
+    Ok(signatures.chunks(SIGNATURE_CHUNK_LENGTH)
+        .map(|chunk| EcdsaSignature {
+            signature: chunk[..64].try_into().unwrap(),
+            recovery_id: chunk[64] as u64,
+            personal_sign: false,
+        })
+        .collect())
+}
+
+fn decode_admin(data: &[u8]) -> Result<UpgradeAdmin, JsError> {
+    match VersionedUpgradeAdmin::unpack(data).map_err(|e| JsError::new(&e.to_string()))? {
+        VersionedUpgradeAdmin::V2(upgrade_admin) if upgrade_admin.is_initialized => Ok(upgrade_admin),
+        VersionedUpgradeAdmin::V2(_) => Err(JsError::new("upgrade admin is not initialized")),
+        VersionedUpgradeAdmin::V1(_) => Err(JsError::new("upgrade admin has V1 layout and should be migrated")),
+    }
+}
+
+/// Address of UpgradeAdmin of `contract`.
+#[wasm_bindgen(js_name = adminAddress)]
+pub fn admin_address(program_id: &str, contract: &str) -> Result<String, JsError> {
+    Ok(pda::find_admin_address(&parse_pubkey(program_id)?, &parse_pubkey(contract)?).0.to_string())
+}
+
+/// JSON of UpgradeAdmin account data of any supported layout.
+#[wasm_bindgen(js_name = decodeUpgradeAdmin)]
+pub fn decode_upgrade_admin(data: &[u8]) -> Result<String, JsError> {
+    to_json(&VersionedUpgradeAdmin::unpack(data).map_err(|e| JsError::new(&e.to_string()))?)
+}
+
+/// Hash to sign for Upgrade of `contract` by UpgradeAdmin with account data `admin_data`.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = upgradeHash)]
+pub fn upgrade_hash(program_id: &str, admin_data: &[u8], contract: &str, buffer: &str, code_hash: &str, spill: &str, expires_at: i64) -> Result<Vec<u8>, JsError> {
+    Ok(hashing::upgrade_hash(
+        &parse_pubkey(program_id)?,
+        &decode_admin(admin_data)?,
+        &parse_pubkey(contract)?,
+        &parse_pubkey(buffer)?,
+        &parse_hash(code_hash)?,
+        &parse_pubkey(spill)?,
+        expires_at,
+    ).to_bytes().to_vec())
+}
+
+/// Hash to sign for ChangePublicKey of UpgradeAdmin with account data `admin_data`.
+#[wasm_bindgen(js_name = changePublicKeyHash)]
+pub fn change_public_key_hash(program_id: &str, admin_data: &[u8], new_public_keys: Vec<String>, new_threshold: u8, expires_at: i64) -> Result<Vec<u8>, JsError> {
+    Ok(hashing::change_public_key_hash(
+        &parse_pubkey(program_id)?,
+        &decode_admin(admin_data)?,
+        &parse_admin_keys(&new_public_keys)?,
+        &[],
+        new_threshold,
+        None,
+        &None,
+        expires_at,
+    ).to_bytes().to_vec())
+}
+
+/// Hash to sign for ChangeAuthority of `contract` by UpgradeAdmin with account data `admin_data`.
+#[wasm_bindgen(js_name = changeAuthorityHash)]
+pub fn change_authority_hash(program_id: &str, admin_data: &[u8], contract: &str, new_authority: &str, expires_at: i64) -> Result<Vec<u8>, JsError> {
+    Ok(hashing::change_authority_hash(
+        &parse_pubkey(program_id)?,
+        &decode_admin(admin_data)?,
+        &parse_pubkey(contract)?,
+        &parse_pubkey(new_authority)?,
+        expires_at,
+    ).to_bytes().to_vec())
+}
+
+/// Hash to sign for CloseAdmin of UpgradeAdmin with account data `admin_data`.
+#[wasm_bindgen(js_name = closeAdminHash)]
+pub fn close_admin_hash(program_id: &str, admin_data: &[u8], destination: &str, expires_at: i64) -> Result<Vec<u8>, JsError> {
+    Ok(hashing::close_admin_hash(
+        &parse_pubkey(program_id)?,
+        &decode_admin(admin_data)?,
+        &parse_pubkey(destination)?,
+        expires_at,
+    ).to_bytes().to_vec())
+}
+
+/// InitializeAdmin of `contract` with legacy secp256k1 Keccak signing, u64 nonces and no optional policies.
+#[wasm_bindgen(js_name = initializeAdminInstruction)]
+pub fn initialize_admin_instruction(program_id: &str, contract: &str, fee_payer: &str, public_keys: Vec<String>, threshold: u8) -> Result<String, JsError> {
+    instruction_json(instructions::initialize_admin(
+        parse_pubkey(program_id)?,
+        parse_pubkey(contract)?,
+        parse_pubkey(fee_payer)?,
+        parse_admin_keys(&public_keys)?,
+        vec![],
+        threshold,
+        HashingMode::Legacy,
+        [0; 32],
+        None,
+        None,
+        0,
+        Curve::Secp256k1,
+        HashAlgorithm::Keccak,
+        None,
+        0,
+        vec![],
+        None,
+        vec![],
+        0,
+        0,
+        0,
+        NonceWidth::U64,
+    ))
+}
+
+/// Upgrade of `contract` from `buffer` with signatures made for upgrade nonce `nonce`.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = upgradeInstruction)]
+pub fn upgrade_instruction(program_id: &str, contract: &str, buffer: &str, spill: &str, nonce: u64, code_hash: &str, signatures: &[u8], expires_at: i64) -> Result<String, JsError> {
+    instruction_json(instructions::upgrade(
+        parse_pubkey(program_id)?,
+        parse_pubkey(contract)?,
+        parse_pubkey(buffer)?,
+        parse_pubkey(spill)?,
+        nonce.into(),
+        parse_hash(code_hash)?,
+        parse_signatures(signatures)?,
+        vec![],
+        expires_at,
+    ))
+}
+
+/// ChangePublicKey of UpgradeAdmin of `contract` with signatures made for key nonce `nonce`.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = changePublicKeyInstruction)]
+pub fn change_public_key_instruction(program_id: &str, contract: &str, new_public_keys: Vec<String>, new_threshold: u8, nonce: u64, signatures: &[u8], expires_at: i64) -> Result<String, JsError> {
+    instruction_json(instructions::change_public_key(
+        parse_pubkey(program_id)?,
+        parse_pubkey(contract)?,
+        parse_admin_keys(&new_public_keys)?,
+        vec![],
+        new_threshold,
+        None,
+        None,
+        nonce.into(),
+        parse_signatures(signatures)?,
+        vec![],
+        expires_at,
+    ))
+}
+
+/// ChangeAuthority of `contract` with signatures made for authority nonce `nonce`.
+#[wasm_bindgen(js_name = changeAuthorityInstruction)]
+pub fn change_authority_instruction(program_id: &str, contract: &str, new_authority: &str, nonce: u64, signatures: &[u8], expires_at: i64) -> Result<String, JsError> {
+    instruction_json(instructions::change_authority(
+        parse_pubkey(program_id)?,
+        parse_pubkey(contract)?,
+        parse_pubkey(new_authority)?,
+        nonce.into(),
+        parse_signatures(signatures)?,
+        vec![],
+        expires_at,
+    ))
+}
+
+/// CloseAdmin of UpgradeAdmin of `contract` with signatures made for authority nonce `nonce`.
+#[wasm_bindgen(js_name = closeAdminInstruction)]
+pub fn close_admin_instruction(program_id: &str, contract: &str, destination: &str, nonce: u64, signatures: &[u8], expires_at: i64) -> Result<String, JsError> {
+    instruction_json(instructions::close_admin(
+        parse_pubkey(program_id)?,
+        parse_pubkey(contract)?,
+        parse_pubkey(destination)?,
+        nonce.into(),
+        parse_signatures(signatures)?,
+        vec![],
+        expires_at,
+    ))
+}
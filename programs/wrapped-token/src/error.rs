@@ -0,0 +1,150 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, msg, program_error::{PrintProgramError, ProgramError}};
+use thiserror::Error;
+use upgrade::utils::{CommonError, TokenError};
+
+/// Errors that may be returned by the wrapped token program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum WrappedTokenError {
+    /// 0 The account cannot be initialized because it is already being used.
+    #[error("Already in use")]
+    AlreadyInUse,
+    /// 1 The account hasn't been initialized
+    #[error("Not initialized")]
+    NotInitialized,
+    /// 2 Account address does not match the seeds
+    #[error("Wrong seeds")]
+    WrongSeeds,
+    /// 3 Factory or WrappedAsset account is owned by another program
+    #[error("Account owner mismatch")]
+    AccountOwnerMismatch,
+    /// 4 Signer set is empty, too large, contains unsupported keys or has wrong threshold
+    #[error("Invalid signer set")]
+    InvalidSignerSet,
+    /// 5 Signature is malformed or does not recover to a signer of the factory
+    #[error("Invalid signature")]
+    InvalidSignature,
+    /// 6 Several signatures recovered to the same signer
+    #[error("Duplicate signature")]
+    DuplicateSignature,
+    /// 7 Number of valid signatures is less than threshold
+    #[error("Not enough signatures")]
+    NotEnoughSignatures,
+    /// 8 Signed operation has expired
+    #[error("Signature expired")]
+    SignatureExpired,
+    /// 9 Operation is signed for another nonce
+    #[error("Nonce mismatch")]
+    NonceMismatch,
+    /// 10 Amount is zero
+    #[error("Invalid amount")]
+    InvalidAmount,
+    /// 11 Origin token address is empty or too long
+    #[error("Invalid origin token")]
+    InvalidOriginToken,
+    /// 12 Name, symbol or URI exceeds the Metaplex limits
+    #[error("Invalid metadata")]
+    InvalidMetadata,
+    /// 13 Destination address is empty or too long
+    #[error("Invalid destination")]
+    InvalidDestination,
+    /// 14 Token account or program is not of SPL Token
+    #[error("Invalid token program")]
+    InvalidTokenProgram,
+    /// 15 Account passed in place of Metaplex Token Metadata program is not that program
+    #[error("Invalid metadata program")]
+    InvalidMetadataProgram,
+    /// 16 WrappedAsset account belongs to another mint or factory
+    #[error("Wrong mint")]
+    WrongMint,
+    /// 17 Account that is modified by the instruction is passed as readonly
+    #[error("Account not writable")]
+    AccountNotWritable,
+    /// 18 Account that should sign the transaction did not sign it
+    #[error("Missing signer")]
+    MissingSigner,
+    /// 19 Arithmetic overflow
+    #[error("Math overflow")]
+    MathOverflow,
+    /// 20 Instruction data is encoded with unsupported format version
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    /// 21 Account passed in place of a sysvar is not that sysvar
+    #[error("Invalid sysvar account")]
+    InvalidSysvarAccount,
+    /// 22 Account passed in place of the system program is not the system program
+    #[error("Invalid system program")]
+    InvalidSystemProgram,
+}
+
+impl From<WrappedTokenError> for ProgramError {
+    fn from(e: WrappedTokenError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl CommonError for WrappedTokenError {
+    fn already_in_use() -> Self {
+        WrappedTokenError::AlreadyInUse
+    }
+
+    fn invalid_sysvar_account() -> Self {
+        WrappedTokenError::InvalidSysvarAccount
+    }
+
+    fn invalid_system_program() -> Self {
+        WrappedTokenError::InvalidSystemProgram
+    }
+
+    fn signature_expired() -> Self {
+        WrappedTokenError::SignatureExpired
+    }
+
+    fn nonce_mismatch() -> Self {
+        WrappedTokenError::NonceMismatch
+    }
+
+    fn duplicate_signature() -> Self {
+        WrappedTokenError::DuplicateSignature
+    }
+
+    fn not_enough_signatures() -> Self {
+        WrappedTokenError::NotEnoughSignatures
+    }
+
+    fn invalid_signature() -> Self {
+        WrappedTokenError::InvalidSignature
+    }
+
+    fn invalid_signer_set() -> Self {
+        WrappedTokenError::InvalidSignerSet
+    }
+
+    fn math_overflow() -> Self {
+        WrappedTokenError::MathOverflow
+    }
+
+    fn account_not_writable() -> Self {
+        WrappedTokenError::AccountNotWritable
+    }
+}
+
+impl TokenError for WrappedTokenError {
+    fn invalid_token_program() -> Self {
+        WrappedTokenError::InvalidTokenProgram
+    }
+}
+
+impl PrintProgramError for WrappedTokenError {
+    fn print<E>(&self) {
+        msg!(&self.to_string());
+    }
+}
+
+impl<T> DecodeError<T> for WrappedTokenError {
+    fn type_of() -> &'static str {
+        "WrappedTokenError"
+    }
+}
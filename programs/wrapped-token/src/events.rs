@@ -0,0 +1,63 @@
+//! Borsh-encoded events emitted through `sol_log_data`, the oracle decodes burns from transaction logs
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+
+/// Wrapped mint of an origin asset was created by CreateWrappedMint.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct WrappedMintCreated {
+    pub factory: Pubkey,
+    pub mint: Pubkey,
+    pub origin_chain: u64,
+    pub origin_token: Vec<u8>,
+    pub decimals: u8,
+}
+
+/// Wrapped tokens were minted by Mint.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Minted {
+    pub factory: Pubkey,
+    pub mint: Pubkey,
+    // Recipient token account
+    pub recipient: Pubkey,
+    pub amount: u64,
+    // Id of the transaction on the origin chain that locked the asset
+    pub tx_id: [u8; 32],
+    // Nonce the signatures were made for
+    pub nonce: u64,
+}
+
+/// Wrapped tokens were burned by Burn to be released on the origin chain.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Burned {
+    pub factory: Pubkey,
+    pub mint: Pubkey,
+    // Owner of the burned token account
+    pub sender: Pubkey,
+    pub amount: u64,
+    pub origin_chain: u64,
+    pub origin_token: Vec<u8>,
+    // Recipient address on the origin chain
+    pub destination_address: Vec<u8>,
+    // Burn nonce, unique within the factory
+    pub nonce: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub enum WrappedTokenEvent {
+    WrappedMintCreated(WrappedMintCreated),
+    Minted(Minted),
+    Burned(Burned),
+}
+
+impl WrappedTokenEvent {
+    /// Writes the event to the transaction log.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+}
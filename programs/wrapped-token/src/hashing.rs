@@ -0,0 +1,80 @@
+//! Hashes of the messages that should be signed by the oracle signers
+
+use borsh::BorshSerialize;
+use solana_program::keccak::{hashv, Hash};
+use solana_program::pubkey::Pubkey;
+use crate::instructions::WrappedMetadata;
+use crate::state::Factory;
+use crate::HASH_CONSTANT;
+
+/// Payload prefix for CreateWrappedMint.
+pub const CREATE_WRAPPED_MINT_TAG: &str = "create-wrapped-mint";
+
+/// Payload prefix for Mint.
+pub const MINT_TAG: &str = "mint";
+
+/// Identifier of the origin asset, seeds the address of its wrapped mint:
+/// `keccak_hash([origin_chain, origin_token])` with the chain in big-endian.
+pub fn origin_id(origin_chain: u64, origin_token: &[u8]) -> Hash {
+    hashv(&[origin_chain.to_be_bytes().as_ref(), origin_token])
+}
+
+/// Hash to sign for CreateWrappedMint instruction:
+/// `keccak_hash(["solana-wrapped-token-program".bytes, program_id, factory, domain, "create-wrapped-mint".bytes, origin_chain, origin_token.borsh_bytes, decimals, metadata.borsh_bytes, nonce, expires_at])`
+/// with integers in big-endian.
+#[allow(clippy::too_many_arguments)]
+pub fn create_wrapped_mint_hash(
+    program_id: &Pubkey,
+    factory_address: &Pubkey,
+    factory: &Factory,
+    origin_chain: u64,
+    origin_token: &[u8],
+    decimals: u8,
+    metadata: &WrappedMetadata,
+    nonce: u64,
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        factory_address.as_ref(),
+        factory.domain.as_ref(),
+        CREATE_WRAPPED_MINT_TAG.as_bytes(),
+        origin_chain.to_be_bytes().as_ref(),
+        origin_token.to_vec().try_to_vec().unwrap().as_ref(),
+        &[decimals],
+        metadata.try_to_vec().unwrap().as_ref(),
+        nonce.to_be_bytes().as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
+
+/// Hash to sign for Mint instruction:
+/// `keccak_hash(["solana-wrapped-token-program".bytes, program_id, factory, domain, "mint".bytes, mint, recipient, amount, tx_id, nonce, expires_at])`
+/// with integers in big-endian.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_hash(
+    program_id: &Pubkey,
+    factory_address: &Pubkey,
+    factory: &Factory,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    tx_id: &[u8; 32],
+    nonce: u64,
+    expires_at: i64,
+) -> Hash {
+    hashv(&[
+        HASH_CONSTANT.as_bytes(),
+        program_id.as_ref(),
+        factory_address.as_ref(),
+        factory.domain.as_ref(),
+        MINT_TAG.as_bytes(),
+        mint.as_ref(),
+        recipient.as_ref(),
+        amount.to_be_bytes().as_ref(),
+        tx_id.as_ref(),
+        nonce.to_be_bytes().as_ref(),
+        expires_at.to_be_bytes().as_ref(),
+    ])
+}
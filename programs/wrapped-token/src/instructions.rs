@@ -0,0 +1,332 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use solana_program::instruction::{Instruction, AccountMeta};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use shank::ShankInstruction;
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::state::AdminKey;
+use crate::pda;
+use crate::error::WrappedTokenError;
+
+/// Version of the instruction data format, stored in the first byte of every instruction.
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Instruction data can not be larger than the transaction packet.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 1232;
+
+/// Metaplex token metadata of a wrapped mint, as it is on the origin chain.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct WrappedMetadata {
+    // At most MAX_NAME_LENGTH bytes
+    pub name: String,
+    // At most MAX_SYMBOL_LENGTH bytes
+    pub symbol: String,
+    // At most MAX_URI_LENGTH bytes
+    pub uri: String,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct InitializeFactoryArgs {
+    // Seed of the factory address
+    pub seed: [u8; 32],
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the oracle signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize operation
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct CreateWrappedMintArgs {
+    // Identifier of the origin chain, interpreted by the oracle
+    pub origin_chain: u64,
+    // Token address on the origin chain, at most MAX_ORIGIN_TOKEN_LENGTH bytes
+    pub origin_token: Vec<u8>,
+    // Decimals of the wrapped mint
+    pub decimals: u8,
+    pub metadata: WrappedMetadata,
+    // Nonce of the factory the signatures were made for
+    pub nonce: u64,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the oracle signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct MintArgs {
+    // Amount of tokens to mint, in base units of the mint
+    pub amount: u64,
+    // Id of the transaction on the origin chain that locked the asset
+    pub tx_id: [u8; 32],
+    // Nonce of the factory the signatures were made for
+    pub nonce: u64,
+    // Unix timestamp after which the signatures are rejected
+    pub expires_at: i64,
+    // Signatures of the oracle signers
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct BurnArgs {
+    // Amount of tokens to burn, in base units of the mint
+    pub amount: u64,
+    // Recipient address on the origin chain, at most MAX_DESTINATION_ADDRESS_LENGTH bytes
+    pub destination_address: Vec<u8>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankInstruction)]
+pub enum WrappedTokenInstruction {
+    /// Initialize new Factory controlled by the oracle signer set with threshold.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Factory account to initialize
+    ///   1. `[writable,signer]` The fee payer
+    ///   2. `[]` System program
+    ///   3. `[]` Rent sysvar
+    #[account(0, writable, name="factory", desc="The Factory account to initialize")]
+    #[account(1, writable, signer, name="payer", desc="The fee payer")]
+    #[account(2, name="system_program", desc="System program")]
+    #[account(3, name="rent", desc="Rent sysvar")]
+    InitializeFactory(InitializeFactoryArgs),
+
+    /// Create the wrapped mint of `origin_token` on `origin_chain` with Metaplex token metadata. The mint address
+    /// is derived from the origin, its mint authority and metadata update authority is the factory, the mint has
+    /// no freeze authority. The Keccak Hash of
+    /// `["solana-wrapped-token-program".bytes, program_id, factory, domain, "create-wrapped-mint".bytes, origin_chain, origin_token.borsh_bytes, decimals, metadata.borsh_bytes, nonce, expires_at]`
+    /// should be signed by `threshold` of the oracle signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Factory account
+    ///   1. `[writable]` The wrapped mint to create
+    ///   2. `[writable]` The WrappedAsset account to initialize
+    ///   3. `[writable]` The Metaplex metadata account of the mint
+    ///   4. `[writable,signer]` The fee payer
+    ///   5. `[]` Token program
+    ///   6. `[]` Metaplex Token Metadata program
+    ///   7. `[]` System program
+    ///   8. `[]` Rent sysvar
+    ///   9. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, writable, name="factory", desc="The Factory account")]
+    #[account(1, writable, name="mint", desc="The wrapped mint to create")]
+    #[account(2, writable, name="wrapped_asset", desc="The WrappedAsset account to initialize")]
+    #[account(3, writable, name="metadata", desc="The Metaplex metadata account of the mint")]
+    #[account(4, writable, signer, name="payer", desc="The fee payer")]
+    #[account(5, name="token_program", desc="Token program")]
+    #[account(6, name="metadata_program", desc="Metaplex Token Metadata program")]
+    #[account(7, name="system_program", desc="System program")]
+    #[account(8, name="rent", desc="Rent sysvar")]
+    #[account(9, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    CreateWrappedMint(CreateWrappedMintArgs),
+
+    /// Mint `amount` of wrapped tokens to the recipient token account for the asset locked on the origin chain.
+    /// The Keccak Hash of
+    /// `["solana-wrapped-token-program".bytes, program_id, factory, domain, "mint".bytes, mint, recipient, amount, tx_id, nonce, expires_at]`
+    /// should be signed by `threshold` of the oracle signers, either in instruction args or by `secp256k1_program`
+    /// instruction preceding the current one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Factory account
+    ///   1. `[writable]` The wrapped mint
+    ///   2. `[writable]` The recipient token account
+    ///   3. `[]` Token program
+    ///   4. `[]` Instructions sysvar (optional, required for secp256k1_program signers)
+    #[account(0, writable, name="factory", desc="The Factory account")]
+    #[account(1, writable, name="mint", desc="The wrapped mint")]
+    #[account(2, writable, name="recipient", desc="The recipient token account")]
+    #[account(3, name="token_program", desc="Token program")]
+    #[account(4, optional, name="instructions_sysvar", desc="Instructions sysvar (optional, required for secp256k1_program signers)")]
+    Mint(MintArgs),
+
+    /// Burn `amount` of wrapped tokens of the owner and emit Burned event for the oracle to release the asset
+    /// to the destination address on the origin chain.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Factory account
+    ///   1. `[writable]` The wrapped mint
+    ///   2. `[]` The WrappedAsset account of the mint
+    ///   3. `[writable]` The source token account
+    ///   4. `[signer]` The owner of the source token account
+    ///   5. `[]` Token program
+    #[account(0, writable, name="factory", desc="The Factory account")]
+    #[account(1, writable, name="mint", desc="The wrapped mint")]
+    #[account(2, name="wrapped_asset", desc="The WrappedAsset account of the mint")]
+    #[account(3, writable, name="source", desc="The source token account")]
+    #[account(4, signer, name="owner", desc="The owner of the source token account")]
+    #[account(5, name="token_program", desc="Token program")]
+    Burn(BurnArgs),
+}
+
+impl WrappedTokenInstruction {
+    /// Encodes the instruction prefixed with INSTRUCTION_VERSION.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![INSTRUCTION_VERSION];
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Decodes instruction data produced by `pack`. Data of another format version is rejected instead of being
+    /// misparsed, and trailing bytes are not allowed.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < 2 || input.len() > MAX_INSTRUCTION_DATA_LEN {
+            msg!("Invalid instruction data length: {}", input.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if input[0] != INSTRUCTION_VERSION {
+            msg!("Instruction version {} is not supported, expected {}", input[0], INSTRUCTION_VERSION);
+            return Err(WrappedTokenError::UnsupportedInstructionVersion.into());
+        }
+
+        Ok(Self::try_from_slice(&input[1..])?)
+    }
+}
+
+pub fn initialize_factory(
+    program_id: Pubkey,
+    fee_payer: Pubkey,
+    seed: [u8; 32],
+    public_keys: Vec<AdminKey>,
+    threshold: u8,
+    domain: [u8; 32],
+) -> Instruction {
+    let (factory, _) = pda::find_factory_address(&program_id, &seed);
+    Instruction{
+        program_id,
+        data: WrappedTokenInstruction::InitializeFactory(
+            InitializeFactoryArgs {
+                seed,
+                public_keys,
+                threshold,
+                domain,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(factory, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// CreateWrappedMint with signatures in instruction args. Signatures verified by a preceding `secp256k1_program`
+/// instruction require the instructions sysvar appended to the accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn create_wrapped_mint(
+    program_id: Pubkey,
+    factory: Pubkey,
+    fee_payer: Pubkey,
+    origin_chain: u64,
+    origin_token: Vec<u8>,
+    decimals: u8,
+    metadata: WrappedMetadata,
+    nonce: u64,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    let (mint, _) = pda::find_mint_address(&program_id, &factory, origin_chain, &origin_token);
+    let (wrapped_asset, _) = pda::find_wrapped_asset_address(&program_id, &mint);
+    let (metadata_account, _) = pda::find_metadata_address(&mint);
+    Instruction{
+        program_id,
+        data: WrappedTokenInstruction::CreateWrappedMint(
+            CreateWrappedMintArgs {
+                origin_chain,
+                origin_token,
+                decimals,
+                metadata,
+                nonce,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(factory, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(wrapped_asset, false),
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new(fee_payer, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// Mint with signatures in instruction args. Signatures verified by a preceding `secp256k1_program`
+/// instruction require the instructions sysvar appended to the accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn mint(
+    program_id: Pubkey,
+    factory: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+    tx_id: [u8; 32],
+    nonce: u64,
+    expires_at: i64,
+    signatures: Vec<EcdsaSignature>,
+) -> Instruction {
+    Instruction{
+        program_id,
+        data: WrappedTokenInstruction::Mint(
+            MintArgs {
+                amount,
+                tx_id,
+                nonce,
+                expires_at,
+                signatures,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(factory, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(recipient, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+pub fn burn(
+    program_id: Pubkey,
+    factory: Pubkey,
+    mint: Pubkey,
+    source: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+    destination_address: Vec<u8>,
+) -> Instruction {
+    let (wrapped_asset, _) = pda::find_wrapped_asset_address(&program_id, &mint);
+    Instruction{
+        program_id,
+        data: WrappedTokenInstruction::Burn(
+            BurnArgs {
+                amount,
+                destination_address,
+            }
+        ).pack(),
+        accounts: vec![
+            AccountMeta::new(factory, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(wrapped_asset, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new_readonly(owner, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
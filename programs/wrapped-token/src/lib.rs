@@ -0,0 +1,18 @@
+//! Factory of wrapped SPL tokens of assets originating on other chains. Every wrapped mint is created with Metaplex
+//! token metadata from a payload signed by the oracle signer set, its mint authority is a PDA of the factory,
+//! so tokens are minted only by signed Mint instructions and leave Solana by Burn with an event for the oracle.
+//! Signatures are verified with `upgrade::ecdsa`, the scheme of the upgrade program.
+
+pub mod entrypoint;
+pub mod error;
+pub mod events;
+pub mod hashing;
+pub mod instructions;
+pub mod pda;
+pub mod processor;
+pub mod state;
+
+const HASH_CONSTANT: &str = "solana-wrapped-token-program";
+const PDA_FACTORY_SEED: &str = "wrapped-factory-account";
+const PDA_MINT_SEED: &str = "wrapped-mint-account";
+const PDA_WRAPPED_ASSET_SEED: &str = "wrapped-asset-account";
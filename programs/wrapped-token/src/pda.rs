@@ -0,0 +1,26 @@
+//! Addresses of the program derived accounts. Off-chain builders and the processor derive them here, so the seed
+//! strings and their ordering are defined once.
+
+use solana_program::pubkey::Pubkey;
+use crate::hashing::origin_id;
+use crate::{PDA_FACTORY_SEED, PDA_MINT_SEED, PDA_WRAPPED_ASSET_SEED};
+
+/// Address and bump of Factory created with `seed`.
+pub fn find_factory_address(program_id: &Pubkey, seed: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_FACTORY_SEED.as_bytes(), seed], program_id)
+}
+
+/// Address and bump of the wrapped mint of `origin_token` on `origin_chain` created by Factory at `factory`.
+pub fn find_mint_address(program_id: &Pubkey, factory: &Pubkey, origin_chain: u64, origin_token: &[u8]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_MINT_SEED.as_bytes(), factory.as_ref(), origin_id(origin_chain, origin_token).as_ref()], program_id)
+}
+
+/// Address and bump of WrappedAsset of the wrapped `mint`.
+pub fn find_wrapped_asset_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PDA_WRAPPED_ASSET_SEED.as_bytes(), mint.as_ref()], program_id)
+}
+
+/// Address of Metaplex token metadata of `mint`.
+pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    mpl_token_metadata::accounts::Metadata::find_pda(mint)
+}
@@ -0,0 +1,345 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult, msg,
+    program::{invoke, invoke_signed}, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    sysvar::{self, rent::Rent, Sysvar},
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use mpl_token_metadata::instructions::{CreateMetadataAccountV3, CreateMetadataAccountV3InstructionArgs};
+use mpl_token_metadata::types::DataV2;
+use mpl_token_metadata::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+use crate::state::{Factory, WrappedAsset, MAX_DESTINATION_ADDRESS_LENGTH, MAX_FACTORY_SIZE, MAX_ORIGIN_TOKEN_LENGTH, MAX_WRAPPED_ASSET_SIZE};
+use crate::instructions::{BurnArgs, CreateWrappedMintArgs, InitializeFactoryArgs, MintArgs, WrappedMetadata, WrappedTokenInstruction};
+use crate::hashing::{create_wrapped_mint_hash, mint_hash, origin_id};
+use crate::pda;
+use crate::{PDA_FACTORY_SEED, PDA_MINT_SEED, PDA_WRAPPED_ASSET_SEED};
+use crate::error::WrappedTokenError;
+use upgrade::utils::{create_pda_account, next_nonce, validate_signer_set, verify_nonce, verify_not_expired, verify_signatures, verify_system_program, verify_sysvar, verify_token_program};
+use crate::events::{Burned, Minted, WrappedMintCreated, WrappedTokenEvent};
+
+pub fn process_instruction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let instruction = WrappedTokenInstruction::unpack(input)?;
+    match instruction {
+        WrappedTokenInstruction::InitializeFactory(args) => {
+            msg!("Instruction: Create factory");
+            process_init_factory(program_id, accounts, args)
+        }
+        WrappedTokenInstruction::CreateWrappedMint(args) => {
+            msg!("Instruction: Create wrapped mint");
+            process_create_wrapped_mint(program_id, accounts, args)
+        }
+        WrappedTokenInstruction::Mint(args) => {
+            msg!("Instruction: Mint");
+            process_mint(program_id, accounts, args)
+        }
+        WrappedTokenInstruction::Burn(args) => {
+            msg!("Instruction: Burn");
+            process_burn(program_id, accounts, args)
+        }
+    }
+}
+
+pub fn process_init_factory<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: InitializeFactoryArgs,
+) -> ProgramResult {
+    validate_signer_set::<WrappedTokenError>(&args.public_keys, args.threshold)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let factory_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(WrappedTokenError::MissingSigner.into());
+    }
+
+    verify_system_program::<WrappedTokenError>(system_program)?;
+    verify_sysvar::<WrappedTokenError>(rent_info, &sysvar::rent::id())?;
+
+    let (factory_key, bump) = pda::find_factory_address(program_id, &args.seed);
+    if factory_key != *factory_info.key {
+        return Err(WrappedTokenError::WrongSeeds.into());
+    }
+
+    let rent = Rent::from_account_info(rent_info)?;
+
+    create_pda_account::<WrappedTokenError>(factory_info, fee_payer_info, system_program, &rent, MAX_FACTORY_SIZE, program_id, &[PDA_FACTORY_SEED.as_bytes(), args.seed.as_ref(), &[bump]])?;
+
+    let mut factory: Factory = BorshDeserialize::deserialize(&mut factory_info.data.borrow_mut().as_ref())?;
+    if factory.is_initialized {
+        return Err(WrappedTokenError::AlreadyInUse.into());
+    }
+
+    factory.seed = args.seed;
+    factory.bump = bump;
+    factory.public_keys = args.public_keys;
+    factory.threshold = args.threshold;
+    factory.domain = args.domain;
+    factory.nonce = 0;
+    factory.burn_nonce = 0;
+    factory.is_initialized = true;
+    factory.serialize(&mut *factory_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_create_wrapped_mint<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: CreateWrappedMintArgs,
+) -> ProgramResult {
+    if args.origin_token.is_empty() || args.origin_token.len() > MAX_ORIGIN_TOKEN_LENGTH {
+        return Err(WrappedTokenError::InvalidOriginToken.into());
+    }
+
+    validate_metadata(&args.metadata)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let factory_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let wrapped_asset_info = next_account_info(account_info_iter)?;
+    let metadata_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let metadata_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    if !fee_payer_info.is_signer {
+        return Err(WrappedTokenError::MissingSigner.into());
+    }
+
+    verify_token_program::<WrappedTokenError>(token_program)?;
+    verify_system_program::<WrappedTokenError>(system_program)?;
+    verify_sysvar::<WrappedTokenError>(rent_info, &sysvar::rent::id())?;
+
+    if metadata_program.key != &mpl_token_metadata::ID {
+        return Err(WrappedTokenError::InvalidMetadataProgram.into());
+    }
+
+    let mut factory = load_factory(program_id, factory_info)?;
+
+    let (mint_key, mint_bump) = pda::find_mint_address(program_id, factory_info.key, args.origin_chain, &args.origin_token);
+    if mint_key != *mint_info.key {
+        return Err(WrappedTokenError::WrongSeeds.into());
+    }
+
+    let (wrapped_asset_key, wrapped_asset_bump) = pda::find_wrapped_asset_address(program_id, mint_info.key);
+    if wrapped_asset_key != *wrapped_asset_info.key || pda::find_metadata_address(mint_info.key).0 != *metadata_info.key {
+        return Err(WrappedTokenError::WrongSeeds.into());
+    }
+
+    verify_not_expired::<WrappedTokenError>(args.expires_at)?;
+    verify_nonce::<WrappedTokenError>(args.nonce, factory.nonce)?;
+
+    let hash = create_wrapped_mint_hash(program_id, factory_info.key, &factory, args.origin_chain, &args.origin_token, args.decimals, &args.metadata, args.nonce, args.expires_at);
+    verify_signatures::<WrappedTokenError>(hash.as_ref(), &args.signatures, accounts, &factory.public_keys, factory.threshold)?;
+
+    next_nonce::<WrappedTokenError>(&mut factory.nonce)?;
+    factory.serialize(&mut *factory_info.data.borrow_mut())?;
+
+    let rent = Rent::from_account_info(rent_info)?;
+    let origin = origin_id(args.origin_chain, &args.origin_token);
+
+    create_pda_account::<WrappedTokenError>(mint_info, fee_payer_info, system_program, &rent, spl_token::state::Mint::LEN, &spl_token::id(), &[PDA_MINT_SEED.as_bytes(), factory_info.key.as_ref(), origin.as_ref(), &[mint_bump]])?;
+    invoke(
+        &spl_token::instruction::initialize_mint2(&spl_token::id(), mint_info.key, factory_info.key, None, args.decimals)?,
+        std::slice::from_ref(mint_info),
+    )?;
+
+    create_pda_account::<WrappedTokenError>(wrapped_asset_info, fee_payer_info, system_program, &rent, MAX_WRAPPED_ASSET_SIZE, program_id, &[PDA_WRAPPED_ASSET_SEED.as_bytes(), mint_info.key.as_ref(), &[wrapped_asset_bump]])?;
+    let wrapped_asset = WrappedAsset {
+        factory: *factory_info.key,
+        mint: *mint_info.key,
+        origin_chain: args.origin_chain,
+        origin_token: args.origin_token.clone(),
+        decimals: args.decimals,
+        is_initialized: true,
+    };
+    wrapped_asset.serialize(&mut *wrapped_asset_info.data.borrow_mut())?;
+
+    // Factory stays the update authority, so metadata can follow changes of the origin token
+    let create_metadata = CreateMetadataAccountV3 {
+        metadata: *metadata_info.key,
+        mint: *mint_info.key,
+        mint_authority: *factory_info.key,
+        payer: *fee_payer_info.key,
+        update_authority: (*factory_info.key, true),
+        system_program: *system_program.key,
+        rent: None,
+    };
+    invoke_signed(
+        &create_metadata.instruction(CreateMetadataAccountV3InstructionArgs {
+            data: DataV2 {
+                name: args.metadata.name,
+                symbol: args.metadata.symbol,
+                uri: args.metadata.uri,
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            is_mutable: true,
+            collection_details: None,
+        }),
+        &[metadata_info.clone(), mint_info.clone(), factory_info.clone(), fee_payer_info.clone(), system_program.clone(), metadata_program.clone()],
+        &[&[PDA_FACTORY_SEED.as_bytes(), factory.seed.as_ref(), &[factory.bump]]],
+    )?;
+
+    WrappedTokenEvent::WrappedMintCreated(WrappedMintCreated {
+        factory: *factory_info.key,
+        mint: *mint_info.key,
+        origin_chain: args.origin_chain,
+        origin_token: args.origin_token,
+        decimals: args.decimals,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_mint<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: MintArgs,
+) -> ProgramResult {
+    if args.amount == 0 {
+        return Err(WrappedTokenError::InvalidAmount.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let factory_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    verify_token_program::<WrappedTokenError>(token_program)?;
+
+    let mut factory = load_factory(program_id, factory_info)?;
+
+    verify_not_expired::<WrappedTokenError>(args.expires_at)?;
+    verify_nonce::<WrappedTokenError>(args.nonce, factory.nonce)?;
+
+    let hash = mint_hash(program_id, factory_info.key, &factory, mint_info.key, recipient_info.key, args.amount, &args.tx_id, args.nonce, args.expires_at);
+    verify_signatures::<WrappedTokenError>(hash.as_ref(), &args.signatures, accounts, &factory.public_keys, factory.threshold)?;
+
+    next_nonce::<WrappedTokenError>(&mut factory.nonce)?;
+    factory.serialize(&mut *factory_info.data.borrow_mut())?;
+
+    // Token program rejects mints whose authority is not the factory
+    invoke_signed(
+        &spl_token::instruction::mint_to(&spl_token::id(), mint_info.key, recipient_info.key, factory_info.key, &[], args.amount)?,
+        &[mint_info.clone(), recipient_info.clone(), factory_info.clone()],
+        &[&[PDA_FACTORY_SEED.as_bytes(), factory.seed.as_ref(), &[factory.bump]]],
+    )?;
+
+    WrappedTokenEvent::Minted(Minted {
+        factory: *factory_info.key,
+        mint: *mint_info.key,
+        recipient: *recipient_info.key,
+        amount: args.amount,
+        tx_id: args.tx_id,
+        nonce: args.nonce,
+    }).emit();
+    Ok(())
+}
+
+pub fn process_burn<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: BurnArgs,
+) -> ProgramResult {
+    if args.amount == 0 {
+        return Err(WrappedTokenError::InvalidAmount.into());
+    }
+
+    if args.destination_address.is_empty() || args.destination_address.len() > MAX_DESTINATION_ADDRESS_LENGTH {
+        return Err(WrappedTokenError::InvalidDestination.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let factory_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let wrapped_asset_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !owner_info.is_signer {
+        return Err(WrappedTokenError::MissingSigner.into());
+    }
+
+    verify_token_program::<WrappedTokenError>(token_program)?;
+
+    let mut factory = load_factory(program_id, factory_info)?;
+
+    if wrapped_asset_info.owner != program_id {
+        return Err(WrappedTokenError::AccountOwnerMismatch.into());
+    }
+
+    let wrapped_asset: WrappedAsset = BorshDeserialize::deserialize(&mut wrapped_asset_info.data.borrow().as_ref())?;
+    if !wrapped_asset.is_initialized {
+        return Err(WrappedTokenError::NotInitialized.into());
+    }
+
+    if wrapped_asset.mint != *mint_info.key || wrapped_asset.factory != *factory_info.key {
+        return Err(WrappedTokenError::WrongMint.into());
+    }
+
+    invoke(
+        &spl_token::instruction::burn(&spl_token::id(), source_info.key, mint_info.key, owner_info.key, &[], args.amount)?,
+        &[source_info.clone(), mint_info.clone(), owner_info.clone()],
+    )?;
+
+    let nonce = next_nonce::<WrappedTokenError>(&mut factory.burn_nonce)?;
+    factory.serialize(&mut *factory_info.data.borrow_mut())?;
+
+    WrappedTokenEvent::Burned(Burned {
+        factory: *factory_info.key,
+        mint: *mint_info.key,
+        sender: *owner_info.key,
+        amount: args.amount,
+        origin_chain: wrapped_asset.origin_chain,
+        origin_token: wrapped_asset.origin_token,
+        destination_address: args.destination_address,
+        nonce,
+    }).emit();
+    Ok(())
+}
+
+
+/// Loads initialized Factory owned by the program from writable `factory_info`.
+fn load_factory(program_id: &Pubkey, factory_info: &AccountInfo) -> Result<Factory, ProgramError> {
+    if factory_info.owner != program_id {
+        return Err(WrappedTokenError::AccountOwnerMismatch.into());
+    }
+
+    if !factory_info.is_writable {
+        return Err(WrappedTokenError::AccountNotWritable.into());
+    }
+
+    let factory: Factory = BorshDeserialize::deserialize(&mut factory_info.data.borrow().as_ref())?;
+    if !factory.is_initialized {
+        return Err(WrappedTokenError::NotInitialized.into());
+    }
+
+    Ok(factory)
+}
+
+
+fn validate_metadata(metadata: &WrappedMetadata) -> ProgramResult {
+    if metadata.name.len() > MAX_NAME_LENGTH || metadata.symbol.len() > MAX_SYMBOL_LENGTH || metadata.uri.len() > MAX_URI_LENGTH {
+        return Err(WrappedTokenError::InvalidMetadata.into());
+    }
+
+    Ok(())
+}
+
@@ -0,0 +1,47 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::pubkey::Pubkey;
+use upgrade::state::{AdminKey, MAX_ADMIN_KEY_SIZE, MAX_SIGNERS};
+
+/// Maximal length of the token address on the origin chain, fits addresses of EVM, Solana and Cosmos chains
+pub const MAX_ORIGIN_TOKEN_LENGTH: usize = 64;
+
+/// Maximal length of the address on the destination chain of Burn
+pub const MAX_DESTINATION_ADDRESS_LENGTH: usize = 64;
+
+pub const MAX_FACTORY_SIZE: usize = 32 + 1 + 4 + MAX_SIGNERS * MAX_ADMIN_KEY_SIZE + 1 + 32 + 8 + 8 + 1;
+
+pub const MAX_WRAPPED_ASSET_SIZE: usize = 32 + 32 + 8 + 4 + MAX_ORIGIN_TOKEN_LENGTH + 1 + 1;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct Factory {
+    // Seed of the factory address, several factories can be deployed by one program
+    pub seed: [u8; 32],
+    pub bump: u8,
+    // ECDSA public keys (64 byte format) or Ethereum addresses (20 byte format) of the oracle signers
+    pub public_keys: Vec<AdminKey>,
+    // Number of signers required to authorize operation
+    pub threshold: u8,
+    // Chain/deployment tag included in signed messages (e.g. cluster genesis hash)
+    pub domain: [u8; 32],
+    // Nonce of the next signed operation: CreateWrappedMint or Mint
+    pub nonce: u64,
+    // Nonce of the next burn, identifies the burn for the oracle
+    pub burn_nonce: u64,
+    pub is_initialized: bool,
+}
+
+/// Origin of a wrapped mint created by the factory.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, ShankAccount)]
+pub struct WrappedAsset {
+    pub factory: Pubkey,
+    pub mint: Pubkey,
+    // Identifier of the origin chain, interpreted by the oracle
+    pub origin_chain: u64,
+    // Token address on the origin chain
+    pub origin_token: Vec<u8>,
+    pub decimals: u8,
+    pub is_initialized: bool,
+}
@@ -0,0 +1,72 @@
+//! gRPC service driving upgrades of programs controlled by UpgradeAdmin, so backend systems propose upgrades,
+//! collect signatures and execute them without linking the Rust client. Signing requests are kept as JSON files
+//! in the format of `upgrade-admin request`, so operators can inspect and sign them with the CLI as well.
+
+// Handlers return tonic::Status, which is large by design
+#![allow(clippy::result_large_err)]
+
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::Parser;
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::read_keypair_file;
+use tonic::transport::Server;
+
+mod service;
+
+pub mod proto {
+    tonic::include_proto!("upgrade_admin.v1");
+}
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(name = "upgrade-admin-service", about = "gRPC service driving upgrades of the upgrade program")]
+struct Cli {
+    /// JSON RPC URL of the cluster
+    #[arg(long, default_value = "http://localhost:8899")]
+    url: String,
+    /// Address of the upgrade program
+    #[arg(long)]
+    program_id: Pubkey,
+    /// Keypair file of the fee payer of executed operations
+    #[arg(long, env = "UPGRADE_ADMIN_SERVICE_KEYPAIR")]
+    keypair: PathBuf,
+    /// Address the gRPC server listens on
+    #[arg(long, default_value = "127.0.0.1:50051")]
+    listen: SocketAddr,
+    /// Directory of signing request files
+    #[arg(long, default_value = "requests")]
+    requests_dir: PathBuf,
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let payer = read_keypair_file(&cli.keypair).map_err(|e| format!("{}: {}", cli.keypair.display(), e))?;
+    std::fs::create_dir_all(&cli.requests_dir)?;
+
+    let service = service::AdminService::new(
+        RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        cli.program_id,
+        payer,
+        cli.requests_dir,
+    );
+
+    println!("Listening on {}", cli.listen);
+    Server::builder()
+        .add_service(proto::upgrade_admin_service_server::UpgradeAdminServiceServer::new(service))
+        .serve(cli.listen)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,239 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::client_error::Error as RpcError;
+use solana_sdk::signature::{Keypair, Signer};
+use tonic::{Request, Response, Status};
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::state::{AdminKey, UpgradeAdmin};
+use upgrade_program_client::coordinator::{self, CoordinatorError, SigningRequest};
+use upgrade_program_client::signing::Operation;
+use upgrade_program_client::{self as client, ClientError};
+
+use crate::proto::upgrade_admin_service_server::UpgradeAdminService;
+use crate::proto::{self, Admin, ExecuteRequest, ExecuteResponse, GetAdminRequest, ProposeUpgradeRequest, SubmitSignatureRequest};
+
+pub struct AdminService {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    rpc: RpcClient,
+    program_id: Pubkey,
+    payer: Keypair,
+    requests_dir: PathBuf,
+    // Serializes read-modify-write of signing request files
+    lock: Mutex<()>,
+}
+
+impl AdminService {
+    pub fn new(rpc: RpcClient, program_id: Pubkey, payer: Keypair, requests_dir: PathBuf) -> Self {
+        AdminService { inner: Arc::new(Inner { rpc, program_id, payer, requests_dir, lock: Mutex::new(()) }) }
+    }
+}
+
+/// Runs blocking RPC calls of the client off the async executor.
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, Status> + Send + 'static) -> Result<Response<T>, Status> {
+    tokio::task::spawn_blocking(f).await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map(Response::new)
+}
+
+#[tonic::async_trait]
+impl UpgradeAdminService for AdminService {
+    async fn get_admin(&self, request: Request<GetAdminRequest>) -> Result<Response<Admin>, Status> {
+        let inner = self.inner.clone();
+        blocking(move || inner.get_admin(request.into_inner())).await
+    }
+
+    async fn propose_upgrade(&self, request: Request<ProposeUpgradeRequest>) -> Result<Response<proto::SigningRequest>, Status> {
+        let inner = self.inner.clone();
+        blocking(move || inner.propose_upgrade(request.into_inner())).await
+    }
+
+    async fn submit_signature(&self, request: Request<SubmitSignatureRequest>) -> Result<Response<proto::SigningRequest>, Status> {
+        let inner = self.inner.clone();
+        blocking(move || inner.submit_signature(request.into_inner())).await
+    }
+
+    async fn execute(&self, request: Request<ExecuteRequest>) -> Result<Response<ExecuteResponse>, Status> {
+        let inner = self.inner.clone();
+        blocking(move || inner.execute(request.into_inner())).await
+    }
+}
+
+impl Inner {
+    fn get_admin(&self, request: GetAdminRequest) -> Result<Admin, Status> {
+        let contract = parse_pubkey("contract", &request.contract)?;
+        let address = client::admin_address(&self.program_id, &contract);
+        let admin = self.fetch_admin(&address)?;
+
+        Ok(Admin {
+            address: address.to_string(),
+            contract: admin.contract.to_string(),
+            public_keys: admin.public_keys.iter().map(admin_key_string).collect(),
+            weights: admin.weights.iter().map(|weight| *weight as u32).collect(),
+            threshold: admin.threshold as u32,
+            upgrade_nonce: admin.upgrade_nonce.to_string(),
+            authority_nonce: admin.authority_nonce.to_string(),
+            key_nonce: admin.key_nonce.to_string(),
+            managed_contracts: admin.managed_contracts.iter().map(Pubkey::to_string).collect(),
+            paused: admin.paused,
+            active: admin.active,
+            json: serde_json::to_string(&admin).map_err(|e| Status::internal(e.to_string()))?,
+        })
+    }
+
+    fn propose_upgrade(&self, request: ProposeUpgradeRequest) -> Result<proto::SigningRequest, Status> {
+        let contract = parse_pubkey("contract", &request.contract)?;
+        let target = optional_pubkey("target", &request.target)?.unwrap_or(contract);
+        let buffer = parse_pubkey("buffer", &request.buffer)?;
+        let code_hash = match request.code_hash.is_empty() {
+            true => client::get_buffer_code_hash(&self.rpc, &buffer).map_err(client_status)?,
+            false => parse_digest("code_hash", &request.code_hash)?,
+        };
+        let operation = match request.delayed {
+            true => Operation::ProposeUpgrade {
+                contract: target,
+                buffer,
+                code_hash,
+                not_before_slot: request.not_before_slot,
+                not_after_slot: request.not_after_slot,
+            },
+            false => Operation::Upgrade {
+                contract: target,
+                buffer,
+                code_hash,
+                spill: optional_pubkey("spill", &request.spill)?.unwrap_or(self.payer.pubkey()),
+            },
+        };
+
+        let admin = self.fetch_admin(&client::admin_address(&self.program_id, &contract))?;
+        let signing_request = coordinator::request(&self.program_id, &admin, operation, request.expires_at).map_err(coordinator_status)?;
+
+        let _lock = self.lock.lock().unwrap();
+        let path = self.request_path(&signing_request.envelope.digest);
+        // Proposing the same upgrade again returns the request with the signatures collected so far
+        let signing_request = match path.exists() {
+            true => coordinator::read(&path).map_err(coordinator_status)?,
+            false => {
+                coordinator::write(&path, &signing_request).map_err(coordinator_status)?;
+                signing_request
+            }
+        };
+        self.reply(&signing_request, &admin)
+    }
+
+    fn submit_signature(&self, request: SubmitSignatureRequest) -> Result<proto::SigningRequest, Status> {
+        let signature = EcdsaSignature {
+            signature: request.signature.try_into().map_err(|_| Status::invalid_argument("signature should be 64 bytes"))?,
+            recovery_id: request.recovery_id,
+            personal_sign: request.personal_sign,
+        };
+
+        let _lock = self.lock.lock().unwrap();
+        let path = self.request_path(&parse_digest("id", &request.id)?);
+        let mut signing_request = read_request(&path)?;
+        let admin = self.fetch_admin(&signing_request.envelope.admin)?;
+        coordinator::add_signature(&mut signing_request, &admin, &signature).map_err(coordinator_status)?;
+        coordinator::write(&path, &signing_request).map_err(coordinator_status)?;
+        self.reply(&signing_request, &admin)
+    }
+
+    fn execute(&self, request: ExecuteRequest) -> Result<ExecuteResponse, Status> {
+        let path = self.request_path(&parse_digest("id", &request.id)?);
+        let signing_request = {
+            let _lock = self.lock.lock().unwrap();
+            read_request(&path)?
+        };
+        let admin = self.fetch_admin(&signing_request.envelope.admin)?;
+        let instruction = coordinator::assemble(&signing_request, &admin, &self.payer.pubkey()).map_err(coordinator_status)?;
+        let signature = client::send_instructions(&self.rpc, &self.payer, &[instruction]).map_err(client_status)?;
+        Ok(ExecuteResponse { signature: signature.to_string() })
+    }
+
+    fn fetch_admin(&self, address: &Pubkey) -> Result<UpgradeAdmin, Status> {
+        let account = self.rpc.get_account_with_commitment(address, self.rpc.commitment())
+            .map_err(rpc_status)?
+            .value
+            .ok_or_else(|| Status::not_found(format!("UpgradeAdmin {} does not exist", address)))?;
+        client::decode_upgrade_admin(address, &account.data).map_err(client_status)
+    }
+
+    /// File of the signing request with `digest`, named by the digest so ids can not escape the directory.
+    fn request_path(&self, digest: &[u8; 32]) -> PathBuf {
+        self.requests_dir.join(format!("{}.json", hex::encode(digest)))
+    }
+
+    fn reply(&self, signing_request: &SigningRequest, admin: &UpgradeAdmin) -> Result<proto::SigningRequest, Status> {
+        let quorum = coordinator::quorum(signing_request, admin).map_err(coordinator_status)?;
+        Ok(proto::SigningRequest {
+            id: hex::encode(signing_request.envelope.digest),
+            envelope: serde_json::to_string(&signing_request.envelope).map_err(|e| Status::internal(e.to_string()))?,
+            signers: signing_request.signatures.iter().map(|partial| admin_key_string(&partial.signer)).collect(),
+            signed_weight: quorum.weight,
+            threshold: quorum.threshold as u32,
+            quorum_reached: quorum.is_reached(),
+        })
+    }
+}
+
+fn read_request(path: &std::path::Path) -> Result<SigningRequest, Status> {
+    if !path.exists() {
+        return Err(Status::not_found("signing request does not exist"));
+    }
+    coordinator::read(path).map_err(coordinator_status)
+}
+
+fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey, Status> {
+    value.parse().map_err(|_| Status::invalid_argument(format!("{} is not a valid address", field)))
+}
+
+fn optional_pubkey(field: &str, value: &str) -> Result<Option<Pubkey>, Status> {
+    match value.is_empty() {
+        true => Ok(None),
+        false => parse_pubkey(field, value).map(Some),
+    }
+}
+
+fn parse_digest(field: &str, value: &str) -> Result<[u8; 32], Status> {
+    hex::decode(value.trim_start_matches("0x")).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Status::invalid_argument(format!("{} should be 32 bytes hex", field)))
+}
+
+fn admin_key_string(key: &AdminKey) -> String {
+    match key {
+        AdminKey::PublicKey(key) => hex::encode(key),
+        AdminKey::EthAddress(address) => format!("0x{}", hex::encode(address)),
+        AdminKey::CompressedPublicKey(key) => hex::encode(key),
+        AdminKey::Ed25519(key) => key.to_string(),
+    }
+}
+
+fn rpc_status(e: RpcError) -> Status {
+    Status::unavailable(e.to_string())
+}
+
+fn client_status(e: ClientError) -> Status {
+    match e {
+        ClientError::Rpc(e) => rpc_status(*e),
+        ClientError::InvalidBuffer(_) => Status::invalid_argument(e.to_string()),
+        ClientError::AdminNotInitialized(_) | ClientError::OutdatedAdmin(_) | ClientError::Simulation(_) =>
+            Status::failed_precondition(e.to_string()),
+        _ => Status::internal(e.to_string()),
+    }
+}
+
+fn coordinator_status(e: CoordinatorError) -> Status {
+    match e {
+        CoordinatorError::Io(_) | CoordinatorError::InvalidFormat(_) => Status::internal(e.to_string()),
+        CoordinatorError::InvalidSignature(_) | CoordinatorError::UnknownSigner(_) | CoordinatorError::InvalidOperation(_) =>
+            Status::invalid_argument(e.to_string()),
+        CoordinatorError::DuplicateSigner(_) => Status::already_exists(e.to_string()),
+        CoordinatorError::AdminMismatch(_) | CoordinatorError::StaleRequest | CoordinatorError::UnsupportedCurve(_)
+            | CoordinatorError::QuorumNotReached { .. } => Status::failed_precondition(e.to_string()),
+    }
+}
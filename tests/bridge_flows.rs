@@ -0,0 +1,201 @@
+//! End-to-end flows of the bridge program on solana-program-test: deposit of SPL tokens into the vault and
+//! withdrawal authorized by the oracle signature, including replay of an executed withdrawal and withdrawals executed
+//! out of order, and the same for native SOL.
+//!
+//! The program is loaded from `bridge.so` in `SBF_OUT_DIR`, build it first:
+//!
+//! ```shell
+//! cargo build-sbf --manifest-path programs/bridge/Cargo.toml
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test --manifest-path tests/Cargo.toml --features test-sbf
+//! ```
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use common::{mint_account, program_test, token_account, token_balance, wallet_account, Signer256k1, EXPIRES_AT};
+use bridge::hashing;
+use bridge::instructions;
+use bridge::pda;
+use bridge::state::{Bridge, Withdrawal};
+use borsh::BorshDeserialize;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+
+const DOMAIN: [u8; 32] = [7; 32];
+
+const SEED: [u8; 32] = [1; 32];
+
+/// Mint with a token account of the depositor holding its whole supply and an empty token account of the recipient.
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    bridge: Pubkey,
+    mint: Pubkey,
+    depositor: Keypair,
+    source: Pubkey,
+    recipient: Pubkey,
+}
+
+async fn setup() -> Env {
+    let program_id = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let depositor = Keypair::new();
+    let source = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+
+    let mut program_test = program_test("bridge", program_id);
+
+    program_test.add_account(mint, mint_account(1_000, 6));
+    program_test.add_account(source, token_account(mint, depositor.pubkey(), 1_000));
+    program_test.add_account(recipient, token_account(mint, Pubkey::new_unique(), 0));
+    program_test.add_account(depositor.pubkey(), wallet_account());
+
+    Env {
+        context: program_test.start_with_context().await,
+        program_id,
+        bridge: pda::find_bridge_address(&program_id, &SEED).0,
+        mint,
+        depositor,
+        source,
+        recipient,
+    }
+}
+
+impl Env {
+    async fn send(&mut self, instruction: Instruction, signers: &[&Keypair]) -> Result<(), TransactionError> {
+        common::send(&mut self.context, &[instruction], signers).await
+    }
+
+    async fn bridge(&mut self) -> Bridge {
+        let account = self.context.banks_client.get_account(self.bridge).await.unwrap().expect("bridge is not created");
+        Bridge::deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn balance(&mut self, address: Pubkey) -> u64 {
+        token_balance(&mut self.context, address).await
+    }
+
+    async fn init_bridge(&mut self, signer: &Signer256k1) {
+        let instruction = instructions::initialize_bridge(self.program_id, self.context.payer.pubkey(), SEED, vec![signer.admin_key()], 1, DOMAIN);
+        self.send(instruction, &[]).await.unwrap();
+    }
+
+    async fn deposit(&mut self, amount: u64) -> Result<(), TransactionError> {
+        let instruction = instructions::deposit(self.program_id, self.bridge, self.mint, self.source, self.depositor.pubkey(), amount, 1, vec![0xab; 20]);
+        let depositor = Keypair::from_bytes(&self.depositor.to_bytes()).unwrap();
+        self.send(instruction, &[&depositor]).await
+    }
+
+    async fn withdraw(&mut self, signer: &Signer256k1, amount: u64, tx_id: &[u8]) -> Result<(), TransactionError> {
+        let bridge = self.bridge().await;
+        let hash = hashing::withdraw_hash(&self.program_id, &self.bridge, &bridge, &self.mint, &self.recipient, amount, tx_id, EXPIRES_AT);
+        let instruction = instructions::withdraw(self.program_id, self.bridge, self.mint, self.recipient, self.context.payer.pubkey(), amount, tx_id.to_vec(), EXPIRES_AT, vec![signer.sign(hash.as_ref())]);
+        self.send(instruction, &[]).await
+    }
+
+    async fn deposit_native(&mut self, amount: u64) -> Result<(), TransactionError> {
+        let instruction = instructions::deposit_native(self.program_id, self.bridge, self.depositor.pubkey(), amount, 1, vec![0xab; 20]);
+        let depositor = Keypair::from_bytes(&self.depositor.to_bytes()).unwrap();
+        self.send(instruction, &[&depositor]).await
+    }
+
+    async fn withdraw_native(&mut self, signer: &Signer256k1, recipient: Pubkey, amount: u64, tx_id: &[u8]) -> Result<(), TransactionError> {
+        let bridge = self.bridge().await;
+        let hash = hashing::withdraw_native_hash(&self.program_id, &self.bridge, &bridge, &recipient, amount, tx_id, EXPIRES_AT);
+        let instruction = instructions::withdraw_native(self.program_id, self.bridge, recipient, self.context.payer.pubkey(), amount, tx_id.to_vec(), EXPIRES_AT, vec![signer.sign(hash.as_ref())]);
+        self.send(instruction, &[]).await
+    }
+
+    async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.context.banks_client.get_balance(address).await.unwrap()
+    }
+
+    async fn withdrawal(&mut self, tx_id: &[u8]) -> Option<Withdrawal> {
+        let address = pda::find_withdrawal_address(&self.program_id, &self.bridge, tx_id).0;
+        let account = self.context.banks_client.get_account(address).await.unwrap()?;
+        Some(Withdrawal::deserialize(&mut account.data.as_slice()).unwrap())
+    }
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw() {
+    let mut env = setup().await;
+    let oracle = Signer256k1::new(1);
+    let vault = pda::find_vault_address(&env.program_id, &env.bridge, &env.mint).0;
+
+    env.init_bridge(&oracle).await;
+    let bridge = env.bridge().await;
+    assert!(bridge.is_initialized);
+    assert_eq!(bridge.public_keys, vec![oracle.admin_key()]);
+    assert_eq!(bridge.deposit_nonce, 0);
+
+    env.deposit(600).await.unwrap();
+    assert_eq!(env.balance(vault).await, 600);
+    assert_eq!(env.balance(env.source).await, 400);
+    assert_eq!(env.bridge().await.deposit_nonce, 1);
+
+    env.withdraw(&oracle, 250, &[7; 32]).await.unwrap();
+    assert_eq!(env.balance(vault).await, 350);
+    assert_eq!(env.balance(env.recipient).await, 250);
+    let withdrawal = env.withdrawal(&[7; 32]).await.expect("withdrawal is not recorded");
+    assert_eq!((withdrawal.bridge, withdrawal.tx_id), (env.bridge, vec![7; 32]));
+}
+
+#[tokio::test]
+async fn withdraw_rejects_foreign_signer_and_replay() {
+    let mut env = setup().await;
+    let oracle = Signer256k1::new(1);
+    let stranger = Signer256k1::new(2);
+
+    env.init_bridge(&oracle).await;
+    env.deposit(600).await.unwrap();
+
+    assert!(env.withdraw(&stranger, 100, &[1; 32]).await.is_err());
+    assert!(env.withdrawal(&[1; 32]).await.is_none());
+    env.withdraw(&oracle, 100, &[1; 32]).await.unwrap();
+
+    // Withdrawal of the transaction already exists
+    assert!(env.withdraw(&oracle, 100, &[1; 32]).await.is_err());
+    assert_eq!(env.balance(env.recipient).await, 100);
+
+    // Withdrawals are independent of each other, a 64 byte id like a Solana signature is accepted
+    env.withdraw(&oracle, 50, &[3; 64]).await.unwrap();
+    env.withdraw(&oracle, 50, &[2; 32]).await.unwrap();
+    assert!(env.withdraw(&oracle, 50, &[4; 65]).await.is_err());
+    assert_eq!(env.balance(env.recipient).await, 200);
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw_native() {
+    let mut env = setup().await;
+    let oracle = Signer256k1::new(1);
+    let native_vault = pda::find_native_vault_address(&env.program_id, &env.bridge).0;
+    let recipient = env.depositor.pubkey();
+    let rent = env.context.banks_client.get_rent().await.unwrap().minimum_balance(0);
+
+    env.init_bridge(&oracle).await;
+    env.deposit_native(5_000_000).await.unwrap();
+    assert_eq!(env.lamports(native_vault).await, 5_000_000 + rent);
+    assert_eq!(env.bridge().await.deposit_nonce, 1);
+
+    let balance = env.lamports(recipient).await;
+    env.withdraw_native(&oracle, recipient, 2_000_000, &[9; 32]).await.unwrap();
+    assert_eq!(env.lamports(recipient).await, balance + 2_000_000);
+    assert_eq!(env.lamports(native_vault).await, 3_000_000 + rent);
+
+    // Executed transaction id, also as a token withdrawal, signature for another transaction id and withdrawal
+    // of the reserve are rejected
+    assert!(env.withdraw_native(&oracle, recipient, 2_000_000, &[9; 32]).await.is_err());
+    env.deposit(600).await.unwrap();
+    assert!(env.withdraw(&oracle, 100, &[9; 32]).await.is_err());
+    let bridge = env.bridge().await;
+    let hash = hashing::withdraw_native_hash(&env.program_id, &env.bridge, &bridge, &recipient, 1_000_000, &[9; 32], EXPIRES_AT);
+    let instruction = instructions::withdraw_native(env.program_id, env.bridge, recipient, env.context.payer.pubkey(), 1_000_000, vec![8; 32], EXPIRES_AT, vec![oracle.sign(hash.as_ref())]);
+    assert!(env.send(instruction, &[]).await.is_err());
+    assert!(env.withdraw_native(&oracle, recipient, 3_000_001, &[10; 32]).await.is_err());
+    assert_eq!(env.lamports(native_vault).await, 3_000_000 + rent);
+}
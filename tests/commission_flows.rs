@@ -0,0 +1,149 @@
+//! End-to-end flows of the commission program on solana-program-test: fee schedule updates authorized by the
+//! signatures, charging flat and basis-point fees into the treasury and withdrawal of accrued fees.
+//!
+//! The program is loaded from `commission.so` in `SBF_OUT_DIR`, build it first:
+//!
+//! ```shell
+//! cargo build-sbf --manifest-path programs/commission/Cargo.toml
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test --manifest-path tests/Cargo.toml --features test-sbf
+//! ```
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use common::{mint_account, program_test, token_account, token_balance, wallet_account, Signer256k1, EXPIRES_AT};
+use borsh::BorshDeserialize;
+use commission::hashing;
+use commission::instructions;
+use commission::pda;
+use commission::state::{Commission, Fee};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+
+const SEED: [u8; 32] = [1; 32];
+
+/// Mint with a funded token account of the payer and an empty token account of the fee recipient.
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    commission: Pubkey,
+    mint: Pubkey,
+    payer: Keypair,
+    source: Pubkey,
+    recipient: Pubkey,
+}
+
+async fn setup() -> Env {
+    let program_id = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let payer = Keypair::new();
+    let source = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+
+    let mut program_test = program_test("commission", program_id);
+
+    program_test.add_account(mint, mint_account(1_000_000, 6));
+    program_test.add_account(source, token_account(mint, payer.pubkey(), 1_000_000));
+    program_test.add_account(recipient, token_account(mint, Pubkey::new_unique(), 0));
+    program_test.add_account(payer.pubkey(), wallet_account());
+
+    Env {
+        context: program_test.start_with_context().await,
+        program_id,
+        commission: pda::find_commission_address(&program_id, &SEED).0,
+        mint,
+        payer,
+        source,
+        recipient,
+    }
+}
+
+impl Env {
+    async fn send(&mut self, instruction: Instruction, signers: &[&Keypair]) -> Result<(), TransactionError> {
+        common::send(&mut self.context, &[instruction], signers).await
+    }
+
+    async fn commission(&mut self) -> Commission {
+        let account = self.context.banks_client.get_account(self.commission).await.unwrap().expect("commission is not created");
+        Commission::deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn balance(&mut self, address: Pubkey) -> u64 {
+        token_balance(&mut self.context, address).await
+    }
+
+    async fn init_commission(&mut self, signer: &Signer256k1) {
+        let instruction = instructions::initialize_commission(self.program_id, self.context.payer.pubkey(), SEED, vec![signer.admin_key()], 1, [0; 32]);
+        self.send(instruction, &[]).await.unwrap();
+    }
+
+    async fn set_fee(&mut self, signer: &Signer256k1, fee: Fee, nonce: u64) -> Result<(), TransactionError> {
+        let commission = self.commission().await;
+        let hash = hashing::set_fee_hash(&self.program_id, &self.commission, &commission, &self.mint, &fee, nonce, EXPIRES_AT);
+        let instruction = instructions::set_fee(self.program_id, self.commission, self.context.payer.pubkey(), self.mint, fee, nonce, EXPIRES_AT, vec![signer.sign(hash.as_ref())]);
+        self.send(instruction, &[]).await
+    }
+
+    async fn charge_fee(&mut self, amount: u64) -> Result<(), TransactionError> {
+        let instruction = instructions::charge_fee(self.program_id, self.commission, self.mint, self.source, self.payer.pubkey(), amount);
+        let payer = Keypair::from_bytes(&self.payer.to_bytes()).unwrap();
+        self.send(instruction, &[&payer]).await
+    }
+
+    async fn withdraw(&mut self, signer: &Signer256k1, amount: u64, nonce: u64) -> Result<(), TransactionError> {
+        let commission = self.commission().await;
+        let hash = hashing::withdraw_hash(&self.program_id, &self.commission, &commission, &self.mint, &self.recipient, amount, nonce, EXPIRES_AT);
+        let instruction = instructions::withdraw(self.program_id, self.commission, self.mint, self.recipient, amount, nonce, EXPIRES_AT, vec![signer.sign(hash.as_ref())]);
+        self.send(instruction, &[]).await
+    }
+}
+
+#[tokio::test]
+async fn charge_and_withdraw() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+    let treasury = pda::find_treasury_address(&env.program_id, &env.commission, &env.mint).0;
+
+    env.init_commission(&signer).await;
+
+    // Mint without fee schedule can not be charged
+    assert!(env.charge_fee(10_000).await.is_err());
+
+    env.set_fee(&signer, Fee::BasisPoints(30), 0).await.unwrap();
+    env.charge_fee(10_000).await.unwrap();
+    assert_eq!(env.balance(treasury).await, 30);
+
+    env.set_fee(&signer, Fee::Flat(500), 1).await.unwrap();
+    env.charge_fee(10_000).await.unwrap();
+    assert!(env.charge_fee(499).await.is_err());
+    assert_eq!(env.balance(treasury).await, 530);
+    assert_eq!(env.balance(env.source).await, 1_000_000 - 530);
+
+    env.withdraw(&signer, 400, 2).await.unwrap();
+    assert_eq!(env.balance(treasury).await, 130);
+    assert_eq!(env.balance(env.recipient).await, 400);
+    assert_eq!(env.commission().await.nonce, 3);
+}
+
+#[tokio::test]
+async fn rejects_foreign_signer_replay_and_invalid_fee() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+    let stranger = Signer256k1::new(2);
+
+    env.init_commission(&signer).await;
+    assert!(env.set_fee(&stranger, Fee::Flat(1), 0).await.is_err());
+    assert!(env.set_fee(&signer, Fee::BasisPoints(10_001), 0).await.is_err());
+    env.set_fee(&signer, Fee::BasisPoints(10_000), 0).await.unwrap();
+    env.charge_fee(1_000).await.unwrap();
+
+    assert!(env.withdraw(&stranger, 1_000, 1).await.is_err());
+    assert!(env.withdraw(&signer, 1_001, 1).await.is_err());
+    env.withdraw(&signer, 500, 1).await.unwrap();
+    assert!(env.withdraw(&signer, 500, 1).await.is_err());
+    assert_eq!(env.balance(env.recipient).await, 500);
+}
@@ -0,0 +1,109 @@
+//! Fixtures shared by the flows of all programs: secp256k1 signers, SPL token accounts, the ProgramTest setup and
+//! transaction helpers.
+
+#![allow(dead_code)]
+
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_option::COption;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use upgrade::ecdsa::EcdsaSignature;
+use upgrade::state::AdminKey;
+
+pub const EXPIRES_AT: i64 = i64::MAX;
+
+pub struct Signer256k1(libsecp256k1::SecretKey);
+
+impl Signer256k1 {
+    pub fn new(seed: u8) -> Self {
+        Signer256k1(libsecp256k1::SecretKey::parse(&[seed; 32]).unwrap())
+    }
+
+    pub fn admin_key(&self) -> AdminKey {
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&self.0).serialize();
+        AdminKey::PublicKey(public_key[1..].try_into().unwrap())
+    }
+
+    pub fn sign(&self, hash: &[u8]) -> EcdsaSignature {
+        let (signature, recovery_id) = libsecp256k1::sign(&libsecp256k1::Message::parse_slice(hash).unwrap(), &self.0);
+        EcdsaSignature {
+            signature: signature.serialize(),
+            recovery_id: recovery_id.serialize() as u64,
+            personal_sign: false,
+        }
+    }
+}
+
+/// ProgramTest with `program_name` loaded from `{program_name}.so` in `SBF_OUT_DIR`. Processors of the programs
+/// move the account data slices while serializing state, so they can not run as native processors.
+pub fn program_test(program_name: &str, program_id: Pubkey) -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_program(program_name, program_id, None);
+    program_test
+}
+
+pub fn account(data: Vec<u8>, owner: Pubkey) -> Account {
+    Account {
+        lamports: 10_000_000_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn packed<T: Pack>(state: T) -> Vec<u8> {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    data
+}
+
+pub fn packed_account<T: Pack>(state: T) -> Account {
+    account(packed(state), spl_token::id())
+}
+
+/// Mint without mint and freeze authorities.
+pub fn mint_account(supply: u64, decimals: u8) -> Account {
+    packed_account(spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    })
+}
+
+pub fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    })
+}
+
+/// System account with lamports for fees and rent.
+pub fn wallet_account() -> Account {
+    account(vec![], solana_sdk::system_program::id())
+}
+
+/// Sends `instructions` signed by the payer and `signers`.
+pub async fn send(context: &mut ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), TransactionError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut keypairs = vec![&context.payer];
+    keypairs.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(instructions, Some(&context.payer.pubkey()), &keypairs, blockhash);
+
+    context.banks_client.process_transaction(transaction).await.map_err(|e| e.unwrap())
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
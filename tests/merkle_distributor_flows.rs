@@ -0,0 +1,166 @@
+//! End-to-end flows of the merkle distributor program on solana-program-test: root updates authorized by the
+//! signatures, claims with inclusion proofs, double claims and claims of leaves that are not in the root.
+//!
+//! The program is loaded from `merkle_distributor.so` in `SBF_OUT_DIR`, build it first:
+//!
+//! ```shell
+//! cargo build-sbf --manifest-path programs/merkle-distributor/Cargo.toml
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test --manifest-path tests/Cargo.toml --features test-sbf
+//! ```
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use common::{mint_account, program_test, token_account, token_balance, wallet_account, Signer256k1, EXPIRES_AT};
+use borsh::BorshDeserialize;
+use merkle_distributor::hashing;
+use merkle_distributor::instructions;
+use merkle_distributor::merkle;
+use merkle_distributor::pda;
+use merkle_distributor::state::{ClaimReceipt, Distributor};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+
+const SEED: [u8; 32] = [1; 32];
+
+const AMOUNTS: [u64; 3] = [100, 250, 400];
+
+/// Mint with tokens of the funder and recipients of AMOUNTS with empty token accounts.
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    distributor: Pubkey,
+    mint: Pubkey,
+    funder: Keypair,
+    funder_account: Pubkey,
+    recipients: Vec<Keypair>,
+    recipient_accounts: Vec<Pubkey>,
+}
+
+async fn setup() -> Env {
+    let program_id = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let funder = Keypair::new();
+    let funder_account = Pubkey::new_unique();
+    let recipients: Vec<Keypair> = AMOUNTS.iter().map(|_| Keypair::new()).collect();
+    let recipient_accounts: Vec<Pubkey> = AMOUNTS.iter().map(|_| Pubkey::new_unique()).collect();
+
+    let mut program_test = program_test("merkle_distributor", program_id);
+
+    program_test.add_account(mint, mint_account(1_000, 6));
+    program_test.add_account(funder_account, token_account(mint, funder.pubkey(), 1_000));
+    for (recipient, account) in recipients.iter().zip(&recipient_accounts) {
+        program_test.add_account(*account, token_account(mint, recipient.pubkey(), 0));
+        program_test.add_account(recipient.pubkey(), wallet_account());
+    }
+
+    Env {
+        context: program_test.start_with_context().await,
+        program_id,
+        distributor: pda::find_distributor_address(&program_id, &SEED).0,
+        mint,
+        funder,
+        funder_account,
+        recipients,
+        recipient_accounts,
+    }
+}
+
+impl Env {
+    async fn send(&mut self, instruction: Instruction, signers: &[&Keypair]) -> Result<(), TransactionError> {
+        common::send(&mut self.context, &[instruction], signers).await
+    }
+
+    async fn distributor(&mut self) -> Distributor {
+        let account = self.context.banks_client.get_account(self.distributor).await.unwrap().expect("distributor is not created");
+        Distributor::deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn balance(&mut self, address: Pubkey) -> u64 {
+        token_balance(&mut self.context, address).await
+    }
+
+    fn leaves(&self) -> Vec<[u8; 32]> {
+        self.recipients.iter().zip(AMOUNTS).enumerate()
+            .map(|(index, (recipient, amount))| merkle::leaf(index as u64, &recipient.pubkey(), amount))
+            .collect()
+    }
+
+    /// Initializes the distributor and funds its vault with all tokens of the funder.
+    async fn init_distributor(&mut self, signer: &Signer256k1) {
+        let instruction = instructions::initialize_distributor(self.program_id, self.mint, self.context.payer.pubkey(), SEED, vec![signer.admin_key()], 1, [0; 32]);
+        self.send(instruction, &[]).await.unwrap();
+
+        let vault = pda::find_vault_address(&self.program_id, &self.distributor).0;
+        let instruction = spl_token::instruction::transfer(&spl_token::id(), &self.funder_account, &vault, &self.funder.pubkey(), &[], 1_000).unwrap();
+        let funder = Keypair::from_bytes(&self.funder.to_bytes()).unwrap();
+        self.send(instruction, &[&funder]).await.unwrap();
+    }
+
+    async fn set_root(&mut self, signer: &Signer256k1, root: [u8; 32], nonce: u64) -> Result<(), TransactionError> {
+        let distributor = self.distributor().await;
+        let hash = hashing::set_root_hash(&self.program_id, &self.distributor, &distributor, &root, nonce, EXPIRES_AT);
+        let instruction = instructions::set_root(self.program_id, self.distributor, root, nonce, EXPIRES_AT, vec![signer.sign(hash.as_ref())]);
+        self.send(instruction, &[]).await
+    }
+
+    async fn claim(&mut self, index: usize, amount: u64, proof: Vec<[u8; 32]>) -> Result<(), TransactionError> {
+        let recipient = Keypair::from_bytes(&self.recipients[index].to_bytes()).unwrap();
+        let instruction = instructions::claim(self.program_id, self.distributor, self.mint, self.recipient_accounts[index], recipient.pubkey(), index as u64, amount, proof);
+        self.send(instruction, &[&recipient]).await
+    }
+}
+
+#[tokio::test]
+async fn set_root_and_claim() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+    let leaves = env.leaves();
+
+    env.init_distributor(&signer).await;
+    assert!(env.claim(0, AMOUNTS[0], merkle::proof(&leaves, 0)).await.is_err());
+
+    env.set_root(&signer, merkle::root(&leaves), 0).await.unwrap();
+    for (index, amount) in AMOUNTS.into_iter().enumerate() {
+        env.claim(index, amount, merkle::proof(&leaves, index)).await.unwrap();
+        assert_eq!(env.balance(env.recipient_accounts[index]).await, amount);
+    }
+
+    let vault = pda::find_vault_address(&env.program_id, &env.distributor).0;
+    assert_eq!(env.balance(vault).await, 1_000 - AMOUNTS.iter().sum::<u64>());
+
+    let address = pda::find_claim_receipt_address(&env.program_id, &env.distributor, 1).0;
+    let account = env.context.banks_client.get_account(address).await.unwrap().unwrap();
+    let claim_receipt = ClaimReceipt::deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!((claim_receipt.recipient, claim_receipt.amount), (env.recipients[1].pubkey(), AMOUNTS[1]));
+}
+
+#[tokio::test]
+async fn claim_rejects_double_claim_and_forged_leaf() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+    let stranger = Signer256k1::new(2);
+    let leaves = env.leaves();
+
+    env.init_distributor(&signer).await;
+    assert!(env.set_root(&stranger, merkle::root(&leaves), 0).await.is_err());
+    env.set_root(&signer, merkle::root(&leaves), 0).await.unwrap();
+
+    assert!(env.claim(0, AMOUNTS[0] + 1, merkle::proof(&leaves, 0)).await.is_err());
+    assert!(env.claim(0, AMOUNTS[0], merkle::proof(&leaves, 1)).await.is_err());
+    env.claim(0, AMOUNTS[0], merkle::proof(&leaves, 0)).await.unwrap();
+    assert!(env.claim(0, AMOUNTS[0], merkle::proof(&leaves, 0)).await.is_err());
+
+    // The leaf stays claimed under a corrected root that keeps its index
+    let mut corrected = leaves.clone();
+    corrected[0] = merkle::leaf(0, &env.recipients[0].pubkey(), AMOUNTS[0] * 2);
+    env.set_root(&signer, merkle::root(&corrected), 1).await.unwrap();
+    assert!(env.claim(0, AMOUNTS[0] * 2, merkle::proof(&corrected, 0)).await.is_err());
+    env.claim(1, AMOUNTS[1], merkle::proof(&corrected, 1)).await.unwrap();
+    assert_eq!(env.balance(env.recipient_accounts[0]).await, AMOUNTS[0]);
+    assert_eq!(env.distributor().await.nonce, 2);
+}
@@ -0,0 +1,175 @@
+//! End-to-end flows of the message inbox program on solana-program-test: messages to the system and token programs
+//! signed by the authority of the inbox, messages that pass the instructions sysvar to their target, replay of an
+//! executed message and messages executed with other accounts or data than they were signed for.
+//!
+//! The program is loaded from `message_inbox.so` in `SBF_OUT_DIR`, build it first:
+//!
+//! ```shell
+//! cargo build-sbf --manifest-path programs/message-inbox/Cargo.toml
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test --manifest-path tests/Cargo.toml --features test-sbf
+//! ```
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use common::{mint_account, program_test, token_account, token_balance, wallet_account, Signer256k1, EXPIRES_AT};
+use borsh::BorshDeserialize;
+use message_inbox::hashing;
+use message_inbox::instructions;
+use message_inbox::pda;
+use message_inbox::state::Inbox;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::sysvar;
+use solana_sdk::transaction::TransactionError;
+
+const SEED: [u8; 32] = [1; 32];
+
+/// Authority of the inbox with lamports and a token account of the whole supply of the mint, and an empty token
+/// account of the recipient.
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    inbox: Pubkey,
+    authority: Pubkey,
+    mint: Pubkey,
+    treasury: Pubkey,
+    recipient: Pubkey,
+}
+
+async fn setup() -> Env {
+    let program_id = Pubkey::new_unique();
+    let inbox = pda::find_inbox_address(&program_id, &SEED).0;
+    let authority = pda::find_authority_address(&program_id, &inbox).0;
+    let mint = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+
+    let mut program_test = program_test("message_inbox", program_id);
+
+    program_test.add_account(mint, mint_account(1_000, 6));
+    program_test.add_account(treasury, token_account(mint, authority, 1_000));
+    program_test.add_account(recipient, token_account(mint, Pubkey::new_unique(), 0));
+    program_test.add_account(authority, wallet_account());
+
+    Env {
+        context: program_test.start_with_context().await,
+        program_id,
+        inbox,
+        authority,
+        mint,
+        treasury,
+        recipient,
+    }
+}
+
+impl Env {
+    async fn send(&mut self, instruction: Instruction) -> Result<(), TransactionError> {
+        common::send(&mut self.context, &[instruction], &[]).await
+    }
+
+    async fn inbox(&mut self) -> Inbox {
+        let account = self.context.banks_client.get_account(self.inbox).await.unwrap().expect("inbox is not created");
+        Inbox::deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn balance(&mut self, address: Pubkey) -> u64 {
+        token_balance(&mut self.context, address).await
+    }
+
+    async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.context.banks_client.get_balance(address).await.unwrap()
+    }
+
+    async fn init_inbox(&mut self, signer: &Signer256k1) {
+        let instruction = instructions::initialize_inbox(self.program_id, self.context.payer.pubkey(), SEED, vec![signer.admin_key()], 1, [0; 32]);
+        self.send(instruction).await.unwrap();
+    }
+
+    fn token_transfer(&self, amount: u64) -> Instruction {
+        spl_token::instruction::transfer_checked(&spl_token::id(), &self.treasury, &self.mint, &self.recipient, &self.authority, &[], amount, 6).unwrap()
+    }
+
+    /// Execute of `executed` with the signature of `signed` for the current nonce.
+    async fn execute_signed_for(&mut self, signer: &Signer256k1, signed: &Instruction, executed: Instruction) -> Result<(), TransactionError> {
+        let inbox = self.inbox().await;
+        let hash = hashing::execute_hash(&self.program_id, &self.inbox, &inbox, signed, inbox.nonce, EXPIRES_AT);
+        let instruction = instructions::execute(self.program_id, self.inbox, executed, inbox.nonce, EXPIRES_AT, vec![signer.sign(hash.as_ref())]);
+        self.send(instruction).await
+    }
+
+    async fn execute(&mut self, signer: &Signer256k1, message: Instruction) -> Result<(), TransactionError> {
+        let signed = message.clone();
+        self.execute_signed_for(signer, &signed, message).await
+    }
+}
+
+#[tokio::test]
+async fn execute_messages() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+    let recipient = Pubkey::new_unique();
+
+    env.init_inbox(&signer).await;
+    let inbox = env.inbox().await;
+    assert!(inbox.is_initialized);
+    assert_eq!((inbox.public_keys, inbox.nonce), (vec![signer.admin_key()], 0));
+
+    env.execute(&signer, system_instruction::transfer(&env.authority, &recipient, 5_000_000)).await.unwrap();
+    assert_eq!(env.lamports(recipient).await, 5_000_000);
+
+    let message = env.token_transfer(250);
+    env.execute(&signer, message).await.unwrap();
+    assert_eq!(env.balance(env.recipient).await, 250);
+    assert_eq!(env.balance(env.treasury).await, 750);
+    assert_eq!(env.inbox().await.nonce, 2);
+}
+
+#[tokio::test]
+async fn execute_message_with_instructions_sysvar() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+    let recipient = Pubkey::new_unique();
+
+    env.init_inbox(&signer).await;
+
+    // The sysvar is the last account of the message and is signed as part of it
+    let mut message = system_instruction::transfer(&env.authority, &recipient, 5_000_000);
+    message.accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    env.execute(&signer, message).await.unwrap();
+    assert_eq!(env.lamports(recipient).await, 5_000_000);
+}
+
+#[tokio::test]
+async fn execute_rejects_foreign_signer_replay_and_other_messages() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+    let stranger = Signer256k1::new(2);
+
+    env.init_inbox(&signer).await;
+    let message = env.token_transfer(100);
+    assert!(env.execute(&stranger, message.clone()).await.is_err());
+
+    // Signature of the executed message is bound to its nonce
+    let inbox = env.inbox().await;
+    let hash = hashing::execute_hash(&env.program_id, &env.inbox, &inbox, &message, 0, EXPIRES_AT);
+    let signatures = vec![signer.sign(hash.as_ref())];
+    env.send(instructions::execute(env.program_id, env.inbox, message.clone(), 0, EXPIRES_AT, signatures.clone())).await.unwrap();
+    assert!(env.send(instructions::execute(env.program_id, env.inbox, message.clone(), 0, EXPIRES_AT, signatures)).await.is_err());
+
+    // Accounts and data of the message are signed
+    let mut redirected = message.clone();
+    redirected.accounts[2].pubkey = Pubkey::new_unique();
+    assert!(env.execute_signed_for(&signer, &message, redirected).await.is_err());
+    assert!(env.execute_signed_for(&signer, &message, env.token_transfer(900)).await.is_err());
+    assert_eq!(env.balance(env.recipient).await, 100);
+
+    // The inbox can not execute its own instructions
+    let initialize = instructions::initialize_inbox(env.program_id, env.authority, [2; 32], vec![signer.admin_key()], 1, [0; 32]);
+    assert!(env.execute(&signer, initialize).await.is_err());
+    assert_eq!(env.inbox().await.nonce, 1);
+}
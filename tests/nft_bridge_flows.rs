@@ -0,0 +1,183 @@
+//! End-to-end flows of the NFT bridge program on solana-program-test: registration of a collection authorized by the
+//! oracle signature, deposit of its NFT to custody and release of it back to the holder.
+//!
+//! NFT mints and their Metaplex metadata are preloaded, Metaplex Token Metadata is not available in
+//! solana-program-test. The program is loaded from `nft_bridge.so` in `SBF_OUT_DIR`:
+//!
+//! ```shell
+//! cargo build-sbf --manifest-path programs/nft-bridge/Cargo.toml
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test --manifest-path tests/Cargo.toml --features test-sbf
+//! ```
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use common::{account, mint_account, program_test, token_balance, wallet_account, Signer256k1, EXPIRES_AT};
+use borsh::BorshDeserialize;
+use borsh_mpl::BorshSerialize;
+use mpl_token_metadata::accounts::Metadata;
+use mpl_token_metadata::types::{Collection, Key};
+use nft_bridge::hashing;
+use nft_bridge::instructions;
+use nft_bridge::pda;
+use nft_bridge::state::NftBridge;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+
+const SEED: [u8; 32] = [1; 32];
+
+const DESTINATION_CHAIN: u64 = 1;
+
+/// NFT of the holder that is a verified member of `collection` and a counterfeit NFT that claims the same
+/// collection without verification.
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    bridge: Pubkey,
+    collection: Pubkey,
+    mint: Pubkey,
+    token_account: Pubkey,
+    counterfeit_mint: Pubkey,
+    counterfeit_token_account: Pubkey,
+    holder: Keypair,
+}
+
+fn add_nft(program_test: &mut ProgramTest, mint: Pubkey, token_account: Pubkey, holder: Pubkey, collection: Collection) {
+    program_test.add_account(mint, mint_account(1, 0));
+    program_test.add_account(token_account, common::token_account(mint, holder, 1));
+
+    let metadata = Metadata {
+        key: Key::MetadataV1,
+        update_authority: Pubkey::new_unique(),
+        mint,
+        name: "Bridged".to_string(),
+        symbol: "BRDG".to_string(),
+        uri: "https://example.com/nft.json".to_string(),
+        seller_fee_basis_points: 0,
+        creators: None,
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: None,
+        collection: Some(collection),
+        uses: None,
+        collection_details: None,
+        programmable_config: None,
+    };
+    program_test.add_account(pda::find_metadata_address(&mint).0, account(metadata.try_to_vec().unwrap(), mpl_token_metadata::ID));
+}
+
+async fn setup() -> Env {
+    let program_id = Pubkey::new_unique();
+    let bridge = pda::find_bridge_address(&program_id, &SEED).0;
+    let collection = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    let counterfeit_mint = Pubkey::new_unique();
+    let counterfeit_token_account = Pubkey::new_unique();
+    let holder = Keypair::new();
+
+    let mut program_test = program_test("nft_bridge", program_id);
+    program_test.add_account(holder.pubkey(), wallet_account());
+
+    add_nft(&mut program_test, mint, token_account, holder.pubkey(), Collection { verified: true, key: collection });
+    add_nft(&mut program_test, counterfeit_mint, counterfeit_token_account, holder.pubkey(), Collection { verified: false, key: collection });
+
+    Env {
+        context: program_test.start_with_context().await,
+        program_id,
+        bridge,
+        collection,
+        mint,
+        token_account,
+        counterfeit_mint,
+        counterfeit_token_account,
+        holder,
+    }
+}
+
+impl Env {
+    async fn send(&mut self, instruction: Instruction, signers: &[&Keypair]) -> Result<(), TransactionError> {
+        common::send(&mut self.context, &[instruction], signers).await
+    }
+
+    async fn bridge(&mut self) -> NftBridge {
+        let account = self.context.banks_client.get_account(self.bridge).await.unwrap().expect("bridge is not created");
+        NftBridge::deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn balance(&mut self, address: Pubkey) -> u64 {
+        token_balance(&mut self.context, address).await
+    }
+
+    async fn init_bridge(&mut self, signer: &Signer256k1) {
+        let instruction = instructions::initialize_bridge(self.program_id, self.context.payer.pubkey(), SEED, vec![signer.admin_key()], 1, [0; 32]);
+        self.send(instruction, &[]).await.unwrap();
+    }
+
+    async fn register_collection(&mut self, signer: &Signer256k1, nonce: u64) -> Result<(), TransactionError> {
+        let bridge = self.bridge().await;
+        let hash = hashing::register_collection_hash(&self.program_id, &self.bridge, &bridge, &self.collection, nonce, EXPIRES_AT);
+        let instruction = instructions::register_collection(self.program_id, self.bridge, self.context.payer.pubkey(), self.collection, nonce, EXPIRES_AT, vec![signer.sign(hash.as_ref())]);
+        self.send(instruction, &[]).await
+    }
+
+    async fn deposit(&mut self, mint: Pubkey, source: Pubkey) -> Result<(), TransactionError> {
+        let instruction = instructions::deposit(self.program_id, self.bridge, mint, source, self.holder.pubkey(), Some(self.collection), DESTINATION_CHAIN, vec![0xab; 20]);
+        let holder = Keypair::from_bytes(&self.holder.to_bytes()).unwrap();
+        self.send(instruction, &[&holder]).await
+    }
+
+    async fn release(&mut self, signer: &Signer256k1, nonce: u64) -> Result<(), TransactionError> {
+        let bridge = self.bridge().await;
+        let tx_id = [3; 32];
+        let hash = hashing::release_hash(&self.program_id, &self.bridge, &bridge, &self.mint, &self.token_account, &tx_id, nonce, EXPIRES_AT);
+        let instruction = instructions::release(self.program_id, self.bridge, self.mint, self.token_account, tx_id, nonce, EXPIRES_AT, vec![signer.sign(hash.as_ref())]);
+        self.send(instruction, &[]).await
+    }
+}
+
+#[tokio::test]
+async fn register_deposit_and_release() {
+    let mut env = setup().await;
+    let oracle = Signer256k1::new(1);
+    let custody = pda::find_custody_address(&env.program_id, &env.bridge, &env.mint).0;
+
+    env.init_bridge(&oracle).await;
+    env.register_collection(&oracle, 0).await.unwrap();
+    assert_eq!(env.bridge().await.nonce, 1);
+
+    env.deposit(env.mint, env.token_account).await.unwrap();
+    assert_eq!(env.balance(env.token_account).await, 0);
+    assert_eq!(env.balance(custody).await, 1);
+    assert_eq!(env.bridge().await.deposit_nonce, 1);
+
+    env.release(&oracle, 1).await.unwrap();
+    assert_eq!(env.balance(env.token_account).await, 1);
+    assert_eq!(env.balance(custody).await, 0);
+    assert_eq!(env.bridge().await.nonce, 2);
+}
+
+#[tokio::test]
+async fn deposit_rejects_unregistered_and_unverified_collection() {
+    let mut env = setup().await;
+    let oracle = Signer256k1::new(1);
+    let stranger = Signer256k1::new(2);
+
+    env.init_bridge(&oracle).await;
+    assert!(env.deposit(env.mint, env.token_account).await.is_err());
+
+    assert!(env.register_collection(&stranger, 0).await.is_err());
+    env.register_collection(&oracle, 0).await.unwrap();
+
+    assert!(env.deposit(env.counterfeit_mint, env.counterfeit_token_account).await.is_err());
+    assert_eq!(env.balance(env.counterfeit_token_account).await, 1);
+
+    env.deposit(env.mint, env.token_account).await.unwrap();
+    assert!(env.release(&stranger, 1).await.is_err());
+    assert_eq!(env.balance(env.token_account).await, 0);
+}
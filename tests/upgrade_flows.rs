@@ -0,0 +1,450 @@
+//! End-to-end flows of the upgrade program on solana-program-test: admin initialization, signer rotation, upgrade
+//! of a deployed contract from a real buffer through CPI to BPFLoaderUpgradeable and rollback to a registered
+//! snapshot, with the guards of direct upgrades, rollbacks and staged buffer writes.
+//!
+//! The program is loaded from `upgrade.so` in `SBF_OUT_DIR`, the same ELF serves as the code of the upgraded contract.
+//! Build it first:
+//!
+//! ```shell
+//! cargo build-sbf --manifest-path programs/upgrade-program/Cargo.toml
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test --manifest-path tests/Cargo.toml --features test-sbf
+//! ```
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use common::{program_test, Signer256k1, EXPIRES_AT};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::account::Account;
+use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use upgrade::error::UpgradeError;
+use upgrade::hashing::{self, HashAlgorithm, HashingMode, NonceWidth};
+use upgrade::instructions;
+use upgrade::pda;
+use upgrade::state::{Curve, UpgradeAdmin, VersionedUpgradeAdmin};
+
+/// Deployed contract with UpgradeAdmin PDA as upgrade authority, a buffer with its next version and a buffer
+/// with the same code for a rollback snapshot.
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    contract: Pubkey,
+    program_data: Pubkey,
+    buffer: Pubkey,
+    snapshot: Pubkey,
+    code_hash: [u8; 32],
+}
+
+fn program_elf() -> Vec<u8> {
+    let out_dir = std::env::var("SBF_OUT_DIR").unwrap_or_else(|_| "../target/deploy".to_string());
+    std::fs::read(format!("{}/upgrade.so", out_dir)).expect("upgrade.so is not built, run cargo build-sbf")
+}
+
+fn loader_account(data: Vec<u8>, executable: bool) -> Account {
+    Account {
+        lamports: 10_000_000_000,
+        data,
+        owner: bpf_loader_upgradeable::id(),
+        executable,
+        rent_epoch: 0,
+    }
+}
+
+async fn setup() -> Env {
+    let program_id = Pubkey::new_unique();
+    let contract = Pubkey::new_unique();
+    let buffer = Pubkey::new_unique();
+    let snapshot = Pubkey::new_unique();
+    let (program_data, _) = pda::find_program_data_address(&contract);
+    let admin = pda::find_admin_address(&program_id, &contract).0;
+    let elf = program_elf();
+
+    let mut program_test = program_test("upgrade", program_id);
+
+    program_test.add_account(contract, loader_account(
+        bincode::serialize(&UpgradeableLoaderState::Program { programdata_address: program_data }).unwrap(),
+        true,
+    ));
+
+    let mut data = bincode::serialize(&UpgradeableLoaderState::ProgramData { slot: 0, upgrade_authority_address: Some(admin) }).unwrap();
+    data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+    data.extend_from_slice(&elf);
+    program_test.add_account(program_data, loader_account(data, false));
+
+    let mut data = bincode::serialize(&UpgradeableLoaderState::Buffer { authority_address: Some(admin) }).unwrap();
+    data.resize(UpgradeableLoaderState::size_of_buffer_metadata(), 0);
+    data.extend_from_slice(&elf);
+    program_test.add_account(buffer, loader_account(data.clone(), false));
+    program_test.add_account(snapshot, loader_account(data, false));
+
+    Env {
+        context: program_test.start_with_context().await,
+        program_id,
+        contract,
+        program_data,
+        buffer,
+        snapshot,
+        code_hash: hash(&elf).to_bytes(),
+    }
+}
+
+impl Env {
+    async fn send(&mut self, instruction: Instruction) -> Result<(), TransactionError> {
+        self.send_signed(vec![instruction], &[]).await
+    }
+
+    /// Sends `instructions` after the compute budget one, signed by the payer and `signers`.
+    async fn send_signed(&mut self, instructions: Vec<Instruction>, signers: &[&Keypair]) -> Result<(), TransactionError> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let mut all_instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+        all_instructions.extend(instructions);
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            &all_instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+
+        self.context.banks_client.process_transaction(transaction).await.map_err(|e| e.unwrap())
+    }
+
+    async fn admin(&mut self) -> UpgradeAdmin {
+        let address = pda::find_admin_address(&self.program_id, &self.contract).0;
+        let account = self.context.banks_client.get_account(address).await.unwrap().expect("admin is not created");
+        match VersionedUpgradeAdmin::unpack(&account.data).unwrap() {
+            VersionedUpgradeAdmin::V2(upgrade_admin) => upgrade_admin,
+            VersionedUpgradeAdmin::V1(_) => panic!("admin has V1 layout"),
+        }
+    }
+
+    async fn init_admin(&mut self, signer: &Signer256k1) {
+        self.init_admin_with_nonce_width(signer, NonceWidth::U64).await;
+    }
+
+    async fn init_admin_with_nonce_width(&mut self, signer: &Signer256k1, nonce_width: NonceWidth) {
+        let instruction = instructions::initialize_admin(
+            self.program_id,
+            self.contract,
+            self.context.payer.pubkey(),
+            vec![signer.admin_key()],
+            vec![],
+            1,
+            HashingMode::Legacy,
+            [0; 32],
+            None,
+            None,
+            0,
+            Curve::Secp256k1,
+            HashAlgorithm::Keccak,
+            None,
+            0,
+            vec![],
+            None,
+            vec![],
+            0,
+            0,
+            0,
+            nonce_width,
+        );
+        self.send(instruction).await.unwrap();
+
+        let instruction = instructions::init_upgrade_history(self.program_id, self.contract, self.contract, self.context.payer.pubkey());
+        self.send(instruction).await.unwrap();
+    }
+
+    async fn rotate(&mut self, old: &Signer256k1, new: &Signer256k1) {
+        let upgrade_admin = self.admin().await;
+        let hash = hashing::change_public_key_hash(&self.program_id, &upgrade_admin, &[new.admin_key()], &[], 1, None, &None, EXPIRES_AT);
+        let instruction = instructions::change_public_key(
+            self.program_id,
+            self.contract,
+            vec![new.admin_key()],
+            vec![],
+            1,
+            None,
+            None,
+            upgrade_admin.key_nonce,
+            vec![old.sign(hash.as_ref())],
+            vec![],
+            EXPIRES_AT,
+        );
+        self.send(instruction).await.unwrap();
+        self.send(instructions::activate_key(self.program_id, self.contract)).await.unwrap();
+    }
+
+    async fn set_upgrade_delay(&mut self, signer: &Signer256k1, min_delay_slots: u64) {
+        let upgrade_admin = self.admin().await;
+        let hash = hashing::set_upgrade_delay_hash(&self.program_id, &upgrade_admin, min_delay_slots, EXPIRES_AT);
+        let instruction = instructions::set_upgrade_delay(
+            self.program_id,
+            self.contract,
+            min_delay_slots,
+            vec![signer.sign(hash.as_ref())],
+            vec![],
+            EXPIRES_AT,
+        );
+        self.send(instruction).await.unwrap();
+    }
+
+    async fn set_upgrade_interval(&mut self, signer: &Signer256k1, min_upgrade_interval_slots: u64) {
+        let upgrade_admin = self.admin().await;
+        let hash = hashing::set_upgrade_interval_hash(&self.program_id, &upgrade_admin, min_upgrade_interval_slots, EXPIRES_AT);
+        let instruction = instructions::set_upgrade_interval(
+            self.program_id,
+            self.contract,
+            min_upgrade_interval_slots,
+            vec![signer.sign(hash.as_ref())],
+            vec![],
+            EXPIRES_AT,
+        );
+        self.send(instruction).await.unwrap();
+    }
+
+    /// Registers the snapshot buffer as the rollback target of the contract.
+    async fn register_snapshot(&mut self, signer: &Signer256k1) {
+        let upgrade_admin = self.admin().await;
+        let hash = hashing::register_snapshot_hash(&self.program_id, &upgrade_admin, &self.contract, &self.snapshot, &self.code_hash, EXPIRES_AT);
+        let instruction = instructions::register_snapshot(
+            self.program_id,
+            self.contract,
+            self.contract,
+            self.snapshot,
+            self.context.payer.pubkey(),
+            self.code_hash,
+            vec![signer.sign(hash.as_ref())],
+            vec![],
+            EXPIRES_AT,
+        );
+        self.send(instruction).await.unwrap();
+    }
+
+    async fn rollback(&mut self, signer: &Signer256k1) -> Result<(), TransactionError> {
+        let spill = self.context.payer.pubkey();
+        let upgrade_admin = self.admin().await;
+        let hash = hashing::rollback_hash(&self.program_id, &upgrade_admin, &self.contract, &self.snapshot, EXPIRES_AT);
+        let instruction = instructions::rollback(
+            self.program_id,
+            self.contract,
+            self.contract,
+            self.snapshot,
+            spill,
+            upgrade_admin.upgrade_nonce,
+            vec![signer.sign(hash.as_ref())],
+            EXPIRES_AT,
+        );
+        self.send(instruction).await
+    }
+
+    /// Creates the `buffer` account of `data_len` bytes staged for chunks of `writer`.
+    async fn create_buffer(&mut self, signer: &Signer256k1, buffer: &Keypair, writer: Pubkey, data_len: u64) {
+        let upgrade_admin = self.admin().await;
+        let code_hash = [7; 32];
+        let hash = hashing::create_buffer_hash(&self.program_id, &upgrade_admin, &self.contract, &buffer.pubkey(), &writer, data_len, &code_hash, EXPIRES_AT);
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let instructions = instructions::create_buffer(
+            self.program_id,
+            self.contract,
+            self.contract,
+            buffer.pubkey(),
+            writer,
+            self.context.payer.pubkey(),
+            rent.minimum_balance(UpgradeableLoaderState::size_of_buffer(data_len as usize)),
+            data_len,
+            code_hash,
+            upgrade_admin.upgrade_nonce,
+            vec![signer.sign(hash.as_ref())],
+            vec![],
+            EXPIRES_AT,
+        );
+        self.send_signed(instructions, &[buffer]).await.unwrap();
+    }
+
+    async fn upgrade(&mut self, signer: &Signer256k1, nonce: u128) -> Result<(), TransactionError> {
+        let nonce_width = self.admin().await.nonce_width;
+        self.upgrade_with_nonce_width(signer, nonce, nonce_width).await
+    }
+
+    /// Upgrade signed as if the admin had `nonce_width` nonces
+    async fn upgrade_with_nonce_width(&mut self, signer: &Signer256k1, nonce: u128, nonce_width: NonceWidth) -> Result<(), TransactionError> {
+        let spill = self.context.payer.pubkey();
+        let mut upgrade_admin = self.admin().await;
+        upgrade_admin.upgrade_nonce = nonce;
+        upgrade_admin.nonce_width = nonce_width;
+        let hash = hashing::upgrade_hash(&self.program_id, &upgrade_admin, &self.contract, &self.buffer, &self.code_hash, &spill, EXPIRES_AT);
+        let instruction = instructions::upgrade(
+            self.program_id,
+            self.contract,
+            self.buffer,
+            spill,
+            nonce,
+            self.code_hash,
+            vec![signer.sign(hash.as_ref())],
+            vec![],
+            EXPIRES_AT,
+        );
+        self.send(instruction).await
+    }
+}
+
+#[tokio::test]
+async fn init_rotate_and_upgrade() {
+    let mut env = setup().await;
+    let old_signer = Signer256k1::new(1);
+    let new_signer = Signer256k1::new(2);
+
+    env.init_admin(&old_signer).await;
+    let upgrade_admin = env.admin().await;
+    assert!(upgrade_admin.is_initialized);
+    assert_eq!(upgrade_admin.public_keys, vec![old_signer.admin_key()]);
+    assert_eq!((upgrade_admin.upgrade_nonce, upgrade_admin.key_nonce, upgrade_admin.authority_nonce), (0, 0, 0));
+
+    env.rotate(&old_signer, &new_signer).await;
+    let upgrade_admin = env.admin().await;
+    assert_eq!(upgrade_admin.public_keys, vec![new_signer.admin_key()]);
+    assert_eq!(upgrade_admin.pending_key_change, None);
+    assert_eq!((upgrade_admin.upgrade_nonce, upgrade_admin.key_nonce), (0, 1));
+
+    // The loader rejects upgrades in the slot the program was deployed in
+    env.context.warp_to_slot(2).unwrap();
+    env.upgrade(&new_signer, 0).await.unwrap();
+
+    let upgrade_admin = env.admin().await;
+    assert_eq!(upgrade_admin.upgrade_nonce, 1);
+    assert_eq!(upgrade_admin.key_nonce, 1);
+    assert!(upgrade_admin.last_upgrade_slot.is_some());
+
+    // The loader closes the buffer and records the deployment slot in ProgramData
+    assert!(env.context.banks_client.get_account(env.buffer).await.unwrap().is_none());
+    let program_data = env.context.banks_client.get_account(env.program_data).await.unwrap().unwrap();
+    match bincode::deserialize(&program_data.data).unwrap() {
+        UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address } => {
+            assert_eq!(Some(slot), upgrade_admin.last_upgrade_slot);
+            assert_eq!(upgrade_authority_address, Some(pda::find_admin_address(&env.program_id, &env.contract).0));
+        }
+        state => panic!("unexpected ProgramData state {:?}", state),
+    }
+}
+
+#[tokio::test]
+async fn upgrade_rejects_rotated_key_and_stale_nonce() {
+    let mut env = setup().await;
+    let old_signer = Signer256k1::new(1);
+    let new_signer = Signer256k1::new(2);
+
+    env.init_admin(&old_signer).await;
+    env.rotate(&old_signer, &new_signer).await;
+    env.context.warp_to_slot(2).unwrap();
+
+    assert!(env.upgrade(&old_signer, 0).await.is_err());
+    assert!(env.upgrade(&new_signer, 1).await.is_err());
+    assert_eq!(env.admin().await.upgrade_nonce, 0);
+
+    env.upgrade(&new_signer, 0).await.unwrap();
+    assert_eq!(env.admin().await.upgrade_nonce, 1);
+}
+
+#[tokio::test]
+async fn upgrade_with_wide_nonces() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+
+    env.init_admin_with_nonce_width(&signer, NonceWidth::U128).await;
+    env.context.warp_to_slot(2).unwrap();
+
+    // Signature of the 8 byte nonce message of U64 admins does not authorize the upgrade
+    assert!(env.upgrade_with_nonce_width(&signer, 0, NonceWidth::U64).await.is_err());
+    assert_eq!(env.admin().await.upgrade_nonce, 0);
+
+    env.upgrade(&signer, 0).await.unwrap();
+    assert_eq!(env.admin().await.upgrade_nonce, 1);
+}
+
+fn upgrade_error(error: UpgradeError) -> TransactionError {
+    // Index 1 is the instruction of the upgrade program after the compute budget one
+    TransactionError::InstructionError(1, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn upgrade_rejected_under_timelock() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+
+    env.init_admin(&signer).await;
+    env.set_upgrade_delay(&signer, 10).await;
+    env.context.warp_to_slot(2).unwrap();
+
+    assert_eq!(env.upgrade(&signer, 0).await, Err(upgrade_error(UpgradeError::TimelockRequired)));
+    assert_eq!(env.admin().await.upgrade_nonce, 0);
+    assert!(env.context.banks_client.get_account(env.buffer).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn rollback_rejected_under_timelock() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+
+    env.init_admin(&signer).await;
+    env.register_snapshot(&signer).await;
+    env.set_upgrade_delay(&signer, 10).await;
+    env.context.warp_to_slot(2).unwrap();
+
+    assert_eq!(env.rollback(&signer).await, Err(upgrade_error(UpgradeError::TimelockRequired)));
+    assert_eq!(env.admin().await.upgrade_nonce, 0);
+}
+
+#[tokio::test]
+async fn rollback_waits_for_upgrade_interval() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+    let stranger = Signer256k1::new(2);
+
+    env.init_admin(&signer).await;
+    env.register_snapshot(&signer).await;
+    env.set_upgrade_interval(&signer, 100).await;
+    env.context.warp_to_slot(2).unwrap();
+    env.upgrade(&signer, 0).await.unwrap();
+
+    assert_eq!(env.rollback(&signer).await, Err(upgrade_error(UpgradeError::UpgradeTooSoon)));
+
+    // Snapshot is approved in advance, but the rollback is still signed by one of the signers
+    env.context.warp_to_slot(200).unwrap();
+    assert!(env.rollback(&stranger).await.is_err());
+    assert_eq!(env.admin().await.upgrade_nonce, 1);
+
+    env.rollback(&signer).await.unwrap();
+    assert_eq!(env.admin().await.upgrade_nonce, 2);
+    assert!(env.context.banks_client.get_account(env.snapshot).await.unwrap().is_none());
+    let (rollback_snapshot, _) = pda::find_rollback_snapshot_address(&env.program_id, &pda::find_admin_address(&env.program_id, &env.contract).0, &env.contract);
+    assert!(env.context.banks_client.get_account(rollback_snapshot).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn write_buffer_chunk_requires_committed_writer() {
+    let mut env = setup().await;
+    let signer = Signer256k1::new(1);
+    let buffer = Keypair::new();
+    let writer = Keypair::new();
+    let stranger = Keypair::new();
+    let chunk = vec![1; 64];
+
+    env.init_admin(&signer).await;
+    env.create_buffer(&signer, &buffer, writer.pubkey(), chunk.len() as u64).await;
+
+    let instruction = instructions::write_buffer_chunk(env.program_id, env.contract, buffer.pubkey(), stranger.pubkey(), 0, chunk.clone());
+    assert_eq!(env.send_signed(vec![instruction], &[&stranger]).await, Err(upgrade_error(UpgradeError::BufferWriterMismatch)));
+
+    let instruction = instructions::write_buffer_chunk(env.program_id, env.contract, buffer.pubkey(), writer.pubkey(), 0, chunk.clone());
+    env.send_signed(vec![instruction], &[&writer]).await.unwrap();
+
+    let account = env.context.banks_client.get_account(buffer.pubkey()).await.unwrap().unwrap();
+    assert_eq!(account.data[UpgradeableLoaderState::size_of_buffer_metadata()..], chunk[..]);
+}
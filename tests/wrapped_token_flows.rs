@@ -0,0 +1,146 @@
+//! End-to-end flows of the wrapped token program on solana-program-test: minting of wrapped tokens authorized by
+//! the oracle signature and burning them back to the origin chain.
+//!
+//! The wrapped mint and its WrappedAsset are preloaded as CreateWrappedMint would leave them, Metaplex Token Metadata
+//! is not available in solana-program-test. The program is loaded from `wrapped_token.so` in `SBF_OUT_DIR`:
+//!
+//! ```shell
+//! cargo build-sbf --manifest-path programs/wrapped-token/Cargo.toml
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test --manifest-path tests/Cargo.toml --features test-sbf
+//! ```
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use common::{account, packed, program_test, token_balance, Signer256k1, EXPIRES_AT};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_option::COption;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+use wrapped_token::hashing;
+use wrapped_token::instructions;
+use wrapped_token::pda;
+use wrapped_token::state::{Factory, WrappedAsset};
+
+const SEED: [u8; 32] = [1; 32];
+
+const ORIGIN_CHAIN: u64 = 1;
+
+const ORIGIN_TOKEN: [u8; 20] = [0xee; 20];
+
+/// Wrapped mint of the factory with an empty token account of the holder.
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    factory: Pubkey,
+    mint: Pubkey,
+    holder: Keypair,
+    token_account: Pubkey,
+}
+
+async fn setup() -> Env {
+    let program_id = Pubkey::new_unique();
+    let factory = pda::find_factory_address(&program_id, &SEED).0;
+    let mint = pda::find_mint_address(&program_id, &factory, ORIGIN_CHAIN, &ORIGIN_TOKEN).0;
+    let holder = Keypair::new();
+    let token_account = Pubkey::new_unique();
+
+    let mut program_test = program_test("wrapped_token", program_id);
+
+    program_test.add_account(mint, account(packed(spl_token::state::Mint {
+        mint_authority: COption::Some(factory),
+        supply: 0,
+        decimals: 8,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }), spl_token::id()));
+    program_test.add_account(pda::find_wrapped_asset_address(&program_id, &mint).0, account(WrappedAsset {
+        factory,
+        mint,
+        origin_chain: ORIGIN_CHAIN,
+        origin_token: ORIGIN_TOKEN.to_vec(),
+        decimals: 8,
+        is_initialized: true,
+    }.try_to_vec().unwrap(), program_id));
+    program_test.add_account(token_account, account(packed(spl_token::state::Account {
+        mint,
+        owner: holder.pubkey(),
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }), spl_token::id()));
+
+    Env {
+        context: program_test.start_with_context().await,
+        program_id,
+        factory,
+        mint,
+        holder,
+        token_account,
+    }
+}
+
+impl Env {
+    async fn send(&mut self, instruction: Instruction, signers: &[&Keypair]) -> Result<(), TransactionError> {
+        common::send(&mut self.context, &[instruction], signers).await
+    }
+
+    async fn factory(&mut self) -> Factory {
+        let account = self.context.banks_client.get_account(self.factory).await.unwrap().expect("factory is not created");
+        Factory::deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn balance(&mut self) -> u64 {
+        token_balance(&mut self.context, self.token_account).await
+    }
+
+    async fn init_factory(&mut self, signer: &Signer256k1) {
+        let instruction = instructions::initialize_factory(self.program_id, self.context.payer.pubkey(), SEED, vec![signer.admin_key()], 1, [0; 32]);
+        self.send(instruction, &[]).await.unwrap();
+    }
+
+    async fn mint(&mut self, signer: &Signer256k1, amount: u64, nonce: u64) -> Result<(), TransactionError> {
+        let factory = self.factory().await;
+        let tx_id = [3; 32];
+        let hash = hashing::mint_hash(&self.program_id, &self.factory, &factory, &self.mint, &self.token_account, amount, &tx_id, nonce, EXPIRES_AT);
+        let instruction = instructions::mint(self.program_id, self.factory, self.mint, self.token_account, amount, tx_id, nonce, EXPIRES_AT, vec![signer.sign(hash.as_ref())]);
+        self.send(instruction, &[]).await
+    }
+
+    async fn burn(&mut self, amount: u64) -> Result<(), TransactionError> {
+        let instruction = instructions::burn(self.program_id, self.factory, self.mint, self.token_account, self.holder.pubkey(), amount, vec![0xab; 20]);
+        let holder = Keypair::from_bytes(&self.holder.to_bytes()).unwrap();
+        self.send(instruction, &[&holder]).await
+    }
+}
+
+#[tokio::test]
+async fn mint_and_burn() {
+    let mut env = setup().await;
+    let oracle = Signer256k1::new(1);
+
+    env.init_factory(&oracle).await;
+    env.mint(&oracle, 1_000, 0).await.unwrap();
+    assert_eq!(env.balance().await, 1_000);
+    assert_eq!(env.factory().await.nonce, 1);
+
+    env.burn(400).await.unwrap();
+    assert_eq!(env.balance().await, 600);
+    assert_eq!(env.factory().await.burn_nonce, 1);
+}
+
+#[tokio::test]
+async fn mint_rejects_foreign_signer_and_replay() {
+    let mut env = setup().await;
+    let oracle = Signer256k1::new(1);
+    let stranger = Signer256k1::new(2);
+
+    env.init_factory(&oracle).await;
+    assert!(env.mint(&stranger, 1_000, 0).await.is_err());
+    env.mint(&oracle, 1_000, 0).await.unwrap();
+    assert!(env.mint(&oracle, 1_000, 0).await.is_err());
+    assert_eq!(env.balance().await, 1_000);
+}